@@ -0,0 +1,65 @@
+//! Benchmarks of the work done for every page and every action: mouse path
+//! generation, bootstrap script rendering and isolated-world evaluations.
+//!
+//! Evaluations run against a [`MockTransport`], so they measure the
+//! crate's own overhead per round-trip, not Chrome's.
+//!
+//! Run with `cargo bench --bench hot_paths`.
+
+use chaser_oxide::patches::Patch;
+use chaser_oxide::policy::StealthPolicy;
+use chaser_oxide::transport::MockTransport;
+use chaser_oxide::{BezierPath, ChaserPage, ChaserProfile, Page, Point};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use serde_json::json;
+
+fn bezier(c: &mut Criterion) {
+    let start = Point { x: 12.0, y: 640.0 };
+    let end = Point { x: 1180.0, y: 96.0 };
+    c.bench_function("bezier_path_25_steps", |b| {
+        b.iter(|| BezierPath::generate(black_box(start), black_box(end), 25))
+    });
+}
+
+fn bootstrap(c: &mut Criterion) {
+    c.bench_function("bootstrap_script_first", |b| {
+        b.iter_batched(
+            || ChaserProfile::windows().build(),
+            |profile| profile.bootstrap_script(),
+            BatchSize::SmallInput,
+        )
+    });
+
+    let profile = ChaserProfile::windows().build();
+    c.bench_function("bootstrap_script_cached", |b| {
+        b.iter(|| black_box(&profile).bootstrap_script())
+    });
+
+    let policy = StealthPolicy::new().disable("checkout.example", Patch::WebGl);
+    c.bench_function("bootstrap_script_with_policy", |b| {
+        b.iter(|| profile.bootstrap_script_with_policy(black_box(&policy)))
+    });
+}
+
+fn evaluate(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let mock = MockTransport::new();
+    mock.respond(
+        "Runtime.evaluate",
+        json!({ "result": { "type": "number", "value": 2 } }),
+    );
+    let chaser = runtime.block_on(async { ChaserPage::new(Page::with_transport(mock.clone())) });
+
+    c.bench_function("evaluate_stealth_round_trip", |b| {
+        b.iter(|| {
+            mock.clear();
+            runtime.block_on(chaser.evaluate_stealth(black_box("1 + 1")))
+        })
+    });
+}
+
+criterion_group!(benches, bezier, bootstrap, evaluate);
+criterion_main!(benches);
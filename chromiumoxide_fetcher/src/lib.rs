@@ -25,7 +25,7 @@ pub use self::host::BrowserHost;
 pub use self::kind::BrowserKind;
 pub use self::platform::Platform;
 use self::runtime::Runtime;
-pub use self::version::{BrowserVersion, Channel, Revision, Version, VersionError};
+pub use self::version::{BrowserVersion, Channel, Milestone, Revision, Version, VersionError};
 
 mod build_info;
 mod error;
@@ -4,14 +4,14 @@ use std::time::Duration;
 
 use base64::prelude::BASE64_STANDARD;
 use base64::Engine;
-use chromiumoxide::browser::{Browser, BrowserConfig};
-use chromiumoxide::cdp::browser_protocol::fetch::{
+use chaser_oxide::browser::{Browser, BrowserConfig};
+use chaser_oxide::cdp::browser_protocol::fetch::{
     self, ContinueRequestParams, EventRequestPaused, FailRequestParams, FulfillRequestParams,
 };
-use chromiumoxide::cdp::browser_protocol::network::{
+use chaser_oxide::cdp::browser_protocol::network::{
     self, ErrorReason, EventRequestWillBeSent, ResourceType,
 };
-use chromiumoxide::Page;
+use chaser_oxide::Page;
 use futures::{select, StreamExt};
 use tokio::time::sleep;
 
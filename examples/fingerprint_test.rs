@@ -1,40 +1,19 @@
 use anyhow::Result;
-use chaser_oxide::{Browser, BrowserConfig, ChaserPage, ChaserProfile};
-use futures::StreamExt;
+use chaser_oxide::{ChaserBrowser, ChaserProfile};
 use std::time::Duration;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     println!("Launching chaser-oxide Stealth Browser...");
-    let (browser, mut handler) = Browser::launch(
-        BrowserConfig::builder()
-            .with_head() // Show browser for testing
-            .build()
-            .map_err(|e| anyhow::anyhow!(e))?,
-    )
-    .await?;
-
-    tokio::spawn(async move { while let Some(_) = handler.next().await {} });
-
-    // Create profile FIRST
-    let profile = ChaserProfile::windows().build();
-
-    // Create page with about:blank
-    println!("Creating page...");
-    let page = browser.new_page("about:blank").await?;
-
-    // Wrap in ChaserPage and apply full stealth profile
-    // This sets viewport, DPR, UA, and injects all Chrome mocks
-    println!("Applying stealth profile...");
-    let chaser = ChaserPage::new(page);
-    chaser.apply_profile(&profile).await?;
-
-    // Small delay to ensure scripts are registered
-    tokio::time::sleep(Duration::from_millis(100)).await;
+    // Launches the browser, spawns its handler and applies the profile to
+    // every page before it navigates
+    let browser = ChaserBrowser::builder(ChaserProfile::windows().build())
+        .headed() // Show browser for testing
+        .launch()
+        .await?;
 
-    // NOW navigate to the detection test
     println!("Navigating to detection test...");
-    chaser.goto("https://iphey.com/").await?;
+    let chaser = browser.new_page("https://iphey.com/").await?;
 
     // Wait for page to fully load
     tokio::time::sleep(Duration::from_secs(3)).await;
@@ -65,6 +44,7 @@ async fn main() -> Result<()> {
 
     println!("\nBrowser will close in 5 seconds...");
     tokio::time::sleep(Duration::from_secs(5)).await;
+    browser.close().await?;
 
     Ok(())
 }
@@ -4,8 +4,7 @@
 //! with the ergonomic builder pattern.
 
 use anyhow::Result;
-use chaser_oxide::{Browser, BrowserConfig, ChaserPage, ChaserProfile, Gpu};
-use futures::StreamExt;
+use chaser_oxide::{Browser, BrowserConfig, ChaserPage, ChaserProfile, Gpu, WaitUntil};
 use std::time::Duration;
 
 #[tokio::main]
@@ -48,7 +47,7 @@ async fn main() -> Result<()> {
     // === Live Test with Profile ===
     println!("\n=== Starting Browser Test ===");
 
-    let (browser, mut handler) = Browser::launch(
+    let (browser, handler) = Browser::launch(
         BrowserConfig::builder()
             .viewport(None)
             .build()
@@ -56,18 +55,17 @@ async fn main() -> Result<()> {
     )
     .await?;
 
-    tokio::spawn(async move { while let Some(_) = handler.next().await {} });
+    handler.spawn();
 
     // Create page with stealth
     let page = browser.new_page("about:blank").await?;
-    page.enable_stealth_mode().await?;
+    let chaser = ChaserPage::new(page);
+    chaser.apply_profile(&windows_profile).await?;
 
     tokio::time::sleep(Duration::from_millis(100)).await;
-    page.goto("https://bot.sannysoft.com").await?;
-
-    tokio::time::sleep(Duration::from_secs(3)).await;
-
-    let chaser = ChaserPage::new(page);
+    chaser
+        .goto_stealth("https://bot.sannysoft.com", WaitUntil::NetworkIdle)
+        .await?;
 
     // Demonstrate click_human (combines bezier + click)
     println!("\nTesting click_human()...");
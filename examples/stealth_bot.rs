@@ -1,11 +1,11 @@
 use anyhow::Result;
-use chaser_oxide::{ChaserPage, Os};
+use chaser_oxide::{ChaserPage, Os, WaitUntil};
 use std::time::Duration;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     println!("Launching chaser-oxide Stealth Browser...");
-    
+
     // ONE LINE. That's it. Browser launched, profile applied, ready to go.
     let (_browser, chaser) = ChaserPage::launch_headed(Os::Windows).await?;
 
@@ -14,10 +14,10 @@ async fn main() -> Result<()> {
 
     // NOW navigate to the detection test
     println!("Navigating to detection test...");
-    chaser.goto("https://bot.sannysoft.com").await?;
-
-    // Wait for page to fully load
-    tokio::time::sleep(Duration::from_secs(3)).await;
+    // and wait until its scripts are done talking to the network
+    chaser
+        .goto_stealth("https://bot.sannysoft.com", WaitUntil::NetworkIdle)
+        .await?;
 
     // Human-like mouse movement
     println!("Simulating human mouse movement...");
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use chaser_oxide::{ChaserPage, Os};
+use chaser_oxide::{ChaserPage, Os, WaitUntil};
 use std::time::Duration;
 
 #[tokio::main]
@@ -14,10 +14,13 @@ async fn main() -> Result<()> {
 
     // NOW navigate to the detection test
     println!("Navigating to rebrowser bot detector...");
-    chaser.goto("https://bot-detector.rebrowser.net/").await?;
-
-    // Wait for page to fully load
-    tokio::time::sleep(Duration::from_secs(3)).await;
+    // and wait until its scripts are done talking to the network
+    chaser
+        .goto_stealth(
+            "https://bot-detector.rebrowser.net/",
+            WaitUntil::NetworkIdle,
+        )
+        .await?;
 
     // Human-like mouse movement
     println!("Simulating human mouse movement...");
@@ -25,30 +28,40 @@ async fn main() -> Result<()> {
 
     // ========== TRIGGER REBROWSER TESTS ==========
     println!("\nTriggering rebrowser detection tests...");
-    
+
     // Test 1: dummyFn - tests main world access
     println!("  Testing dummyFn (main world access)...");
-    let dummy_result = chaser.evaluate("typeof window.dummyFn === 'function' ? window.dummyFn() : 'no dummyFn'").await?;
+    let dummy_result = chaser
+        .evaluate("typeof window.dummyFn === 'function' ? window.dummyFn() : 'no dummyFn'")
+        .await?;
     println!("    Result: {:?}", dummy_result);
-    
+
     // Test 2: sourceUrlLeak - tests for pptr: or playwright: sourceURL
     println!("  Testing sourceUrlLeak...");
-    let _ = chaser.evaluate("document.getElementById('detections-json')?.textContent || 'no element'").await?;
-    
+    let _ = chaser
+        .evaluate("document.getElementById('detections-json')?.textContent || 'no element'")
+        .await?;
+
     // Test 3: mainWorldExecution - triggers if our code runs in main world
     println!("  Testing mainWorldExecution...");
-    let _ = chaser.evaluate("document.getElementsByClassName('div').length").await?;
+    let _ = chaser
+        .evaluate("document.getElementsByClassName('div').length")
+        .await?;
 
     // Wait for tests to complete
     tokio::time::sleep(Duration::from_secs(2)).await;
 
     // Read the JSON results
     println!("\n========== REBROWSER BOT DETECTOR RESULTS ==========");
-    let results = chaser.evaluate(r#"
+    let results = chaser
+        .evaluate(
+            r#"
         const json = document.getElementById('detections-json');
         json ? json.textContent : 'Results not found'
-    "#).await?;
-    
+    "#,
+        )
+        .await?;
+
     // results is Option<Value>
     if let Some(val) = results {
         if let Some(json_str) = val.as_str() {
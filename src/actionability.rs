@@ -0,0 +1,280 @@
+//! Checking that an element can really be clicked before clicking it.
+//!
+//! A humanized click lands on whatever is at its coordinates: the cookie
+//! banner over the button, the spinner fading out, the button that has not
+//! finished sliding in. Such clicks "succeed" and the flow fails somewhere
+//! later. [`ChaserPage::click_element_human`] waits until the element is
+//! attached, visible, enabled, no longer moving and the topmost element at
+//! its centre (hit-tested with `DOM.getNodeForLocation`), scrolling it into
+//! view if needed, and fails with [`ChaserError::NotActionable`] if that does
+//! not happen in time.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let button = chaser.raw_page().find_element("#checkout").await?;
+//! match chaser.click_element_human(&button).await {
+//!     Err(ChaserError::NotActionable(Unactionable::Covered { by })) => {
+//!         tracing::warn!("checkout button is covered by {by}");
+//!     }
+//!     other => other?,
+//! }
+//! ```
+
+use crate::chaser::{ChaserPage, Point};
+use crate::element::Element;
+use crate::error::{ChaserError, ChaserResult as Result};
+use chromiumoxide_cdp::cdp::browser_protocol::dom::{
+    DescribeNodeParams, GetNodeForLocationParams, ResolveNodeParams,
+};
+use chromiumoxide_cdp::cdp::js_protocol::runtime::{CallArgument, CallFunctionOnParams};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// `null` when attached, rendered and enabled, otherwise why not.
+const STATE_FN: &str = r#"function() {
+    if (!this.isConnected) return 'detached';
+    const style = getComputedStyle(this);
+    const rect = this.getBoundingClientRect();
+    if (style.visibility !== 'visible' || rect.width * rect.height < 1) return 'hidden';
+    if (this.matches(':disabled') || this.closest('[aria-disabled="true"]')) return 'disabled';
+    return null;
+}"#;
+
+/// Whether `hit` is this element or inside it, across shadow roots.
+const CONTAINS_FN: &str = r#"function(hit) {
+    for (let node = hit; node; node = node.parentNode || node.host) {
+        if (node === this) return true;
+    }
+    return false;
+}"#;
+
+/// Why an element cannot be clicked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Unactionable {
+    /// Removed from the document.
+    Detached,
+    /// Not rendered, `visibility: hidden` or without a box.
+    Hidden,
+    /// A disabled form control or inside `aria-disabled="true"`.
+    Disabled,
+    /// Still moving or resizing, e.g. animating in.
+    Moving,
+    /// Another element is on top of its centre.
+    Covered { by: String },
+    /// It could not be scrolled into the viewport.
+    OutOfView,
+}
+
+impl fmt::Display for Unactionable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unactionable::Detached => f.write_str("element is detached"),
+            Unactionable::Hidden => f.write_str("element is not visible"),
+            Unactionable::Disabled => f.write_str("element is disabled"),
+            Unactionable::Moving => f.write_str("element is still moving"),
+            Unactionable::Covered { by } => write!(f, "element is covered by {by}"),
+            Unactionable::OutOfView => f.write_str("element cannot be scrolled into view"),
+        }
+    }
+}
+
+/// `tag#id.class` for error messages.
+fn describe(local_name: &str, attributes: &[String]) -> String {
+    let mut out = local_name.to_string();
+    for pair in attributes.chunks(2) {
+        match pair {
+            [name, value] if name == "id" && !value.is_empty() => {
+                out.push('#');
+                out.push_str(value);
+            }
+            [name, value] if name == "class" => {
+                for class in value.split_whitespace().take(3) {
+                    out.push('.');
+                    out.push_str(class);
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+impl ChaserPage {
+    /// Check once whether `element` can be clicked, scrolling it into view
+    /// if needed, and return the viewport point to click.
+    pub async fn check_actionable(&self, element: &Element) -> Result<Point> {
+        let state = self
+            .call_on(element, STATE_FN, Vec::new())
+            .await
+            .map_err(|_| ChaserError::NotActionable(Unactionable::Detached))?;
+        match state.as_ref().and_then(|v| v.as_str()) {
+            None => {}
+            Some("detached") => return Err(ChaserError::NotActionable(Unactionable::Detached)),
+            Some("disabled") => return Err(ChaserError::NotActionable(Unactionable::Disabled)),
+            Some(_) => return Err(ChaserError::NotActionable(Unactionable::Hidden)),
+        }
+
+        // two frames apart, a settled element has not moved
+        let before = element.bounding_box().await?;
+        tokio::time::sleep(Duration::from_millis(35)).await;
+        let after = element.bounding_box().await?;
+        let moved = (before.x - after.x).abs() > 1.0
+            || (before.y - after.y).abs() > 1.0
+            || (before.width - after.width).abs() > 1.0
+            || (before.height - after.height).abs() > 1.0;
+        if moved {
+            return Err(ChaserError::NotActionable(Unactionable::Moving));
+        }
+
+        let target = self.element_coordinates(element).await?;
+        let point = self
+            .scroll_into_view_coords(target)
+            .await
+            .map_err(|_| ChaserError::NotActionable(Unactionable::OutOfView))?;
+        let viewport = self.viewport_state().await?;
+        if !viewport.contains(point) {
+            return Err(ChaserError::NotActionable(Unactionable::OutOfView));
+        }
+
+        // hit-testing works in layout viewport pixels
+        let layout_x = point.x / viewport.scale + viewport.visual_offset_x;
+        let layout_y = point.y / viewport.scale + viewport.visual_offset_y;
+        let hit = self
+            .raw_page()
+            .execute(GetNodeForLocationParams::new(
+                layout_x.round() as i64,
+                layout_y.round() as i64,
+            ))
+            .await?
+            .result
+            .backend_node_id;
+        if hit == element.backend_node_id {
+            return Ok(point);
+        }
+        let hit_object = self
+            .raw_page()
+            .execute(ResolveNodeParams {
+                backend_node_id: Some(hit),
+                ..Default::default()
+            })
+            .await?
+            .result
+            .object
+            .object_id;
+        let argument = CallArgument {
+            object_id: hit_object,
+            ..Default::default()
+        };
+        let inside = self.call_on(element, CONTAINS_FN, vec![argument]).await?;
+        if inside.and_then(|v| v.as_bool()).unwrap_or(false) {
+            return Ok(point);
+        }
+        let node = self
+            .raw_page()
+            .execute(DescribeNodeParams {
+                backend_node_id: Some(hit),
+                ..Default::default()
+            })
+            .await?
+            .result
+            .node;
+        Err(ChaserError::NotActionable(Unactionable::Covered {
+            by: describe(&node.local_name, node.attributes.as_deref().unwrap_or(&[])),
+        }))
+    }
+
+    /// Wait until `element` can be clicked, see
+    /// [`check_actionable`](Self::check_actionable), for at most the
+    /// page's action timeout. Detached elements fail at once.
+    pub async fn wait_for_actionable(&self, element: &Element) -> Result<Point> {
+        let deadline = Instant::now() + self.timeouts().action;
+        loop {
+            match self.check_actionable(element).await {
+                Err(ChaserError::NotActionable(reason))
+                    if reason != Unactionable::Detached && Instant::now() < deadline =>
+                {
+                    tracing::debug!("waiting for element: {reason}");
+                    self.pause(Duration::from_millis(100)).await?;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Humanized click on the centre of `element` once it is actionable.
+    pub async fn click_element_human(&self, element: &Element) -> Result<()> {
+        let point = self.wait_for_actionable(element).await?;
+        self.click_human(point.x, point.y).await
+    }
+
+    /// Wait until an element matching `selector` exists and is actionable,
+    /// for at most the page's wait timeout, e.g. one that only renders
+    /// after a hover or a click.
+    pub async fn wait_for_selector_actionable(&self, selector: &str) -> Result<(Element, Point)> {
+        let deadline = Instant::now() + self.timeouts().wait;
+        loop {
+            if let Ok(element) = self.raw_page().find_element(selector).await {
+                match self.check_actionable(&element).await {
+                    Ok(point) => return Ok((element, point)),
+                    Err(ChaserError::NotActionable(reason)) => {
+                        tracing::debug!("waiting for {selector}: {reason}");
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            if Instant::now() >= deadline {
+                return Err(ChaserError::Timeout(format!("Waiting for {selector}")));
+            }
+            self.pause(Duration::from_millis(50)).await?;
+        }
+    }
+
+    /// Humanized click on the first element matching `selector` once it is
+    /// actionable.
+    pub async fn click_selector_human(&self, selector: &str) -> Result<()> {
+        let (_, point) = self.wait_for_selector_actionable(selector).await?;
+        self.click_human(point.x, point.y).await
+    }
+
+    /// Call `function` with `this` bound to `element`, returning its value.
+    async fn call_on(
+        &self,
+        element: &Element,
+        function: &str,
+        arguments: Vec<CallArgument>,
+    ) -> Result<Option<serde_json::Value>> {
+        let mut params = CallFunctionOnParams::new(function);
+        params.object_id = Some(element.remote_object_id.clone());
+        params.arguments = Some(arguments);
+        params.return_by_value = Some(true);
+        let result = self.raw_page().execute(params).await?.result;
+        if let Some(details) = result.exception_details {
+            return Err(ChaserError::Script(details.text));
+        }
+        Ok(result.result.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_covering_elements() {
+        let attributes = ["id", "consent", "class", "banner  fixed modal extra"]
+            .map(String::from)
+            .to_vec();
+        assert_eq!(
+            describe("div", &attributes),
+            "div#consent.banner.fixed.modal"
+        );
+        assert_eq!(
+            Unactionable::Covered {
+                by: "div#consent".into()
+            }
+            .to_string(),
+            "element is covered by div#consent"
+        );
+    }
+}
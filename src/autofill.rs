@@ -0,0 +1,370 @@
+//! Filling forms the way Chrome's autofill does.
+//!
+//! People rarely type their address or card number; they click into the
+//! first field, pick the suggestion Chrome offers and the whole form fills
+//! at once. A session that types every field of every checkout stands out
+//! as much as one that never types. [`ChaserPage::autofill`] hands the data
+//! to Chrome's own autofill (`Autofill.trigger`) after a humanized click on
+//! the anchor field and the pause of picking a suggestion, so the fields
+//! are filled by the browser: with its field type heuristics and the
+//! form's `autocomplete` attributes, with trusted events, and matching
+//! `:autofill` afterwards. Nothing is saved to the profile.
+//!
+//! [`ChaserPage::fill_form_human`] chooses between that and typing each
+//! field (found by its `autocomplete` token) per [`FillStyle`], so a
+//! persona can autofill some forms and type others.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::autofill::{Address, FillStyle};
+//!
+//! let address = Address::new()
+//!     .full_name("Ada Lovelace")
+//!     .email("ada@example.com")
+//!     .line1("12 St James's Square")
+//!     .city("London")
+//!     .zip("SW1Y 4JH")
+//!     .country("GB");
+//! chaser.fill_form_human("#shipping-name", &address.into(), FillStyle::Random(0.6)).await?;
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::{ChaserError, ChaserResult as Result};
+use chromiumoxide_cdp::cdp::browser_protocol::autofill::{self, TriggerParams};
+use chromiumoxide_cdp::cdp::browser_protocol::dom::BackendNodeId;
+use rand::Rng;
+use std::time::Duration;
+
+/// A field of an [`Address`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddressField {
+    FullName,
+    FirstName,
+    LastName,
+    Company,
+    Line1,
+    Line2,
+    City,
+    State,
+    Zip,
+    Country,
+    Email,
+    Phone,
+}
+
+impl AddressField {
+    /// Chrome's autofill type, as `Autofill.trigger` expects it.
+    pub fn chrome_type(&self) -> &'static str {
+        match self {
+            AddressField::FullName => "NAME_FULL",
+            AddressField::FirstName => "NAME_FIRST",
+            AddressField::LastName => "NAME_LAST",
+            AddressField::Company => "COMPANY_NAME",
+            AddressField::Line1 => "ADDRESS_HOME_LINE1",
+            AddressField::Line2 => "ADDRESS_HOME_LINE2",
+            AddressField::City => "ADDRESS_HOME_CITY",
+            AddressField::State => "ADDRESS_HOME_STATE",
+            AddressField::Zip => "ADDRESS_HOME_ZIP",
+            AddressField::Country => "ADDRESS_HOME_COUNTRY",
+            AddressField::Email => "EMAIL_ADDRESS",
+            AddressField::Phone => "PHONE_HOME_WHOLE_NUMBER",
+        }
+    }
+
+    /// The `autocomplete` token of inputs for this field.
+    pub fn autocomplete(&self) -> &'static str {
+        match self {
+            AddressField::FullName => "name",
+            AddressField::FirstName => "given-name",
+            AddressField::LastName => "family-name",
+            AddressField::Company => "organization",
+            AddressField::Line1 => "address-line1",
+            AddressField::Line2 => "address-line2",
+            AddressField::City => "address-level2",
+            AddressField::State => "address-level1",
+            AddressField::Zip => "postal-code",
+            AddressField::Country => "country",
+            AddressField::Email => "email",
+            AddressField::Phone => "tel",
+        }
+    }
+}
+
+/// A postal address with contact details, in the order it is filled.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Address {
+    fields: Vec<(AddressField, String)>,
+}
+
+impl Address {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set `field`, replacing an earlier value.
+    pub fn set(mut self, field: AddressField, value: impl Into<String>) -> Self {
+        self.fields.retain(|(f, _)| *f != field);
+        self.fields.push((field, value.into()));
+        self
+    }
+
+    pub fn full_name(self, name: impl Into<String>) -> Self {
+        self.set(AddressField::FullName, name)
+    }
+
+    pub fn first_name(self, name: impl Into<String>) -> Self {
+        self.set(AddressField::FirstName, name)
+    }
+
+    pub fn last_name(self, name: impl Into<String>) -> Self {
+        self.set(AddressField::LastName, name)
+    }
+
+    pub fn company(self, company: impl Into<String>) -> Self {
+        self.set(AddressField::Company, company)
+    }
+
+    pub fn line1(self, line: impl Into<String>) -> Self {
+        self.set(AddressField::Line1, line)
+    }
+
+    pub fn line2(self, line: impl Into<String>) -> Self {
+        self.set(AddressField::Line2, line)
+    }
+
+    pub fn city(self, city: impl Into<String>) -> Self {
+        self.set(AddressField::City, city)
+    }
+
+    pub fn state(self, state: impl Into<String>) -> Self {
+        self.set(AddressField::State, state)
+    }
+
+    pub fn zip(self, zip: impl Into<String>) -> Self {
+        self.set(AddressField::Zip, zip)
+    }
+
+    /// A country name or ISO 3166 code.
+    pub fn country(self, country: impl Into<String>) -> Self {
+        self.set(AddressField::Country, country)
+    }
+
+    pub fn email(self, email: impl Into<String>) -> Self {
+        self.set(AddressField::Email, email)
+    }
+
+    pub fn phone(self, phone: impl Into<String>) -> Self {
+        self.set(AddressField::Phone, phone)
+    }
+
+    pub fn fields(&self) -> &[(AddressField, String)] {
+        &self.fields
+    }
+}
+
+/// A payment card.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Card {
+    pub number: String,
+    pub name: String,
+    /// Two digits, e.g. `"04"`.
+    pub expiry_month: String,
+    /// Four digits, e.g. `"2029"`.
+    pub expiry_year: String,
+    pub cvc: String,
+}
+
+impl Card {
+    /// The fields with the `autocomplete` tokens of their inputs.
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("cc-name", self.name.clone()),
+            ("cc-number", self.number.clone()),
+            ("cc-exp-month", self.expiry_month.clone()),
+            ("cc-exp-year", self.expiry_year.clone()),
+            (
+                "cc-exp",
+                format!(
+                    "{}/{}",
+                    self.expiry_month,
+                    self.expiry_year
+                        .get(self.expiry_year.len().saturating_sub(2)..)
+                        .unwrap_or(&self.expiry_year)
+                ),
+            ),
+            ("cc-csc", self.cvc.clone()),
+        ]
+    }
+}
+
+/// What [`ChaserPage::autofill`] fills a form with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AutofillData {
+    Address(Address),
+    Card(Card),
+}
+
+impl From<Address> for AutofillData {
+    fn from(address: Address) -> Self {
+        AutofillData::Address(address)
+    }
+}
+
+impl From<Card> for AutofillData {
+    fn from(card: Card) -> Self {
+        AutofillData::Card(card)
+    }
+}
+
+impl AutofillData {
+    /// `(autocomplete token, value)` of every field.
+    fn typed_fields(&self) -> Vec<(&'static str, String)> {
+        match self {
+            AutofillData::Address(address) => address
+                .fields()
+                .iter()
+                .map(|(field, value)| (field.autocomplete(), value.clone()))
+                .collect(),
+            AutofillData::Card(card) => card.fields(),
+        }
+    }
+
+    fn trigger(&self, field_id: impl Into<BackendNodeId>) -> TriggerParams {
+        let mut params = TriggerParams::new(field_id);
+        match self {
+            AutofillData::Address(address) => {
+                params.address = Some(autofill::Address::new(
+                    address
+                        .fields()
+                        .iter()
+                        .map(|(field, value)| {
+                            autofill::AddressField::new(field.chrome_type(), value.clone())
+                        })
+                        .collect(),
+                ));
+            }
+            AutofillData::Card(card) => {
+                params.card = Some(autofill::CreditCard {
+                    number: card.number.clone(),
+                    name: card.name.clone(),
+                    expiry_month: card.expiry_month.clone(),
+                    expiry_year: card.expiry_year.clone(),
+                    cvc: card.cvc.clone(),
+                });
+            }
+        }
+        params
+    }
+}
+
+/// How [`ChaserPage::fill_form_human`] fills a form.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FillStyle {
+    /// Click into each field with an `autocomplete` token for the data and
+    /// type its value.
+    Type,
+    /// Let Chrome autofill the form from the anchor field.
+    Autofill,
+    /// Autofill with this probability, otherwise type.
+    Random(f64),
+}
+
+impl ChaserPage {
+    /// Fill the form of the field matching `selector` with Chrome's autofill:
+    /// click into the field, pause as if picking the suggestion, and let
+    /// Chrome fill every field it recognises. See the [module docs](crate::autofill).
+    ///
+    /// Fails if Chrome cannot autofill the form, e.g. when the field is not
+    /// part of one it recognises.
+    pub async fn autofill(&self, selector: &str, data: &AutofillData) -> Result<()> {
+        let (element, point) = self.wait_for_selector_actionable(selector).await?;
+        self.click_human(point.x, point.y).await?;
+        // reading the suggestions and moving to the right one
+        let pick = rand::thread_rng().gen_range(500..1400);
+        self.pause(Duration::from_millis(pick)).await?;
+        self.raw_page()
+            .execute(data.trigger(element.backend_node_id))
+            .await
+            .map_err(|e| ChaserError::msg(format!("Chrome did not autofill {selector}: {e}")))?;
+        Ok(())
+    }
+
+    /// Fill the form of the field matching `selector` with `data`, by
+    /// autofill or by typing as `style` says. Typing goes into the fields
+    /// of the anchor's form whose `autocomplete` attribute names them, in
+    /// the order of `data`; fields without one are left alone.
+    pub async fn fill_form_human(
+        &self,
+        selector: &str,
+        data: &AutofillData,
+        style: FillStyle,
+    ) -> Result<()> {
+        let autofill = match style {
+            FillStyle::Type => false,
+            FillStyle::Autofill => true,
+            FillStyle::Random(p) => rand::thread_rng().gen_bool(p.clamp(0.0, 1.0)),
+        };
+        if autofill {
+            return self.autofill(selector, data).await;
+        }
+
+        let form = format!("form:has({selector})");
+        let scope = match self.raw_page().find_element(form.as_str()).await {
+            Ok(_) => format!("{form} "),
+            Err(_) => String::new(),
+        };
+        for (token, value) in data.typed_fields() {
+            let field = format!("{scope}[autocomplete~=\"{token}\" i]");
+            if self.raw_page().find_element(field.as_str()).await.is_err() {
+                continue;
+            }
+            self.click_selector_human(&field).await?;
+            self.type_text(&value).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_fields_for_chrome_and_for_typing() {
+        let data: AutofillData = Address::new()
+            .full_name("Ada")
+            .zip("SW1Y")
+            .full_name("Ada Lovelace")
+            .into();
+        let params = data.trigger(BackendNodeId::new(7));
+        let address = params.address.unwrap();
+        let chrome: Vec<_> = address
+            .fields
+            .iter()
+            .map(|f| (f.name.as_str(), f.value.as_str()))
+            .collect();
+        assert_eq!(
+            chrome,
+            [("ADDRESS_HOME_ZIP", "SW1Y"), ("NAME_FULL", "Ada Lovelace")]
+        );
+        assert!(params.card.is_none());
+
+        let card = AutofillData::Card(Card {
+            number: "4111111111111111".into(),
+            name: "Ada Lovelace".into(),
+            expiry_month: "04".into(),
+            expiry_year: "2029".into(),
+            cvc: "123".into(),
+        });
+        let typed = card.typed_fields();
+        assert!(typed.contains(&("cc-exp", "04/29".to_string())));
+        assert_eq!(
+            card.trigger(BackendNodeId::new(7))
+                .card
+                .unwrap()
+                .expiry_year,
+            "2029"
+        );
+    }
+}
@@ -0,0 +1,369 @@
+//! Measuring how human a session's input looks.
+//!
+//! Behavioural detectors do not judge single events but distributions:
+//! intervals that are all alike, mouse strokes at constant speed along
+//! straight lines, typing with a metronome's rhythm. [`BehaviorStats`]
+//! collects the input a [`ChaserPage`] dispatches, summarises it as
+//! timing, velocity, curvature and typing-cadence distributions, and
+//! [`BehaviorSummary::compare`] checks them against a [`HumanBaseline`] so
+//! that humanization settings can be tuned against numbers rather than by
+//! feel.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::behavior::HumanBaseline;
+//!
+//! chaser.record_behavior();
+//! // ... run the task ...
+//! let stats = chaser.behavior_stats().unwrap();
+//! for warning in stats.summary().compare(&HumanBaseline::default()) {
+//!     eprintln!("looks robotic: {warning}");
+//! }
+//! ```
+//!
+//! [`ChaserPage`]: crate::chaser::ChaserPage
+
+use crate::chaser::Point;
+use crate::clock;
+use std::time::{Duration, Instant};
+
+/// Moves further apart than this belong to different strokes.
+const STROKE_GAP_MS: f64 = 100.0;
+/// Key intervals longer than this are pauses, not typing cadence.
+const TYPING_GAP_MS: f64 = 2_000.0;
+
+/// One input event as dispatched to the page.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputEvent {
+    /// The pointer moved to this viewport position.
+    Move(Point),
+    /// A mouse button or finger went down.
+    Press,
+    /// A key went down.
+    Key,
+}
+
+/// Collects the input events of a session.
+#[derive(Debug, Clone)]
+pub struct BehaviorStats {
+    started: Instant,
+    events: Vec<(Duration, InputEvent)>,
+}
+
+impl Default for BehaviorStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BehaviorStats {
+    pub fn new() -> Self {
+        Self {
+            started: clock::now(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Record `event` as happening now.
+    pub fn record(&mut self, event: InputEvent) {
+        let at = clock::elapsed(self.started);
+        self.events.push((at, event));
+    }
+
+    /// Record `event` at `at` after the start of the session, e.g. when
+    /// replaying a log.
+    pub fn record_at(&mut self, at: Duration, event: InputEvent) {
+        self.events.push((at, event));
+    }
+
+    /// Number of recorded events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// The recorded events with their time after the start of the session.
+    pub fn events(&self) -> &[(Duration, InputEvent)] {
+        &self.events
+    }
+
+    /// Compute the distributions of the recorded session.
+    pub fn summary(&self) -> BehaviorSummary {
+        let mut events = self.events.clone();
+        events.sort_by_key(|(at, _)| *at);
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+
+        let intervals: Vec<f64> = events.windows(2).map(|w| ms(w[1].0 - w[0].0)).collect();
+
+        let moves: Vec<(f64, Point)> = events
+            .iter()
+            .filter_map(|(at, e)| match e {
+                InputEvent::Move(p) => Some((ms(*at), *p)),
+                _ => None,
+            })
+            .collect();
+        let mut velocities = Vec::new();
+        let mut turns = Vec::new();
+        for w in moves.windows(2) {
+            let dt = w[1].0 - w[0].0;
+            if dt > 0.0 && dt < STROKE_GAP_MS {
+                velocities.push((w[1].1.x - w[0].1.x).hypot(w[1].1.y - w[0].1.y) / dt);
+            }
+        }
+        for w in moves.windows(3) {
+            if w[2].0 - w[0].0 >= 2.0 * STROKE_GAP_MS {
+                continue;
+            }
+            let a = (w[1].1.y - w[0].1.y).atan2(w[1].1.x - w[0].1.x);
+            let b = (w[2].1.y - w[1].1.y).atan2(w[2].1.x - w[1].1.x);
+            let mut turn = (b - a).abs();
+            if turn > std::f64::consts::PI {
+                turn = std::f64::consts::TAU - turn;
+            }
+            turns.push(turn);
+        }
+
+        let keys: Vec<f64> = events
+            .iter()
+            .filter(|(_, e)| *e == InputEvent::Key)
+            .map(|(at, _)| ms(*at))
+            .collect();
+        let typing: Vec<f64> = keys
+            .windows(2)
+            .map(|w| w[1] - w[0])
+            .filter(|gap| *gap < TYPING_GAP_MS)
+            .collect();
+
+        BehaviorSummary {
+            events: events.len(),
+            interval_hist: Histogram::new(
+                &[0.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1_000.0, 5_000.0],
+                &intervals,
+            ),
+            velocity_hist: Histogram::new(&[0.0, 0.1, 0.25, 0.5, 1.0, 2.0, 4.0, 8.0], &velocities),
+            curvature_hist: Histogram::new(
+                &[0.0, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, std::f64::consts::PI],
+                &turns,
+            ),
+            typing_hist: Histogram::new(
+                &[0.0, 50.0, 100.0, 150.0, 200.0, 300.0, 500.0, 1_000.0],
+                &typing,
+            ),
+            intervals: Stats::of(&intervals),
+            velocity: Stats::of(&velocities),
+            curvature: Stats::of(&turns),
+            typing: Stats::of(&typing),
+        }
+    }
+}
+
+/// Summary statistics of one distribution.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Stats {
+    pub count: usize,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub median: f64,
+}
+
+impl Stats {
+    fn of(samples: &[f64]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n;
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        Self {
+            count: samples.len(),
+            mean,
+            std_dev: variance.sqrt(),
+            median: sorted[sorted.len() / 2],
+        }
+    }
+
+    /// Coefficient of variation; 0 for a constant or empty distribution.
+    pub fn cv(&self) -> f64 {
+        if self.mean == 0.0 {
+            0.0
+        } else {
+            self.std_dev / self.mean
+        }
+    }
+}
+
+/// Counts of samples per bin; bin `i` covers `edges[i]..edges[i + 1]` and the
+/// last bin also takes everything above the last edge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    pub edges: Vec<f64>,
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    fn new(edges: &[f64], samples: &[f64]) -> Self {
+        let mut counts = vec![0; edges.len() - 1];
+        for &sample in samples {
+            let bin = edges[1..]
+                .iter()
+                .position(|&edge| sample < edge)
+                .unwrap_or(counts.len() - 1);
+            counts[bin] += 1;
+        }
+        Self {
+            edges: edges.to_vec(),
+            counts,
+        }
+    }
+}
+
+/// The distributions of a recorded session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BehaviorSummary {
+    pub events: usize,
+    /// Time between consecutive events of any kind, in ms.
+    pub intervals: Stats,
+    pub interval_hist: Histogram,
+    /// Pointer speed within strokes, in px/ms.
+    pub velocity: Stats,
+    pub velocity_hist: Histogram,
+    /// Turning angle between consecutive stroke segments, in radians.
+    pub curvature: Stats,
+    pub curvature_hist: Histogram,
+    /// Time between key presses while typing, in ms.
+    pub typing: Stats,
+    pub typing_hist: Histogram,
+}
+
+impl BehaviorSummary {
+    /// Everything about this session that falls outside `baseline`.
+    ///
+    /// Distributions with too few samples to judge are skipped.
+    pub fn compare(&self, baseline: &HumanBaseline) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.intervals.count >= 20 && self.intervals.cv() < baseline.min_interval_cv {
+            warnings.push(format!(
+                "event timing is too regular (cv {:.2}, humans > {:.2})",
+                self.intervals.cv(),
+                baseline.min_interval_cv
+            ));
+        }
+        if self.velocity.count >= 20 {
+            let (lo, hi) = baseline.velocity_mean;
+            if !(lo..=hi).contains(&self.velocity.mean) {
+                warnings.push(format!(
+                    "mean pointer speed {:.2}px/ms is outside {lo}-{hi}px/ms",
+                    self.velocity.mean
+                ));
+            }
+            if self.velocity.cv() < baseline.min_velocity_cv {
+                warnings.push(format!(
+                    "pointer speed is too constant (cv {:.2}, humans > {:.2})",
+                    self.velocity.cv(),
+                    baseline.min_velocity_cv
+                ));
+            }
+        }
+        if self.curvature.count >= 20 && self.curvature.mean < baseline.min_curvature {
+            warnings.push(format!(
+                "strokes are too straight (mean turn {:.3} rad, humans > {:.3})",
+                self.curvature.mean, baseline.min_curvature
+            ));
+        }
+        if self.typing.count >= 10 {
+            let (lo, hi) = baseline.typing_mean_ms;
+            if !(lo..=hi).contains(&self.typing.mean) {
+                warnings.push(format!(
+                    "mean key interval {:.0}ms is outside {lo}-{hi}ms",
+                    self.typing.mean
+                ));
+            }
+            if self.typing.cv() < baseline.min_typing_cv {
+                warnings.push(format!(
+                    "typing rhythm is too even (cv {:.2}, humans > {:.2})",
+                    self.typing.cv(),
+                    baseline.min_typing_cv
+                ));
+            }
+        }
+        warnings
+    }
+
+    /// Whether [`compare`](Self::compare) against the default baseline finds
+    /// anything.
+    pub fn looks_robotic(&self) -> bool {
+        !self.compare(&HumanBaseline::default()).is_empty()
+    }
+}
+
+/// Ranges that recorded human sessions fall into.
+///
+/// The defaults are deliberately wide so that only clearly mechanical input
+/// is flagged; tighten them with numbers from your own recorded sessions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HumanBaseline {
+    pub min_interval_cv: f64,
+    /// Mean pointer speed in px/ms.
+    pub velocity_mean: (f64, f64),
+    pub min_velocity_cv: f64,
+    /// Mean turning angle in radians.
+    pub min_curvature: f64,
+    /// Mean key interval in ms.
+    pub typing_mean_ms: (f64, f64),
+    pub min_typing_cv: f64,
+}
+
+impl Default for HumanBaseline {
+    fn default() -> Self {
+        Self {
+            min_interval_cv: 0.5,
+            velocity_mean: (0.2, 4.0),
+            min_velocity_cv: 0.3,
+            min_curvature: 0.02,
+            typing_mean_ms: (80.0, 400.0),
+            min_typing_cv: 0.25,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_a_metronomic_straight_line_session() {
+        let mut bot = BehaviorStats::new();
+        for i in 0..40 {
+            let at = Duration::from_millis(i * 10);
+            let point = Point {
+                x: i as f64 * 10.0,
+                y: 100.0,
+            };
+            bot.record_at(at, InputEvent::Move(point));
+        }
+        for i in 0..20 {
+            bot.record_at(Duration::from_millis(1_000 + i * 100), InputEvent::Key);
+        }
+        let summary = bot.summary();
+        assert_eq!(summary.events, 60);
+        assert_eq!(summary.velocity.mean, 1.0);
+        assert_eq!(summary.curvature.mean, 0.0);
+
+        let warnings = summary.compare(&HumanBaseline::default());
+        assert!(warnings
+            .iter()
+            .any(|w| w.starts_with("pointer speed is too constant")));
+        assert!(warnings
+            .iter()
+            .any(|w| w.starts_with("strokes are too straight")));
+        assert!(warnings
+            .iter()
+            .any(|w| w.starts_with("typing rhythm is too even")));
+    }
+}
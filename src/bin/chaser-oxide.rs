@@ -0,0 +1,61 @@
+//! Command line tools for chaser-oxide.
+//!
+//! ```text
+//! chaser-oxide repl [--connect <url>] [--launch <windows|mac|linux>]
+//! ```
+//!
+//! `--connect` attaches to a running browser started with
+//! `--remote-debugging-port` (default `http://127.0.0.1:9222`) and drives its
+//! first tab; `--launch` starts a new headed stealth browser instead.
+
+use chaser_oxide::repl::Repl;
+use chaser_oxide::{Browser, ChaserPage, Os};
+
+const USAGE: &str = "usage: chaser-oxide repl [--connect <url>] [--launch <windows|mac|linux>]";
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) != Some("repl") {
+        eprintln!("{USAGE}");
+        std::process::exit(2);
+    }
+
+    let mut connect = "http://127.0.0.1:9222".to_string();
+    let mut launch = None;
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        match (arg.as_str(), rest.next()) {
+            ("--connect", Some(url)) => connect = url.clone(),
+            ("--launch", Some(os)) => launch = Some(os.clone()),
+            _ => {
+                eprintln!("{USAGE}");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    let (_browser, page) = match launch {
+        Some(os) => {
+            let os = match os.as_str() {
+                "windows" => Os::Windows,
+                "mac" => Os::MacOSArm,
+                "linux" => Os::Linux,
+                other => anyhow::bail!("unknown OS {other}"),
+            };
+            ChaserPage::launch_headed(os).await?
+        }
+        None => {
+            let (browser, handler) = Browser::connect(connect).await?;
+            handler.spawn();
+            let page = match browser.pages().await?.into_iter().next() {
+                Some(page) => page,
+                None => browser.new_page("about:blank").await?,
+            };
+            (browser, ChaserPage::new(page))
+        }
+    };
+
+    let history = std::env::temp_dir().join("chaser-oxide-repl.history");
+    Repl::new(page).history_file(history).run().await
+}
@@ -0,0 +1,349 @@
+//! Pinning each profile to one proxy exit, for good.
+//!
+//! An account that logs in from Frankfurt on Monday and from Ohio on
+//! Tuesday, or two accounts that briefly share an address because a
+//! rotation list was reshuffled, is flagged no matter how clean the
+//! fingerprint is. [`BindingRegistry`] makes the association between a
+//! profile id and its proxy permanent: a profile is bound once, a proxy
+//! serves one profile, and [`BindingRegistry::acquire`] refuses to lease a
+//! page whose binding cannot be honored.
+//!
+//! The proxy URL is not the whole story: rotating gateways change the exit
+//! behind the same URL. The registry learns the exit address at the first
+//! checkout from an IP echo service and compares it on every later
+//! checkout and, with [`BindingRegistry::watch`], periodically while the
+//! page is in use. Every mismatch is logged and kept as a [`Leak`].
+//!
+//! Bindings are kept in memory, or in a JSON file with
+//! [`BindingRegistry::open`] so they survive restarts.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::binding::{Binding, BindingRegistry};
+//! use chaser_oxide::regions::Region;
+//!
+//! let registry = BindingRegistry::open("bindings.json")?;
+//! registry.bind("acct-17", Binding::new("http://user-17@gate.example:7000").region(Region::Germany))?;
+//!
+//! let profile = ChaserProfile::windows().region(Region::Germany).build();
+//! let lease = registry.acquire(&pool, "acct-17", profile).await?;
+//! let watch = registry.watch("acct-17", lease.page().clone(), Duration::from_secs(300));
+//! // ... work with lease.page()
+//! watch.abort();
+//! pool.release(lease).await?;
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::{ChaserError, ChaserResult};
+use crate::pool::{BrowserPool, PooledPage, TaskAssignment};
+use crate::profiles::ChaserProfile;
+use crate::regions::Region;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// Returns the caller's address as plain text.
+pub const DEFAULT_ECHO_URL: &str = "https://api.ipify.org";
+
+/// The proxy a profile is bound to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Binding {
+    pub proxy: String,
+    /// The region the proxy exits in. Profiles with another region are
+    /// refused.
+    pub region: Option<Region>,
+    /// The address the proxy exits through, learned at the first checkout
+    /// unless set up front.
+    pub exit_ip: Option<IpAddr>,
+}
+
+impl Binding {
+    pub fn new(proxy: impl Into<String>) -> Self {
+        Self {
+            proxy: proxy.into(),
+            region: None,
+            exit_ip: None,
+        }
+    }
+
+    pub fn region(mut self, region: Region) -> Self {
+        self.region = Some(region);
+        self
+    }
+
+    pub fn exit_ip(mut self, ip: IpAddr) -> Self {
+        self.exit_ip = Some(ip);
+        self
+    }
+}
+
+/// Traffic of a profile seen leaving through an address it is not bound
+/// to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Leak {
+    pub profile_id: String,
+    pub expected: IpAddr,
+    pub observed: IpAddr,
+    pub at: SystemTime,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    bindings: BTreeMap<String, Binding>,
+    leaks: Vec<Leak>,
+}
+
+/// Permanent profile to proxy bindings. Cheap to clone; clones share the
+/// bindings.
+#[derive(Debug, Clone)]
+pub struct BindingRegistry {
+    state: Arc<Mutex<State>>,
+    path: Option<PathBuf>,
+    echo_url: String,
+}
+
+impl BindingRegistry {
+    /// A registry that is not persisted anywhere.
+    pub fn in_memory() -> Self {
+        Self {
+            state: Arc::default(),
+            path: None,
+            echo_url: DEFAULT_ECHO_URL.to_string(),
+        }
+    }
+
+    /// Open (or create) a registry kept in the JSON file at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let bindings = match fs::read(&path) {
+            Ok(json) => serde_json::from_slice(&json)
+                .map_err(|e| anyhow!("unreadable bindings in {}: {e}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let registry = Self::in_memory();
+        registry.state.lock().unwrap().bindings = bindings;
+        Ok(Self {
+            path: Some(path),
+            ..registry
+        })
+    }
+
+    /// The service checkouts and watches ask for the exit address, which
+    /// must answer with the caller's IP as plain text or as `{"ip": ...}`
+    /// JSON and allow cross-origin reads.
+    pub fn echo_url(mut self, url: impl Into<String>) -> Self {
+        self.echo_url = url.into();
+        self
+    }
+
+    /// Bind `profile_id` to `binding.proxy` for good. Binding a profile
+    /// again to the same proxy is a no-op; to another proxy, or to a proxy
+    /// serving another profile, an error.
+    pub fn bind(&self, profile_id: &str, binding: Binding) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(existing) = state.bindings.get(profile_id) {
+            if existing.proxy == binding.proxy {
+                return Ok(());
+            }
+            return Err(anyhow!(
+                "{profile_id} is bound to {}, not {}",
+                existing.proxy,
+                binding.proxy
+            ));
+        }
+        if let Some((owner, _)) = state
+            .bindings
+            .iter()
+            .find(|(_, b)| b.proxy == binding.proxy)
+        {
+            return Err(anyhow!("{} already serves {owner}", binding.proxy));
+        }
+        state.bindings.insert(profile_id.to_string(), binding);
+        self.persist(&state)
+    }
+
+    pub fn binding(&self, profile_id: &str) -> Option<Binding> {
+        self.state.lock().unwrap().bindings.get(profile_id).cloned()
+    }
+
+    /// Every mismatch seen so far, oldest first.
+    pub fn leaks(&self) -> Vec<Leak> {
+        self.state.lock().unwrap().leaks.clone()
+    }
+
+    /// Lease a page for `profile_id` running `profile` through its bound
+    /// proxy, after checking the page actually exits where the profile
+    /// always has. Fails without leasing when the profile is unbound,
+    /// `profile` is set to another region than the proxy, or the exit has
+    /// moved.
+    pub async fn acquire(
+        &self,
+        pool: &BrowserPool,
+        profile_id: &str,
+        profile: ChaserProfile,
+    ) -> Result<PooledPage> {
+        let binding = self
+            .binding(profile_id)
+            .ok_or_else(|| anyhow!("{profile_id} is not bound to a proxy"))?;
+        if let (Some(bound), Some(claimed)) = (binding.region, profile.region()) {
+            if bound != claimed {
+                return Err(anyhow!(
+                    "{profile_id} exits in {bound:?} but its profile claims {claimed:?}"
+                ));
+            }
+        }
+
+        let assignment = TaskAssignment::new()
+            .profile(profile)
+            .proxy(binding.proxy.clone());
+        let lease = pool.acquire(assignment).await?;
+        if let Err(e) = self.verify(profile_id, lease.page()).await {
+            let _ = pool.release(lease).await;
+            return Err(e);
+        }
+        Ok(lease)
+    }
+
+    /// Check that `page` exits through the address `profile_id` is bound
+    /// to, pinning the address if it is not known yet.
+    pub async fn verify(&self, profile_id: &str, page: &ChaserPage) -> Result<IpAddr> {
+        let observed = page.exit_ip(&self.echo_url).await?;
+        let mut state = self.state.lock().unwrap();
+        let binding = state
+            .bindings
+            .get_mut(profile_id)
+            .ok_or_else(|| anyhow!("{profile_id} is not bound to a proxy"))?;
+        match binding.exit_ip {
+            None => {
+                binding.exit_ip = Some(observed);
+                self.persist(&state)?;
+                Ok(observed)
+            }
+            Some(expected) if expected == observed => Ok(observed),
+            Some(expected) => {
+                tracing::error!(
+                    "{profile_id} left through {observed} instead of its bound exit {expected}"
+                );
+                state.leaks.push(Leak {
+                    profile_id: profile_id.to_string(),
+                    expected,
+                    observed,
+                    at: crate::clock::system_time(),
+                });
+                Err(anyhow!(
+                    "{profile_id} exits through {observed}, bound to {expected}"
+                ))
+            }
+        }
+    }
+
+    /// Verify the exit of `page` every `every` until the returned task is
+    /// aborted. Mismatches are logged and recorded in
+    /// [`leaks`](Self::leaks); failed echo requests are logged as warnings.
+    pub fn watch(
+        &self,
+        profile_id: &str,
+        page: ChaserPage,
+        every: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let registry = self.clone();
+        let profile_id = profile_id.to_string();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(every).await;
+                if let Err(e) = registry.verify(&profile_id, &page).await {
+                    tracing::warn!("exit check for {profile_id} failed: {e}");
+                }
+            }
+        })
+    }
+
+    fn persist(&self, state: &State) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(&state.bindings)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+}
+
+/// Reads the echo response as text, with the page's network stack and so
+/// through its proxy.
+fn echo_script(url: &str) -> String {
+    format!(
+        r#"fetch({}, {{ cache: "no-store", credentials: "omit" }}).then(r => r.text())"#,
+        serde_json::to_string(url).unwrap_or_default()
+    )
+}
+
+/// The address in an echo response: plain text, or JSON with an `ip`
+/// field.
+fn parse_echo(body: &str) -> Option<IpAddr> {
+    let body = body.trim();
+    if let Ok(ip) = body.parse() {
+        return Some(ip);
+    }
+    let json: serde_json::Value = serde_json::from_str(body).ok()?;
+    json.get("ip")?.as_str()?.trim().parse().ok()
+}
+
+impl ChaserPage {
+    /// The address this page's traffic leaves through, as the IP echo
+    /// service at `echo_url` sees it (see [`DEFAULT_ECHO_URL`]).
+    pub async fn exit_ip(&self, echo_url: &str) -> ChaserResult<IpAddr> {
+        let body = self
+            .evaluate_stealth(&echo_script(echo_url))
+            .await?
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        parse_echo(&body)
+            .ok_or_else(|| ChaserError::msg(format!("{echo_url} returned no address: {body:?}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_are_permanent_and_exclusive() {
+        let path =
+            std::env::temp_dir().join(format!("chaser-bindings-{}.json", uuid::Uuid::new_v4()));
+        let registry = BindingRegistry::open(&path).unwrap();
+        registry
+            .bind(
+                "a",
+                Binding::new("http://a@gate:7000").region(Region::Germany),
+            )
+            .unwrap();
+        registry
+            .bind("a", Binding::new("http://a@gate:7000"))
+            .unwrap();
+        assert!(registry
+            .bind("a", Binding::new("http://b@gate:7000"))
+            .is_err());
+        assert!(registry
+            .bind("b", Binding::new("http://a@gate:7000"))
+            .is_err());
+
+        let reopened = BindingRegistry::open(&path).unwrap();
+        assert_eq!(reopened.binding("a").unwrap().region, Some(Region::Germany));
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(parse_echo("203.0.113.7\n"), "203.0.113.7".parse().ok());
+        assert_eq!(
+            parse_echo(r#"{"ip": "2001:db8::1", "country": "DE"}"#),
+            "2001:db8::1".parse().ok()
+        );
+        assert_eq!(parse_echo("<html>blocked</html>"), None);
+    }
+}
@@ -15,7 +15,11 @@ impl ArgsBuilder {
     pub fn arg<T: Into<Arg>>(&mut self, arg: T) -> &mut Self {
         let arg = arg.into();
         if let Some(values) = self.0.get_mut(&arg.key) {
-            values.extend(arg.values);
+            for value in arg.values {
+                if !values.contains(&value) {
+                    values.push(value);
+                }
+            }
         } else {
             self.0.insert(arg.key, arg.values);
         }
@@ -90,21 +94,20 @@ impl From<(&str, &[&str])> for Arg {
     }
 }
 
+/// Accepts both `key` and command line style `--key=value`.
 impl From<&str> for Arg {
     fn from(value: &str) -> Self {
-        Self {
-            key: value.to_string(),
-            values: Vec::new(),
+        let value = value.trim_start_matches("--");
+        match value.split_once('=') {
+            Some((key, value)) => Self::value(key, value),
+            None => Self::key(value),
         }
     }
 }
 
 impl From<String> for Arg {
     fn from(value: String) -> Self {
-        Self {
-            key: value,
-            values: Vec::new(),
-        }
+        Self::from(value.as_str())
     }
 }
 
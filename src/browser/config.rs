@@ -10,6 +10,7 @@ use crate::async_process::{self, Child, Stdio};
 use crate::detection::{self, DetectionOptions};
 use crate::handler::viewport::Viewport;
 use crate::handler::REQUEST_TIMEOUT;
+use crate::listeners::DEFAULT_EVENT_BUFFER;
 
 /// Default `Browser::launch` timeout in MS
 pub const LAUNCH_TIMEOUT: u64 = 20_000;
@@ -83,6 +84,9 @@ pub struct BrowserConfig {
     /// The duration after a request with no response should time out
     pub(crate) request_timeout: Duration,
 
+    /// Unread events an event stream may hold before new ones are dropped
+    pub(crate) event_buffer: usize,
+
     /// Additional command line arguments to pass to the browser instance.
     pub(crate) args: Vec<Arg>,
 
@@ -117,6 +121,7 @@ pub struct BrowserConfigBuilder {
     disable_https_first: bool,
     viewport: Option<Viewport>,
     request_timeout: Duration,
+    event_buffer: usize,
     args: Vec<Arg>,
     disable_default_args: bool,
     request_intercept: bool,
@@ -153,6 +158,7 @@ impl Default for BrowserConfigBuilder {
             disable_https_first: false,
             viewport: Some(Default::default()),
             request_timeout: Duration::from_millis(REQUEST_TIMEOUT),
+            event_buffer: DEFAULT_EVENT_BUFFER,
             args: Vec::new(),
             disable_default_args: false,
             request_intercept: false,
@@ -220,6 +226,15 @@ impl BrowserConfigBuilder {
         self
     }
 
+    /// How many events a stream from `event_listener` may hold unread
+    /// before new events are dropped with a warning, 4096 by default. Keeps
+    /// a forgotten or slow listener from growing memory without bound.
+    /// `Fetch` events are never dropped, as their requests would hang.
+    pub fn event_buffer(mut self, events: usize) -> Self {
+        self.event_buffer = events;
+        self
+    }
+
     /// Configures the viewport of the browser, which defaults to `800x600`.
     /// `None` disables viewport emulation (i.e., it uses the browsers default
     /// configuration, which fills the available space. This is similar to what
@@ -354,6 +369,7 @@ impl BrowserConfigBuilder {
             disable_https_first: self.disable_https_first,
             viewport: self.viewport,
             request_timeout: self.request_timeout,
+            event_buffer: self.event_buffer,
             args: self.args,
             disable_default_args: self.disable_default_args,
             request_intercept: self.request_intercept,
@@ -365,6 +381,17 @@ impl BrowserConfigBuilder {
 
 impl BrowserConfig {
     pub fn launch(&self) -> io::Result<Child> {
+        let mut cmd = async_process::Command::new(&self.executable);
+        cmd.args(self.launch_args());
+
+        if let Some(ref envs) = self.process_envs {
+            cmd.envs(envs);
+        }
+        cmd.stderr(Stdio::piped()).spawn()
+    }
+
+    /// The command line arguments the browser is launched with.
+    pub fn launch_args(&self) -> Vec<String> {
         let mut builder = ArgsBuilder::new();
 
         if self.disable_default_args {
@@ -440,15 +467,7 @@ impl BrowserConfig {
             ));
         }
 
-        let mut cmd = async_process::Command::new(&self.executable);
-
-        let args = builder.into_iter().collect::<Vec<String>>();
-        cmd.args(args);
-
-        if let Some(ref envs) = self.process_envs {
-            cmd.envs(envs);
-        }
-        cmd.stderr(Stdio::piped()).spawn()
+        builder.into_iter().collect()
     }
 }
 
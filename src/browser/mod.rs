@@ -1,7 +1,7 @@
 use std::future::Future;
 use std::io;
 
-use futures::channel::mpsc::{channel, unbounded, Sender};
+use futures::channel::mpsc::{channel, Sender};
 use futures::channel::oneshot::channel as oneshot_channel;
 use futures::select;
 use futures::SinkExt;
@@ -27,7 +27,7 @@ use crate::conn::Connection;
 use crate::error::{BrowserStderr, CdpError, Result};
 use crate::handler::browser::BrowserContext;
 use crate::handler::{Handler, HandlerConfig, HandlerMessage};
-use crate::listeners::{EventListenerRequest, EventStream};
+use crate::listeners::{subscription, EventStream};
 use crate::page::Page;
 use crate::utils;
 
@@ -208,6 +208,7 @@ impl Browser {
             viewport: config.viewport.clone(),
             context_ids: Vec::new(),
             request_timeout: config.request_timeout,
+            event_buffer: config.event_buffer,
             request_intercept: config.request_intercept,
             cache_enabled: config.cache_enabled,
         };
@@ -226,6 +227,17 @@ impl Browser {
         Ok((browser, fut))
     }
 
+    /// A browser without a process, whose handler messages go to `sender`.
+    pub(crate) fn from_sender(sender: Sender<HandlerMessage>) -> Self {
+        Self {
+            sender,
+            config: None,
+            child: None,
+            debug_ws_url: String::new(),
+            browser_context: BrowserContext::default(),
+        }
+    }
+
     /// Request to fetch all existing browser targets.
     ///
     /// By default, only targets launched after the browser connection are tracked
@@ -449,15 +461,13 @@ impl Browser {
 
     /// Set listener for browser event
     pub async fn event_listener<T: IntoEventKind>(&self) -> Result<EventStream<T>> {
-        let (tx, rx) = unbounded();
+        let (request, stream) = subscription::<T>();
         self.sender
             .clone()
-            .send(HandlerMessage::AddEventListener(
-                EventListenerRequest::new::<T>(tx),
-            ))
+            .send(HandlerMessage::AddEventListener(request))
             .await?;
 
-        Ok(EventStream::new(rx))
+        Ok(stream)
     }
 
     /// Creates a new empty browser context.
@@ -0,0 +1,114 @@
+//! Cooperative cancellation of humanized operations.
+//!
+//! Dropping the future of a half-finished drag or keystroke can leave the
+//! page with a button or key held down. Instead, hand a [`CancellationToken`]
+//! to the page with [`ChaserPage::set_cancellation_token`]: mouse paths,
+//! typing and scrolling check it between steps, release whatever they hold
+//! and fail with [`ChaserError::Cancelled`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::cancel::CancellationToken;
+//!
+//! let token = CancellationToken::new();
+//! chaser.set_cancellation_token(Some(token.clone()));
+//! tokio::spawn(async move {
+//!     tokio::time::sleep(Duration::from_secs(5)).await;
+//!     token.cancel();
+//! });
+//! let typed = chaser.type_text("a very long text ...").await;
+//! assert!(matches!(typed, Err(ChaserError::Cancelled)));
+//! ```
+//!
+//! [`ChaserPage::set_cancellation_token`]: crate::chaser::ChaserPage::set_cancellation_token
+//! [`ChaserError::Cancelled`]: crate::error::ChaserError::Cancelled
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+/// A flag that, once set, stays set; clones share it.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel every operation watching this token.
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        for waker in self.inner.wakers.lock().unwrap().drain(..) {
+            waker.wake();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once the token is cancelled.
+    pub fn cancelled(&self) -> Cancelled<'_> {
+        Cancelled { token: self }
+    }
+}
+
+/// Future returned by [`CancellationToken::cancelled`].
+#[derive(Debug)]
+pub struct Cancelled<'a> {
+    token: &'a CancellationToken,
+}
+
+impl Future for Cancelled<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+        let mut wakers = self.token.inner.wakers.lock().unwrap();
+        // checked again under the lock so a concurrent cancel cannot be missed
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+        if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn wakes_waiters_on_cancel() {
+        let token = CancellationToken::new();
+        let waiter = {
+            let token = token.clone();
+            tokio::spawn(async move { token.cancelled().await })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!waiter.is_finished());
+        token.cancel();
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .expect("waiter woke up")
+            .unwrap();
+        assert!(token.is_cancelled());
+    }
+}
@@ -1,37 +1,193 @@
-use crate::browser::{Browser, BrowserConfig};
+use crate::behavior::{BehaviorStats, InputEvent};
+use crate::browser::Browser;
+use crate::cancel::CancellationToken;
+use crate::clock;
+use crate::error::{CdpError, ChaserError, ChaserResult as Result};
+use crate::geometry::Geometry;
+use crate::header_template::HeaderRewrite;
+use crate::input_pipeline::InputPipeline;
+use crate::keyboard::KeyboardLayout;
+use crate::obfuscation::Obfuscation;
 use crate::page::Page;
-use crate::profiles::ChaserProfile;
-use anyhow::{anyhow, Result};
+use crate::page_errors::ErrorLog;
+use crate::policy::StealthPolicy;
+use crate::profiles::{ChaserProfile, Os};
+use crate::reaction::{Interaction, PageContext, ReactionModel};
+use crate::signing::RequestSigner;
+use crate::timeouts::Timeouts;
+use crate::tokens::TokenWatch;
+use crate::utils;
+use crate::verdict::NavigationVerdict;
+use crate::window::WindowTracker;
 use base64::{engine::general_purpose::STANDARD, Engine};
-use chromiumoxide_cdp::cdp::browser_protocol::emulation::SetDeviceMetricsOverrideParams;
+use chromiumoxide_cdp::cdp::browser_protocol::browser::{
+    GetVersionParams, PermissionDescriptor, PermissionSetting, SetPermissionParams,
+};
+use chromiumoxide_cdp::cdp::browser_protocol::emulation::{
+    MediaFeature, SetEmulatedMediaParams, SetGeolocationOverrideParams, SetLocaleOverrideParams,
+    SetTimezoneOverrideParams, SetTouchEmulationEnabledParams, SetUserAgentOverrideParams,
+};
 use chromiumoxide_cdp::cdp::browser_protocol::fetch::{
     ContinueRequestParams, DisableParams as FetchDisableParams, EnableParams as FetchEnableParams,
     FulfillRequestParams, HeaderEntry, RequestPattern,
 };
 use chromiumoxide_cdp::cdp::browser_protocol::input::{
-    DispatchKeyEventParams, DispatchKeyEventType,
+    DispatchKeyEventParams, DispatchKeyEventType, DispatchMouseEventParams,
+    DispatchMouseEventPointerType, DispatchMouseEventType, DispatchTouchEventParams,
+    DispatchTouchEventType, GestureSourceType, MouseButton, SynthesizeScrollGestureParams,
+    TouchPoint,
 };
 use chromiumoxide_cdp::cdp::browser_protocol::network::ResourceType;
 use chromiumoxide_cdp::cdp::browser_protocol::page::{
-    AddScriptToEvaluateOnNewDocumentParams, CreateIsolatedWorldParams,
+    AddScriptToEvaluateOnNewDocumentParams, CreateIsolatedWorldParams, EventLifecycleEvent,
+    NavigateParams, ScriptIdentifier,
 };
-use chromiumoxide_cdp::cdp::js_protocol::runtime::EvaluateParams;
+use chromiumoxide_cdp::cdp::browser_protocol::target::GetTargetInfoParams;
+use chromiumoxide_cdp::cdp::js_protocol::runtime::{EvaluateParams, ExecutionContextId};
+use chromiumoxide_types::{Command, CommandResponse};
 use futures::StreamExt;
 use rand::Rng;
 use serde_json::Value;
+use std::future::Future;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Point {
     pub x: f64,
     pub y: f64,
 }
 
+/// Milliseconds since the document started loading and since its last layout
+/// shift that was not caused by input. Resolves after at most 30ms.
+const PAGE_CONTEXT_SCRIPT: &str = r#"new Promise(resolve => {
+    const now = performance.now();
+    let last = null;
+    const done = () => resolve([now, last === null ? null : now - last]);
+    try {
+        new PerformanceObserver((list, observer) => {
+            for (const e of list.getEntries()) {
+                if (!e.hadRecentInput) last = Math.max(last ?? 0, e.startTime);
+            }
+            observer.disconnect();
+            done();
+        }).observe({ type: 'layout-shift', buffered: true });
+    } catch (e) {}
+    setTimeout(done, 30);
+})"#;
+
+/// `[kind, name]` of the anti-bot page or captcha showing, or `null`.
+pub(crate) const BLOCK_CHECK_SCRIPT: &str = r#"(() => {
+    const title = document.title || '';
+    const text = document.body ? document.body.innerText.slice(0, 5000) : '';
+    const visible = selector => Array.from(document.querySelectorAll(selector)).some(el => {
+        const r = el.getBoundingClientRect();
+        return r.width > 30 && r.height > 30;
+    });
+    if (/^Just a moment|^Attention Required! \| Cloudflare/.test(title)
+        || document.querySelector('#challenge-form, #cf-challenge-running')) {
+        return ['block', 'Cloudflare'];
+    }
+    if (title === 'Access Denied' && /Reference #[0-9a-f.]+/.test(text)) return ['block', 'Akamai'];
+    if (/Incapsula incident ID/.test(text) || document.querySelector('iframe[src*="_Incapsula_Resource"]')) {
+        return ['block', 'Imperva'];
+    }
+    if (visible('iframe[src*="captcha-delivery.com"]')) return ['captcha', 'DataDome'];
+    if (visible('#px-captcha')) return ['captcha', 'PerimeterX'];
+    if (visible('iframe[src*="challenges.cloudflare.com"]')) return ['captcha', 'Turnstile'];
+    if (visible('iframe[src*="hcaptcha.com"]')) return ['captcha', 'hCaptcha'];
+    if (visible('iframe[src*="/recaptcha/"][src*="bframe"], iframe[src*="/recaptcha/"][src*="anchor"]:not([src*="size=invisible"])')) {
+        return ['captcha', 'reCAPTCHA'];
+    }
+    return null;
+})()"#;
+
+/// The point in a document's lifecycle [`ChaserPage::goto_stealth`] waits
+/// for, as reported by `Page.lifecycleEvent`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WaitUntil {
+    /// The new document was committed; nothing of it has loaded yet.
+    Commit,
+    DomContentLoaded,
+    #[default]
+    Load,
+    /// At most two network connections for 500ms.
+    NetworkAlmostIdle,
+    /// No network connections for 500ms.
+    NetworkIdle,
+    /// The paint after which the page's main content is visible. Chrome no
+    /// longer computes it for every page, so `networkIdle` counts as well.
+    FirstMeaningfulPaint,
+}
+
+impl WaitUntil {
+    /// Whether the lifecycle event `name` satisfies this wait.
+    fn is_reached_by(self, name: &str) -> bool {
+        match self {
+            WaitUntil::Commit => true,
+            WaitUntil::DomContentLoaded => name == "DOMContentLoaded",
+            WaitUntil::Load => name == "load",
+            WaitUntil::NetworkAlmostIdle => name == "networkAlmostIdle",
+            WaitUntil::NetworkIdle => name == "networkIdle",
+            WaitUntil::FirstMeaningfulPaint => {
+                name == "firstMeaningfulPaint" || name == "networkIdle"
+            }
+        }
+    }
+}
+
+/// The JavaScript world a script runs in. Neither enables the `Runtime`
+/// domain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExecutionWorld {
+    /// The page's DOM, but none of its globals; the page cannot see the
+    /// script's variables or tell that it ran.
+    #[default]
+    Isolated,
+    /// The page's own globals, for calling its functions or reading what
+    /// its scripts set. Goes through the postMessage bridge, so the page
+    /// can notice the call.
+    Main,
+}
+
+/// Where the simulated cursor is, in viewport CSS pixels.
+///
+/// Viewport coordinates are what `Input.dispatchMouseEvent` uses, so the
+/// position stays valid across scrolls and navigations; only a viewport
+/// resize can move it out of bounds.
+#[derive(Debug, Clone, Copy)]
+struct MouseState {
+    pos: Point,
+    viewport: Option<(f64, f64)>,
+    /// Whether the cursor was ever placed on the page.
+    placed: bool,
+    /// `MouseEvent.buttons` bitmask of the buttons currently held; a finger
+    /// on the screen counts as the left button.
+    buttons: i64,
+    /// Input comes from a finger (mobile profiles) rather than a mouse.
+    touch: bool,
+}
+
+impl MouseState {
+    fn clamp(&self, point: Point) -> Point {
+        match self.viewport {
+            Some((w, h)) => Point {
+                x: point.x.clamp(0.0, (w - 1.0).max(0.0)),
+                y: point.y.clamp(0.0, (h - 1.0).max(0.0)),
+            },
+            None => Point {
+                x: point.x.max(0.0),
+                y: point.y.max(0.0),
+            },
+        }
+    }
+}
+
 /// Stealth browser page with human-like input simulation.
 ///
 /// # Stealth JavaScript Execution
 ///
-/// ```rust
+/// ```ignore
 /// // Safe - uses isolated world, no Runtime.enable leak
 /// let title = chaser.evaluate("document.title").await?;
 ///
@@ -46,10 +202,57 @@ pub struct Point {
 /// - Zero-footprint JS execution via `Page.createIsolatedWorld`
 /// - Bezier curve mouse movements with jitter
 /// - Realistic typing with variable delays
+/// - Reaction times that depend on what the page just did
 #[derive(Clone, Debug)]
 pub struct ChaserPage {
     page: Page,
-    mouse_pos: Arc<Mutex<Point>>,
+    mouse: Arc<Mutex<MouseState>>,
+    reaction: Arc<Mutex<ReactionModel>>,
+    behavior: Arc<Mutex<Option<BehaviorStats>>>,
+    timeouts: Arc<Mutex<Timeouts>>,
+    keyboard: Arc<Mutex<KeyboardLayout>>,
+    cancel: Arc<Mutex<Option<CancellationToken>>>,
+    /// The key-up event owed for a key that is currently down.
+    pending_key_up: Arc<Mutex<Option<DispatchKeyEventParams>>>,
+    window: Arc<Mutex<Option<WindowTracker>>>,
+    errors: Arc<Mutex<Option<ErrorLog>>>,
+    tokens: Arc<Mutex<Option<TokenWatch>>>,
+    header_rewrite: Arc<Mutex<Option<HeaderRewrite>>>,
+    request_signers: Arc<Mutex<Vec<RequestSigner>>>,
+    /// The isolated world stealth evaluations run in.
+    world: Arc<Mutex<String>>,
+    /// Marks the messages of the main world bridge.
+    bridge_key: Arc<str>,
+    bridge_installed: Arc<Mutex<bool>>,
+    focus_check: Arc<Mutex<bool>>,
+    /// Verdicts collected on this page, kept while the orchestrator's
+    /// rotation is listening.
+    verdicts: Arc<Mutex<Option<Vec<NavigationVerdict>>>>,
+    obfuscation: Arc<Mutex<Option<Obfuscation>>>,
+    /// The stealth policy and the bootstrap script it was compiled into.
+    policy: Arc<Mutex<(StealthPolicy, Option<ScriptIdentifier>)>>,
+    /// When a navigation, script or humanized input last ran.
+    last_active: Arc<Mutex<tokio::time::Instant>>,
+}
+
+/// Releases whatever input is still held when a humanized operation is
+/// dropped halfway, e.g. by `select!` or an aborted task.
+struct ReleaseOnDrop<'a>(&'a ChaserPage);
+
+impl Drop for ReleaseOnDrop<'_> {
+    fn drop(&mut self) {
+        if !self.0.holds_input() {
+            return;
+        }
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let page = self.0.clone();
+            runtime.spawn(async move {
+                if let Err(e) = page.release_input().await {
+                    tracing::warn!("failed to release held input: {}", e);
+                }
+            });
+        }
+    }
 }
 
 impl ChaserPage {
@@ -57,22 +260,47 @@ impl ChaserPage {
     pub fn new(page: Page) -> Self {
         Self {
             page,
-            mouse_pos: Arc::new(Mutex::new(Point { x: 0.0, y: 0.0 })),
+            mouse: Arc::new(Mutex::new(MouseState {
+                pos: Point { x: 0.0, y: 0.0 },
+                viewport: None,
+                placed: false,
+                buttons: 0,
+                touch: false,
+            })),
+            reaction: Arc::new(Mutex::new(ReactionModel::new())),
+            behavior: Arc::new(Mutex::new(None)),
+            timeouts: Arc::new(Mutex::new(Timeouts::default())),
+            keyboard: Arc::new(Mutex::new(KeyboardLayout::default())),
+            cancel: Arc::new(Mutex::new(None)),
+            pending_key_up: Arc::new(Mutex::new(None)),
+            window: Arc::new(Mutex::new(None)),
+            errors: Arc::new(Mutex::new(None)),
+            tokens: Arc::new(Mutex::new(None)),
+            header_rewrite: Arc::new(Mutex::new(None)),
+            request_signers: Arc::new(Mutex::new(Vec::new())),
+            world: Arc::new(Mutex::new(utils::random_identifier())),
+            bridge_key: utils::random_identifier().into(),
+            bridge_installed: Arc::new(Mutex::new(false)),
+            focus_check: Arc::new(Mutex::new(true)),
+            verdicts: Arc::new(Mutex::new(None)),
+            obfuscation: Arc::new(Mutex::new(Some(Obfuscation::random()))),
+            policy: Arc::new(Mutex::new((StealthPolicy::default(), None))),
+            last_active: Arc::new(Mutex::new(tokio::time::Instant::now())),
         }
     }
 
     /// Launch a fully configured stealth browser in ONE call.
-    /// 
+    ///
     /// This handles EVERYTHING:
     /// - Launches browser with correct window size
     /// - Sets all stealth args (--disable-blink-features=AutomationControlled, etc.)
     /// - Creates page with profile applied
     /// - Spawns the browser handler
-    /// 
+    ///
     /// Returns (Browser, ChaserPage) so you have full access to both.
-    /// 
+    ///
     /// # Example
-    /// ```rust
+    /// ```ignore
     /// // That's it. One line.
     /// let (browser, chaser) = ChaserPage::launch(Os::Windows).await?;
     /// chaser.goto("https://example.com").await?;
@@ -83,9 +311,9 @@ impl ChaserPage {
     }
 
     /// Launch with a custom profile (if you need to tweak settings).
-    /// 
+    ///
     /// # Example
-    /// ```rust
+    /// ```ignore
     /// let profile = ChaserProfile::windows()
     ///     .chrome_version(130)
     ///     .build();
@@ -102,34 +330,13 @@ impl ChaserPage {
 
     /// Internal launch implementation
     async fn launch_internal(profile: ChaserProfile, headed: bool) -> Result<(Browser, Self)> {
-        // Build browser config with ALL the right settings
-        let mut builder = BrowserConfig::builder()
-            // Use patched Chromium build (chaser-browser)
-            .chrome_executable("/Users/marcxavier/chaser-browser/src/out/chaser-browser/Chromium.app/Contents/MacOS/Chromium")
-            .window_size(profile.screen_width(), profile.screen_height())
-            .args(vec![
-                "--disable-blink-features=AutomationControlled".to_string(),
-                "--disable-infobars".to_string(),
-                format!("--window-size={},{}", profile.screen_width(), profile.screen_height()),
-            ]);
-
-        if headed {
-            builder = builder.with_head();
-        }
-
-        let config = builder.build().map_err(|e| anyhow!("{}", e))?;
-
-        // Launch browser
-        let (browser, mut handler) = Browser::launch(config).await?;
-
-        // Spawn handler (required for browser to work)
-        tokio::spawn(async move {
-            while let Some(_) = handler.next().await {}
-        });
+        // The handler task keeps running after its handle is dropped
+        let (browser, _handler) =
+            crate::launcher::launch_browser(&profile, None, None, None, headed).await?;
 
         // Create page with about:blank first
         let page = browser.new_page("about:blank").await?;
-        
+
         // Wrap and apply profile
         let chaser = Self::new(page);
         chaser.apply_profile(&profile).await?;
@@ -161,19 +368,126 @@ impl ChaserPage {
     /// Navigate to a URL (stealth-safe).
     ///
     /// This is equivalent to `raw_page().goto()` but provided for convenience.
+    /// Network errors are classified, e.g. a proxy refusing the tunnel
+    /// becomes [`ChaserError::ProxyUnreachable`].
     pub async fn goto(&self, url: &str) -> Result<()> {
-        self.page.goto(url).await.map_err(|e| anyhow!("{}", e))?;
+        self.navigate(NavigateParams::new(url)).await
+    }
+
+    /// [`goto`](Self::goto) with full navigation parameters.
+    pub(crate) async fn navigate(&self, params: NavigateParams) -> Result<()> {
+        let url = params.url.clone();
+        let limit = self.timeouts().navigation;
+        self.mark_active();
+        let navigation = tokio::time::timeout(limit, self.page.goto(params));
+        let result = navigation
+            .await
+            .map_err(|_| ChaserError::Timeout(format!("Navigation to {url} ({limit:?})")));
+        self.mark_active();
+        let result = result?;
+        match result {
+            Ok(_) => Ok(()),
+            Err(CdpError::ChromeMessage(reason)) if reason.starts_with("net::") => {
+                Err(ChaserError::navigation(&url, reason))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Navigate to a URL and wait until the new document reaches `until`.
+    ///
+    /// Unlike [`goto`](Self::goto), which waits for whatever Chrome considers
+    /// the end of the navigation, this follows the `Page.lifecycleEvent`s of
+    /// the committed document, so a flow can act on `DOMContentLoaded` or
+    /// wait for the network to go quiet instead of sleeping. Same-document
+    /// navigations (fragments, `history.pushState` targets) return at once.
+    ///
+    /// Once the new document is in, the cursor is re-announced to it with a
+    /// `mouseMoved` at its resting position, as Chrome does for a cursor over
+    /// a freshly laid out page, and the viewport is read again.
+    pub async fn goto_stealth(&self, url: &str, until: WaitUntil) -> Result<()> {
+        let limit = self.timeouts().navigation;
+        let navigation = async {
+            let mut events = self.page.event_listener::<EventLifecycleEvent>().await?;
+            let navigated = self.page.execute(NavigateParams::new(url)).await?.result;
+            if let Some(reason) = navigated.error_text {
+                return Err(ChaserError::navigation(url, reason));
+            }
+            let Some(loader) = navigated.loader_id else {
+                return Ok(());
+            };
+            if until != WaitUntil::Commit {
+                while let Some(event) = events.next().await {
+                    if event.frame_id == navigated.frame_id
+                        && event.loader_id == loader
+                        && until.is_reached_by(&event.name)
+                    {
+                        break;
+                    }
+                }
+            }
+            self.rearm_mouse().await
+        };
+        self.within(limit, &format!("Navigation to {url}"), navigation)
+            .await
+    }
+
+    /// Bring the cursor state in line with a newly committed document.
+    async fn rearm_mouse(&self) -> Result<()> {
+        let (placed, pos) = {
+            let mut mouse = self.mouse.lock().unwrap();
+            // the new document may lay out to another size (scrollbars)
+            mouse.viewport = None;
+            (mouse.placed, mouse.pos)
+        };
+        if !placed {
+            return Ok(());
+        }
+        // a document that is still loading may not have a body to hover yet
+        let viewport = self
+            .evaluate_stealth("[window.innerWidth, window.innerHeight]")
+            .await
+            .ok()
+            .flatten()
+            .and_then(|v| serde_json::from_value::<(f64, f64)>(v).ok());
+        match viewport {
+            Some((width, height)) => self.set_viewport_size(width, height).await?,
+            None => return Ok(()),
+        }
+        if self.mouse.lock().unwrap().pos == pos {
+            self.dispatch_mouse_move(pos).await?;
+        }
         Ok(())
     }
 
     /// Get the page HTML content (stealth-safe).
     pub async fn content(&self) -> Result<String> {
-        self.page.content().await.map_err(|e| anyhow!("{}", e))
+        Ok(self.page.content().await?)
     }
 
     /// Get the current page URL (stealth-safe).
     pub async fn url(&self) -> Result<Option<String>> {
-        self.page.url().await.map_err(|e| anyhow!("{}", e))
+        Ok(self.page.url().await?)
+    }
+
+    /// Check the current page for anti-bot interstitials and captchas.
+    ///
+    /// Returns [`ChaserError::BlockedByAntiBot`] for block and challenge pages
+    /// of Cloudflare, Akamai and Imperva, and [`ChaserError::CaptchaRequired`]
+    /// when a reCAPTCHA, hCaptcha, Turnstile, DataDome or PerimeterX captcha
+    /// is showing.
+    pub async fn check_blocked(&self) -> Result<()> {
+        let verdict = self
+            .evaluate_stealth(BLOCK_CHECK_SCRIPT)
+            .await?
+            .and_then(|v| serde_json::from_value::<(String, String)>(v).ok());
+        match verdict {
+            Some((kind, name)) if kind == "block" => {
+                Err(ChaserError::BlockedByAntiBot { vendor: name })
+            }
+            Some((_, name)) => Err(ChaserError::CaptchaRequired { provider: name }),
+            None => Ok(()),
+        }
     }
 
     /// Execute JavaScript using **stealth execution** (no Runtime.enable leak).
@@ -183,7 +497,7 @@ impl ChaserPage {
     ///
     /// # Example
     ///
-    /// ```rust
+    /// ```ignore
     /// // Get page title
     /// let title: String = chaser.evaluate("document.title").await?;
     ///
@@ -197,14 +511,17 @@ impl ChaserPage {
     /// Apply a ChaserProfile to this page in one clean call.
     ///
     /// This method:
-    /// 1. Sets viewport dimensions and DPR via CDP (Emulation.setDeviceMetricsOverride)
-    /// 2. Sets the User-Agent HTTP header
-    /// 3. Injects the profile's bootstrap script for JS-level spoofing
+    /// 1. Sets the viewport (headless only), screen and DPR from the profile's
+    ///    [`Geometry`](crate::geometry::Geometry) via CDP
+    ///    (Emulation.setDeviceMetricsOverride)
+    /// 2. Emulates the profile's media features (Emulation.setEmulatedMedia)
+    /// 3. Sets the User-Agent HTTP header
+    /// 4. Injects the profile's bootstrap script for JS-level spoofing
     ///
     /// **IMPORTANT:** Call this BEFORE navigating to the target site.
     ///
     /// # Example
-    /// ```rust
+    /// ```ignore
     /// let profile = ChaserProfile::windows().build();
     /// let page = browser.new_page("about:blank").await?;
     /// let chaser = ChaserPage::new(page);
@@ -213,43 +530,597 @@ impl ChaserPage {
     /// ```
     pub async fn apply_profile(&self, profile: &ChaserProfile) -> Result<()> {
         // 1. Set viewport and DPR via CDP - this ensures innerWidth/Height and
-        // devicePixelRatio match what we spoof in JS
-        self.page
-            .execute(
-                SetDeviceMetricsOverrideParams::builder()
-                    .width(profile.screen_width() as i64)
-                    .height(profile.screen_height() as i64)
-                    .device_scale_factor(profile.device_pixel_ratio() as f64)
-                    .mobile(false)
-                    .build()
-                    .map_err(|e| anyhow!("Failed to build device metrics: {}", e))?,
-            )
-            .await
-            .map_err(|e| anyhow!("Failed to set device metrics: {}", e))?;
+        // devicePixelRatio match the window and screen we spoof in JS
+        let geometry = Geometry::resolve(profile);
+        let headless = self.is_headless().await;
+        self.page.execute(geometry.device_metrics(headless)).await?;
+
+        // Phones have touch screens and motion sensors that are never still
+        if profile.os().is_mobile() {
+            self.page
+                .execute(
+                    SetTouchEmulationEnabledParams::builder()
+                        .enabled(true)
+                        .max_touch_points(5)
+                        .build()
+                        .map_err(ChaserError::msg)?,
+                )
+                .await?;
+            crate::sensors::SensorEmulation::default()
+                .start(self)
+                .await?;
+        }
 
-        // 2. Set the HTTP User-Agent header
+        // 2. Emulate the profile's media features (color scheme, gamut, ...)
+        let features = profile
+            .media()
+            .css_features()
+            .into_iter()
+            .map(|(name, value)| MediaFeature::new(name, value))
+            .collect();
         self.page
-            .set_user_agent(&profile.user_agent())
-            .await
-            .map_err(|e| anyhow!("{}", e))?;
+            .execute(SetEmulatedMediaParams {
+                media: None,
+                features: Some(features),
+            })
+            .await?;
 
-        // 3. Inject the unified stealth script (single source of truth in profiles.rs)
+        // 3. Set the HTTP User-Agent, client hints and Accept-Language
+        // headers; they also drive navigator.userAgentData and
+        // navigator.language(s), in workers too
+        self.page
+            .execute(SetUserAgentOverrideParams {
+                user_agent: profile.user_agent(),
+                accept_language: Some(profile.accept_language()),
+                platform: None,
+                user_agent_metadata: profile.user_agent_metadata(),
+            })
+            .await?;
+        // Intl formats dates and numbers for the profile's locale
+        self.page
+            .emulate_locale(
+                SetLocaleOverrideParams::builder()
+                    .locale(profile.locale())
+                    .build(),
+            )
+            .await?;
+        // The clock and position must agree with the locale
         self.page
+            .execute(SetTimezoneOverrideParams::new(profile.timezone()))
+            .await?;
+        if let Some(geo) = profile.geolocation() {
+            self.page
+                .execute(
+                    SetGeolocationOverrideParams::builder()
+                        .latitude(geo.latitude)
+                        .longitude(geo.longitude)
+                        .accuracy(geo.accuracy)
+                        .build(),
+                )
+                .await?;
+        }
+        // Headless denies notifications while a fresh profile asks first;
+        // iOS has no notification APIs to ask about
+        if profile.os() != Os::Ios {
+            self.reset_notification_permission().await;
+        }
+        *self.keyboard.lock().unwrap() = profile.keyboard_layout();
+
+        // 4. Inject the unified stealth script (single source of truth in
+        // profiles.rs), with this page's per-origin exceptions
+        let policy = self.stealth_policy();
+        let bootstrap = self
+            .page
             .execute(AddScriptToEvaluateOnNewDocumentParams {
-                source: profile.bootstrap_script(),
+                source: self.bootstrap_source(profile, &policy),
                 world_name: None,
                 include_command_line_api: None,
                 run_immediately: None,
             })
-            .await
-            .map_err(|e| anyhow!("{}", e))?;
+            .await?
+            .result
+            .identifier;
+        self.policy.lock().unwrap().1 = Some(bootstrap);
 
-        // 4. Install main world bridge for evaluate_main() support
+        // 5. Install main world bridge for evaluate_main() support
         self.install_main_world_bridge().await?;
 
+        // 6. Keep the cursor inside the new viewport, using touch on phones
+        self.mouse.lock().unwrap().touch = profile.os().is_mobile();
+        self.set_viewport_size(geometry.inner_width as f64, geometry.inner_height as f64)
+            .await?;
+        *self.window.lock().unwrap() = Some(WindowTracker::new(profile, geometry));
+
+        Ok(())
+    }
+
+    /// The profile last applied with [`apply_profile`](Self::apply_profile).
+    pub fn profile(&self) -> Option<ChaserProfile> {
+        self.window
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|tracker| tracker.profile().clone())
+    }
+
+    pub(crate) fn window_tracker(&self) -> &Arc<Mutex<Option<WindowTracker>>> {
+        &self.window
+    }
+
+    pub(crate) fn error_log(&self) -> &Arc<Mutex<Option<ErrorLog>>> {
+        &self.errors
+    }
+
+    pub(crate) fn token_watch(&self) -> &Arc<Mutex<Option<TokenWatch>>> {
+        &self.tokens
+    }
+
+    pub(crate) fn header_rewrite(&self) -> &Arc<Mutex<Option<HeaderRewrite>>> {
+        &self.header_rewrite
+    }
+
+    pub(crate) fn focus_check_state(&self) -> &Arc<Mutex<bool>> {
+        &self.focus_check
+    }
+
+    pub(crate) fn verdict_log(&self) -> &Arc<Mutex<Option<Vec<NavigationVerdict>>>> {
+        &self.verdicts
+    }
+
+    pub(crate) fn obfuscation(&self) -> &Arc<Mutex<Option<Obfuscation>>> {
+        &self.obfuscation
+    }
+
+    pub(crate) fn request_signers(&self) -> &Arc<Mutex<Vec<RequestSigner>>> {
+        &self.request_signers
+    }
+
+    pub(crate) fn policy_state(&self) -> &Arc<Mutex<(StealthPolicy, Option<ScriptIdentifier>)>> {
+        &self.policy
+    }
+
+    /// Put the notification permission of this page's browser context back
+    /// to `prompt`, so `Notification.permission` reads `default` and
+    /// `permissions.query` agrees without patching either. Best effort: a
+    /// browser refusing the override keeps its own state.
+    async fn reset_notification_permission(&self) {
+        let context = self
+            .page
+            .execute(
+                GetTargetInfoParams::builder()
+                    .target_id(self.page.target_id().clone())
+                    .build(),
+            )
+            .await
+            .ok()
+            .and_then(|info| info.result.target_info.browser_context_id);
+        let mut params = SetPermissionParams::new(
+            PermissionDescriptor::new("notifications"),
+            PermissionSetting::Prompt,
+        );
+        params.browser_context_id = context;
+        if let Err(e) = self.page.execute(params).await {
+            tracing::debug!("could not reset the notification permission: {}", e);
+        }
+    }
+
+    /// Whether the browser runs headless, which has no browser UI around
+    /// the viewport. Assumes headless if the browser does not say.
+    pub(crate) async fn is_headless(&self) -> bool {
+        self.page
+            .execute(GetVersionParams::default())
+            .await
+            .map(|version| version.result.product.starts_with("HeadlessChrome"))
+            .unwrap_or(true)
+    }
+
+    // ========== MOUSE POSITION ==========
+
+    /// The position of the simulated cursor in viewport coordinates.
+    ///
+    /// Updated by every mouse event this page dispatches (`move_mouse_human`,
+    /// `click`, ...). Until the cursor is first placed this is `(0, 0)`.
+    pub fn current_mouse_position(&self) -> Point {
+        self.mouse.lock().unwrap().pos
+    }
+
+    /// Tell the page its viewport changed to `width` x `height` CSS pixels.
+    ///
+    /// A cursor that was never placed is parked at a plausible resting spot;
+    /// one that would now lie outside the viewport is moved back inside with
+    /// a real `mouseMoved` event. Called by `apply_profile()`.
+    pub async fn set_viewport_size(&self, width: f64, height: f64) -> Result<()> {
+        let target = {
+            let mut mouse = self.mouse.lock().unwrap();
+            mouse.viewport = Some((width, height));
+            if mouse.placed {
+                let clamped = mouse.clamp(mouse.pos);
+                (clamped.x != mouse.pos.x || clamped.y != mouse.pos.y).then_some(clamped)
+            } else {
+                Some(resting_point(width, height))
+            }
+        };
+        if let Some(target) = target {
+            self.dispatch_mouse_move(target).await?;
+        }
+        Ok(())
+    }
+
+    /// The viewport size last seen by the cursor, if any.
+    pub(crate) fn known_viewport(&self) -> Option<(f64, f64)> {
+        self.mouse.lock().unwrap().viewport
+    }
+
+    /// Place the cursor somewhere plausible if it was never placed, so paths
+    /// do not start from the top-left corner.
+    async fn ensure_mouse_placed(&self) -> Result<()> {
+        if self.mouse.lock().unwrap().placed {
+            return Ok(());
+        }
+        let (width, height) = self
+            .evaluate_stealth("[window.innerWidth, window.innerHeight]")
+            .await
+            .ok()
+            .flatten()
+            .and_then(|v| serde_json::from_value::<(f64, f64)>(v).ok())
+            .unwrap_or((1280.0, 720.0));
+        self.set_viewport_size(width, height).await
+    }
+
+    /// Replace the model that decides how long to wait before humanized
+    /// clicks, typing and key presses.
+    pub fn set_reaction_model(&self, model: ReactionModel) {
+        *self.reaction.lock().unwrap() = model;
+    }
+
+    /// Type on `layout` from now on; [`apply_profile`](Self::apply_profile)
+    /// sets the profile's layout.
+    pub fn set_keyboard_layout(&self, layout: KeyboardLayout) {
+        *self.keyboard.lock().unwrap() = layout;
+    }
+
+    /// Replace the time limits of this page (and its clones).
+    pub fn set_timeouts(&self, timeouts: Timeouts) {
+        *self.timeouts.lock().unwrap() = timeouts;
+    }
+
+    pub fn timeouts(&self) -> Timeouts {
+        *self.timeouts.lock().unwrap()
+    }
+
+    /// The name of the isolated world [`evaluate_stealth`](Self::evaluate_stealth)
+    /// runs in: random per page unless set with
+    /// [`set_world_name`](Self::set_world_name).
+    pub fn world_name(&self) -> String {
+        self.world.lock().unwrap().clone()
+    }
+
+    /// Run stealth evaluations in the isolated world `name` from now on,
+    /// e.g. to use one name for all pages of a session. Globals and
+    /// listeners set up in the previous world, such as those of
+    /// [`capture_page_errors`](Self::capture_page_errors), stay there.
+    pub fn set_world_name(&self, name: impl Into<String>) {
+        *self.world.lock().unwrap() = name.into();
+    }
+
+    /// Run `operation`, failing with [`ChaserError::Timeout`] after `limit`.
+    pub(crate) async fn within<T>(
+        &self,
+        limit: Duration,
+        what: &str,
+        operation: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        self.mark_active();
+        let result = tokio::time::timeout(limit, operation).await;
+        self.mark_active();
+        result.map_err(|_| ChaserError::Timeout(format!("{what} ({limit:?})")))?
+    }
+
+    fn mark_active(&self) {
+        *self.last_active.lock().unwrap() = tokio::time::Instant::now();
+    }
+
+    /// How long ago a navigation, script or humanized input of this page
+    /// (or a clone) last ran. Commands sent through `raw_page()` do not
+    /// count.
+    pub fn idle_for(&self) -> Duration {
+        self.last_active.lock().unwrap().elapsed()
+    }
+
+    /// Let `token` abort humanized operations of this page (and its clones).
+    ///
+    /// Once it is cancelled, mouse paths, clicks, drags, typing, scrolling
+    /// and dwelling stop at their next step, release any held button or key
+    /// and fail with [`ChaserError::Cancelled`]. `None` detaches the token.
+    pub fn set_cancellation_token(&self, token: Option<CancellationToken>) {
+        *self.cancel.lock().unwrap() = token;
+    }
+
+    pub fn cancellation_token(&self) -> Option<CancellationToken> {
+        self.cancel.lock().unwrap().clone()
+    }
+
+    /// Sleep for `duration` between the steps of a humanized operation.
+    ///
+    /// Fails with [`ChaserError::Cancelled`] as soon as the cancellation
+    /// token fires, after releasing anything still held.
+    pub(crate) async fn pause(&self, duration: Duration) -> Result<()> {
+        self.mark_active();
+        let Some(token) = self.cancellation_token() else {
+            clock::sleep(duration).await;
+            return Ok(());
+        };
+        if !token.is_cancelled() {
+            tokio::select! {
+                _ = clock::sleep(duration) => return Ok(()),
+                _ = token.cancelled() => {}
+            }
+        }
+        // the operation is abandoned either way; a failed release must not
+        // hide why
+        let _ = self.release_input().await;
+        Err(ChaserError::Cancelled)
+    }
+
+    fn holds_input(&self) -> bool {
+        self.mouse.lock().unwrap().buttons != 0 || self.pending_key_up.lock().unwrap().is_some()
+    }
+
+    /// Release any mouse button, finger or key that is still held down.
+    ///
+    /// Humanized operations do this themselves when they are cancelled or
+    /// dropped; call it directly after aborting a flow by other means.
+    pub async fn release_input(&self) -> Result<()> {
+        let key_up = self.pending_key_up.lock().unwrap().take();
+        if let Some(key_up) = key_up {
+            self.input(key_up).await?;
+        }
+        let (pos, buttons, touch) = {
+            let mut mouse = self.mouse.lock().unwrap();
+            (mouse.pos, std::mem::take(&mut mouse.buttons), mouse.touch)
+        };
+        if buttons == 0 {
+            return Ok(());
+        }
+        if touch {
+            self.input(
+                DispatchTouchEventParams::builder()
+                    .r#type(DispatchTouchEventType::TouchEnd)
+                    .touch_points(Vec::<TouchPoint>::new())
+                    .build()
+                    .map_err(ChaserError::msg)?,
+            )
+            .await?;
+        } else {
+            self.input(
+                DispatchMouseEventParams::builder()
+                    .r#type(DispatchMouseEventType::MouseReleased)
+                    .x(pos.x)
+                    .y(pos.y)
+                    .button(MouseButton::Left)
+                    .buttons(0)
+                    .click_count(1)
+                    .pointer_type(DispatchMouseEventPointerType::Mouse)
+                    .build()
+                    .map_err(ChaserError::msg)?,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Press and release a key, remembering the key-up until it is sent.
+    pub(crate) async fn key_stroke(
+        &self,
+        key_down: DispatchKeyEventParams,
+        key_up: DispatchKeyEventParams,
+    ) -> Result<()> {
+        let _release = ReleaseOnDrop(self);
+        *self.pending_key_up.lock().unwrap() = Some(key_up.clone());
+        self.input(key_down).await?;
+        self.note(InputEvent::Key);
+        self.input(key_up).await?;
+        self.pending_key_up.lock().unwrap().take();
+        Ok(())
+    }
+
+    /// Press a key while a modifier is held, e.g. Ctrl+A. The modifier is
+    /// released even if this is dropped halfway.
+    pub(crate) async fn key_chord(
+        &self,
+        modifier: (DispatchKeyEventParams, DispatchKeyEventParams),
+        key: (DispatchKeyEventParams, DispatchKeyEventParams),
+    ) -> Result<()> {
+        let (modifier_down, modifier_up) = modifier;
+        let (key_down, key_up) = key;
+        let (lead, hold, trail) = {
+            let mut rng = rand::thread_rng();
+            (
+                rng.gen_range(40..110),
+                rng.gen_range(50..100),
+                rng.gen_range(20..70),
+            )
+        };
+        let _release = ReleaseOnDrop(self);
+        *self.pending_key_up.lock().unwrap() = Some(modifier_up.clone());
+        self.input(modifier_down).await?;
+        self.pause(Duration::from_millis(lead)).await?;
+        self.input(key_down).await?;
+        self.note(InputEvent::Key);
+        self.pause(Duration::from_millis(hold)).await?;
+        self.input(key_up).await?;
+        self.pause(Duration::from_millis(trail)).await?;
+        self.input(modifier_up).await?;
+        self.pending_key_up.lock().unwrap().take();
+        Ok(())
+    }
+
+    /// Dispatch an input command within the action time limit.
+    pub(crate) async fn input<C: Command>(&self, cmd: C) -> Result<CommandResponse<C::Response>> {
+        let limit = self.timeouts().action;
+        self.within(limit, "Input dispatch", async {
+            Ok(self.page.execute(cmd).await?)
+        })
+        .await
+    }
+
+    /// Start recording the input this page (and its clones) dispatches,
+    /// discarding any earlier recording.
+    pub fn record_behavior(&self) {
+        *self.behavior.lock().unwrap() = Some(BehaviorStats::new());
+    }
+
+    /// A copy of the input recorded since [`record_behavior`](Self::record_behavior).
+    pub fn behavior_stats(&self) -> Option<BehaviorStats> {
+        self.behavior.lock().unwrap().clone()
+    }
+
+    /// Stop recording and return what was recorded.
+    pub fn stop_recording_behavior(&self) -> Option<BehaviorStats> {
+        self.behavior.lock().unwrap().take()
+    }
+
+    fn note(&self, event: InputEvent) {
+        if let Some(stats) = self.behavior.lock().unwrap().as_mut() {
+            stats.record(event);
+        }
+    }
+
+    /// Wait as long as a person would before `interaction` on `target`.
+    async fn react(&self, interaction: Interaction, target: Option<Point>) -> Result<()> {
+        let started = clock::now();
+        let page = self
+            .evaluate_stealth(PAGE_CONTEXT_SCRIPT)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|v| serde_json::from_value::<(f64, Option<f64>)>(v).ok())
+            .map(|(since_load, since_shift)| PageContext {
+                since_load: Some(Duration::from_secs_f64(since_load.max(0.0) / 1000.0)),
+                since_layout_shift: since_shift
+                    .map(|ms| Duration::from_secs_f64(ms.max(0.0) / 1000.0)),
+            })
+            .unwrap_or_default();
+        let delay = self
+            .reaction
+            .lock()
+            .unwrap()
+            .delay(interaction, target, &page);
+        // the probe itself took part of the reaction time
+        self.pause(delay.saturating_sub(clock::elapsed(started)))
+            .await
+    }
+
+    pub(crate) async fn dispatch_mouse_move(&self, point: Point) -> Result<()> {
+        let mut moves = InputPipeline::new(self);
+        self.queue_mouse_move(&mut moves, point).await?;
+        moves.finish().await
+    }
+
+    /// Move the cursor to `point` through `moves`, without waiting for the
+    /// browser to confirm the event.
+    pub(crate) async fn queue_mouse_move(
+        &self,
+        moves: &mut InputPipeline,
+        point: Point,
+    ) -> Result<()> {
+        let (point, buttons, touch) = {
+            let mouse = self.mouse.lock().unwrap();
+            (mouse.clamp(point), mouse.buttons, mouse.touch)
+        };
+        // a finger does not hover, it only touches down where it taps
+        if !touch {
+            let button = if buttons & 1 != 0 {
+                MouseButton::Left
+            } else {
+                MouseButton::None
+            };
+            moves
+                .send(
+                    DispatchMouseEventParams::builder()
+                        .r#type(DispatchMouseEventType::MouseMoved)
+                        .x(point.x)
+                        .y(point.y)
+                        .button(button)
+                        .buttons(buttons)
+                        .pointer_type(DispatchMouseEventPointerType::Mouse)
+                        .build()
+                        .map_err(ChaserError::msg)?,
+                )
+                .await?;
+            self.note(InputEvent::Move(point));
+        }
+        let mut mouse = self.mouse.lock().unwrap();
+        mouse.pos = point;
+        mouse.placed = true;
+        Ok(())
+    }
+
+    /// Press or release the left button at the current position.
+    async fn dispatch_button(&self, pressed: bool) -> Result<()> {
+        self.dispatch_button_count(pressed, 1).await
+    }
+
+    /// Press or release the left button as the `click_count`th click of a
+    /// multi-click.
+    async fn dispatch_button_count(&self, pressed: bool, click_count: i64) -> Result<()> {
+        let pos = self.current_mouse_position();
+        let (kind, buttons) = if pressed {
+            (DispatchMouseEventType::MousePressed, 1)
+        } else {
+            (DispatchMouseEventType::MouseReleased, 0)
+        };
+        self.input(
+            DispatchMouseEventParams::builder()
+                .r#type(kind)
+                .x(pos.x)
+                .y(pos.y)
+                .button(MouseButton::Left)
+                .buttons(buttons)
+                .click_count(click_count)
+                .pointer_type(DispatchMouseEventPointerType::Mouse)
+                .build()
+                .map_err(ChaserError::msg)?,
+        )
+        .await?;
+        self.mouse.lock().unwrap().buttons = buttons;
+        if pressed {
+            self.note(InputEvent::Press);
+        }
         Ok(())
     }
 
+    /// Put a finger down at the current position and lift it after `hold_ms`.
+    async fn tap(&self, hold_ms: u64) -> Result<()> {
+        let _release = ReleaseOnDrop(self);
+        let pos = self.current_mouse_position();
+        let (radius, force) = {
+            let mut rng = rand::thread_rng();
+            (rng.gen_range(8.0..14.0), rng.gen_range(0.3..0.7))
+        };
+        let finger = TouchPoint::builder()
+            .x(pos.x)
+            .y(pos.y)
+            .radius_x(radius)
+            .radius_y(radius * 1.1)
+            .force(force)
+            .id(0.0)
+            .build()
+            .map_err(ChaserError::msg)?;
+        self.input(
+            DispatchTouchEventParams::builder()
+                .r#type(DispatchTouchEventType::TouchStart)
+                .touch_point(finger)
+                .build()
+                .map_err(ChaserError::msg)?,
+        )
+        .await?;
+        self.mouse.lock().unwrap().buttons = 1;
+        self.note(InputEvent::Press);
+        self.pause(Duration::from_millis(hold_ms)).await?;
+        self.release_input().await
+    }
+
     // ========== REQUEST INTERCEPTION API ==========
 
     /// Enable request interception for specific URL patterns.
@@ -263,7 +1134,7 @@ impl ChaserPage {
     /// * `resource_type` - Optional resource type filter (Document, Script, etc.)
     ///
     /// # Example
-    /// ```rust
+    /// ```ignore
     /// // Intercept all document requests
     /// chaser.enable_request_interception("*", Some(ResourceType::Document)).await?;
     /// ```
@@ -284,18 +1155,14 @@ impl ChaserPage {
                     .pattern(pattern_builder.build())
                     .build(),
             )
-            .await
-            .map_err(|e| anyhow!("{}", e))?;
+            .await?;
 
         Ok(())
     }
 
     /// Disable request interception.
     pub async fn disable_request_interception(&self) -> Result<()> {
-        self.page
-            .execute(FetchDisableParams::default())
-            .await
-            .map_err(|e| anyhow!("{}", e))?;
+        self.page.execute(FetchDisableParams::default()).await?;
         Ok(())
     }
 
@@ -310,7 +1177,7 @@ impl ChaserPage {
     /// * `status_code` - HTTP status code (usually 200)
     ///
     /// # Example
-    /// ```rust
+    /// ```ignore
     /// let fake_html = r#"
     ///     <!DOCTYPE html>
     ///     <html>
@@ -345,10 +1212,9 @@ impl ChaserPage {
                         value: "text/html; charset=utf-8".to_string(),
                     })
                     .build()
-                    .map_err(|e| anyhow!("{}", e))?,
+                    .map_err(ChaserError::msg)?,
             )
-            .await
-            .map_err(|e| anyhow!("{}", e))?;
+            .await?;
 
         Ok(())
     }
@@ -364,10 +1230,9 @@ impl ChaserPage {
                 ContinueRequestParams::builder()
                     .request_id(RequestId::from(request_id.into()))
                     .build()
-                    .map_err(|e| anyhow!("{}", e))?,
+                    .map_err(ChaserError::msg)?,
             )
-            .await
-            .map_err(|e| anyhow!("{}", e))?;
+            .await?;
 
         Ok(())
     }
@@ -381,31 +1246,74 @@ impl ChaserPage {
     ///
     /// Site scripts cannot see your variables (isolated world).
     /// Anti-bots cannot detect CDP activity (Runtime domain untouched).
+    ///
+    /// Fails with [`ChaserError::Timeout`] if the script (or the promise it
+    /// returns) takes longer than [`Timeouts::evaluate`].
     pub async fn evaluate_stealth(&self, script: &str) -> Result<Option<Value>> {
-        // Get the main frame ID
-        let frame_id = self
-            .page
-            .mainframe()
+        let limit = self.timeouts().evaluate;
+        self.within(limit, "Script evaluation", self.evaluate_isolated(script))
             .await
-            .map_err(|e| anyhow!("{}", e))?
-            .ok_or_else(|| anyhow!("No main frame available"))?;
+    }
 
-        // Create an isolated world - Chrome returns the Context ID in the response!
-        // This is the key insight: we get a context ID without touching Runtime domain
-        let isolated_world = self
-            .page
-            .execute(
-                CreateIsolatedWorldParams::builder()
-                    .frame_id(frame_id)
-                    .world_name("chaser") // Our stealth world
-                    .grant_univeral_access(true) // Access to page DOM
-                    .build()
-                    .unwrap(),
-            )
+    /// Evaluate `script` in `world`: [`evaluate_stealth`](Self::evaluate_stealth)
+    /// or [`evaluate_main`](Self::evaluate_main).
+    ///
+    /// # Example
+    /// ```ignore
+    /// // The site's own function, with its side effects
+    /// chaser.evaluate_in(ExecutionWorld::Main, "window.app.loadMore()").await?;
+    /// ```
+    pub async fn evaluate_in(&self, world: ExecutionWorld, script: &str) -> Result<Option<Value>> {
+        match world {
+            ExecutionWorld::Isolated => self.evaluate_stealth(script).await,
+            ExecutionWorld::Main => self.evaluate_main(script).await,
+        }
+    }
+
+    /// Poll `predicate` in the isolated world until it returns a truthy
+    /// value, and return that value.
+    ///
+    /// Gives up with [`ChaserError::Timeout`] after [`Timeouts::wait`].
+    pub async fn wait_for_function(&self, predicate: &str) -> Result<Value> {
+        self.wait_for_function_in(ExecutionWorld::Isolated, predicate)
             .await
-            .map_err(|e| anyhow!("{}", e))?;
+    }
+
+    /// [`wait_for_function`](Self::wait_for_function) in `world`, e.g. to
+    /// wait for a global the page's scripts set.
+    pub async fn wait_for_function_in(
+        &self,
+        world: ExecutionWorld,
+        predicate: &str,
+    ) -> Result<Value> {
+        let limit = self.timeouts().wait;
+        let poll = async {
+            loop {
+                let value = match world {
+                    ExecutionWorld::Isolated => self.evaluate_isolated(predicate).await,
+                    ExecutionWorld::Main => self.evaluate_main(predicate).await,
+                };
+                // the isolated world is gone while a navigation commits
+                if let Ok(Some(value)) = value {
+                    let truthy = match &value {
+                        Value::Null => false,
+                        Value::Bool(b) => *b,
+                        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+                        Value::String(s) => !s.is_empty(),
+                        _ => true,
+                    };
+                    if truthy {
+                        return Ok(value);
+                    }
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        };
+        self.within(limit, "Waiting for function", poll).await
+    }
 
-        let ctx_id = isolated_world.result.execution_context_id;
+    async fn evaluate_isolated(&self, script: &str) -> Result<Option<Value>> {
+        let ctx_id = self.isolated_context().await?;
 
         // Execute in the isolated world using the captured context ID
         let params = EvaluateParams::builder()
@@ -416,14 +1324,35 @@ impl ChaserPage {
             .build()
             .unwrap();
 
-        let res = self
-            .page
-            .execute(params)
-            .await
-            .map_err(|e| anyhow!("{}", e))?;
+        let res = self.page.execute(params).await?;
+        if let Some(details) = res.result.exception_details.clone() {
+            return Err(CdpError::JavascriptException(Box::new(details)).into());
+        }
         Ok(res.result.result.value)
     }
 
+    /// The execution context of the main frame's isolated world.
+    pub(crate) async fn isolated_context(&self) -> Result<ExecutionContextId> {
+        // Get the main frame ID
+        let frame_id = self.page.mainframe().await?.ok_or(ChaserError::Detached)?;
+
+        // Create an isolated world - Chrome returns the Context ID in the response!
+        // This is the key insight: we get a context ID without touching Runtime domain
+        let isolated_world = self
+            .page
+            .execute(
+                CreateIsolatedWorldParams::builder()
+                    .frame_id(frame_id)
+                    .world_name(self.world_name())
+                    .grant_univeral_access(true) // Access to page DOM
+                    .build()
+                    .unwrap(),
+            )
+            .await?;
+
+        Ok(isolated_world.result.execution_context_id)
+    }
+
     /// Execute JavaScript in the **main world** (not isolated).
     ///
     /// Use this when you need to access main context objects like:
@@ -434,10 +1363,12 @@ impl ChaserPage {
     /// **WARNING**: Code executed here CAN be detected by the page via MutationObserver
     /// or other techniques. Use `evaluate()` (isolated world) for most operations.
     ///
-    /// This uses the postMessage bridge pattern from rebrowser-patches.
+    /// This uses the postMessage bridge pattern from rebrowser-patches,
+    /// installed on first use, and never touches the `Runtime` domain. A
+    /// returned promise is awaited.
     ///
     /// # Example
-    /// ```rust
+    /// ```ignore
     /// // Access Turnstile token from main world
     /// let token = chaser.evaluate_main("window.turnstileToken").await?;
     ///
@@ -445,40 +1376,43 @@ impl ChaserPage {
     /// let ready = chaser.evaluate_main("typeof window.grecaptcha?.execute === 'function'").await?;
     /// ```
     pub async fn evaluate_main(&self, script: &str) -> Result<Option<Value>> {
+        self.install_main_world_bridge().await?;
+
         // Generate unique ID for this call
         let call_id = uuid::Uuid::new_v4().to_string();
-        
+
         // The bridge script sends message to main world and waits for response
-        let bridge_script = format!(r#"
+        let bridge_script = format!(
+            r#"
             new Promise((resolve, reject) => {{
+                const key = '{key}';
                 const callId = '{call_id}';
-                
+
                 // Listen for response from main world
                 const handler = (event) => {{
-                    if (event.data && event.data._chaserCallId === callId && event.data._chaserFromMain) {{
+                    const reply = event.data && event.data[key];
+                    if (reply && reply.reply && reply.id === callId) {{
                         window.removeEventListener('message', handler);
-                        if (event.data._chaserError) {{
-                            reject(new Error(event.data._chaserError));
+                        if (reply.error !== undefined) {{
+                            reject(new Error(reply.error));
                         }} else {{
-                            resolve(event.data._chaserResult);
+                            resolve(reply.result);
                         }}
                     }}
                 }};
                 window.addEventListener('message', handler);
-                
+
                 // Send request to main world
-                window.postMessage({{
-                    _chaserCallId: callId,
-                    _chaserScript: {script_json}
-                }}, '*');
-                
+                window.postMessage({{ [key]: {{ id: callId, script: {script_json} }} }}, '*');
+
                 // Timeout after 10 seconds
                 setTimeout(() => {{
                     window.removeEventListener('message', handler);
                     reject(new Error('Main world evaluation timeout'));
                 }}, 10000);
             }})
-        "#, 
+        "#,
+            key = self.bridge_key,
             call_id = call_id,
             script_json = serde_json::to_string(script).unwrap_or_else(|_| "\"\"".to_string())
         );
@@ -487,46 +1421,54 @@ impl ChaserPage {
     }
 
     /// Install the main world bridge.
-    /// 
+    ///
     /// This is automatically called by `apply_profile()`, but you can call it
     /// manually if you need main world access without a full profile.
     ///
     /// The bridge listens for postMessage from isolated world and executes
-    /// code in the main world, sending results back.
+    /// code in the main world, sending results back once a returned promise
+    /// settles. Its messages are keyed per `ChaserPage`, so only this page
+    /// and its clones can use it. Installing it again does nothing.
     pub async fn install_main_world_bridge(&self) -> Result<()> {
-        let bridge_script = r#"
-            // Chaser main world bridge - receives messages from isolated world
-            window.addEventListener('message', (event) => {
-                if (!event.data || !event.data._chaserCallId || event.data._chaserFromMain) {
-                    return; // Ignore irrelevant messages or our own responses
-                }
-                
-                const response = {
-                    _chaserCallId: event.data._chaserCallId,
-                    _chaserFromMain: true
-                };
-                
-                try {
-                    // Execute the script in main world
-                    response._chaserResult = eval(event.data._chaserScript);
-                } catch (err) {
-                    response._chaserError = err.message || String(err);
-                }
-                
-                // Send response back (will be received by isolated world)
-                window.postMessage(JSON.parse(JSON.stringify(response)), '*');
-            });
-        "#;
+        // Messages carry this page's random key, not a name shared by
+        // every user of the crate
+        let bridge_script = format!(
+            r#"
+            window.addEventListener('message', (event) => {{
+                const call = event.data && event.data['{key}'];
+                if (!call || call.reply) {{
+                    return;
+                }}
+                const send = reply => window.postMessage({{
+                    '{key}': JSON.parse(JSON.stringify({{ id: call.id, reply: true, ...reply }})),
+                }}, '*');
+                // a result that cannot be cloned is reported as an error
+                new Promise(resolve => resolve(eval(call.script)))
+                    .then(result => send({{ result }}))
+                    .catch(err => send({{ error: (err && err.message) || String(err) }}));
+            }});
+        "#,
+            key = self.bridge_key
+        );
 
-        self.page
+        if std::mem::replace(&mut *self.bridge_installed.lock().unwrap(), true) {
+            return Ok(());
+        }
+        // also run in the current document, so the bridge works before the
+        // next navigation
+        let installed = self
+            .page
             .execute(AddScriptToEvaluateOnNewDocumentParams {
-                source: bridge_script.to_string(),
+                source: bridge_script,
                 world_name: None,
                 include_command_line_api: None,
-                run_immediately: None,
+                run_immediately: Some(true),
             })
-            .await
-            .map_err(|e| anyhow!("{}", e))?;
+            .await;
+        if installed.is_err() {
+            *self.bridge_installed.lock().unwrap() = false;
+        }
+        installed?;
 
         Ok(())
     }
@@ -537,16 +1479,21 @@ impl ChaserPage {
     /// - Randomized control points for natural arcs
     /// - 20% chance of slight overshoot
     /// - Target jitter (±2px)
-    /// - Variable delays between movements (5-15ms)
+    /// - Variable delays between movements (5-15ms), with the occasional
+    ///   burst of moves inside one frame that pages see as coalesced events
+    ///
+    /// Points that round to the previous pixel are skipped, so every move
+    /// carries a non-zero `movementX`/`movementY`.
     pub async fn move_mouse_human(&self, x: f64, y: f64) -> Result<()> {
-        let start = { *self.mouse_pos.lock().unwrap() };
+        self.ensure_mouse_placed().await?;
+        let start = self.current_mouse_position();
         let end = Point { x, y };
 
-        let mut rng = rand::thread_rng();
-
         // Target Selection Jitter: don't land exactly on the pixel
-        let jitter_x = rng.gen_range(-2.0..2.0);
-        let jitter_y = rng.gen_range(-2.0..2.0);
+        let (jitter_x, jitter_y) = {
+            let mut rng = rand::thread_rng();
+            (rng.gen_range(-2.0..2.0), rng.gen_range(-2.0..2.0))
+        };
         let target_with_jitter = Point {
             x: end.x + jitter_x,
             y: end.y + jitter_y,
@@ -554,61 +1501,132 @@ impl ChaserPage {
 
         let path = BezierPath::generate(start, target_with_jitter, 25);
 
+        let mut moves = InputPipeline::new(self);
+        let mut last = (start.x.round(), start.y.round());
         for point in path {
-            self.page
-                .move_mouse(crate::layout::Point {
-                    x: point.x,
-                    y: point.y,
-                })
-                .await
-                .map_err(|e| anyhow!("{}", e))?;
-            *self.mouse_pos.lock().unwrap() = point;
-            // Tiny delay to simulate physical movement
-            tokio::time::sleep(tokio::time::Duration::from_millis(rng.gen_range(5..15))).await;
+            let pixel = (point.x.round(), point.y.round());
+            if pixel == last {
+                continue;
+            }
+            last = pixel;
+            self.queue_mouse_move(&mut moves, point).await?;
+            // Tiny delay to simulate physical movement; now and then several
+            // moves land in the same frame
+            let delay = {
+                let mut rng = rand::thread_rng();
+                (!rng.gen_bool(0.15)).then(|| rng.gen_range(5..15))
+            };
+            if let Some(delay) = delay {
+                self.pause(Duration::from_millis(delay)).await?;
+            }
         }
 
-        Ok(())
+        moves.finish().await
     }
 
     /// Perform a click at the current mouse position.
+    ///
+    /// The button is held for a human 40-120ms with `buttons` set while it
+    /// is down. A cursor that was never placed is moved onto the page first,
+    /// so a click is never the first mouse event a page sees. Mobile profiles
+    /// tap with a finger instead.
     pub async fn click(&self) -> Result<()> {
-        let pos = { *self.mouse_pos.lock().unwrap() };
-        self.page
-            .click(crate::layout::Point { x: pos.x, y: pos.y })
-            .await
-            .map_err(|e| anyhow!("{}", e))?;
+        if !self.mouse.lock().unwrap().placed {
+            self.ensure_mouse_placed().await?;
+        }
+        let hold = rand::thread_rng().gen_range(40..120);
+        if self.mouse.lock().unwrap().touch {
+            return self.tap(hold).await;
+        }
+        let _release = ReleaseOnDrop(self);
+        self.dispatch_button(true).await?;
+        self.pause(Duration::from_millis(hold)).await?;
+        self.dispatch_button(false).await
+    }
+
+    /// Click `count` times in quick succession at the current position: 2
+    /// selects a word, 3 a paragraph.
+    ///
+    /// Each press carries its running click count, and the clicks follow
+    /// each other well within the double-click interval.
+    pub async fn multi_click(&self, count: u32) -> Result<()> {
+        if !self.mouse.lock().unwrap().placed {
+            self.ensure_mouse_placed().await?;
+        }
+        let touch = self.mouse.lock().unwrap().touch;
+        let _release = ReleaseOnDrop(self);
+        for i in 1..=count.max(1) {
+            let (hold, gap) = {
+                let mut rng = rand::thread_rng();
+                (rng.gen_range(35..90), rng.gen_range(70..150))
+            };
+            if i > 1 {
+                self.pause(Duration::from_millis(gap)).await?;
+            }
+            if touch {
+                self.tap(hold).await?;
+                continue;
+            }
+            self.dispatch_button_count(true, i as i64).await?;
+            self.pause(Duration::from_millis(hold)).await?;
+            self.dispatch_button_count(false, i as i64).await?;
+        }
         Ok(())
     }
 
+    /// Drag from the current position to (`x`, `y`) with the left button
+    /// held, e.g. for sliders and drag-and-drop.
+    ///
+    /// Every move in between reports the held button in `buttons`.
+    pub async fn drag_human(&self, x: f64, y: f64) -> Result<()> {
+        self.ensure_mouse_placed().await?;
+        let grab = rand::thread_rng().gen_range(80..200);
+        let _release = ReleaseOnDrop(self);
+        self.dispatch_button(true).await?;
+        self.pause(Duration::from_millis(grab)).await?;
+        let moved = self.move_mouse_human(x, y).await;
+        let released = self.release_input().await;
+        moved.and(released)
+    }
+
     /// Move to target and click with full human-like behavior.
     ///
     /// Combines Bezier curve mouse movement with a natural click, including:
     /// - Human-like path to target
-    /// - Small random delay before clicking (50-150ms)
+    /// - A reaction-time pause before clicking, longer on freshly loaded or
+    ///   shifting pages and shorter in streaks of similar clicks
     /// - Variable click duration
+    ///
+    /// A target above or below the viewport is scrolled into view first
+    /// (and `x`, `y` follow the scroll), since the cursor cannot leave the
+    /// viewport; one that cannot be brought into view is an error rather
+    /// than a click on the viewport's edge.
     pub async fn click_human(&self, x: f64, y: f64) -> Result<()> {
-        let mut rng = rand::thread_rng();
+        let Point { x, y } = self.bring_into_view(Point { x, y }).await?;
 
         // Move to target with bezier curve
         self.move_mouse_human(x, y).await?;
 
-        // Small pause before clicking (humans don't click instantly after arriving)
-        tokio::time::sleep(tokio::time::Duration::from_millis(rng.gen_range(50..150))).await;
+        // Humans don't click instantly after arriving
+        self.react(Interaction::Click, Some(Point { x, y })).await?;
 
         // Click
         self.click().await?;
 
         // Small pause after clicking
-        tokio::time::sleep(tokio::time::Duration::from_millis(rng.gen_range(30..80))).await;
-
-        Ok(())
+        let after = rand::thread_rng().gen_range(30..80);
+        self.pause(Duration::from_millis(after)).await
     }
 
     /// Type text with human-like delays between keystrokes.
     ///
     /// Simulates realistic typing with:
+    /// - A reaction-time pause before the first key
     /// - Variable delay between keys (50-150ms by default)
     /// - Occasional longer pauses (5% chance of 200-400ms pause)
+    ///
+    /// Fails with [`ChaserError::FocusLost`] if the focused element changes
+    /// before the text is typed; see [`crate::focus`].
     pub async fn type_text(&self, text: &str) -> Result<()> {
         self.type_text_with_delay(text, 50, 150).await
     }
@@ -625,43 +1643,30 @@ impl ChaserPage {
         min_delay_ms: u64,
         max_delay_ms: u64,
     ) -> Result<()> {
-        let mut rng = rand::thread_rng();
+        if !text.is_empty() {
+            self.react(Interaction::Type, None).await?;
+        }
+        let focus = self.mark_focus().await?;
 
         for c in text.chars() {
+            self.check_focus(focus.as_deref()).await?;
             // Send keyDown with the character
-            let key_down = DispatchKeyEventParams::builder()
-                .r#type(DispatchKeyEventType::KeyDown)
-                .text(c.to_string())
-                .build()
-                .unwrap();
-
-            self.page
-                .execute(key_down)
-                .await
-                .map_err(|e| anyhow!("{}", e))?;
-
-            // Send keyUp
-            let key_up = DispatchKeyEventParams::builder()
-                .r#type(DispatchKeyEventType::KeyUp)
-                .build()
-                .unwrap();
-
-            self.page
-                .execute(key_up)
-                .await
-                .map_err(|e| anyhow!("{}", e))?;
+            self.type_single_char(c).await?;
 
-            // Random delay between keystrokes
-            let delay = rng.gen_range(min_delay_ms..max_delay_ms);
+            let actual_delay = {
+                let mut rng = rand::thread_rng();
+                // Random delay between keystrokes
+                let delay = rng.gen_range(min_delay_ms..max_delay_ms);
 
-            // 5% chance of a longer "thinking" pause
-            let actual_delay = if rng.gen_bool(0.05) {
-                rng.gen_range(200..400)
-            } else {
-                delay
+                // 5% chance of a longer "thinking" pause
+                if rng.gen_bool(0.05) {
+                    rng.gen_range(200..400)
+                } else {
+                    delay
+                }
             };
 
-            tokio::time::sleep(tokio::time::Duration::from_millis(actual_delay)).await;
+            self.pause(Duration::from_millis(actual_delay)).await?;
         }
 
         Ok(())
@@ -690,11 +1695,6 @@ impl ChaserPage {
             .build()
             .unwrap();
 
-        self.page
-            .execute(key_down)
-            .await
-            .map_err(|e| anyhow!("{}", e))?;
-
         let key_up = DispatchKeyEventParams::builder()
             .r#type(DispatchKeyEventType::KeyUp)
             .key(key_str)
@@ -702,25 +1702,18 @@ impl ChaserPage {
             .build()
             .unwrap();
 
-        self.page
-            .execute(key_up)
-            .await
-            .map_err(|e| anyhow!("{}", e))?;
-
-        Ok(())
+        self.key_stroke(key_down, key_up).await
     }
 
-    /// Press Enter key with a small random delay before pressing.
+    /// Press Enter key after a reaction-time pause.
     pub async fn press_enter(&self) -> Result<()> {
-        let mut rng = rand::thread_rng();
-        tokio::time::sleep(tokio::time::Duration::from_millis(rng.gen_range(100..300))).await;
+        self.react(Interaction::Key, None).await?;
         self.press_key("Enter").await
     }
 
-    /// Press Tab key to move to next field.
+    /// Press Tab key to move to next field, after a reaction-time pause.
     pub async fn press_tab(&self) -> Result<()> {
-        let mut rng = rand::thread_rng();
-        tokio::time::sleep(tokio::time::Duration::from_millis(rng.gen_range(50..150))).await;
+        self.react(Interaction::Key, None).await?;
         self.press_key("Tab").await
     }
 
@@ -731,15 +1724,30 @@ impl ChaserPage {
     /// - Variable scroll distances per step
     /// - Easing at start and end (deceleration)
     ///
+    /// Mobile profiles swipe with a touch scroll gesture instead.
+    ///
     /// # Arguments
     /// * `delta_y` - Total pixels to scroll (positive = down, negative = up)
     pub async fn scroll_human(&self, delta_y: i32) -> Result<()> {
-        use chromiumoxide_cdp::cdp::browser_protocol::input::{
-            DispatchMouseEventParams, DispatchMouseEventType, MouseButton,
-        };
-
-        let mut rng = rand::thread_rng();
-        let pos = { *self.mouse_pos.lock().unwrap() };
+        self.ensure_mouse_placed().await?;
+        let pos = self.current_mouse_position();
+
+        // phones scroll with a swipe, not a wheel
+        if self.mouse.lock().unwrap().touch {
+            let speed = rand::thread_rng().gen_range(600..1400);
+            self.input(
+                SynthesizeScrollGestureParams::builder()
+                    .x(pos.x)
+                    .y(pos.y)
+                    .y_distance(-delta_y as f64)
+                    .speed(speed)
+                    .gesture_source_type(GestureSourceType::Touch)
+                    .build()
+                    .map_err(ChaserError::msg)?,
+            )
+            .await?;
+            return Ok(());
+        }
 
         // Number of scroll steps (more steps = smoother)
         let steps = (delta_y.abs() / 50).clamp(3, 15) as usize;
@@ -757,7 +1765,10 @@ impl ChaserPage {
             };
 
             let base_step = remaining / (steps - i) as i32;
-            let jitter = rng.gen_range(-10..10);
+            let (jitter, pause) = {
+                let mut rng = rand::thread_rng();
+                (rng.gen_range(-10..10), rng.gen_range(16..50))
+            };
             let step = ((base_step as f64 * ease) as i32 + jitter).clamp(-200, 200);
 
             if step == 0 {
@@ -774,14 +1785,11 @@ impl ChaserPage {
                 .build()
                 .unwrap();
 
-            self.page
-                .execute(scroll)
-                .await
-                .map_err(|e| anyhow!("{}", e))?;
+            self.input(scroll).await?;
             remaining -= step;
 
             // Variable delay between scroll events (16-50ms for 60-20 FPS feel)
-            tokio::time::sleep(tokio::time::Duration::from_millis(rng.gen_range(16..50))).await;
+            self.pause(Duration::from_millis(pause)).await?;
         }
 
         Ok(())
@@ -792,64 +1800,91 @@ impl ChaserPage {
     /// This method has a small chance (~3%) of making a typo and then correcting it,
     /// mimicking how real humans type.
     pub async fn type_text_with_typos(&self, text: &str) -> Result<()> {
-        let mut rng = rand::thread_rng();
+        if !text.is_empty() {
+            self.react(Interaction::Type, None).await?;
+        }
+        let focus = self.mark_focus().await?;
         let typo_chars = ['q', 'w', 'e', 'r', 't', 'a', 's', 'd', 'f', 'g'];
 
         for c in text.chars() {
-            // 3% chance of typo
-            if rng.gen_bool(0.03) && c.is_alphabetic() {
+            self.check_focus(focus.as_deref()).await?;
+            // 3% chance of typo: the wrong character, a pause to "notice"
+            // the mistake and one before correcting it
+            let typo = {
+                let mut rng = rand::thread_rng();
+                (rng.gen_bool(0.03) && c.is_alphabetic()).then(|| {
+                    (
+                        typo_chars[rng.gen_range(0..typo_chars.len())],
+                        rng.gen_range(100..300),
+                        rng.gen_range(30..80),
+                    )
+                })
+            };
+            if let Some((typo, notice, correct)) = typo {
                 // Type wrong character
-                let typo = typo_chars[rng.gen_range(0..typo_chars.len())];
                 self.type_single_char(typo).await?;
 
                 // Brief pause to "notice" the mistake
-                tokio::time::sleep(tokio::time::Duration::from_millis(rng.gen_range(100..300)))
-                    .await;
+                self.pause(Duration::from_millis(notice)).await?;
 
                 // Backspace to correct
                 self.press_key("Backspace").await?;
-                tokio::time::sleep(tokio::time::Duration::from_millis(rng.gen_range(30..80))).await;
+                self.pause(Duration::from_millis(correct)).await?;
             }
 
             // Type the correct character
             self.type_single_char(c).await?;
 
             // Random delay
-            let delay = rng.gen_range(50..150);
-            let actual_delay = if rng.gen_bool(0.05) {
-                rng.gen_range(200..400) // thinking pause
-            } else {
-                delay
+            let actual_delay = {
+                let mut rng = rand::thread_rng();
+                let delay = rng.gen_range(50..150);
+                if rng.gen_bool(0.05) {
+                    rng.gen_range(200..400) // thinking pause
+                } else {
+                    delay
+                }
             };
-            tokio::time::sleep(tokio::time::Duration::from_millis(actual_delay)).await;
+            self.pause(Duration::from_millis(actual_delay)).await?;
         }
 
         Ok(())
     }
 
-    /// Helper to type a single character
+    /// Helper to type a single character on the page's keyboard layout
     async fn type_single_char(&self, c: char) -> Result<()> {
-        let key_down = DispatchKeyEventParams::builder()
-            .r#type(DispatchKeyEventType::KeyDown)
+        let code = self.keyboard.lock().unwrap().code(c);
+        let key = |kind| {
+            let mut event = DispatchKeyEventParams::builder()
+                .r#type(kind)
+                .key(c.to_string());
+            if let Some(code) = code {
+                event = event.code(code);
+            }
+            if let Some(vk) = crate::keyboard::virtual_key_code(c) {
+                event = event
+                    .windows_virtual_key_code(vk)
+                    .native_virtual_key_code(vk);
+            }
+            event
+        };
+        let key_down = key(DispatchKeyEventType::KeyDown)
             .text(c.to_string())
             .build()
             .unwrap();
+        let key_up = key(DispatchKeyEventType::KeyUp).build().unwrap();
 
-        self.page
-            .execute(key_down)
-            .await
-            .map_err(|e| anyhow!("{}", e))?;
-
-        let key_up = DispatchKeyEventParams::builder()
-            .r#type(DispatchKeyEventType::KeyUp)
-            .build()
-            .unwrap();
+        self.key_stroke(key_down, key_up).await
+    }
+}
 
-        self.page
-            .execute(key_up)
-            .await
-            .map_err(|e| anyhow!("{}", e))?;
-        Ok(())
+/// A resting spot in the lower middle of the viewport, where cursors tend to
+/// idle after typing a URL or switching tabs.
+fn resting_point(width: f64, height: f64) -> Point {
+    let mut rng = rand::thread_rng();
+    Point {
+        x: (width * rng.gen_range(0.35..0.65)).round(),
+        y: (height * rng.gen_range(0.45..0.8)).round(),
     }
 }
 
@@ -862,23 +1897,31 @@ impl BezierPath {
     /// The curve includes randomized control points to create natural, human-like arcs.
     pub fn generate(start: Point, end: Point, steps: usize) -> Vec<Point> {
         let mut rng = rand::thread_rng();
-        let mut path = Vec::with_capacity(steps);
+        let mut path = Vec::with_capacity(steps + 1);
 
         // Calculate distance for offset scaling
         let dist = ((end.x - start.x).powi(2) + (end.y - start.y).powi(2)).sqrt();
         let offset_range = dist * 0.3;
+        // an empty range would panic when start and end coincide
+        let mut offset = || {
+            if offset_range > 0.0 {
+                rng.gen_range(-offset_range..offset_range)
+            } else {
+                0.0
+            }
+        };
 
         // First control point (25% along the path with random offset)
         let p1 = Point {
-            x: start.x + (end.x - start.x) * 0.25 + rng.gen_range(-offset_range..offset_range),
-            y: start.y + (end.y - start.y) * 0.25 + rng.gen_range(-offset_range..offset_range),
+            x: start.x + (end.x - start.x) * 0.25 + offset(),
+            y: start.y + (end.y - start.y) * 0.25 + offset(),
         };
 
         // Second control point (75% along the path with random offset)
         // 20% chance of overshoot
         let mut p2 = Point {
-            x: start.x + (end.x - start.x) * 0.75 + rng.gen_range(-offset_range..offset_range),
-            y: start.y + (end.y - start.y) * 0.75 + rng.gen_range(-offset_range..offset_range),
+            x: start.x + (end.x - start.x) * 0.75 + offset(),
+            y: start.y + (end.y - start.y) * 0.75 + offset(),
         };
 
         if rng.gen_bool(0.20) {
@@ -898,21 +1941,228 @@ impl BezierPath {
         // Generate points along the Bezier curve
         for i in 0..=steps {
             let t = i as f64 / steps as f64;
+            let u = 1.0 - t;
 
-            // Cubic Bezier formula
-            let x = (1.0 - t).powi(3) * start.x
-                + 3.0 * (1.0 - t).powi(2) * t * p1.x
-                + 3.0 * (1.0 - t) * t.powi(2) * p2.x
-                + t.powi(3) * end.x;
+            // Cubic Bezier formula, with the Bernstein weights shared by both axes
+            let (w0, w1, w2, w3) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+            path.push(Point {
+                x: w0 * start.x + w1 * p1.x + w2 * p2.x + w3 * end.x,
+                y: w0 * start.y + w1 * p1.y + w2 * p2.y + w3 * end.y,
+            });
+        }
 
-            let y = (1.0 - t).powi(3) * start.y
-                + 3.0 * (1.0 - t).powi(2) * t * p1.y
-                + 3.0 * (1.0 - t) * t.powi(2) * p2.y
-                + t.powi(3) * end.y;
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MockTransport;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn apply_profile_resets_the_notification_permission_natively() {
+        let mock = MockTransport::new();
+        mock.respond(
+            "Target.getTargetInfo",
+            json!({ "targetInfo": {
+                "targetId": "MOCK_TARGET", "type": "page", "title": "", "url": "about:blank",
+                "attached": true, "canAccessOpener": false, "browserContextId": "POOL_CONTEXT"
+            } }),
+        );
+        let chaser = ChaserPage::new(Page::with_transport(mock.clone()));
+        let profile = ChaserProfile::windows().build();
+        chaser.apply_profile(&profile).await.unwrap();
+
+        assert_eq!(
+            mock.commands_to("Browser.setPermission"),
+            [json!({
+                "permission": { "name": "notifications" },
+                "setting": "prompt",
+                "browserContextId": "POOL_CONTEXT"
+            })]
+        );
+        assert!(!profile
+            .bootstrap_script()
+            .contains("Permissions.prototype.query"));
+
+        // iOS has no notifications to reset
+        mock.clear();
+        chaser
+            .apply_profile(&ChaserProfile::ios().build())
+            .await
+            .unwrap();
+        assert!(mock.commands_to("Browser.setPermission").is_empty());
+    }
 
-            path.push(Point { x, y });
+    #[tokio::test]
+    async fn evaluates_in_an_isolated_world_without_runtime_enable() {
+        let mock = MockTransport::new();
+        mock.respond(
+            "Page.createIsolatedWorld",
+            json!({ "executionContextId": 7 }),
+        );
+        mock.respond(
+            "Runtime.evaluate",
+            json!({ "result": { "type": "number", "value": 42 } }),
+        );
+        let chaser = ChaserPage::new(Page::with_transport(mock.clone()));
+
+        assert_eq!(chaser.evaluate("6 * 7").await.unwrap(), Some(json!(42)));
+        let world = &mock.commands_to("Page.createIsolatedWorld")[0];
+        assert_eq!(world["frameId"], crate::transport::MAIN_FRAME);
+        assert_eq!(world["worldName"], chaser.world_name());
+        let evaluate = &mock.commands_to("Runtime.evaluate")[0];
+        assert_eq!(evaluate["contextId"], 7);
+        assert_eq!(evaluate["awaitPromise"], true);
+        assert_eq!(evaluate["returnByValue"], true);
+        assert!(mock
+            .commands()
+            .iter()
+            .all(|command| command.method != "Runtime.enable"));
+
+        mock.respond(
+            "Runtime.evaluate",
+            json!({
+                "result": { "type": "object" },
+                "exceptionDetails": {
+                    "exceptionId": 1, "text": "Uncaught", "lineNumber": 0, "columnNumber": 0,
+                    "exception": { "type": "object", "description": "ReferenceError: nope is not defined" }
+                }
+            }),
+        );
+        assert!(matches!(
+            chaser.evaluate("nope").await,
+            Err(ChaserError::Script(_))
+        ));
+    }
+
+    /// Wait until the page has issued its first `method` command.
+    async fn issued(mock: &MockTransport, method: &str) {
+        while mock.commands_to(method).is_empty() {
+            tokio::task::yield_now().await;
         }
+    }
 
-        path
+    fn lifecycle(loader: &str, name: &str) -> Value {
+        json!({
+            "frameId": crate::transport::MAIN_FRAME, "loaderId": loader,
+            "name": name, "timestamp": 1.0
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn goto_stealth_waits_for_the_lifecycle_event_of_the_new_document() {
+        let mock = MockTransport::new();
+        mock.respond(
+            "Page.navigate",
+            json!({ "frameId": crate::transport::MAIN_FRAME, "loaderId": "NEW" }),
+        );
+        let chaser = ChaserPage::new(Page::with_transport(mock.clone()));
+
+        let navigation = tokio::spawn({
+            let chaser = chaser.clone();
+            async move {
+                chaser
+                    .goto_stealth("https://shop.test/", WaitUntil::DomContentLoaded)
+                    .await
+            }
+        });
+        issued(&mock, "Page.navigate").await;
+        // the old document finishing, and an earlier event of the new one
+        mock.emit("Page.lifecycleEvent", lifecycle("OLD", "DOMContentLoaded"));
+        mock.emit("Page.lifecycleEvent", lifecycle("NEW", "init"));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!navigation.is_finished());
+
+        mock.emit("Page.lifecycleEvent", lifecycle("NEW", "DOMContentLoaded"));
+        navigation.await.unwrap().unwrap();
+        assert_eq!(
+            mock.commands_to("Page.navigate")[0]["url"],
+            "https://shop.test/"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn goto_stealth_reports_failed_and_stalled_navigations() {
+        let mock = MockTransport::new();
+        mock.respond(
+            "Page.navigate",
+            json!({ "frameId": crate::transport::MAIN_FRAME, "errorText": "net::ERR_NAME_NOT_RESOLVED" }),
+        );
+        let chaser = ChaserPage::new(Page::with_transport(mock.clone()));
+        assert!(matches!(
+            chaser.goto_stealth("https://gone.test/", WaitUntil::Load).await,
+            Err(ChaserError::Navigation { reason, .. }) if reason == "net::ERR_NAME_NOT_RESOLVED"
+        ));
+
+        // a document that never loads runs into the navigation timeout
+        mock.respond(
+            "Page.navigate",
+            json!({ "frameId": crate::transport::MAIN_FRAME, "loaderId": "NEW" }),
+        );
+        assert!(matches!(
+            chaser
+                .goto_stealth("https://slow.test/", WaitUntil::Load)
+                .await,
+            Err(ChaserError::Timeout(_))
+        ));
+    }
+
+    /// Answers the focus scripts: the field is focused until `stolen_after`
+    /// checks have passed, then a chat widget has the focus.
+    fn focus_responder(
+        stolen_after: usize,
+    ) -> impl FnMut(&Value) -> std::result::Result<Value, chromiumoxide_types::Error> {
+        let mut checks = 0;
+        move |params| {
+            let expression = params["expression"].as_str().unwrap_or_default();
+            let value = if expression.contains("now === globalThis") {
+                checks += 1;
+                if checks > stolen_after {
+                    json!("div.chat")
+                } else {
+                    Value::Null
+                }
+            } else if expression.contains("= deep()") {
+                json!("input#email")
+            } else {
+                return Ok(json!({ "result": { "type": "undefined" } }));
+            };
+            Ok(json!({ "result": { "type": "object", "value": value } }))
+        }
+    }
+
+    fn typed(mock: &MockTransport) -> String {
+        mock.commands_to("Input.dispatchKeyEvent")
+            .iter()
+            .filter_map(|event| event["text"].as_str().map(str::to_string))
+            .collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn typing_stops_when_the_page_steals_focus() {
+        let mock = MockTransport::new();
+        mock.respond_with("Runtime.evaluate", focus_responder(2));
+        let chaser = ChaserPage::new(Page::with_transport(mock.clone()));
+
+        match chaser.type_text("hello").await {
+            Err(ChaserError::FocusLost { expected, now }) => {
+                assert_eq!(
+                    (expected.as_str(), now.as_str()),
+                    ("input#email", "div.chat")
+                );
+            }
+            other => panic!("expected FocusLost, got {other:?}"),
+        }
+        assert_eq!(typed(&mock), "he");
+
+        // with the check off, fields that move focus on purpose get every key
+        mock.clear();
+        mock.respond_with("Runtime.evaluate", focus_responder(0));
+        chaser.set_focus_check(false);
+        chaser.type_text("123456").await.unwrap();
+        assert_eq!(typed(&mock), "123456");
     }
 }
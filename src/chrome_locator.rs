@@ -0,0 +1,345 @@
+//! Finding Chrome binaries and pinning them to the profile's version.
+//!
+//! A profile claims a Chrome major version in its user agent and UA client
+//! hints, but everything else a page sees (supported APIs, CSS features,
+//! `navigator.userAgentData.getHighEntropyValues` full versions) comes from
+//! the binary that actually runs. [`verify`] catches the mismatch before
+//! launch; with the `fetcher` feature [`download_pinned`] fetches a
+//! Chrome-for-Testing build of exactly the claimed major version.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::chrome_locator;
+//!
+//! for install in chrome_locator::locate_all() {
+//!     println!("{install}");
+//! }
+//! let install = chrome_locator::locate(profile.chrome_version())
+//!     .ok_or_else(|| anyhow!("no Chrome {} installed", profile.chrome_version()))?;
+//! let config = profile
+//!     .configure_browser(BrowserConfig::builder().chrome_executable(&install.path))
+//!     .build()?;
+//! ```
+
+use crate::browser::Browser;
+use crate::profiles::ChaserProfile;
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+
+/// Which Chromium-based browser a binary is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BrowserBrand {
+    Chrome,
+    ChromeForTesting,
+    Chromium,
+    Edge,
+}
+
+/// A four-part Chrome version such as `131.0.6778.85`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ChromeVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub build: u32,
+    pub patch: u32,
+}
+
+impl ChromeVersion {
+    /// Extract the version from `--version` output or a `Browser.getVersion`
+    /// product string, e.g. `Google Chrome 131.0.6778.85` or
+    /// `HeadlessChrome/131.0.6778.85`.
+    pub fn find_in(text: &str) -> Option<Self> {
+        text.split(|c: char| c.is_whitespace() || c == '/')
+            .find_map(|word| word.parse().ok())
+    }
+}
+
+impl FromStr for ChromeVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parts = s
+            .split('.')
+            .map(|part| part.parse::<u32>())
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| anyhow!("Invalid Chrome version {}", s))?;
+        match parts[..] {
+            [major, minor, build, patch] => Ok(Self {
+                major,
+                minor,
+                build,
+                patch,
+            }),
+            _ => Err(anyhow!("Invalid Chrome version {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for ChromeVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}.{}",
+            self.major, self.minor, self.build, self.patch
+        )
+    }
+}
+
+/// An installed browser binary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChromeInstall {
+    pub path: PathBuf,
+    pub brand: BrowserBrand,
+    /// `None` if the binary could not be asked for its version.
+    pub version: Option<ChromeVersion>,
+}
+
+impl ChromeInstall {
+    /// Inspect the binary at `path`.
+    pub fn inspect(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        if !path.is_file() {
+            return Err(anyhow!("No browser binary at {}", path.display()));
+        }
+        let output = version_output(&path);
+        let brand = output
+            .as_deref()
+            .and_then(brand_of)
+            .unwrap_or_else(|| brand_of(&path.to_string_lossy()).unwrap_or(BrowserBrand::Chrome));
+        let version = output
+            .as_deref()
+            .and_then(ChromeVersion::find_in)
+            .or_else(|| version_from_siblings(&path));
+        Ok(Self {
+            path,
+            brand,
+            version,
+        })
+    }
+
+    pub fn major(&self) -> Option<u32> {
+        self.version.map(|v| v.major)
+    }
+}
+
+impl fmt::Display for ChromeInstall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.version {
+            Some(version) => write!(f, "{:?} {} ({})", self.brand, version, self.path.display()),
+            None => write!(f, "{:?} ({})", self.brand, self.path.display()),
+        }
+    }
+}
+
+/// Every browser binary found on this machine, newest first.
+///
+/// Looks at the `CHROME` environment variable, the usual executable names on
+/// `PATH` and the default install locations of the current OS. Each binary is
+/// run once with `--version`.
+pub fn locate_all() -> Vec<ChromeInstall> {
+    let mut seen = HashSet::new();
+    let mut installs: Vec<ChromeInstall> = candidates()
+        .into_iter()
+        .filter(|path| seen.insert(dunce::canonicalize(path).unwrap_or_else(|_| path.clone())))
+        .filter_map(|path| ChromeInstall::inspect(path).ok())
+        .collect();
+    installs.sort_by_key(|install| std::cmp::Reverse(install.version));
+    installs
+}
+
+/// The first installed binary of the given major version.
+///
+/// Chrome and Chrome for Testing are preferred over Chromium, Edge last,
+/// since Edge adds its own brand to the UA client hints.
+pub fn locate(major: u32) -> Option<ChromeInstall> {
+    let mut matching: Vec<_> = locate_all()
+        .into_iter()
+        .filter(|install| install.major() == Some(major))
+        .collect();
+    matching.sort_by_key(|install| match install.brand {
+        BrowserBrand::Chrome | BrowserBrand::ChromeForTesting => 0,
+        BrowserBrand::Chromium => 1,
+        BrowserBrand::Edge => 2,
+    });
+    matching.into_iter().next()
+}
+
+/// Check that the binary at `path` is the Chrome major version `profile`
+/// claims.
+pub fn verify(path: impl AsRef<Path>, profile: &ChaserProfile) -> Result<ChromeVersion> {
+    let install = ChromeInstall::inspect(path.as_ref())?;
+    let version = install
+        .version
+        .ok_or_else(|| anyhow!("Could not determine the version of {}", install))?;
+    check_major(version, profile)
+}
+
+/// Check a running browser against `profile`.
+pub async fn verify_browser(browser: &Browser, profile: &ChaserProfile) -> Result<ChromeVersion> {
+    let product = browser
+        .version()
+        .await
+        .map_err(|e| anyhow!("{}", e))?
+        .product;
+    let version = ChromeVersion::find_in(&product)
+        .ok_or_else(|| anyhow!("Unrecognized browser product {}", product))?;
+    check_major(version, profile)
+}
+
+fn check_major(version: ChromeVersion, profile: &ChaserProfile) -> Result<ChromeVersion> {
+    if version.major != profile.chrome_version() {
+        return Err(anyhow!(
+            "Browser is Chrome {} but the profile claims Chrome {}; client hints will not match",
+            version,
+            profile.chrome_version()
+        ));
+    }
+    Ok(version)
+}
+
+/// Download (or reuse from `cache_dir`) the latest Chrome-for-Testing build
+/// of the given major version.
+#[cfg(feature = "fetcher")]
+pub async fn download_pinned(major: u32, cache_dir: impl Into<PathBuf>) -> Result<ChromeInstall> {
+    use chromiumoxide_fetcher::{
+        BrowserFetcher, BrowserFetcherOptions, BrowserKind, BrowserVersion, Milestone,
+    };
+
+    let options = BrowserFetcherOptions::builder()
+        .with_kind(BrowserKind::Chrome)
+        .with_version(BrowserVersion::Milestone(Milestone::new(major)))
+        .with_path(cache_dir)
+        .build()?;
+    let installation = BrowserFetcher::new(options).fetch().await?;
+    let version = installation
+        .build_info
+        .version
+        .as_deref()
+        .and_then(|v| v.parse().ok());
+    Ok(ChromeInstall {
+        path: installation.executable_path,
+        brand: BrowserBrand::ChromeForTesting,
+        version,
+    })
+}
+
+fn version_output(path: &Path) -> Option<String> {
+    let output = Command::new(path).arg("--version").output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Windows builds print nothing for `--version`; the install directory has
+/// a folder named after the version next to the executable instead.
+fn version_from_siblings(path: &Path) -> Option<ChromeVersion> {
+    std::fs::read_dir(path.parent()?)
+        .ok()?
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+        .max()
+}
+
+fn brand_of(text: &str) -> Option<BrowserBrand> {
+    let text = text.to_lowercase();
+    if text.contains("edge") {
+        Some(BrowserBrand::Edge)
+    } else if text.contains("for testing") || text.contains("chrome-for-testing") {
+        Some(BrowserBrand::ChromeForTesting)
+    } else if text.contains("chromium") {
+        Some(BrowserBrand::Chromium)
+    } else if text.contains("chrome") {
+        Some(BrowserBrand::Chrome)
+    } else {
+        None
+    }
+}
+
+fn candidates() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if let Ok(path) = std::env::var("CHROME") {
+        paths.push(PathBuf::from(path));
+    }
+    for name in [
+        "google-chrome-stable",
+        "google-chrome",
+        "google-chrome-beta",
+        "chrome",
+        "chromium",
+        "chromium-browser",
+        "microsoft-edge-stable",
+        "microsoft-edge",
+        "msedge",
+    ] {
+        if let Ok(path) = which::which(name) {
+            paths.push(path);
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    for app in [
+        "Google Chrome",
+        "Google Chrome Beta",
+        "Google Chrome Dev",
+        "Google Chrome Canary",
+        "Google Chrome for Testing",
+        "Chromium",
+        "Microsoft Edge",
+    ] {
+        paths.push(PathBuf::from(format!(
+            "/Applications/{app}.app/Contents/MacOS/{app}"
+        )));
+    }
+
+    #[cfg(windows)]
+    for root in ["PROGRAMFILES", "PROGRAMFILES(X86)", "LOCALAPPDATA"] {
+        if let Ok(root) = std::env::var(root) {
+            let root = PathBuf::from(root);
+            paths.push(root.join(r"Google\Chrome\Application\chrome.exe"));
+            paths.push(root.join(r"Chromium\Application\chrome.exe"));
+            paths.push(root.join(r"Microsoft\Edge\Application\msedge.exe"));
+        }
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    paths.extend(
+        [
+            "/opt/google/chrome/chrome",
+            "/opt/google/chrome-beta/chrome",
+            "/opt/chromium.org/chromium/chrome",
+            "/opt/microsoft/msedge/msedge",
+            "/snap/bin/chromium",
+        ]
+        .map(PathBuf::from),
+    );
+
+    paths.retain(|path| path.is_file());
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_versions_in_product_strings() {
+        let expected = ChromeVersion {
+            major: 131,
+            minor: 0,
+            build: 6778,
+            patch: 85,
+        };
+        assert_eq!(
+            ChromeVersion::find_in("Google Chrome 131.0.6778.85 "),
+            Some(expected)
+        );
+        assert_eq!(
+            ChromeVersion::find_in("HeadlessChrome/131.0.6778.85"),
+            Some(expected)
+        );
+        assert_eq!(ChromeVersion::find_in("Chromium"), None);
+    }
+}
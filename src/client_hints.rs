@@ -0,0 +1,235 @@
+//! User-Agent client hints (`navigator.userAgentData` and `Sec-CH-UA-*`).
+//!
+//! Chrome freezes the OS part of its user agent string (macOS always reads
+//! `10_15_7`, Windows always `NT 10.0`) but reports the real version through
+//! the `platformVersion` high-entropy hint. A profile has to tell the same
+//! story in both places, so the hints are derived from the same
+//! [`ChaserProfile`] as the user agent and handed to
+//! `Emulation.setUserAgentOverride`, which serves them to scripts and in
+//! request headers alike.
+
+use crate::profiles::{ChaserProfile, Os};
+use chromiumoxide_cdp::cdp::browser_protocol::emulation::{
+    UserAgentBrandVersion, UserAgentMetadata,
+};
+use serde::{Deserialize, Serialize};
+
+/// The CPU architecture of the Chrome build.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Architecture {
+    /// 64-bit x86, the desktop default
+    X86_64,
+    /// 32-bit Chrome; on Windows it runs under WOW64
+    X86,
+    /// 64-bit Arm: Apple Silicon, Snapdragon laptops, phones
+    Arm64,
+}
+
+impl Architecture {
+    /// The default architecture of `os`.
+    pub fn default_for(os: Os) -> Self {
+        match os {
+            Os::MacOSArm | Os::Android | Os::Ios => Architecture::Arm64,
+            Os::Windows | Os::MacOSIntel | Os::Linux => Architecture::X86_64,
+        }
+    }
+
+    /// The `architecture` client hint.
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Architecture::X86_64 | Architecture::X86 => "x86",
+            Architecture::Arm64 => "arm",
+        }
+    }
+
+    /// The `bitness` client hint.
+    pub fn bitness(&self) -> &'static str {
+        match self {
+            Architecture::X86 => "32",
+            Architecture::X86_64 | Architecture::Arm64 => "64",
+        }
+    }
+}
+
+/// The default `platformVersion` of `os`: Windows 11, recent macOS releases
+/// for each architecture, a current LTS kernel, Android 14 and iOS 17.5.
+pub fn default_os_version(os: Os) -> &'static str {
+    match os {
+        Os::Windows => "15.0.0",
+        Os::MacOSIntel => "14.7.1",
+        Os::MacOSArm => "15.1.0",
+        Os::Linux => "6.8.0",
+        Os::Android => "14.0.0",
+        Os::Ios => "17.5",
+    }
+}
+
+/// The OS version token of the user agent string for `os_version`.
+///
+/// Chrome has frozen macOS at `10_15_7` since Big Sur; only older releases
+/// show their real version. The token never reveals Apple Silicon either:
+/// Arm Macs claim `Intel Mac OS X` like every other Mac.
+pub(crate) fn mac_ua_token(os_version: &str) -> String {
+    let mut parts: Vec<&str> = os_version.split('.').collect();
+    match parts.first().and_then(|major| major.parse::<u32>().ok()) {
+        Some(major) if major < 11 => {
+            parts.resize(3, "0");
+            parts.join("_")
+        }
+        _ => "10_15_7".to_string(),
+    }
+}
+
+/// The UA client hints for `profile`, or `None` where the browser has none
+/// (Chrome on iOS runs on WebKit).
+pub fn metadata(profile: &ChaserProfile) -> Option<UserAgentMetadata> {
+    let os = profile.os();
+    if os == Os::Ios {
+        return None;
+    }
+    let major = profile.chrome_version().to_string();
+    let full = profile.chrome_full_version();
+    let arch = profile.architecture();
+    // Chrome for Android leaves both empty whatever the CPU
+    let (architecture, bitness) = match os {
+        Os::Android => ("", ""),
+        _ => (arch.hint(), arch.bitness()),
+    };
+    Some(UserAgentMetadata {
+        brands: Some(brands(profile.chrome_version(), &major)),
+        full_version_list: Some(brands(profile.chrome_version(), &full)),
+        platform: os.hints_platform().to_string(),
+        platform_version: profile.os_version(),
+        architecture: architecture.to_string(),
+        // the Android presets describe a Pixel 8
+        model: if os == Os::Android { "Pixel 8" } else { "" }.to_string(),
+        mobile: os.is_mobile(),
+        bitness: Some(bitness.to_string()),
+        wow64: Some(os == Os::Windows && arch == Architecture::X86),
+        form_factors: None,
+    })
+}
+
+/// The GREASE brand Chrome `major` adds to its brand list, and its version.
+///
+/// Follows Chrome's algorithm, seeded with the major version: since 105 the
+/// brand is `Not?A_Brand`-like with two of eleven escape characters and
+/// version 8, 99 or 24; 103 and 104 used three such characters and always
+/// 99; earlier releases rotated `" Not A;Brand"` and `";Not A Brand"`.
+pub fn grease_brand(major: u32) -> (String, &'static str) {
+    const CHARS: [char; 11] = [' ', '(', ':', '-', '.', '/', ')', ';', '=', '?', '_'];
+    const LEGACY_CHARS: [char; 3] = [' ', ' ', ';'];
+    const VERSIONS: [&str; 3] = ["8", "99", "24"];
+    let seed = major as usize;
+    let pick = |chars: &[char], offset: usize| chars[(seed + offset) % chars.len()];
+    if major >= 105 {
+        let brand = format!("Not{}A{}Brand", pick(&CHARS, 0), pick(&CHARS, 1));
+        (brand, VERSIONS[seed % VERSIONS.len()])
+    } else {
+        let chars: &[char] = if major >= 103 { &CHARS } else { &LEGACY_CHARS };
+        let brand = format!(
+            "{}Not{}A{}Brand",
+            pick(chars, 0),
+            pick(chars, 1),
+            pick(chars, 2)
+        );
+        (brand, "99")
+    }
+}
+
+/// The brand list of Chrome `major` with `version` (the major version for
+/// `Sec-CH-UA`, the full one for `Sec-CH-UA-Full-Version-List`), in the
+/// order Chrome permutes it into for that release.
+pub fn brands(major: u32, version: &str) -> Vec<UserAgentBrandVersion> {
+    const ORDERS: [[usize; 3]; 6] = [
+        [0, 1, 2],
+        [0, 2, 1],
+        [1, 0, 2],
+        [1, 2, 0],
+        [2, 0, 1],
+        [2, 1, 0],
+    ];
+    let (grease, grease_version) = grease_brand(major);
+    let grease_version = if version.contains('.') {
+        format!("{grease_version}.0.0.0")
+    } else {
+        grease_version.to_string()
+    };
+    let order = ORDERS[major as usize % ORDERS.len()];
+    let mut list = vec![UserAgentBrandVersion::new("", ""); 3];
+    list[order[0]] = UserAgentBrandVersion::new(grease, grease_version);
+    list[order[1]] = UserAgentBrandVersion::new("Chromium", version);
+    list[order[2]] = UserAgentBrandVersion::new("Google Chrome", version);
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mac_versions_stay_frozen_in_the_user_agent() {
+        assert_eq!(mac_ua_token("15.1.0"), "10_15_7");
+        assert_eq!(mac_ua_token("10.14"), "10_14_0");
+
+        let profile = ChaserProfile::macos_arm().os_version("14.5.0").build();
+        assert!(profile.user_agent().contains("Intel Mac OS X 10_15_7"));
+        let hints = metadata(&profile).unwrap();
+        assert_eq!(hints.platform, "macOS");
+        assert_eq!(hints.platform_version, "14.5.0");
+
+        assert!(metadata(&ChaserProfile::ios().build()).is_none());
+    }
+
+    #[test]
+    fn brand_lists_match_real_releases() {
+        let header = |major: u32| {
+            brands(major, &major.to_string())
+                .iter()
+                .map(|b| format!("\"{}\";v=\"{}\"", b.brand, b.version))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        assert_eq!(
+            header(131),
+            r#""Google Chrome";v="131", "Chromium";v="131", "Not_A Brand";v="24""#
+        );
+        assert_eq!(
+            header(120),
+            r#""Not_A Brand";v="8", "Chromium";v="120", "Google Chrome";v="120""#
+        );
+        assert_eq!(
+            header(110),
+            r#""Chromium";v="110", "Not A(Brand";v="24", "Google Chrome";v="110""#
+        );
+        assert_eq!(
+            header(103),
+            r#"".Not/A)Brand";v="99", "Google Chrome";v="103", "Chromium";v="103""#
+        );
+        assert_eq!(
+            header(89),
+            r#""Google Chrome";v="89", "Chromium";v="89", ";Not A Brand";v="99""#
+        );
+        assert_eq!(brands(131, "131.0.6778.85")[2].version, "24.0.0.0");
+    }
+
+    #[test]
+    fn architecture_follows_the_os_unless_overridden() {
+        let arm_mac = metadata(&ChaserProfile::macos_arm().build()).unwrap();
+        assert_eq!(
+            (arm_mac.architecture.as_str(), arm_mac.bitness.as_deref()),
+            ("arm", Some("64"))
+        );
+        let phone = metadata(&ChaserProfile::android().build()).unwrap();
+        assert_eq!(phone.architecture, "");
+
+        let wow64 = metadata(
+            &ChaserProfile::windows()
+                .architecture(Architecture::X86)
+                .build(),
+        )
+        .unwrap();
+        assert_eq!(wow64.bitness.as_deref(), Some("32"));
+        assert_eq!(wow64.wow64, Some(true));
+    }
+}
@@ -0,0 +1,112 @@
+//! The clock behind every humanized delay.
+//!
+//! Reaction times, typing cadence, dwell time, politeness delays and
+//! persona schedules all sleep and read the time through this module
+//! instead of `std::time` directly. In production it is the real clock.
+//! Tests can make it virtual in two ways:
+//!
+//! - under a paused tokio runtime (`#[tokio::test(start_paused = true)]`,
+//!   with tokio's `test-util` feature) sleeps complete instantly and
+//!   [`now`] follows the runtime's virtual time, for logic that needs no
+//!   browser;
+//! - [`set_time_scale`] shortens every delay by a factor while [`now`] and
+//!   [`system_time`] report time as if it had passed at full length, for
+//!   integration tests against a real browser, whose I/O needs real time.
+//!
+//! Timeouts of CDP commands and waits for page state are not humanization
+//! and keep running on real time.
+//!
+//! # Example
+//!
+//! ```ignore
+//! // a full humanized checkout in a fiftieth of the time
+//! chaser_oxide::clock::set_time_scale(0.02);
+//! chaser.click_selector_human("#buy").await?;
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime};
+
+/// The bits of `1.0_f64`.
+static SCALE: AtomicU64 = AtomicU64::new(0x3FF0_0000_0000_0000);
+/// Real and wall-clock time when the scale was first set.
+static EPOCH: OnceLock<(tokio::time::Instant, SystemTime)> = OnceLock::new();
+
+/// Make delays take `scale` times their length, e.g. `0.01` to run a
+/// hundred times faster. Process-wide; set it once, before any humanized
+/// operation starts.
+///
+/// # Panics
+///
+/// If `scale` is not a positive, finite number.
+pub fn set_time_scale(scale: f64) {
+    assert!(
+        scale.is_finite() && scale > 0.0,
+        "time scale must be positive, got {scale}"
+    );
+    EPOCH.get_or_init(|| (tokio::time::Instant::now(), SystemTime::now()));
+    SCALE.store(scale.to_bits(), Ordering::Relaxed);
+}
+
+/// The factor set by [`set_time_scale`], 1 by default.
+pub fn time_scale() -> f64 {
+    f64::from_bits(SCALE.load(Ordering::Relaxed))
+}
+
+/// `real` time after the epoch as time passed on the scaled clock.
+fn unscale(real: Duration, scale: f64) -> Duration {
+    real.div_f64(scale)
+}
+
+/// The current time: virtual under a paused tokio runtime, stretched by
+/// the time scale.
+pub fn now() -> Instant {
+    let real = tokio::time::Instant::now();
+    let scale = time_scale();
+    match EPOCH.get() {
+        Some((epoch, _)) if scale != 1.0 => {
+            (*epoch + unscale(real.duration_since(*epoch), scale)).into_std()
+        }
+        _ => real.into_std(),
+    }
+}
+
+/// Time passed on this clock since `earlier`, a value of [`now`].
+pub fn elapsed(earlier: Instant) -> Duration {
+    now().saturating_duration_since(earlier)
+}
+
+/// The wall-clock time, advancing at the pace of [`now`].
+pub fn system_time() -> SystemTime {
+    match EPOCH.get() {
+        Some((epoch, wall)) if time_scale() != 1.0 => {
+            *wall + now().saturating_duration_since(epoch.into_std())
+        }
+        _ => SystemTime::now(),
+    }
+}
+
+/// Sleep for `duration` of this clock's time.
+pub async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration.mul_f64(time_scale())).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn follows_the_paused_runtime() {
+        let started = now();
+        let real = Instant::now();
+        sleep(Duration::from_secs(600)).await;
+        assert!(elapsed(started) >= Duration::from_secs(600));
+        assert!(real.elapsed() < Duration::from_secs(5));
+
+        assert_eq!(
+            unscale(Duration::from_millis(20), 0.02),
+            Duration::from_secs(1)
+        );
+    }
+}
@@ -145,7 +145,7 @@ impl<T: EventMessage + Unpin> Stream for Connection<T> {
                     }
                     Err(err) => {
                         let msg = text.as_str().to_string();
-                        tracing::debug!(target: "chromiumoxide::conn::raw_ws::parse_errors", msg, "Failed to parse raw WS message {}", err);
+                        tracing::debug!(target: "chaser_oxide::conn::raw_ws::parse_errors", msg, "Failed to parse raw WS message {}", err);
                         Err(CdpError::InvalidMessage(text.as_str().to_string(), err))
                     }
                 };
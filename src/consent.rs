@@ -0,0 +1,514 @@
+//! IAB TCF consent strings and the CMP state that carries them.
+//!
+//! European sites show a consent dialog until the visitor's choice is
+//! stored, typically as a TCF v2.2 "TC string" in a cookie the consent
+//! management platform (CMP) reads on every page. Clicking through the
+//! dialog on every visit is slow and differs per CMP. [`TcfConsent`]
+//! builds the string for a given set of choices, and
+//! [`ChaserPage::seed_consent`] stores it where a [`Cmp`] looks for it, so
+//! the dialog does not come up.
+//!
+//! A TC string records consent decisions. Seed one only where asserting
+//! those decisions on the user's behalf is appropriate: it is the same as
+//! the user clicking the buttons, and sites pass it on to their vendors.
+//!
+//! Vendor ids and purposes follow the Global Vendor List (GVL) version the
+//! string names; a CMP discards strings from a GVL version it does not
+//! know, so use a current one.
+//!
+//! # Example
+//!
+//! ```
+//! use chaser_oxide::consent::TcfConsent;
+//!
+//! // reject everything except strictly necessary processing
+//! let tc = TcfConsent::new(10, 1, 80).language("DE").publisher_country("DE");
+//! let string = tc.encode();
+//! assert!(TcfConsent::decode(&string).unwrap().purposes_consent().is_empty());
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::clock;
+use crate::error::{ChaserError, ChaserResult as Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chromiumoxide_cdp::cdp::browser_protocol::network::{CookieParam, TimeSinceEpoch};
+use std::collections::BTreeSet;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// TCF policy version of v2.2.
+const POLICY_VERSION: u64 = 4;
+/// How long CMPs keep a choice: 13 months.
+const CONSENT_LIFETIME: Duration = Duration::from_secs(395 * 24 * 3600);
+
+/// A visitor's choices, encodable as a TCF v2.2 TC string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TcfConsent {
+    created: u64,
+    last_updated: u64,
+    cmp_id: u16,
+    cmp_version: u16,
+    consent_screen: u8,
+    language: [u8; 2],
+    vendor_list_version: u16,
+    service_specific: bool,
+    special_features: BTreeSet<u8>,
+    purposes_consent: BTreeSet<u8>,
+    purposes_li: BTreeSet<u8>,
+    publisher_country: [u8; 2],
+    vendors_consent: BTreeSet<u16>,
+    vendors_li: BTreeSet<u16>,
+    disclosed_vendors: Option<BTreeSet<u16>>,
+}
+
+impl TcfConsent {
+    /// Nothing consented, as CMP `cmp_id` in version `cmp_version` would
+    /// record it now against GVL version `vendor_list_version`.
+    pub fn new(cmp_id: u16, cmp_version: u16, vendor_list_version: u16) -> Self {
+        let now = deciseconds(clock::system_time());
+        Self {
+            created: now,
+            last_updated: now,
+            cmp_id,
+            cmp_version,
+            consent_screen: 1,
+            language: *b"EN",
+            vendor_list_version,
+            service_specific: true,
+            special_features: BTreeSet::new(),
+            purposes_consent: BTreeSet::new(),
+            purposes_li: BTreeSet::new(),
+            publisher_country: *b"AA",
+            vendors_consent: BTreeSet::new(),
+            vendors_li: BTreeSet::new(),
+            disclosed_vendors: None,
+        }
+    }
+
+    /// Everything consented for `vendors`, as "Accept all" records it:
+    /// purposes 1 to 11, both special features, and legitimate interest
+    /// for the purposes that allow it.
+    pub fn accept_all(mut self, vendors: impl IntoIterator<Item = u16>) -> Self {
+        let vendors: BTreeSet<u16> = vendors.into_iter().collect();
+        self.purposes_consent = (1..=11).collect();
+        self.purposes_li = [2, 7, 8, 9, 10, 11].into();
+        self.special_features = [1, 2].into();
+        self.vendors_li = vendors.clone();
+        self.vendors_consent = vendors;
+        self
+    }
+
+    /// When the choice was made. Defaults to now.
+    pub fn created_at(mut self, at: SystemTime) -> Self {
+        self.created = deciseconds(at);
+        self.last_updated = self.created;
+        self
+    }
+
+    /// The language of the dialog, two letters, e.g. `FR`.
+    pub fn language(mut self, language: &str) -> Self {
+        self.language = letters(language);
+        self
+    }
+
+    /// The publisher's country, two letters. Defaults to `AA`, unknown.
+    pub fn publisher_country(mut self, country: &str) -> Self {
+        self.publisher_country = letters(country);
+        self
+    }
+
+    /// The purposes (1 to 24) consented to.
+    pub fn purposes(mut self, purposes: impl IntoIterator<Item = u8>) -> Self {
+        self.purposes_consent = purposes.into_iter().collect();
+        self
+    }
+
+    /// The purposes (1 to 24) processed under legitimate interest, i.e.
+    /// not objected to.
+    pub fn legitimate_interests(mut self, purposes: impl IntoIterator<Item = u8>) -> Self {
+        self.purposes_li = purposes.into_iter().collect();
+        self
+    }
+
+    /// The special features (1 to 12) opted into.
+    pub fn special_features(mut self, features: impl IntoIterator<Item = u8>) -> Self {
+        self.special_features = features.into_iter().collect();
+        self
+    }
+
+    /// The vendors consented to, by GVL id.
+    pub fn vendors(mut self, vendors: impl IntoIterator<Item = u16>) -> Self {
+        self.vendors_consent = vendors.into_iter().collect();
+        self
+    }
+
+    /// The vendors whose legitimate interest is not objected to.
+    pub fn vendor_legitimate_interests(mut self, vendors: impl IntoIterator<Item = u16>) -> Self {
+        self.vendors_li = vendors.into_iter().collect();
+        self
+    }
+
+    /// The vendors the dialog listed, appended as the disclosed vendors
+    /// segment that v2.2 CMPs write.
+    pub fn disclosed_vendors(mut self, vendors: impl IntoIterator<Item = u16>) -> Self {
+        self.disclosed_vendors = Some(vendors.into_iter().collect());
+        self
+    }
+
+    pub fn purposes_consent(&self) -> Vec<u8> {
+        self.purposes_consent.iter().copied().collect()
+    }
+
+    pub fn vendors_consent(&self) -> Vec<u16> {
+        self.vendors_consent.iter().copied().collect()
+    }
+
+    pub fn cmp_id(&self) -> u16 {
+        self.cmp_id
+    }
+
+    pub fn vendor_list_version(&self) -> u16 {
+        self.vendor_list_version
+    }
+
+    /// The TC string: the core segment, then the disclosed vendors
+    /// segment if set, base64url without padding, joined by dots.
+    pub fn encode(&self) -> String {
+        let mut core = BitWriter::default();
+        core.push(2, 6);
+        core.push(self.created, 36);
+        core.push(self.last_updated, 36);
+        core.push(self.cmp_id.into(), 12);
+        core.push(self.cmp_version.into(), 12);
+        core.push(self.consent_screen.into(), 6);
+        core.push_letters(self.language);
+        core.push(self.vendor_list_version.into(), 12);
+        core.push(POLICY_VERSION, 6);
+        core.push(self.service_specific.into(), 1);
+        // use non-standard texts
+        core.push(0, 1);
+        core.push_flags(&self.special_features, 12);
+        core.push_flags(&self.purposes_consent, 24);
+        core.push_flags(&self.purposes_li, 24);
+        // purpose one treatment
+        core.push(0, 1);
+        core.push_letters(self.publisher_country);
+        core.push_vendors(&self.vendors_consent);
+        core.push_vendors(&self.vendors_li);
+        // publisher restrictions
+        core.push(0, 12);
+        let mut segments = vec![core.finish()];
+
+        if let Some(disclosed) = &self.disclosed_vendors {
+            let mut segment = BitWriter::default();
+            segment.push(1, 3);
+            segment.push_vendors(disclosed);
+            segments.push(segment.finish());
+        }
+        segments.join(".")
+    }
+
+    /// Parse a TC string, core segment and disclosed vendors. Publisher
+    /// restrictions and the publisher purposes segment are skipped.
+    pub fn decode(tc_string: &str) -> Result<Self> {
+        let invalid = |what: &str| ChaserError::msg(format!("invalid TC string: {what}"));
+        let mut segments = tc_string.split('.');
+        let core = URL_SAFE_NO_PAD
+            .decode(segments.next().unwrap_or_default())
+            .map_err(|_| invalid("not base64url"))?;
+        let mut bits = BitReader::new(&core);
+        let read = |bits: &mut BitReader<'_>, n| bits.read(n).ok_or_else(|| invalid("too short"));
+        if read(&mut bits, 6)? != 2 {
+            return Err(invalid("not version 2"));
+        }
+        let mut tc = Self::new(0, 0, 0);
+        tc.created = read(&mut bits, 36)?;
+        tc.last_updated = read(&mut bits, 36)?;
+        tc.cmp_id = read(&mut bits, 12)? as u16;
+        tc.cmp_version = read(&mut bits, 12)? as u16;
+        tc.consent_screen = read(&mut bits, 6)? as u8;
+        tc.language = bits.read_letters().ok_or_else(|| invalid("too short"))?;
+        tc.vendor_list_version = read(&mut bits, 12)? as u16;
+        read(&mut bits, 6)?;
+        tc.service_specific = read(&mut bits, 1)? == 1;
+        read(&mut bits, 1)?;
+        tc.special_features = bits.read_flags(12).ok_or_else(|| invalid("too short"))?;
+        tc.purposes_consent = bits.read_flags(24).ok_or_else(|| invalid("too short"))?;
+        tc.purposes_li = bits.read_flags(24).ok_or_else(|| invalid("too short"))?;
+        read(&mut bits, 1)?;
+        tc.publisher_country = bits.read_letters().ok_or_else(|| invalid("too short"))?;
+        tc.vendors_consent = bits.read_vendors().ok_or_else(|| invalid("bad vendors"))?;
+        tc.vendors_li = bits.read_vendors().ok_or_else(|| invalid("bad vendors"))?;
+
+        for segment in segments {
+            let bytes = URL_SAFE_NO_PAD
+                .decode(segment)
+                .map_err(|_| invalid("not base64url"))?;
+            let mut bits = BitReader::new(&bytes);
+            if read(&mut bits, 3)? == 1 {
+                tc.disclosed_vendors =
+                    Some(bits.read_vendors().ok_or_else(|| invalid("bad vendors"))?);
+            }
+        }
+        Ok(tc)
+    }
+}
+
+fn deciseconds(at: SystemTime) -> u64 {
+    at.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64 / 100)
+}
+
+fn letters(code: &str) -> [u8; 2] {
+    let mut bytes = code.bytes().map(|b| b.to_ascii_uppercase());
+    [bytes.next().unwrap_or(b'A'), bytes.next().unwrap_or(b'A')]
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bits: Vec<bool>,
+}
+
+impl BitWriter {
+    fn push(&mut self, value: u64, width: u32) {
+        for i in (0..width).rev() {
+            self.bits.push(value >> i & 1 == 1);
+        }
+    }
+
+    fn push_letters(&mut self, letters: [u8; 2]) {
+        for letter in letters {
+            self.push(letter.saturating_sub(b'A').min(25).into(), 6);
+        }
+    }
+
+    /// Ids `1..=width` as one bit each.
+    fn push_flags(&mut self, set: &BTreeSet<u8>, width: u8) {
+        for id in 1..=width {
+            self.bits.push(set.contains(&id));
+        }
+    }
+
+    /// A vendor section: max id, then a bitfield or ranges, whichever is
+    /// shorter, as CMPs write it.
+    fn push_vendors(&mut self, vendors: &BTreeSet<u16>) {
+        let max = vendors.last().copied().unwrap_or(0);
+        self.push(max.into(), 16);
+        let mut ranges: Vec<(u16, u16)> = Vec::new();
+        for &id in vendors {
+            match ranges.last_mut() {
+                Some((_, end)) if *end + 1 == id => *end = id,
+                _ => ranges.push((id, id)),
+            }
+        }
+        let range_bits: usize = 12
+            + ranges
+                .iter()
+                .map(|(start, end)| if start == end { 17 } else { 33 })
+                .sum::<usize>();
+        if range_bits < usize::from(max) {
+            self.push(1, 1);
+            self.push(ranges.len() as u64, 12);
+            for (start, end) in ranges {
+                self.push((start != end).into(), 1);
+                self.push(start.into(), 16);
+                if start != end {
+                    self.push(end.into(), 16);
+                }
+            }
+        } else {
+            self.push(0, 1);
+            for id in 1..=max {
+                self.bits.push(vendors.contains(&id));
+            }
+        }
+    }
+
+    fn finish(self) -> String {
+        let bytes: Vec<u8> = self
+            .bits
+            .chunks(8)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0u8, |byte, (i, &bit)| byte | (u8::from(bit) << (7 - i)))
+            })
+            .collect();
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn read(&mut self, width: u32) -> Option<u64> {
+        let mut value = 0;
+        for _ in 0..width {
+            let byte = self.bytes.get(self.pos / 8)?;
+            value = value << 1 | u64::from(byte >> (7 - self.pos % 8) & 1);
+            self.pos += 1;
+        }
+        Some(value)
+    }
+
+    fn read_letters(&mut self) -> Option<[u8; 2]> {
+        Some([self.read(6)? as u8 + b'A', self.read(6)? as u8 + b'A'])
+    }
+
+    fn read_flags<T: TryFrom<u64> + Ord>(&mut self, width: u64) -> Option<BTreeSet<T>> {
+        let mut set = BTreeSet::new();
+        for id in 1..=width {
+            if self.read(1)? == 1 {
+                set.insert(T::try_from(id).ok()?);
+            }
+        }
+        Some(set)
+    }
+
+    fn read_vendors(&mut self) -> Option<BTreeSet<u16>> {
+        let max = self.read(16)?;
+        if self.read(1)? == 0 {
+            return self.read_flags(max);
+        }
+        let mut set = BTreeSet::new();
+        for _ in 0..self.read(12)? {
+            let is_range = self.read(1)? == 1;
+            let start = self.read(16)? as u16;
+            let end = if is_range {
+                self.read(16)? as u16
+            } else {
+                start
+            };
+            set.extend(start..=end);
+        }
+        Some(set)
+    }
+}
+
+/// Where a consent management platform keeps the TC string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cmp {
+    /// The `euconsent-v2` cookie the IAB reference implementation and
+    /// most CMPs (InMobi Choice, Didomi, Sourcepoint, Usercentrics) read.
+    Standard,
+    /// OneTrust: the `eupubconsent-v2` cookie, plus
+    /// `OptanonAlertBoxClosed` so the banner stays closed.
+    OneTrust,
+    /// Any other cookie name, plus `localStorage` keys to store the string
+    /// under.
+    Custom {
+        cookie: String,
+        local_storage: Vec<String>,
+    },
+}
+
+impl ChaserPage {
+    /// Store `consent` for the current site where `cmp` reads it, as if
+    /// the visitor had made those choices in its dialog. Navigate to the
+    /// site first (any page), then reload or go on: the CMP finds the
+    /// choice and stays closed.
+    ///
+    /// Cookies are set for the current host and last 13 months, like the
+    /// ones CMPs write.
+    pub async fn seed_consent(&self, consent: &TcfConsent, cmp: &Cmp) -> Result<()> {
+        let tc_string = consent.encode();
+        let expires = clock::system_time()
+            .checked_add(CONSENT_LIFETIME)
+            .and_then(|at| at.duration_since(UNIX_EPOCH).ok())
+            .map_or(0.0, |d| d.as_secs_f64());
+        let cookie = |name: &str, value: String| {
+            let mut cookie = CookieParam::new(name, value);
+            cookie.path = Some("/".to_string());
+            cookie.expires = Some(TimeSinceEpoch::new(expires));
+            cookie
+        };
+        let (cookies, keys) = match cmp {
+            Cmp::Standard => (vec![cookie("euconsent-v2", tc_string.clone())], Vec::new()),
+            Cmp::OneTrust => {
+                let closed = iso_timestamp(clock::system_time());
+                (
+                    vec![
+                        cookie("eupubconsent-v2", tc_string.clone()),
+                        cookie("OptanonAlertBoxClosed", closed),
+                    ],
+                    Vec::new(),
+                )
+            }
+            Cmp::Custom {
+                cookie: name,
+                local_storage,
+            } => (vec![cookie(name, tc_string.clone())], local_storage.clone()),
+        };
+        self.raw_page().set_cookies(cookies).await?;
+        if !keys.is_empty() {
+            let script = format!(
+                "(() => {{ for (const key of {}) localStorage.setItem(key, {}); }})()",
+                serde_json::to_string(&keys).unwrap_or_default(),
+                serde_json::to_string(&tc_string).unwrap_or_default()
+            );
+            self.evaluate_stealth(&script).await?;
+        }
+        Ok(())
+    }
+}
+
+/// `at` as an ISO 8601 UTC timestamp with milliseconds, the way
+/// `Date.prototype.toISOString` writes it.
+fn iso_timestamp(at: SystemTime) -> String {
+    let since = at.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since.as_secs();
+    let (days, rest) = (secs / 86_400, secs % 86_400);
+    // civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60,
+        since.subsec_millis()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tc_strings_round_trip() {
+        let at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let vendors = (1..=40).chain([755, 793]);
+        let tc = TcfConsent::new(300, 2, 80)
+            .created_at(at)
+            .language("fr")
+            .publisher_country("FR")
+            .accept_all(vendors)
+            .disclosed_vendors([1, 2, 755]);
+        let encoded = tc.encode();
+        // version 2 and the creation time in the first bits
+        assert!(encoded.starts_with("CP"));
+        assert_eq!(encoded.matches('.').count(), 1);
+        assert_eq!(TcfConsent::decode(&encoded).unwrap(), tc);
+
+        let rejected = TcfConsent::new(300, 2, 80).created_at(at);
+        assert_eq!(TcfConsent::decode(&rejected.encode()).unwrap(), rejected);
+        assert!(TcfConsent::decode("not a tc string").is_err());
+
+        assert_eq!(iso_timestamp(at), "2023-11-14T22:13:20.000Z");
+    }
+}
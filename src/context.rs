@@ -0,0 +1,536 @@
+//! Isolated browser contexts carrying a stealth profile.
+//!
+//! A [`ChaserContext`] is an incognito-like browser context: pages opened in
+//! it share cookies, storage and cache with each other but with nothing else
+//! in the browser. Every page it opens has the context's profile applied.
+//!
+//! A context that lives long can be given a [`PageBudget`] with
+//! [`ChaserContext::page_budget`] to keep its page count and memory in
+//! check, see [`page_budget`](crate::page_budget).
+
+use crate::browser::Browser;
+use crate::chaser::ChaserPage;
+use crate::page_budget::{closable, BudgetStats, PageBudget};
+use crate::profiles::ChaserProfile;
+use anyhow::{anyhow, Result};
+use chromiumoxide_cdp::cdp::browser_protocol::browser::BrowserContextId;
+use chromiumoxide_cdp::cdp::browser_protocol::network::ClearBrowserCacheParams;
+use chromiumoxide_cdp::cdp::browser_protocol::storage::ClearCookiesParams;
+use chromiumoxide_cdp::cdp::browser_protocol::target::{
+    CreateBrowserContextParams, CreateTargetParams, GetTargetsParams,
+};
+use chromiumoxide_cdp::cdp::js_protocol::runtime::GetHeapUsageParams;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A browser context with a fixed profile and optional proxy.
+#[derive(Debug, Clone)]
+pub struct ChaserContext {
+    browser: Arc<Browser>,
+    id: BrowserContextId,
+    profile: ChaserProfile,
+    proxy: Option<String>,
+    /// Shared by clones, so every handle sees the same pages.
+    budget: Arc<Mutex<Budgeted>>,
+}
+
+/// The pages a budget keeps track of.
+#[derive(Debug, Default)]
+struct Budgeted {
+    budget: Option<PageBudget>,
+    pages: Vec<ChaserPage>,
+    stats: BudgetStats,
+}
+
+impl Budgeted {
+    /// Stop tracking the pages at `indices` and hand them out.
+    fn take(&mut self, mut indices: Vec<usize>) -> Vec<ChaserPage> {
+        indices.sort_unstable_by(|a, b| b.cmp(a));
+        let pages = indices
+            .into_iter()
+            .map(|i| self.pages.swap_remove(i))
+            .collect();
+        self.stats.open = self.pages.len();
+        pages
+    }
+}
+
+impl ChaserContext {
+    /// Create a new context in `browser`, routed through `proxy` if given.
+    pub async fn create(
+        browser: Arc<Browser>,
+        profile: ChaserProfile,
+        proxy: Option<String>,
+    ) -> Result<Self> {
+        let mut params = CreateBrowserContextParams::builder().dispose_on_detach(true);
+        if let Some(proxy) = &proxy {
+            params = params.proxy_server(proxy.clone());
+        }
+        let id = browser.create_browser_context(params.build()).await?;
+        Ok(Self {
+            browser,
+            id,
+            profile,
+            proxy,
+            budget: Arc::default(),
+        })
+    }
+
+    /// Keep at most `max_pages` pages open in this context, and their
+    /// JavaScript heaps around `max_rss_hint` bytes if given, with the
+    /// defaults of [`PageBudget::new`].
+    ///
+    /// # Panics
+    ///
+    /// If `max_pages` is zero.
+    pub fn page_budget(self, max_pages: usize, max_rss_hint: Option<u64>) -> Self {
+        self.with_page_budget(PageBudget::new(max_pages, max_rss_hint))
+    }
+
+    /// Keep the pages of this context within `budget`. Only pages opened
+    /// from now on through [`new_page`](Self::new_page) count against it.
+    pub fn with_page_budget(self, budget: PageBudget) -> Self {
+        self.budget.lock().unwrap().budget = Some(budget);
+        self
+    }
+
+    pub fn budget(&self) -> Option<PageBudget> {
+        self.budget.lock().unwrap().budget.clone()
+    }
+
+    /// What the budget did so far.
+    pub fn budget_stats(&self) -> BudgetStats {
+        self.budget.lock().unwrap().stats
+    }
+
+    /// Open a blank page in this context with the profile applied.
+    ///
+    /// With a [`PageBudget`], a context that is full first closes its
+    /// least recently active idle page, and fails if no page is idle.
+    pub async fn new_page(&self) -> Result<ChaserPage> {
+        let budgeted = self.budget.lock().unwrap().budget.is_some();
+        if budgeted {
+            self.make_room().await?;
+        }
+        let page = self.open_page(false).await?;
+        if budgeted {
+            let mut state = self.budget.lock().unwrap();
+            state.pages.push(page.clone());
+            state.stats.opened += 1;
+            state.stats.open = state.pages.len();
+        }
+        Ok(page)
+    }
+
+    /// Open a blank page with the profile applied, in a background tab if
+    /// `background`. The page does not count against the budget.
+    pub(crate) async fn open_page(&self, background: bool) -> Result<ChaserPage> {
+        let mut params = CreateTargetParams::builder()
+            .url("about:blank")
+            .browser_context_id(self.id.clone());
+        if background {
+            params = params.background(true);
+        }
+        let params = params.build().map_err(|e| anyhow!("{}", e))?;
+        let page = ChaserPage::new(self.browser.new_page(params).await?);
+        if let Err(e) = page.apply_profile(&self.profile).await {
+            let _ = page.raw_page().clone().close().await;
+            return Err(e.into());
+        }
+        Ok(page)
+    }
+
+    /// Close idle pages until a new page fits in `max_pages`.
+    async fn make_room(&self) -> Result<()> {
+        self.forget_closed_pages().await?;
+        let victims = {
+            let mut state = self.budget.lock().unwrap();
+            let Some(budget) = state.budget.clone() else {
+                return Ok(());
+            };
+            let excess = (state.pages.len() + 1).saturating_sub(budget.max_pages());
+            if excess == 0 {
+                return Ok(());
+            }
+            let idle: Vec<Duration> = state.pages.iter().map(ChaserPage::idle_for).collect();
+            let mut candidates = closable(&idle, budget.idle_threshold());
+            if candidates.len() < excess {
+                return Err(anyhow!(
+                    "page budget of {} exhausted: no page has been idle for {:?}",
+                    budget.max_pages(),
+                    budget.idle_threshold()
+                ));
+            }
+            candidates.truncate(excess);
+            state.stats.closed_for_pages += excess as u64;
+            state.take(candidates)
+        };
+        close_pages(victims).await;
+        Ok(())
+    }
+
+    /// Stop tracking pages that were closed by their users or the site.
+    async fn forget_closed_pages(&self) -> Result<()> {
+        let live: HashSet<_> = self
+            .browser
+            .execute(GetTargetsParams::default())
+            .await?
+            .result
+            .target_infos
+            .into_iter()
+            .filter(|info| info.browser_context_id.as_ref() == Some(&self.id))
+            .map(|info| info.target_id)
+            .collect();
+        let mut state = self.budget.lock().unwrap();
+        state
+            .pages
+            .retain(|page| live.contains(page.raw_page().target_id()));
+        state.stats.open = state.pages.len();
+        Ok(())
+    }
+
+    /// One round of upkeep for a budgeted context:
+    ///
+    /// 1. forgets pages that were closed elsewhere;
+    /// 2. closes pages idle beyond [`PageBudget::close_idle_after`];
+    /// 3. with a memory hint, measures the heap of every page and closes
+    ///    idle pages, least recently active first, until the total fits;
+    /// 4. clears the cache and cookies as the [`ClearPolicy`] says.
+    ///
+    /// Returns the stats after the pass. Does nothing without a budget.
+    ///
+    /// [`ClearPolicy`]: crate::page_budget::ClearPolicy
+    pub async fn hygiene(&self) -> Result<BudgetStats> {
+        let Some(budget) = self.budget() else {
+            return Ok(self.budget_stats());
+        };
+        self.forget_closed_pages().await?;
+
+        if let Some(timeout) = budget.idle_timeout() {
+            let expired = {
+                let mut state = self.budget.lock().unwrap();
+                let idle: Vec<Duration> = state.pages.iter().map(ChaserPage::idle_for).collect();
+                let expired = closable(&idle, timeout);
+                state.stats.closed_idle += expired.len() as u64;
+                state.take(expired)
+            };
+            close_pages(expired).await;
+        }
+
+        if let Some(limit) = budget.max_rss_hint() {
+            let pages = self.budget.lock().unwrap().pages.clone();
+            let mut heaps = Vec::with_capacity(pages.len());
+            for page in &pages {
+                // a page that cannot answer is about to go anyway
+                let heap = page
+                    .raw_page()
+                    .execute(GetHeapUsageParams::default())
+                    .await
+                    .map(|usage| usage.result.total_size as u64)
+                    .unwrap_or(0);
+                heaps.push(heap);
+            }
+            let mut total: u64 = heaps.iter().sum();
+            let mut victims = Vec::new();
+            if total > limit {
+                let idle: Vec<Duration> = pages.iter().map(ChaserPage::idle_for).collect();
+                for i in closable(&idle, budget.idle_threshold()) {
+                    if total <= limit {
+                        break;
+                    }
+                    total -= heaps[i];
+                    victims.push(pages[i].raw_page().target_id().clone());
+                }
+            }
+            let closing = {
+                let mut state = self.budget.lock().unwrap();
+                let indices = state
+                    .pages
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, page)| victims.contains(page.raw_page().target_id()))
+                    .map(|(i, _)| i)
+                    .collect();
+                state.stats.heap_bytes = total;
+                state.stats.closed_for_memory += victims.len() as u64;
+                state.take(indices)
+            };
+            close_pages(closing).await;
+        }
+
+        let clear = budget.clear_policy();
+        if clear.cache {
+            let page = self.budget.lock().unwrap().pages.first().cloned();
+            // the cache belongs to the context, any of its pages can clear it
+            if let Some(page) = page {
+                page.raw_page()
+                    .execute(ClearBrowserCacheParams::default())
+                    .await?;
+                self.budget.lock().unwrap().stats.cache_clears += 1;
+            }
+        }
+        if clear.cookies {
+            self.browser
+                .execute(
+                    ClearCookiesParams::builder()
+                        .browser_context_id(self.id.clone())
+                        .build(),
+                )
+                .await?;
+            self.budget.lock().unwrap().stats.cookie_clears += 1;
+        }
+        Ok(self.budget_stats())
+    }
+
+    /// Run [`hygiene`](Self::hygiene) every `every` on a tokio task, until
+    /// the task is aborted or a pass fails, typically because the context
+    /// was disposed.
+    pub fn spawn_hygiene(&self, every: Duration) -> tokio::task::JoinHandle<()> {
+        let context = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(every).await;
+                match context.hygiene().await {
+                    Ok(stats) => tracing::debug!(?stats, "page budget hygiene"),
+                    Err(e) => {
+                        tracing::warn!("page budget hygiene stopped: {}", e);
+                        break;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Replace this context with a fresh one: same profile, proxy and
+    /// budget, but none of the pages, cookies, storage or cache, which are
+    /// disposed with the old context (`Target.disposeBrowserContext`).
+    /// The budget stats carry over.
+    ///
+    /// Clones of the old context stop working.
+    pub async fn recycle(self) -> Result<Self> {
+        let (budget, mut stats) = {
+            let state = self.budget.lock().unwrap();
+            (state.budget.clone(), state.stats)
+        };
+        let fresh = Self::create(
+            Arc::clone(&self.browser),
+            self.profile.clone(),
+            self.proxy.clone(),
+        )
+        .await?;
+        self.dispose().await?;
+        stats.recycles += 1;
+        stats.open = 0;
+        {
+            let mut state = fresh.budget.lock().unwrap();
+            state.budget = budget;
+            state.stats = stats;
+        }
+        Ok(fresh)
+    }
+
+    pub fn id(&self) -> &BrowserContextId {
+        &self.id
+    }
+
+    pub fn profile(&self) -> &ChaserProfile {
+        &self.profile
+    }
+
+    pub fn proxy(&self) -> Option<&str> {
+        self.proxy.as_deref()
+    }
+
+    pub fn browser(&self) -> &Arc<Browser> {
+        &self.browser
+    }
+
+    /// Dispose the context, closing all of its pages.
+    pub async fn dispose(self) -> Result<()> {
+        self.browser.dispose_browser_context(self.id).await?;
+        Ok(())
+    }
+}
+
+async fn close_pages(pages: Vec<ChaserPage>) {
+    for page in pages {
+        if let Err(e) = page.raw_page().clone().close().await {
+            tracing::debug!("closing a page over budget failed: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::page_budget::ClearPolicy;
+    use crate::transport::MockTransport;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A browser whose targets are `T1`, `T2`, ... in the order they were
+    /// created, all of them still open.
+    fn browser(mock: &MockTransport) -> Arc<Browser> {
+        let created = Arc::new(AtomicUsize::new(0));
+        mock.respond_with("Target.createTarget", {
+            let created = created.clone();
+            move |_| {
+                let n = created.fetch_add(1, Ordering::SeqCst) + 1;
+                Ok(json!({ "targetId": format!("T{n}") }))
+            }
+        });
+        mock.respond_with("Target.getTargets", move |_| {
+            let infos: Vec<_> = (1..=created.load(Ordering::SeqCst))
+                .map(|n| {
+                    json!({
+                        "targetId": format!("T{n}"), "type": "page", "title": "", "url": "about:blank",
+                        "attached": true, "canAccessOpener": false, "browserContextId": "MOCK_CONTEXT"
+                    })
+                })
+                .collect();
+            Ok(json!({ "targetInfos": infos }))
+        });
+        Arc::new(Browser::with_transport(mock.clone()))
+    }
+
+    #[tokio::test]
+    async fn pages_open_in_the_context_with_its_profile() {
+        let mock = MockTransport::new();
+        let context = ChaserContext::create(
+            browser(&mock),
+            ChaserProfile::macos_arm().build(),
+            Some("http://10.0.0.1:8080".to_string()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            mock.commands_to("Target.createBrowserContext"),
+            [json!({ "disposeOnDetach": true, "proxyServer": "http://10.0.0.1:8080" })]
+        );
+        assert_eq!(context.proxy(), Some("http://10.0.0.1:8080"));
+
+        let page = context.new_page().await.unwrap();
+        assert_eq!(page.raw_page().target_id().as_ref(), "T1");
+        let target = &mock.commands_to("Target.createTarget")[0];
+        assert_eq!(target["url"], "about:blank");
+        assert_eq!(target["browserContextId"], "MOCK_CONTEXT");
+        assert!(
+            mock.commands_to("Emulation.setUserAgentOverride")[0]["userAgent"]
+                .as_str()
+                .unwrap()
+                .contains("Macintosh")
+        );
+        // unbudgeted pages are not tracked
+        assert_eq!(context.budget_stats(), BudgetStats::default());
+
+        context.dispose().await.unwrap();
+        assert_eq!(
+            mock.commands_to("Target.disposeBrowserContext"),
+            [json!({ "browserContextId": "MOCK_CONTEXT" })]
+        );
+    }
+
+    #[tokio::test]
+    async fn a_page_the_profile_fails_on_is_closed() {
+        let mock = MockTransport::new();
+        mock.fail("Page.addScriptToEvaluateOnNewDocument", "Target closed");
+        let context = ChaserContext::create(browser(&mock), ChaserProfile::default(), None)
+            .await
+            .unwrap();
+        assert!(context.new_page().await.is_err());
+        assert_eq!(mock.commands_to("Page.close").len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_full_budget_closes_the_longest_idle_page() {
+        let mock = MockTransport::new();
+        let context = ChaserContext::create(browser(&mock), ChaserProfile::default(), None)
+            .await
+            .unwrap()
+            .page_budget(1, None);
+
+        context.new_page().await.unwrap();
+        let err = context.new_page().await.unwrap_err();
+        assert!(err.to_string().contains("page budget of 1 exhausted"));
+        assert!(mock.commands_to("Page.close").is_empty());
+
+        tokio::time::advance(Duration::from_secs(31)).await;
+        // the rejected page was never created
+        let page = context.new_page().await.unwrap();
+        assert_eq!(page.raw_page().target_id().as_ref(), "T2");
+        assert_eq!(mock.commands_to("Page.close").len(), 1);
+        let stats = context.budget_stats();
+        assert_eq!(
+            (stats.open, stats.opened, stats.closed_for_pages),
+            (1, 2, 1)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hygiene_closes_idle_and_heavy_pages_and_clears_data() {
+        let mock = MockTransport::new();
+        mock.respond(
+            "Runtime.getHeapUsage",
+            json!({ "usedSize": 600, "totalSize": 800, "embedderHeapUsedSize": 0, "backingStorageSize": 0 }),
+        );
+        let budget = PageBudget::new(3, Some(1000))
+            .close_idle_after(Duration::from_secs(60))
+            .clear(ClearPolicy {
+                cache: true,
+                cookies: true,
+            });
+        let context = ChaserContext::create(browser(&mock), ChaserProfile::default(), None)
+            .await
+            .unwrap()
+            .with_page_budget(budget);
+        context.new_page().await.unwrap();
+        context.new_page().await.unwrap();
+
+        // both heaps are over the hint, but no page is idle yet
+        let stats = context.hygiene().await.unwrap();
+        assert_eq!((stats.open, stats.heap_bytes, stats.closed()), (2, 1600, 0));
+
+        tokio::time::advance(Duration::from_secs(31)).await;
+        let stats = context.hygiene().await.unwrap();
+        assert_eq!((stats.open, stats.heap_bytes), (1, 800));
+        assert_eq!(stats.closed_for_memory, 1);
+
+        tokio::time::advance(Duration::from_secs(30)).await;
+        let stats = context.hygiene().await.unwrap();
+        assert_eq!((stats.open, stats.closed_idle), (0, 1));
+
+        // the cache is cleared through a page, the cookies for the context
+        assert_eq!(stats.cache_clears, 2);
+        assert_eq!(stats.cookie_clears, 3);
+        assert_eq!(
+            mock.commands_to("Storage.clearCookies")[0],
+            json!({ "browserContextId": "MOCK_CONTEXT" })
+        );
+    }
+
+    #[tokio::test]
+    async fn recycling_keeps_the_budget_and_its_stats() {
+        let mock = MockTransport::new();
+        let context = ChaserContext::create(browser(&mock), ChaserProfile::default(), None)
+            .await
+            .unwrap()
+            .page_budget(2, None);
+        context.new_page().await.unwrap();
+
+        let fresh = context.recycle().await.unwrap();
+        assert_eq!(mock.commands_to("Target.createBrowserContext").len(), 2);
+        assert_eq!(mock.commands_to("Target.disposeBrowserContext").len(), 1);
+        assert_eq!(fresh.budget().map(|b| b.max_pages()), Some(2));
+        let stats = fresh.budget_stats();
+        assert_eq!((stats.open, stats.opened, stats.recycles), (0, 1, 1));
+
+        // and with no budget, hygiene does nothing
+        let plain = ChaserContext::create(browser(&mock), ChaserProfile::default(), None)
+            .await
+            .unwrap();
+        mock.clear();
+        assert_eq!(plain.hygiene().await.unwrap(), BudgetStats::default());
+        assert!(mock.commands().is_empty());
+    }
+}
@@ -0,0 +1,382 @@
+//! Viewport-relative and page-relative coordinates.
+//!
+//! CDP input events take *viewport* CSS pixels, while positions computed from
+//! the document (offsets, previously recorded element positions) are *page*
+//! coordinates that only line up with the viewport when the page is not
+//! scrolled. [`Coordinates`] makes the distinction explicit; humanized input
+//! accepting it scrolls page coordinates into view before acting on them.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::coordinates::Coordinates;
+//!
+//! // 1800px down the document, scrolled into view first
+//! chaser.click_at(Coordinates::page(400.0, 1800.0)).await?;
+//!
+//! let button = chaser.raw_page().find_element("#submit").await?;
+//! let target = chaser.element_coordinates(&button).await?;
+//! chaser.click_at(target).await?;
+//! ```
+
+use crate::chaser::{ChaserPage, Point};
+use crate::element::Element;
+use crate::error::{ChaserError, ChaserResult as Result};
+
+/// A position either relative to the visible viewport or to the document.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Coordinates {
+    /// CSS pixels from the top-left corner of the visible viewport.
+    Viewport(Point),
+    /// CSS pixels from the top-left corner of the document.
+    Page(Point),
+}
+
+impl Coordinates {
+    pub fn viewport(x: f64, y: f64) -> Self {
+        Coordinates::Viewport(Point { x, y })
+    }
+
+    pub fn page(x: f64, y: f64) -> Self {
+        Coordinates::Page(Point { x, y })
+    }
+}
+
+/// Scroll position and size of the visible viewport.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewportState {
+    /// Document offset of the visible viewport's left edge.
+    pub scroll_x: f64,
+    /// Document offset of the visible viewport's top edge.
+    pub scroll_y: f64,
+    /// Visible width in CSS pixels.
+    pub width: f64,
+    /// Visible height in CSS pixels.
+    pub height: f64,
+    /// Offset of the visual viewport inside the layout viewport (pinch zoom).
+    pub visual_offset_x: f64,
+    pub visual_offset_y: f64,
+    /// Pinch zoom scale of the visual viewport.
+    pub scale: f64,
+    /// Device pixels per CSS pixel.
+    pub device_pixel_ratio: f64,
+}
+
+impl ViewportState {
+    /// Resolve `coords` against this scroll position.
+    pub fn to_viewport(&self, coords: Coordinates) -> Point {
+        match coords {
+            Coordinates::Viewport(p) => p,
+            Coordinates::Page(p) => Point {
+                x: p.x - self.scroll_x,
+                y: p.y - self.scroll_y,
+            },
+        }
+    }
+
+    /// Resolve `coords` to a document position.
+    pub fn to_page(&self, coords: Coordinates) -> Point {
+        match coords {
+            Coordinates::Page(p) => p,
+            Coordinates::Viewport(p) => Point {
+                x: p.x + self.scroll_x,
+                y: p.y + self.scroll_y,
+            },
+        }
+    }
+
+    /// Whether a viewport point lies inside the visible area.
+    pub fn contains(&self, p: Point) -> bool {
+        p.x >= 0.0 && p.y >= 0.0 && p.x < self.width && p.y < self.height
+    }
+
+    /// Translate a point from DOM domain quads (layout viewport CSS pixels)
+    /// into visual viewport coordinates usable for input events.
+    pub fn from_layout(&self, p: crate::layout::Point) -> Point {
+        Point {
+            x: (p.x - self.visual_offset_x) * self.scale,
+            y: (p.y - self.visual_offset_y) * self.scale,
+        }
+    }
+
+    /// Translate a viewport point into screenshot (device) pixels.
+    pub fn to_device(&self, p: Point) -> Point {
+        Point {
+            x: p.x * self.device_pixel_ratio,
+            y: p.y * self.device_pixel_ratio,
+        }
+    }
+
+    /// Translate screenshot (device) pixels into a viewport point.
+    pub fn from_device(&self, p: Point) -> Point {
+        Point {
+            x: p.x / self.device_pixel_ratio,
+            y: p.y / self.device_pixel_ratio,
+        }
+    }
+}
+
+impl ChaserPage {
+    /// Current scroll position and viewport size, read via
+    /// `Page.getLayoutMetrics` (no script runs in the page).
+    pub async fn viewport_state(&self) -> Result<ViewportState> {
+        let metrics = self.raw_page().layout_metrics().await?;
+        let visual = metrics.css_visual_viewport;
+        let device_pixel_ratio = self
+            .evaluate_stealth("window.devicePixelRatio")
+            .await
+            .ok()
+            .flatten()
+            .and_then(|v| v.as_f64())
+            .unwrap_or(1.0);
+        Ok(ViewportState {
+            scroll_x: visual.page_x,
+            scroll_y: visual.page_y,
+            width: visual.client_width,
+            height: visual.client_height,
+            visual_offset_x: visual.offset_x,
+            visual_offset_y: visual.offset_y,
+            scale: visual.scale,
+            device_pixel_ratio,
+        })
+    }
+
+    /// Scroll (humanly) until `coords` is visible and return its viewport
+    /// position.
+    ///
+    /// Targets are brought into the middle band of the viewport rather than
+    /// its very edge, the way people scroll to something they want to click.
+    pub async fn scroll_into_view_coords(&self, coords: Coordinates) -> Result<Point> {
+        let mut state = self.viewport_state().await?;
+        for _ in 0..4 {
+            let point = state.to_viewport(coords);
+            let margin = state.height * 0.15;
+            if state.contains(point) && point.y >= margin && point.y <= state.height - margin {
+                return Ok(point);
+            }
+            if let Coordinates::Viewport(_) = coords {
+                // viewport targets cannot be scrolled to
+                return Ok(point);
+            }
+            let delta = point.y - state.height / 2.0;
+            self.scroll_human(delta.round() as i32).await?;
+            let next = self.viewport_state().await?;
+            let stuck = next.scroll_y == state.scroll_y;
+            state = next;
+            if stuck {
+                break;
+            }
+        }
+        let point = state.to_viewport(coords);
+        if state.contains(point) {
+            Ok(point)
+        } else {
+            Err(ChaserError::msg(format!(
+                "Point ({}, {}) cannot be scrolled into view",
+                point.x, point.y
+            )))
+        }
+    }
+
+    /// Scroll a viewport point outside the viewport into view and return
+    /// where it ended up. Points inside are returned as they are.
+    pub(crate) async fn bring_into_view(&self, point: Point) -> Result<Point> {
+        let inside = |(width, height): (f64, f64)| {
+            point.x >= 0.0 && point.y >= 0.0 && point.x < width && point.y < height
+        };
+        if self.known_viewport().is_some_and(inside) {
+            return Ok(point);
+        }
+        let state = self.viewport_state().await?;
+        if state.contains(point) {
+            return Ok(point);
+        }
+        if point.x < 0.0 || point.x >= state.width {
+            return Err(ChaserError::msg(format!(
+                "Point ({}, {}) is beside the viewport",
+                point.x, point.y
+            )));
+        }
+        let target = Coordinates::Page(state.to_page(Coordinates::Viewport(point)));
+        self.scroll_into_view_coords(target).await
+    }
+
+    /// Move the cursor humanly to `coords`, scrolling first if needed.
+    pub async fn move_mouse_to(&self, coords: Coordinates) -> Result<()> {
+        let point = self.scroll_into_view_coords(coords).await?;
+        self.move_mouse_human(point.x, point.y).await
+    }
+
+    /// Humanized click at `coords`, scrolling first if needed.
+    pub async fn click_at(&self, coords: Coordinates) -> Result<()> {
+        let point = self.scroll_into_view_coords(coords).await?;
+        self.click_human(point.x, point.y).await
+    }
+
+    /// Page coordinates of the centre of `element`'s border box.
+    ///
+    /// Page coordinates stay valid while the page scrolls, so the result can
+    /// be passed to [`ChaserPage::click_at`] at any later point.
+    pub async fn element_coordinates(&self, element: &Element) -> Result<Coordinates> {
+        let bounds = element.bounding_box().await?;
+        if bounds.width * bounds.height <= 1.0 {
+            return Err(ChaserError::msg("Element has no visible box"));
+        }
+        let state = self.viewport_state().await?;
+        let viewport = state.from_layout(crate::layout::Point::new(
+            bounds.x + bounds.width / 2.0,
+            bounds.y + bounds.height / 2.0,
+        ));
+        Ok(Coordinates::Page(
+            state.to_page(Coordinates::Viewport(viewport)),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::page::Page;
+    use crate::transport::MockTransport;
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn state(scroll_y: f64) -> ViewportState {
+        ViewportState {
+            scroll_x: 10.0,
+            scroll_y,
+            width: 1280.0,
+            height: 800.0,
+            visual_offset_x: 0.0,
+            visual_offset_y: 0.0,
+            scale: 1.0,
+            device_pixel_ratio: 2.0,
+        }
+    }
+
+    #[test]
+    fn page_and_viewport_coordinates_convert_both_ways() {
+        let state = state(1500.0);
+        let page = Coordinates::page(410.0, 1800.0);
+        assert_eq!(state.to_viewport(page), Point { x: 400.0, y: 300.0 });
+        assert_eq!(
+            state.to_page(page),
+            Point {
+                x: 410.0,
+                y: 1800.0
+            }
+        );
+
+        let viewport = Coordinates::viewport(400.0, 300.0);
+        assert_eq!(state.to_viewport(viewport), Point { x: 400.0, y: 300.0 });
+        assert_eq!(
+            state.to_page(viewport),
+            Point {
+                x: 410.0,
+                y: 1800.0
+            }
+        );
+
+        assert!(state.contains(Point { x: 0.0, y: 0.0 }));
+        assert!(!state.contains(Point { x: 1280.0, y: 10.0 }));
+        assert!(!state.contains(Point { x: 10.0, y: -1.0 }));
+    }
+
+    #[test]
+    fn pinch_zoom_and_device_pixels_scale_points() {
+        let zoomed = ViewportState {
+            visual_offset_x: 100.0,
+            visual_offset_y: 50.0,
+            scale: 2.0,
+            ..state(0.0)
+        };
+        assert_eq!(
+            zoomed.from_layout(crate::layout::Point::new(150.0, 100.0)),
+            Point { x: 100.0, y: 100.0 }
+        );
+
+        let device = zoomed.to_device(Point { x: 12.5, y: 4.0 });
+        assert_eq!(device, Point { x: 25.0, y: 8.0 });
+        assert_eq!(zoomed.from_device(device), Point { x: 12.5, y: 4.0 });
+    }
+
+    fn layout_metrics(scroll_y: f64) -> Value {
+        let viewport = json!({
+            "offsetX": 0, "offsetY": 0, "pageX": 0, "pageY": scroll_y,
+            "clientWidth": 1280, "clientHeight": 800, "scale": 1, "zoom": 1
+        });
+        let layout = json!({ "pageX": 0, "pageY": scroll_y as i64, "clientWidth": 1280, "clientHeight": 800 });
+        let content = json!({ "x": 0, "y": 0, "width": 1280, "height": 6000 });
+        json!({
+            "layoutViewport": layout, "visualViewport": viewport, "contentSize": content,
+            "cssLayoutViewport": layout, "cssVisualViewport": viewport, "cssContentSize": content
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn page_targets_are_scrolled_into_the_middle_band() {
+        let mock = MockTransport::new();
+        let reads = Arc::new(AtomicUsize::new(0));
+        mock.respond_with("Page.getLayoutMetrics", {
+            let reads = reads.clone();
+            // scrolled down to the target after the first read
+            move |_| {
+                let scroll_y = match reads.fetch_add(1, Ordering::SeqCst) {
+                    0 => 0.0,
+                    _ => 4600.0,
+                };
+                Ok(layout_metrics(scroll_y))
+            }
+        });
+        let chaser = ChaserPage::new(Page::with_transport(mock.clone()));
+
+        let point = chaser
+            .scroll_into_view_coords(Coordinates::page(400.0, 5000.0))
+            .await
+            .unwrap();
+        assert_eq!(point, Point { x: 400.0, y: 400.0 });
+        assert!(mock
+            .commands_to("Input.dispatchMouseEvent")
+            .iter()
+            .any(|event| event["type"] == "mouseWheel"));
+
+        // already in the band, nothing to scroll
+        mock.clear();
+        let point = chaser
+            .scroll_into_view_coords(Coordinates::page(400.0, 4900.0))
+            .await
+            .unwrap();
+        assert_eq!(point, Point { x: 400.0, y: 300.0 });
+        assert!(mock.commands_to("Input.dispatchMouseEvent").is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn unreachable_points_are_errors() {
+        let mock = MockTransport::new();
+        // a page that never scrolls
+        mock.respond("Page.getLayoutMetrics", layout_metrics(0.0));
+        let chaser = ChaserPage::new(Page::with_transport(mock.clone()));
+
+        let err = chaser
+            .scroll_into_view_coords(Coordinates::page(400.0, 5000.0))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("cannot be scrolled into view"));
+
+        // viewport targets are never scrolled, wherever they are
+        mock.clear();
+        let point = chaser
+            .scroll_into_view_coords(Coordinates::viewport(400.0, 5.0))
+            .await
+            .unwrap();
+        assert_eq!(point, Point { x: 400.0, y: 5.0 });
+        assert!(mock.commands_to("Input.dispatchMouseEvent").is_empty());
+
+        let err = chaser
+            .bring_into_view(Point { x: 2000.0, y: 10.0 })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("beside the viewport"));
+    }
+}
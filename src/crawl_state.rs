@@ -0,0 +1,601 @@
+//! Checkpoint/resume support for long running crawls.
+//!
+//! [`CrawlState`] tracks every URL a crawl has seen together with its status,
+//! and a snapshot of each identity's session (profile, proxy and cookies).
+//! Every change is persisted immediately, so after a crash the crawl can be
+//! re-opened and continues with the URLs that were still pending, using the
+//! same profile identities it had before.
+//!
+//! Two backends are available:
+//! - an append-only JSON lines journal (always available)
+//! - an SQLite database (with the `sqlite` feature)
+//!
+//! # Example
+//!
+//! ```no_run
+//! use chaser_oxide::crawl_state::CrawlState;
+//! # fn run() -> std::io::Result<()> {
+//! let mut state = CrawlState::open_journal("crawl.journal")?;
+//! state.enqueue("https://example.com/")?;
+//! while let Some(url) = state.next_pending()? {
+//!     // ... visit url ...
+//!     state.mark_done(&url)?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::profiles::ChaserProfile;
+use anyhow::Result;
+use chromiumoxide_cdp::cdp::browser_protocol::network::{Cookie, CookieParam, TimeSinceEpoch};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Processing status of a URL.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum UrlStatus {
+    /// Discovered but not visited yet.
+    Pending,
+    /// Handed out by [`CrawlState::next_pending`]. URLs still in progress
+    /// when a crawl is re-opened are treated as pending again.
+    InProgress,
+    /// Visited successfully.
+    Done,
+    /// Visiting failed.
+    Failed { attempts: u32, error: String },
+}
+
+/// Persisted session of a crawl identity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    /// Caller chosen identity name, e.g. `"worker-3"`.
+    pub identity: String,
+    pub profile: ChaserProfile,
+    pub proxy: Option<String>,
+    pub cookies: Vec<Cookie>,
+}
+
+impl SessionSnapshot {
+    /// Capture the cookies of `page` for `identity`.
+    pub async fn capture(
+        identity: impl Into<String>,
+        page: &ChaserPage,
+        profile: ChaserProfile,
+        proxy: Option<String>,
+    ) -> Result<Self> {
+        Ok(Self {
+            identity: identity.into(),
+            profile,
+            proxy,
+            cookies: page.raw_page().get_cookies().await?,
+        })
+    }
+
+    /// Re-install the captured cookies into `page`.
+    ///
+    /// The profile itself is not applied here; pass [`SessionSnapshot::profile`]
+    /// to [`ChaserPage::apply_profile`] (or a pool assignment) first.
+    pub async fn restore(&self, page: &ChaserPage) -> Result<()> {
+        if self.cookies.is_empty() {
+            return Ok(());
+        }
+        let cookies = self
+            .cookies
+            .iter()
+            .map(|c| {
+                let mut param = CookieParam::new(c.name.clone(), c.value.clone());
+                param.domain = Some(c.domain.clone());
+                param.path = Some(c.path.clone());
+                param.secure = Some(c.secure);
+                param.http_only = Some(c.http_only);
+                param.same_site = c.same_site.clone();
+                if !c.session {
+                    param.expires = Some(TimeSinceEpoch::new(c.expires));
+                }
+                param
+            })
+            .collect();
+        page.raw_page().set_cookies(cookies).await?;
+        Ok(())
+    }
+}
+
+/// A single persisted change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Entry {
+    Url {
+        url: String,
+        #[serde(flatten)]
+        status: UrlStatus,
+        #[serde(default, skip_serializing_if = "is_zero")]
+        depth: u32,
+    },
+    Session(Box<SessionSnapshot>),
+}
+
+fn is_zero(depth: &u32) -> bool {
+    *depth == 0
+}
+
+trait StateBackend: Send {
+    fn load(&mut self) -> io::Result<Vec<Entry>>;
+
+    fn append(&mut self, entry: &Entry) -> io::Result<()>;
+
+    /// Replace the persisted state with exactly `entries`.
+    fn rewrite(&mut self, entries: &[Entry]) -> io::Result<()>;
+}
+
+/// Append-only JSON lines journal; the last entry for a key wins.
+struct JournalBackend {
+    path: PathBuf,
+    out: Option<BufWriter<File>>,
+}
+
+impl JournalBackend {
+    fn writer(&mut self) -> io::Result<&mut BufWriter<File>> {
+        if self.out.is_none() {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            self.out = Some(BufWriter::new(file));
+        }
+        Ok(self.out.as_mut().unwrap())
+    }
+}
+
+impl StateBackend for JournalBackend {
+    fn load(&mut self) -> io::Result<Vec<Entry>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut entries = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(entry) => entries.push(entry),
+                // a torn write from a crash only ever affects the last line
+                Err(e) => tracing::warn!("skipping unreadable crawl journal line: {}", e),
+            }
+        }
+        Ok(entries)
+    }
+
+    fn append(&mut self, entry: &Entry) -> io::Result<()> {
+        let out = self.writer()?;
+        serde_json::to_writer(&mut *out, entry)?;
+        out.write_all(b"\n")?;
+        out.flush()
+    }
+
+    fn rewrite(&mut self, entries: &[Entry]) -> io::Result<()> {
+        self.out = None;
+        let tmp = self.path.with_extension("compact");
+        {
+            let mut out = BufWriter::new(File::create(&tmp)?);
+            for entry in entries {
+                serde_json::to_writer(&mut out, entry)?;
+                out.write_all(b"\n")?;
+            }
+            out.flush()?;
+        }
+        fs::rename(tmp, &self.path)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use super::{Entry, StateBackend};
+    use rusqlite::{params, Connection};
+    use std::io;
+    use std::path::Path;
+
+    fn to_io(err: rusqlite::Error) -> io::Error {
+        io::Error::other(err)
+    }
+
+    pub(super) struct SqliteBackend {
+        conn: Connection,
+    }
+
+    impl SqliteBackend {
+        pub(super) fn open(path: &Path) -> io::Result<Self> {
+            let conn = Connection::open(path).map_err(to_io)?;
+            conn.execute_batch(
+                "PRAGMA journal_mode = WAL;
+                 CREATE TABLE IF NOT EXISTS urls (url TEXT PRIMARY KEY, entry TEXT NOT NULL);
+                 CREATE TABLE IF NOT EXISTS sessions (identity TEXT PRIMARY KEY, entry TEXT NOT NULL);",
+            )
+            .map_err(to_io)?;
+            Ok(Self { conn })
+        }
+    }
+
+    /// Insert `entry`, replacing the earlier one for its URL or identity.
+    fn upsert(conn: &Connection, entry: &Entry) -> io::Result<()> {
+        let json = serde_json::to_string(entry)?;
+        match entry {
+            Entry::Url { url, .. } => conn.execute(
+                "INSERT OR REPLACE INTO urls (url, entry) VALUES (?1, ?2)",
+                params![url, json],
+            ),
+            Entry::Session(session) => conn.execute(
+                "INSERT OR REPLACE INTO sessions (identity, entry) VALUES (?1, ?2)",
+                params![session.identity, json],
+            ),
+        }
+        .map_err(to_io)?;
+        Ok(())
+    }
+
+    impl StateBackend for SqliteBackend {
+        fn load(&mut self) -> io::Result<Vec<Entry>> {
+            let mut entries = Vec::new();
+            for table in ["urls", "sessions"] {
+                let mut stmt = self
+                    .conn
+                    .prepare(&format!("SELECT entry FROM {table} ORDER BY rowid"))
+                    .map_err(to_io)?;
+                let rows = stmt
+                    .query_map([], |row| row.get::<_, String>(0))
+                    .map_err(to_io)?;
+                for row in rows {
+                    entries.push(serde_json::from_str(&row.map_err(to_io)?)?);
+                }
+            }
+            Ok(entries)
+        }
+
+        fn append(&mut self, entry: &Entry) -> io::Result<()> {
+            upsert(&self.conn, entry)
+        }
+
+        fn rewrite(&mut self, entries: &[Entry]) -> io::Result<()> {
+            let tx = self.conn.transaction().map_err(to_io)?;
+            tx.execute_batch("DELETE FROM urls; DELETE FROM sessions;")
+                .map_err(to_io)?;
+            for entry in entries {
+                upsert(&tx, entry)?;
+            }
+            // dropping the transaction on an error rolls the deletes back
+            tx.commit().map_err(to_io)
+        }
+    }
+}
+
+/// Counts of URLs per status.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CrawlStats {
+    pub pending: usize,
+    pub in_progress: usize,
+    pub done: usize,
+    pub failed: usize,
+}
+
+/// Persistent record of a crawl's frontier and sessions.
+pub struct CrawlState {
+    urls: HashMap<String, UrlStatus>,
+    depths: HashMap<String, u32>,
+    queue: VecDeque<String>,
+    sessions: HashMap<String, SessionSnapshot>,
+    backend: Option<Box<dyn StateBackend>>,
+}
+
+impl std::fmt::Debug for CrawlState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CrawlState")
+            .field("stats", &self.stats())
+            .field("sessions", &self.sessions.len())
+            .field("persistent", &self.backend.is_some())
+            .finish()
+    }
+}
+
+impl CrawlState {
+    /// A state that is not persisted anywhere.
+    pub fn in_memory() -> Self {
+        Self {
+            urls: HashMap::new(),
+            depths: HashMap::new(),
+            queue: VecDeque::new(),
+            sessions: HashMap::new(),
+            backend: None,
+        }
+    }
+
+    /// Open (or create) a JSON lines journal at `path` and replay it.
+    pub fn open_journal(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::with_backend(Box::new(JournalBackend {
+            path: path.as_ref().to_path_buf(),
+            out: None,
+        }))
+    }
+
+    /// Open (or create) an SQLite database at `path` and load it.
+    #[cfg(feature = "sqlite")]
+    pub fn open_sqlite(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::with_backend(Box::new(sqlite::SqliteBackend::open(path.as_ref())?))
+    }
+
+    fn with_backend(mut backend: Box<dyn StateBackend>) -> io::Result<Self> {
+        let mut state = Self::in_memory();
+        for entry in backend.load()? {
+            match entry {
+                Entry::Url { url, status, depth } => {
+                    let status = match status {
+                        UrlStatus::InProgress => UrlStatus::Pending,
+                        other => other,
+                    };
+                    if depth > 0 {
+                        state.depths.insert(url.clone(), depth);
+                    }
+                    state.urls.insert(url, status);
+                }
+                Entry::Session(session) => {
+                    state.sessions.insert(session.identity.clone(), *session);
+                }
+            }
+        }
+        let mut pending: Vec<_> = state
+            .urls
+            .iter()
+            .filter(|(_, s)| **s == UrlStatus::Pending)
+            .map(|(u, _)| (state.depth(u), u.clone()))
+            .collect();
+        // resume breadth first
+        pending.sort();
+        let pending: Vec<_> = pending.into_iter().map(|(_, u)| u).collect();
+        state.queue = pending.into();
+        state.backend = Some(backend);
+        Ok(state)
+    }
+
+    fn persist(&mut self, entry: Entry) -> io::Result<()> {
+        match self.backend.as_mut() {
+            Some(backend) => backend.append(&entry),
+            None => Ok(()),
+        }
+    }
+
+    fn set_status(&mut self, url: &str, status: UrlStatus) -> io::Result<()> {
+        self.urls.insert(url.to_string(), status.clone());
+        self.persist(Entry::Url {
+            url: url.to_string(),
+            status,
+            depth: self.depth(url),
+        })
+    }
+
+    /// Add a URL to the frontier. Returns `false` if it was already known.
+    pub fn enqueue(&mut self, url: impl Into<String>) -> io::Result<bool> {
+        self.enqueue_at_depth(url, 0)
+    }
+
+    /// Add a URL discovered `depth` links away from a seed.
+    pub fn enqueue_at_depth(&mut self, url: impl Into<String>, depth: u32) -> io::Result<bool> {
+        let url = url.into();
+        if self.urls.contains_key(&url) {
+            return Ok(false);
+        }
+        if depth > 0 {
+            self.depths.insert(url.clone(), depth);
+        }
+        self.set_status(&url, UrlStatus::Pending)?;
+        self.queue.push_back(url);
+        Ok(true)
+    }
+
+    /// Take the next pending URL and mark it as in progress.
+    pub fn next_pending(&mut self) -> io::Result<Option<String>> {
+        while let Some(url) = self.queue.pop_front() {
+            if self.urls.get(&url) == Some(&UrlStatus::Pending) {
+                self.set_status(&url, UrlStatus::InProgress)?;
+                return Ok(Some(url));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn mark_done(&mut self, url: &str) -> io::Result<()> {
+        self.set_status(url, UrlStatus::Done)
+    }
+
+    /// Record a failed visit, counting attempts per URL.
+    pub fn mark_failed(&mut self, url: &str, error: impl Into<String>) -> io::Result<()> {
+        let attempts = match self.urls.get(url) {
+            Some(UrlStatus::Failed { attempts, .. }) => attempts + 1,
+            _ => 1,
+        };
+        self.set_status(
+            url,
+            UrlStatus::Failed {
+                attempts,
+                error: error.into(),
+            },
+        )
+    }
+
+    /// Put a failed URL back into the frontier.
+    pub fn retry(&mut self, url: &str) -> io::Result<()> {
+        if matches!(self.urls.get(url), Some(UrlStatus::Failed { .. })) {
+            self.set_status(url, UrlStatus::Pending)?;
+            self.queue.push_back(url.to_string());
+        }
+        Ok(())
+    }
+
+    pub fn status(&self, url: &str) -> Option<&UrlStatus> {
+        self.urls.get(url)
+    }
+
+    /// Link distance of `url` from the seeds it was discovered from.
+    pub fn depth(&self, url: &str) -> u32 {
+        self.depths.get(url).copied().unwrap_or(0)
+    }
+
+    /// Store (or replace) the session snapshot of an identity.
+    pub fn save_session(&mut self, snapshot: SessionSnapshot) -> io::Result<()> {
+        self.sessions
+            .insert(snapshot.identity.clone(), snapshot.clone());
+        self.persist(Entry::Session(Box::new(snapshot)))
+    }
+
+    pub fn session(&self, identity: &str) -> Option<&SessionSnapshot> {
+        self.sessions.get(identity)
+    }
+
+    pub fn sessions(&self) -> impl Iterator<Item = &SessionSnapshot> + '_ {
+        self.sessions.values()
+    }
+
+    pub fn stats(&self) -> CrawlStats {
+        let mut stats = CrawlStats::default();
+        for status in self.urls.values() {
+            match status {
+                UrlStatus::Pending => stats.pending += 1,
+                UrlStatus::InProgress => stats.in_progress += 1,
+                UrlStatus::Done => stats.done += 1,
+                UrlStatus::Failed { .. } => stats.failed += 1,
+            }
+        }
+        stats
+    }
+
+    /// Whether no URL is pending or in progress.
+    pub fn is_finished(&self) -> bool {
+        let stats = self.stats();
+        stats.pending == 0 && stats.in_progress == 0
+    }
+
+    /// Rewrite the backend with only the current state, dropping superseded
+    /// journal entries.
+    pub fn compact(&mut self) -> io::Result<()> {
+        let Some(backend) = self.backend.as_mut() else {
+            return Ok(());
+        };
+        let mut entries: Vec<Entry> = self
+            .urls
+            .iter()
+            .map(|(url, status)| Entry::Url {
+                url: url.clone(),
+                status: status.clone(),
+                depth: self.depths.get(url).copied().unwrap_or(0),
+            })
+            .collect();
+        entries.extend(
+            self.sessions
+                .values()
+                .cloned()
+                .map(Box::new)
+                .map(Entry::Session),
+        );
+        backend.rewrite(&entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn journal_resumes_in_progress_urls() {
+        let path =
+            std::env::temp_dir().join(format!("chaser-crawl-{}.journal", uuid::Uuid::new_v4()));
+        {
+            let mut state = CrawlState::open_journal(&path).unwrap();
+            state.enqueue("https://a.test/").unwrap();
+            state.enqueue("https://b.test/").unwrap();
+            state.enqueue("https://a.test/").unwrap();
+            let first = state.next_pending().unwrap().unwrap();
+            state.mark_done(&first).unwrap();
+            // crash while the second URL is being visited
+            state.next_pending().unwrap().unwrap();
+        }
+
+        let mut state = CrawlState::open_journal(&path).unwrap();
+        assert_eq!(state.stats().done, 1);
+        assert_eq!(
+            state.next_pending().unwrap().as_deref(),
+            Some("https://b.test/")
+        );
+        assert_eq!(state.next_pending().unwrap(), None);
+
+        state.compact().unwrap();
+        let state = CrawlState::open_journal(&path).unwrap();
+        assert_eq!(state.stats().pending, 1);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_resumes_and_compacts_atomically() {
+        let path = std::env::temp_dir().join(format!("chaser-crawl-{}.db", uuid::Uuid::new_v4()));
+        {
+            let mut state = CrawlState::open_sqlite(&path).unwrap();
+            state.enqueue("https://a.test/").unwrap();
+            state.enqueue_at_depth("https://b.test/", 2).unwrap();
+            let first = state.next_pending().unwrap().unwrap();
+            state.mark_failed(&first, "timeout").unwrap();
+            state.next_pending().unwrap().unwrap();
+        }
+
+        let mut state = CrawlState::open_sqlite(&path).unwrap();
+        assert_eq!(state.stats().failed, 1);
+        assert_eq!(state.depth("https://b.test/"), 2);
+        assert_eq!(
+            state.next_pending().unwrap().as_deref(),
+            Some("https://b.test/")
+        );
+        state.mark_done("https://b.test/").unwrap();
+        state.compact().unwrap();
+        drop(state);
+        let state = CrawlState::open_sqlite(&path).unwrap();
+        assert_eq!(state.stats().done, 1);
+        assert_eq!(state.stats().failed, 1);
+        drop(state);
+
+        // a write failing halfway through a compaction leaves the old state
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TRIGGER reject BEFORE INSERT ON urls WHEN NEW.url = 'https://c.test/'
+                 BEGIN SELECT RAISE(ABORT, 'rejected'); END;",
+            )
+            .unwrap();
+        let mut backend = sqlite::SqliteBackend::open(&path).unwrap();
+        let entries = [
+            Entry::Url {
+                url: "https://b.test/".into(),
+                status: UrlStatus::Pending,
+                depth: 0,
+            },
+            Entry::Url {
+                url: "https://c.test/".into(),
+                status: UrlStatus::Pending,
+                depth: 0,
+            },
+        ];
+        assert!(backend.rewrite(&entries).is_err());
+        drop(backend);
+        let state = CrawlState::open_sqlite(&path).unwrap();
+        assert_eq!(state.stats().done, 1);
+        assert_eq!(state.stats().failed, 1);
+        assert_eq!(state.stats().pending, 0);
+        drop(state);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = fs::remove_file(format!("{}{suffix}", path.display()));
+        }
+    }
+}
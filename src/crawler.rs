@@ -0,0 +1,520 @@
+//! A frontier based crawler driving a [`ChaserPage`].
+//!
+//! The crawler starts from seed URLs (or a sitemap), follows links that pass
+//! the configured domain and pattern rules up to a depth limit, waits a
+//! politeness delay between requests to the same host and hands every loaded
+//! page to an extraction callback. The frontier lives in a [`CrawlState`], so
+//! a crawl backed by a journal or SQLite file resumes where it stopped.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::crawl_state::CrawlState;
+//! use chaser_oxide::crawler::{Crawler, CrawlerConfig};
+//!
+//! let config = CrawlerConfig::builder()
+//!     .seed("https://example.com/")
+//!     .max_depth(2)
+//!     .deny("*/logout*")
+//!     .build();
+//! let mut crawler = Crawler::new(config).with_state(CrawlState::open_journal("crawl.journal")?);
+//!
+//! crawler
+//!     .run(&page, |crawled| async move {
+//!         let title = crawled.page.evaluate("document.title").await?;
+//!         println!("{} {:?}", crawled.url, title);
+//!         Ok(())
+//!     })
+//!     .await?;
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::clock;
+use crate::crawl_state::{CrawlState, CrawlStats, UrlStatus};
+use anyhow::{anyhow, Result};
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::time::{Duration, Instant};
+use url::Url;
+
+const LINKS_SCRIPT: &str = "Array.from(document.querySelectorAll('a[href]'), a => a.href)";
+
+const SITEMAP_SCRIPT: &str = r#"({
+    index: document.documentElement.localName === 'sitemapindex',
+    locs: Array.from(document.getElementsByTagName('loc'), l => l.textContent.trim())
+})"#;
+
+/// Configuration of a [`Crawler`].
+#[derive(Debug, Clone)]
+pub struct CrawlerConfig {
+    seeds: Vec<String>,
+    sitemaps: Vec<String>,
+    max_depth: u32,
+    max_pages: Option<usize>,
+    same_domain: bool,
+    allow: Vec<String>,
+    deny: Vec<String>,
+    delay: Duration,
+    jitter: Duration,
+    max_attempts: u32,
+}
+
+impl CrawlerConfig {
+    pub fn builder() -> CrawlerConfigBuilder {
+        CrawlerConfigBuilder::default()
+    }
+}
+
+/// Builder for [`CrawlerConfig`].
+#[derive(Debug, Clone)]
+pub struct CrawlerConfigBuilder {
+    config: CrawlerConfig,
+}
+
+impl Default for CrawlerConfigBuilder {
+    fn default() -> Self {
+        Self {
+            config: CrawlerConfig {
+                seeds: Vec::new(),
+                sitemaps: Vec::new(),
+                max_depth: 3,
+                max_pages: None,
+                same_domain: true,
+                allow: Vec::new(),
+                deny: Vec::new(),
+                delay: Duration::from_secs(2),
+                jitter: Duration::from_secs(1),
+                max_attempts: 2,
+            },
+        }
+    }
+}
+
+impl CrawlerConfigBuilder {
+    /// Add a start URL
+    pub fn seed(mut self, url: impl Into<String>) -> Self {
+        self.config.seeds.push(url.into());
+        self
+    }
+
+    /// Add several start URLs
+    pub fn seeds<I, S>(mut self, urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.seeds.extend(urls.into_iter().map(Into::into));
+        self
+    }
+
+    /// Seed the frontier with every URL of a sitemap (or sitemap index)
+    pub fn sitemap(mut self, url: impl Into<String>) -> Self {
+        self.config.sitemaps.push(url.into());
+        self
+    }
+
+    /// Maximum link distance from a seed (default: 3, seeds are depth 0)
+    pub fn max_depth(mut self, depth: u32) -> Self {
+        self.config.max_depth = depth;
+        self
+    }
+
+    /// Stop after this many pages were visited
+    pub fn max_pages(mut self, pages: usize) -> Self {
+        self.config.max_pages = Some(pages);
+        self
+    }
+
+    /// Only follow links to the hosts of the seeds (default: true)
+    pub fn same_domain(mut self, same_domain: bool) -> Self {
+        self.config.same_domain = same_domain;
+        self
+    }
+
+    /// Only follow URLs matching this pattern (`*` matches anything).
+    ///
+    /// When no allow pattern is set every URL is allowed.
+    pub fn allow(mut self, pattern: impl Into<String>) -> Self {
+        self.config.allow.push(pattern.into());
+        self
+    }
+
+    /// Never follow URLs matching this pattern (`*` matches anything)
+    pub fn deny(mut self, pattern: impl Into<String>) -> Self {
+        self.config.deny.push(pattern.into());
+        self
+    }
+
+    /// Minimum delay between two requests to the same host (default: 2s)
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.config.delay = delay;
+        self
+    }
+
+    /// Random extra delay added on top of [`CrawlerConfigBuilder::delay`] (default: 1s)
+    pub fn jitter(mut self, jitter: Duration) -> Self {
+        self.config.jitter = jitter;
+        self
+    }
+
+    /// How often a failing URL is tried before giving up (default: 2)
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.config.max_attempts = attempts.max(1);
+        self
+    }
+
+    pub fn build(self) -> CrawlerConfig {
+        self.config
+    }
+}
+
+/// A page handed to the extraction callback.
+#[derive(Debug, Clone)]
+pub struct CrawledPage {
+    /// The URL taken from the frontier.
+    pub url: String,
+    /// Link distance from the seed.
+    pub depth: u32,
+    /// Links found on the page (absolute, without fragment).
+    pub links: Vec<String>,
+    /// The page, still showing `url`.
+    pub page: ChaserPage,
+}
+
+/// Crawls a site with a single [`ChaserPage`].
+#[derive(Debug)]
+pub struct Crawler {
+    config: CrawlerConfig,
+    state: CrawlState,
+    hosts: Vec<String>,
+    last_visit: HashMap<String, Instant>,
+}
+
+impl Crawler {
+    /// Create a crawler with an in-memory frontier.
+    pub fn new(config: CrawlerConfig) -> Self {
+        let hosts = config
+            .seeds
+            .iter()
+            .chain(&config.sitemaps)
+            .filter_map(|s| Url::parse(s).ok())
+            .filter_map(|u| u.host_str().map(str::to_string))
+            .collect();
+        Self {
+            config,
+            state: CrawlState::in_memory(),
+            hosts,
+            last_visit: HashMap::new(),
+        }
+    }
+
+    /// Keep the frontier in `state`, resuming whatever it already contains.
+    pub fn with_state(mut self, state: CrawlState) -> Self {
+        self.state = state;
+        self
+    }
+
+    pub fn state(&self) -> &CrawlState {
+        &self.state
+    }
+
+    pub fn into_state(self) -> CrawlState {
+        self.state
+    }
+
+    /// Crawl until the frontier is exhausted or the page limit is reached.
+    ///
+    /// `extract` is called once for every successfully loaded page. An error
+    /// from navigation or from `extract` marks the URL as failed; it is
+    /// retried up to the configured number of attempts.
+    pub async fn run<F, Fut>(&mut self, page: &ChaserPage, mut extract: F) -> Result<CrawlStats>
+    where
+        F: FnMut(CrawledPage) -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        for seed in self.config.seeds.clone() {
+            if let Some(url) = normalize(&seed, None) {
+                self.state.enqueue(url)?;
+            }
+        }
+        for sitemap in self.config.sitemaps.clone() {
+            self.load_sitemap(page, &sitemap).await?;
+        }
+
+        let mut visited = 0;
+        while self.config.max_pages.map_or(true, |max| visited < max) {
+            let Some(url) = self.state.next_pending()? else {
+                break;
+            };
+            visited += 1;
+
+            let depth = self.state.depth(&url);
+            match self.visit(page, &url, depth, &mut extract).await {
+                Ok(()) => self.state.mark_done(&url)?,
+                Err(e) => {
+                    tracing::debug!("crawling {} failed: {}", url, e);
+                    self.state.mark_failed(&url, e.to_string())?;
+                    if let Some(UrlStatus::Failed { attempts, .. }) = self.state.status(&url) {
+                        if *attempts < self.config.max_attempts {
+                            self.state.retry(&url)?;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(self.state.stats())
+    }
+
+    async fn visit<F, Fut>(
+        &mut self,
+        page: &ChaserPage,
+        url: &str,
+        depth: u32,
+        extract: &mut F,
+    ) -> Result<()>
+    where
+        F: FnMut(CrawledPage) -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        self.wait_politely(url).await;
+        page.goto(url).await?;
+
+        let links = links_of(page).await.unwrap_or_default();
+        if depth < self.config.max_depth {
+            for link in &links {
+                if self.should_follow(link) {
+                    self.state.enqueue_at_depth(link.clone(), depth + 1)?;
+                }
+            }
+        }
+
+        extract(CrawledPage {
+            url: url.to_string(),
+            depth,
+            links,
+            page: page.clone(),
+        })
+        .await
+    }
+
+    async fn load_sitemap(&mut self, page: &ChaserPage, sitemap: &str) -> Result<()> {
+        let mut pending = vec![sitemap.to_string()];
+        // an index listing itself, or two listing each other, would loop
+        let mut visited = HashSet::new();
+        while let Some(sitemap) = pending.pop() {
+            if !visited.insert(sitemap.clone()) {
+                continue;
+            }
+            self.wait_politely(&sitemap).await;
+            page.goto(&sitemap).await?;
+            let value = page
+                .evaluate(SITEMAP_SCRIPT)
+                .await?
+                .ok_or_else(|| anyhow!("Sitemap {} returned nothing", sitemap))?;
+            let locs: Vec<String> = serde_json::from_value(value["locs"].clone())?;
+            if value["index"].as_bool().unwrap_or(false) {
+                pending.extend(locs);
+                continue;
+            }
+            for loc in locs {
+                if let Some(url) = normalize(&loc, None) {
+                    if self.should_follow(&url) {
+                        self.state.enqueue(url)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn should_follow(&self, url: &str) -> bool {
+        let Ok(parsed) = Url::parse(url) else {
+            return false;
+        };
+        if self.config.same_domain
+            && !self.hosts.is_empty()
+            && !parsed
+                .host_str()
+                .is_some_and(|h| self.hosts.iter().any(|s| s == h))
+        {
+            return false;
+        }
+        if self.config.deny.iter().any(|p| wildcard_match(p, url)) {
+            return false;
+        }
+        self.config.allow.is_empty() || self.config.allow.iter().any(|p| wildcard_match(p, url))
+    }
+
+    async fn wait_politely(&mut self, url: &str) {
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+            .unwrap_or_default();
+        if let Some(last) = self.last_visit.get(&host) {
+            let jitter = if self.config.jitter.is_zero() {
+                Duration::ZERO
+            } else {
+                rand::thread_rng().gen_range(Duration::ZERO..self.config.jitter)
+            };
+            let wait = (self.config.delay + jitter).saturating_sub(clock::elapsed(*last));
+            if !wait.is_zero() {
+                clock::sleep(wait).await;
+            }
+        }
+        self.last_visit.insert(host, clock::now());
+    }
+}
+
+async fn links_of(page: &ChaserPage) -> Result<Vec<String>> {
+    let base = page.url().await?;
+    let hrefs: Vec<String> = match page.evaluate(LINKS_SCRIPT).await? {
+        Some(value) => serde_json::from_value(value)?,
+        None => Vec::new(),
+    };
+    let mut links: Vec<String> = hrefs
+        .iter()
+        .filter_map(|href| normalize(href, base.as_deref()))
+        .collect();
+    links.sort();
+    links.dedup();
+    Ok(links)
+}
+
+/// Resolve `href` against `base`, keeping only http(s) URLs without fragment.
+fn normalize(href: &str, base: Option<&str>) -> Option<String> {
+    let mut url = match base.and_then(|b| Url::parse(b).ok()) {
+        Some(base) => base.join(href).ok()?,
+        None => Url::parse(href).ok()?,
+    };
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    url.set_fragment(None);
+    Some(url.into())
+}
+
+/// Match `text` against a pattern where `*` matches any run of characters.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // no `*` in the pattern
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::page::Page;
+    use crate::transport::MockTransport;
+    use serde_json::json;
+
+    #[test]
+    fn wildcards_match_any_run_of_characters() {
+        assert!(wildcard_match("https://a.test/", "https://a.test/"));
+        assert!(!wildcard_match("https://a.test/", "https://a.test/x"));
+        assert!(wildcard_match("*/logout*", "https://a.test/logout?next=/"));
+        assert!(wildcard_match(
+            "https://*.test/*.pdf",
+            "https://a.test/docs/x.pdf"
+        ));
+        assert!(!wildcard_match(
+            "https://*.test/*.pdf",
+            "https://a.test/x.pdf.html"
+        ));
+        assert!(wildcard_match("*", ""));
+        // the last part must not overlap the ones before it
+        assert!(!wildcard_match("*ab*ab", "xab"));
+        assert!(wildcard_match("*ab*ab", "xabab"));
+    }
+
+    #[test]
+    fn normalize_resolves_and_drops_fragments_and_other_schemes() {
+        let base = Some("https://a.test/dir/page.html");
+        assert_eq!(
+            normalize("../other#top", base).as_deref(),
+            Some("https://a.test/other")
+        );
+        assert_eq!(
+            normalize("https://b.test/x?y=1", base).as_deref(),
+            Some("https://b.test/x?y=1")
+        );
+        assert_eq!(normalize("mailto:a@a.test", base), None);
+        assert_eq!(normalize("javascript:void(0)", base), None);
+        assert_eq!(normalize("/relative", None), None);
+    }
+
+    #[test]
+    fn should_follow_applies_domain_deny_and_allow_rules() {
+        let crawler = Crawler::new(
+            CrawlerConfig::builder()
+                .seed("https://a.test/")
+                .allow("*/products/*")
+                .deny("*/products/*/reviews*")
+                .build(),
+        );
+        assert!(crawler.should_follow("https://a.test/products/1"));
+        assert!(!crawler.should_follow("https://a.test/products/1/reviews"));
+        assert!(!crawler.should_follow("https://a.test/about"));
+        assert!(!crawler.should_follow("https://b.test/products/1"));
+        assert!(!crawler.should_follow("not a url"));
+
+        let anywhere = Crawler::new(
+            CrawlerConfig::builder()
+                .seed("https://a.test/")
+                .same_domain(false)
+                .build(),
+        );
+        assert!(anywhere.should_follow("https://b.test/"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sitemap_indexes_listing_each_other_are_loaded_once() {
+        let mock = MockTransport::new();
+        let mut loads = 0;
+        mock.respond_with("Runtime.evaluate", move |_| {
+            loads += 1;
+            let value = match loads {
+                // the index lists itself and a second index listing it back
+                1 => json!({ "index": true, "locs": [
+                    "https://a.test/sitemap.xml", "https://a.test/more.xml"
+                ] }),
+                2 => json!({ "index": true, "locs": ["https://a.test/sitemap.xml"] }),
+                _ => json!({ "index": false, "locs": [] }),
+            };
+            Ok(json!({ "result": { "type": "object", "value": value } }))
+        });
+        let page = ChaserPage::new(Page::with_transport(mock.clone()));
+        let mut crawler = Crawler::new(CrawlerConfig::builder().build());
+
+        crawler
+            .load_sitemap(&page, "https://a.test/sitemap.xml")
+            .await
+            .unwrap();
+        let loaded: Vec<_> = mock
+            .commands_to("Page.navigate")
+            .into_iter()
+            .map(|params| params["url"].clone())
+            .collect();
+        assert_eq!(
+            loaded,
+            [
+                json!("https://a.test/sitemap.xml"),
+                json!("https://a.test/more.xml")
+            ]
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! Detector pages bundled with the crate.
+//!
+//! Public detector sites change without notice and go down. The pages
+//! here run the checks those suites are known for, headless tells,
+//! fingerprint consistency and CreepJS-style lie detection, from a copy
+//! compiled into the crate, so a run against them is reproducible and its
+//! results can be compared across crate and Chrome versions. The fixture server serves them under
+//! `/detectors/<name>` and [`ChaserPage::run_detector`] reads their results
+//! into a [`DetectionReport`].
+//!
+//! Every page writes its results as JSON into `#detector-result` once all
+//! checks are done:
+//!
+//! ```json
+//! {"suite": "headless", "version": "1", "checks": [{"name": "webdriver", "passed": true, "detail": "false"}]}
+//! ```
+//!
+//! A page's version changes whenever one of its checks does.
+//!
+//! [`ChaserPage::run_detector`]: crate::chaser::ChaserPage::run_detector
+//! [`DetectionReport`]: crate::diagnostics::DetectionReport
+
+/// A bundled detector page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Detector {
+    /// Tells of headless and automated Chrome, like bot.sannysoft.com:
+    /// `navigator.webdriver`, a missing `window.chrome`, empty plugins,
+    /// software WebGL, inconsistent permissions, patched natives.
+    Headless,
+    /// Whether the fingerprint agrees with itself: user agent against
+    /// platform and client hints, time zone against offset, page against
+    /// iframe and worker, canvas stability, natives that are not native.
+    Fingerprint,
+    /// CreepJS-style lie detection: natives probed for the traces patching
+    /// leaves (a `toString` that is not native, a `prototype`, own keys,
+    /// getters that work without an instance, proxies), summed up into a
+    /// trust score. Besides the checks it writes `{version, trustScore,
+    /// lies, errors}` into `#creep-result`, read by
+    /// [`ChaserPage::run_creep`](crate::chaser::ChaserPage::run_creep).
+    Creep,
+}
+
+impl Detector {
+    pub const ALL: [Detector; 3] = [Detector::Headless, Detector::Fingerprint, Detector::Creep];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Detector::Headless => "headless",
+            Detector::Fingerprint => "fingerprint",
+            Detector::Creep => "creep",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.name() == name)
+    }
+
+    /// Where the fixture server serves the page.
+    pub fn path(&self) -> String {
+        format!("/detectors/{}", self.name())
+    }
+
+    /// The version the page reports.
+    pub fn version(&self) -> &'static str {
+        match self {
+            Detector::Headless => "1",
+            Detector::Fingerprint => "1",
+            Detector::Creep => "1",
+        }
+    }
+
+    pub fn html(&self) -> &'static str {
+        match self {
+            Detector::Headless => include_str!("detectors/headless.html"),
+            Detector::Fingerprint => include_str!("detectors/fingerprint.html"),
+            Detector::Creep => include_str!("detectors/creep.html"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_report_their_suite_and_version() {
+        for detector in Detector::ALL {
+            let html = detector.html();
+            assert!(html.contains(&format!("suite: '{}'", detector.name())));
+            assert!(html.contains(&format!("version: '{}'", detector.version())));
+            assert!(html.contains("id=\"detector-result\""));
+            assert_eq!(Detector::from_name(detector.name()), Some(detector));
+        }
+        assert_eq!(Detector::Headless.path(), "/detectors/headless");
+    }
+}
@@ -0,0 +1,677 @@
+//! Self-checks of the input pipeline and of host rendering.
+//!
+//! Detectors do not only look at where a click lands but at the events
+//! around it: `isTrusted`, the pointer fields of `pointerdown`, whether the
+//! cursor moved before the button went down, how long the button was held.
+//! [`ChaserPage::probe_click_at`] records everything a page sees during one
+//! humanized click and [`ClickProbeReport::issues`] lists what a detector
+//! would flag, so regressions show up in a test instead of on a detector
+//! page.
+//!
+//! Some giveaways come from the host rather than the input: a macOS profile
+//! rendered on Linux shows classic 15px scrollbars where a Mac has overlay
+//! ones. [`ChaserPage::check_scrollbars`] measures them like a site would.
+//!
+//! Fingerprint-level checks run on the [bundled detector
+//! pages](crate::detectors): [`ChaserPage::run_detector`] loads one and
+//! returns its verdicts as a [`DetectionReport`], without depending on a
+//! public detector site being up and unchanged. [`ChaserPage::run_creep`]
+//! reads the trust score of the CreepJS-style page, one number to track
+//! across profile changes.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let report = chaser.diagnose_trusted_clicks().await?;
+//! assert!(report.passed(), "{:#?}", report.issues);
+//!
+//! let server = FixtureServer::start().await?;
+//! let report = chaser.run_detector(&server.detector_url(Detector::Headless)).await?;
+//! assert!(report.passed(), "{:#?}", report.failures());
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::{ChaserError, ChaserResult as Result};
+use crate::profiles::ChaserProfile;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+/// Records the mouse and pointer events of the next click; resolves shortly
+/// after the `click` event (or after 10s without one).
+const RECORDER_SCRIPT: &str = r#"new Promise(resolve => {
+    const kinds = ['pointermove', 'mousemove', 'pointerdown', 'mousedown',
+                   'pointerup', 'mouseup', 'click'];
+    const events = [];
+    const record = e => events.push({
+        kind: e.type,
+        trusted: e.isTrusted,
+        timestamp: e.timeStamp,
+        clientX: e.clientX, clientY: e.clientY,
+        movementX: e.movementX || 0, movementY: e.movementY || 0,
+        buttons: e.buttons, detail: e.detail,
+        pointerId: e.pointerId ?? null,
+        pointerType: e.pointerType ?? null,
+        pressure: e.pressure ?? null,
+        isPrimary: e.isPrimary ?? null
+    });
+    const finish = () => {
+        kinds.forEach(k => window.removeEventListener(k, record, true));
+        resolve(events);
+    };
+    kinds.forEach(k => window.addEventListener(k, record, true));
+    window.addEventListener('click', () => setTimeout(finish, 250), { capture: true, once: true });
+    setTimeout(finish, 10000);
+})"#;
+
+/// Layout width of the vertical scrollbar of a scrolling box.
+const SCROLLBAR_SCRIPT: &str = r#"(() => {
+    const probe = document.createElement('div');
+    probe.style.cssText = 'position:absolute;top:-200px;width:100px;height:100px;overflow:scroll';
+    document.documentElement.appendChild(probe);
+    const width = probe.offsetWidth - probe.clientWidth;
+    probe.remove();
+    return width;
+})()"#;
+
+/// The results a detector page writes into `#detector-result`, once all
+/// its checks are done.
+const DETECTOR_RESULT_SCRIPT: &str =
+    "document.getElementById('detector-result')?.textContent || ''";
+
+/// The score the creep detector writes into `#creep-result`.
+const CREEP_RESULT_SCRIPT: &str = "document.getElementById('creep-result')?.textContent || ''";
+
+/// One event as the page saw it.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedEvent {
+    pub kind: String,
+    pub trusted: bool,
+    /// `event.timeStamp` in milliseconds.
+    pub timestamp: f64,
+    pub client_x: f64,
+    pub client_y: f64,
+    pub movement_x: f64,
+    pub movement_y: f64,
+    pub buttons: i64,
+    pub detail: i64,
+    pub pointer_id: Option<i64>,
+    pub pointer_type: Option<String>,
+    pub pressure: Option<f64>,
+    pub is_primary: Option<bool>,
+}
+
+/// The events of one probed click and what looks automated about them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClickProbeReport {
+    pub target: (f64, f64),
+    pub events: Vec<RecordedEvent>,
+    pub issues: Vec<String>,
+}
+
+impl ClickProbeReport {
+    /// Analyse recorded events of a click aimed at `target`.
+    pub fn new(target: (f64, f64), events: Vec<RecordedEvent>) -> Self {
+        let issues = analyze(target, &events);
+        Self {
+            target,
+            events,
+            issues,
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// One check of a detector page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DetectorCheck {
+    pub name: String,
+    pub passed: bool,
+    /// What the check saw, e.g. the WebGL renderer.
+    pub detail: String,
+}
+
+/// The verdicts of one detector page run.
+///
+/// Reports serialize stably: [`to_json`](Self::to_json) orders the checks
+/// by name, so a baseline saved with [`save`](Self::save) and checked into
+/// a repository only changes when a verdict or detail does.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DetectionReport {
+    /// The detector, e.g. `headless`.
+    pub suite: String,
+    /// The version of the detector page.
+    pub version: String,
+    pub checks: Vec<DetectorCheck>,
+}
+
+impl DetectionReport {
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|c| c.passed)
+    }
+
+    pub fn failures(&self) -> Vec<&DetectorCheck> {
+        self.checks.iter().filter(|c| !c.passed).collect()
+    }
+
+    pub fn check(&self, name: &str) -> Option<&DetectorCheck> {
+        self.checks.iter().find(|c| c.name == name)
+    }
+
+    /// Pretty-printed JSON with the checks sorted by name.
+    pub fn to_json(&self) -> String {
+        let mut sorted = self.clone();
+        sorted.checks.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut json = serde_json::to_string_pretty(&sorted).unwrap_or_default();
+        json.push('\n');
+        json
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Write [`to_json`](Self::to_json) to `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_json())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        Self::from_json(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// What got worse since `baseline`: checks that fail now but passed or
+    /// did not exist then, and checks of `baseline` this run no longer has.
+    pub fn regressions(&self, baseline: &DetectionReport) -> Vec<Regression> {
+        let mut regressions = Vec::new();
+        for check in self.checks.iter().filter(|c| !c.passed) {
+            match baseline.check(&check.name) {
+                Some(before) if !before.passed => {}
+                before => regressions.push(Regression::Failing {
+                    name: check.name.clone(),
+                    before: before.map(|b| b.detail.clone()),
+                    now: check.detail.clone(),
+                }),
+            }
+        }
+        for before in &baseline.checks {
+            if self.check(&before.name).is_none() {
+                regressions.push(Regression::Missing {
+                    name: before.name.clone(),
+                });
+            }
+        }
+        regressions.sort_by(|a, b| a.name().cmp(b.name()));
+        regressions
+    }
+
+    /// Panic with a diff against `baseline` if any check
+    /// [regressed](Self::regressions), for use in CI after bumping the
+    /// crate or Chrome. The message also lists checks that got fixed and
+    /// passing checks whose detail changed, which often explain a
+    /// regression.
+    ///
+    /// # Example
+    ///
+    /// ```should_panic
+    /// use chaser_oxide::diagnostics::DetectionReport;
+    ///
+    /// let baseline = DetectionReport::from_json(
+    ///     r#"{"suite":"headless","version":"1","checks":[
+    ///         {"name":"webgl","passed":true,"detail":"Intel Iris"}]}"#,
+    /// ).unwrap();
+    /// let current = DetectionReport::from_json(
+    ///     r#"{"suite":"headless","version":"1","checks":[
+    ///         {"name":"webgl","passed":false,"detail":"SwiftShader"}]}"#,
+    /// ).unwrap();
+    /// // stealth regressions in headless since the baseline:
+    /// //   - webgl: passed ("Intel Iris") -> failed ("SwiftShader")
+    /// current.assert_no_regressions(&baseline);
+    /// ```
+    ///
+    /// With a baseline kept in the repository:
+    ///
+    /// ```ignore
+    /// let report = chaser.run_detector(&server.detector_url(Detector::Headless)).await?;
+    /// report.assert_no_regressions(&DetectionReport::load("tests/baselines/headless.json")?);
+    /// ```
+    #[track_caller]
+    pub fn assert_no_regressions(&self, baseline: &DetectionReport) {
+        assert_eq!(
+            self.suite, baseline.suite,
+            "comparing a {} report with a {} baseline",
+            self.suite, baseline.suite
+        );
+        let regressions = self.regressions(baseline);
+        if !regressions.is_empty() {
+            panic!("{}", self.diff(baseline, &regressions));
+        }
+    }
+
+    fn diff(&self, baseline: &DetectionReport, regressions: &[Regression]) -> String {
+        let mut diff = format!("stealth regressions in {} since the baseline", self.suite);
+        if self.version != baseline.version {
+            diff += &format!(
+                " (detector version {} -> {})",
+                baseline.version, self.version
+            );
+        }
+        diff.push(':');
+        for regression in regressions {
+            diff += &format!("\n  - {regression}");
+        }
+        let mut checks: Vec<&DetectorCheck> = self.checks.iter().collect();
+        checks.sort_by(|a, b| a.name.cmp(&b.name));
+        let fixed: Vec<_> = checks
+            .iter()
+            .filter(|c| c.passed && baseline.check(&c.name).is_some_and(|b| !b.passed))
+            .collect();
+        if !fixed.is_empty() {
+            diff += "\nfixed since the baseline:";
+            for check in fixed {
+                diff += &format!("\n  + {}", check.name);
+            }
+        }
+        let changed: Vec<_> = checks
+            .iter()
+            .filter_map(|c| {
+                let before = baseline.check(&c.name)?;
+                (c.passed && before.passed && c.detail != before.detail).then_some((c, before))
+            })
+            .collect();
+        if !changed.is_empty() {
+            diff += "\nstill passing, but changed:";
+            for (check, before) in changed {
+                diff += &format!(
+                    "\n  ~ {}: {:?} -> {:?}",
+                    check.name, before.detail, check.detail
+                );
+            }
+        }
+        diff
+    }
+}
+
+/// A check that got worse since a baseline report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Regression {
+    /// Fails now. `before` is the baseline's detail, `None` for a check the
+    /// baseline did not have.
+    Failing {
+        name: String,
+        before: Option<String>,
+        now: String,
+    },
+    /// In the baseline but not in this run.
+    Missing { name: String },
+}
+
+impl Regression {
+    pub fn name(&self) -> &str {
+        match self {
+            Regression::Failing { name, .. } | Regression::Missing { name } => name,
+        }
+    }
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Regression::Failing {
+                name,
+                before: Some(before),
+                now,
+            } => write!(f, "{name}: passed ({before:?}) -> failed ({now:?})"),
+            Regression::Failing {
+                name,
+                before: None,
+                now,
+            } => write!(f, "{name}: new check, failed ({now:?})"),
+            Regression::Missing { name } => write!(f, "{name}: not run any more"),
+        }
+    }
+}
+
+/// A native API that looks patched.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Lie {
+    /// E.g. `Navigator.hardwareConcurrency` or
+    /// `HTMLCanvasElement.prototype.toDataURL`.
+    pub api: String,
+    /// The tests it failed, e.g. `to-string` or `illegal-invocation`.
+    pub reasons: Vec<String>,
+}
+
+/// An API the creep detector could not inspect.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ApiError {
+    pub api: String,
+    pub message: String,
+}
+
+/// The outcome of the [creep detector](crate::detectors::Detector::Creep).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreepReport {
+    /// The version of the detector page.
+    pub version: String,
+    /// 0 to 100; every lie costs 5, every error 2 and `navigator.webdriver`
+    /// 25.
+    pub trust_score: f64,
+    pub lies: Vec<Lie>,
+    pub errors: Vec<ApiError>,
+}
+
+impl CreepReport {
+    /// Whether `api` was caught lying, for any reason.
+    pub fn lied(&self, api: &str) -> bool {
+        self.lies.iter().any(|l| l.api == api)
+    }
+}
+
+fn analyze(target: (f64, f64), events: &[RecordedEvent]) -> Vec<String> {
+    let mut issues = Vec::new();
+    let first = |kind: &str| events.iter().position(|e| e.kind == kind);
+
+    for event in events.iter().filter(|e| !e.trusted) {
+        issues.push(format!("{} event is not trusted", event.kind));
+    }
+
+    let sequence = ["pointerdown", "mousedown", "pointerup", "mouseup", "click"];
+    let positions: Vec<_> = sequence.iter().map(|kind| first(kind)).collect();
+    for (kind, position) in sequence.iter().zip(&positions) {
+        if position.is_none() {
+            issues.push(format!("no {kind} event"));
+        }
+    }
+    let found: Vec<usize> = positions.iter().flatten().copied().collect();
+    if found.windows(2).any(|w| w[0] > w[1]) {
+        issues.push("button events arrived out of order".to_string());
+    }
+
+    let Some(down) = first("pointerdown").or(first("mousedown")) else {
+        return issues;
+    };
+    let moves: Vec<&RecordedEvent> = events[..down]
+        .iter()
+        .filter(|e| e.kind == "mousemove")
+        .collect();
+    match moves.len() {
+        0 => issues.push("click without preceding mouse movement".to_string()),
+        n if n < 3 => issues.push(format!("only {n} mouse moves before the click")),
+        _ => {}
+    }
+    if !moves.is_empty()
+        && moves
+            .iter()
+            .all(|e| e.movement_x == 0.0 && e.movement_y == 0.0)
+    {
+        issues.push("movementX/movementY are always zero".to_string());
+    }
+
+    let pointers: Vec<&RecordedEvent> = events
+        .iter()
+        .filter(|e| e.kind.starts_with("pointer"))
+        .collect();
+    if let Some(pointer) = pointers.first() {
+        if pointers.iter().any(|e| e.pointer_id != pointer.pointer_id) {
+            issues.push("pointerId changes between pointer events".to_string());
+        }
+        if pointers.iter().any(|e| e.is_primary == Some(false)) {
+            issues.push("pointer is not primary".to_string());
+        }
+        for e in &pointers {
+            if !matches!(e.pointer_type.as_deref(), Some("mouse" | "touch" | "pen")) {
+                issues.push(format!("{} has pointerType {:?}", e.kind, e.pointer_type));
+            }
+        }
+    }
+    for e in events {
+        match e.kind.as_str() {
+            "pointerdown"
+                if e.pressure != Some(0.5) && e.pointer_type.as_deref() == Some("mouse") =>
+            {
+                issues.push(format!(
+                    "pointerdown pressure is {:?}, expected 0.5",
+                    e.pressure
+                ))
+            }
+            "mousedown" | "pointerdown" if e.buttons & 1 == 0 => {
+                issues.push(format!("{} reports buttons={}", e.kind, e.buttons))
+            }
+            "click" if e.detail != 1 => issues.push(format!("click detail is {}", e.detail)),
+            _ => {}
+        }
+    }
+
+    if let (Some(down), Some(up)) = (first("mousedown"), first("mouseup")) {
+        let held = events[up].timestamp - events[down].timestamp;
+        if held < 15.0 {
+            issues.push(format!("button held for only {held:.0}ms"));
+        }
+    }
+    if let Some(click) = first("click").map(|i| &events[i]) {
+        let distance = (click.client_x - target.0).hypot(click.client_y - target.1);
+        if distance > 10.0 {
+            issues.push(format!("click landed {distance:.0}px from the target"));
+        }
+    }
+    issues
+}
+
+impl ChaserPage {
+    /// Perform a humanized click at viewport position (`x`, `y`) on the
+    /// current page while recording the events it produces.
+    ///
+    /// The click is real: whatever is at that position gets clicked.
+    pub async fn probe_click_at(&self, x: f64, y: f64) -> Result<ClickProbeReport> {
+        let record = self.evaluate_stealth(RECORDER_SCRIPT);
+        let click = async {
+            // let the recorder install its listeners first
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            self.click_human(x, y).await
+        };
+        let (recorded, clicked) = futures::join!(record, click);
+        clicked?;
+        let events =
+            recorded?.ok_or_else(|| ChaserError::msg("Click recorder returned nothing"))?;
+        Ok(ClickProbeReport::new(
+            (x, y),
+            serde_json::from_value(events)?,
+        ))
+    }
+
+    /// Probe the click path on a blank page.
+    ///
+    /// Navigates this page to `about:blank` and clicks near its centre.
+    pub async fn diagnose_trusted_clicks(&self) -> Result<ClickProbeReport> {
+        self.goto("about:blank").await?;
+        let (width, height) = self
+            .evaluate_stealth("[window.innerWidth, window.innerHeight]")
+            .await?
+            .and_then(|v| serde_json::from_value::<(f64, f64)>(v).ok())
+            .ok_or_else(|| ChaserError::msg("Could not read the viewport size"))?;
+        self.probe_click_at(width * 0.5, height * 0.45).await
+    }
+
+    /// Navigate to the detector page at `url`, wait for its checks to
+    /// finish and return their verdicts. `url` is normally a [bundled
+    /// detector](crate::detectors) served by the fixture server, but any
+    /// page writing the same JSON into `#detector-result` works.
+    ///
+    /// Gives up with [`ChaserError::Timeout`] after [`Timeouts::wait`].
+    ///
+    /// [`Timeouts::wait`]: crate::timeouts::Timeouts::wait
+    pub async fn run_detector(&self, url: &str) -> Result<DetectionReport> {
+        self.goto(url).await?;
+        let result = self.wait_for_function(DETECTOR_RESULT_SCRIPT).await?;
+        let result = result.as_str().unwrap_or_default();
+        serde_json::from_str(result)
+            .map_err(|e| ChaserError::msg(format!("unreadable detector results from {url}: {e}")))
+    }
+
+    /// Navigate to the creep detector at `url`, e.g.
+    /// `server.detector_url(Detector::Creep)`, wait for it to score the
+    /// page and return the score with the lies and errors behind it.
+    ///
+    /// Gives up with [`ChaserError::Timeout`] after [`Timeouts::wait`].
+    ///
+    /// [`Timeouts::wait`]: crate::timeouts::Timeouts::wait
+    pub async fn run_creep(&self, url: &str) -> Result<CreepReport> {
+        self.goto(url).await?;
+        let result = self.wait_for_function(CREEP_RESULT_SCRIPT).await?;
+        serde_json::from_str(result.as_str().unwrap_or_default())
+            .map_err(|e| ChaserError::msg(format!("unreadable creep score from {url}: {e}")))
+    }
+
+    /// Layout width of a vertical scrollbar on the current page, 0 for
+    /// overlay scrollbars.
+    pub async fn scrollbar_width(&self) -> Result<u32> {
+        self.evaluate_stealth(SCROLLBAR_SCRIPT)
+            .await?
+            .and_then(|v| v.as_u64())
+            .map(|width| width as u32)
+            .ok_or_else(|| ChaserError::msg("Could not measure the scrollbar"))
+    }
+
+    /// Compare the scrollbars of the current page with those of `profile`'s
+    /// OS, returning the mismatch a width probe would reveal.
+    pub async fn check_scrollbars(&self, profile: &ChaserProfile) -> Result<Option<String>> {
+        let measured = self.scrollbar_width().await?;
+        let expected = profile.scrollbar_style();
+        Ok((measured != expected.width()).then(|| {
+            format!(
+                "scrollbars are {measured}px wide, {} has {expected:?} scrollbars",
+                profile.os().platform()
+            )
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(kind: &str, timestamp: f64, movement: f64, buttons: i64) -> RecordedEvent {
+        let pointer = kind.starts_with("pointer");
+        RecordedEvent {
+            kind: kind.to_string(),
+            trusted: true,
+            timestamp,
+            client_x: 100.0,
+            client_y: 100.0,
+            movement_x: movement,
+            movement_y: movement,
+            buttons,
+            detail: if kind == "click" { 1 } else { 0 },
+            pointer_id: pointer.then_some(1),
+            pointer_type: pointer.then(|| "mouse".to_string()),
+            pressure: pointer.then_some(if buttons == 1 { 0.5 } else { 0.0 }),
+            is_primary: pointer.then_some(true),
+        }
+    }
+
+    #[test]
+    fn flags_a_bare_instant_click() {
+        let human: Vec<_> = (0..5)
+            .map(|i| event("mousemove", i as f64 * 10.0, 3.0, 0))
+            .chain([
+                event("pointerdown", 100.0, 0.0, 1),
+                event("mousedown", 100.0, 0.0, 1),
+                event("pointerup", 170.0, 0.0, 0),
+                event("mouseup", 170.0, 0.0, 0),
+                event("click", 170.0, 0.0, 0),
+            ])
+            .collect();
+        assert!(ClickProbeReport::new((100.0, 100.0), human).passed());
+
+        let bot = vec![
+            event("pointerdown", 100.0, 0.0, 0),
+            event("mousedown", 100.0, 0.0, 0),
+            event("pointerup", 100.0, 0.0, 0),
+            event("mouseup", 100.0, 0.0, 0),
+            event("click", 100.0, 0.0, 0),
+        ];
+        let report = ClickProbeReport::new((100.0, 100.0), bot);
+        assert!(report
+            .issues
+            .contains(&"click without preceding mouse movement".to_string()));
+        assert!(report.issues.iter().any(|i| i.starts_with("button held")));
+    }
+
+    #[test]
+    fn reads_detector_results() {
+        let report: DetectionReport = serde_json::from_str(
+            r#"{"suite":"headless","version":"1","checks":[
+                {"name":"webdriver","passed":true,"detail":"false"},
+                {"name":"webgl","passed":false,"detail":"Google Inc. / SwiftShader"}]}"#,
+        )
+        .unwrap();
+        assert!(!report.passed());
+        assert_eq!(report.failures()[0].name, "webgl");
+        assert_eq!(report.check("webdriver").map(|c| c.passed), Some(true));
+
+        // checks come out sorted, so a saved baseline round-trips unchanged
+        let json = report.to_json();
+        assert!(json.find("\"webdriver\"") < json.find("\"webgl\""));
+        assert_eq!(DetectionReport::from_json(&json).unwrap().to_json(), json);
+
+        let creep: CreepReport = serde_json::from_str(
+            r#"{"version":"1","trustScore":93,"lies":[
+                {"api":"Navigator.hardwareConcurrency","reasons":["to-string","own-keys"]}],
+                "errors":[{"api":"Intl.DateTimeFormat","message":"missing"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(creep.trust_score, 93.0);
+        assert!(creep.lied("Navigator.hardwareConcurrency"));
+        assert_eq!(creep.errors[0].message, "missing");
+    }
+
+    #[test]
+    fn regressions_against_a_baseline() {
+        let check = |name: &str, passed: bool, detail: &str| DetectorCheck {
+            name: name.to_string(),
+            passed,
+            detail: detail.to_string(),
+        };
+        let report = |checks| DetectionReport {
+            suite: "headless".to_string(),
+            version: "1".to_string(),
+            checks,
+        };
+        let baseline = report(vec![
+            check("webgl", true, "Intel Iris"),
+            check("plugins-length", false, "0"),
+            check("languages", true, "en-US"),
+            check("media-devices", true, "audioinput"),
+        ]);
+        let current = report(vec![
+            check("webgl", false, "SwiftShader"),
+            check("plugins-length", true, "5"),
+            check("languages", true, "de-DE,de"),
+            check("connection-rtt", false, "0"),
+        ]);
+        let regressions = current.regressions(&baseline);
+        assert_eq!(
+            regressions.iter().map(Regression::name).collect::<Vec<_>>(),
+            ["connection-rtt", "media-devices", "webgl"]
+        );
+        assert!(baseline.regressions(&baseline).is_empty());
+
+        let diff = current.diff(&baseline, &regressions);
+        assert!(diff.contains(r#"webgl: passed ("Intel Iris") -> failed ("SwiftShader")"#));
+        assert!(diff.contains("+ plugins-length"));
+        assert!(diff.contains(r#"~ languages: "en-US" -> "de-DE,de""#));
+        let panicked =
+            std::panic::catch_unwind(|| current.assert_no_regressions(&baseline)).unwrap_err();
+        assert_eq!(panicked.downcast_ref::<String>(), Some(&diff));
+    }
+}
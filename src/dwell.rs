@@ -0,0 +1,139 @@
+//! Staying on a page as long as its content takes to read.
+//!
+//! Bots either leave a page the moment the data is extracted or sit on every
+//! page for the same fixed time. [`ChaserPage::dwell_for_content`] measures
+//! the visible text and images through the isolated world, estimates how
+//! long a person would spend skimming them, and idles for that long with
+//! small mouse drifts and reading scrolls, so nothing looks frozen.
+//!
+//! # Example
+//!
+//! ```ignore
+//! chaser.goto("https://example.com/article").await?;
+//! let spent = chaser.dwell_for_content().await?;
+//! println!("read for {spent:?}");
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::clock;
+use crate::error::{ChaserError, ChaserResult as Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::Duration;
+
+/// Counts the words of rendered text and the images large enough to look at.
+const CONTENT_STATS_SCRIPT: &str = r#"(() => {
+    const text = document.body ? document.body.innerText : '';
+    const words = (text.match(/\S+/g) || []).length;
+    const images = Array.from(document.images).filter(img => {
+        const r = img.getBoundingClientRect();
+        return r.width >= 80 && r.height >= 80;
+    }).length;
+    return [words, images];
+})()"#;
+
+const MIN_DWELL: Duration = Duration::from_secs(4);
+const MAX_DWELL: Duration = Duration::from_secs(180);
+
+/// How much there is to read and look at on a page.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ContentStats {
+    /// Words of rendered text.
+    pub words: u32,
+    /// Images of at least 80x80 CSS pixels.
+    pub images: u32,
+}
+
+impl ContentStats {
+    /// Sample a plausible dwell time for this content.
+    ///
+    /// Readers skim: only a quarter to a half of the text is read, at 180-300
+    /// words per minute, plus one to two seconds per image (at most twenty).
+    /// The result is kept between 4 seconds and 3 minutes.
+    pub fn estimate_dwell(&self, rng: &mut impl Rng) -> Duration {
+        let wpm = rng.gen_range(180.0..300.0);
+        let read = rng.gen_range(0.25..0.5);
+        let reading = self.words as f64 * read / wpm * 60.0;
+        let looking: f64 = (0..self.images.min(20))
+            .map(|_| rng.gen_range(1.0..2.0))
+            .sum();
+        Duration::from_secs_f64(reading + looking).clamp(MIN_DWELL, MAX_DWELL)
+    }
+}
+
+impl ChaserPage {
+    /// Measure the content of the current page.
+    pub async fn content_stats(&self) -> Result<ContentStats> {
+        let (words, images) = self
+            .evaluate_stealth(CONTENT_STATS_SCRIPT)
+            .await?
+            .and_then(|v| serde_json::from_value::<(u32, u32)>(v).ok())
+            .ok_or_else(|| ChaserError::msg("Could not measure the page content"))?;
+        Ok(ContentStats { words, images })
+    }
+
+    /// Stay on the current page for as long as its content takes to skim,
+    /// drifting the mouse and scrolling now and then. Returns the time spent.
+    pub async fn dwell_for_content(&self) -> Result<Duration> {
+        let stats = self.content_stats().await?;
+        let duration = stats.estimate_dwell(&mut StdRng::from_entropy());
+        self.dwell_for(duration).await?;
+        Ok(duration)
+    }
+
+    /// Idle on the page for `duration` with micro-interactions: small mouse
+    /// drifts, short reading scrolls and still pauses.
+    ///
+    /// Stops early with [`ChaserError::Cancelled`] when the page's
+    /// cancellation token fires.
+    pub async fn dwell_for(&self, duration: Duration) -> Result<()> {
+        let mut rng = StdRng::from_entropy();
+        let deadline = clock::now() + duration;
+        loop {
+            let left = deadline.saturating_duration_since(clock::now());
+            let pause = Duration::from_millis(rng.gen_range(600..2_500));
+            if left <= pause {
+                return self.pause(left).await;
+            }
+            self.pause(pause).await?;
+
+            match rng.gen_range(0..10) {
+                0..=4 => {
+                    let pos = self.current_mouse_position();
+                    let dx = rng.gen_range(20.0..120.0) * if rng.gen() { 1.0 } else { -1.0 };
+                    let dy = rng.gen_range(-60.0..60.0);
+                    self.move_mouse_human(pos.x + dx, pos.y + dy).await?;
+                }
+                5..=7 => self.scroll_human(rng.gen_range(80..400)).await?,
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dwell_grows_with_content_within_bounds() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let empty = ContentStats::default().estimate_dwell(&mut rng);
+        assert_eq!(empty, MIN_DWELL);
+
+        let article = ContentStats {
+            words: 1_500,
+            images: 4,
+        }
+        .estimate_dwell(&mut rng);
+        // 375-750 words read at 180-300 wpm, plus 4-8s of images
+        assert!(article >= Duration::from_secs(79) && article <= Duration::from_secs(258));
+
+        let huge = ContentStats {
+            words: 100_000,
+            images: 500,
+        }
+        .estimate_dwell(&mut rng);
+        assert_eq!(huge, MAX_DWELL);
+    }
+}
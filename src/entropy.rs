@@ -0,0 +1,418 @@
+//! How rare a profile's fingerprint is.
+//!
+//! Every attribute a profile claims narrows down the crowd it hides in. A
+//! Windows machine with 1920x1080, 8 cores and Intel graphics is one of
+//! millions; 16 cores with a GTX 1660 on a 1440x900 screen may be the only
+//! one a site has ever seen. [`ChaserProfile::entropy_report`] scores each
+//! attribute against embedded market-share tables (approximate shares of
+//! Chrome users, conditioned on the OS, and the core count on the GPU) and
+//! adds the surprisal up into an estimate of how many users share the whole
+//! fingerprint.
+//!
+//! The tables are coarse and the attributes are otherwise treated as
+//! independent, so read the result as an order of
+//! magnitude, not a measurement.
+//!
+//! # Example
+//!
+//! ```
+//! use chaser_oxide::profiles::{ChaserProfile, Gpu};
+//!
+//! let common = ChaserProfile::windows().gpu(Gpu::IntelIrisXe).build();
+//! let odd = ChaserProfile::windows()
+//!     .gpu(Gpu::NvidiaGTX1660)
+//!     .cpu_cores(16)
+//!     .screen(1440, 900)
+//!     .build();
+//! assert!(odd.entropy_report().one_in() > 10.0 * common.entropy_report().one_in());
+//! ```
+
+use crate::profiles::{ChaserProfile, Gpu, Os};
+
+/// Chrome users worldwide, the crowd [`EntropyReport::expected_matches`]
+/// divides up.
+pub const CHROME_USERS: f64 = 3.4e9;
+
+/// Share assumed for values missing from a table.
+const UNLISTED: f64 = 0.005;
+
+/// Fingerprints rarer than one in this many users are flagged.
+const RARE_ONE_IN: f64 = 1e6;
+
+/// One scored attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeEntropy {
+    pub name: &'static str,
+    pub value: String,
+    /// Estimated share of users with this value.
+    pub share: f64,
+    /// Surprisal, `-log2(share)`.
+    pub bits: f64,
+}
+
+/// The result of [`ChaserProfile::entropy_report`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntropyReport {
+    pub attributes: Vec<AttributeEntropy>,
+    /// Sum of the attributes' surprisal.
+    pub total_bits: f64,
+}
+
+impl EntropyReport {
+    /// Roughly one in how many users has this exact fingerprint.
+    pub fn one_in(&self) -> f64 {
+        self.total_bits.exp2()
+    }
+
+    /// Estimated number of Chrome users sharing the fingerprint.
+    pub fn expected_matches(&self) -> f64 {
+        CHROME_USERS / self.one_in()
+    }
+
+    /// The attribute contributing the most bits.
+    pub fn rarest(&self) -> Option<&AttributeEntropy> {
+        self.attributes
+            .iter()
+            .max_by(|a, b| a.bits.total_cmp(&b.bits))
+    }
+
+    /// Whether the fingerprint is rarer than one in a million.
+    pub fn is_rare(&self) -> bool {
+        self.one_in() > RARE_ONE_IN
+    }
+
+    /// Attributes held by fewer than 1% of users, rarest first.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut rare: Vec<&AttributeEntropy> =
+            self.attributes.iter().filter(|a| a.share < 0.01).collect();
+        rare.sort_by(|a, b| b.bits.total_cmp(&a.bits));
+        rare.into_iter()
+            .map(|a| {
+                format!(
+                    "{} {} is shared by ~{:.2}% of users",
+                    a.name,
+                    a.value,
+                    a.share * 100.0
+                )
+            })
+            .collect()
+    }
+}
+
+fn lookup<K: PartialEq>(table: &[(K, f64)], key: K) -> f64 {
+    table
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map_or(UNLISTED, |(_, share)| *share)
+}
+
+fn os_share(os: Os) -> f64 {
+    match os {
+        Os::Windows => 0.38,
+        Os::Android => 0.40,
+        Os::MacOSArm => 0.06,
+        Os::MacOSIntel => 0.03,
+        Os::Ios => 0.07,
+        Os::Linux => 0.02,
+    }
+}
+
+fn screen_shares(os: Os) -> &'static [((u32, u32), f64)] {
+    match os {
+        Os::Windows => &[
+            ((1920, 1080), 0.35),
+            ((1536, 864), 0.12),
+            ((1366, 768), 0.10),
+            ((2560, 1440), 0.08),
+            ((1600, 900), 0.04),
+            ((1440, 900), 0.03),
+            ((1280, 720), 0.03),
+            ((1920, 1200), 0.03),
+            ((3840, 2160), 0.03),
+            ((1680, 1050), 0.02),
+            ((1280, 1024), 0.02),
+        ],
+        Os::MacOSIntel | Os::MacOSArm => &[
+            ((1440, 900), 0.20),
+            ((1470, 956), 0.15),
+            ((1512, 982), 0.12),
+            ((1920, 1080), 0.10),
+            ((1728, 1117), 0.08),
+            ((1280, 800), 0.08),
+            ((2560, 1440), 0.07),
+            ((1680, 1050), 0.05),
+            ((1536, 960), 0.04),
+        ],
+        Os::Linux => &[
+            ((1920, 1080), 0.45),
+            ((2560, 1440), 0.10),
+            ((1366, 768), 0.08),
+            ((1920, 1200), 0.05),
+            ((3840, 2160), 0.05),
+        ],
+        Os::Android => &[
+            ((360, 800), 0.15),
+            ((412, 915), 0.10),
+            ((393, 873), 0.08),
+            ((384, 854), 0.06),
+            ((412, 892), 0.05),
+        ],
+        Os::Ios => &[
+            ((390, 844), 0.20),
+            ((393, 852), 0.20),
+            ((430, 932), 0.12),
+            ((414, 896), 0.10),
+            ((428, 926), 0.08),
+            ((375, 812), 0.08),
+            ((375, 667), 0.07),
+        ],
+    }
+}
+
+fn core_shares(os: Os) -> &'static [(u32, f64)] {
+    match os {
+        Os::Windows => &[
+            (8, 0.28),
+            (4, 0.15),
+            (12, 0.15),
+            (16, 0.15),
+            (6, 0.10),
+            (2, 0.04),
+            (20, 0.04),
+            (24, 0.04),
+            (32, 0.03),
+        ],
+        Os::MacOSArm => &[
+            (8, 0.35),
+            (10, 0.25),
+            (12, 0.15),
+            (14, 0.08),
+            (11, 0.05),
+            (16, 0.05),
+        ],
+        Os::MacOSIntel => &[(8, 0.35), (4, 0.30), (12, 0.15), (16, 0.15)],
+        Os::Linux => &[(8, 0.30), (4, 0.20), (16, 0.15), (12, 0.12), (32, 0.05)],
+        Os::Android => &[(8, 0.80), (4, 0.10), (6, 0.05), (9, 0.03)],
+        Os::Ios => &[(6, 0.70), (4, 0.15), (2, 0.10)],
+    }
+}
+
+/// Share of the OS's users with `gpu`; GPUs the OS cannot have are all but
+/// unique.
+fn gpu_share(os: Os, gpu: Gpu) -> f64 {
+    let table: &[(Gpu, f64)] = match os {
+        Os::Windows => &[
+            (Gpu::IntelIrisXe, 0.12),
+            (Gpu::IntelUHD630, 0.06),
+            (Gpu::NvidiaGTX1660, 0.03),
+            (Gpu::NvidiaRTX3080, 0.015),
+            (Gpu::NvidiaRTX4080, 0.008),
+            (Gpu::AmdRadeonRX6800, 0.005),
+        ],
+        Os::Linux => &[
+            (Gpu::IntelUHD630, 0.08),
+            (Gpu::IntelIrisXe, 0.08),
+            (Gpu::NvidiaGTX1660, 0.03),
+            (Gpu::NvidiaRTX3080, 0.02),
+            (Gpu::AmdRadeonRX6800, 0.02),
+            (Gpu::NvidiaRTX4080, 0.01),
+        ],
+        Os::MacOSArm => &[
+            (Gpu::AppleM1Pro, 0.08),
+            (Gpu::AppleM2Max, 0.03),
+            (Gpu::AppleM4Max, 0.02),
+        ],
+        // Intel Macs run Intel or AMD graphics; Apple GPUs here contradict the OS
+        Os::MacOSIntel => &[(Gpu::IntelUHD630, 0.30)],
+        Os::Android => &[(Gpu::Adreno740, 0.05), (Gpu::MaliG715, 0.03)],
+        Os::Ios => &[(Gpu::AppleMobile, 1.0)],
+    };
+    table
+        .iter()
+        .find(|(g, _)| *g == gpu)
+        .map_or(1e-5, |(_, share)| *share)
+}
+
+/// Share of the OS's users with `gpu` that have `cores`. Apple chips and
+/// phone SoCs fix the core count; discrete and integrated PC graphics make
+/// some counts much less likely than the OS-wide table says.
+fn cores_given_gpu(os: Os, gpu: Gpu, cores: u32) -> f64 {
+    let across_os = lookup(core_shares(os), cores);
+    match gpu {
+        Gpu::AppleM1Pro if matches!(cores, 8 | 10) => 0.5,
+        Gpu::AppleM2Max if cores == 12 => 0.9,
+        Gpu::AppleM4Max if matches!(cores, 14 | 16) => 0.5,
+        Gpu::AppleM1Pro | Gpu::AppleM2Max | Gpu::AppleM4Max => 0.001,
+        Gpu::MaliG715 | Gpu::Adreno740 if matches!(cores, 8 | 9) => 0.8,
+        Gpu::MaliG715 | Gpu::Adreno740 => across_os * 0.3,
+        Gpu::AppleMobile => across_os,
+        // integrated graphics live in laptops and office machines
+        Gpu::IntelUHD630 | Gpu::IntelIrisXe => {
+            across_os
+                * match cores {
+                    0..=12 => 1.0,
+                    13..=16 => 0.1,
+                    _ => 0.03,
+                }
+        }
+        Gpu::NvidiaGTX1660 => {
+            across_os
+                * match cores {
+                    0..=3 => 0.1,
+                    4..=12 => 1.0,
+                    13..=16 => 0.15,
+                    _ => 0.04,
+                }
+        }
+        Gpu::NvidiaRTX3080 | Gpu::NvidiaRTX4080 | Gpu::AmdRadeonRX6800 => {
+            across_os
+                * match cores {
+                    0..=4 => 0.04,
+                    5..=7 => 0.15,
+                    _ => 1.0,
+                }
+        }
+    }
+}
+
+fn memory_shares(os: Os) -> &'static [(u64, f64)] {
+    // keyed by deviceMemory in quarters of a GB
+    if os.is_mobile() {
+        &[(32, 0.45), (16, 0.35), (8, 0.15), (4, 0.05)]
+    } else {
+        &[(32, 0.80), (16, 0.15), (8, 0.04), (4, 0.01)]
+    }
+}
+
+fn dpr_shares(os: Os) -> &'static [(u32, f64)] {
+    // keyed by devicePixelRatio in percent
+    match os {
+        Os::Windows => &[
+            (100, 0.55),
+            (125, 0.20),
+            (150, 0.15),
+            (200, 0.05),
+            (175, 0.03),
+        ],
+        Os::MacOSIntel | Os::MacOSArm => &[(200, 0.85), (100, 0.15)],
+        Os::Linux => &[(100, 0.85), (200, 0.08), (150, 0.04)],
+        Os::Android => &[
+            (300, 0.30),
+            (263, 0.15),
+            (275, 0.15),
+            (200, 0.15),
+            (350, 0.10),
+        ],
+        Os::Ios => &[(300, 0.75), (200, 0.25)],
+    }
+}
+
+fn locale_share(locale: &str) -> f64 {
+    const TABLE: &[(&str, f64)] = &[
+        ("en-US", 0.30),
+        ("zh-CN", 0.08),
+        ("es-ES", 0.03),
+        ("es-MX", 0.03),
+        ("pt-BR", 0.05),
+        ("en-GB", 0.04),
+        ("de-DE", 0.04),
+        ("fr-FR", 0.03),
+        ("ja-JP", 0.03),
+        ("ru-RU", 0.03),
+        ("en-IN", 0.03),
+        ("it-IT", 0.02),
+        ("ko-KR", 0.02),
+        ("id-ID", 0.02),
+        ("tr-TR", 0.02),
+        ("pl-PL", 0.015),
+        ("nl-NL", 0.01),
+        ("en-CA", 0.01),
+        ("en-AU", 0.01),
+    ];
+    lookup(TABLE, locale)
+}
+
+fn attribute(name: &'static str, value: String, share: f64) -> AttributeEntropy {
+    let share = share.clamp(1e-9, 1.0);
+    AttributeEntropy {
+        name,
+        value,
+        share,
+        bits: -share.log2(),
+    }
+}
+
+impl ChaserProfile {
+    /// Score how common this profile's fingerprint is, see
+    /// [`crate::entropy`].
+    pub fn entropy_report(&self) -> EntropyReport {
+        let os = self.os();
+        let screen = (self.screen_width(), self.screen_height());
+        let memory = (self.device_memory() * 4.0) as u64;
+        let dpr = (self.device_pixel_ratio() * 100.0).round() as u32;
+        let attributes = vec![
+            attribute("os", format!("{os:?}"), os_share(os)),
+            attribute(
+                "screen",
+                format!("{}x{}", screen.0, screen.1),
+                lookup(screen_shares(os), screen),
+            ),
+            attribute(
+                "devicePixelRatio",
+                self.device_pixel_ratio().to_string(),
+                lookup(dpr_shares(os), dpr),
+            ),
+            attribute(
+                "hardwareConcurrency",
+                self.cpu_cores().to_string(),
+                cores_given_gpu(os, self.gpu(), self.cpu_cores()),
+            ),
+            attribute(
+                "deviceMemory",
+                self.device_memory().to_string(),
+                lookup(memory_shares(os), memory),
+            ),
+            attribute(
+                "gpu",
+                self.gpu().renderer().to_string(),
+                gpu_share(os, self.gpu()),
+            ),
+            attribute(
+                "locale",
+                self.locale().to_string(),
+                locale_share(self.locale()),
+            ),
+        ];
+        let total_bits = attributes.iter().map(|a| a.bits).sum();
+        EntropyReport {
+            attributes,
+            total_bits,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_are_common_and_odd_mixes_are_flagged() {
+        for profile in [
+            ChaserProfile::windows().gpu(Gpu::IntelIrisXe).build(),
+            ChaserProfile::macos_arm().build(),
+            ChaserProfile::android().build(),
+        ] {
+            let report = profile.entropy_report();
+            assert!(!report.is_rare(), "{profile}: one in {}", report.one_in());
+        }
+
+        let odd = ChaserProfile::macos_arm()
+            .gpu(Gpu::NvidiaRTX4080)
+            .cpu_cores(32)
+            .build()
+            .entropy_report();
+        assert!(odd.is_rare());
+        assert_eq!(odd.rarest().unwrap().name, "gpu");
+        assert!(odd.warnings().len() >= 2);
+    }
+}
@@ -152,3 +152,178 @@ impl fmt::Debug for BrowserStderr {
             .finish()
     }
 }
+
+pub type ChaserResult<T> = std::result::Result<T, ChaserError>;
+
+/// Why a [`ChaserPage`](crate::chaser::ChaserPage) operation failed.
+///
+/// The variants separate failures worth retrying (timeouts, a detached
+/// target, network errors) from ones that call for a different identity
+/// (anti-bot blocks, captchas, a rejected proxy), see
+/// [`is_retryable`](Self::is_retryable) and
+/// [`needs_rotation`](Self::needs_rotation).
+#[derive(Debug, Error)]
+pub enum ChaserError {
+    /// A CDP command failed.
+    #[error("{0}")]
+    Cdp(CdpError),
+    /// The operation did not finish in time.
+    #[error("{0} timed out")]
+    Timeout(String),
+    /// The page or its target was closed or detached.
+    #[error("Page was closed or detached")]
+    Detached,
+    /// The page's cancellation token fired; held input was released.
+    #[error("Operation was cancelled")]
+    Cancelled,
+    /// No element matches the selector.
+    #[error("No element matches {0}")]
+    ElementNotFound(String),
+    /// Navigation failed with a network error such as `net::ERR_NAME_NOT_RESOLVED`.
+    #[error("Navigation to {url} failed: {reason}")]
+    Navigation { url: String, reason: String },
+    /// The site served an anti-bot block or challenge page.
+    #[error("Blocked by {vendor}")]
+    BlockedByAntiBot { vendor: String },
+    /// The site wants a captcha solved.
+    #[error("{provider} captcha required")]
+    CaptchaRequired { provider: String },
+    /// The element could not be clicked in time.
+    #[error("Not actionable: {0}")]
+    NotActionable(crate::actionability::Unactionable),
+    /// The site answered 429 Too Many Requests.
+    #[error("Rate limited by {0}")]
+    RateLimited(String),
+    /// The proxy rejected its credentials.
+    #[error("Proxy authentication failed: {0}")]
+    ProxyAuthFailed(String),
+    /// The proxy could not be reached or refused the tunnel.
+    #[error("Proxy unreachable: {0}")]
+    ProxyUnreachable(String),
+    /// The element being typed into lost focus, e.g. to an overlay.
+    #[error("Focus moved from {expected} to {now}")]
+    FocusLost { expected: String, now: String },
+    /// A script threw an exception.
+    #[error("Script error: {0}")]
+    Script(String),
+    /// Any other failure, see [`ChaserError::msg`].
+    #[error("{0}")]
+    Other(String),
+}
+
+impl ChaserError {
+    /// An [`Other`](Self::Other) error carrying `msg`.
+    pub fn msg(msg: impl Into<String>) -> Self {
+        ChaserError::Other(msg.into())
+    }
+
+    /// Whether trying the same thing again may succeed.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ChaserError::Timeout(_)
+                | ChaserError::Detached
+                | ChaserError::Navigation { .. }
+                | ChaserError::RateLimited(_)
+                | ChaserError::ProxyUnreachable(_)
+        )
+    }
+
+    /// Whether the profile or proxy is burnt for this site and should be
+    /// rotated.
+    pub fn needs_rotation(&self) -> bool {
+        matches!(
+            self,
+            ChaserError::BlockedByAntiBot { .. }
+                | ChaserError::CaptchaRequired { .. }
+                | ChaserError::ProxyAuthFailed(_)
+                | ChaserError::ProxyUnreachable(_)
+        )
+    }
+
+    /// Classify the `errorText` of a failed navigation to `url`.
+    pub(crate) fn navigation(url: &str, reason: String) -> Self {
+        let code = reason.trim_start_matches("net::");
+        match code {
+            "ERR_PROXY_AUTH_UNSUPPORTED"
+            | "ERR_PROXY_AUTH_REQUESTED"
+            | "ERR_PROXY_AUTH_REQUESTED_WITH_NO_CONNECTION"
+            | "ERR_INVALID_AUTH_CREDENTIALS" => ChaserError::ProxyAuthFailed(reason),
+            "ERR_PROXY_CONNECTION_FAILED"
+            | "ERR_TUNNEL_CONNECTION_FAILED"
+            | "ERR_SOCKS_CONNECTION_FAILED"
+            | "ERR_NO_SUPPORTED_PROXIES"
+            | "ERR_PROXY_CERTIFICATE_INVALID" => ChaserError::ProxyUnreachable(reason),
+            _ => ChaserError::Navigation {
+                url: url.to_string(),
+                reason,
+            },
+        }
+    }
+}
+
+impl From<CdpError> for ChaserError {
+    fn from(err: CdpError) -> Self {
+        const DETACHED: [&str; 4] = [
+            "No target with given id",
+            "Session with given id not found",
+            "Target closed",
+            "Inspected target navigated or closed",
+        ];
+        match err {
+            CdpError::Timeout => ChaserError::Timeout("CDP request".to_string()),
+            CdpError::NoResponse | CdpError::ChannelSendError(_) => ChaserError::Detached,
+            CdpError::JavascriptException(details) => ChaserError::Script(
+                details
+                    .exception
+                    .as_ref()
+                    .and_then(|e| e.description.clone())
+                    .unwrap_or(details.text),
+            ),
+            err if DETACHED.iter().any(|m| err.to_string().contains(m)) => ChaserError::Detached,
+            err => ChaserError::Cdp(err),
+        }
+    }
+}
+
+impl From<anyhow::Error> for ChaserError {
+    fn from(err: anyhow::Error) -> Self {
+        match err.downcast::<ChaserError>() {
+            Ok(err) => err,
+            Err(err) => match err.downcast::<CdpError>() {
+                Ok(err) => err.into(),
+                Err(err) => ChaserError::Other(err.to_string()),
+            },
+        }
+    }
+}
+
+impl From<serde_json::Error> for ChaserError {
+    fn from(err: serde_json::Error) -> Self {
+        CdpError::from(err).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_navigation_errors() {
+        let url = "https://example.com";
+        assert!(matches!(
+            ChaserError::navigation(url, "net::ERR_TUNNEL_CONNECTION_FAILED".into()),
+            ChaserError::ProxyUnreachable(_)
+        ));
+        assert!(matches!(
+            ChaserError::navigation(url, "net::ERR_INVALID_AUTH_CREDENTIALS".into()),
+            ChaserError::ProxyAuthFailed(_)
+        ));
+        let dns = ChaserError::navigation(url, "net::ERR_NAME_NOT_RESOLVED".into());
+        assert!(dns.is_retryable() && !dns.needs_rotation());
+        assert_eq!(
+            dns.to_string(),
+            "Navigation to https://example.com failed: net::ERR_NAME_NOT_RESOLVED"
+        );
+    }
+}
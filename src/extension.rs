@@ -0,0 +1,170 @@
+//! Generating a minimal, benign unpacked extension.
+//!
+//! On a regular web page `chrome.runtime` only exists when an installed
+//! extension lists the page in its `externally_connectable` manifest key. A
+//! JavaScript stub can imitate the object, but not the native functions and
+//! the messaging behind it. [`BenignExtension`] writes an extension without
+//! permissions whose only purpose is to make Chrome expose the genuine
+//! `chrome.runtime` on the origins you choose.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::extension::BenignExtension;
+//!
+//! let dir = BenignExtension::new("Tab Notes")
+//!     .externally_connectable("https://*.example.com/*")
+//!     .write_to("/tmp/tab-notes")?;
+//! let profile = ChaserProfile::windows()
+//!     .extension(dir.to_string_lossy())
+//!     .build();
+//! ```
+
+use anyhow::{anyhow, Result};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+const BACKGROUND: &str = "\
+chrome.runtime.onMessageExternal.addListener((message, sender, sendResponse) => {
+  sendResponse({ ok: true });
+});
+";
+
+/// A Manifest V3 extension without permissions or content scripts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenignExtension {
+    name: String,
+    version: String,
+    description: String,
+    matches: Vec<String>,
+}
+
+impl BenignExtension {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: "1.0.0".to_string(),
+            description: String::new(),
+            matches: Vec::new(),
+        }
+    }
+
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Expose `chrome.runtime` on pages matching `pattern`, e.g.
+    /// `https://*.example.com/*`.
+    ///
+    /// Chrome rejects wildcard hosts like `*://*/*`, so every site needs its
+    /// own pattern.
+    pub fn externally_connectable(mut self, pattern: impl Into<String>) -> Self {
+        self.matches.push(pattern.into());
+        self
+    }
+
+    /// The `manifest.json` contents.
+    pub fn manifest(&self) -> serde_json::Value {
+        let mut manifest = json!({
+            "manifest_version": 3,
+            "name": self.name,
+            "version": self.version,
+            "description": self.description,
+            "background": { "service_worker": "background.js" },
+        });
+        if !self.matches.is_empty() {
+            manifest["externally_connectable"] = json!({ "matches": self.matches });
+        }
+        manifest
+    }
+
+    /// Write the extension into `dir` (created if missing) and return the
+    /// directory to pass to `--load-extension`.
+    pub fn write_to(&self, dir: impl AsRef<Path>) -> Result<PathBuf> {
+        if let Some(bad) = self
+            .matches
+            .iter()
+            .find(|m| m.contains("://*/") || m.as_str() == "<all_urls>")
+        {
+            return Err(anyhow!(
+                "externally_connectable does not allow wildcard hosts: {}",
+                bad
+            ));
+        }
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(
+            dir.join("manifest.json"),
+            serde_json::to_string_pretty(&self.manifest())?,
+        )?;
+        std::fs::write(dir.join("background.js"), BACKGROUND)?;
+        Ok(dunce::canonicalize(dir)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("chaser-extension-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn manifest_lists_only_the_connectable_origins() {
+        let plain = BenignExtension::new("Tab Notes").version("2.1.0");
+        let manifest = plain.manifest();
+        assert_eq!(manifest["manifest_version"], 3);
+        assert_eq!(manifest["name"], "Tab Notes");
+        assert_eq!(manifest["version"], "2.1.0");
+        assert_eq!(manifest["background"]["service_worker"], "background.js");
+        assert!(manifest.get("externally_connectable").is_none());
+        assert!(manifest.get("permissions").is_none());
+
+        let connectable = plain
+            .externally_connectable("https://*.example.com/*")
+            .externally_connectable("https://shop.test/*");
+        assert_eq!(
+            connectable.manifest()["externally_connectable"]["matches"],
+            json!(["https://*.example.com/*", "https://shop.test/*"])
+        );
+    }
+
+    #[test]
+    fn writes_an_unpacked_extension() {
+        let dir = temp_dir();
+        let written = BenignExtension::new("Tab Notes")
+            .externally_connectable("https://*.example.com/*")
+            .write_to(dir.join("nested"))
+            .unwrap();
+        assert!(written.is_absolute());
+
+        let manifest: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(written.join("manifest.json")).unwrap()).unwrap();
+        assert_eq!(manifest["name"], "Tab Notes");
+        assert_eq!(
+            std::fs::read_to_string(written.join("background.js")).unwrap(),
+            BACKGROUND
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn wildcard_hosts_are_rejected_before_writing() {
+        for pattern in ["*://*/*", "https://*/*", "<all_urls>"] {
+            let dir = temp_dir();
+            let err = BenignExtension::new("Tab Notes")
+                .externally_connectable(pattern)
+                .write_to(&dir)
+                .unwrap_err();
+            assert!(err.to_string().contains(pattern));
+            assert!(!dir.exists());
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! Clearing form fields with the keyboard and mouse instead of script.
+//!
+//! Setting `input.value = ''` fires no `keydown`, `beforeinput` or `input`
+//! events, leaves frameworks with stale state and is easy to spot next to a
+//! field that was typed into with trusted events. [`ChaserPage::clear_field_human`]
+//! clicks into the field and empties it the way people do: selecting
+//! everything with Ctrl+A (Cmd+A on macOS profiles) and deleting it,
+//! dragging across the text, or backspacing with an uneven cadence.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::fields::ClearStrategy;
+//!
+//! chaser.clear_field_human("#search").await?;
+//! chaser.type_text("new query").await?;
+//!
+//! chaser.clear_field_human_with("#zip", ClearStrategy::Backspace).await?;
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::{ChaserError, ChaserResult as Result};
+use crate::profiles::Os;
+use crate::selection::SelectTarget;
+use chromiumoxide_cdp::cdp::browser_protocol::input::{
+    DispatchKeyEventParams, DispatchKeyEventType,
+};
+use rand::Rng;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// `{units, chars}` of the value (or text) of the element matching
+/// `SELECTOR`, in UTF-16 units and in characters.
+const LENGTH_SCRIPT: &str = r#"(() => {
+    const el = document.querySelector(SELECTOR);
+    if (!el) return null;
+    const field = el instanceof HTMLInputElement || el instanceof HTMLTextAreaElement;
+    const text = field ? el.value : el.textContent;
+    return { units: text.length, chars: [...text].length };
+})()"#;
+
+/// `Modifiers` bit of `Input.dispatchKeyEvent`.
+const CTRL: i64 = 2;
+const META: i64 = 4;
+
+/// A key with its `KeyboardEvent.key`, `code` and Windows virtual key code.
+#[derive(Debug, Clone, Copy)]
+struct Key(&'static str, &'static str, i64);
+
+const BACKSPACE: Key = Key("Backspace", "Backspace", 8);
+const DELETE: Key = Key("Delete", "Delete", 46);
+const CONTROL: Key = Key("Control", "ControlLeft", 17);
+const COMMAND: Key = Key("Meta", "MetaLeft", 91);
+const KEY_A: Key = Key("a", "KeyA", 65);
+
+impl Key {
+    fn event(self, kind: DispatchKeyEventType, modifiers: i64) -> DispatchKeyEventParams {
+        let Key(key, code, vk) = self;
+        DispatchKeyEventParams::builder()
+            .r#type(kind)
+            .key(key)
+            .code(code)
+            .windows_virtual_key_code(vk)
+            .native_virtual_key_code(vk)
+            .modifiers(modifiers)
+            .build()
+            .unwrap()
+    }
+}
+
+/// How [`ChaserPage::clear_field_human_with`] empties a field.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ClearStrategy {
+    /// Backspace short values, otherwise usually the select-all shortcut,
+    /// sometimes a drag or backspaces. Phones always backspace.
+    #[default]
+    Auto,
+    /// Ctrl+A, or Cmd+A on macOS profiles, then Backspace.
+    SelectAll,
+    /// Drag across the text, then Delete.
+    MouseSelect,
+    /// Backspace once per character from the end.
+    Backspace,
+}
+
+impl ClearStrategy {
+    /// The concrete strategy for a value of `chars` characters.
+    fn resolve(self, chars: usize, mobile: bool) -> ClearStrategy {
+        if self != ClearStrategy::Auto {
+            return self;
+        }
+        if mobile || chars <= 6 {
+            return ClearStrategy::Backspace;
+        }
+        match rand::thread_rng().gen_range(0..100) {
+            0..=69 => ClearStrategy::SelectAll,
+            70..=84 => ClearStrategy::MouseSelect,
+            _ => ClearStrategy::Backspace,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FieldLength {
+    units: usize,
+    chars: usize,
+}
+
+impl ChaserPage {
+    /// Empty the field matching `selector` with trusted input, picking a
+    /// [`ClearStrategy`] that suits the value.
+    pub async fn clear_field_human(&self, selector: &str) -> Result<()> {
+        self.clear_field_human_with(selector, ClearStrategy::Auto)
+            .await
+    }
+
+    /// Empty the field matching `selector` with `strategy`.
+    ///
+    /// Whatever the strategy leaves behind, e.g. a trailing newline a drag
+    /// did not reach, is backspaced. Fails if the field is still not empty
+    /// after that.
+    pub async fn clear_field_human_with(
+        &self,
+        selector: &str,
+        strategy: ClearStrategy,
+    ) -> Result<()> {
+        let length = self.field_length(selector).await?;
+        if length.chars == 0 {
+            return Ok(());
+        }
+        let os = self.profile().map(|profile| profile.os());
+        let mobile = os.is_some_and(|os| os.is_mobile());
+        match strategy.resolve(length.chars, mobile) {
+            ClearStrategy::SelectAll => {
+                self.click_caret_at(selector, length.units).await?;
+                let (modifier, bit) = match os {
+                    Some(Os::MacOSIntel | Os::MacOSArm) => (COMMAND, META),
+                    _ => (CONTROL, CTRL),
+                };
+                let mut select_all = KEY_A.event(DispatchKeyEventType::RawKeyDown, bit);
+                // the page's platform may differ from the host's shortcuts
+                select_all.commands = Some(vec!["selectAll".to_string()]);
+                self.key_chord(
+                    (
+                        modifier.event(DispatchKeyEventType::RawKeyDown, bit),
+                        modifier.event(DispatchKeyEventType::KeyUp, 0),
+                    ),
+                    (select_all, KEY_A.event(DispatchKeyEventType::KeyUp, bit)),
+                )
+                .await?;
+                self.pause_between_keys(0).await?;
+                self.tap_key(BACKSPACE).await?;
+            }
+            ClearStrategy::MouseSelect => {
+                self.select_text_human(SelectTarget::Element(selector.to_string()))
+                    .await?;
+                self.pause_between_keys(0).await?;
+                self.tap_key(DELETE).await?;
+            }
+            ClearStrategy::Backspace | ClearStrategy::Auto => {
+                self.click_caret_at(selector, length.units).await?;
+                self.backspace(length.chars).await?;
+            }
+        }
+
+        let left = self.field_length(selector).await?.chars;
+        if left > 0 {
+            self.backspace(left).await?;
+            if self.field_length(selector).await?.chars > 0 {
+                return Err(ChaserError::msg(format!(
+                    "`{selector}` could not be cleared"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    async fn field_length(&self, selector: &str) -> Result<FieldLength> {
+        let script = LENGTH_SCRIPT.replacen("SELECTOR", &serde_json::to_string(selector)?, 1);
+        match self.evaluate_stealth(&script).await? {
+            Some(value) if !value.is_null() => Ok(serde_json::from_value(value)?),
+            _ => Err(ChaserError::msg(format!("no element matches `{selector}`"))),
+        }
+    }
+
+    /// Press Backspace `count` times, hesitant at first and quicker once
+    /// under way.
+    async fn backspace(&self, count: usize) -> Result<()> {
+        for i in 0..count {
+            self.tap_key(BACKSPACE).await?;
+            if i + 1 < count {
+                self.pause_between_keys(i).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn pause_between_keys(&self, streak: usize) -> Result<()> {
+        let delay = {
+            let mut rng = rand::thread_rng();
+            if rng.gen_bool(0.04) {
+                rng.gen_range(250..500)
+            } else if streak < 3 {
+                rng.gen_range(90..180)
+            } else {
+                rng.gen_range(45..95)
+            }
+        };
+        self.pause(Duration::from_millis(delay)).await
+    }
+
+    async fn tap_key(&self, key: Key) -> Result<()> {
+        self.key_stroke(
+            key.event(DispatchKeyEventType::RawKeyDown, 0),
+            key.event(DispatchKeyEventType::KeyUp, 0),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_backspaces_short_values_and_on_phones() {
+        assert_eq!(
+            ClearStrategy::Auto.resolve(4, false),
+            ClearStrategy::Backspace
+        );
+        assert_eq!(
+            ClearStrategy::Auto.resolve(40, true),
+            ClearStrategy::Backspace
+        );
+        assert_ne!(ClearStrategy::Auto.resolve(40, false), ClearStrategy::Auto);
+        assert_eq!(
+            ClearStrategy::MouseSelect.resolve(4, true),
+            ClearStrategy::MouseSelect
+        );
+    }
+}
@@ -0,0 +1,298 @@
+//! Clustering risks across a fleet of profiles.
+//!
+//! Each profile can look fine on its own and the fleet still give itself
+//! away: two hundred identities with the same screen, GPU, locale and
+//! timezone, two profiles sharing a noise seed (and so the exact same client
+//! rects), or a quarter of all "Windows users" owning an RTX 4080. A site
+//! sees all of them. [`FleetAuditor`] looks at the profiles together before
+//! they are deployed.
+//!
+//! Expected frequencies come from the tables of [`crate::entropy`]. The OS
+//! and locale mix of a fleet is usually chosen on purpose (desktop sites,
+//! proxies in one country), so only hardware attributes are compared with
+//! real-world shares, within each OS.
+//!
+//! # Example
+//!
+//! ```
+//! use chaser_oxide::fleet::FleetAuditor;
+//! use chaser_oxide::profiles::ChaserProfile;
+//!
+//! let fleet = vec![ChaserProfile::windows().build(); 20];
+//! let report = FleetAuditor::new(fleet).audit();
+//! assert!(!report.passed());
+//! for issue in report.issues() {
+//!     println!("{issue}");
+//! }
+//! ```
+
+use crate::pool::BrowserPool;
+use crate::profiles::ChaserProfile;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Attributes compared with their real-world share.
+const HARDWARE: [&str; 5] = [
+    "screen",
+    "devicePixelRatio",
+    "hardwareConcurrency",
+    "deviceMemory",
+    "gpu",
+];
+
+/// A risk found by [`FleetAuditor::audit`]. Profiles are referred to by
+/// their index in the audited fleet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FleetFinding {
+    /// Profiles with the same noise seed produce identical noise.
+    SharedSeed { seed: u32, profiles: Vec<usize> },
+    /// More profiles with the same hardware, version, locale and timezone
+    /// than a random sample of real users of this size would contain.
+    Cluster {
+        key: String,
+        profiles: Vec<usize>,
+        expected: f64,
+    },
+    /// An attribute value far more common in the fleet than among real
+    /// users of the same OS.
+    Skewed {
+        attribute: &'static str,
+        value: String,
+        fleet_share: f64,
+        expected_share: f64,
+    },
+    /// A profile rarer than one in a million on its own.
+    Rare { profile: usize, one_in: f64 },
+}
+
+impl fmt::Display for FleetFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FleetFinding::SharedSeed { seed, profiles } => {
+                write!(f, "profiles {profiles:?} share the noise seed {seed}")
+            }
+            FleetFinding::Cluster {
+                key,
+                profiles,
+                expected,
+            } => write!(
+                f,
+                "{} profiles are {key} (~{expected:.2} expected)",
+                profiles.len()
+            ),
+            FleetFinding::Skewed {
+                attribute,
+                value,
+                fleet_share,
+                expected_share,
+            } => write!(
+                f,
+                "{attribute} {value} is {:.0}% of the fleet but ~{:.1}% of real users",
+                fleet_share * 100.0,
+                expected_share * 100.0
+            ),
+            FleetFinding::Rare { profile, one_in } => {
+                write!(f, "profile {profile} is one in ~{one_in:.0} users")
+            }
+        }
+    }
+}
+
+/// The result of [`FleetAuditor::audit`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FleetReport {
+    /// Number of profiles audited.
+    pub profiles: usize,
+    pub findings: Vec<FleetFinding>,
+}
+
+impl FleetReport {
+    pub fn passed(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Human-readable findings.
+    pub fn issues(&self) -> Vec<String> {
+        self.findings.iter().map(ToString::to_string).collect()
+    }
+}
+
+/// Audits a set of profiles for clustering risks.
+#[derive(Debug, Clone)]
+pub struct FleetAuditor {
+    profiles: Vec<ChaserProfile>,
+    skew_factor: f64,
+}
+
+impl FleetAuditor {
+    pub fn new(profiles: impl IntoIterator<Item = ChaserProfile>) -> Self {
+        FleetAuditor {
+            profiles: profiles.into_iter().collect(),
+            skew_factor: 3.0,
+        }
+    }
+
+    /// Audit the profiles a pool hands out.
+    pub fn for_pool(pool: &BrowserPool) -> Self {
+        Self::new(pool.config().profiles().iter().cloned())
+    }
+
+    /// How many times its real-world share a value may reach in the fleet
+    /// before it is reported, 3 by default.
+    pub fn skew_factor(mut self, factor: f64) -> Self {
+        self.skew_factor = factor;
+        self
+    }
+
+    pub fn audit(&self) -> FleetReport {
+        let reports: Vec<_> = self.profiles.iter().map(|p| p.entropy_report()).collect();
+        let mut findings = Vec::new();
+
+        let mut seeds: BTreeMap<u32, Vec<usize>> = BTreeMap::new();
+        for (i, profile) in self.profiles.iter().enumerate() {
+            if let Some(seed) = profile.rect_noise_seed() {
+                seeds.entry(seed).or_default().push(i);
+            }
+        }
+        findings.extend(
+            seeds
+                .into_iter()
+                .filter(|(_, profiles)| profiles.len() > 1)
+                .map(|(seed, profiles)| FleetFinding::SharedSeed { seed, profiles }),
+        );
+
+        let mut clusters: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (i, profile) in self.profiles.iter().enumerate() {
+            clusters.entry(cluster_key(profile)).or_default().push(i);
+        }
+        for (key, profiles) in clusters {
+            // chance a random user has this tuple; timezones split a locale a
+            // few ways at most, so they are not counted
+            let share = 1.0 / reports[profiles[0]].one_in();
+            let expected = share * self.profiles.len() as f64;
+            // more than a Poisson count would reach by chance
+            if profiles.len() > 1 && profiles.len() as f64 > expected + 3.0 * expected.sqrt() + 1.0
+            {
+                findings.push(FleetFinding::Cluster {
+                    key,
+                    profiles,
+                    expected,
+                });
+            }
+        }
+
+        // (os, attribute, value) -> (profiles, real-world share)
+        let mut values: BTreeMap<(String, &'static str, String), (usize, f64)> = BTreeMap::new();
+        let mut per_os: BTreeMap<String, usize> = BTreeMap::new();
+        for (profile, report) in self.profiles.iter().zip(&reports) {
+            let os = format!("{:?}", profile.os());
+            *per_os.entry(os.clone()).or_default() += 1;
+            for attribute in report
+                .attributes
+                .iter()
+                .filter(|a| HARDWARE.contains(&a.name))
+            {
+                let entry = values
+                    .entry((os.clone(), attribute.name, attribute.value.clone()))
+                    .or_default();
+                entry.0 += 1;
+                entry.1 = entry.1.max(attribute.share);
+            }
+        }
+        for ((os, attribute, value), (count, expected_share)) in values {
+            let fleet_share = count as f64 / per_os[&os] as f64;
+            // a handful of profiles cannot show a distribution
+            if count >= 3
+                && fleet_share > (expected_share * self.skew_factor).max(expected_share + 0.1)
+            {
+                findings.push(FleetFinding::Skewed {
+                    attribute,
+                    value: format!("{value} on {os}"),
+                    fleet_share,
+                    expected_share,
+                });
+            }
+        }
+
+        findings.extend(
+            reports
+                .iter()
+                .enumerate()
+                .filter(|(_, report)| report.is_rare())
+                .map(|(profile, report)| FleetFinding::Rare {
+                    profile,
+                    one_in: report.one_in(),
+                }),
+        );
+
+        FleetReport {
+            profiles: self.profiles.len(),
+            findings,
+        }
+    }
+}
+
+/// What a site sees of a profile at a glance.
+fn cluster_key(profile: &ChaserProfile) -> String {
+    format!(
+        "{:?} Chrome {} {}x{}@{} {:?} {} cores {}GB {} {}",
+        profile.os(),
+        profile.chrome_version(),
+        profile.screen_width(),
+        profile.screen_height(),
+        profile.device_pixel_ratio(),
+        profile.gpu(),
+        profile.cpu_cores(),
+        profile.device_memory(),
+        profile.locale(),
+        profile.timezone(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiles::Gpu;
+
+    #[test]
+    fn finds_clusters_and_skew() {
+        let mut fleet = vec![
+            ChaserProfile::windows()
+                .gpu(Gpu::IntelIrisXe)
+                .screen(1920, 1080)
+                .build();
+            10
+        ];
+        fleet.extend(vec![
+            ChaserProfile::windows().gpu(Gpu::NvidiaRTX4080).build();
+            4
+        ]);
+        let report = FleetAuditor::new(fleet).audit();
+        assert!(report.findings.iter().any(|f| matches!(
+            f,
+            FleetFinding::Cluster { profiles, .. } if profiles.len() == 10
+        )));
+        assert!(report.findings.iter().any(|f| matches!(
+            f,
+            FleetFinding::Skewed { attribute: "gpu", value, .. } if value.contains("4080")
+        )));
+
+        let varied = [
+            ChaserProfile::windows().build(),
+            ChaserProfile::macos_arm().build(),
+            ChaserProfile::linux().build(),
+        ];
+        assert!(FleetAuditor::new(varied).audit().passed());
+
+        #[cfg(feature = "rect-noise")]
+        {
+            let seeded = (0..4).map(|i| ChaserProfile::windows().rect_noise_seed(i % 3).build());
+            assert!(FleetAuditor::new(seeded).audit().findings.contains(
+                &FleetFinding::SharedSeed {
+                    seed: 0,
+                    profiles: vec![0, 3]
+                }
+            ));
+        }
+    }
+}
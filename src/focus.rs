@@ -0,0 +1,208 @@
+//! Noticing when the page moves focus.
+//!
+//! Keystrokes go to whatever element has focus. A cookie banner, a chat
+//! widget or a login overlay that loads late and focuses itself takes the
+//! rest of the text, and without a check the typing "succeeds". The typing
+//! methods of [`ChaserPage`] therefore note the focused element when they
+//! start and check before every keystroke that it still has focus, failing
+//! with [`ChaserError::FocusLost`] otherwise. Fields that move focus on
+//! purpose, such as one-box-per-digit code inputs, need
+//! [`ChaserPage::set_focus_check`] turned off.
+//!
+//! [`ChaserPage::watch_focus`] streams every focus change of the main
+//! frame, through an [observer](crate::observe) in the isolated world.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::ChaserError;
+//!
+//! chaser.click_human(x, y).await?;
+//! match chaser.type_text("ada@example.com").await {
+//!     Err(ChaserError::FocusLost { now, .. }) => println!("{now} took the focus"),
+//!     other => other?,
+//! }
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::{ChaserError, ChaserResult as Result};
+use crate::observe::Observer;
+use crate::utils;
+use serde::Deserialize;
+use std::sync::OnceLock;
+
+/// `deep()`, the focused element inside open shadow roots, and
+/// `describe(el)`, a short selector-like name for error messages.
+pub(crate) const FOCUS_HELPERS: &str = r#"
+    const deep = () => {
+        let el = document.activeElement;
+        while (el && el.shadowRoot && el.shadowRoot.activeElement) el = el.shadowRoot.activeElement;
+        return el;
+    };
+    const describe = el => {
+        if (!el) return 'nothing';
+        let name = el.localName || el.nodeName.toLowerCase();
+        if (el.id) name += '#' + el.id;
+        else if (el.getAttribute && el.getAttribute('name')) name += `[name="${el.getAttribute('name')}"]`;
+        else if (el.classList && el.classList.length) name += '.' + Array.from(el.classList).slice(0, 2).join('.');
+        return name;
+    };"#;
+
+/// The global of the isolated world holding the element being typed into.
+fn focus_key() -> &'static str {
+    static KEY: OnceLock<String> = OnceLock::new();
+    KEY.get_or_init(utils::random_identifier)
+}
+
+/// Remembers the focused element and returns its description.
+fn mark_script() -> String {
+    format!(
+        "(() => {{{FOCUS_HELPERS}\n    globalThis['{key}'] = deep();\n    return describe(globalThis['{key}']);\n}})()",
+        key = focus_key()
+    )
+}
+
+/// `null` while the remembered element has focus, else the description of
+/// the element that has it now.
+fn check_script() -> String {
+    format!(
+        "(() => {{{FOCUS_HELPERS}\n    const now = deep();\n    return now === globalThis['{key}'] ? null : describe(now);\n}})()",
+        key = focus_key()
+    )
+}
+
+/// Emits a [`FocusChange`] for every focus and blur in the document.
+fn watch_script() -> String {
+    format!(
+        r#"emit => {{{FOCUS_HELPERS}
+    for (const [type, kind] of [['focusin', 'focus'], ['focusout', 'blur']]) {{
+        addEventListener(type, event => emit({{
+            kind,
+            element: describe(event.composedPath()[0] || event.target),
+            related: event.relatedTarget ? describe(event.relatedTarget) : null,
+            trusted: event.isTrusted,
+            active: describe(deep()),
+        }}), true);
+    }}
+}}"#
+    )
+}
+
+/// Whether an element gained or lost focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FocusKind {
+    Focus,
+    Blur,
+}
+
+/// One focus change seen by [`ChaserPage::watch_focus`]. Elements are
+/// described selector-like, e.g. `input#email` or `div.modal.open`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct FocusChange {
+    pub kind: FocusKind,
+    pub element: String,
+    /// The element focus came from (for `Focus`) or goes to (for `Blur`),
+    /// when the browser reports it.
+    pub related: Option<String>,
+    /// `false` for events the page dispatched itself.
+    pub trusted: bool,
+    /// The element that had focus when the event fired.
+    pub active: String,
+}
+
+impl ChaserPage {
+    /// Check before every keystroke of the typing methods that the element
+    /// focused when typing started still has focus (default: on).
+    pub fn set_focus_check(&self, on: bool) {
+        *self.focus_check_state().lock().unwrap() = on;
+    }
+
+    pub fn focus_check(&self) -> bool {
+        *self.focus_check_state().lock().unwrap()
+    }
+
+    /// A description of the focused element, e.g. `input#email`, or
+    /// `body` when nothing is focused.
+    pub async fn focused_element(&self) -> Result<String> {
+        let script = format!("(() => {{{FOCUS_HELPERS}\n    return describe(deep());\n}})()");
+        Ok(self
+            .evaluate_stealth(&script)
+            .await?
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default())
+    }
+
+    /// Call `handler` with every focus change in the main frame until the
+    /// returned [`Observer`] is stopped or dropped.
+    pub async fn watch_focus<F>(&self, mut handler: F) -> Result<Observer>
+    where
+        F: FnMut(FocusChange) + Send + 'static,
+    {
+        self.observe(&watch_script(), move |value| {
+            match serde_json::from_value(value) {
+                Ok(change) => handler(change),
+                Err(e) => tracing::warn!("unreadable focus change: {e}"),
+            }
+        })
+        .await
+    }
+
+    /// Note the focused element for [`check_focus`](Self::check_focus),
+    /// unless the check is off.
+    pub(crate) async fn mark_focus(&self) -> Result<Option<String>> {
+        if !self.focus_check() {
+            return Ok(None);
+        }
+        let focused = self.evaluate_stealth(&mark_script()).await?;
+        Ok(Some(
+            focused
+                .and_then(|v| v.as_str().map(str::to_string))
+                .unwrap_or_default(),
+        ))
+    }
+
+    /// Fail with [`ChaserError::FocusLost`] if the element noted by
+    /// [`mark_focus`](Self::mark_focus) lost focus.
+    pub(crate) async fn check_focus(&self, marked: Option<&str>) -> Result<()> {
+        let Some(expected) = marked else {
+            return Ok(());
+        };
+        match self.evaluate_stealth(&check_script()).await? {
+            Some(serde_json::Value::String(now)) => Err(ChaserError::FocusLost {
+                expected: expected.to_string(),
+                now,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_focus_changes() {
+        let change: FocusChange = serde_json::from_value(serde_json::json!({
+            "kind": "blur",
+            "element": "input#email",
+            "related": "div.modal",
+            "trusted": true,
+            "active": "body",
+        }))
+        .unwrap();
+        assert_eq!(change.kind, FocusKind::Blur);
+        assert_eq!(change.related.as_deref(), Some("div.modal"));
+
+        let error = ChaserError::FocusLost {
+            expected: "input#email".into(),
+            now: "div.modal".into(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Focus moved from input#email to div.modal"
+        );
+        assert!(check_script().contains(focus_key()));
+    }
+}
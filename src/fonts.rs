@@ -0,0 +1,280 @@
+//! Installed fonts as a cross-OS consistency check.
+//!
+//! Sites measure text in dozens of font families to learn which are
+//! installed: a Windows profile without Segoe UI and Calibri, or with
+//! DejaVu Sans, is a Linux server in disguise. [`ChaserPage::probe_fonts`]
+//! runs the same measurement and compares it with what the claimed OS
+//! ships ([`expected_fonts`], [`foreign_fonts`]).
+//!
+//! The usual fix on a Linux host is installing metric-compatible fonts
+//! ([`font_packages`]) and aliasing the Windows and macOS family names to
+//! them with a private fontconfig file ([`write_fontconfig`]), so text lays
+//! out to the same widths as on the claimed OS.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::fonts;
+//! use chaser_oxide::profiles::Os;
+//!
+//! let conf = fonts::write_fontconfig(Os::Windows, "/tmp/chaser-fonts")?;
+//! let config = profile
+//!     .configure_browser(BrowserConfig::builder())
+//!     .env("FONTCONFIG_FILE", conf.display().to_string())
+//!     .build()?;
+//! // ... launch, apply the profile, then
+//! let report = chaser.probe_fonts(profile.os()).await?;
+//! for issue in report.issues() {
+//!     tracing::warn!("{issue}");
+//! }
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::{ChaserError, ChaserResult as Result};
+use crate::profiles::Os;
+use std::path::{Path, PathBuf};
+
+/// Reports for each family whether text set in it measures differently from
+/// all three generic fallbacks, i.e. whether it is installed.
+const FONT_PROBE_SCRIPT: &str = r#"((families) => {
+    const ctx = document.createElement('canvas').getContext('2d');
+    const text = 'mmmmmmmmmmlli WwQq@#0123456789';
+    const width = font => { ctx.font = '72px ' + font; return ctx.measureText(text).width; };
+    const generics = ['monospace', 'serif', 'sans-serif'];
+    const base = generics.map(width);
+    return families.map(family => generics.some(
+        (generic, i) => width(`"${family}", ${generic}`) !== base[i]));
+})"#;
+
+/// Families every install of `os` has.
+pub fn expected_fonts(os: Os) -> &'static [&'static str] {
+    match os {
+        Os::Windows => &[
+            "Arial",
+            "Times New Roman",
+            "Courier New",
+            "Segoe UI",
+            "Calibri",
+            "Cambria",
+            "Consolas",
+            "Tahoma",
+            "Verdana",
+            "Georgia",
+        ],
+        Os::MacOSIntel | Os::MacOSArm => &[
+            "Helvetica",
+            "Helvetica Neue",
+            "Arial",
+            "Times",
+            "Courier New",
+            "Menlo",
+            "Monaco",
+            "Geneva",
+            "Avenir",
+            "Georgia",
+        ],
+        Os::Linux => &["DejaVu Sans", "DejaVu Serif", "DejaVu Sans Mono"],
+        Os::Android => &["Roboto", "Noto Sans", "Droid Sans Mono"],
+        Os::Ios => &["Helvetica", "Helvetica Neue", "Menlo", "Avenir", "Georgia"],
+    }
+}
+
+/// Families that give away another OS when present.
+pub fn foreign_fonts(os: Os) -> &'static [&'static str] {
+    match os {
+        Os::Windows => &[
+            "DejaVu Sans",
+            "Ubuntu",
+            "Cantarell",
+            "Helvetica Neue",
+            "Menlo",
+        ],
+        Os::MacOSIntel | Os::MacOSArm | Os::Ios => {
+            &["Segoe UI", "Calibri", "DejaVu Sans", "Ubuntu", "Roboto"]
+        }
+        Os::Linux => &["Segoe UI", "Helvetica Neue", "Menlo"],
+        Os::Android => &["Segoe UI", "Helvetica Neue", "DejaVu Sans", "Ubuntu"],
+    }
+}
+
+/// Debian/Ubuntu packages with fonts metric-compatible to those of `os`.
+pub fn font_packages(os: Os) -> &'static [&'static str] {
+    match os {
+        Os::Windows => &[
+            "fonts-liberation",
+            "fonts-crosextra-carlito",
+            "fonts-crosextra-caladea",
+            "fonts-inconsolata",
+        ],
+        Os::MacOSIntel | Os::MacOSArm | Os::Ios => &["fonts-liberation", "fonts-urw-base35"],
+        Os::Linux => &["fonts-dejavu-core"],
+        Os::Android => &["fonts-roboto", "fonts-noto-core"],
+    }
+}
+
+/// `(family, metric-compatible substitute)` pairs for [`fontconfig`].
+fn aliases(os: Os) -> &'static [(&'static str, &'static str)] {
+    match os {
+        Os::Windows => &[
+            ("Arial", "Liberation Sans"),
+            ("Times New Roman", "Liberation Serif"),
+            ("Courier New", "Liberation Mono"),
+            ("Calibri", "Carlito"),
+            ("Cambria", "Caladea"),
+            ("Consolas", "Inconsolata"),
+            ("Segoe UI", "Liberation Sans"),
+            ("Tahoma", "Liberation Sans"),
+            ("Verdana", "Liberation Sans"),
+        ],
+        Os::MacOSIntel | Os::MacOSArm | Os::Ios => &[
+            ("Helvetica", "Nimbus Sans"),
+            ("Helvetica Neue", "Nimbus Sans"),
+            ("Arial", "Liberation Sans"),
+            ("Times", "Nimbus Roman"),
+            ("Courier New", "Liberation Mono"),
+            ("Menlo", "Liberation Mono"),
+            ("Monaco", "Liberation Mono"),
+        ],
+        Os::Linux | Os::Android => &[],
+    }
+}
+
+/// Chrome's default `sans-serif`, `serif` and `monospace` families on `os`.
+fn generic_defaults(os: Os) -> &'static [(&'static str, &'static str)] {
+    match os {
+        Os::Windows => &[
+            ("sans-serif", "Arial"),
+            ("serif", "Times New Roman"),
+            ("monospace", "Consolas"),
+        ],
+        Os::MacOSIntel | Os::MacOSArm | Os::Ios => &[
+            ("sans-serif", "Helvetica"),
+            ("serif", "Times"),
+            ("monospace", "Menlo"),
+        ],
+        Os::Linux | Os::Android => &[],
+    }
+}
+
+/// A fontconfig file that keeps the system configuration, aliases the
+/// families of `os` to their metric-compatible substitutes from
+/// [`font_packages`], points the generic families at the same defaults as
+/// on `os` and hides the [`foreign_fonts`] of `os`.
+pub fn fontconfig(os: Os) -> String {
+    let mut conf = String::from(
+        "<?xml version=\"1.0\"?>\n<!DOCTYPE fontconfig SYSTEM \"fonts.dtd\">\n<fontconfig>\n  \
+         <include ignore_missing=\"yes\">/etc/fonts/fonts.conf</include>\n",
+    );
+    for (family, substitute) in aliases(os) {
+        conf.push_str(&format!(
+            "  <alias binding=\"same\">\n    <family>{family}</family>\n    \
+             <accept><family>{substitute}</family></accept>\n  </alias>\n"
+        ));
+    }
+    for (generic, family) in generic_defaults(os) {
+        conf.push_str(&format!(
+            "  <alias>\n    <family>{generic}</family>\n    \
+             <prefer><family>{family}</family></prefer>\n  </alias>\n"
+        ));
+    }
+    if !aliases(os).is_empty() {
+        conf.push_str("  <selectfont>\n    <rejectfont>\n");
+        for family in foreign_fonts(os) {
+            conf.push_str(&format!(
+                "      <pattern><patelt name=\"family\"><string>{family}</string></patelt></pattern>\n"
+            ));
+        }
+        conf.push_str("    </rejectfont>\n  </selectfont>\n");
+    }
+    conf.push_str("</fontconfig>\n");
+    conf
+}
+
+/// Write [`fontconfig`] for `os` to `dir/fonts.conf` and return its path,
+/// to be passed to Chrome as `FONTCONFIG_FILE`.
+pub fn write_fontconfig(os: Os, dir: impl AsRef<Path>) -> std::io::Result<PathBuf> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join("fonts.conf");
+    std::fs::write(&path, fontconfig(os))?;
+    Ok(path)
+}
+
+/// What [`ChaserPage::probe_fonts`] found.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FontReport {
+    /// Families the claimed OS ships that the page cannot use.
+    pub missing: Vec<&'static str>,
+    /// Families of other systems the page can use.
+    pub foreign: Vec<&'static str>,
+}
+
+impl FontReport {
+    pub fn passed(&self) -> bool {
+        self.missing.is_empty() && self.foreign.is_empty()
+    }
+
+    /// Human-readable findings.
+    pub fn issues(&self) -> Vec<String> {
+        let missing = self
+            .missing
+            .iter()
+            .map(|family| format!("{family} is missing"));
+        let foreign = self
+            .foreign
+            .iter()
+            .map(|family| format!("{family} is installed but foreign to the profile's OS"));
+        missing.chain(foreign).collect()
+    }
+}
+
+impl ChaserPage {
+    /// Measure which of the families relevant to `os` the current page can
+    /// use, the way font fingerprinting scripts do.
+    pub async fn probe_fonts(&self, os: Os) -> Result<FontReport> {
+        let expected = expected_fonts(os);
+        let foreign = foreign_fonts(os);
+        let families: Vec<&str> = expected.iter().chain(foreign).copied().collect();
+        let script = format!("{FONT_PROBE_SCRIPT}({})", serde_json::to_string(&families)?);
+        let installed: Vec<bool> = self
+            .evaluate_stealth(&script)
+            .await?
+            .and_then(|v| serde_json::from_value(v).ok())
+            .ok_or_else(|| ChaserError::msg("Could not measure fonts"))?;
+        let (expected_installed, foreign_installed) = installed.split_at(expected.len());
+        Ok(FontReport {
+            missing: expected
+                .iter()
+                .zip(expected_installed)
+                .filter(|(_, installed)| !**installed)
+                .map(|(family, _)| *family)
+                .collect(),
+            foreign: foreign
+                .iter()
+                .zip(foreign_installed)
+                .filter(|(_, installed)| **installed)
+                .map(|(family, _)| *family)
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fontconfig_aliases_only_families_the_os_expects() {
+        let conf = fontconfig(Os::Windows);
+        assert!(conf.contains("<family>Calibri</family>"));
+        assert!(conf.contains("<family>Carlito</family>"));
+        assert!(conf.contains("<string>DejaVu Sans</string>"));
+        for os in [Os::Windows, Os::MacOSArm] {
+            for (family, substitute) in aliases(os) {
+                assert!(expected_fonts(os).contains(family), "{family}");
+                // hiding a substitute would undo its alias
+                assert!(!foreign_fonts(os).contains(substitute), "{substitute}");
+            }
+        }
+    }
+}
@@ -0,0 +1,303 @@
+//! Consistent screen, window and viewport sizes for a profile.
+//!
+//! Pages cross-check the sizes they can read: the viewport
+//! (`innerWidth`/`innerHeight`) must fit in the window
+//! (`outerWidth`/`outerHeight`), which must fit in the available screen
+//! area, and the differences must match the browser UI of the claimed OS.
+//! A headless browser gets all of them wrong by default (its window has no
+//! tab strip, so outer and inner sizes are equal). [`Geometry`] derives every
+//! value from the profile's screen, available area, DPR and window state, so
+//! the launch window size, the CDP device metrics and the bootstrap script
+//! all agree.
+//!
+//! # Example
+//!
+//! ```
+//! use chaser_oxide::geometry::{Geometry, WindowState};
+//! use chaser_oxide::profiles::ChaserProfile;
+//!
+//! let profile = ChaserProfile::windows().build();
+//! let geometry = Geometry::resolve_with(&profile, WindowState::Maximized);
+//! // Windows keeps the 8px resize borders of a maximized window off-screen
+//! assert_eq!(geometry.outer_width, 1936);
+//! assert_eq!(geometry.inner_width, 1920);
+//! assert_eq!(geometry.inner_height, 1032 + 16 - 87);
+//! ```
+
+use crate::profiles::{AvailArea, ChaserProfile, Os};
+use chromiumoxide_cdp::cdp::browser_protocol::emulation::SetDeviceMetricsOverrideParams;
+use serde::{Deserialize, Serialize};
+
+/// Space the browser UI takes from the window, in CSS pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Insets {
+    /// Tab strip and toolbar (desktop), status bar and address bar (phones).
+    pub top: u32,
+    /// Bottom border or toolbar.
+    pub bottom: u32,
+    /// Left and right border, each.
+    pub side: u32,
+}
+
+impl Insets {
+    /// The browser UI of Chrome on `os` without a bookmarks bar.
+    pub fn for_os(os: Os) -> Self {
+        let (top, bottom, side) = match os {
+            // Windows counts its invisible 8px resize borders into the window
+            Os::Windows => (79, 8, 8),
+            Os::MacOSIntel | Os::MacOSArm => (79, 0, 0),
+            Os::Linux => (72, 0, 0),
+            Os::Android => (56, 0, 0),
+            Os::Ios => (50, 49, 0),
+        };
+        Insets { top, bottom, side }
+    }
+}
+
+/// Whether the window fills the available area.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WindowState {
+    Maximized,
+    /// Outer bounds of a restored window, relative to its monitor.
+    Windowed {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    },
+}
+
+/// Every size and position a page can read, resolved for one profile.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Geometry {
+    pub screen_width: u32,
+    pub screen_height: u32,
+    /// Available area, relative to the monitor.
+    pub avail: AvailArea,
+    /// `devicePixelRatio`: the screen's scale factor times the zoom.
+    pub device_pixel_ratio: f64,
+    /// Browser zoom factor, 1.0 at 100%.
+    pub zoom: f64,
+    pub mobile: bool,
+    pub window: WindowState,
+    /// `screenX`/`screenY` on the virtual desktop.
+    pub window_x: i32,
+    pub window_y: i32,
+    pub outer_width: u32,
+    pub outer_height: u32,
+    /// `innerWidth`/`innerHeight` in CSS pixels, which shrink as the page
+    /// is zoomed in.
+    pub inner_width: u32,
+    pub inner_height: u32,
+}
+
+/// The zoom levels Chrome's menu and Ctrl+/- step through, in percent.
+pub const ZOOM_LEVELS: [u32; 17] = [
+    25, 33, 50, 67, 75, 80, 90, 100, 110, 125, 150, 175, 200, 250, 300, 400, 500,
+];
+
+/// The zoom level closest to `percent`.
+pub fn nearest_zoom_level(percent: u32) -> u32 {
+    ZOOM_LEVELS
+        .into_iter()
+        .min_by_key(|level| level.abs_diff(percent))
+        .unwrap_or(100)
+}
+
+/// Restored windows are never smaller than this.
+const MIN_WINDOW: (u32, u32) = (500, 400);
+
+/// Height of the phone status bar above the browser.
+fn status_bar(os: Os) -> u32 {
+    match os {
+        Os::Ios => 59,
+        _ => 24,
+    }
+}
+
+impl Geometry {
+    /// The geometry of the profile's window: on desktops its
+    /// [`window_state`](ChaserProfile::window_state) or else a restored window
+    /// reaching from the profile's window position to the bottom right of
+    /// the available area, on phones the full screen.
+    pub fn resolve(profile: &ChaserProfile) -> Self {
+        let window = profile
+            .window_state()
+            .unwrap_or_else(|| default_window(profile));
+        Self::resolve_with(profile, window)
+    }
+
+    /// The geometry of `profile` with its window in `window`.
+    ///
+    /// Windowed bounds are kept inside the available area (plus the resize
+    /// borders on Windows) and at least 500x400. Phones ignore `window`.
+    pub fn resolve_with(profile: &ChaserProfile, window: WindowState) -> Self {
+        let os = profile.os();
+        let insets = Insets::for_os(os);
+        let avail = profile.avail_area();
+        let (origin_x, origin_y) = profile.screen_origin();
+        // Android's text scaling is no page zoom
+        let zoom = if os.is_mobile() {
+            1.0
+        } else {
+            profile.zoom_percent() as f64 / 100.0
+        };
+        let mut geometry = Geometry {
+            screen_width: profile.screen_width(),
+            screen_height: profile.screen_height(),
+            avail,
+            device_pixel_ratio: profile.device_pixel_ratio() as f64 * zoom,
+            zoom,
+            mobile: os.is_mobile(),
+            window,
+            window_x: origin_x,
+            window_y: origin_y,
+            outer_width: 0,
+            outer_height: 0,
+            inner_width: 0,
+            inner_height: 0,
+        };
+
+        if os.is_mobile() {
+            let status = status_bar(os);
+            geometry.window = WindowState::Maximized;
+            geometry.outer_width = geometry.screen_width;
+            geometry.outer_height = geometry.screen_height.saturating_sub(status);
+            geometry.inner_width = geometry.outer_width;
+            geometry.inner_height = geometry
+                .outer_height
+                .saturating_sub(insets.top + insets.bottom);
+            return geometry;
+        }
+
+        let border = insets.side;
+        let (x, y, width, height) = match window {
+            WindowState::Maximized => (
+                avail.left as i32 - border as i32,
+                avail.top as i32 - border as i32,
+                avail.width + 2 * border,
+                avail.height + 2 * border,
+            ),
+            WindowState::Windowed {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                let width = width.clamp(MIN_WINDOW.0, avail.width + 2 * border);
+                let height = height.clamp(MIN_WINDOW.1, avail.height + 2 * border);
+                (x, y, width, height)
+            }
+        };
+        geometry.window_x += x;
+        geometry.window_y += y;
+        geometry.outer_width = width;
+        geometry.outer_height = height;
+        let inner_width = width - 2 * border;
+        let inner_height = height.saturating_sub(insets.top + insets.bottom);
+        geometry.inner_width = (inner_width as f64 / zoom).round() as u32;
+        geometry.inner_height = (inner_height as f64 / zoom).round() as u32;
+        geometry
+    }
+
+    /// The device metrics override for this geometry.
+    ///
+    /// Headless windows have no browser UI, so the viewport is pinned to the
+    /// inner size. A headed window already has the right viewport; only the
+    /// scale factor and screen are overridden, unless it is zoomed: then the
+    /// smaller CSS viewport is scaled up to fill the window, as Chrome's own
+    /// zoom does.
+    pub fn device_metrics(&self, headless: bool) -> SetDeviceMetricsOverrideParams {
+        let zoomed = self.zoom != 1.0;
+        let (width, height) = if headless || zoomed {
+            (self.inner_width as i64, self.inner_height as i64)
+        } else {
+            // 0 leaves the size alone
+            (0, 0)
+        };
+        let mut params = SetDeviceMetricsOverrideParams::new(
+            width,
+            height,
+            self.device_pixel_ratio,
+            self.mobile,
+        );
+        if zoomed && !headless {
+            params.scale = Some(self.zoom);
+        }
+        params.screen_width = Some(self.screen_width as i64);
+        params.screen_height = Some(self.screen_height as i64);
+        params
+    }
+
+    /// Script pinning the window position and outer size a page reads.
+    pub(crate) fn window_script(&self) -> String {
+        format!(
+            r#"
+                for (const [key, value] of [['screenX', {x}], ['screenLeft', {x}],
+                                            ['screenY', {y}], ['screenTop', {y}],
+                                            ['outerWidth', {width}],
+                                            ['outerHeight', {height}]]) {{
+                    Object.defineProperty(window, key, {{
+                        get: () => value,
+                        configurable: true, enumerable: true
+                    }});
+                }}"#,
+            x = self.window_x,
+            y = self.window_y,
+            width = self.outer_width,
+            height = self.outer_height,
+        )
+    }
+}
+
+fn default_window(profile: &ChaserProfile) -> WindowState {
+    let avail = profile.avail_area();
+    let (origin_x, origin_y) = profile.screen_origin();
+    let (x, y) = profile.window_position();
+    let (x, y) = (x - origin_x, y - origin_y);
+    WindowState::Windowed {
+        x,
+        y,
+        width: (avail.left + avail.width).saturating_sub(x.max(0) as u32),
+        height: (avail.top + avail.height).saturating_sub(y.max(0) as u32),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_nest_inside_each_other() {
+        for profile in [
+            ChaserProfile::windows().build(),
+            ChaserProfile::macos_arm().build(),
+            ChaserProfile::linux().build(),
+            ChaserProfile::android().build(),
+            ChaserProfile::ios().build(),
+        ] {
+            let g = Geometry::resolve(&profile);
+            assert!(g.inner_width <= g.outer_width && g.inner_height < g.outer_height);
+            assert!(g.inner_width <= g.avail.width && g.inner_height <= g.avail.height);
+            assert!(g.outer_width <= g.screen_width + 16, "{profile}");
+        }
+
+        let mac = ChaserProfile::macos_arm().build();
+        let g = Geometry::resolve_with(
+            &mac,
+            WindowState::Windowed {
+                x: 100,
+                y: 60,
+                width: 100,
+                height: 5_000,
+            },
+        );
+        assert_eq!((g.outer_width, g.outer_height), (500, 1117 - 38));
+        assert_eq!((g.window_x, g.window_y), (100, 60));
+
+        let zoomed = ChaserProfile::windows().zoom(125).build();
+        let g = Geometry::resolve_with(&zoomed, WindowState::Maximized);
+        assert_eq!((g.outer_width, g.inner_width), (1936, 1536));
+        assert_eq!(g.device_pixel_ratio, 1.25);
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::VecDeque;
 use std::collections::{HashMap, HashSet};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::time::{Duration, Instant};
 
 use serde_json::map::Entry;
@@ -22,8 +22,18 @@ use crate::handler::http::HttpRequest;
 use crate::handler::REQUEST_TIMEOUT;
 use crate::{cmd::CommandChain, ArcHttpRequest};
 
-pub const UTILITY_WORLD_NAME: &str = "util";
-const EVALUATION_SCRIPT_URL: &str = "app.js";
+/// The name of the isolated world the handler creates in every frame,
+/// random per process.
+pub fn utility_world_name() -> &'static str {
+    static NAME: OnceLock<String> = OnceLock::new();
+    NAME.get_or_init(crate::utils::random_identifier)
+}
+
+/// The `sourceURL` of the utility world's script, random per process.
+fn evaluation_script_url() -> &'static str {
+    static URL: OnceLock<String> = OnceLock::new();
+    URL.get_or_init(|| format!("{}.js", crate::utils::random_identifier()))
+}
 
 /// Represents a frame on the page
 #[derive(Debug)]
@@ -457,7 +467,7 @@ impl FrameManager {
                     frame
                         .main_world
                         .set_context(event.context.id, event.context.unique_id.clone());
-                } else if event.context.name == UTILITY_WORLD_NAME
+                } else if event.context.name == utility_world_name()
                     && frame.secondary_world.execution_context().is_none()
                 {
                     frame
@@ -532,7 +542,7 @@ impl FrameManager {
         }
         self.isolated_worlds.insert(world_name.to_string());
         let cmd = AddScriptToEvaluateOnNewDocumentParams::builder()
-            .source(format!("//# sourceURL={EVALUATION_SCRIPT_URL}"))
+            .source(format!("//# sourceURL={}", evaluation_script_url()))
             .world_name(world_name)
             .build()
             .unwrap();
@@ -11,14 +11,16 @@ use futures::channel::oneshot::Sender as OneshotSender;
 use futures::stream::{Fuse, Stream, StreamExt};
 use futures::task::{Context, Poll};
 
-use crate::listeners::{EventListenerRequest, EventListeners};
+use crate::listeners::{EventListenerRequest, EventListeners, DEFAULT_EVENT_BUFFER};
 use chromiumoxide_cdp::cdp::browser_protocol::browser::*;
 use chromiumoxide_cdp::cdp::browser_protocol::target::*;
 use chromiumoxide_cdp::cdp::events::CdpEvent;
 use chromiumoxide_cdp::cdp::events::CdpEventMessage;
 use chromiumoxide_types::{CallId, Message, Method, Response};
 use chromiumoxide_types::{MethodId, Request as CdpRequest};
-pub(crate) use page::PageInner;
+pub(crate) use page::{PageHandle, PageInner};
+#[cfg(feature = "tokio-runtime")]
+pub use task::HandlerTask;
 
 use crate::cmd::{to_command_response, CommandMessage};
 use crate::conn::Connection;
@@ -49,6 +51,8 @@ mod page;
 mod session;
 pub mod target;
 pub mod target_message_future;
+#[cfg(feature = "tokio-runtime")]
+pub mod task;
 pub mod viewport;
 
 /// The handler that monitors the state of the chromium browser and drives all
@@ -123,8 +127,8 @@ impl Handler {
             conn,
             evict_command_timeout: PeriodicJob::new(config.request_timeout),
             next_navigation_id: 0,
+            event_listeners: EventListeners::with_capacity(config.event_buffer),
             config,
-            event_listeners: Default::default(),
             closing: false,
             contexts: Arc::new(DashMap::new()),
         }
@@ -455,6 +459,7 @@ impl Handler {
             TargetConfig {
                 ignore_https_errors: self.config.ignore_https_errors,
                 request_timeout: self.config.request_timeout,
+                event_buffer: self.config.event_buffer,
                 viewport: self.config.viewport.clone(),
                 request_intercept: self.config.request_intercept,
                 cache_enabled: self.config.cache_enabled,
@@ -690,6 +695,8 @@ pub struct HandlerConfig {
     pub context_ids: Vec<BrowserContextId>,
     /// default request timeout to use
     pub request_timeout: Duration,
+    /// Unread events allowed per event stream
+    pub event_buffer: usize,
     /// Whether to enable request interception
     pub request_intercept: bool,
     /// Whether to enable cache
@@ -704,6 +711,7 @@ impl Default for HandlerConfig {
             viewport: Default::default(),
             context_ids: Vec::new(),
             request_timeout: Duration::from_millis(REQUEST_TIMEOUT),
+            event_buffer: DEFAULT_EVENT_BUFFER,
             request_intercept: false,
             cache_enabled: true,
         }
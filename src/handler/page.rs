@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use futures::channel::mpsc::{channel, Receiver, Sender};
-use futures::channel::oneshot::channel as oneshot_channel;
+use futures::channel::oneshot::{channel as oneshot_channel, Receiver as OneshotReceiver};
 use futures::stream::Fuse;
 use futures::{SinkExt, StreamExt};
 
@@ -25,7 +25,7 @@ use chromiumoxide_cdp::cdp::browser_protocol::target::{ActivateTargetParams, Ses
 use chromiumoxide_cdp::cdp::js_protocol::runtime::{
     CallFunctionOnParams, CallFunctionOnReturns, EvaluateParams, ExecutionContextId, RemoteObjectId,
 };
-use chromiumoxide_types::{Command, CommandResponse};
+use chromiumoxide_types::{Command, CommandResponse, Response};
 
 use crate::cmd::{to_command_response, CommandMessage};
 use crate::error::{CdpError, Result};
@@ -79,6 +79,32 @@ impl PageInner {
         execute(cmd, self.sender.clone(), Some(self.session_id.clone())).await
     }
 
+    /// Execute a PDL command in another session, e.g. that of an
+    /// out-of-process iframe, or the browser's with `None`
+    pub(crate) async fn execute_in_session<T: Command>(
+        &self,
+        cmd: T,
+        session: Option<SessionId>,
+    ) -> Result<CommandResponse<T::Response>> {
+        execute(cmd, self.sender.clone(), session).await
+    }
+
+    /// Send a PDL command without waiting for its response, which arrives
+    /// on the returned receiver. Commands enqueued one after another reach
+    /// the browser in that order.
+    pub(crate) async fn enqueue<T: Command>(
+        &self,
+        cmd: T,
+    ) -> Result<OneshotReceiver<Result<Response>>> {
+        let (tx, rx) = oneshot_channel();
+        let message = CommandMessage::with_session(cmd, tx, Some(self.session_id.clone()))?;
+        self.sender
+            .clone()
+            .send(TargetMessage::Command(message))
+            .await?;
+        Ok(rx)
+    }
+
     /// Create a PDL command future
     pub(crate) fn command_future<T: Command>(&self, cmd: T) -> Result<CommandFuture<T>> {
         CommandFuture::new(cmd, self.sender.clone(), Some(self.session_id.clone()))
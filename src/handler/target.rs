@@ -26,15 +26,15 @@ use crate::error::{CdpError, Result};
 use crate::handler::browser::BrowserContext;
 use crate::handler::domworld::DOMWorldKind;
 use crate::handler::emulation::EmulationManager;
+use crate::handler::frame::{utility_world_name, FrameNavigationRequest};
 use crate::handler::frame::{
     FrameEvent, FrameManager, NavigationError, NavigationId, NavigationOk,
 };
-use crate::handler::frame::{FrameNavigationRequest, UTILITY_WORLD_NAME};
 use crate::handler::network::{NetworkEvent, NetworkManager};
 use crate::handler::page::PageHandle;
 use crate::handler::viewport::Viewport;
 use crate::handler::{PageInner, REQUEST_TIMEOUT};
-use crate::listeners::{EventListenerRequest, EventListeners};
+use crate::listeners::{EventListenerRequest, EventListeners, DEFAULT_EVENT_BUFFER};
 use crate::{page::Page, ArcHttpRequest};
 use chromiumoxide_cdp::cdp::js_protocol::runtime::{
     ExecutionContextId, RunIfWaitingForDebuggerParams,
@@ -108,6 +108,7 @@ impl Target {
         Self {
             info,
             r#type: ty,
+            event_listeners: EventListeners::with_capacity(config.event_buffer),
             config,
             frame_manager: FrameManager::new(request_timeout),
             network_manager,
@@ -117,7 +118,6 @@ impl Target {
             init_state: TargetInit::AttachToTarget,
             wait_for_frame_navigation: Default::default(),
             queued_events: Default::default(),
-            event_listeners: Default::default(),
             initiator: None,
             browser_context,
         }
@@ -345,8 +345,9 @@ impl Target {
                 if let Poll::Ready(poll) = cmds.poll(now) {
                     return match poll {
                         None => {
-                            if let Some(isolated_world_cmds) =
-                                self.frame_manager.ensure_isolated_world(UTILITY_WORLD_NAME)
+                            if let Some(isolated_world_cmds) = self
+                                .frame_manager
+                                .ensure_isolated_world(utility_world_name())
                             {
                                 *cmds = isolated_world_cmds;
                             } else {
@@ -599,6 +600,8 @@ pub struct TargetConfig {
     pub ignore_https_errors: bool,
     ///  Request timeout to use
     pub request_timeout: Duration,
+    /// Unread events allowed per event stream
+    pub event_buffer: usize,
     pub viewport: Option<Viewport>,
     pub request_intercept: bool,
     pub cache_enabled: bool,
@@ -609,6 +612,7 @@ impl Default for TargetConfig {
         Self {
             ignore_https_errors: true,
             request_timeout: Duration::from_secs(REQUEST_TIMEOUT),
+            event_buffer: DEFAULT_EVENT_BUFFER,
             viewport: Default::default(),
             request_intercept: false,
             cache_enabled: true,
@@ -0,0 +1,103 @@
+//! Driving a [`Handler`] on its own tokio task.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use tokio::task::JoinHandle;
+
+use crate::handler::Handler;
+
+/// A [`Handler`] running on a tokio task, returned by [`Handler::spawn`].
+///
+/// The handler stops by itself once the browser is closed. Dropping the
+/// task leaves it running, like a [`JoinHandle`]; use
+/// [`shutdown`](Self::shutdown) to make sure it is gone.
+#[derive(Debug)]
+pub struct HandlerTask {
+    task: JoinHandle<()>,
+    errors: Arc<AtomicUsize>,
+}
+
+impl Handler {
+    /// Drive this handler on a new tokio task, replacing the usual
+    /// `while handler.next().await.is_some() {}` loop.
+    ///
+    /// Errors from the connection are logged and counted, see
+    /// [`HandlerTask::errors`]. Event streams are bounded by
+    /// [`BrowserConfigBuilder::event_buffer`](crate::browser::BrowserConfigBuilder::event_buffer),
+    /// so a listener that falls behind loses events with a warning instead
+    /// of growing memory.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use chaser_oxide::{Browser, BrowserConfig};
+    /// # async fn demo() -> Result<(), Box<dyn std::error::Error>> {
+    /// let (mut browser, handler) = Browser::launch(BrowserConfig::builder().build()?).await?;
+    /// let handler = handler.spawn();
+    /// // ...
+    /// browser.close().await?;
+    /// handler.shutdown(std::time::Duration::from_secs(5)).await;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn spawn(mut self) -> HandlerTask {
+        let errors = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&errors);
+        let task = tokio::spawn(async move {
+            while let Some(result) = self.next().await {
+                if let Err(e) = result {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                    tracing::warn!("browser handler error: {}", e);
+                }
+            }
+            tracing::debug!("browser handler stopped");
+        });
+        HandlerTask { task, errors }
+    }
+}
+
+impl HandlerTask {
+    /// A handler with nothing to drive, for a browser on a transport.
+    #[cfg(test)]
+    pub(crate) fn idle() -> Self {
+        HandlerTask {
+            task: tokio::spawn(async {}),
+            errors: Arc::default(),
+        }
+    }
+
+    /// How many errors the handler reported and recovered from.
+    pub fn errors(&self) -> usize {
+        self.errors.load(Ordering::Relaxed)
+    }
+
+    /// Whether the handler stopped, because the browser was closed or its
+    /// connection ended.
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Wait until the handler stops.
+    pub async fn join(self) {
+        let _ = self.task.await;
+    }
+
+    /// Stop the handler now. Every command still waiting for the browser
+    /// fails.
+    pub fn abort(&self) {
+        self.task.abort();
+    }
+
+    /// Give the handler `grace` to finish by itself, typically after
+    /// `Browser::close`, then abort it.
+    pub async fn shutdown(self, grace: Duration) {
+        let mut task = self.task;
+        if tokio::time::timeout(grace, &mut task).await.is_err() {
+            task.abort();
+            let _ = task.await;
+        }
+    }
+}
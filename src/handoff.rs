@@ -0,0 +1,270 @@
+//! Attended mode: hand a headed page over to a human and wait.
+//!
+//! Some steps (hard captchas, ID checks, 2FA prompts) are best solved by a
+//! person. [`ChaserPage::handoff_to_human`] brings the window to the front,
+//! shows a banner explaining what to do and pauses the flow until the human
+//! presses "Continue" or an optional JavaScript predicate becomes truthy.
+//!
+//! The banner lives in a closed shadow root and is driven from the isolated
+//! world, so page scripts cannot read its text or click it programmatically.
+//! It is re-inserted after navigations that happen while the human works.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::handoff::HandoffOptions;
+//!
+//! let outcome = chaser
+//!     .handoff_to_human_with(
+//!         "Solve the captcha, then press Continue",
+//!         HandoffOptions::default().until("!!document.querySelector('#dashboard')"),
+//!     )
+//!     .await?;
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::ChaserResult as Result;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Options of [`ChaserPage::handoff_to_human_with`].
+#[derive(Debug, Clone)]
+pub struct HandoffOptions {
+    until: Option<String>,
+    timeout: Option<Duration>,
+    poll_interval: Duration,
+}
+
+impl Default for HandoffOptions {
+    fn default() -> Self {
+        Self {
+            until: None,
+            timeout: None,
+            poll_interval: Duration::from_millis(500),
+        }
+    }
+}
+
+impl HandoffOptions {
+    /// Also resume as soon as this JavaScript expression is truthy.
+    pub fn until(mut self, predicate: impl Into<String>) -> Self {
+        self.until = Some(predicate.into());
+        self
+    }
+
+    /// Give up waiting after `timeout` (default: wait forever).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// How often the banner and predicate are checked (default: 500ms).
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+}
+
+/// Why an attended handoff ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandoffOutcome {
+    /// The human pressed "Continue".
+    Continued,
+    /// The `until` predicate passed.
+    PredicatePassed,
+    /// The timeout elapsed first.
+    TimedOut,
+}
+
+fn banner_script(host_id: &str, reason: &str) -> String {
+    format!(
+        r#"(() => {{
+    let host = document.getElementById({id});
+    if (!host) {{
+        host = document.createElement('div');
+        host.id = {id};
+        host.style.cssText = 'all:initial;position:fixed;top:0;left:0;right:0;z-index:2147483647;';
+        const root = host.attachShadow({{ mode: 'closed' }});
+        root.innerHTML = `
+            <style>
+                .bar {{ font: 14px/1.4 system-ui, sans-serif; background: #1f2937; color: #f9fafb;
+                        padding: 10px 16px; display: flex; gap: 16px; align-items: center;
+                        box-shadow: 0 2px 8px rgba(0,0,0,.3); }}
+                .reason {{ flex: 1; }}
+                button {{ font: inherit; background: #10b981; color: #fff; border: 0;
+                          border-radius: 4px; padding: 6px 14px; cursor: pointer; }}
+            </style>
+            <div class="bar"><span class="reason"></span><button>Continue</button></div>`;
+        root.querySelector('.reason').textContent = {reason};
+        root.querySelector('button').addEventListener('click', (e) => {{
+            if (e.isTrusted) host.dataset.done = '1';
+        }});
+        (document.body || document.documentElement).appendChild(host);
+    }}
+    return host.dataset.done === '1';
+}})()"#,
+        id = serde_json::to_string(host_id).unwrap_or_default(),
+        reason = serde_json::to_string(reason).unwrap_or_default(),
+    )
+}
+
+impl ChaserPage {
+    /// Hand the page to a human until they press "Continue".
+    ///
+    /// Only meaningful for headed browsers; see [`HandoffOptions`] for
+    /// predicates and timeouts.
+    pub async fn handoff_to_human(&self, reason: &str) -> Result<HandoffOutcome> {
+        self.handoff_to_human_with(reason, HandoffOptions::default())
+            .await
+    }
+
+    /// Hand the page to a human with explicit options.
+    pub async fn handoff_to_human_with(
+        &self,
+        reason: &str,
+        options: HandoffOptions,
+    ) -> Result<HandoffOutcome> {
+        self.raw_page().bring_to_front().await?;
+
+        let host_id = format!("h{}", uuid::Uuid::new_v4().simple());
+        let script = banner_script(&host_id, reason);
+        let started = Instant::now();
+        tracing::info!("waiting for human: {}", reason);
+
+        let outcome = loop {
+            // navigation in progress: the isolated world is gone, retry next tick
+            if let Ok(Some(value)) = self.evaluate_stealth(&script).await {
+                if value.as_bool() == Some(true) {
+                    break HandoffOutcome::Continued;
+                }
+            }
+            if let Some(predicate) = &options.until {
+                let passed = self
+                    .evaluate_stealth(&format!("!!({})", predicate))
+                    .await
+                    .ok()
+                    .flatten()
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                if passed {
+                    break HandoffOutcome::PredicatePassed;
+                }
+            }
+            if options.timeout.is_some_and(|t| started.elapsed() >= t) {
+                break HandoffOutcome::TimedOut;
+            }
+            tokio::time::sleep(options.poll_interval).await;
+        };
+
+        let _ = self
+            .evaluate_stealth(&format!(
+                "document.getElementById({})?.remove()",
+                serde_json::to_string(&host_id)?
+            ))
+            .await;
+        Ok(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::page::Page;
+    use crate::transport::MockTransport;
+    use serde_json::{json, Value};
+
+    fn boolean(value: bool) -> Value {
+        json!({ "result": { "type": "boolean", "value": value } })
+    }
+
+    /// A page where the banner reports "Continue" from its `done`th check
+    /// on, and the predicate `window.ready` never passes.
+    fn page(done: usize) -> (ChaserPage, MockTransport) {
+        let mock = MockTransport::new();
+        let mut checks = 0;
+        mock.respond_with("Runtime.evaluate", move |params| {
+            let expression = params["expression"].as_str().unwrap_or_default();
+            if expression.contains("attachShadow") {
+                checks += 1;
+                return Ok(boolean(checks >= done));
+            }
+            Ok(boolean(expression.contains("passes")))
+        });
+        (ChaserPage::new(Page::with_transport(mock.clone())), mock)
+    }
+
+    fn expressions(mock: &MockTransport) -> Vec<String> {
+        mock.commands_to("Runtime.evaluate")
+            .into_iter()
+            .map(|params| params["expression"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn the_banner_quotes_its_reason() {
+        let script = banner_script("hx", "Press `Continue` when \"done\"</div>");
+        assert!(script.contains(r#".textContent = "Press `Continue` when \"done\"</div>";"#));
+        assert!(script.contains(r#"document.getElementById("hx")"#));
+        assert!(script.contains("mode: 'closed'"));
+        assert!(script.contains("if (e.isTrusted)"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_for_the_human_to_continue() {
+        let (chaser, mock) = page(3);
+        let outcome = chaser.handoff_to_human("Solve the captcha").await.unwrap();
+        assert_eq!(outcome, HandoffOutcome::Continued);
+        assert_eq!(mock.commands_to("Page.bringToFront").len(), 1);
+
+        let expressions = expressions(&mock);
+        let banners = expressions
+            .iter()
+            .filter(|e| e.contains("attachShadow"))
+            .count();
+        assert_eq!(banners, 3);
+        // the banner is taken down again
+        assert!(expressions.last().unwrap().ends_with("?.remove()"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_predicate_or_the_timeout_ends_the_wait() {
+        let (chaser, _) = page(usize::MAX);
+        let options = HandoffOptions::default().until("window.passes");
+        assert_eq!(
+            chaser
+                .handoff_to_human_with("Log in", options)
+                .await
+                .unwrap(),
+            HandoffOutcome::PredicatePassed
+        );
+
+        let (chaser, mock) = page(usize::MAX);
+        let started = Instant::now();
+        let options = HandoffOptions::default()
+            .until("window.ready")
+            .timeout(Duration::from_secs(5))
+            .poll_interval(Duration::from_secs(1));
+        assert_eq!(
+            chaser
+                .handoff_to_human_with("Log in", options)
+                .await
+                .unwrap(),
+            HandoffOutcome::TimedOut
+        );
+        assert_eq!(started.elapsed(), Duration::from_secs(5));
+        assert!(expressions(&mock).contains(&"!!(window.ready)".to_string()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn failed_checks_are_retried() {
+        // e.g. while the page navigates and the isolated world is gone
+        let (chaser, mock) = page(2);
+        mock.fail("Runtime.evaluate", "Cannot find context with specified id");
+        let waiting = tokio::spawn(async move { chaser.handoff_to_human("Verify").await });
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert!(!waiting.is_finished());
+
+        mock.respond("Runtime.evaluate", boolean(true));
+        assert_eq!(waiting.await.unwrap().unwrap(), HandoffOutcome::Continued);
+    }
+}
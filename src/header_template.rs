@@ -0,0 +1,386 @@
+//! The full, ordered header set Chrome sends with each kind of request.
+//!
+//! Spoofing a user agent and leaving the rest alone produces requests no
+//! Chrome sends: a `Sec-CH-UA` naming another version, `Sec-Fetch-Dest:
+//! empty` on a navigation, an `Accept` from a different browser, or the
+//! right values in the wrong order. Servers that check one of these usually
+//! check them all. A [`HeaderTemplate`] derives every default header of a
+//! [`ChaserProfile`] for a [`RequestType`] and [`FetchSite`], in Chrome's
+//! order, so replayed or rewritten requests match what the profile's
+//! browser would send.
+//!
+//! [`ChaserPage::apply_header_template`] rewrites the page's own requests
+//! through the Fetch domain. Chrome's network service still sets some
+//! headers itself after interception (`Sec-Fetch-*`, `Cookie`,
+//! `Accept-Encoding`), which only keeps them consistent with the real
+//! request; the template decides the rest and the order.
+//!
+//! # Example
+//!
+//! ```
+//! use chaser_oxide::header_template::{FetchSite, HeaderTemplate, RequestType};
+//! use chaser_oxide::profiles::ChaserProfile;
+//!
+//! let template = HeaderTemplate::new(&ChaserProfile::windows().build());
+//! let headers = template.headers(RequestType::Fetch, FetchSite::SameOrigin);
+//! assert!(headers.contains(&("sec-fetch-mode", "cors".to_string())));
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::ChaserResult as Result;
+use crate::partition::registrable_domain;
+use crate::profiles::ChaserProfile;
+use chromiumoxide_cdp::cdp::browser_protocol::fetch::{
+    ContinueRequestParams, EventRequestPaused, HeaderEntry,
+};
+use chromiumoxide_cdp::cdp::browser_protocol::network::ResourceType;
+use futures::StreamExt;
+use tokio::task::JoinHandle;
+use url::Url;
+
+/// What a request loads, which decides its `Accept`, `Sec-Fetch-Mode`,
+/// `Sec-Fetch-Dest` and `Priority`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RequestType {
+    /// A top-level navigation.
+    Document,
+    /// An `XMLHttpRequest`.
+    Xhr,
+    /// A `fetch()` call.
+    Fetch,
+    /// An `<img>` or CSS image.
+    Image,
+}
+
+impl RequestType {
+    /// The type of a request Chrome reports as `resource_type`, if the
+    /// template covers it.
+    pub fn of(resource_type: &ResourceType) -> Option<Self> {
+        match resource_type {
+            ResourceType::Document => Some(RequestType::Document),
+            ResourceType::Xhr => Some(RequestType::Xhr),
+            ResourceType::Fetch => Some(RequestType::Fetch),
+            ResourceType::Image => Some(RequestType::Image),
+            _ => None,
+        }
+    }
+}
+
+/// The relation between the document making a request and its target, as
+/// `Sec-Fetch-Site` reports it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FetchSite {
+    /// User-initiated: typed, bookmarked, opened from outside.
+    None,
+    SameOrigin,
+    SameSite,
+    CrossSite,
+}
+
+impl FetchSite {
+    /// The relation of a request for `target` made from `initiator`, or
+    /// [`FetchSite::None`] without one. Sites are told apart by scheme and
+    /// registrable domain, approximated without a public suffix list.
+    pub fn between(initiator: Option<&Url>, target: &Url) -> Self {
+        let Some(initiator) = initiator else {
+            return FetchSite::None;
+        };
+        if initiator.origin() == target.origin() {
+            return FetchSite::SameOrigin;
+        }
+        let site = |url: &Url| {
+            url.host_str().map(|host| {
+                (
+                    url.scheme().to_string(),
+                    registrable_domain(&host.to_ascii_lowercase()),
+                )
+            })
+        };
+        match (site(initiator), site(target)) {
+            (Some(a), Some(b)) if a == b => FetchSite::SameSite,
+            _ => FetchSite::CrossSite,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FetchSite::None => "none",
+            FetchSite::SameOrigin => "same-origin",
+            FetchSite::SameSite => "same-site",
+            FetchSite::CrossSite => "cross-site",
+        }
+    }
+}
+
+/// The default request headers of one profile's Chrome.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderTemplate {
+    user_agent: String,
+    accept_language: String,
+    /// `Sec-CH-UA`, `Sec-CH-UA-Mobile` and `Sec-CH-UA-Platform`, absent
+    /// where the browser has no client hints.
+    client_hints: Option<[String; 3]>,
+    accept_encoding: &'static str,
+}
+
+impl HeaderTemplate {
+    pub fn new(profile: &ChaserProfile) -> Self {
+        let client_hints = profile.user_agent_metadata().map(|metadata| {
+            let brands = metadata
+                .brands
+                .unwrap_or_default()
+                .iter()
+                .map(|b| format!("\"{}\";v=\"{}\"", b.brand, b.version))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let mobile = if metadata.mobile { "?1" } else { "?0" };
+            [
+                brands,
+                mobile.to_string(),
+                format!("\"{}\"", metadata.platform),
+            ]
+        });
+        Self {
+            user_agent: profile.user_agent(),
+            accept_language: profile.accept_language(),
+            client_hints,
+            // zstd since Chrome 123
+            accept_encoding: if profile.chrome_version() >= 123 {
+                "gzip, deflate, br, zstd"
+            } else {
+                "gzip, deflate, br"
+            },
+        }
+    }
+
+    /// The headers Chrome sends with a `kind` request from a `site`
+    /// context, in its order, without `Cookie`, `Origin`, `Referer` and
+    /// headers set by the page. Client hints are included; Chrome sends
+    /// them to secure origins only, see [`rewrite`](Self::rewrite).
+    pub fn headers(&self, kind: RequestType, site: FetchSite) -> Vec<(&'static str, String)> {
+        let hints = self.client_hints.as_ref();
+        let hint = |i: usize, name: &'static str| hints.map(|h| (name, h[i].clone()));
+        let site = ("sec-fetch-site", site.as_str().to_string());
+        let tail = |priority: &str| {
+            [
+                ("accept-encoding", self.accept_encoding.to_string()),
+                ("accept-language", self.accept_language.clone()),
+                ("priority", priority.to_string()),
+            ]
+        };
+
+        let mut headers = Vec::new();
+        if kind == RequestType::Document {
+            headers.extend(hint(0, "sec-ch-ua"));
+            headers.extend(hint(1, "sec-ch-ua-mobile"));
+            headers.extend(hint(2, "sec-ch-ua-platform"));
+            headers.extend([
+                ("upgrade-insecure-requests", "1".to_string()),
+                ("user-agent", self.user_agent.clone()),
+                ("accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.7".to_string()),
+                site,
+                ("sec-fetch-mode", "navigate".to_string()),
+                ("sec-fetch-user", "?1".to_string()),
+                ("sec-fetch-dest", "document".to_string()),
+            ]);
+            headers.extend(tail("u=0, i"));
+            return headers;
+        }
+
+        let (accept, mode, dest, priority) = match kind {
+            RequestType::Image => (
+                "image/avif,image/webp,image/apng,image/svg+xml,image/*,*/*;q=0.8",
+                "no-cors",
+                "image",
+                "i",
+            ),
+            _ => ("*/*", "cors", "empty", "u=1, i"),
+        };
+        headers.extend(hint(2, "sec-ch-ua-platform"));
+        headers.push(("user-agent", self.user_agent.clone()));
+        headers.extend(hint(0, "sec-ch-ua"));
+        headers.extend(hint(1, "sec-ch-ua-mobile"));
+        headers.extend([
+            ("accept", accept.to_string()),
+            site,
+            ("sec-fetch-mode", mode.to_string()),
+            ("sec-fetch-dest", dest.to_string()),
+        ]);
+        headers.extend(tail(priority));
+        headers
+    }
+
+    /// The headers of a `kind` request for `url`, made from `site`,
+    /// carrying over what the template does not define from `original`:
+    /// `Content-Type` and page-set headers after the client hints,
+    /// `Origin` before `Sec-Fetch-Site`, `Referer` after `Sec-Fetch-Dest`
+    /// and `Cookie` after `Accept-Language`, as Chrome orders them. Client
+    /// hints are left out for insecure origins.
+    pub fn rewrite(
+        &self,
+        kind: RequestType,
+        site: FetchSite,
+        url: &Url,
+        original: &[(String, String)],
+    ) -> Vec<(String, String)> {
+        let secure = url.scheme() == "https"
+            || matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"));
+        let mut headers: Vec<(String, String)> = self
+            .headers(kind, site)
+            .into_iter()
+            .filter(|(name, _)| secure || !name.starts_with("sec-ch-ua"))
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        let defined: Vec<String> = headers.iter().map(|(name, _)| name.clone()).collect();
+
+        let position =
+            |headers: &[(String, String)], name: &str| headers.iter().position(|(n, _)| n == name);
+        for (name, value) in original {
+            let lower = name.to_ascii_lowercase();
+            if defined.contains(&lower) || lower.starts_with("sec-ch-ua") {
+                continue;
+            }
+            let at = match lower.as_str() {
+                "origin" => position(&headers, "sec-fetch-site"),
+                "referer" => position(&headers, "sec-fetch-dest").map(|i| i + 1),
+                "cookie" => position(&headers, "accept-language").map(|i| i + 1),
+                // with the page's own headers, after the last client hint
+                // or the user agent
+                _ => position(&headers, "accept").or_else(|| position(&headers, "sec-fetch-site")),
+            };
+            let at = at.unwrap_or(headers.len());
+            headers.insert(at, (lower, value.clone()));
+        }
+        headers
+    }
+}
+
+/// Rewrites the page's requests until dropped.
+#[derive(Debug)]
+pub(crate) struct HeaderRewrite {
+    task: JoinHandle<()>,
+}
+
+impl Drop for HeaderRewrite {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl ChaserPage {
+    /// Rewrite the headers of this page's documents, XHR, fetches and
+    /// images to `template` before they are sent. Other requests continue
+    /// unchanged. Replaces an earlier template.
+    ///
+    /// `Sec-Fetch-Site` is derived from the request's `Origin` or
+    /// `Referer`; a document request with neither counts as typed.
+    pub async fn apply_header_template(&self, template: HeaderTemplate) -> Result<()> {
+        let mut paused = self
+            .raw_page()
+            .event_listener::<EventRequestPaused>()
+            .await?;
+        self.enable_request_interception("*", None).await?;
+        let page = self.raw_page().clone();
+        let task = tokio::spawn(async move {
+            while let Some(event) = paused.next().await {
+                let mut params = ContinueRequestParams::new(event.request_id.clone());
+                let url = Url::parse(&event.request.url).ok();
+                if let (Some(kind), Some(url)) = (RequestType::of(&event.resource_type), url) {
+                    let original: Vec<(String, String)> = event
+                        .request
+                        .headers
+                        .inner()
+                        .as_object()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|(name, value)| {
+                            Some((name.clone(), value.as_str()?.to_string()))
+                        })
+                        .collect();
+                    let initiator = original
+                        .iter()
+                        .find(|(name, _)| name.eq_ignore_ascii_case("origin"))
+                        .or_else(|| {
+                            original
+                                .iter()
+                                .find(|(name, _)| name.eq_ignore_ascii_case("referer"))
+                        })
+                        .and_then(|(_, value)| Url::parse(value).ok());
+                    let site = FetchSite::between(initiator.as_ref(), &url);
+                    params.headers = Some(
+                        template
+                            .rewrite(kind, site, &url, &original)
+                            .into_iter()
+                            .map(|(name, value)| HeaderEntry { name, value })
+                            .collect(),
+                    );
+                }
+                if let Err(e) = page.execute(params).await {
+                    tracing::debug!("failed to continue {}: {}", event.request.url, e);
+                }
+            }
+        });
+        *self.header_rewrite().lock().unwrap() = Some(HeaderRewrite { task });
+        Ok(())
+    }
+
+    /// Stop rewriting request headers and turn interception off.
+    pub async fn clear_header_template(&self) -> Result<()> {
+        self.header_rewrite().lock().unwrap().take();
+        self.disable_request_interception().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_follow_chrome_order() {
+        let template = HeaderTemplate::new(&ChaserProfile::windows().build());
+        let names = |headers: Vec<(String, String)>| -> Vec<String> {
+            headers.into_iter().map(|(name, _)| name).collect()
+        };
+
+        let url = Url::parse("https://shop.example/").unwrap();
+        let document = template.rewrite(RequestType::Document, FetchSite::None, &url, &[]);
+        assert_eq!(document[0].0, "sec-ch-ua");
+        assert!(document.contains(&("sec-fetch-user".into(), "?1".into())));
+
+        let api = Url::parse("https://api.shop.example/cart").unwrap();
+        let page = Url::parse("https://shop.example/cart").unwrap();
+        let site = FetchSite::between(Some(&page), &api);
+        assert_eq!(site, FetchSite::SameSite);
+        let original = [
+            ("Referer".to_string(), page.to_string()),
+            ("Content-Type".to_string(), "application/json".to_string()),
+            ("User-Agent".to_string(), "HeadlessChrome".to_string()),
+            ("Origin".to_string(), "https://shop.example".to_string()),
+        ];
+        let fetch = template.rewrite(RequestType::Fetch, site, &api, &original);
+        assert_eq!(
+            names(fetch.clone()),
+            [
+                "sec-ch-ua-platform",
+                "user-agent",
+                "sec-ch-ua",
+                "sec-ch-ua-mobile",
+                "content-type",
+                "accept",
+                "origin",
+                "sec-fetch-site",
+                "sec-fetch-mode",
+                "sec-fetch-dest",
+                "referer",
+                "accept-encoding",
+                "accept-language",
+                "priority",
+            ]
+        );
+        assert!(!fetch[1].1.contains("Headless"));
+
+        let insecure = Url::parse("http://shop.example/logo.png").unwrap();
+        let image = template.rewrite(RequestType::Image, FetchSite::CrossSite, &insecure, &[]);
+        assert!(image.iter().all(|(name, _)| !name.starts_with("sec-ch-ua")));
+    }
+}
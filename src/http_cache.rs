@@ -0,0 +1,183 @@
+//! Inspecting, priming and carrying over the HTTP cache.
+//!
+//! A returning visitor loads most of a site's scripts, styles and fonts
+//! from cache. A browser whose cache is cold on every visit is slower and
+//! downloads a pattern of assets no regular visitor would. This module
+//! helps in three ways:
+//!
+//! - [`LaunchArgs::disk_cache_dir`](crate::launch_args::LaunchArgs::disk_cache_dir)
+//!   points Chrome at a cache directory that outlives the browser, and
+//!   [`snapshot`] copies one so several browsers can start from the same
+//!   session without sharing (and corrupting) it;
+//! - [`ChaserPage::warm_cache`] fetches asset URLs from the page, so a
+//!   later visit finds them cached;
+//! - [`ChaserPage::cache_report`] tells which resources of the current
+//!   document were served from cache.
+//!
+//! Only the default browser context caches on disk. The contexts of
+//! [`ChaserContext`](crate::context::ChaserContext) and the pool keep their
+//! cache in memory and lose it when disposed; warm them instead.
+//!
+//! The cache is keyed by the top-level site, so warm it from a page of the
+//! site that will load the assets.
+//!
+//! # Example
+//!
+//! ```ignore
+//! page.goto("https://shop.example/").await?;
+//! page.warm_cache(&["https://shop.example/static/app.js"]).await?;
+//! // ... later, after the next navigation
+//! let report = page.cache_report().await?;
+//! println!("{} of {} resources from cache", report.hits(), report.resources.len());
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::{ChaserError, ChaserResult as Result};
+use chromiumoxide_cdp::cdp::browser_protocol::network::ClearBrowserCacheParams;
+use serde::Deserialize;
+use std::path::Path;
+
+/// A resource the current document loaded.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct CachedResource {
+    pub url: String,
+    /// `script`, `link`, `img`, `fetch`, ... as the resource timing entry
+    /// reports it.
+    #[serde(rename = "initiatorType")]
+    pub initiator: String,
+    /// Served from the HTTP cache without a network round-trip.
+    #[serde(rename = "fromCache")]
+    pub from_cache: bool,
+    /// The size of the response body, decoded.
+    pub bytes: u64,
+}
+
+/// Which resources of a document came from cache, see
+/// [`ChaserPage::cache_report`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheReport {
+    pub resources: Vec<CachedResource>,
+}
+
+impl CacheReport {
+    /// Resources served from cache.
+    pub fn hits(&self) -> usize {
+        self.resources.iter().filter(|r| r.from_cache).count()
+    }
+
+    /// Share of the body bytes that came from cache, in `[0, 1]`.
+    pub fn byte_hit_rate(&self) -> f64 {
+        let total: u64 = self.resources.iter().map(|r| r.bytes).sum();
+        if total == 0 {
+            return 0.0;
+        }
+        let cached: u64 = self
+            .resources
+            .iter()
+            .filter(|r| r.from_cache)
+            .map(|r| r.bytes)
+            .sum();
+        cached as f64 / total as f64
+    }
+}
+
+/// Resource timing entries with a zero transfer size but a body were
+/// served from cache. Cross-origin entries without `Timing-Allow-Origin`
+/// report zero for both and count as misses.
+const CACHE_REPORT_SCRIPT: &str = r#"performance.getEntriesByType("resource").map(e => ({
+    url: e.name,
+    initiatorType: e.initiatorType,
+    fromCache: e.transferSize === 0 && e.decodedBodySize > 0,
+    bytes: e.decodedBodySize,
+}))"#;
+
+fn warm_script(urls: &[&str]) -> String {
+    format!(
+        r#"Promise.all({}.map(url =>
+    fetch(url, {{ mode: "no-cors", credentials: "include", priority: "low" }})
+        .then(r => r.blob().then(() => true), () => false)
+)).then(ok => ok.filter(Boolean).length)"#,
+        serde_json::to_string(urls).unwrap_or_default()
+    )
+}
+
+impl ChaserPage {
+    /// Fetch `urls` from this page so they land in its HTTP cache, and
+    /// return how many loaded. Responses the server marks as uncacheable
+    /// load but are not kept.
+    ///
+    /// The requests go out like the page's own subresource requests, with
+    /// its cookies and referrer.
+    pub async fn warm_cache(&self, urls: &[&str]) -> Result<usize> {
+        if urls.is_empty() {
+            return Ok(0);
+        }
+        let loaded = self
+            .evaluate_stealth(&warm_script(urls))
+            .await?
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        Ok(loaded as usize)
+    }
+
+    /// Which resources the current document loaded, and whether each came
+    /// from the HTTP cache.
+    pub async fn cache_report(&self) -> Result<CacheReport> {
+        let Some(entries) = self.evaluate_stealth(CACHE_REPORT_SCRIPT).await? else {
+            return Ok(CacheReport::default());
+        };
+        let resources = serde_json::from_value(entries)
+            .map_err(|e| ChaserError::msg(format!("unreadable resource timings: {e}")))?;
+        Ok(CacheReport { resources })
+    }
+
+    /// Empty the HTTP cache of this page's browser context.
+    pub async fn clear_http_cache(&self) -> Result<()> {
+        self.raw_page()
+            .execute(ClearBrowserCacheParams::default())
+            .await?;
+        Ok(())
+    }
+}
+
+/// Copy the cache directory `from` to `to`, returning the bytes copied.
+///
+/// Take snapshots of a cache no browser is using: Chrome writes its index
+/// lazily, and a copy taken mid-write is discarded as corrupt at the next
+/// launch.
+pub fn snapshot(from: &Path, to: &Path) -> std::io::Result<u64> {
+    std::fs::create_dir_all(to)?;
+    let mut copied = 0;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copied += snapshot(&entry.path(), &target)?;
+        } else {
+            copied += std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_copy_nested_cache_files() {
+        let root = std::env::temp_dir().join(format!("chaser-cache-{}", uuid::Uuid::new_v4()));
+        let from = root.join("Cache_Data");
+        std::fs::create_dir_all(from.join("index-dir")).unwrap();
+        std::fs::write(from.join("data_0"), [0u8; 512]).unwrap();
+        std::fs::write(from.join("index-dir").join("the-real-index"), [1u8; 24]).unwrap();
+
+        let to = root.join("copy");
+        assert_eq!(snapshot(&from, &to).unwrap(), 536);
+        assert_eq!(
+            std::fs::read(to.join("index-dir").join("the-real-index")).unwrap(),
+            [1u8; 24]
+        );
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,142 @@
+//! Sending the events of a humanized gesture without a round-trip each.
+//!
+//! A Bezier mouse path is some 25 `Input.dispatchMouseEvent` commands.
+//! Awaiting every response puts a WebSocket round-trip between consecutive
+//! moves: it stretches the path by the latency of the connection and, with
+//! many pages on one browser, the chatter becomes the bottleneck. An
+//! [`InputPipeline`] sends each event as soon as it is due and only checks
+//! the responses as they come in, so the pauses between events are the
+//! ones the humanization model chose and a path costs one wait at its end.
+//!
+//! Commands leave in the order they are sent, through the same channel as
+//! every other command of the page, so the page sees the same events in
+//! the same order. A failed event surfaces on the next send or at
+//! [`finish`](InputPipeline::finish).
+
+use crate::chaser::ChaserPage;
+use crate::error::{CdpError, ChaserResult as Result};
+use chromiumoxide_types::{Command, Response};
+use futures::channel::oneshot::Receiver;
+
+/// The events of one gesture, sent without waiting for their responses.
+pub(crate) struct InputPipeline {
+    page: ChaserPage,
+    pending: Vec<Receiver<std::result::Result<Response, CdpError>>>,
+}
+
+impl InputPipeline {
+    pub(crate) fn new(page: &ChaserPage) -> Self {
+        Self {
+            page: page.clone(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Send `cmd` behind the events sent before it.
+    pub(crate) async fn send<C: Command>(&mut self, cmd: C) -> Result<()> {
+        self.check()?;
+        let limit = self.page.timeouts().action;
+        let response = self
+            .page
+            .within(limit, "Input dispatch", async {
+                Ok(self.page.raw_page().enqueue(cmd).await?)
+            })
+            .await?;
+        self.pending.push(response);
+        Ok(())
+    }
+
+    /// Fail with the first error among the responses that arrived, and
+    /// forget the successful ones.
+    fn check(&mut self) -> Result<()> {
+        let mut failure = None;
+        self.pending
+            .retain_mut(|response| match response.try_recv() {
+                Ok(None) => true,
+                Ok(Some(Ok(Response {
+                    error: Some(error), ..
+                }))) => {
+                    failure.get_or_insert(CdpError::from(error));
+                    false
+                }
+                Ok(Some(Ok(_))) => false,
+                Ok(Some(Err(error))) => {
+                    failure.get_or_insert(error);
+                    false
+                }
+                Err(canceled) => {
+                    failure.get_or_insert(canceled.into());
+                    false
+                }
+            });
+        match failure {
+            Some(error) => Err(error.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Wait for the responses to every event sent.
+    pub(crate) async fn finish(mut self) -> Result<()> {
+        let limit = self.page.timeouts().action;
+        let pending = std::mem::take(&mut self.pending);
+        self.page
+            .within(limit, "Input dispatch", async {
+                for response in pending {
+                    if let Some(error) = response.await.map_err(CdpError::from)??.error {
+                        return Err(CdpError::from(error).into());
+                    }
+                }
+                Ok(())
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::behavior::InputEvent;
+    use crate::transport::MockTransport;
+    use crate::{ChaserError, ChaserPage, Page};
+    use serde_json::json;
+
+    #[tokio::test(start_paused = true)]
+    async fn moves_are_sent_in_order_and_failures_surface() {
+        let mock = MockTransport::new();
+        let chaser = ChaserPage::new(Page::with_transport(mock.clone()));
+        chaser.set_viewport_size(1280.0, 720.0).await.unwrap();
+        mock.clear();
+
+        chaser.record_behavior();
+        chaser.move_mouse_human(1000.0, 100.0).await.unwrap();
+        let sent: Vec<(f64, f64)> = mock
+            .commands_to("Input.dispatchMouseEvent")
+            .iter()
+            .map(|event| (event["x"].as_f64().unwrap(), event["y"].as_f64().unwrap()))
+            .collect();
+        let recorded: Vec<(f64, f64)> = chaser
+            .behavior_stats()
+            .unwrap()
+            .events()
+            .iter()
+            .filter_map(|(_, event)| match event {
+                InputEvent::Move(point) => Some((point.x, point.y)),
+                _ => None,
+            })
+            .collect();
+        assert!(sent.len() > 5);
+        assert_eq!(sent, recorded);
+
+        mock.respond_with("Input.dispatchMouseEvent", |params| {
+            if params["x"].as_f64().unwrap() < 600.0 {
+                Err(chromiumoxide_types::Error {
+                    code: -32000,
+                    message: "Target closed".to_string(),
+                })
+            } else {
+                Ok(json!({}))
+            }
+        });
+        let error = chaser.move_mouse_human(100.0, 600.0).await.unwrap_err();
+        assert!(matches!(error, ChaserError::Detached), "{error:?}");
+    }
+}
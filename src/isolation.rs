@@ -0,0 +1,457 @@
+//! Checking that nothing links two identities.
+//!
+//! Accounts run side by side stay separate only as long as a site cannot
+//! tie their sessions together. The fingerprint is one way; the others are
+//! quieter: two identities launched on the same user data directory share
+//! cookies and storage, two profiles with the same noise seed render the
+//! exact same client rects, two proxies that leave through the same address
+//! put both accounts on one IP, and a browser clock that is off by the same
+//! odd amount in both is as good as a shared cookie. Nothing fails when that
+//! happens; the accounts are just linked.
+//!
+//! [`IsolationAuditor`] compares the [`Identity`] of everything that runs
+//! together and returns the [`Violation`]s found. Audit the whole set before
+//! a run, or [`admit`](IsolationAuditor::admit) identities one at a time
+//! before each task.
+//!
+//! Proxies are compared by the address they leave through when it is known
+//! ([`Identity::exit_ip`]); otherwise by gateway and user name, since
+//! rotating providers hand out sticky sessions by user name on one gateway.
+//!
+//! # Example
+//!
+//! ```
+//! use chaser_oxide::isolation::{Identity, IsolationAuditor, Violation};
+//!
+//! let mut auditor = IsolationAuditor::new([
+//!     Identity::new("alice").user_data_dir("/data/alice").proxy("http://alice@gate.example:7000"),
+//! ]);
+//! let bob = Identity::new("bob").user_data_dir("/data/alice").proxy("http://bob@gate.example:7000");
+//! let violations = auditor.admit(bob).unwrap_err();
+//! assert!(matches!(violations[0], Violation::SharedUserDataDir { .. }));
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::clock;
+use crate::error::{ChaserError, ChaserResult};
+use crate::pool::PooledPage;
+use crate::profiles::ChaserProfile;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+use url::Url;
+
+/// Everything one identity runs with that could link it to another.
+#[derive(Debug, Clone, Default)]
+pub struct Identity {
+    name: String,
+    profile: Option<ChaserProfile>,
+    proxy: Option<String>,
+    exit_ip: Option<IpAddr>,
+    user_data_dir: Option<PathBuf>,
+    clock_skew_ms: Option<i64>,
+}
+
+impl Identity {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    /// The identity a pooled page runs as: its profile and proxy.
+    pub fn of(name: impl Into<String>, lease: &PooledPage) -> Self {
+        let identity = Self::new(name).profile(lease.profile().clone());
+        match lease.proxy() {
+            Some(proxy) => identity.proxy(proxy),
+            None => identity,
+        }
+    }
+
+    pub fn profile(mut self, profile: ChaserProfile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// The address the proxy leaves through, as an IP echo service reports
+    /// it. Compared instead of the proxy URL when set.
+    pub fn exit_ip(mut self, ip: IpAddr) -> Self {
+        self.exit_ip = Some(ip);
+        self
+    }
+
+    pub fn user_data_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.user_data_dir = Some(dir.into());
+        self
+    }
+
+    /// How far the browser clock is ahead of true time (negative: behind),
+    /// e.g. from [`ChaserPage::clock_skew`].
+    pub fn clock_skew(mut self, skew_ms: i64) -> Self {
+        self.clock_skew_ms = Some(skew_ms);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// What the proxy is compared by: the exit address, or the gateway
+    /// and user name.
+    fn exit(&self) -> Option<String> {
+        if let Some(ip) = self.exit_ip {
+            return Some(ip.to_string());
+        }
+        let proxy = self.proxy.as_deref()?;
+        Some(match Url::parse(proxy) {
+            Ok(url) if url.host_str().is_some() => {
+                let user = url.username();
+                let gateway = format!(
+                    "{}:{}",
+                    url.host_str().unwrap_or_default().to_ascii_lowercase(),
+                    url.port_or_known_default().unwrap_or_default()
+                );
+                if user.is_empty() {
+                    gateway
+                } else {
+                    format!("{user}@{gateway}")
+                }
+            }
+            _ => proxy.to_ascii_lowercase(),
+        })
+    }
+}
+
+/// Something two or more identities share. Identities are referred to by
+/// name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// Same cookies, storage, cache and history.
+    SharedUserDataDir {
+        dir: PathBuf,
+        identities: Vec<String>,
+    },
+    /// Identical canvas and client rect noise.
+    SharedNoiseSeed { seed: u32, identities: Vec<String> },
+    /// One exit address, or one sticky session on a rotating gateway.
+    SharedProxyExit {
+        exit: String,
+        identities: Vec<String>,
+    },
+    /// Browser clocks off from true time by the same unusual amount.
+    SharedClockSkew {
+        skew_ms: i64,
+        identities: Vec<String>,
+    },
+}
+
+impl Violation {
+    /// The identities involved.
+    pub fn identities(&self) -> &[String] {
+        match self {
+            Violation::SharedUserDataDir { identities, .. }
+            | Violation::SharedNoiseSeed { identities, .. }
+            | Violation::SharedProxyExit { identities, .. }
+            | Violation::SharedClockSkew { identities, .. } => identities,
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::SharedUserDataDir { dir, identities } => {
+                write!(
+                    f,
+                    "{identities:?} share the user data dir {}",
+                    dir.display()
+                )
+            }
+            Violation::SharedNoiseSeed { seed, identities } => {
+                write!(f, "{identities:?} share the noise seed {seed}")
+            }
+            Violation::SharedProxyExit { exit, identities } => {
+                write!(f, "{identities:?} share the proxy exit {exit}")
+            }
+            Violation::SharedClockSkew {
+                skew_ms,
+                identities,
+            } => write!(f, "{identities:?} share a clock skew of ~{skew_ms}ms"),
+        }
+    }
+}
+
+/// Compares identities for anything that links them.
+#[derive(Debug, Clone)]
+pub struct IsolationAuditor {
+    identities: Vec<Identity>,
+    shared_exits: bool,
+    skew_tolerance: Duration,
+    normal_skew: Duration,
+}
+
+impl IsolationAuditor {
+    pub fn new(identities: impl IntoIterator<Item = Identity>) -> Self {
+        Self {
+            identities: identities.into_iter().collect(),
+            shared_exits: false,
+            skew_tolerance: Duration::from_secs(1),
+            normal_skew: Duration::from_secs(2),
+        }
+    }
+
+    /// Allow identities to leave through the same proxy exit, for sites
+    /// where many users behind one address (an office, a carrier NAT) are
+    /// normal.
+    pub fn allow_shared_exits(mut self) -> Self {
+        self.shared_exits = true;
+        self
+    }
+
+    /// How close two clock skews must be to count as the same, 1s by
+    /// default. Skew measured against a server's `Date` header is only
+    /// good to about a second.
+    pub fn skew_tolerance(mut self, tolerance: Duration) -> Self {
+        self.skew_tolerance = tolerance;
+        self
+    }
+
+    /// Skews within this of true time are what any synchronized clock
+    /// shows and link nobody, 2s by default.
+    pub fn normal_skew(mut self, skew: Duration) -> Self {
+        self.normal_skew = skew;
+        self
+    }
+
+    pub fn identities(&self) -> &[Identity] {
+        &self.identities
+    }
+
+    /// Everything the identities share.
+    pub fn audit(&self) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        let dirs = shared(&self.identities, |i| {
+            i.user_data_dir.as_deref().map(normalize)
+        });
+        violations.extend(
+            dirs.into_iter()
+                .map(|(dir, identities)| Violation::SharedUserDataDir { dir, identities }),
+        );
+
+        let seeds = shared(&self.identities, |i| {
+            i.profile.as_ref().and_then(ChaserProfile::rect_noise_seed)
+        });
+        violations.extend(
+            seeds
+                .into_iter()
+                .map(|(seed, identities)| Violation::SharedNoiseSeed { seed, identities }),
+        );
+
+        if !self.shared_exits {
+            let exits = shared(&self.identities, Identity::exit);
+            violations.extend(
+                exits
+                    .into_iter()
+                    .map(|(exit, identities)| Violation::SharedProxyExit { exit, identities }),
+            );
+        }
+
+        violations.extend(self.shared_skews());
+        violations
+    }
+
+    /// Add `identity` if it shares nothing with the identities already
+    /// admitted; otherwise leave it out and return what it shares.
+    pub fn admit(&mut self, identity: Identity) -> Result<(), Vec<Violation>> {
+        let name = identity.name.clone();
+        self.identities.push(identity);
+        let violations: Vec<_> = self
+            .audit()
+            .into_iter()
+            .filter(|v| v.identities().contains(&name))
+            .collect();
+        if violations.is_empty() {
+            return Ok(());
+        }
+        self.identities.pop();
+        Err(violations)
+    }
+
+    /// Remove an identity, e.g. once its task is done.
+    pub fn release(&mut self, name: &str) {
+        self.identities.retain(|i| i.name != name);
+    }
+
+    /// Groups of unusual skews within the tolerance of their neighbours.
+    fn shared_skews(&self) -> Vec<Violation> {
+        let normal = self.normal_skew.as_millis() as i64;
+        let tolerance = self.skew_tolerance.as_millis() as i64;
+        let mut skews: Vec<(i64, &str)> = self
+            .identities
+            .iter()
+            .filter_map(|i| Some((i.clock_skew_ms?, i.name.as_str())))
+            .filter(|(skew, _)| skew.abs() > normal)
+            .collect();
+        skews.sort();
+
+        let mut groups: Vec<Vec<(i64, &str)>> = Vec::new();
+        for skew in skews {
+            match groups.last_mut() {
+                Some(group) if skew.0 - group[group.len() - 1].0 <= tolerance => group.push(skew),
+                _ => groups.push(vec![skew]),
+            }
+        }
+        groups
+            .into_iter()
+            .filter(|group| group.len() > 1)
+            .map(|group| Violation::SharedClockSkew {
+                skew_ms: group.iter().map(|(skew, _)| skew).sum::<i64>() / group.len() as i64,
+                identities: group
+                    .into_iter()
+                    .map(|(_, name)| name.to_string())
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+/// Values held by more than one identity, with their holders.
+fn shared<K: Ord>(
+    identities: &[Identity],
+    key: impl Fn(&Identity) -> Option<K>,
+) -> BTreeMap<K, Vec<String>> {
+    let mut holders: BTreeMap<K, Vec<String>> = BTreeMap::new();
+    for identity in identities {
+        if let Some(key) = key(identity) {
+            holders.entry(key).or_default().push(identity.name.clone());
+        }
+    }
+    holders.retain(|_, names| names.len() > 1);
+    holders
+}
+
+/// The same directory however it was spelled, as far as the filesystem
+/// can tell.
+fn normalize(dir: &Path) -> PathBuf {
+    std::fs::canonicalize(dir).unwrap_or_else(|_| dir.components().collect())
+}
+
+/// Reads the server's `Date` header for the current document's URL and the
+/// browser clock halfway through the request.
+const CLOCK_SKEW_SCRIPT: &str = r#"(async () => {
+    const before = Date.now();
+    const response = await fetch(location.href, { method: "HEAD", cache: "no-store" });
+    const after = Date.now();
+    const date = response.headers.get("date");
+    return date ? { browser: (before + after) / 2, server: Date.parse(date) } : null;
+})()"#;
+
+impl ChaserPage {
+    /// How far the browser clock is ahead of the current site's clock, in
+    /// milliseconds, as the site could measure it. Good to about a second:
+    /// the `Date` header has no fractions. The page must be on an http(s)
+    /// URL whose server sends `Date`.
+    pub async fn clock_skew(&self) -> ChaserResult<i64> {
+        let unavailable = || ChaserError::msg("the server sent no Date header");
+        let reading = self
+            .evaluate_stealth(CLOCK_SKEW_SCRIPT)
+            .await?
+            .filter(|v| !v.is_null())
+            .ok_or_else(unavailable)?;
+        let browser = reading["browser"].as_f64().ok_or_else(unavailable)?;
+        let server = reading["server"].as_f64().ok_or_else(unavailable)?;
+        // the header is truncated to the second: on average half a second
+        // behind the moment it was written
+        Ok((browser - server - 500.0).round() as i64)
+    }
+
+    /// How far the browser clock is ahead of this process's clock, in
+    /// milliseconds. Differs from zero only where the page's clock is
+    /// overridden or the browser runs on another host.
+    pub async fn local_clock_skew(&self) -> ChaserResult<i64> {
+        let local = clock::system_time()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as f64;
+        let browser = self
+            .evaluate_stealth("Date.now()")
+            .await?
+            .and_then(|v| v.as_f64())
+            .ok_or_else(|| ChaserError::msg("the page returned no time"))?;
+        Ok((browser - local).round() as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_what_identities_share() {
+        let auditor = IsolationAuditor::new([
+            Identity::new("a")
+                .user_data_dir("/data/./a")
+                .proxy("http://user-1@gate.example:7000")
+                .clock_skew(-90_000),
+            Identity::new("b")
+                .user_data_dir("/data/a")
+                .proxy("http://user-2@gate.example:7000")
+                .clock_skew(-89_400),
+            Identity::new("c")
+                .user_data_dir("/data/c")
+                .proxy("socks5://user-1@GATE.example:7000")
+                .clock_skew(300),
+            Identity::new("d")
+                .proxy("http://10.0.0.1:8080")
+                .exit_ip("203.0.113.7".parse().unwrap())
+                .clock_skew(200),
+            Identity::new("e")
+                .proxy("http://10.0.0.2:8080")
+                .exit_ip("203.0.113.7".parse().unwrap()),
+        ]);
+        let violations = auditor.audit();
+        assert_eq!(
+            violations,
+            [
+                Violation::SharedUserDataDir {
+                    dir: "/data/a".into(),
+                    identities: vec!["a".into(), "b".into()]
+                },
+                Violation::SharedProxyExit {
+                    exit: "203.0.113.7".into(),
+                    identities: vec!["d".into(), "e".into()]
+                },
+                Violation::SharedProxyExit {
+                    exit: "user-1@gate.example:7000".into(),
+                    identities: vec!["a".into(), "c".into()]
+                },
+                Violation::SharedClockSkew {
+                    skew_ms: -89_700,
+                    identities: vec!["a".into(), "b".into()]
+                },
+            ]
+        );
+        assert_eq!(auditor.clone().allow_shared_exits().audit().len(), 2);
+
+        let mut auditor = IsolationAuditor::new([]);
+        assert!(auditor
+            .admit(Identity::new("a").proxy("http://a@gate:1"))
+            .is_ok());
+        let rejected = auditor.admit(Identity::new("b").proxy("http://a@gate:1"));
+        assert_eq!(rejected.unwrap_err()[0].identities(), ["a", "b"]);
+        assert_eq!(auditor.identities().len(), 1);
+        auditor.release("a");
+        assert!(auditor
+            .admit(Identity::new("b").proxy("http://a@gate:1"))
+            .is_ok());
+    }
+}
@@ -0,0 +1,162 @@
+//! Physical keyboard layouts for typed characters.
+//!
+//! `KeyboardEvent.code` names the physical key, not the character: typing
+//! `z` on a German QWERTZ keyboard presses `KeyY`. A de-DE identity whose
+//! keystrokes all come from a US keyboard is an easy tell, so the typing
+//! methods of [`ChaserPage`] look up the key for every character in the
+//! profile's [`KeyboardLayout`].
+//!
+//! [`ChaserPage`]: crate::chaser::ChaserPage
+
+use serde::{Deserialize, Serialize};
+
+const LETTER_CODES: [&str; 26] = [
+    "KeyA", "KeyB", "KeyC", "KeyD", "KeyE", "KeyF", "KeyG", "KeyH", "KeyI", "KeyJ", "KeyK", "KeyL",
+    "KeyM", "KeyN", "KeyO", "KeyP", "KeyQ", "KeyR", "KeyS", "KeyT", "KeyU", "KeyV", "KeyW", "KeyX",
+    "KeyY", "KeyZ",
+];
+
+const DIGIT_CODES: [&str; 10] = [
+    "Digit0", "Digit1", "Digit2", "Digit3", "Digit4", "Digit5", "Digit6", "Digit7", "Digit8",
+    "Digit9",
+];
+
+/// A national keyboard layout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum KeyboardLayout {
+    /// US QWERTY, also common in the Netherlands, Poland and Asia
+    #[default]
+    Us,
+    /// UK QWERTY
+    Uk,
+    /// German, Austrian and Swiss QWERTZ
+    German,
+    /// French AZERTY
+    French,
+    /// Spanish and Latin American QWERTY with `ñ`
+    Spanish,
+    /// Italian QWERTY
+    Italian,
+    /// Swedish, Finnish, Norwegian and Danish QWERTY with `å`
+    Nordic,
+    /// Brazilian ABNT2 with `ç`
+    Brazilian,
+}
+
+impl KeyboardLayout {
+    /// The `KeyboardEvent.code` of the key that types `c` (ignoring Shift),
+    /// or `None` for characters that need a dead key or an IME.
+    pub fn code(&self, c: char) -> Option<&'static str> {
+        let c = c.to_lowercase().next()?;
+        let layout_specific = match (self, c) {
+            (KeyboardLayout::German, 'z') => Some("KeyY"),
+            (KeyboardLayout::German, 'y') => Some("KeyZ"),
+            (KeyboardLayout::German, 'ü') => Some("BracketLeft"),
+            (KeyboardLayout::German, 'ö') => Some("Semicolon"),
+            (KeyboardLayout::German, 'ä') => Some("Quote"),
+            (KeyboardLayout::German, 'ß') => Some("Minus"),
+            (KeyboardLayout::French, 'a') => Some("KeyQ"),
+            (KeyboardLayout::French, 'q') => Some("KeyA"),
+            (KeyboardLayout::French, 'z') => Some("KeyW"),
+            (KeyboardLayout::French, 'w') => Some("KeyZ"),
+            (KeyboardLayout::French, 'm') => Some("Semicolon"),
+            (KeyboardLayout::French, ',') => Some("KeyM"),
+            (KeyboardLayout::French, '.') => Some("Comma"),
+            (KeyboardLayout::French, '-') => Some("Digit6"),
+            (KeyboardLayout::French, 'é') => Some("Digit2"),
+            (KeyboardLayout::French, 'è') => Some("Digit7"),
+            (KeyboardLayout::French, 'ç') => Some("Digit9"),
+            (KeyboardLayout::French, 'à') => Some("Digit0"),
+            (KeyboardLayout::French, 'ù') => Some("Quote"),
+            (KeyboardLayout::Spanish, 'ñ') => Some("Semicolon"),
+            (KeyboardLayout::Spanish, 'ç') => Some("Backslash"),
+            (KeyboardLayout::Italian, 'ò') => Some("Semicolon"),
+            (KeyboardLayout::Italian, 'à') => Some("Quote"),
+            (KeyboardLayout::Italian, 'ù') => Some("Backslash"),
+            (KeyboardLayout::Italian, 'è') => Some("BracketLeft"),
+            (KeyboardLayout::Italian, 'ì') => Some("Equal"),
+            (KeyboardLayout::Nordic, 'å') => Some("BracketLeft"),
+            (KeyboardLayout::Nordic, 'ö' | 'ø') => Some("Semicolon"),
+            (KeyboardLayout::Nordic, 'ä' | 'æ') => Some("Quote"),
+            (KeyboardLayout::Brazilian, 'ç') => Some("Semicolon"),
+            (
+                KeyboardLayout::German
+                | KeyboardLayout::Spanish
+                | KeyboardLayout::Italian
+                | KeyboardLayout::Nordic,
+                '-',
+            ) => Some("Slash"),
+            _ => None,
+        };
+        layout_specific.or(match c {
+            'a'..='z' => Some(LETTER_CODES[c as usize - 'a' as usize]),
+            '0'..='9' => Some(DIGIT_CODES[c as usize - '0' as usize]),
+            ' ' => Some("Space"),
+            '\n' | '\r' => Some("Enter"),
+            '\t' => Some("Tab"),
+            '.' => Some("Period"),
+            ',' => Some("Comma"),
+            '-' => Some("Minus"),
+            '/' if matches!(self, KeyboardLayout::Us | KeyboardLayout::Uk) => Some("Slash"),
+            _ => None,
+        })
+    }
+}
+
+/// The Windows virtual key code for `c`, which follows the character rather
+/// than the physical key on every layout; `None` outside letters and digits.
+pub fn virtual_key_code(c: char) -> Option<i64> {
+    c.is_ascii_alphanumeric()
+        .then(|| c.to_ascii_uppercase() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letters_follow_the_physical_layout() {
+        assert_eq!(KeyboardLayout::Us.code('z'), Some("KeyZ"));
+        assert_eq!(KeyboardLayout::German.code('z'), Some("KeyY"));
+        assert_eq!(KeyboardLayout::German.code('Y'), Some("KeyZ"));
+        assert_eq!(KeyboardLayout::French.code('A'), Some("KeyQ"));
+        assert_eq!(KeyboardLayout::French.code('m'), Some("Semicolon"));
+        assert_eq!(KeyboardLayout::Nordic.code('Ø'), Some("Semicolon"));
+        assert_eq!(KeyboardLayout::Spanish.code('ñ'), Some("Semicolon"));
+    }
+
+    #[test]
+    fn punctuation_and_whitespace_have_shared_keys() {
+        for layout in [
+            KeyboardLayout::Us,
+            KeyboardLayout::Uk,
+            KeyboardLayout::Brazilian,
+        ] {
+            assert_eq!(layout.code('-'), Some("Minus"));
+        }
+        assert_eq!(KeyboardLayout::German.code('-'), Some("Slash"));
+        assert_eq!(KeyboardLayout::French.code('-'), Some("Digit6"));
+        assert_eq!(KeyboardLayout::French.code(','), Some("KeyM"));
+        assert_eq!(KeyboardLayout::Italian.code(','), Some("Comma"));
+        assert_eq!(KeyboardLayout::Uk.code('/'), Some("Slash"));
+        assert_eq!(KeyboardLayout::German.code('/'), None);
+        assert_eq!(KeyboardLayout::Us.code('7'), Some("Digit7"));
+        assert_eq!(KeyboardLayout::Us.code('\r'), Some("Enter"));
+        assert_eq!(KeyboardLayout::Us.code('\t'), Some("Tab"));
+        assert_eq!(KeyboardLayout::Us.code(' '), Some("Space"));
+    }
+
+    #[test]
+    fn characters_without_a_key_are_none() {
+        // typed through dead keys or an IME
+        assert_eq!(KeyboardLayout::Us.code('é'), None);
+        assert_eq!(KeyboardLayout::German.code('ñ'), None);
+        assert_eq!(KeyboardLayout::Us.code('日'), None);
+
+        assert_eq!(virtual_key_code('a'), Some(65));
+        assert_eq!(virtual_key_code('Z'), Some(90));
+        assert_eq!(virtual_key_code('5'), Some(53));
+        assert_eq!(virtual_key_code('-'), None);
+        assert_eq!(virtual_key_code('ä'), None);
+    }
+}
@@ -0,0 +1,364 @@
+//! Curated Chrome command line arguments and an auditor for risky ones.
+//!
+//! Several of chromiumoxide's default arguments are detectable on their own:
+//! a hardcoded `--user-agent` that disagrees with the real version and UA
+//! client hints, `--force-color-profile=srgb` pinning the color gamut,
+//! `--disable-popup-blocking` changing popup behavior. [`LaunchArgs`] replaces
+//! them with a minimal set per Chrome version, and [`audit`] flags arguments
+//! known to leak automation.
+//!
+//! # Example
+//!
+//! ```
+//! use chaser_oxide::launch_args::LaunchArgs;
+//!
+//! let args = LaunchArgs::stealth_default(131).with("--no-sandbox");
+//! for warning in args.audit() {
+//!     println!("{warning}");
+//! }
+//! ```
+
+use crate::browser::{BrowserConfig, BrowserConfigBuilder};
+use crate::geometry::WindowState;
+use crate::profiles::{ChaserProfile, ColorScheme, ScrollbarStyle};
+use std::fmt;
+
+/// How bad an audited argument is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Changes behavior in a way that is worth knowing about.
+    Risky,
+    /// Directly observable by pages or fingerprinting scripts.
+    Detectable,
+}
+
+/// A finding of [`audit`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgWarning {
+    /// The argument as passed, e.g. `--disable-gpu`.
+    pub arg: String,
+    pub severity: Severity,
+    pub reason: String,
+}
+
+impl fmt::Display for ArgWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Risky => "risky",
+            Severity::Detectable => "detectable",
+        };
+        write!(f, "{} ({}): {}", self.arg, severity, self.reason)
+    }
+}
+
+/// A curated set of launch arguments for one Chrome version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchArgs {
+    chrome_version: u32,
+    args: Vec<String>,
+}
+
+impl LaunchArgs {
+    /// The minimal arguments a stealth launch of `chrome_version` needs.
+    ///
+    /// Everything that only exists to make automation convenient and that a
+    /// page can observe is left out.
+    pub fn stealth_default(chrome_version: u32) -> Self {
+        let mut args = vec![
+            "--disable-blink-features=AutomationControlled".to_string(),
+            "--no-first-run".to_string(),
+            "--no-default-browser-check".to_string(),
+            "--password-store=basic".to_string(),
+            "--use-mock-keychain".to_string(),
+            "--disable-breakpad".to_string(),
+            "--disable-component-update".to_string(),
+            "--disable-dev-shm-usage".to_string(),
+        ];
+        // the translate bubble feature was renamed in 120
+        if chrome_version >= 120 {
+            args.push("--disable-features=Translate".to_string());
+        } else {
+            args.push("--disable-features=TranslateUI".to_string());
+        }
+        if chrome_version >= 127 {
+            args.push("--disable-search-engine-choice-screen".to_string());
+        }
+        Self {
+            chrome_version,
+            args,
+        }
+    }
+
+    /// [`stealth_default`](Self::stealth_default) for the profile's Chrome
+    /// version plus its window position and state, its languages, dark
+    /// browser UI for a dark color scheme and overlay scrollbars where the
+    /// OS has them.
+    pub fn for_profile(profile: &ChaserProfile) -> Self {
+        let geometry = crate::geometry::Geometry::resolve(profile);
+        let mut args = Self::stealth_default(profile.chrome_version());
+        if profile.media().color_scheme == ColorScheme::Dark {
+            // native form controls and scrollbars follow the OS theme
+            args = args.with("--force-dark-mode");
+        }
+        if profile.scrollbar_style() == ScrollbarStyle::Overlay && !geometry.mobile {
+            // a Linux or Windows host would draw classic scrollbars under a
+            // macOS profile; mobile emulation brings its own overlay ones
+            args = args.with("--enable-features=OverlayScrollbar");
+        }
+        let args = args
+            .with(format!(
+                "--window-position={},{}",
+                geometry.window_x, geometry.window_y
+            ))
+            .with(format!("--lang={}", profile.locale()))
+            .with(format!("--accept-lang={}", profile.languages().join(",")));
+        match geometry.window {
+            // a window sized like a maximized one still reports "normal"
+            WindowState::Maximized if !geometry.mobile => args.with("--start-maximized"),
+            _ => args,
+        }
+    }
+
+    /// Add an argument.
+    pub fn with(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Add `features` to `--disable-features`. Chrome only reads the last
+    /// occurrence of a switch, so they are merged into the existing one.
+    pub fn disable_features(mut self, features: &[&str]) -> Self {
+        match self
+            .args
+            .iter_mut()
+            .find(|arg| switch(arg).0 == "disable-features")
+        {
+            Some(arg) => {
+                for feature in features {
+                    if !switch(arg).1.split(',').any(|f| f == *feature) {
+                        arg.push(',');
+                        arg.push_str(feature);
+                    }
+                }
+            }
+            None => self
+                .args
+                .push(format!("--disable-features={}", features.join(","))),
+        }
+        self
+    }
+
+    /// Stop Chrome from loading pages on its own: no prerendering or
+    /// prefetching from speculation rules or the omnibox, and no DNS
+    /// prefetching of links. Runs become reproducible, and a site no
+    /// longer sees a load for a page the flow never visits.
+    pub fn without_speculation(self) -> Self {
+        self.disable_features(&[
+            "Prerender2",
+            "NoStatePrefetch",
+            "SpeculationRulesPrefetchProxy",
+        ])
+        .with("--dns-prefetch-disable")
+    }
+
+    /// Keep the HTTP cache of the default browser context in `dir`, so a
+    /// later launch with the same directory starts warm. See
+    /// [`http_cache`](crate::http_cache).
+    pub fn disk_cache_dir(self, dir: impl AsRef<std::path::Path>) -> Self {
+        self.without("--disk-cache-dir")
+            .with(format!("--disk-cache-dir={}", dir.as_ref().display()))
+    }
+
+    /// Remove every argument with the given switch name.
+    pub fn without(mut self, key: &str) -> Self {
+        self.args
+            .retain(|arg| switch(arg).0 != key.trim_start_matches("--"));
+        self
+    }
+
+    /// Whether an argument with the given switch name is present.
+    pub fn contains(&self, key: &str) -> bool {
+        self.args
+            .iter()
+            .any(|arg| switch(arg).0 == key.trim_start_matches("--"))
+    }
+
+    pub fn chrome_version(&self) -> u32 {
+        self.chrome_version
+    }
+
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Audit these arguments for the configured Chrome version.
+    pub fn audit(&self) -> Vec<ArgWarning> {
+        audit(&self.args, Some(self.chrome_version))
+    }
+
+    /// Replace chromiumoxide's default arguments with these.
+    pub fn apply(&self, builder: BrowserConfigBuilder) -> BrowserConfigBuilder {
+        builder.disable_default_args().args(self.args.clone())
+    }
+}
+
+/// Audit the full command line `config` launches the browser with.
+pub fn audit_config(config: &BrowserConfig, chrome_version: Option<u32>) -> Vec<ArgWarning> {
+    audit(config.launch_args(), chrome_version)
+}
+
+/// Flag arguments that leak automation or change observable behavior.
+///
+/// With `chrome_version` set, a `--user-agent` claiming a different version
+/// is reported as detectable.
+pub fn audit<I, S>(args: I, chrome_version: Option<u32>) -> Vec<ArgWarning>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut warnings = Vec::new();
+    for arg in args {
+        let arg = arg.as_ref();
+        let (key, value) = switch(arg);
+        let finding = match key {
+            "enable-automation" => Some((
+                Severity::Detectable,
+                "sets navigator.webdriver and shows the automation infobar".to_string(),
+            )),
+            "test-type" => Some((
+                Severity::Detectable,
+                "disables security warnings the way only test runners do".to_string(),
+            )),
+            "disable-gpu" => Some((
+                Severity::Detectable,
+                "WebGL falls back to SwiftShader, contradicting the profile's GPU".to_string(),
+            )),
+            "use-gl" | "use-angle" if value.contains("swiftshader") => Some((
+                Severity::Detectable,
+                "software rendering reports a SwiftShader WebGL renderer".to_string(),
+            )),
+            "no-sandbox" | "disable-setuid-sandbox" => Some((
+                Severity::Detectable,
+                "headed Chrome shows an unsupported command-line flag infobar".to_string(),
+            )),
+            "headless" if value != "new" => Some((
+                Severity::Detectable,
+                "old headless mode puts HeadlessChrome in the user agent".to_string(),
+            )),
+            "user-agent" => Some(match chrome_version {
+                Some(version) if !value.contains(&format!("Chrome/{version}.")) => (
+                    Severity::Detectable,
+                    format!(
+                        "claims a different version than Chrome {version} and its UA client hints"
+                    ),
+                ),
+                _ => (
+                    Severity::Risky,
+                    "only overrides the header and navigator.userAgent, not UA client hints"
+                        .to_string(),
+                ),
+            }),
+            "disable-web-security" => Some((
+                Severity::Detectable,
+                "cross-origin reads succeed where real Chrome blocks them".to_string(),
+            )),
+            "force-color-profile" => Some((
+                Severity::Detectable,
+                "pins the color-gamut media queries regardless of the profile".to_string(),
+            )),
+            "hide-scrollbars" => Some((
+                Severity::Detectable,
+                "scrollbars have zero width on desktop profiles".to_string(),
+            )),
+            "disable-popup-blocking" => Some((
+                Severity::Risky,
+                "window.open succeeds without a user gesture".to_string(),
+            )),
+            "remote-debugging-address" => Some((
+                Severity::Risky,
+                "exposes DevTools to the network".to_string(),
+            )),
+            "disable-features"
+                if value
+                    .split(',')
+                    .any(|f| f == "IsolateOrigins" || f == "site-per-process") =>
+            {
+                Some((
+                    Severity::Risky,
+                    "disables site isolation, observable through timing and process sharing"
+                        .to_string(),
+                ))
+            }
+            "disable-blink-features" if value.split(',').any(|f| f != "AutomationControlled") => {
+                Some((
+                    Severity::Risky,
+                    "removes web platform features fingerprinters probe for".to_string(),
+                ))
+            }
+            _ => None,
+        };
+        if let Some((severity, reason)) = finding {
+            warnings.push(ArgWarning {
+                arg: arg.to_string(),
+                severity,
+                reason,
+            });
+        }
+    }
+    warnings
+}
+
+/// Split `--key=value` into its switch name and value.
+fn switch(arg: &str) -> (&str, &str) {
+    let arg = arg.trim_start_matches("--");
+    arg.split_once('=').unwrap_or((arg, ""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stealth_default_passes_its_own_audit() {
+        let args = LaunchArgs::stealth_default(131);
+        assert!(args.audit().is_empty());
+
+        let warnings = args
+            .with("--disable-gpu")
+            .with("--user-agent=Mozilla/5.0 Chrome/129.0.0.0 Safari/537.36")
+            .audit();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings.iter().all(|w| w.severity == Severity::Detectable));
+    }
+
+    #[test]
+    fn profile_args_follow_theme_and_scrollbars() {
+        let mac = ChaserProfile::macos_arm()
+            .color_scheme(ColorScheme::Dark)
+            .build();
+        let args = LaunchArgs::for_profile(&mac);
+        assert!(args.contains("--force-dark-mode"));
+        assert!(args
+            .args()
+            .contains(&"--enable-features=OverlayScrollbar".to_string()));
+        assert!(args.audit().is_empty());
+
+        let quiet = args.without_speculation();
+        let disabled: Vec<_> = quiet
+            .args()
+            .iter()
+            .filter(|arg| arg.starts_with("--disable-features="))
+            .collect();
+        assert_eq!(
+            disabled,
+            ["--disable-features=Translate,Prerender2,NoStatePrefetch,SpeculationRulesPrefetchProxy"]
+        );
+
+        let windows = ChaserProfile::windows().build();
+        assert_eq!(
+            windows.scrollbar_style(),
+            ScrollbarStyle::Classic { width: 15 }
+        );
+        assert!(!LaunchArgs::for_profile(&windows).contains("--enable-features"));
+    }
+}
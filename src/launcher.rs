@@ -0,0 +1,297 @@
+//! One-call launch of a stealth browser.
+//!
+//! Getting a usable stealth page used to take a ritual: build a config from
+//! the profile, launch, spawn the handler loop, open a page, apply the
+//! profile, wait for the scripts to register and only then navigate.
+//! [`ChaserBrowser`] owns all of it. Every page it opens has the profile
+//! applied and the bootstrap script registered before the first navigation
+//! starts, so nothing the page runs sees the unpatched browser.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::{ChaserBrowser, ChaserProfile};
+//!
+//! let browser = ChaserBrowser::launch(ChaserProfile::windows().build()).await?;
+//! let page = browser.new_page("https://example.com").await?;
+//! println!("{}", page.content().await?);
+//! browser.close().await?;
+//! ```
+
+use crate::browser::{Browser, BrowserConfig};
+use crate::chaser::ChaserPage;
+use crate::context::ChaserContext;
+use crate::handler::HandlerTask;
+use crate::profiles::ChaserProfile;
+use crate::timeouts::Timeouts;
+use anyhow::{anyhow, Result};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long a closed browser's handler may take to finish by itself.
+const HANDLER_GRACE: Duration = Duration::from_secs(5);
+
+/// A launched browser whose pages all carry one stealth profile.
+#[derive(Debug)]
+pub struct ChaserBrowser {
+    browser: Arc<Browser>,
+    profile: ChaserProfile,
+    timeouts: Timeouts,
+    handler: HandlerTask,
+}
+
+impl ChaserBrowser {
+    /// Launch a headless browser for `profile` with the default options.
+    pub async fn launch(profile: ChaserProfile) -> Result<Self> {
+        Self::builder(profile).launch().await
+    }
+
+    pub fn builder(profile: ChaserProfile) -> ChaserBrowserBuilder {
+        ChaserBrowserBuilder {
+            profile,
+            executable: None,
+            user_data_dir: None,
+            proxy: None,
+            headed: false,
+            timeouts: Timeouts::default(),
+        }
+    }
+
+    /// Open a page with the profile applied and navigate it to `url`.
+    pub async fn new_page(&self, url: &str) -> Result<ChaserPage> {
+        let page = ChaserPage::new(self.browser.new_page("about:blank").await?);
+        page.set_timeouts(self.timeouts);
+        if let Err(e) = page.apply_profile(&self.profile).await {
+            let _ = page.raw_page().clone().close().await;
+            return Err(e.into());
+        }
+        if url != "about:blank" {
+            page.goto(url).await?;
+        }
+        Ok(page)
+    }
+
+    /// Create an isolated context with its own profile and proxy in this
+    /// browser.
+    pub async fn new_context(
+        &self,
+        profile: ChaserProfile,
+        proxy: Option<String>,
+    ) -> Result<ChaserContext> {
+        ChaserContext::create(self.browser.clone(), profile, proxy).await
+    }
+
+    pub fn profile(&self) -> &ChaserProfile {
+        &self.profile
+    }
+
+    pub fn browser(&self) -> &Arc<Browser> {
+        &self.browser
+    }
+
+    /// Close the browser and wait for the process to exit.
+    ///
+    /// Fails if a [`ChaserContext`] created by [`new_context`](Self::new_context)
+    /// is still alive.
+    pub async fn close(self) -> Result<()> {
+        let Self {
+            browser, handler, ..
+        } = self;
+        let mut browser = Arc::try_unwrap(browser)
+            .map_err(|_| anyhow!("Browser is still in use by a context"))?;
+        browser.close().await?;
+        browser.wait().await?;
+        handler.shutdown(HANDLER_GRACE).await;
+        Ok(())
+    }
+}
+
+/// Launch options for a [`ChaserBrowser`].
+#[derive(Debug, Clone)]
+pub struct ChaserBrowserBuilder {
+    profile: ChaserProfile,
+    executable: Option<PathBuf>,
+    user_data_dir: Option<PathBuf>,
+    proxy: Option<String>,
+    headed: bool,
+    timeouts: Timeouts,
+}
+
+impl ChaserBrowserBuilder {
+    /// Path to the browser binary. Defaults to an installed Chrome of the
+    /// profile's major version, then to any detected Chrome.
+    pub fn executable(mut self, path: impl Into<PathBuf>) -> Self {
+        self.executable = Some(path.into());
+        self
+    }
+
+    /// Keep cookies, history and cache in `dir` across launches.
+    pub fn user_data_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.user_data_dir = Some(dir.into());
+        self
+    }
+
+    /// Route all traffic through `proxy`, e.g. `http://10.0.0.1:8080`.
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// Show the browser window.
+    pub fn headed(mut self) -> Self {
+        self.headed = true;
+        self
+    }
+
+    /// Time limits for every page opened by the browser.
+    pub fn timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    pub async fn launch(self) -> Result<ChaserBrowser> {
+        let (browser, handler) = launch_browser(
+            &self.profile,
+            self.executable,
+            self.user_data_dir,
+            self.proxy,
+            self.headed,
+        )
+        .await?;
+        Ok(ChaserBrowser {
+            browser: Arc::new(browser),
+            profile: self.profile,
+            timeouts: self.timeouts,
+            handler,
+        })
+    }
+}
+
+/// Launch a browser configured for `profile`, audit its arguments and spawn
+/// its handler loop.
+pub(crate) async fn launch_browser(
+    profile: &ChaserProfile,
+    executable: Option<PathBuf>,
+    user_data_dir: Option<PathBuf>,
+    proxy: Option<String>,
+    headed: bool,
+) -> Result<(Browser, HandlerTask)> {
+    let executable = executable.or_else(|| {
+        crate::chrome_locator::locate(profile.chrome_version()).map(|install| install.path)
+    });
+    let mut builder = profile.configure_browser(BrowserConfig::builder());
+    if let Some(executable) = executable {
+        builder = builder.chrome_executable(executable);
+    }
+    if let Some(dir) = user_data_dir {
+        builder = builder.user_data_dir(dir);
+    }
+    if let Some(proxy) = proxy {
+        builder = builder.arg(format!("--proxy-server={proxy}"));
+    }
+    if headed {
+        builder = builder.with_head();
+    }
+
+    let config = builder.build().map_err(|e| anyhow!("{}", e))?;
+    for warning in crate::launch_args::audit_config(&config, Some(profile.chrome_version())) {
+        tracing::warn!("launch argument {}", warning);
+    }
+
+    let (browser, handler) = Browser::launch(config).await?;
+    let handler = handler.spawn();
+
+    if let Err(e) = crate::chrome_locator::verify_browser(&browser, profile).await {
+        tracing::warn!("{}", e);
+    }
+    Ok((browser, handler))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MockTransport;
+    use serde_json::json;
+
+    fn browser(mock: &MockTransport, profile: ChaserProfile) -> ChaserBrowser {
+        ChaserBrowser {
+            browser: Arc::new(Browser::with_transport(mock.clone())),
+            profile,
+            timeouts: Timeouts::default().navigation(Duration::from_secs(3)),
+            handler: HandlerTask::idle(),
+        }
+    }
+
+    #[tokio::test]
+    async fn pages_carry_the_profile_before_navigating() {
+        let mock = MockTransport::new();
+        let browser = browser(&mock, ChaserProfile::macos_arm().build());
+
+        let blank = browser.new_page("about:blank").await.unwrap();
+        assert_eq!(blank.timeouts().navigation, Duration::from_secs(3));
+        assert_eq!(
+            mock.commands_to("Target.createTarget")[0]["url"],
+            "about:blank"
+        );
+        assert!(mock.commands_to("Page.navigate").is_empty());
+
+        mock.clear();
+        browser.new_page("https://example.com/").await.unwrap();
+        let commands = mock.commands();
+        let position = |method: &str| commands.iter().position(|c| c.method == method);
+        let bootstrap = position("Page.addScriptToEvaluateOnNewDocument").unwrap();
+        let navigate = position("Page.navigate").unwrap();
+        assert!(bootstrap < navigate, "the profile is applied first");
+        assert_eq!(commands[navigate].params["url"], "https://example.com/");
+    }
+
+    #[tokio::test]
+    async fn a_page_the_profile_fails_on_is_closed() {
+        let mock = MockTransport::new();
+        mock.fail("Emulation.setUserAgentOverride", "Target closed");
+        let browser = browser(&mock, ChaserProfile::windows().build());
+        assert!(browser.new_page("https://example.com/").await.is_err());
+        assert_eq!(mock.commands_to("Page.close").len(), 1);
+        assert!(mock.commands_to("Page.navigate").is_empty());
+    }
+
+    #[tokio::test]
+    async fn contexts_keep_the_browser_open() {
+        let mock = MockTransport::new();
+        let browser = browser(&mock, ChaserProfile::windows().build());
+        let context = browser
+            .new_context(
+                ChaserProfile::android().build(),
+                Some("socks5://10.0.0.9:1080".to_string()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            mock.commands_to("Target.createBrowserContext")[0]["proxyServer"],
+            "socks5://10.0.0.9:1080"
+        );
+
+        let err = browser.close().await.unwrap_err();
+        assert!(err.to_string().contains("still in use by a context"));
+        assert!(mock.commands_to("Browser.close").is_empty());
+        drop(context);
+
+        let browser = self::browser(&mock, ChaserProfile::windows().build());
+        browser.close().await.unwrap();
+        assert_eq!(mock.commands_to("Browser.close"), [json!({})]);
+    }
+
+    #[tokio::test]
+    async fn launching_a_missing_executable_fails() {
+        let err = ChaserBrowser::builder(ChaserProfile::windows().build())
+            .executable("/nonexistent/chrome")
+            .launch()
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<crate::error::CdpError>(),
+            Some(crate::error::CdpError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound
+        ));
+    }
+}
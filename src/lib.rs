@@ -5,19 +5,15 @@
 //!
 //! # Example
 //! ```no_run
-//! use futures::StreamExt;
-//! use chromiumoxide::{Browser, BrowserConfig};
+//! use chaser_oxide::{Browser, BrowserConfig};
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     let (browser, mut handler) =
+//!     let (mut browser, handler) =
 //!         Browser::launch(BrowserConfig::builder().with_head().build()?).await?;
 //!
-//!     let handle = tokio::spawn(async move {
-//!         loop {
-//!             let _event = handler.next().await.unwrap();
-//!         }
-//!     });
+//!     // drive the connection to the browser in the background
+//!     let handler = handler.spawn();
 //!
 //!     let page = browser.new_page("https://en.wikipedia.org").await?;
 //!
@@ -34,7 +30,8 @@
 //!
 //!     let html = page.wait_for_navigation().await?.content().await?;
 //!
-//!     handle.await?;
+//!     browser.close().await?;
+//!     handler.join().await;
 //!     Ok(())
 //! }
 //! ```
@@ -70,31 +67,117 @@ pub use chromiumoxide_types::{self as types, Binary, Command, Method, MethodType
 pub use crate::browser::{Browser, BrowserConfig};
 pub use crate::conn::Connection;
 pub use crate::element::Element;
-pub use crate::error::Result;
+pub use crate::error::{ChaserError, ChaserResult, Result};
 #[cfg(feature = "fetcher")]
 pub use crate::fetcher::{BrowserFetcher, BrowserFetcherOptions};
 pub use crate::handler::Handler;
+#[cfg(feature = "tokio-runtime")]
+pub use crate::handler::HandlerTask;
+pub use crate::launcher::{ChaserBrowser, ChaserBrowserBuilder};
 pub use crate::page::Page;
 
+pub mod actionability;
 pub mod auth;
+pub mod autofill;
+pub mod behavior;
+pub mod binding;
 pub mod browser;
+pub mod cancel;
+pub mod chrome_locator;
+pub mod client_hints;
+pub mod clock;
 pub mod cmd;
 pub mod conn;
+pub mod consent;
+pub mod context;
+pub mod coordinates;
+pub mod crawl_state;
+pub mod crawler;
 pub mod detection;
+pub mod detectors;
+pub mod diagnostics;
+pub mod dwell;
 pub mod element;
+pub mod entropy;
 pub mod error;
+pub mod extension;
+pub mod fields;
+pub mod fleet;
+pub mod focus;
+pub mod fonts;
 #[cfg(feature = "fetcher")]
 pub mod fetcher {
     pub use chromiumoxide_fetcher::*;
 }
 pub mod async_process;
+pub mod geometry;
 pub mod handler;
+pub mod handoff;
+pub mod header_template;
+pub mod http_cache;
+pub(crate) mod input_pipeline;
+pub mod isolation;
 pub mod js;
+pub mod keyboard;
 pub mod keys;
+pub mod launch_args;
+pub mod launcher;
 pub mod layout;
+pub mod links;
 pub mod listeners;
+pub mod locales;
+pub mod menus;
+pub mod metrics;
+pub mod obfuscation;
+pub mod observe;
+#[cfg(feature = "ocr")]
+pub mod ocr;
+pub mod orchestrator;
 pub mod page;
+pub mod page_budget;
+pub mod page_errors;
+pub mod partition;
+pub mod patches;
+pub mod payment;
+pub mod persona;
+pub mod policy;
+pub mod pool;
+pub mod prewarm;
+pub mod query;
+pub mod reaction;
+#[cfg(feature = "readability")]
+pub mod readability;
+pub mod referrer;
+pub mod regions;
+#[cfg(feature = "repl")]
+pub mod repl;
+pub mod rotation;
+pub mod safari;
+pub mod scripts;
+pub mod seeding;
+pub mod selection;
+pub mod self_check;
+pub mod sensor_lab;
+pub mod sensors;
+pub mod signing;
+pub mod sinks;
+pub mod storage;
+pub mod structured_data;
+pub mod tables;
+#[cfg(feature = "test-harness")]
+pub mod test_harness;
+pub mod timeouts;
+pub mod tokens;
+pub mod transport;
 pub(crate) mod utils;
+pub mod verdict;
+#[cfg(feature = "vision")]
+pub mod vision;
+#[cfg(feature = "vision")]
+pub mod visual_diff;
+pub mod warmup;
+pub mod widgets;
+pub mod window;
 
 pub type ArcHttpRequest = Option<Arc<HttpRequest>>;
 
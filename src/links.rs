@@ -0,0 +1,340 @@
+//! The links and assets of a page, for crawl frontiers and SEO audits.
+//!
+//! [`ChaserPage::inventory`] reads every link and every referenced asset in
+//! one isolated-world pass. URLs are resolved against the document base,
+//! stripped of their fragment and deduplicated; a link found several times
+//! keeps its first anchor text, the union of its `rel` values and a count.
+//! [`ChaserPage::links`] and [`ChaserPage::assets`] return one half each.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::links::LinkContext;
+//!
+//! let inventory = chaser.inventory().await?;
+//! let frontier: Vec<_> = inventory
+//!     .links
+//!     .iter()
+//!     .filter(|link| link.internal && !link.is_nofollow())
+//!     .filter(|link| link.context != LinkContext::Footer)
+//!     .map(|link| link.url.clone())
+//!     .collect();
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::ChaserResult as Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use url::Url;
+
+/// `{base, links, assets}` as found in the DOM, plus the resources the
+/// performance timeline saw load (fonts and CSS backgrounds included).
+const INVENTORY_SCRIPT: &str = r#"(() => {
+    const norm = s => (s || '').replace(/\s+/g, ' ').trim();
+    const context = el => {
+        if (el.closest('nav, [role="navigation"]')) return 'navigation';
+        if (el.closest('header, [role="banner"]')) return 'header';
+        if (el.closest('footer, [role="contentinfo"]')) return 'footer';
+        if (el.closest('aside, [role="complementary"]')) return 'aside';
+        if (el.closest('main, article, [role="main"]')) return 'main';
+        return 'body';
+    };
+    const links = Array.from(document.querySelectorAll('a[href], area[href]'), el => ({
+        href: el.getAttribute('href'),
+        rel: norm(el.getAttribute('rel')).toLowerCase().split(' ').filter(Boolean),
+        text: norm(el.innerText ?? el.textContent) || norm(el.getAttribute('aria-label'))
+            || norm(el.querySelector('img[alt]') && el.querySelector('img[alt]').alt),
+        title: el.getAttribute('title'),
+        context: context(el),
+    }));
+
+    const assets = [];
+    const add = (url, kind, tag) => { if (url) assets.push({ url, kind, tag }); };
+    const srcset = value => (value || '').split(',').map(part => part.trim().split(/\s+/)[0]).filter(Boolean);
+    for (const el of document.querySelectorAll('script[src]')) add(el.getAttribute('src'), 'script', 'script');
+    for (const el of document.querySelectorAll('link[href]')) {
+        const rel = (el.getAttribute('rel') || '').toLowerCase().split(/\s+/);
+        const as = (el.getAttribute('as') || '').toLowerCase();
+        let kind = null;
+        if (rel.includes('stylesheet')) kind = 'stylesheet';
+        else if (rel.includes('icon') || rel.includes('apple-touch-icon') || rel.includes('mask-icon')) kind = 'icon';
+        else if (rel.includes('modulepreload')) kind = 'script';
+        else if (rel.includes('preload') || rel.includes('prefetch')) {
+            kind = { script: 'script', style: 'stylesheet', image: 'image', font: 'font', video: 'media', audio: 'media', document: 'frame' }[as] || 'other';
+        } else if (rel.includes('manifest')) kind = 'other';
+        if (kind) add(el.getAttribute('href'), kind, 'link');
+    }
+    for (const el of document.querySelectorAll('img, picture source, input[type="image"]')) {
+        add(el.getAttribute('src'), 'image', el.tagName.toLowerCase());
+        for (const url of srcset(el.getAttribute('srcset'))) add(url, 'image', el.tagName.toLowerCase());
+    }
+    for (const el of document.querySelectorAll('video, audio, video source, audio source, track')) {
+        add(el.getAttribute('src'), 'media', el.tagName.toLowerCase());
+        if (el.tagName === 'VIDEO') add(el.getAttribute('poster'), 'image', 'video');
+    }
+    for (const el of document.querySelectorAll('iframe[src], frame[src], embed[src], object[data]')) {
+        add(el.getAttribute('src') || el.getAttribute('data'), 'frame', el.tagName.toLowerCase());
+    }
+    const initiators = { script: 'script', css: 'other', img: 'image', image: 'image', link: 'other', iframe: 'frame', video: 'media', audio: 'media' };
+    for (const entry of performance.getEntriesByType('resource')) {
+        if (['fetch', 'xmlhttprequest', 'beacon'].includes(entry.initiatorType)) continue;
+        let kind = initiators[entry.initiatorType] || 'other';
+        if (/\.(woff2?|ttf|otf|eot)(\?|$)/i.test(entry.name)) kind = 'font';
+        else if (kind === 'other' && /\.css(\?|$)/i.test(entry.name)) kind = 'stylesheet';
+        else if (kind === 'other' && /\.(png|jpe?g|gif|webp|avif|svg)(\?|$)/i.test(entry.name)) kind = 'image';
+        add(entry.name, kind, entry.initiatorType);
+    }
+    return { base: document.baseURI, page: location.href, links, assets };
+})()"#;
+
+/// Where on the page a link sits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkContext {
+    Navigation,
+    Header,
+    Footer,
+    Aside,
+    Main,
+    Body,
+}
+
+/// A link target, once per URL.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Link {
+    /// Absolute http(s) URL without fragment.
+    pub url: String,
+    /// `rel` values of every anchor pointing here, lowercased.
+    pub rel: Vec<String>,
+    /// The first non-empty anchor text, falling back to `aria-label` or an
+    /// image's `alt`.
+    pub text: String,
+    pub title: Option<String>,
+    /// Where the first anchor pointing here sits.
+    pub context: LinkContext,
+    /// Whether the URL is on the page's host, ignoring a `www.` prefix.
+    pub internal: bool,
+    /// How many anchors point here.
+    pub occurrences: usize,
+}
+
+impl Link {
+    /// Whether an anchor pointing here asks crawlers not to follow it.
+    pub fn is_nofollow(&self) -> bool {
+        self.rel
+            .iter()
+            .any(|rel| matches!(rel.as_str(), "nofollow" | "ugc" | "sponsored"))
+    }
+}
+
+/// What an asset is loaded as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssetKind {
+    Script,
+    Stylesheet,
+    Image,
+    Font,
+    Media,
+    Frame,
+    Icon,
+    Other,
+}
+
+/// A resource the page references or loaded, once per URL.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Asset {
+    /// Absolute http(s) URL without fragment.
+    pub url: String,
+    pub kind: AssetKind,
+    /// The element that references it, or the performance timeline's
+    /// initiator type for resources only seen loading.
+    pub tag: String,
+    /// Whether the URL is on the page's host, ignoring a `www.` prefix.
+    pub internal: bool,
+}
+
+/// Links and assets of one page.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageInventory {
+    pub links: Vec<Link>,
+    pub assets: Vec<Asset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawLink {
+    href: String,
+    rel: Vec<String>,
+    text: String,
+    title: Option<String>,
+    context: LinkContext,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawAsset {
+    url: String,
+    kind: AssetKind,
+    tag: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawInventory {
+    base: String,
+    page: String,
+    links: Vec<RawLink>,
+    assets: Vec<RawAsset>,
+}
+
+/// Resolve `href` against `base`, keeping only http(s) URLs without fragment.
+fn normalize(href: &str, base: Option<&Url>) -> Option<Url> {
+    let mut url = match base {
+        Some(base) => base.join(href.trim()).ok()?,
+        None => Url::parse(href.trim()).ok()?,
+    };
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    url.set_fragment(None);
+    Some(url)
+}
+
+/// Whether `url` is on the host of `page`, ignoring a `www.` prefix.
+pub(crate) fn same_site(url: &Url, page: Option<&Url>) -> bool {
+    let host = |url: &Url| {
+        url.host_str()
+            .map(|host| host.trim_start_matches("www.").to_ascii_lowercase())
+    };
+    page.is_some_and(|page| host(page).is_some() && host(page) == host(url))
+}
+
+impl From<RawInventory> for PageInventory {
+    fn from(raw: RawInventory) -> Self {
+        let page = Url::parse(&raw.page).ok();
+        let base = Url::parse(&raw.base).ok().or_else(|| page.clone());
+
+        let mut links: Vec<Link> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for found in raw.links {
+            let Some(url) = normalize(&found.href, base.as_ref()) else {
+                continue;
+            };
+            match index.get(url.as_str()) {
+                Some(&i) => {
+                    let link = &mut links[i];
+                    link.occurrences += 1;
+                    if link.text.is_empty() {
+                        link.text = found.text;
+                    }
+                    if link.title.is_none() {
+                        link.title = found.title.filter(|title| !title.trim().is_empty());
+                    }
+                    for rel in found.rel {
+                        if !link.rel.contains(&rel) {
+                            link.rel.push(rel);
+                        }
+                    }
+                }
+                None => {
+                    index.insert(url.to_string(), links.len());
+                    links.push(Link {
+                        internal: same_site(&url, page.as_ref()),
+                        url: url.into(),
+                        rel: found.rel,
+                        text: found.text,
+                        title: found.title.filter(|title| !title.trim().is_empty()),
+                        context: found.context,
+                        occurrences: 1,
+                    });
+                }
+            }
+        }
+
+        let mut assets: Vec<Asset> = Vec::new();
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        for found in raw.assets {
+            let Some(url) = normalize(&found.url, base.as_ref()) else {
+                continue;
+            };
+            match seen.get(url.as_str()) {
+                // the DOM knows better than the timeline what a URL is for
+                Some(&i) if assets[i].kind == AssetKind::Other => assets[i].kind = found.kind,
+                Some(_) => {}
+                None => {
+                    seen.insert(url.to_string(), assets.len());
+                    assets.push(Asset {
+                        internal: same_site(&url, page.as_ref()),
+                        url: url.into(),
+                        kind: found.kind,
+                        tag: found.tag,
+                    });
+                }
+            }
+        }
+        Self { links, assets }
+    }
+}
+
+impl ChaserPage {
+    /// The links and assets of the current page, read in one pass.
+    pub async fn inventory(&self) -> Result<PageInventory> {
+        match self.evaluate_stealth(INVENTORY_SCRIPT).await? {
+            Some(value) if !value.is_null() => {
+                let raw: RawInventory = serde_json::from_value(value)?;
+                Ok(raw.into())
+            }
+            _ => Ok(PageInventory::default()),
+        }
+    }
+
+    /// The http(s) links of the current page, once per URL, in document
+    /// order.
+    pub async fn links(&self) -> Result<Vec<Link>> {
+        Ok(self.inventory().await?.links)
+    }
+
+    /// The scripts, stylesheets, images, fonts, media and frames the
+    /// current page references or loaded, once per URL.
+    pub async fn assets(&self) -> Result<Vec<Asset>> {
+        Ok(self.inventory().await?.assets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn resolves_and_merges_duplicates() {
+        let raw: RawInventory = serde_json::from_value(json!({
+            "base": "https://shop.test/catalog/",
+            "page": "https://www.shop.test/catalog/?page=2",
+            "links": [
+                {"href": "kettles#top", "rel": [], "text": "", "title": null, "context": "navigation"},
+                {"href": "/catalog/kettles", "rel": ["nofollow"], "text": "Kettles", "title": null, "context": "main"},
+                {"href": "mailto:shop@shop.test", "rel": [], "text": "Mail", "title": null, "context": "footer"},
+                {"href": "https://cdn.other.test/x", "rel": [], "text": "Other", "title": "", "context": "body"}
+            ],
+            "assets": [
+                {"url": "https://cdn.other.test/app.js", "kind": "other", "tag": "script"},
+                {"url": "//cdn.other.test/app.js", "kind": "script", "tag": "script"},
+                {"url": "data:image/png;base64,AAAA", "kind": "image", "tag": "img"}
+            ]
+        }))
+        .unwrap();
+        let inventory = PageInventory::from(raw);
+
+        assert_eq!(inventory.links.len(), 2);
+        let kettles = &inventory.links[0];
+        assert_eq!(kettles.url, "https://shop.test/catalog/kettles");
+        assert_eq!(kettles.text, "Kettles");
+        assert_eq!(kettles.context, LinkContext::Navigation);
+        assert_eq!(kettles.occurrences, 2);
+        assert!(kettles.internal && kettles.is_nofollow());
+        assert!(!inventory.links[1].internal);
+        assert_eq!(inventory.links[1].title, None);
+
+        assert_eq!(inventory.assets.len(), 1);
+        assert_eq!(inventory.assets[0].kind, AssetKind::Script);
+    }
+}
@@ -2,35 +2,64 @@ use std::collections::{HashMap, VecDeque};
 use std::fmt;
 use std::marker::PhantomData;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
-use futures::channel::mpsc::{SendError, UnboundedReceiver, UnboundedSender};
+use futures::channel::mpsc::{unbounded, SendError, UnboundedReceiver, UnboundedSender};
 use futures::{Sink, Stream};
 
 use chromiumoxide_cdp::cdp::{Event, EventKind, IntoEventKind};
 use chromiumoxide_types::MethodId;
 
+/// How many events a [`subscription`] may hold unread before further
+/// events are dropped. `Fetch` events are never dropped, see
+/// [`subscription`].
+pub const DEFAULT_EVENT_BUFFER: usize = 4096;
+
 /// All the currently active listeners
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct EventListeners {
     /// Tracks the listeners for each event identified by the key
     listeners: HashMap<MethodId, Vec<EventListener>>,
+    /// Unread events allowed per tracked subscription
+    capacity: usize,
+}
+
+impl Default for EventListeners {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_EVENT_BUFFER)
+    }
 }
 
 impl EventListeners {
+    /// Listeners whose tracked subscriptions hold at most `capacity` unread
+    /// events each.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            listeners: HashMap::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
     /// Register a subscription for a method
     pub fn add_listener(&mut self, req: EventListenerRequest) {
         let EventListenerRequest {
             listener,
             method,
             kind,
+            backlog,
         } = req;
-        let subs = self.listeners.entry(method).or_default();
+        let subs = self.listeners.entry(method.clone()).or_default();
         subs.push(EventListener {
             listener,
             kind,
             queued_events: Default::default(),
+            lossless: method.starts_with("Fetch."),
+            method,
+            backlog,
+            capacity: self.capacity,
+            dropped: 0,
         });
     }
 
@@ -99,18 +128,52 @@ pub struct EventListenerRequest {
     listener: UnboundedSender<Arc<dyn Event>>,
     method: MethodId,
     kind: EventKind,
+    /// Unread events of the matching stream, if it counts them
+    backlog: Option<Arc<AtomicUsize>>,
 }
 
 impl EventListenerRequest {
+    /// A request for an unbounded subscription, see [`subscription`] for a
+    /// bounded one.
     pub fn new<T: IntoEventKind>(listener: UnboundedSender<Arc<dyn Event>>) -> Self {
         Self {
             listener,
             method: T::method_id(),
             kind: T::event_kind(),
+            backlog: None,
         }
     }
 }
 
+/// A subscription to `T` events: the request to hand to the handler and the
+/// stream receiving them.
+///
+/// Events the stream has not read yet count against the capacity of the
+/// listeners ([`DEFAULT_EVENT_BUFFER`] unless configured otherwise); once it
+/// is reached, new events are dropped with a warning instead of piling up
+/// in memory.
+///
+/// The `Fetch` domain is exempt: every paused request waits for an answer,
+/// so dropping `Fetch.requestPaused` or `Fetch.authRequired` would hang
+/// the request, and the page with it. Those events queue up past the
+/// capacity instead.
+pub fn subscription<T: IntoEventKind>() -> (EventListenerRequest, EventStream<T>) {
+    let (listener, events) = unbounded();
+    let backlog = Arc::new(AtomicUsize::new(0));
+    let request = EventListenerRequest {
+        listener,
+        method: T::method_id(),
+        kind: T::event_kind(),
+        backlog: Some(Arc::clone(&backlog)),
+    };
+    let stream = EventStream {
+        events,
+        backlog: Some(backlog),
+        _marker: PhantomData,
+    };
+    (request, stream)
+}
+
 impl fmt::Debug for EventListenerRequest {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("EventListenerRequest")
@@ -128,11 +191,35 @@ pub struct EventListener {
     queued_events: VecDeque<Arc<dyn Event>>,
     /// For what kind of event this event is for
     kind: EventKind,
+    method: MethodId,
+    /// Events sent to the stream and not read yet, if it counts them
+    backlog: Option<Arc<AtomicUsize>>,
+    capacity: usize,
+    /// Whether events are delivered however far behind the stream is
+    lossless: bool,
+    /// Events dropped because the stream fell behind
+    dropped: usize,
 }
 
 impl EventListener {
-    /// queue in a new event
+    /// queue in a new event, unless the subscription is full and may lose
+    /// events
     pub fn start_send(&mut self, event: Arc<dyn Event>) {
+        if let Some(backlog) = &self.backlog {
+            if !self.lossless && backlog.load(Ordering::Relaxed) >= self.capacity {
+                self.dropped += 1;
+                if self.dropped.is_power_of_two() {
+                    tracing::warn!(
+                        "slow consumer of {} events: {} unread, {} dropped",
+                        self.method,
+                        self.capacity,
+                        self.dropped
+                    );
+                }
+                return;
+            }
+            backlog.fetch_add(1, Ordering::Relaxed);
+        }
         self.queued_events.push_back(event)
     }
 
@@ -170,6 +257,7 @@ impl fmt::Debug for EventListener {
 /// The receiver part of an event subscription
 pub struct EventStream<T: IntoEventKind> {
     events: UnboundedReceiver<Arc<dyn Event>>,
+    backlog: Option<Arc<AtomicUsize>>,
     _marker: PhantomData<T>,
 }
 
@@ -183,6 +271,7 @@ impl<T: IntoEventKind> EventStream<T> {
     pub fn new(events: UnboundedReceiver<Arc<dyn Event>>) -> Self {
         Self {
             events,
+            backlog: None,
             _marker: PhantomData,
         }
     }
@@ -195,6 +284,9 @@ impl<T: IntoEventKind + Unpin> Stream for EventStream<T> {
         let pin = self.get_mut();
         match Stream::poll_next(Pin::new(&mut pin.events), cx) {
             Poll::Ready(Some(event)) => {
+                if let Some(backlog) = &pin.backlog {
+                    backlog.fetch_sub(1, Ordering::Relaxed);
+                }
                 if let Ok(e) = event.into_any_arc().downcast() {
                     Poll::Ready(Some(e))
                 } else {
@@ -209,9 +301,10 @@ impl<T: IntoEventKind + Unpin> Stream for EventStream<T> {
 
 #[cfg(test)]
 mod tests {
-    use futures::{SinkExt, StreamExt};
+    use futures::{FutureExt, SinkExt, StreamExt};
 
     use chromiumoxide_cdp::cdp::browser_protocol::animation::EventAnimationCanceled;
+    use chromiumoxide_cdp::cdp::browser_protocol::fetch::EventRequestPaused;
     use chromiumoxide_cdp::cdp::CustomEvent;
     use chromiumoxide_types::MethodType;
 
@@ -272,6 +365,7 @@ mod tests {
             method: EventAnimationCanceled::method_id(),
             kind: EventAnimationCanceled::event_kind(),
             listener: tx,
+            backlog: None,
         });
 
         listeners.start_send(event.clone());
@@ -291,4 +385,55 @@ mod tests {
         let next = stream.next().await.unwrap();
         assert_eq!(&*next, &event);
     }
+    #[tokio::test]
+    async fn slow_subscriptions_drop_new_events() {
+        let mut listeners = EventListeners::with_capacity(2);
+        let (request, mut stream) = subscription::<EventAnimationCanceled>();
+        listeners.add_listener(request);
+        let flush = |listeners: &mut EventListeners| {
+            futures::executor::block_on(std::future::poll_fn(|cx| {
+                listeners.poll(cx);
+                Poll::Ready(())
+            }))
+        };
+
+        for id in ["a", "b", "c"] {
+            listeners.start_send(EventAnimationCanceled { id: id.into() });
+        }
+        flush(&mut listeners);
+        assert_eq!(stream.next().await.unwrap().id, "a");
+        listeners.start_send(EventAnimationCanceled { id: "d".into() });
+        flush(&mut listeners);
+
+        let ids: Vec<_> = std::iter::from_fn(|| stream.next().now_or_never().flatten())
+            .map(|event| event.id.clone())
+            .collect();
+        assert_eq!(ids, ["b", "d"]);
+    }
+
+    #[tokio::test]
+    async fn paused_requests_are_delivered_past_the_capacity() {
+        let mut listeners = EventListeners::with_capacity(1);
+        let (request, mut stream) = subscription::<EventRequestPaused>();
+        listeners.add_listener(request);
+
+        for id in ["1", "2", "3"] {
+            let paused: EventRequestPaused = serde_json::from_value(serde_json::json!({
+                "requestId": id, "frameId": "MAIN", "resourceType": "Document",
+                "request": { "url": "https://shop.test/", "method": "GET", "headers": {},
+                             "initialPriority": "VeryHigh", "referrerPolicy": "origin" }
+            }))
+            .unwrap();
+            listeners.start_send(paused);
+        }
+        futures::executor::block_on(std::future::poll_fn(|cx| {
+            listeners.poll(cx);
+            Poll::Ready(())
+        }));
+
+        let ids: Vec<_> = std::iter::from_fn(|| stream.next().now_or_never().flatten())
+            .map(|event| event.request_id.inner().clone())
+            .collect();
+        assert_eq!(ids, ["1", "2", "3"]);
+    }
 }
@@ -0,0 +1,156 @@
+//! Language preferences of real browsers, per locale.
+//!
+//! A German user's Chrome does not send `Accept-Language: de-DE`; it sends
+//! `de-DE,de;q=0.9,en-US;q=0.8,en;q=0.7`, and `navigator.languages` lists the
+//! same four entries. This module knows the language lists Chrome ships with
+//! (or users typically end up with) for the most common locales and renders
+//! them the way Chrome does, with q-values falling by 0.1 per entry.
+//!
+//! # Example
+//!
+//! ```
+//! use chaser_oxide::locales;
+//!
+//! let languages = locales::languages("de-DE");
+//! assert_eq!(languages, ["de-DE", "de", "en-US", "en"]);
+//! assert_eq!(
+//!     locales::accept_language(&languages),
+//!     "de-DE,de;q=0.9,en-US;q=0.8,en;q=0.7"
+//! );
+//! ```
+
+/// The preferred languages of a browser set up for one locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocaleData {
+    /// BCP 47 tag, e.g. `de-DE`.
+    pub locale: &'static str,
+    /// `navigator.languages`, most preferred first.
+    pub languages: &'static [&'static str],
+}
+
+const fn entry(locale: &'static str, languages: &'static [&'static str]) -> LocaleData {
+    LocaleData { locale, languages }
+}
+
+/// Every locale with known language preferences.
+pub const LOCALES: &[LocaleData] = &[
+    entry("en-US", &["en-US", "en"]),
+    entry("en-GB", &["en-GB", "en-US", "en"]),
+    entry("en-CA", &["en-CA", "en-US", "en", "fr-CA"]),
+    entry("en-AU", &["en-AU", "en-GB", "en-US", "en"]),
+    entry("en-NZ", &["en-NZ", "en-GB", "en-US", "en"]),
+    entry("en-IE", &["en-IE", "en-GB", "en-US", "en"]),
+    entry("en-IN", &["en-IN", "en-GB", "en-US", "en", "hi"]),
+    entry("en-ZA", &["en-ZA", "en-GB", "en-US", "en"]),
+    entry("en-SG", &["en-SG", "en-GB", "en-US", "en", "zh-CN"]),
+    entry("en-PH", &["en-PH", "en-US", "en", "fil"]),
+    entry("de-DE", &["de-DE", "de", "en-US", "en"]),
+    entry("de-AT", &["de-AT", "de", "en-US", "en"]),
+    entry("de-CH", &["de-CH", "de", "fr", "en-US", "en"]),
+    entry("fr-FR", &["fr-FR", "fr", "en-US", "en"]),
+    entry("fr-CA", &["fr-CA", "fr", "en-CA", "en"]),
+    entry("fr-BE", &["fr-BE", "fr", "nl", "en-US", "en"]),
+    entry("fr-CH", &["fr-CH", "fr", "de", "en-US", "en"]),
+    entry("es-ES", &["es-ES", "es", "en-US", "en"]),
+    entry("es-MX", &["es-MX", "es-419", "es", "en"]),
+    entry("es-419", &["es-419", "es", "en"]),
+    entry("es-AR", &["es-AR", "es-419", "es", "en"]),
+    entry("es-CO", &["es-CO", "es-419", "es", "en"]),
+    entry("es-CL", &["es-CL", "es-419", "es", "en"]),
+    entry("es-US", &["es-US", "es", "en-US", "en"]),
+    entry("pt-BR", &["pt-BR", "pt", "en-US", "en"]),
+    entry("pt-PT", &["pt-PT", "pt", "en-US", "en"]),
+    entry("it-IT", &["it-IT", "it", "en-US", "en"]),
+    entry("nl-NL", &["nl-NL", "nl", "en-US", "en"]),
+    entry("nl-BE", &["nl-BE", "nl", "fr-BE", "fr", "en"]),
+    entry("pl-PL", &["pl-PL", "pl", "en-US", "en"]),
+    entry("cs-CZ", &["cs-CZ", "cs", "en-US", "en"]),
+    entry("sk-SK", &["sk-SK", "sk", "cs", "en-US", "en"]),
+    entry("hu-HU", &["hu-HU", "hu", "en-US", "en"]),
+    entry("ro-RO", &["ro-RO", "ro", "en-US", "en"]),
+    entry("bg-BG", &["bg-BG", "bg", "en-US", "en"]),
+    entry("hr-HR", &["hr-HR", "hr", "en-US", "en"]),
+    entry("sr-RS", &["sr-RS", "sr", "en-US", "en"]),
+    entry("el-GR", &["el-GR", "el", "en-US", "en"]),
+    entry("ru-RU", &["ru-RU", "ru", "en-US", "en"]),
+    entry("uk-UA", &["uk-UA", "uk", "ru", "en-US", "en"]),
+    entry("tr-TR", &["tr-TR", "tr", "en-US", "en"]),
+    entry("sv-SE", &["sv-SE", "sv", "en-US", "en"]),
+    entry("da-DK", &["da-DK", "da", "en-US", "en"]),
+    entry("nb-NO", &["nb-NO", "nb", "no", "nn", "en-US", "en"]),
+    entry("fi-FI", &["fi-FI", "fi", "en-US", "en"]),
+    entry("he-IL", &["he-IL", "he", "en-US", "en"]),
+    entry("ar-SA", &["ar-SA", "ar", "en-US", "en"]),
+    entry("ar-EG", &["ar-EG", "ar", "en-US", "en"]),
+    entry("ar-AE", &["ar-AE", "ar", "en-US", "en"]),
+    entry("fa-IR", &["fa-IR", "fa", "en-US", "en"]),
+    entry("hi-IN", &["hi-IN", "hi", "en-IN", "en-US", "en"]),
+    entry("th-TH", &["th-TH", "th", "en-US", "en"]),
+    entry("vi-VN", &["vi-VN", "vi", "en-US", "en"]),
+    entry("id-ID", &["id-ID", "id", "en-US", "en"]),
+    entry("ms-MY", &["ms-MY", "ms", "en-US", "en"]),
+    entry("ja-JP", &["ja", "en-US", "en"]),
+    entry("ko-KR", &["ko-KR", "ko", "en-US", "en"]),
+    entry("zh-CN", &["zh-CN", "zh", "en"]),
+    entry("zh-TW", &["zh-TW", "zh", "en-US", "en"]),
+    entry("zh-HK", &["zh-HK", "zh-TW", "zh", "en"]),
+];
+
+/// The known language preferences for `locale`, matched case-insensitively
+/// and with `_` accepted in place of `-`.
+pub fn lookup(locale: &str) -> Option<&'static LocaleData> {
+    let locale = locale.replace('_', "-");
+    LOCALES
+        .iter()
+        .find(|data| data.locale.eq_ignore_ascii_case(&locale))
+}
+
+/// `navigator.languages` for a browser set up for `locale`.
+///
+/// Unknown locales get the locale, its bare language and English, the way
+/// most non-English installs end up.
+pub fn languages(locale: &str) -> Vec<String> {
+    if let Some(data) = lookup(locale) {
+        return data.languages.iter().map(|l| l.to_string()).collect();
+    }
+    let locale = locale.replace('_', "-");
+    let base = locale.split('-').next().unwrap_or_default().to_string();
+    let mut languages = vec![locale.clone()];
+    if base != locale && !base.is_empty() {
+        languages.push(base.clone());
+    }
+    if base != "en" {
+        languages.extend(["en-US".to_string(), "en".to_string()]);
+    }
+    languages
+}
+
+/// The `Accept-Language` header Chrome sends for `languages`: the first
+/// without a q-value, then 0.9, 0.8, ... down to at least 0.1.
+pub fn accept_language<S: AsRef<str>>(languages: &[S]) -> String {
+    languages
+        .iter()
+        .enumerate()
+        .map(|(i, lang)| match i {
+            0 => lang.as_ref().to_string(),
+            _ => format!("{};q=0.{}", lang.as_ref(), 10usize.saturating_sub(i).max(1)),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_locales_fall_back_to_language_and_english() {
+        assert_eq!(languages("eu_ES"), ["eu-ES", "eu", "en-US", "en"]);
+        assert_eq!(languages("en-KE"), ["en-KE", "en"]);
+        assert_eq!(lookup("pt_br").unwrap().locale, "pt-BR");
+        assert!(LOCALES.len() >= 40);
+
+        let many: Vec<String> = (0..12).map(|i| format!("x{i}")).collect();
+        assert!(accept_language(&many).ends_with("x10;q=0.1,x11;q=0.1"));
+    }
+}
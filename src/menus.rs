@@ -0,0 +1,142 @@
+//! Walking hover menus and nested dropdowns like a person.
+//!
+//! Mega-menus open a submenu on hover and close it as soon as the cursor
+//! rests on another top-level entry, so the curved move-then-click of
+//! [`ChaserPage::click_human`] loses the submenu halfway. People cut
+//! diagonally from the open entry straight into the submenu, inside the
+//! "menu triangle" that menus such as Amazon's tolerate, and linger on each
+//! entry until its submenu has appeared. [`ChaserPage::navigate_menu_human`]
+//! does the same: it hovers each level, waits for the next one to render
+//! and crosses into it on a fast, nearly straight line. Touch profiles tap
+//! each level instead.
+//!
+//! # Example
+//!
+//! ```ignore
+//! chaser
+//!     .navigate_menu_human(&["#nav-departments", "#nav-electronics", "a[href='/tv']"])
+//!     .await?;
+//! ```
+
+use crate::chaser::{ChaserPage, Point};
+use crate::error::ChaserResult as Result;
+use crate::input_pipeline::InputPipeline;
+use rand::Rng;
+use std::time::Duration;
+
+/// Points from `from` to `to` along an eased, almost straight line with a
+/// slight sideways sway, as in a quick flick of the wrist.
+fn diagonal_path(from: Point, to: Point, rng: &mut impl Rng) -> Vec<Point> {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length < 1.0 {
+        return vec![to];
+    }
+    let steps = (length / 25.0).clamp(6.0, 18.0) as usize;
+    // unit normal to the line, for the sway
+    let (nx, ny) = (-dy / length, dx / length);
+    let sway = rng.gen_range(-1.0..1.0) * (length * 0.03).min(4.0);
+    (1..=steps)
+        .map(|i| {
+            let t = i as f64 / steps as f64;
+            let eased = t * t * (3.0 - 2.0 * t);
+            let bulge = sway * (std::f64::consts::PI * t).sin();
+            Point {
+                x: from.x + dx * eased + nx * bulge,
+                y: from.y + dy * eased + ny * bulge,
+            }
+        })
+        .collect()
+}
+
+impl ChaserPage {
+    /// Hover through the menu levels matching `selectors`, in order, and
+    /// click the last one.
+    ///
+    /// Each level must become visible within the page's wait timeout after
+    /// the previous one was hovered.
+    pub async fn navigate_menu_human(&self, selectors: &[&str]) -> Result<()> {
+        let touch = self.hover_menu_human(selectors).await?;
+        if touch {
+            // the last level was already tapped
+            return Ok(());
+        }
+        let aim = rand::thread_rng().gen_range(80..220);
+        self.pause(Duration::from_millis(aim)).await?;
+        self.click().await?;
+        let after = rand::thread_rng().gen_range(30..80);
+        self.pause(Duration::from_millis(after)).await
+    }
+
+    /// Hover through the menu levels matching `selectors`, in order,
+    /// leaving the cursor on the last one, e.g. to read a submenu. Touch
+    /// profiles tap every level, the last one included; returns whether
+    /// they did.
+    pub async fn hover_menu_human(&self, selectors: &[&str]) -> Result<bool> {
+        let touch = self
+            .profile()
+            .is_some_and(|profile| profile.os().is_mobile());
+        for (level, selector) in selectors.iter().enumerate() {
+            let target = self.wait_for_menu_item(selector).await?;
+            if touch {
+                self.click_human(target.x, target.y).await?;
+            } else if level == 0 {
+                self.move_mouse_human(target.x, target.y).await?;
+            } else {
+                let path = diagonal_path(
+                    self.current_mouse_position(),
+                    target,
+                    &mut rand::thread_rng(),
+                );
+                let mut moves = InputPipeline::new(self);
+                for point in path {
+                    self.queue_mouse_move(&mut moves, point).await?;
+                    let step = rand::thread_rng().gen_range(6..12);
+                    self.pause(Duration::from_millis(step)).await?;
+                }
+                moves.finish().await?;
+            }
+            if level + 1 < selectors.len() {
+                // hover intent: menus wait for the cursor to settle
+                let dwell = rand::thread_rng().gen_range(180..420);
+                self.pause(Duration::from_millis(dwell)).await?;
+            }
+        }
+        Ok(touch)
+    }
+
+    /// Wait until the menu entry matching `selector` is rendered and can be
+    /// pointed at, and pick a spot on it.
+    async fn wait_for_menu_item(&self, selector: &str) -> Result<Point> {
+        let (element, center) = self.wait_for_selector_actionable(selector).await?;
+        let size = element.bounding_box().await?;
+        let mut rng = rand::thread_rng();
+        Ok(Point {
+            x: center.x + size.width * rng.gen_range(-0.25..0.25),
+            y: center.y + size.height * rng.gen_range(-0.2..0.2),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn diagonals_stay_close_to_the_line() {
+        let from = Point { x: 100.0, y: 40.0 };
+        let to = Point { x: 400.0, y: 240.0 };
+        let path = diagonal_path(from, to, &mut StdRng::seed_from_u64(7));
+        let end = path.last().unwrap();
+        assert!((end.x - to.x).abs() < 1e-9 && (end.y - to.y).abs() < 1e-9);
+        let length = (300.0f64 * 300.0 + 200.0 * 200.0).sqrt();
+        for p in &path {
+            // distance from the line through `from` and `to`
+            let off = ((to.x - from.x) * (from.y - p.y) - (from.x - p.x) * (to.y - from.y)).abs()
+                / length;
+            assert!(off <= 4.0 + 1e-9, "{p:?} is {off}px off the line");
+        }
+    }
+}
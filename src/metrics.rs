@@ -0,0 +1,320 @@
+//! Outcome metrics for a fleet: how often navigations pass, get challenged
+//! or blocked, per domain, and how long pages and tasks take.
+//!
+//! A [`Metrics`] handle is cheap to clone and shared by everything that
+//! records into it: a [`VerdictCollector`](crate::verdict::VerdictCollector)
+//! built with [`metrics`](crate::verdict::VerdictCollector::metrics) counts
+//! every navigation verdict, and a pool configured with
+//! [`PoolConfigBuilder::metrics`](crate::pool::PoolConfigBuilder::metrics)
+//! counts every task the orchestrator runs on it. [`Metrics::snapshot`]
+//! gives the rates; with the `prometheus` feature,
+//! [`Metrics::render_openmetrics`] and [`Metrics::serve`] expose the raw
+//! counters for Prometheus to scrape.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::metrics::Metrics;
+//! use chaser_oxide::pool::PoolConfig;
+//! use chaser_oxide::verdict::VerdictCollector;
+//!
+//! let metrics = Metrics::new();
+//! metrics.serve("0.0.0.0:9464")?; // feature `prometheus`
+//! let config = PoolConfig::builder().metrics(metrics.clone()).build();
+//! let verdicts = VerdictCollector::new().metrics(metrics.clone());
+//! // ... run tasks that navigate with `verdicts.goto(&page, url)`
+//! let snapshot = metrics.snapshot();
+//! println!("{:.1}% challenged", snapshot.challenge_rate() * 100.0);
+//! ```
+
+use crate::verdict::{NavigationVerdict, Outcome};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Counts for one domain.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DomainMetrics {
+    /// Navigations per [`Outcome::name`].
+    pub outcomes: BTreeMap<&'static str, u64>,
+    /// Navigations whose load time was measured, and their sum.
+    pub loads: u64,
+    pub load_time: Duration,
+}
+
+impl DomainMetrics {
+    pub fn navigations(&self) -> u64 {
+        self.outcomes.values().sum()
+    }
+
+    fn count(&self, outcome: &str) -> u64 {
+        self.outcomes.get(outcome).copied().unwrap_or(0)
+    }
+
+    /// Challenges, captchas and blocks.
+    pub fn blocks(&self) -> u64 {
+        self.count("challenged") + self.count("captcha") + self.count("blocked")
+    }
+}
+
+/// The state of a [`Metrics`] handle at one point in time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    pub domains: BTreeMap<String, DomainMetrics>,
+    pub tasks_succeeded: u64,
+    pub tasks_failed: u64,
+    /// Time spent in tasks, successful or not.
+    pub task_time: Duration,
+}
+
+impl MetricsSnapshot {
+    pub fn navigations(&self) -> u64 {
+        self.domains.values().map(DomainMetrics::navigations).sum()
+    }
+
+    fn rate(&self, outcome: &str) -> f64 {
+        let navigations = self.navigations();
+        if navigations == 0 {
+            return 0.0;
+        }
+        let count: u64 = self.domains.values().map(|d| d.count(outcome)).sum();
+        count as f64 / navigations as f64
+    }
+
+    /// Share of navigations that passed.
+    pub fn success_rate(&self) -> f64 {
+        self.rate("passed")
+    }
+
+    /// Share of navigations that met a self-clearing challenge.
+    pub fn challenge_rate(&self) -> f64 {
+        self.rate("challenged")
+    }
+
+    /// Share of navigations that met a captcha.
+    pub fn captcha_rate(&self) -> f64 {
+        self.rate("captcha")
+    }
+
+    /// Average load time of the navigations that were timed.
+    pub fn avg_load_time(&self) -> Option<Duration> {
+        let loads: u64 = self.domains.values().map(|d| d.loads).sum();
+        let total: Duration = self.domains.values().map(|d| d.load_time).sum();
+        (loads > 0).then(|| total / loads as u32)
+    }
+
+    /// Challenges, captchas and blocks per domain, most first.
+    pub fn blocks_by_domain(&self) -> Vec<(String, u64)> {
+        let mut blocks: Vec<_> = self
+            .domains
+            .iter()
+            .map(|(domain, d)| (domain.clone(), d.blocks()))
+            .filter(|(_, blocks)| *blocks > 0)
+            .collect();
+        blocks.sort_by_key(|(_, blocks)| std::cmp::Reverse(*blocks));
+        blocks
+    }
+
+    /// Share of tasks that succeeded.
+    pub fn task_success_rate(&self) -> f64 {
+        let tasks = self.tasks_succeeded + self.tasks_failed;
+        if tasks == 0 {
+            return 0.0;
+        }
+        self.tasks_succeeded as f64 / tasks as f64
+    }
+}
+
+/// A shared, thread-safe recorder of navigation and task outcomes.
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    inner: Arc<Mutex<MetricsSnapshot>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a navigation under its URL's host.
+    pub fn record_verdict(&self, verdict: &NavigationVerdict) {
+        let domain = url::Url::parse(&verdict.url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_else(|| "unknown".to_string());
+        let mut inner = self.inner.lock().unwrap();
+        let metrics = inner.domains.entry(domain).or_default();
+        *metrics.outcomes.entry(verdict.outcome.name()).or_default() += 1;
+        if verdict.elapsed_ms > 0 && !matches!(verdict.outcome, Outcome::Failed { .. }) {
+            metrics.loads += 1;
+            metrics.load_time += Duration::from_millis(verdict.elapsed_ms);
+        }
+    }
+
+    /// Count a finished task.
+    pub fn record_task(&self, succeeded: bool, elapsed: Duration) {
+        let mut inner = self.inner.lock().unwrap();
+        if succeeded {
+            inner.tasks_succeeded += 1;
+        } else {
+            inner.tasks_failed += 1;
+        }
+        inner.task_time += elapsed;
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        self.inner.lock().unwrap().clone()
+    }
+
+    /// Forget everything recorded so far.
+    pub fn reset(&self) {
+        *self.inner.lock().unwrap() = MetricsSnapshot::default();
+    }
+}
+
+#[cfg(feature = "prometheus")]
+impl Metrics {
+    /// The counters in the OpenMetrics text format, which Prometheus scrapes.
+    pub fn render_openmetrics(&self) -> String {
+        use std::fmt::Write;
+
+        fn label(value: &str) -> String {
+            value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+        }
+
+        let snapshot = self.snapshot();
+        let mut out = String::new();
+        out.push_str(
+            "# TYPE chaser_navigations counter\n\
+             # HELP chaser_navigations Navigations by domain and outcome.\n",
+        );
+        for (domain, metrics) in &snapshot.domains {
+            for (outcome, count) in &metrics.outcomes {
+                let _ = writeln!(
+                    out,
+                    "chaser_navigations_total{{domain=\"{}\",outcome=\"{outcome}\"}} {count}",
+                    label(domain)
+                );
+            }
+        }
+        out.push_str(
+            "# TYPE chaser_navigation_duration_seconds summary\n\
+             # UNIT chaser_navigation_duration_seconds seconds\n\
+             # HELP chaser_navigation_duration_seconds Load time of timed navigations.\n",
+        );
+        for (domain, metrics) in &snapshot.domains {
+            let domain = label(domain);
+            let _ = writeln!(
+                out,
+                "chaser_navigation_duration_seconds_sum{{domain=\"{domain}\"}} {}\n\
+                 chaser_navigation_duration_seconds_count{{domain=\"{domain}\"}} {}",
+                metrics.load_time.as_secs_f64(),
+                metrics.loads
+            );
+        }
+        let _ = write!(
+            out,
+            "# TYPE chaser_tasks counter\n\
+             # HELP chaser_tasks Orchestrated tasks by result.\n\
+             chaser_tasks_total{{result=\"ok\"}} {}\n\
+             chaser_tasks_total{{result=\"error\"}} {}\n\
+             # TYPE chaser_task_duration_seconds counter\n\
+             # UNIT chaser_task_duration_seconds seconds\n\
+             # HELP chaser_task_duration_seconds Time spent in tasks.\n\
+             chaser_task_duration_seconds_total {}\n\
+             # EOF\n",
+            snapshot.tasks_succeeded,
+            snapshot.tasks_failed,
+            snapshot.task_time.as_secs_f64(),
+        );
+        out
+    }
+
+    /// Answer every HTTP request on `addr` with the
+    /// [`render_openmetrics`](Self::render_openmetrics) text, from a
+    /// background thread.
+    pub fn serve(
+        &self,
+        addr: impl std::net::ToSocketAddrs,
+    ) -> std::io::Result<std::thread::JoinHandle<()>> {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind(addr)?;
+        let metrics = self.clone();
+        Ok(std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                // the request itself does not matter, only that it was sent
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request);
+                let body = metrics.render_openmetrics();
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\n\
+                     Content-Type: application/openmetrics-text; version=1.0.0; charset=utf-8\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verdict(url: &str, outcome: Outcome, elapsed_ms: u64) -> NavigationVerdict {
+        NavigationVerdict {
+            url: url.to_string(),
+            final_url: None,
+            at: 0,
+            elapsed_ms,
+            status: None,
+            outcome,
+            cf_ray: None,
+            vendors: Vec::new(),
+            signals: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn rates_and_blocks_per_domain() {
+        let metrics = Metrics::new();
+        metrics.record_verdict(&verdict("https://a.test/x", Outcome::Passed, 800));
+        metrics.record_verdict(&verdict("https://a.test/y", Outcome::Passed, 1200));
+        metrics.record_verdict(&verdict(
+            "https://b.test/",
+            Outcome::Captcha {
+                provider: "hCaptcha".into(),
+            },
+            0,
+        ));
+        metrics.record_verdict(&verdict(
+            "https://b.test/",
+            Outcome::Challenged {
+                vendor: "Cloudflare".into(),
+            },
+            0,
+        ));
+        metrics.record_task(true, Duration::from_secs(3));
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.success_rate(), 0.5);
+        assert_eq!(snapshot.captcha_rate(), 0.25);
+        assert_eq!(snapshot.avg_load_time(), Some(Duration::from_secs(1)));
+        assert_eq!(snapshot.blocks_by_domain(), vec![("b.test".to_string(), 2)]);
+        assert_eq!(snapshot.task_success_rate(), 1.0);
+
+        #[cfg(feature = "prometheus")]
+        {
+            let text = metrics.render_openmetrics();
+            assert!(
+                text.contains("chaser_navigations_total{domain=\"b.test\",outcome=\"captcha\"} 1")
+            );
+            assert!(text.ends_with("# EOF\n"));
+        }
+    }
+}
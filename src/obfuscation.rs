@@ -0,0 +1,437 @@
+//! Per-session variants of the bootstrap script.
+//!
+//! Every page running the crate's patches used to carry the same text: the
+//! same local names, the same string literals, the same comments, the same
+//! patch order. Sensors read patched functions back with
+//! `Function.prototype.toString`, and one hash of that text is enough to
+//! blocklist every user of the crate at once. An [`Obfuscation`] rewrites
+//! the script from a seed before it is injected:
+//!
+//! - the patches are injected in a shuffled order (each is self-contained),
+//! - local variables get random names; function names, which pages can
+//!   read through `.name`, and property names are kept,
+//! - string literals are split into concatenations at random points,
+//! - comments are dropped and whitespace collapsed.
+//!
+//! Pages get a random seed of their own unless
+//! [`ChaserPage::set_script_obfuscation`] sets one (for a script that stays
+//! the same across sessions of one identity) or turns the pass off.
+//!
+//! # Example
+//!
+//! ```
+//! use chaser_oxide::obfuscation::Obfuscation;
+//! use chaser_oxide::policy::StealthPolicy;
+//! use chaser_oxide::ChaserProfile;
+//!
+//! let profile = ChaserProfile::windows().build();
+//! let script = profile.obfuscated_bootstrap_script(&StealthPolicy::default(), &Obfuscation::new(7));
+//! assert!(!script.contains("gestureError"));
+//! assert!(script.contains("function PushManager("));
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::patches::Patch;
+use crate::policy::StealthPolicy;
+use crate::profiles::ChaserProfile;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet};
+
+/// How to rewrite the bootstrap script; the same seed always gives the
+/// same script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Obfuscation {
+    seed: u64,
+    rename: bool,
+    split_strings: bool,
+    shuffle: bool,
+}
+
+impl Obfuscation {
+    /// Every transformation, driven by `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rename: true,
+            split_strings: true,
+            shuffle: true,
+        }
+    }
+
+    /// Every transformation with a random seed.
+    pub fn random() -> Self {
+        Self::new(rand::random())
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Give local variables random names (default: on).
+    pub fn rename_identifiers(mut self, on: bool) -> Self {
+        self.rename = on;
+        self
+    }
+
+    /// Split string literals into concatenations (default: on).
+    pub fn split_strings(mut self, on: bool) -> Self {
+        self.split_strings = on;
+        self
+    }
+
+    /// Inject the patches in a shuffled order (default: on).
+    pub fn shuffle_patches(mut self, on: bool) -> Self {
+        self.shuffle = on;
+        self
+    }
+
+    /// `patches` in the order to inject them.
+    pub(crate) fn order(&self, patches: &[Patch]) -> Vec<Patch> {
+        let mut patches = patches.to_vec();
+        if self.shuffle {
+            patches.shuffle(&mut StdRng::seed_from_u64(self.seed));
+        }
+        patches
+    }
+
+    /// Rewrite `script`, JavaScript in the style of the patches.
+    pub fn apply(&self, script: &str) -> String {
+        // a stream of its own, so toggling the shuffle leaves names alone
+        let mut rng = StdRng::seed_from_u64(self.seed ^ 0x9e37_79b9_7f4a_7c15);
+        let tokens = tokenize(script);
+        let renames = if self.rename {
+            renames(&tokens, &mut rng)
+        } else {
+            HashMap::new()
+        };
+
+        let mut out = String::with_capacity(script.len());
+        for (i, token) in tokens.iter().enumerate() {
+            match *token {
+                Token::Space(s) => out.push(if s.contains('\n') { '\n' } else { ' ' }),
+                Token::Comment(c) => {
+                    if !c.starts_with("//") {
+                        out.push(' ');
+                    }
+                }
+                Token::Ident(name) => match renames.get(name) {
+                    Some(new) if is_variable(&tokens, i) => out.push_str(new),
+                    _ => out.push_str(name),
+                },
+                Token::Str(s) if self.split_strings && !is_key(&tokens, i) => {
+                    out.push_str(&split_string(s, &mut rng))
+                }
+                Token::Str(s) | Token::Other(s) => out.push_str(s),
+            }
+        }
+        out.trim().to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    Space(&'a str),
+    Comment(&'a str),
+    Ident(&'a str),
+    /// A single- or double-quoted string literal, quotes included.
+    Str(&'a str),
+    /// Punctuation, numbers, regular expression and template literals.
+    Other(&'a str),
+}
+
+impl Token<'_> {
+    fn text(&self) -> &str {
+        match *self {
+            Token::Space(s)
+            | Token::Comment(s)
+            | Token::Ident(s)
+            | Token::Str(s)
+            | Token::Other(s) => s,
+        }
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || c == '$'
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Enough of a JavaScript lexer to tell code from strings, comments and
+/// regular expressions.
+fn tokenize(src: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let bytes = src.as_bytes();
+    let mut i = 0;
+    while i < src.len() {
+        let c = src[i..].chars().next().unwrap_or_default();
+        let start = i;
+        let end_of = |from: usize, pred: &dyn Fn(char) -> bool| {
+            src[from..]
+                .char_indices()
+                .find(|&(_, c)| !pred(c))
+                .map_or(src.len(), |(j, _)| from + j)
+        };
+        let token = if c.is_whitespace() {
+            i = end_of(i, &|c| c.is_whitespace());
+            Token::Space(&src[start..i])
+        } else if src[i..].starts_with("//") {
+            i = src[i..].find('\n').map_or(src.len(), |j| i + j);
+            Token::Comment(&src[start..i])
+        } else if src[i..].starts_with("/*") {
+            i = src[i + 2..].find("*/").map_or(src.len(), |j| i + 2 + j + 2);
+            Token::Comment(&src[start..i])
+        } else if c == '\'' || c == '"' || c == '`' {
+            i += 1;
+            while i < src.len() && bytes[i] != c as u8 {
+                i += if bytes[i] == b'\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(src.len());
+            if c == '`' {
+                Token::Other(&src[start..i])
+            } else {
+                Token::Str(&src[start..i])
+            }
+        } else if c == '/' && regex_allowed(&tokens) {
+            i += 1;
+            let mut class = false;
+            while i < src.len() && (bytes[i] != b'/' || class) {
+                match bytes[i] {
+                    b'\\' => i += 1,
+                    b'[' => class = true,
+                    b']' => class = false,
+                    _ => {}
+                }
+                i += 1;
+            }
+            i = end_of((i + 1).min(src.len()), &|c| c.is_ascii_alphabetic());
+            Token::Other(&src[start..i])
+        } else if is_ident_start(c) {
+            i = end_of(i, &is_ident_char);
+            Token::Ident(&src[start..i])
+        } else if c.is_ascii_digit() {
+            i = end_of(i, &|c| c.is_ascii_alphanumeric() || c == '.' || c == '_');
+            Token::Other(&src[start..i])
+        } else {
+            i += c.len_utf8();
+            Token::Other(&src[start..i])
+        };
+        tokens.push(token);
+    }
+    tokens
+}
+
+fn significant<'a, 'b>(mut tokens: impl Iterator<Item = &'b Token<'a>>) -> Option<&'b Token<'a>> {
+    tokens.find(|t| !matches!(t, Token::Space(_) | Token::Comment(_)))
+}
+
+fn previous<'a, 'b>(tokens: &'b [Token<'a>], i: usize) -> Option<&'b Token<'a>> {
+    significant(tokens[..i].iter().rev())
+}
+
+fn next<'a, 'b>(tokens: &'b [Token<'a>], i: usize) -> Option<&'b Token<'a>> {
+    significant(tokens[i + 1..].iter())
+}
+
+/// Whether a `/` here starts a regular expression rather than a division.
+fn regex_allowed(tokens: &[Token<'_>]) -> bool {
+    match previous(tokens, tokens.len()) {
+        None => true,
+        Some(Token::Ident(word)) => matches!(
+            *word,
+            "return" | "typeof" | "case" | "in" | "of" | "new" | "delete" | "void" | "throw"
+        ),
+        Some(Token::Other(p)) => matches!(
+            *p,
+            "(" | ","
+                | "="
+                | ":"
+                | "["
+                | "!"
+                | "&"
+                | "|"
+                | "?"
+                | "{"
+                | "}"
+                | ";"
+                | "+"
+                | "-"
+                | "*"
+                | "%"
+                | "<"
+                | ">"
+                | "~"
+                | "^"
+        ),
+        Some(_) => false,
+    }
+}
+
+/// Whether token `i` is the key of an object literal entry.
+fn is_key(tokens: &[Token<'_>], i: usize) -> bool {
+    next(tokens, i).map(Token::text) == Some(":")
+        && matches!(previous(tokens, i).map(Token::text), Some("{" | ","))
+}
+
+/// Whether identifier `i` refers to a variable, not a property.
+fn is_variable(tokens: &[Token<'_>], i: usize) -> bool {
+    previous(tokens, i).map(Token::text) != Some(".") && !is_key(tokens, i)
+}
+
+/// New names for the variables declared with `const`, `let` or `var`.
+fn renames(tokens: &[Token<'_>], rng: &mut StdRng) -> HashMap<String, String> {
+    let taken: HashSet<&str> = tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Ident(name) => Some(*name),
+            _ => None,
+        })
+        .collect();
+    let mut renames = HashMap::new();
+    let mut used = HashSet::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if !matches!(token, Token::Ident("const" | "let" | "var")) {
+            continue;
+        }
+        let Some(Token::Ident(name)) = next(tokens, i) else {
+            continue;
+        };
+        if renames.contains_key(*name) {
+            continue;
+        }
+        let fresh = loop {
+            let name = fresh_name(rng);
+            if !taken.contains(name.as_str()) && used.insert(name.clone()) {
+                break name;
+            }
+        };
+        renames.insert(name.to_string(), fresh);
+    }
+    renames
+}
+
+/// Letters, a digit, then letters or digits: never a keyword or a global.
+fn fresh_name(rng: &mut StdRng) -> String {
+    const LETTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+    const ALPHANUMERIC: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+    let pick = |rng: &mut StdRng, pool: &[u8]| pool[rng.gen_range(0..pool.len())] as char;
+    let mut name = String::new();
+    for _ in 0..rng.gen_range(1..=4) {
+        name.push(pick(rng, LETTERS));
+    }
+    name.push(char::from(b'0' + rng.gen_range(0..10u8)));
+    for _ in 0..rng.gen_range(0..=3) {
+        name.push(pick(rng, ALPHANUMERIC));
+    }
+    name
+}
+
+/// `'abcdef'` as `('abc'+'def')`, split at a random point. Literals with
+/// escapes or fewer than four characters are kept.
+fn split_string(literal: &str, rng: &mut StdRng) -> String {
+    let quote = &literal[..1];
+    let body = &literal[1..literal.len() - 1];
+    let chars: Vec<(usize, char)> = body.char_indices().collect();
+    if chars.len() < 4 || body.contains('\\') {
+        return literal.to_string();
+    }
+    let at = chars[rng.gen_range(1..chars.len())].0;
+    format!(
+        "({quote}{}{quote}+{quote}{}{quote})",
+        &body[..at],
+        &body[at..]
+    )
+}
+
+impl ChaserProfile {
+    /// [`bootstrap_script_with_policy`](Self::bootstrap_script_with_policy)
+    /// rewritten by `obfuscation`.
+    pub fn obfuscated_bootstrap_script(
+        &self,
+        policy: &StealthPolicy,
+        obfuscation: &Obfuscation,
+    ) -> String {
+        let patches = obfuscation.order(&self.patches());
+        obfuscation.apply(&crate::patches::compose(self, &patches, policy))
+    }
+}
+
+impl ChaserPage {
+    /// How the bootstrap script of this page is rewritten, `None` if it is
+    /// injected as is.
+    pub fn script_obfuscation(&self) -> Option<Obfuscation> {
+        *self.obfuscation().lock().unwrap()
+    }
+
+    /// Rewrite the bootstrap script with `obfuscation` (or not at all with
+    /// `None`) from the next [`apply_profile`](Self::apply_profile) or
+    /// [`set_stealth_policy`](Self::set_stealth_policy) on. Pages start
+    /// with [`Obfuscation::random`].
+    pub fn set_script_obfuscation(&self, obfuscation: Option<Obfuscation>) {
+        *self.obfuscation().lock().unwrap() = obfuscation;
+    }
+
+    /// The bootstrap script to inject for `profile` under `policy`.
+    pub(crate) fn bootstrap_source(
+        &self,
+        profile: &ChaserProfile,
+        policy: &StealthPolicy,
+    ) -> String {
+        match self.script_obfuscation() {
+            Some(obfuscation) => profile.obfuscated_bootstrap_script(policy, &obfuscation),
+            None => profile.bootstrap_script_with_policy(policy),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renames_variables_only() {
+        let script = r#"
+            // patch
+            const realQuery = Permissions.prototype.query;
+            const name = descriptor.name;
+            Permissions.prototype.query = function query(d) {
+                /* keep */ const re = /^cdc_|'\/x/g;
+                if (name === 'push') throw new DOMException("isn't a name request.", 'NotSupportedError');
+                return realQuery.apply(this, { name: name, 'x': 1 });
+            };"#;
+        let obfuscation = Obfuscation::new(1).split_strings(false);
+        let out = obfuscation.apply(script);
+        assert_eq!(out, obfuscation.apply(script));
+        assert!(!out.contains("realQuery") && !out.contains("patch") && !out.contains("keep"));
+        assert!(out.contains("function query(d)"));
+        assert!(out.contains("descriptor.name;"));
+        assert!(out.contains("{ name: "));
+        assert!(out.contains(r#""isn't a name request.""#));
+        assert!(out.contains(r"/^cdc_|'\/x/g"));
+        assert!(!out.contains("(name ==="));
+
+        let split = Obfuscation::new(1).apply(script);
+        assert!(!split.contains("'NotSupportedError'"));
+        assert!(split.contains("'x':"));
+        assert_ne!(split, Obfuscation::new(2).apply(script));
+    }
+
+    #[test]
+    fn profiles_get_distinct_scripts() {
+        let profile = ChaserProfile::windows().build();
+        let policy = StealthPolicy::default();
+        let a = profile.obfuscated_bootstrap_script(&policy, &Obfuscation::new(1));
+        let b = profile.obfuscated_bootstrap_script(&policy, &Obfuscation::new(2));
+        assert_ne!(a, b);
+        assert_eq!(
+            a,
+            profile.obfuscated_bootstrap_script(&policy, &Obfuscation::new(1))
+        );
+        assert!(!a.contains("MINIMAL STEALTH"));
+        assert!(a.contains("function PushManager("));
+    }
+}
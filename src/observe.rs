@@ -0,0 +1,240 @@
+//! Streaming values out of long-running scripts in the isolated world.
+//!
+//! `Runtime.addBinding` is the usual way for page code to call back into
+//! the client, and it needs the `Runtime` domain this crate never enables.
+//! [`ChaserPage::observe`] gets by without it: the observer runs in the
+//! isolated world with an `emit` function that queues JSON values in a
+//! global of that world, under a random name, and a task drains the queue
+//! every [`POLL_INTERVAL`] and hands each value to a Rust handler. The
+//! page sees no binding, no DOM change and no console output.
+//!
+//! The observer is also registered for new documents, so it keeps running
+//! across navigations, main frame only. Values emitted in a document that
+//! is left before the next drain are lost.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let observer = chaser
+//!     .observe(
+//!         r#"emit => new MutationObserver(records => {
+//!             for (const r of records) for (const n of r.addedNodes) {
+//!                 if (n.matches && n.matches('.price')) emit(n.textContent);
+//!             }
+//!         }).observe(document, { childList: true, subtree: true })"#,
+//!         |price| println!("new price: {price}"),
+//!     )
+//!     .await?;
+//! chaser.goto("https://shop.example/live").await?;
+//! // ...
+//! observer.stop().await;
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::{ChaserError, ChaserResult as Result};
+use crate::utils;
+use chromiumoxide_cdp::cdp::browser_protocol::page::{
+    AddScriptToEvaluateOnNewDocumentParams, RemoveScriptToEvaluateOnNewDocumentParams,
+    ScriptIdentifier,
+};
+use futures::channel::oneshot;
+use serde_json::Value;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// How often the queue of an observer is drained.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Queued values beyond this are dropped, oldest first.
+const QUEUE_CAPACITY: usize = 10_000;
+
+/// Starts `observer`, a function taking `emit`, once per document.
+fn observer_script(key: &str, observer: &str) -> String {
+    format!(
+        r#"(() => {{
+    if (window !== top || globalThis['{key}']) return;
+    const queue = globalThis['{key}'] = [];
+    const push = entry => {{
+        queue.push(entry);
+        if (queue.length > {QUEUE_CAPACITY}) queue.shift();
+    }};
+    const emit = value => {{
+        try {{
+            push({{ value: value === undefined ? null : JSON.parse(JSON.stringify(value)) }});
+        }} catch (e) {{
+            push({{ error: 'emit: ' + e }});
+        }}
+    }};
+    try {{
+        Promise.resolve(({observer})(emit)).catch(e => push({{ error: String(e) }}));
+    }} catch (e) {{
+        push({{ error: String(e) }});
+    }}
+}})()"#
+    )
+}
+
+/// Empties the queue; `null` before the observer ran in this document.
+fn drain_script(key: &str) -> String {
+    format!(
+        "(() => {{ const q = globalThis['{key}']; return q ? q.splice(0, q.length) : null; }})()"
+    )
+}
+
+/// A running [`ChaserPage::observe`]. Dropping it stops the polling; call
+/// [`stop`](Self::stop) to also deliver what is still queued and keep the
+/// observer out of later documents.
+#[derive(Debug)]
+pub struct Observer {
+    page: ChaserPage,
+    script: ScriptIdentifier,
+    stop: Option<oneshot::Sender<()>>,
+    task: JoinHandle<()>,
+}
+
+impl Observer {
+    /// Whether the polling task is still running.
+    pub fn is_running(&self) -> bool {
+        !self.task.is_finished()
+    }
+
+    /// Deliver the values still queued and stop observing. The observer
+    /// keeps running in the current document, with nobody reading it.
+    pub async fn stop(mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        let _ = (&mut self.task).await;
+        let _ = self
+            .page
+            .raw_page()
+            .execute(RemoveScriptToEvaluateOnNewDocumentParams::new(
+                self.script.clone(),
+            ))
+            .await;
+    }
+}
+
+impl Drop for Observer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Hand the drained `entries` to `handler`, logging errors the observer
+/// reported.
+fn deliver(entries: Value, handler: &mut impl FnMut(Value)) {
+    let Value::Array(entries) = entries else {
+        return;
+    };
+    for mut entry in entries {
+        if let Some(error) = entry.get("error") {
+            tracing::warn!("observer failed: {}", error.as_str().unwrap_or_default());
+        } else {
+            handler(entry["value"].take());
+        }
+    }
+}
+
+impl ChaserPage {
+    /// Run `observer` in the isolated world and call `handler` with every
+    /// value it emits. `observer` is the source of a function taking
+    /// `emit`; values go through JSON, and `undefined` arrives as `null`.
+    /// See the [module docs](crate::observe).
+    ///
+    /// Fails if `observer` throws in the current document. Errors in later
+    /// documents are logged.
+    pub async fn observe<F>(&self, observer: &str, mut handler: F) -> Result<Observer>
+    where
+        F: FnMut(Value) + Send + 'static,
+    {
+        let key = utils::random_identifier();
+        let source = observer_script(&key, observer);
+        let drain = drain_script(&key);
+        let script = self
+            .raw_page()
+            .execute(AddScriptToEvaluateOnNewDocumentParams {
+                source: source.clone(),
+                world_name: Some(self.world_name()),
+                include_command_line_api: None,
+                run_immediately: None,
+            })
+            .await?
+            .result
+            .identifier;
+
+        let started = async {
+            self.evaluate_stealth(&source).await?;
+            let entries = self.evaluate_stealth(&drain).await?.unwrap_or_default();
+            let failed = entries
+                .as_array()
+                .and_then(|entries| entries.iter().find_map(|entry| entry.get("error")));
+            if let Some(error) = failed {
+                return Err(ChaserError::msg(format!(
+                    "observer failed: {}",
+                    error.as_str().unwrap_or_default()
+                )));
+            }
+            Ok(entries)
+        };
+        let entries = match started.await {
+            Ok(entries) => entries,
+            Err(e) => {
+                let _ = self
+                    .raw_page()
+                    .execute(RemoveScriptToEvaluateOnNewDocumentParams::new(script))
+                    .await;
+                return Err(e);
+            }
+        };
+        deliver(entries, &mut handler);
+
+        let (stop, mut stopped) = oneshot::channel();
+        let page = self.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                let last = tokio::select! {
+                    _ = tokio::time::sleep(POLL_INTERVAL) => false,
+                    _ = &mut stopped => true,
+                };
+                // fails while a navigation commits
+                if let Ok(Some(entries)) = page.evaluate_stealth(&drain).await {
+                    deliver(entries, &mut handler);
+                }
+                if last {
+                    break;
+                }
+            }
+        });
+        Ok(Observer {
+            page: self.clone(),
+            script,
+            stop: Some(stop),
+            task,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn delivers_values_and_skips_errors() {
+        let mut seen = Vec::new();
+        deliver(
+            json!([{ "value": 1 }, { "error": "boom" }, { "value": null }, { "value": { "a": [2] } }]),
+            &mut |value| seen.push(value),
+        );
+        assert_eq!(seen, vec![json!(1), Value::Null, json!({ "a": [2] })]);
+
+        let script = observer_script("k1x", "emit => emit(1)");
+        assert!(script.contains("globalThis['k1x']"));
+        assert!(script.contains("(emit => emit(1))(emit)"));
+        assert_eq!(
+            drain_script("k1x"),
+            "(() => { const q = globalThis['k1x']; return q ? q.splice(0, q.length) : null; })()"
+        );
+    }
+}
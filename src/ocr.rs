@@ -0,0 +1,278 @@
+//! Locating visible text in screenshots when the DOM does not give it away.
+//!
+//! Text drawn into a canvas, behind a closed shadow root or split into
+//! decoy spans has no usable selector, but it is still on screen. With the
+//! `ocr` feature, [`ChaserPage::find_text_on_screen`] screenshots the
+//! viewport, hands it to an [`OcrBackend`] and returns where a phrase was
+//! read, in viewport coordinates; [`ChaserPage::click_text_on_screen`]
+//! clicks it.
+//!
+//! Backends are pluggable. [`TesseractCli`] runs the `tesseract` binary,
+//! which must be installed; bindings to a library or a pure-Rust engine only
+//! need to implement [`OcrBackend::recognize`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::ocr::TesseractCli;
+//!
+//! let ocr = TesseractCli::default().language("eng");
+//! if let Some(bounds) = chaser.find_text_on_screen(&ocr, "Accept all").await? {
+//!     println!("consent button at {bounds:?}");
+//! }
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::{ChaserError, ChaserResult as Result};
+use crate::layout::BoundingBox;
+use crate::page::ScreenshotParams;
+use chromiumoxide_cdp::cdp::browser_protocol::page::CaptureScreenshotFormat;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// A word an OCR engine read, in image pixels.
+#[derive(Debug, Clone)]
+pub struct OcrWord {
+    pub text: String,
+    pub bounds: BoundingBox,
+    /// 0 to 100.
+    pub confidence: f64,
+    /// Words with the same value are on the same line, in reading order.
+    pub line: u64,
+}
+
+/// An OCR engine.
+pub trait OcrBackend: Send + Sync {
+    /// The words in a PNG image, in reading order.
+    fn recognize(&self, png: &[u8]) -> Result<Vec<OcrWord>>;
+}
+
+/// Runs the `tesseract` command line tool.
+#[derive(Debug, Clone)]
+pub struct TesseractCli {
+    program: String,
+    language: Option<String>,
+    min_confidence: f64,
+}
+
+impl Default for TesseractCli {
+    fn default() -> Self {
+        Self {
+            program: "tesseract".to_string(),
+            language: None,
+            min_confidence: 40.0,
+        }
+    }
+}
+
+impl TesseractCli {
+    /// Run `program` instead of the `tesseract` on the `PATH`.
+    pub fn program(mut self, program: impl Into<String>) -> Self {
+        self.program = program.into();
+        self
+    }
+
+    /// Tesseract language codes, e.g. `eng+deu`.
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Drop words read with less confidence (0 to 100, default 40).
+    pub fn min_confidence(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+}
+
+impl OcrBackend for TesseractCli {
+    fn recognize(&self, png: &[u8]) -> Result<Vec<OcrWord>> {
+        let mut command = Command::new(&self.program);
+        command.args(["stdin", "stdout"]);
+        if let Some(language) = &self.language {
+            command.args(["-l", language]);
+        }
+        let mut child = command
+            .arg("tsv")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| ChaserError::msg(format!("cannot run {}: {e}", self.program)))?;
+        let written = child.stdin.take().map(|mut stdin| stdin.write_all(png));
+        let output = child
+            .wait_with_output()
+            .map_err(|e| ChaserError::msg(e.to_string()))?;
+        if let Some(Err(e)) = written {
+            return Err(ChaserError::msg(format!(
+                "{} closed its input: {e}",
+                self.program
+            )));
+        }
+        if !output.status.success() {
+            return Err(ChaserError::msg(format!(
+                "{} failed with {}",
+                self.program, output.status
+            )));
+        }
+        Ok(parse_tsv(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .filter(|word| word.confidence >= self.min_confidence)
+            .collect())
+    }
+}
+
+/// Words from Tesseract's TSV output.
+fn parse_tsv(tsv: &str) -> Vec<OcrWord> {
+    tsv.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.splitn(12, '\t').collect();
+            let [level, _page, block, paragraph, line, _word, left, top, width, height, confidence, text] =
+                fields[..]
+            else {
+                return None;
+            };
+            if level != "5" || text.trim().is_empty() {
+                return None;
+            }
+            let number = |s: &str| s.trim().parse::<f64>().ok();
+            let id = |s: &str| s.trim().parse::<u64>().ok();
+            Some(OcrWord {
+                text: text.trim().to_string(),
+                bounds: BoundingBox {
+                    x: number(left)?,
+                    y: number(top)?,
+                    width: number(width)?,
+                    height: number(height)?,
+                },
+                confidence: number(confidence)?,
+                line: (id(block)? << 32) | (id(paragraph)? << 16) | id(line)?,
+            })
+        })
+        .collect()
+}
+
+/// Lowercase alphanumerics only, so `"Accept,"` reads as `"accept"`.
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The bounds of the first run of consecutive words on one line that reads
+/// `phrase`, ignoring case and punctuation.
+fn find_phrase(words: &[OcrWord], phrase: &str) -> Option<BoundingBox> {
+    let wanted: Vec<String> = phrase
+        .split_whitespace()
+        .map(normalize)
+        .filter(|w| !w.is_empty())
+        .collect();
+    if wanted.is_empty() {
+        return None;
+    }
+    let run = words.windows(wanted.len()).find(|run| {
+        run.iter().all(|w| w.line == run[0].line)
+            && run
+                .iter()
+                .zip(&wanted)
+                .all(|(w, want)| normalize(&w.text) == *want)
+    })?;
+    let left = run.iter().map(|w| w.bounds.x).fold(f64::INFINITY, f64::min);
+    let top = run.iter().map(|w| w.bounds.y).fold(f64::INFINITY, f64::min);
+    let right = run
+        .iter()
+        .map(|w| w.bounds.x + w.bounds.width)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let bottom = run
+        .iter()
+        .map(|w| w.bounds.y + w.bounds.height)
+        .fold(f64::NEG_INFINITY, f64::max);
+    Some(BoundingBox {
+        x: left,
+        y: top,
+        width: right - left,
+        height: bottom - top,
+    })
+}
+
+/// Width of a PNG from its header.
+fn png_width(png: &[u8]) -> Option<u32> {
+    let bytes = png.get(16..20)?;
+    Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+impl ChaserPage {
+    /// Where `text` is visible in the viewport, read by `ocr` from a
+    /// screenshot, in viewport CSS pixels.
+    pub async fn find_text_on_screen(
+        &self,
+        ocr: &dyn OcrBackend,
+        text: &str,
+    ) -> Result<Option<BoundingBox>> {
+        let state = self.viewport_state().await?;
+        let png = self
+            .raw_page()
+            .screenshot(
+                ScreenshotParams::builder()
+                    .format(CaptureScreenshotFormat::Png)
+                    .build(),
+            )
+            .await?;
+        let ratio = png_width(&png)
+            .map(|width| width as f64 / state.width.max(1.0))
+            .unwrap_or(state.device_pixel_ratio);
+        let words = ocr.recognize(&png)?;
+        Ok(find_phrase(&words, text).map(|b| BoundingBox {
+            x: b.x / ratio,
+            y: b.y / ratio,
+            width: b.width / ratio,
+            height: b.height / ratio,
+        }))
+    }
+
+    /// Find `text` like [`find_text_on_screen`](Self::find_text_on_screen)
+    /// and click it with humanized movement.
+    pub async fn click_text_on_screen(
+        &self,
+        ocr: &dyn OcrBackend,
+        text: &str,
+    ) -> Result<BoundingBox> {
+        let bounds = self
+            .find_text_on_screen(ocr, text)
+            .await?
+            .ok_or_else(|| ChaserError::msg(format!("{text:?} is not on screen")))?;
+        let (dx, dy) = {
+            use rand::Rng;
+            let mut rng = rand::thread_rng();
+            (rng.gen_range(0.3..0.7), rng.gen_range(0.35..0.65))
+        };
+        self.click_human(bounds.x + bounds.width * dx, bounds.y + bounds.height * dy)
+            .await?;
+        Ok(bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_phrases_in_tesseract_output() {
+        let tsv = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext\n\
+                   4\t1\t1\t1\t1\t0\t10\t10\t300\t20\t-1\t\n\
+                   5\t1\t1\t1\t1\t1\t10\t10\t60\t20\t95.1\tAccept\n\
+                   5\t1\t1\t1\t1\t2\t76\t12\t30\t18\t93.0\tall,\n\
+                   5\t1\t1\t1\t2\t1\t10\t40\t50\t20\t90.0\tcookies\n";
+        let words = parse_tsv(tsv);
+        assert_eq!(words.len(), 3);
+        let bounds = find_phrase(&words, "accept ALL").unwrap();
+        assert_eq!(
+            (bounds.x, bounds.y, bounds.width, bounds.height),
+            (10.0, 10.0, 96.0, 20.0)
+        );
+        // not on one line
+        assert!(find_phrase(&words, "all cookies").is_none());
+    }
+}
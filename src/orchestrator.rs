@@ -0,0 +1,572 @@
+//! Run many independent flows concurrently across a [`BrowserPool`].
+//!
+//! A flow is an async closure receiving a fully profiled [`ChaserPage`]. The
+//! orchestrator leases a page per task (honouring per-task profile and proxy
+//! assignments), bounds the number of tasks in flight, reports progress as
+//! tasks finish and returns every outcome together with an error summary.
+//! With an [`IdentityRotation`](crate::rotation) configured on the pool,
+//! blocks retire proxies and profiles and cool domains down for later tasks.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::orchestrator::{self, Task};
+//! use chaser_oxide::pool::PoolConfig;
+//!
+//! let tasks = ["https://example.com", "https://example.org"]
+//!     .into_iter()
+//!     .map(|url| {
+//!         Task::new(url, move |page| async move {
+//!             page.goto(url).await?;
+//!             page.content().await
+//!         })
+//!     })
+//!     .collect();
+//!
+//! let summary = orchestrator::run(tasks, PoolConfig::builder().browsers(2).build()).await?;
+//! println!("{} ok, {} failed", summary.success_count(), summary.failure_count());
+//! ```
+
+use crate::cancel::CancellationToken;
+use crate::chaser::ChaserPage;
+use crate::error::{ChaserError, ChaserResult as Result};
+use crate::pool::{BrowserPool, PoolConfig, TaskAssignment};
+use crate::profiles::ChaserProfile;
+use futures::future::LocalBoxFuture;
+use futures::StreamExt;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+type Flow<T> = Box<dyn FnOnce(ChaserPage) -> LocalBoxFuture<'static, Result<T>>>;
+
+/// A named unit of work executed on a leased page.
+pub struct Task<T> {
+    name: String,
+    pub(crate) assignment: TaskAssignment,
+    domain: Option<String>,
+    flow: Flow<T>,
+}
+
+impl<T> Task<T> {
+    /// Create a task running `flow` on a page from the pool's rotation.
+    pub fn new<F, Fut>(name: impl Into<String>, flow: F) -> Self
+    where
+        F: FnOnce(ChaserPage) -> Fut + 'static,
+        Fut: Future<Output = Result<T>> + 'static,
+    {
+        Self {
+            name: name.into(),
+            assignment: TaskAssignment::default(),
+            domain: None,
+            flow: Box::new(move |page| Box::pin(flow(page))),
+        }
+    }
+
+    /// Run this task with a specific profile.
+    pub fn profile(mut self, profile: ChaserProfile) -> Self {
+        self.assignment.profile = Some(profile);
+        self
+    }
+
+    /// Route this task through a specific proxy.
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.assignment.proxy = Some(proxy.into());
+        self
+    }
+
+    /// The site this task works on. While the pool's
+    /// [`IdentityRotation`](crate::rotation::IdentityRotation) has the domain
+    /// (or a parent domain) cooling down, the task waits before leasing a
+    /// page.
+    pub fn domain(mut self, domain: impl Into<String>) -> Self {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    /// The task name used in outcomes and progress reports.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<T> fmt::Debug for Task<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Task")
+            .field("name", &self.name)
+            .field("assignment", &self.assignment)
+            .field("domain", &self.domain)
+            .finish()
+    }
+}
+
+/// Snapshot of a run's progress, reported every time a task finishes.
+#[derive(Debug, Clone, Default)]
+pub struct Progress {
+    pub total: usize,
+    pub completed: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Name of the task that just finished.
+    pub last_task: String,
+}
+
+/// Result of a single task.
+#[derive(Debug)]
+pub struct TaskOutcome<T> {
+    pub name: String,
+    /// Proxy the task was routed through, if any.
+    pub proxy: Option<String>,
+    /// Short description of the profile the task ran with.
+    pub profile: Option<String>,
+    pub result: Result<T>,
+    pub elapsed: Duration,
+}
+
+impl<T> TaskOutcome<T> {
+    pub fn is_ok(&self) -> bool {
+        self.result.is_ok()
+    }
+}
+
+/// All outcomes of a run, in task submission order.
+#[derive(Debug)]
+pub struct RunSummary<T> {
+    pub outcomes: Vec<TaskOutcome<T>>,
+    pub elapsed: Duration,
+}
+
+impl<T> RunSummary<T> {
+    pub fn success_count(&self) -> usize {
+        self.outcomes.iter().filter(|o| o.is_ok()).count()
+    }
+
+    pub fn failure_count(&self) -> usize {
+        self.outcomes.len() - self.success_count()
+    }
+
+    /// Iterate over the values of successful tasks.
+    pub fn successes(&self) -> impl Iterator<Item = (&str, &T)> + '_ {
+        self.outcomes
+            .iter()
+            .filter_map(|o| o.result.as_ref().ok().map(|v| (o.name.as_str(), v)))
+    }
+
+    /// Iterate over the errors of failed tasks.
+    pub fn failures(&self) -> impl Iterator<Item = (&str, &ChaserError)> + '_ {
+        self.outcomes
+            .iter()
+            .filter_map(|o| o.result.as_ref().err().map(|e| (o.name.as_str(), e)))
+    }
+
+    /// Failure counts grouped by error message, most frequent first.
+    pub fn error_summary(&self) -> Vec<(String, usize)> {
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for (_, err) in self.failures() {
+            *counts.entry(err.to_string()).or_default() += 1;
+        }
+        let mut summary: Vec<_> = counts.into_iter().collect();
+        summary.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        summary
+    }
+}
+
+/// Launch a pool, run all tasks on it and close it again.
+pub async fn run<T: 'static>(tasks: Vec<Task<T>>, config: PoolConfig) -> Result<RunSummary<T>> {
+    run_with_progress(tasks, config, |_| {}).await
+}
+
+/// Like [`run`], calling `on_progress` every time a task finishes.
+pub async fn run_with_progress<T, P>(
+    tasks: Vec<Task<T>>,
+    config: PoolConfig,
+    on_progress: P,
+) -> Result<RunSummary<T>>
+where
+    T: 'static,
+    P: FnMut(&Progress),
+{
+    let pool = BrowserPool::launch(config).await?;
+    let summary = run_on_pool(&pool, tasks, on_progress).await;
+    pool.close().await?;
+    Ok(summary)
+}
+
+/// Run all tasks on an already launched pool.
+///
+/// At most `pool.config().concurrency()` tasks run at the same time. A task
+/// failing (including failing to get a page) never aborts the others.
+pub async fn run_on_pool<T, P>(
+    pool: &BrowserPool,
+    tasks: Vec<Task<T>>,
+    on_progress: P,
+) -> RunSummary<T>
+where
+    T: 'static,
+    P: FnMut(&Progress),
+{
+    run_on_pool_cancellable(pool, tasks, &CancellationToken::new(), on_progress).await
+}
+
+/// Like [`run_on_pool`], shutting down gracefully once `cancel` fires.
+///
+/// Running flows see their humanized input fail with
+/// [`ChaserError::Cancelled`] after held buttons and keys are released;
+/// tasks that have not started yet fail with the same error without
+/// leasing a page.
+pub async fn run_on_pool_cancellable<T, P>(
+    pool: &BrowserPool,
+    tasks: Vec<Task<T>>,
+    cancel: &CancellationToken,
+    mut on_progress: P,
+) -> RunSummary<T>
+where
+    T: 'static,
+    P: FnMut(&Progress),
+{
+    let started = Instant::now();
+    let mut progress = Progress {
+        total: tasks.len(),
+        ..Default::default()
+    };
+
+    let mut stream = futures::stream::iter(tasks.into_iter().enumerate())
+        .map(|(idx, task)| async move { (idx, run_task(pool, task, Some(cancel)).await) })
+        .buffer_unordered(pool.config().concurrency());
+
+    let mut outcomes = Vec::with_capacity(progress.total);
+    while let Some((idx, outcome)) = stream.next().await {
+        progress.completed += 1;
+        if outcome.is_ok() {
+            progress.succeeded += 1;
+        } else {
+            progress.failed += 1;
+        }
+        progress.last_task = outcome.name.clone();
+        on_progress(&progress);
+        outcomes.push((idx, outcome));
+    }
+    outcomes.sort_by_key(|(idx, _)| *idx);
+
+    RunSummary {
+        outcomes: outcomes.into_iter().map(|(_, o)| o).collect(),
+        elapsed: started.elapsed(),
+    }
+}
+
+pub(crate) async fn run_task<T>(
+    pool: &BrowserPool,
+    task: Task<T>,
+    cancel: Option<&CancellationToken>,
+) -> TaskOutcome<T> {
+    let outcome = lease_and_run(pool, task, cancel).await;
+    if let Some(metrics) = pool.config().metrics() {
+        metrics.record_task(outcome.is_ok(), outcome.elapsed);
+    }
+    outcome
+}
+
+async fn lease_and_run<T>(
+    pool: &BrowserPool,
+    task: Task<T>,
+    cancel: Option<&CancellationToken>,
+) -> TaskOutcome<T> {
+    let started = Instant::now();
+    let Task {
+        name,
+        assignment,
+        domain,
+        flow,
+    } = task;
+
+    let failed = |name, assignment: TaskAssignment, error| TaskOutcome {
+        name,
+        proxy: assignment.proxy,
+        profile: assignment.profile.map(|p| p.to_string()),
+        result: Err(error),
+        elapsed: started.elapsed(),
+    };
+    if cancel.is_some_and(CancellationToken::is_cancelled) {
+        return failed(name, assignment, ChaserError::Cancelled);
+    }
+
+    let rotation = pool.config().rotation();
+    let mut profile_slot = None;
+    let assignment = match rotation {
+        Some(rotation) => {
+            if let Some(domain) = &domain {
+                if let Err(e) = rotation.wait_cooldown(domain, cancel).await {
+                    return failed(name, assignment, e);
+                }
+            }
+            let (assignment, slot) = rotation.assign(pool.config(), assignment);
+            profile_slot = slot;
+            assignment
+        }
+        None => assignment,
+    };
+
+    // the lease is handed back however the flow ends, also when it panics
+    // or this future is dropped
+    let mut leased = None;
+    let run = pool
+        .with_lease(assignment.clone(), |lease| {
+            let page = lease.page().clone();
+            page.set_cancellation_token(cancel.cloned());
+            if rotation.is_some() {
+                *page.verdict_log().lock().unwrap() = Some(Vec::new());
+            }
+            leased = Some((
+                lease.proxy().map(str::to_string),
+                lease.profile().to_string(),
+            ));
+            async move {
+                let result = flow(page.clone()).await;
+                let verdicts = page.verdict_log().lock().unwrap().take();
+                (result, verdicts.unwrap_or_default())
+            }
+        })
+        .await;
+    let ((result, verdicts), released) = match run {
+        Ok(run) => run,
+        Err(e) => {
+            if let Some(rotation) = rotation {
+                rotation.record_task(
+                    &name,
+                    domain.as_deref(),
+                    assignment.proxy.as_deref(),
+                    profile_slot,
+                    &[],
+                    Some(&e),
+                );
+            }
+            return failed(name, assignment, e);
+        }
+    };
+    if let Err(e) = released {
+        tracing::warn!("failed to release page of task {}: {}", name, e);
+    }
+    let (proxy, profile) = leased.expect("a page was leased");
+    if let Some(rotation) = rotation {
+        rotation.record_task(
+            &name,
+            domain.as_deref(),
+            proxy.as_deref(),
+            profile_slot,
+            &verdicts,
+            result.as_ref().err(),
+        );
+    }
+
+    TaskOutcome {
+        name,
+        proxy,
+        profile: Some(profile),
+        result,
+        elapsed: started.elapsed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::browser::Browser;
+    use crate::transport::MockTransport;
+
+    fn pool(mocks: &[MockTransport], concurrency: usize) -> BrowserPool {
+        let browsers = mocks.iter().cloned().map(Browser::with_transport).collect();
+        let config = PoolConfig::builder()
+            .browsers(mocks.len())
+            .concurrency(concurrency)
+            .proxy("http://10.0.0.1:8080")
+            .build();
+        BrowserPool::from_browsers(browsers, config)
+    }
+
+    /// A task finishing after `millis` with `result`.
+    fn task(name: &str, millis: u64, result: std::result::Result<u32, &'static str>) -> Task<u32> {
+        Task::new(name, move |_page| async move {
+            tokio::time::sleep(Duration::from_millis(millis)).await;
+            result.map_err(ChaserError::msg)
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reports_every_outcome_in_submission_order() {
+        let mocks = [MockTransport::new(), MockTransport::new()];
+        let pool = pool(&mocks, 2);
+        let tasks = vec![
+            task("slow", 300, Ok(1)),
+            task("blocked", 100, Err("403 Forbidden")),
+            task("captcha", 50, Err("403 Forbidden")),
+            task("direct", 10, Ok(4)).proxy("socks5://10.0.0.9:1080"),
+        ];
+
+        let mut reports = Vec::new();
+        let summary = run_on_pool(&pool, tasks, |progress| reports.push(progress.clone())).await;
+
+        let names: Vec<_> = summary.outcomes.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, ["slow", "blocked", "captcha", "direct"]);
+        assert_eq!((summary.success_count(), summary.failure_count()), (2, 2));
+        assert_eq!(
+            summary.successes().collect::<Vec<_>>(),
+            [("slow", &1), ("direct", &4)]
+        );
+        assert_eq!(summary.error_summary(), [("403 Forbidden".to_string(), 2)]);
+        assert_eq!(
+            summary.outcomes[0].proxy.as_deref(),
+            Some("http://10.0.0.1:8080")
+        );
+        assert_eq!(
+            summary.outcomes[3].proxy.as_deref(),
+            Some("socks5://10.0.0.9:1080")
+        );
+
+        // progress follows completion, not submission
+        let finished: Vec<_> = reports.iter().map(|p| p.last_task.as_str()).collect();
+        assert_eq!(finished, ["blocked", "captcha", "direct", "slow"]);
+        let last = reports.last().unwrap();
+        assert_eq!(
+            (last.total, last.completed, last.succeeded, last.failed),
+            (4, 4, 2, 2)
+        );
+
+        // every lease was handed back
+        let disposed: usize = mocks
+            .iter()
+            .map(|mock| mock.commands_to("Target.disposeBrowserContext").len())
+            .sum();
+        assert_eq!(disposed, 4);
+        pool.close().await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn flows_drive_humanized_input() {
+        /// Flows need not be `Send`, but the crate's input should stay
+        /// usable from spawned tasks too.
+        fn send<F: Future + Send>(future: F) -> F {
+            future
+        }
+
+        let mock = MockTransport::new();
+        mock.respond(
+            "Page.getLayoutMetrics",
+            serde_json::json!({
+                "layoutViewport": { "pageX": 0, "pageY": 0, "clientWidth": 1280, "clientHeight": 800 },
+                "visualViewport": {
+                    "offsetX": 0, "offsetY": 0, "pageX": 0, "pageY": 0,
+                    "clientWidth": 1280, "clientHeight": 800, "scale": 1, "zoom": 1
+                },
+                "contentSize": { "x": 0, "y": 0, "width": 1280, "height": 800 }
+            }),
+        );
+        let pool = pool(std::slice::from_ref(&mock), 1);
+        let click = Task::new("click", |page| async move {
+            send(page.click_human(400.0, 300.0)).await?;
+            Ok(())
+        });
+
+        let summary = run_on_pool(&pool, vec![click], |_| {}).await;
+        assert!(summary.outcomes[0].result.is_ok());
+        let presses: Vec<_> = mock
+            .commands_to("Input.dispatchMouseEvent")
+            .into_iter()
+            .filter(|event| event["type"] == "mousePressed")
+            .collect();
+        assert_eq!(presses.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn a_failed_lease_fails_only_its_task() {
+        let mock = MockTransport::new();
+        let mut contexts = 0;
+        mock.respond_with("Target.createBrowserContext", move |_| {
+            contexts += 1;
+            if contexts == 1 {
+                Err(chromiumoxide_types::Error {
+                    code: -32000,
+                    message: "Failed to create context".into(),
+                })
+            } else {
+                Ok(serde_json::json!({ "browserContextId": "MOCK_CONTEXT" }))
+            }
+        });
+        let pool = pool(&[mock], 1);
+
+        let summary = run_on_pool(
+            &pool,
+            vec![task("a", 0, Ok(1)), task("b", 0, Ok(2))],
+            |_| {},
+        )
+        .await;
+        assert_eq!(
+            summary.outcomes[0].result.as_ref().unwrap_err().to_string(),
+            "Error -32000: Failed to create context"
+        );
+        assert_eq!(summary.outcomes[1].result.as_ref().unwrap(), &2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn pages_come_back_from_panicking_and_dropped_tasks() {
+        use futures::FutureExt;
+        use std::panic::AssertUnwindSafe;
+
+        let mock = MockTransport::new();
+        let pool = pool(std::slice::from_ref(&mock), 1);
+        let panicking = Task::new("panics", |_page| async move {
+            if true {
+                panic!("flow bug");
+            }
+            Ok(0)
+        });
+        let run = run_on_pool(&pool, vec![panicking], |_| {});
+        assert!(AssertUnwindSafe(run).catch_unwind().await.is_err());
+        assert_eq!(pool.leased(), 0);
+        assert_eq!(mock.commands_to("Target.disposeBrowserContext").len(), 1);
+
+        // a run dropped halfway hands its page back in the background
+        let run = run_on_pool(&pool, vec![task("hangs", 60_000, Ok(1))], |_| {});
+        assert!(tokio::time::timeout(Duration::from_secs(1), run)
+            .await
+            .is_err());
+        while pool.leased() > 0 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(mock.commands_to("Target.disposeBrowserContext").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn failures_keep_their_classification() {
+        let pool = pool(&[MockTransport::new()], 1);
+        let blocked = Task::new("blocked", |_page| async move {
+            Err::<u32, _>(ChaserError::BlockedByAntiBot {
+                vendor: "DataDome".into(),
+            })
+        });
+        let summary = run_on_pool(&pool, vec![blocked, task("fine", 0, Ok(1))], |_| {}).await;
+        let (_, error) = summary.failures().next().unwrap();
+        assert!(error.needs_rotation() && !error.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn cancelled_tasks_fail_without_leasing_a_page() {
+        let mock = MockTransport::new();
+        let pool = pool(std::slice::from_ref(&mock), 2);
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let summary = run_on_pool_cancellable(
+            &pool,
+            vec![task("a", 0, Ok(1)), task("b", 0, Ok(2))],
+            &cancel,
+            |_| {},
+        )
+        .await;
+        assert_eq!(summary.failure_count(), 2);
+        assert!(summary
+            .failures()
+            .all(|(_, e)| matches!(e, ChaserError::Cancelled)));
+        assert!(mock.commands_to("Target.createBrowserContext").is_empty());
+    }
+}
@@ -1,8 +1,7 @@
 use std::path::Path;
 use std::sync::Arc;
 
-use futures::channel::mpsc::unbounded;
-use futures::channel::oneshot::channel as oneshot_channel;
+use futures::channel::oneshot::{channel as oneshot_channel, Receiver as OneshotReceiver};
 use futures::{stream, SinkExt, StreamExt};
 
 use chromiumoxide_cdp::cdp::browser_protocol::dom::*;
@@ -36,7 +35,7 @@ use crate::handler::target::{GetName, GetParent, GetUrl, TargetMessage};
 use crate::handler::PageInner;
 use crate::js::{Evaluation, EvaluationResult};
 use crate::layout::Point;
-use crate::listeners::{EventListenerRequest, EventStream};
+use crate::listeners::{subscription, EventStream};
 use crate::{utils, ArcHttpRequest};
 
 #[derive(Debug, Clone)]
@@ -62,6 +61,25 @@ impl Page {
         self.command_future(cmd)?.await
     }
 
+    /// Execute a command in another session than this page's, e.g. that
+    /// of an out-of-process iframe, or the browser's with `None`.
+    pub(crate) async fn execute_in_session<T: Command>(
+        &self,
+        cmd: T,
+        session: Option<SessionId>,
+    ) -> Result<CommandResponse<T::Response>> {
+        self.inner.execute_in_session(cmd, session).await
+    }
+
+    /// Send a command without waiting for its response, see
+    /// [`crate::input_pipeline`].
+    pub(crate) async fn enqueue<T: Command>(
+        &self,
+        cmd: T,
+    ) -> Result<OneshotReceiver<Result<Response>>> {
+        self.inner.enqueue(cmd).await
+    }
+
     /// Execute a command and return the `Command::Response`
     pub fn command_future<T: Command>(&self, cmd: T) -> Result<CommandFuture<T>> {
         self.inner.command_future(cmd)
@@ -95,8 +113,8 @@ impl Page {
     ///
     /// # Example Listen for canceled animations
     /// ```no_run
-    /// # use chromiumoxide::page::Page;
-    /// # use chromiumoxide::error::Result;
+    /// # use chaser_oxide::page::Page;
+    /// # use chaser_oxide::error::Result;
     /// # use chromiumoxide_cdp::cdp::browser_protocol::animation::EventAnimationCanceled;
     /// # use futures::StreamExt;
     /// # async fn demo(page: Page) -> Result<()> {
@@ -111,12 +129,12 @@ impl Page {
     /// # Example Liste for a custom event
     ///
     /// ```no_run
-    /// # use chromiumoxide::page::Page;
-    /// # use chromiumoxide::error::Result;
+    /// # use chaser_oxide::page::Page;
+    /// # use chaser_oxide::error::Result;
     /// # use futures::StreamExt;
     /// # use serde::Deserialize;
-    /// # use chromiumoxide::types::{MethodId, MethodType};
-    /// # use chromiumoxide::cdp::CustomEvent;
+    /// # use chaser_oxide::types::{MethodId, MethodType};
+    /// # use chaser_oxide::cdp::CustomEvent;
     /// # async fn demo(page: Page) -> Result<()> {
     ///     #[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
     ///     struct MyCustomEvent {
@@ -137,16 +155,14 @@ impl Page {
     /// # }
     /// ```
     pub async fn event_listener<T: IntoEventKind>(&self) -> Result<EventStream<T>> {
-        let (tx, rx) = unbounded();
+        let (request, stream) = subscription::<T>();
         self.inner
             .sender()
             .clone()
-            .send(TargetMessage::AddEventListener(
-                EventListenerRequest::new::<T>(tx),
-            ))
+            .send(TargetMessage::AddEventListener(request))
             .await?;
 
-        Ok(EventStream::new(rx))
+        Ok(stream)
     }
 
     pub async fn expose_function(
@@ -350,6 +366,11 @@ impl Page {
         Element::from_nodes(&self.inner, &node_ids).await
     }
 
+    /// The `Element`s for `node_ids`, e.g. nodes pushed by `DOM.requestNode`.
+    pub(crate) async fn elements_from_nodes(&self, node_ids: &[NodeId]) -> Result<Vec<Element>> {
+        Element::from_nodes(&self.inner, node_ids).await
+    }
+
     /// Describes node given its id
     pub async fn describe_node(&self, node_id: NodeId) -> Result<Node> {
         let resp = self
@@ -387,9 +408,9 @@ impl Page {
     /// Trigger a navigation and wait until the triggered navigation is finished
     ///
     /// ```no_run
-    /// # use chromiumoxide::page::Page;
-    /// # use chromiumoxide::error::Result;
-    /// # use chromiumoxide::layout::Point;
+    /// # use chaser_oxide::page::Page;
+    /// # use chaser_oxide::error::Result;
+    /// # use chaser_oxide::layout::Point;
     /// # async fn demo(page: Page, point: Point) -> Result<()> {
     ///     let html = page.click(point).await?.wait_for_navigation().await?.content();
     ///     # Ok(())
@@ -401,9 +422,9 @@ impl Page {
     /// Perform custom click
     ///
     /// ```no_run
-    /// # use chromiumoxide::page::Page;
-    /// # use chromiumoxide::error::Result;
-    /// # use chromiumoxide::layout::Point;
+    /// # use chaser_oxide::page::Page;
+    /// # use chaser_oxide::error::Result;
+    /// # use chaser_oxide::layout::Point;
     /// # use chromiumoxide_cdp::cdp::browser_protocol::input::{DispatchMouseEventParams, MouseButton, DispatchMouseEventType};
     /// # async fn demo(page: Page, point: Point) -> Result<()> {
     ///      // double click
@@ -454,8 +475,8 @@ impl Page {
     /// # Example save a png file of a website
     ///
     /// ```no_run
-    /// # use chromiumoxide::page::{Page, ScreenshotParams};
-    /// # use chromiumoxide::error::Result;
+    /// # use chaser_oxide::page::{Page, ScreenshotParams};
+    /// # use chaser_oxide::error::Result;
     /// # use chromiumoxide_cdp::cdp::browser_protocol::page::CaptureScreenshotFormat;
     /// # async fn demo(page: Page) -> Result<()> {
     ///         page.goto("http://example.com")
@@ -565,8 +586,8 @@ impl Page {
     ///
     /// To reload ignoring cache run:
     /// ```no_run
-    /// # use chromiumoxide::page::Page;
-    /// # use chromiumoxide::error::Result;
+    /// # use chaser_oxide::page::Page;
+    /// # use chaser_oxide::error::Result;
     /// # use chromiumoxide_cdp::cdp::browser_protocol::page::ReloadParams;
     /// # async fn demo(page: Page) -> Result<()> {
     ///     page.execute(ReloadParams::builder().ignore_cache(true).build()).await?;
@@ -680,8 +701,8 @@ impl Page {
     ///
     /// # Example
     /// ```no_run
-    /// # use chromiumoxide::page::Page;
-    /// # use chromiumoxide::error::Result;
+    /// # use chaser_oxide::page::Page;
+    /// # use chaser_oxide::error::Result;
     /// # use chromiumoxide_cdp::cdp::browser_protocol::network::CookieParam;
     /// # async fn demo(page: Page) -> Result<()> {
     ///     page.set_cookie(CookieParam::new("Cookie-name", "Cookie-value")).await?;
@@ -828,8 +849,8 @@ impl Page {
     /// This will take the arguments `(1,2)` and will call the function
     ///
     /// ```no_run
-    /// # use chromiumoxide::page::Page;
-    /// # use chromiumoxide::error::Result;
+    /// # use chaser_oxide::page::Page;
+    /// # use chaser_oxide::error::Result;
     /// # async fn demo(page: Page) -> Result<()> {
     ///     let sum: usize = page
     ///         .evaluate_expression("((a,b) => {return a + b;})(1,2)")
@@ -873,8 +894,8 @@ impl Page {
     /// option
     ///
     /// ```no_run
-    /// # use chromiumoxide::page::Page;
-    /// # use chromiumoxide::error::Result;
+    /// # use chaser_oxide::page::Page;
+    /// # use chaser_oxide::error::Result;
     /// # use chromiumoxide_cdp::cdp::js_protocol::runtime::{EvaluateParams, RemoteObjectType};
     /// # async fn demo(page: Page) -> Result<()> {
     ///     let eval = EvaluateParams::builder().expression("() => {return 42;}");
@@ -898,8 +919,8 @@ impl Page {
     ///
     /// # Example Evaluate basic expression
     /// ```no_run
-    /// # use chromiumoxide::page::Page;
-    /// # use chromiumoxide::error::Result;
+    /// # use chaser_oxide::page::Page;
+    /// # use chaser_oxide::error::Result;
     /// # async fn demo(page: Page) -> Result<()> {
     ///     let sum:usize = page.evaluate("1 + 2").await?.into_value()?;
     ///     assert_eq!(sum, 3);
@@ -938,8 +959,8 @@ impl Page {
     /// # Example Evaluate a promise
     /// This will wait until the promise resolves and then returns the result.
     /// ```no_run
-    /// # use chromiumoxide::page::Page;
-    /// # use chromiumoxide::error::Result;
+    /// # use chaser_oxide::page::Page;
+    /// # use chaser_oxide::error::Result;
     /// # async fn demo(page: Page) -> Result<()> {
     ///     let sum:usize = page.evaluate_function("() => Promise.resolve(1 + 2)").await?.into_value()?;
     ///     assert_eq!(sum, 3);
@@ -949,8 +970,8 @@ impl Page {
     ///
     /// # Example Evaluate an async function
     /// ```no_run
-    /// # use chromiumoxide::page::Page;
-    /// # use chromiumoxide::error::Result;
+    /// # use chaser_oxide::page::Page;
+    /// # use chaser_oxide::error::Result;
     /// # async fn demo(page: Page) -> Result<()> {
     ///     let val:usize = page.evaluate_function("async function() {return 42;}").await?.into_value()?;
     ///     assert_eq!(val, 42);
@@ -960,8 +981,8 @@ impl Page {
     /// # Example Construct a function call
     ///
     /// ```no_run
-    /// # use chromiumoxide::page::Page;
-    /// # use chromiumoxide::error::Result;
+    /// # use chaser_oxide::page::Page;
+    /// # use chaser_oxide::error::Result;
     /// # use chromiumoxide_cdp::cdp::js_protocol::runtime::{CallFunctionOnParams, CallArgument};
     /// # async fn demo(page: Page) -> Result<()> {
     ///     let call = CallFunctionOnParams::builder()
@@ -1031,7 +1052,7 @@ impl Page {
     ///
     /// # Example
     /// ```
-    /// # use chromiumoxide::page::Page;
+    /// # use chaser_oxide::page::Page;
     /// # async fn example(page: Page) -> Result<(), Box<dyn std::error::Error>> {
     /// // Hide webdriver property for stealth scraping
     /// page.evaluate_on_new_document(r#"
@@ -1062,8 +1083,8 @@ impl Page {
     ///
     /// # Example
     /// ```no_run
-    /// # use chromiumoxide::page::Page;
-    /// # use chromiumoxide::error::Result;
+    /// # use chaser_oxide::page::Page;
+    /// # use chaser_oxide::error::Result;
     /// # async fn demo(page: Page) -> Result<()> {
     ///     page.set_content("<body>
     ///  <h1>This was set via chromiumoxide</h1>
@@ -0,0 +1,173 @@
+//! Keeping the pages of a long-lived context within bounds.
+//!
+//! Every page is a renderer with its own DOM, JavaScript heap and caches. A
+//! context that serves tasks for hours piles them up: pages a task forgot to
+//! close, tabs opened by `target=_blank` links, heaps grown by infinite
+//! scrolling. A [`PageBudget`] set with
+//! [`ChaserContext::page_budget`](crate::context::ChaserContext::page_budget)
+//! caps them:
+//!
+//! - opening a page beyond `max_pages` first closes the least recently
+//!   active idle page, or fails if every page is busy;
+//! - [`ChaserContext::hygiene`](crate::context::ChaserContext::hygiene)
+//!   closes pages idle for too long, closes idle pages while their heaps
+//!   add up to more than `max_rss_hint`, and clears the cache and cookies
+//!   of the context if the [`ClearPolicy`] asks for it.
+//!
+//! A page is active while a navigation, script or humanized input of its
+//! [`ChaserPage`](crate::ChaserPage) runs, see
+//! [`idle_for`](crate::ChaserPage::idle_for). Commands sent through
+//! `raw_page()` do not count.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::context::ChaserContext;
+//! use chaser_oxide::page_budget::{ClearPolicy, PageBudget};
+//! use std::time::Duration;
+//!
+//! let context = ChaserContext::create(browser, profile, None)
+//!     .await?
+//!     .with_page_budget(
+//!         PageBudget::new(4, Some(512 << 20))
+//!             .close_idle_after(Duration::from_secs(600))
+//!             .clear(ClearPolicy { cache: true, cookies: false }),
+//!     );
+//! let _hygiene = context.spawn_hygiene(Duration::from_secs(60));
+//! ```
+
+use std::time::Duration;
+
+/// Limits on the pages of a context.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageBudget {
+    max_pages: usize,
+    max_rss_hint: Option<u64>,
+    idle_after: Duration,
+    close_idle_after: Option<Duration>,
+    clear: ClearPolicy,
+}
+
+impl PageBudget {
+    /// At most `max_pages` open pages, and about `max_rss_hint` bytes of
+    /// JavaScript heap across them if given.
+    ///
+    /// The heap is what the renderers report through
+    /// `Runtime.getHeapUsage`; the process takes more for DOM, layout and
+    /// images, so the hint is a lower bound on the real footprint.
+    ///
+    /// # Panics
+    ///
+    /// If `max_pages` is zero.
+    pub fn new(max_pages: usize, max_rss_hint: Option<u64>) -> Self {
+        assert!(max_pages > 0, "a page budget needs room for one page");
+        Self {
+            max_pages,
+            max_rss_hint,
+            idle_after: Duration::from_secs(30),
+            close_idle_after: None,
+            clear: ClearPolicy::default(),
+        }
+    }
+
+    /// How long a page must have been inactive before it may be closed to
+    /// stay within the budget. Defaults to 30 seconds.
+    pub fn idle_after(mut self, idle_after: Duration) -> Self {
+        self.idle_after = idle_after;
+        self
+    }
+
+    /// Close pages inactive for `after` on every hygiene pass, whether the
+    /// context is over budget or not. Off by default.
+    pub fn close_idle_after(mut self, after: Duration) -> Self {
+        self.close_idle_after = Some(after);
+        self
+    }
+
+    /// What to clear on every hygiene pass.
+    pub fn clear(mut self, clear: ClearPolicy) -> Self {
+        self.clear = clear;
+        self
+    }
+
+    pub fn max_pages(&self) -> usize {
+        self.max_pages
+    }
+
+    pub fn max_rss_hint(&self) -> Option<u64> {
+        self.max_rss_hint
+    }
+
+    pub fn idle_threshold(&self) -> Duration {
+        self.idle_after
+    }
+
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.close_idle_after
+    }
+
+    pub fn clear_policy(&self) -> ClearPolicy {
+        self.clear
+    }
+}
+
+/// Browsing data a hygiene pass wipes from the context.
+///
+/// Clearing cookies logs the context out of every site; leave it off for
+/// contexts that carry a session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClearPolicy {
+    /// The HTTP cache, through `Network.clearBrowserCache`.
+    pub cache: bool,
+    /// All cookies, through `Storage.clearCookies`.
+    pub cookies: bool,
+}
+
+/// What a context did to stay within its budget.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BudgetStats {
+    /// Pages currently tracked as open.
+    pub open: usize,
+    /// Pages opened under the budget.
+    pub opened: u64,
+    /// Pages closed for being idle longer than the idle timeout.
+    pub closed_idle: u64,
+    /// Pages closed to make room for a new one.
+    pub closed_for_pages: u64,
+    /// Pages closed because the heaps exceeded the memory hint.
+    pub closed_for_memory: u64,
+    /// JavaScript heap of the open pages at the last hygiene pass, in bytes.
+    pub heap_bytes: u64,
+    pub cache_clears: u64,
+    pub cookie_clears: u64,
+    /// Times the context was replaced by a fresh one.
+    pub recycles: u64,
+}
+
+impl BudgetStats {
+    /// Pages closed by the budget, for whatever reason.
+    pub fn closed(&self) -> u64 {
+        self.closed_idle + self.closed_for_pages + self.closed_for_memory
+    }
+}
+
+/// The pages that may be closed to stay within the budget, as indices into
+/// `idle`, least recently active first.
+pub(crate) fn closable(idle: &[Duration], idle_after: Duration) -> Vec<usize> {
+    let mut candidates: Vec<usize> = (0..idle.len()).filter(|&i| idle[i] >= idle_after).collect();
+    candidates.sort_by(|&a, &b| idle[b].cmp(&idle[a]));
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_idle_pages_are_closable_longest_idle_first() {
+        let secs = Duration::from_secs;
+        let idle = [secs(40), secs(5), secs(300), secs(30), secs(29)];
+        assert_eq!(closable(&idle, secs(30)), [2, 0, 3]);
+        assert!(closable(&idle, secs(600)).is_empty());
+    }
+}
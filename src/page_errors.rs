@@ -0,0 +1,792 @@
+//! What the page complained about: errors, failed requests, console output.
+//!
+//! When a site breaks under the stealth patches, the first question is what
+//! the page itself reported. [`ChaserPage::capture_page_errors`] starts
+//! collecting into a per-page log that [`ChaserPage::page_errors`] returns:
+//!
+//! - uncaught exceptions and unhandled promise rejections, seen by a
+//!   listener in the isolated world (the page cannot see it, but it only
+//!   gets message and location, no stack, and only for the main frame);
+//! - failed requests, HTTP errors, CSP violations, interventions and the
+//!   other browser messages of the `Log` domain, which is always enabled;
+//! - with [`ErrorCapture::console`], `console.*` calls and exceptions with
+//!   full stack traces. This needs `Runtime.enable`, which anti-bot scripts
+//!   detect, so keep it for debugging sessions.
+//!
+//! With [`ErrorCapture::source_maps`], stack frames of minified scripts are
+//! mapped back to their original sources. Scripts and maps are fetched the
+//! way DevTools does (`Network.loadNetworkResource`), outside the page.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::page_errors::{ErrorCapture, Level};
+//!
+//! chaser
+//!     .capture_page_errors(ErrorCapture::default().min_level(Level::Warning).source_maps(true))
+//!     .await?;
+//! chaser.goto("https://example.com/checkout").await?;
+//! for error in chaser.page_errors().await? {
+//!     println!("{error}");
+//! }
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::{ChaserError, ChaserResult as Result};
+use crate::utils;
+use chromiumoxide_cdp::cdp::browser_protocol::io::{CloseParams, ReadParams};
+use chromiumoxide_cdp::cdp::browser_protocol::log::{
+    EventEntryAdded, LogEntryLevel, LogEntrySource,
+};
+use chromiumoxide_cdp::cdp::browser_protocol::network::{
+    LoadNetworkResourceOptions, LoadNetworkResourceParams,
+};
+use chromiumoxide_cdp::cdp::browser_protocol::page::{
+    AddScriptToEvaluateOnNewDocumentParams, RemoveScriptToEvaluateOnNewDocumentParams,
+    ScriptIdentifier,
+};
+use chromiumoxide_cdp::cdp::js_protocol::runtime::{
+    ConsoleApiCalledType, EnableParams as RuntimeEnableParams, EventConsoleApiCalled,
+    EventExceptionThrown, RemoteObject, StackTrace,
+};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
+use url::Url;
+
+/// Records uncaught errors of the document in the isolated world; installed
+/// into the same named world `evaluate_stealth` runs in.
+const LISTENER_SCRIPT: &str = r#"(() => {
+    if (globalThis.__pageErrors) return;
+    const buffer = globalThis.__pageErrors = [];
+    const push = entry => {
+        buffer.push(Object.assign({ timestamp: Date.now() }, entry));
+        if (buffer.length > 1000) buffer.shift();
+    };
+    addEventListener('error', event => {
+        if (!(event instanceof ErrorEvent)) return;
+        push({ rejection: false, text: event.message || 'Script error.', url: event.filename || null,
+            line: event.lineno || null, column: event.colno || null });
+    });
+    addEventListener('unhandledrejection', event => {
+        let text = 'Unhandled promise rejection';
+        try {
+            if (event.reason !== undefined) text += ': ' + String(event.reason && event.reason.message || event.reason);
+        } catch (e) {}
+        push({ rejection: true, text, url: null, line: null, column: null });
+    });
+})()"#;
+
+/// Empties the listener's buffer and returns what it held.
+const DRAIN_SCRIPT: &str =
+    "(() => { const b = globalThis.__pageErrors || []; return b.splice(0, b.length); })()";
+
+/// Where an entry came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorSource {
+    /// A `console.*` call (only with [`ErrorCapture::console`]).
+    Console,
+    /// An uncaught exception or unhandled promise rejection.
+    Exception,
+    /// A failed request or an HTTP error status.
+    Network,
+    /// Anything else the browser logged: CSP, interventions, deprecations.
+    Browser,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+/// A position in an original source, from a source map.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OriginalPosition {
+    pub source: String,
+    /// 1-based.
+    pub line: u32,
+    /// 1-based.
+    pub column: u32,
+    /// The original identifier, if the map names it.
+    pub name: Option<String>,
+}
+
+/// One frame of a stack trace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StackFrame {
+    pub function: String,
+    pub url: String,
+    /// 1-based.
+    pub line: u32,
+    /// 1-based.
+    pub column: u32,
+    /// Where this is in the original source, once resolved.
+    pub original: Option<OriginalPosition>,
+}
+
+/// One entry of the page's log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageError {
+    pub source: ErrorSource,
+    pub level: Level,
+    pub text: String,
+    /// The script or request the entry is about.
+    pub url: Option<String>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    /// Innermost frame first; empty when the source gives no stack.
+    pub stack: Vec<StackFrame>,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: f64,
+}
+
+impl fmt::Display for PageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{:?} {:?}] {}", self.source, self.level, self.text)?;
+        if let Some(url) = &self.url {
+            write!(f, " ({url}")?;
+            if let Some(line) = self.line {
+                write!(f, ":{line}")?;
+            }
+            write!(f, ")")?;
+        }
+        for frame in &self.stack {
+            match &frame.original {
+                Some(original) => write!(
+                    f,
+                    "\n    at {} ({}:{}:{})",
+                    frame.function, original.source, original.line, original.column
+                )?,
+                None => write!(
+                    f,
+                    "\n    at {} ({}:{}:{})",
+                    frame.function, frame.url, frame.line, frame.column
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What [`ChaserPage::capture_page_errors`] collects.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorCapture {
+    console: bool,
+    source_maps: bool,
+    min_level: Level,
+    capacity: usize,
+}
+
+impl Default for ErrorCapture {
+    fn default() -> Self {
+        Self {
+            console: false,
+            source_maps: false,
+            min_level: Level::Warning,
+            capacity: 500,
+        }
+    }
+}
+
+impl ErrorCapture {
+    /// Also capture `console.*` calls and full exception stacks through
+    /// `Runtime.enable`. Detectable by the page; off by default.
+    pub fn console(mut self, console: bool) -> Self {
+        self.console = console;
+        self
+    }
+
+    /// Resolve stack frames through the scripts' source maps.
+    pub fn source_maps(mut self, source_maps: bool) -> Self {
+        self.source_maps = source_maps;
+        self
+    }
+
+    /// Drop entries below `level`. Default: [`Level::Warning`].
+    pub fn min_level(mut self, level: Level) -> Self {
+        self.min_level = level;
+        self
+    }
+
+    /// Keep at most `capacity` entries, dropping the oldest. Default: 500.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+}
+
+/// The capture state of a page.
+#[derive(Debug)]
+pub(crate) struct ErrorLog {
+    config: ErrorCapture,
+    entries: VecDeque<PageError>,
+    /// Parsed maps by script URL; `None` when a script has none.
+    source_maps: HashMap<String, Option<Arc<SourceMap>>>,
+    tasks: Vec<JoinHandle<()>>,
+    script: Option<ScriptIdentifier>,
+}
+
+impl ErrorLog {
+    fn record(&mut self, entry: PageError) {
+        if entry.level < self.config.min_level {
+            return;
+        }
+        if self.entries.len() >= self.config.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+}
+
+impl Drop for ErrorLog {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+fn record(log: &Mutex<Option<ErrorLog>>, entry: PageError) {
+    if let Some(log) = log.lock().unwrap().as_mut() {
+        log.record(entry);
+    }
+}
+
+fn stack_frames(trace: Option<&StackTrace>) -> Vec<StackFrame> {
+    trace
+        .map(|trace| {
+            trace
+                .call_frames
+                .iter()
+                .map(|frame| StackFrame {
+                    function: if frame.function_name.is_empty() {
+                        "<anonymous>".to_string()
+                    } else {
+                        frame.function_name.clone()
+                    },
+                    url: frame.url.clone(),
+                    line: frame.line_number as u32 + 1,
+                    column: frame.column_number as u32 + 1,
+                    original: None,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn describe(object: &RemoteObject) -> String {
+    match (&object.value, &object.description) {
+        (Some(serde_json::Value::String(s)), _) => s.clone(),
+        (Some(value), _) => value.to_string(),
+        (None, Some(description)) => description.clone(),
+        (None, None) => format!("{:?}", object.r#type).to_lowercase(),
+    }
+}
+
+fn from_log(event: &EventEntryAdded) -> PageError {
+    let entry = &event.entry;
+    PageError {
+        source: match entry.source {
+            LogEntrySource::Network => ErrorSource::Network,
+            LogEntrySource::Javascript => ErrorSource::Exception,
+            _ => ErrorSource::Browser,
+        },
+        level: match entry.level {
+            LogEntryLevel::Verbose => Level::Debug,
+            LogEntryLevel::Info => Level::Info,
+            LogEntryLevel::Warning => Level::Warning,
+            LogEntryLevel::Error => Level::Error,
+        },
+        text: entry.text.clone(),
+        url: entry.url.clone(),
+        line: entry.line_number.map(|line| line as u32 + 1),
+        column: None,
+        stack: stack_frames(entry.stack_trace.as_ref()),
+        timestamp: *entry.timestamp.inner(),
+    }
+}
+
+fn from_console(event: &EventConsoleApiCalled) -> PageError {
+    let stack = stack_frames(event.stack_trace.as_ref());
+    PageError {
+        source: ErrorSource::Console,
+        level: match event.r#type {
+            ConsoleApiCalledType::Error | ConsoleApiCalledType::Assert => Level::Error,
+            ConsoleApiCalledType::Warning => Level::Warning,
+            ConsoleApiCalledType::Debug => Level::Debug,
+            _ => Level::Info,
+        },
+        text: event
+            .args
+            .iter()
+            .map(describe)
+            .collect::<Vec<_>>()
+            .join(" "),
+        url: stack.first().map(|frame| frame.url.clone()),
+        line: stack.first().map(|frame| frame.line),
+        column: stack.first().map(|frame| frame.column),
+        stack,
+        timestamp: *event.timestamp.inner(),
+    }
+}
+
+fn from_exception(event: &EventExceptionThrown) -> PageError {
+    let details = &event.exception_details;
+    let text = details
+        .exception
+        .as_ref()
+        .and_then(|e| e.description.as_deref())
+        .and_then(|d| d.lines().next())
+        .map(str::to_string)
+        .unwrap_or_else(|| details.text.clone());
+    PageError {
+        source: ErrorSource::Exception,
+        level: Level::Error,
+        text,
+        url: details.url.clone(),
+        line: Some(details.line_number as u32 + 1),
+        column: Some(details.column_number as u32 + 1),
+        stack: stack_frames(details.stack_trace.as_ref()),
+        timestamp: *event.timestamp.inner(),
+    }
+}
+
+/// An entry of the isolated-world listener.
+#[derive(Debug, Deserialize)]
+struct RawException {
+    rejection: bool,
+    text: String,
+    url: Option<String>,
+    line: Option<u32>,
+    column: Option<u32>,
+    timestamp: f64,
+}
+
+impl From<RawException> for PageError {
+    fn from(raw: RawException) -> Self {
+        // an ErrorEvent seen from another world carries no error object, so
+        // its location is the only frame there is
+        let stack = match (&raw.url, raw.line, raw.column) {
+            (Some(url), Some(line), Some(column)) if !raw.rejection => vec![StackFrame {
+                function: "<anonymous>".to_string(),
+                url: url.clone(),
+                line,
+                column,
+                original: None,
+            }],
+            _ => Vec::new(),
+        };
+        PageError {
+            source: ErrorSource::Exception,
+            level: Level::Error,
+            text: raw.text,
+            url: raw.url,
+            line: raw.line,
+            column: raw.column,
+            stack,
+            timestamp: raw.timestamp,
+        }
+    }
+}
+
+/// One mapped segment of a generated line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Mapping {
+    column: u32,
+    source: u32,
+    line: u32,
+    source_column: u32,
+    name: Option<u32>,
+}
+
+/// A parsed version 3 source map.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SourceMap {
+    sources: Vec<String>,
+    names: Vec<String>,
+    /// Mappings of each generated line, by column.
+    lines: Vec<Vec<Mapping>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSourceMap {
+    #[serde(default)]
+    source_root: Option<String>,
+    sources: Vec<Option<String>>,
+    #[serde(default)]
+    names: Vec<String>,
+    mappings: String,
+}
+
+/// Decode one base64 VLQ value.
+fn vlq(bytes: &mut std::iter::Peekable<std::str::Bytes<'_>>) -> Option<i64> {
+    let mut value = 0i64;
+    let mut shift = 0;
+    loop {
+        let digit = match bytes.next()? {
+            b @ b'A'..=b'Z' => b - b'A',
+            b @ b'a'..=b'z' => b - b'a' + 26,
+            b @ b'0'..=b'9' => b - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        } as i64;
+        value |= (digit & 31) << shift;
+        shift += 5;
+        if digit & 32 == 0 || shift > 60 {
+            break;
+        }
+    }
+    Some(if value & 1 == 1 {
+        -(value >> 1)
+    } else {
+        value >> 1
+    })
+}
+
+impl SourceMap {
+    /// Parse `json`, resolving source paths against `map_url`.
+    pub(crate) fn parse(json: &str, map_url: &str) -> Option<Self> {
+        let raw: RawSourceMap = serde_json::from_str(json).ok()?;
+        let base = Url::parse(map_url).ok();
+        let root = raw.source_root.unwrap_or_default();
+        let sources = raw
+            .sources
+            .into_iter()
+            .map(|source| {
+                let mut path = source.unwrap_or_default();
+                if !root.is_empty() {
+                    path = format!("{}/{}", root.trim_end_matches('/'), path);
+                }
+                base.as_ref()
+                    .and_then(|base| base.join(&path).ok())
+                    .map(String::from)
+                    .unwrap_or(path)
+            })
+            .collect();
+
+        let (mut source, mut line, mut source_column, mut name) = (0i64, 0i64, 0i64, 0i64);
+        let mut lines = Vec::new();
+        for generated in raw.mappings.split(';') {
+            let mut column = 0i64;
+            let mut mappings = Vec::new();
+            for segment in generated.split(',').filter(|s| !s.is_empty()) {
+                let mut bytes = segment.bytes().peekable();
+                column += vlq(&mut bytes)?;
+                if bytes.peek().is_none() {
+                    continue;
+                }
+                source += vlq(&mut bytes)?;
+                line += vlq(&mut bytes)?;
+                source_column += vlq(&mut bytes)?;
+                let named = match bytes.peek() {
+                    Some(_) => {
+                        name += vlq(&mut bytes)?;
+                        Some(name as u32)
+                    }
+                    None => None,
+                };
+                mappings.push(Mapping {
+                    column: column as u32,
+                    source: source as u32,
+                    line: line as u32,
+                    source_column: source_column as u32,
+                    name: named,
+                });
+            }
+            mappings.sort_by_key(|mapping| mapping.column);
+            lines.push(mappings);
+        }
+        Some(Self {
+            sources,
+            names: raw.names,
+            lines,
+        })
+    }
+
+    /// The original position of 1-based `line` and `column` of the
+    /// generated script.
+    pub(crate) fn lookup(&self, line: u32, column: u32) -> Option<OriginalPosition> {
+        let mappings = self.lines.get(line.checked_sub(1)? as usize)?;
+        let column = column.saturating_sub(1);
+        let index = mappings.partition_point(|mapping| mapping.column <= column);
+        let mapping = mappings.get(index.checked_sub(1)?)?;
+        Some(OriginalPosition {
+            source: self.sources.get(mapping.source as usize)?.clone(),
+            line: mapping.line + 1,
+            column: mapping.source_column + 1,
+            name: mapping
+                .name
+                .and_then(|name| self.names.get(name as usize).cloned()),
+        })
+    }
+}
+
+/// The URL in a script's last `//# sourceMappingURL=` comment.
+fn source_mapping_url(script: &str) -> Option<&str> {
+    script.lines().rev().take(5).find_map(|line| {
+        let line = line.trim();
+        line.strip_prefix("//# sourceMappingURL=")
+            .or_else(|| line.strip_prefix("//@ sourceMappingURL="))
+            .map(str::trim)
+    })
+}
+
+impl ChaserPage {
+    /// Start collecting the page's errors, replacing an earlier capture and
+    /// its entries. Entries are read with [`page_errors`](Self::page_errors).
+    pub async fn capture_page_errors(&self, config: ErrorCapture) -> Result<()> {
+        self.stop_page_errors().await;
+        let page = self.raw_page();
+        let state = self.error_log().clone();
+        let mut log = ErrorLog {
+            config: config.clone(),
+            entries: VecDeque::new(),
+            source_maps: HashMap::new(),
+            tasks: Vec::new(),
+            script: None,
+        };
+
+        let mut entries = page.event_listener::<EventEntryAdded>().await?;
+        let sink = state.clone();
+        log.tasks.push(tokio::spawn(async move {
+            while let Some(event) = entries.next().await {
+                record(&sink, from_log(&event));
+            }
+        }));
+
+        if config.console {
+            let mut console = page.event_listener::<EventConsoleApiCalled>().await?;
+            let sink = state.clone();
+            log.tasks.push(tokio::spawn(async move {
+                while let Some(event) = console.next().await {
+                    record(&sink, from_console(&event));
+                }
+            }));
+            let mut exceptions = page.event_listener::<EventExceptionThrown>().await?;
+            let sink = state.clone();
+            log.tasks.push(tokio::spawn(async move {
+                while let Some(event) = exceptions.next().await {
+                    record(&sink, from_exception(&event));
+                }
+            }));
+            page.execute(RuntimeEnableParams::default()).await?;
+        } else {
+            let script = page
+                .execute(AddScriptToEvaluateOnNewDocumentParams {
+                    source: LISTENER_SCRIPT.to_string(),
+                    world_name: Some(self.world_name()),
+                    include_command_line_api: None,
+                    run_immediately: None,
+                })
+                .await?
+                .result
+                .identifier;
+            log.script = Some(script);
+        }
+        *state.lock().unwrap() = Some(log);
+        if !config.console {
+            self.evaluate_stealth(LISTENER_SCRIPT).await?;
+        }
+        Ok(())
+    }
+
+    /// Stop collecting and drop the entries collected so far.
+    pub async fn stop_page_errors(&self) {
+        let previous = self.error_log().lock().unwrap().take();
+        if let Some(script) = previous.and_then(|mut log| log.script.take()) {
+            let _ = self
+                .raw_page()
+                .execute(RemoveScriptToEvaluateOnNewDocumentParams::new(script))
+                .await;
+        }
+    }
+
+    /// The entries collected since [`capture_page_errors`](Self::capture_page_errors),
+    /// oldest first. Empty when no capture is running.
+    ///
+    /// Exceptions seen by the isolated-world listener are collected when
+    /// this is called, so call it before navigating away from a document
+    /// whose errors matter.
+    pub async fn page_errors(&self) -> Result<Vec<PageError>> {
+        let config = match self.error_log().lock().unwrap().as_ref() {
+            Some(log) => log.config.clone(),
+            None => return Ok(Vec::new()),
+        };
+        if !config.console {
+            if let Some(value) = self.evaluate_stealth(DRAIN_SCRIPT).await? {
+                let raw: Vec<RawException> = serde_json::from_value(value)?;
+                if let Some(log) = self.error_log().lock().unwrap().as_mut() {
+                    raw.into_iter().for_each(|raw| log.record(raw.into()));
+                    log.entries
+                        .make_contiguous()
+                        .sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+                }
+            }
+        }
+        if config.source_maps {
+            self.resolve_stacks().await;
+        }
+        Ok(self
+            .error_log()
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|log| log.entries.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    /// Forget the entries collected so far, keeping the capture running.
+    pub fn clear_page_errors(&self) {
+        if let Some(log) = self.error_log().lock().unwrap().as_mut() {
+            log.entries.clear();
+        }
+    }
+
+    /// Fill in the original positions of stack frames whose script has a
+    /// source map, loading maps not seen before.
+    async fn resolve_stacks(&self) {
+        let missing: Vec<String> = {
+            let guard = self.error_log().lock().unwrap();
+            let Some(log) = guard.as_ref() else {
+                return;
+            };
+            let mut urls: Vec<String> = log
+                .entries
+                .iter()
+                .flat_map(|entry| &entry.stack)
+                .filter(|frame| frame.original.is_none() && frame.url.starts_with("http"))
+                .map(|frame| frame.url.clone())
+                .filter(|url| !log.source_maps.contains_key(url))
+                .collect();
+            urls.sort();
+            urls.dedup();
+            urls
+        };
+        let mut loaded = Vec::with_capacity(missing.len());
+        for url in missing {
+            let map = self.load_source_map(&url).await.map(Arc::new);
+            loaded.push((url, map));
+        }
+
+        let mut guard = self.error_log().lock().unwrap();
+        let Some(log) = guard.as_mut() else {
+            return;
+        };
+        log.source_maps.extend(loaded);
+        let ErrorLog {
+            entries,
+            source_maps,
+            ..
+        } = log;
+        for frame in entries.iter_mut().flat_map(|entry| entry.stack.iter_mut()) {
+            if frame.original.is_none() {
+                if let Some(Some(map)) = source_maps.get(&frame.url) {
+                    frame.original = map.lookup(frame.line, frame.column);
+                }
+            }
+        }
+    }
+
+    /// The source map of the script at `script_url`, if it has one.
+    async fn load_source_map(&self, script_url: &str) -> Option<SourceMap> {
+        let script = self.load_resource(script_url).await.ok()?;
+        let reference = source_mapping_url(&script)?;
+        let map_url = Url::parse(script_url).ok()?.join(reference).ok()?;
+        let json = if map_url.scheme() == "data" {
+            let (header, data) = map_url.path().split_once(',')?;
+            if header.ends_with(";base64") {
+                String::from_utf8(utils::base64::decode(data).ok()?).ok()?
+            } else {
+                data.to_string()
+            }
+        } else {
+            self.load_resource(map_url.as_str()).await.ok()?
+        };
+        SourceMap::parse(&json, map_url.as_str())
+    }
+
+    /// Fetch `url` for the main frame the way DevTools loads source maps,
+    /// with the page's cookies but without the page seeing the request.
+    pub(crate) async fn load_resource(&self, url: &str) -> Result<String> {
+        let page = self.raw_page();
+        let frame = page.mainframe().await?.ok_or(ChaserError::Detached)?;
+        let params = LoadNetworkResourceParams::builder()
+            .frame_id(frame)
+            .url(url)
+            .options(LoadNetworkResourceOptions::new(false, true))
+            .build()
+            .map_err(ChaserError::msg)?;
+        let resource = page.execute(params).await?.result.resource;
+        let stream = match resource.stream {
+            Some(stream) if resource.success => stream,
+            _ => {
+                return Err(ChaserError::msg(format!(
+                    "cannot load {url}: {}",
+                    resource
+                        .net_error_name
+                        .or(resource.http_status_code.map(|status| status.to_string()))
+                        .unwrap_or_default()
+                )))
+            }
+        };
+        let mut data = Vec::new();
+        loop {
+            let chunk = page.execute(ReadParams::new(stream.clone())).await?.result;
+            if chunk.base64_encoded == Some(true) {
+                data.extend(
+                    utils::base64::decode(&chunk.data)
+                        .map_err(|e| ChaserError::msg(e.to_string()))?,
+                );
+            } else {
+                data.extend(chunk.data.into_bytes());
+            }
+            if chunk.eof {
+                break;
+            }
+        }
+        let _ = page.execute(CloseParams::new(stream)).await;
+        Ok(String::from_utf8_lossy(&data).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_generated_positions_back() {
+        // a.js: "function hello(name) {\n  throw new Error(name);\n}" minified
+        // to one line; segments map columns 0, 9 and 24 of line 1
+        let json = r#"{
+            "version": 3,
+            "sourceRoot": "src",
+            "sources": ["a.js"],
+            "names": ["hello", "name"],
+            "mappings": "AAAA,SAASA,eACEC"
+        }"#;
+        let map = SourceMap::parse(json, "https://cdn.test/app.min.js.map").unwrap();
+        let hello = map.lookup(1, 12).unwrap();
+        assert_eq!(hello.source, "https://cdn.test/src/a.js");
+        assert_eq!((hello.line, hello.column), (1, 10));
+        assert_eq!(hello.name.as_deref(), Some("hello"));
+        let throw = map.lookup(1, 30).unwrap();
+        assert_eq!((throw.line, throw.column), (2, 12));
+        assert_eq!(throw.name.as_deref(), Some("name"));
+        assert!(map.lookup(2, 1).is_none());
+
+        assert_eq!(
+            source_mapping_url("f();\n//# sourceMappingURL=app.min.js.map\n"),
+            Some("app.min.js.map")
+        );
+    }
+}
@@ -0,0 +1,348 @@
+//! Automatic per-site session partitioning.
+//!
+//! When one pool serves several customer domains it is easy to accidentally
+//! reuse a page (and with it cookies and localStorage) across sites. The
+//! [`SessionPartitioner`] prevents that structurally: every site gets its own
+//! [`ChaserContext`], created on first use, and pages are only ever handed out
+//! from the context belonging to the URL's site.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::partition::SessionPartitioner;
+//!
+//! let partitioner = SessionPartitioner::new(&pool);
+//! let shop = partitioner.goto("https://shop.example.com/cart").await?;
+//! let other = partitioner.goto("https://customer-b.com/").await?;
+//! // `shop` and `other` never see each other's cookies.
+//! ```
+
+use crate::browser::Browser;
+use crate::chaser::ChaserPage;
+use crate::context::ChaserContext;
+use crate::pool::BrowserPool;
+use crate::profiles::ChaserProfile;
+use anyhow::{anyhow, Result};
+use futures::lock::Mutex;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use url::Url;
+
+/// How URLs are grouped into partitions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PartitionKey {
+    /// Registrable domain, e.g. `shop.example.co.uk` -> `example.co.uk`.
+    #[default]
+    Site,
+    /// Full host name; subdomains get separate partitions.
+    Host,
+}
+
+impl PartitionKey {
+    /// The partition `url` belongs to.
+    pub fn of(&self, url: &str) -> Result<String> {
+        let url = Url::parse(url)?;
+        let host = url
+            .host_str()
+            .ok_or_else(|| anyhow!("URL {} has no host", url))?
+            .to_ascii_lowercase();
+        Ok(match self {
+            PartitionKey::Host => host,
+            PartitionKey::Site => registrable_domain(&host),
+        })
+    }
+}
+
+/// Approximate the registrable domain without a public suffix list: the last
+/// two labels, or three for common two-level country suffixes (`co.uk`).
+pub(crate) fn registrable_domain(host: &str) -> String {
+    if host.parse::<std::net::IpAddr>().is_ok() || host.starts_with('[') {
+        return host.to_string();
+    }
+    let labels: Vec<&str> = host.trim_end_matches('.').split('.').collect();
+    let keep = match labels.as_slice() {
+        [.., sld, tld]
+            if labels.len() >= 3
+                && tld.len() == 2
+                && matches!(
+                    *sld,
+                    "co" | "com" | "org" | "net" | "ac" | "gov" | "edu" | "ne"
+                ) =>
+        {
+            3
+        }
+        _ => 2,
+    };
+    labels[labels.len().saturating_sub(keep)..].join(".")
+}
+
+/// Maps sites to dedicated browser contexts of a [`BrowserPool`].
+///
+/// Contexts keep the pool's browsers alive; call [`SessionPartitioner::close`]
+/// before closing the pool.
+pub struct SessionPartitioner {
+    browsers: Vec<Arc<Browser>>,
+    profiles: Vec<ChaserProfile>,
+    proxies: Vec<String>,
+    key: PartitionKey,
+    next: AtomicUsize,
+    contexts: Mutex<HashMap<String, ChaserContext>>,
+}
+
+impl std::fmt::Debug for SessionPartitioner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionPartitioner")
+            .field("browsers", &self.browsers.len())
+            .field("key", &self.key)
+            .finish()
+    }
+}
+
+impl SessionPartitioner {
+    /// Partition the browsers of `pool`, using the pool's profile and proxy
+    /// rotation for new partitions.
+    pub fn new(pool: &BrowserPool) -> Self {
+        Self {
+            browsers: pool.browsers().to_vec(),
+            profiles: pool.config().profiles().to_vec(),
+            proxies: pool.config().proxies().to_vec(),
+            key: PartitionKey::default(),
+            next: AtomicUsize::new(0),
+            contexts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Group URLs by `key` instead of by site.
+    pub fn key(mut self, key: PartitionKey) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// The context of `url`'s partition, created on first use.
+    pub async fn context_for(&self, url: &str) -> Result<ChaserContext> {
+        let partition = self.key.of(url)?;
+        let mut contexts = self.contexts.lock().await;
+        if let Some(context) = contexts.get(&partition) {
+            return Ok(context.clone());
+        }
+        if self.browsers.is_empty() {
+            return Err(anyhow!("Browser pool has no browsers"));
+        }
+
+        let slot = self.next.fetch_add(1, Ordering::Relaxed);
+        let browser = self.browsers[slot % self.browsers.len()].clone();
+        let profile = self
+            .profiles
+            .get(slot % self.profiles.len().max(1))
+            .cloned()
+            .unwrap_or_default();
+        let proxy =
+            (!self.proxies.is_empty()).then(|| self.proxies[slot % self.proxies.len()].clone());
+
+        let context = ChaserContext::create(browser, profile, proxy).await?;
+        tracing::debug!("created partition {} for {}", partition, url);
+        contexts.insert(partition, context.clone());
+        Ok(context)
+    }
+
+    /// A new blank page in `url`'s partition.
+    pub async fn page_for(&self, url: &str) -> Result<ChaserPage> {
+        self.context_for(url).await?.new_page().await
+    }
+
+    /// A new page in `url`'s partition, navigated to `url`.
+    pub async fn goto(&self, url: &str) -> Result<ChaserPage> {
+        let page = self.page_for(url).await?;
+        page.goto(url).await?;
+        Ok(page)
+    }
+
+    /// Whether `page` may navigate to `url` without leaving its partition.
+    pub async fn same_partition(&self, page: &ChaserPage, url: &str) -> Result<bool> {
+        let current = page.url().await?.unwrap_or_default();
+        match (self.key.of(&current), self.key.of(url)) {
+            (Ok(a), Ok(b)) => Ok(a == b),
+            // a blank page has not been bound to a site yet
+            (Err(_), Ok(_)) => Ok(true),
+            (_, Err(e)) => Err(e),
+        }
+    }
+
+    /// Names of all partitions created so far.
+    pub async fn partitions(&self) -> Vec<String> {
+        let mut names: Vec<_> = self.contexts.lock().await.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Dispose the partition of `url`, dropping all of its session data.
+    pub async fn clear(&self, url: &str) -> Result<()> {
+        let partition = self.key.of(url)?;
+        let context = self.contexts.lock().await.remove(&partition);
+        match context {
+            Some(context) => context.dispose().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Dispose every partition.
+    pub async fn close(self) -> Result<()> {
+        for (_, context) in self.contexts.into_inner() {
+            context.dispose().await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::PoolConfig;
+    use crate::profiles::Os;
+    use crate::transport::MockTransport;
+    use serde_json::json;
+
+    #[test]
+    fn sites_group_subdomains_and_country_suffixes() {
+        assert_eq!(registrable_domain("shop.example.com"), "example.com");
+        assert_eq!(
+            registrable_domain("a.b.shop.example.co.uk"),
+            "example.co.uk"
+        );
+        assert_eq!(registrable_domain("example.com.au."), "example.com.au");
+        // a two letter TLD alone is no country suffix
+        assert_eq!(registrable_domain("www.example.de"), "example.de");
+        assert_eq!(registrable_domain("localhost"), "localhost");
+        assert_eq!(registrable_domain("10.0.0.1"), "10.0.0.1");
+        assert_eq!(registrable_domain("[::1]"), "[::1]");
+
+        let url = "https://Shop.Example.co.uk:8443/cart?x=1";
+        assert_eq!(PartitionKey::Site.of(url).unwrap(), "example.co.uk");
+        assert_eq!(PartitionKey::Host.of(url).unwrap(), "shop.example.co.uk");
+        assert!(PartitionKey::Site.of("data:text/plain,hi").is_err());
+        assert!(PartitionKey::Site.of("not a url").is_err());
+    }
+
+    fn partitioner(mocks: &[MockTransport]) -> SessionPartitioner {
+        let browsers = mocks.iter().cloned().map(Browser::with_transport).collect();
+        let config = PoolConfig::builder()
+            .browsers(mocks.len())
+            .profiles([
+                ChaserProfile::windows().build(),
+                ChaserProfile::macos_arm().build(),
+            ])
+            .proxies(["http://10.0.0.1:8080", "http://10.0.0.2:8080"])
+            .build();
+        SessionPartitioner::new(&BrowserPool::from_browsers(browsers, config))
+    }
+
+    #[tokio::test]
+    async fn each_site_gets_its_own_context() {
+        let mocks = [MockTransport::new(), MockTransport::new()];
+        let partitioner = partitioner(&mocks);
+
+        let shop = partitioner
+            .context_for("https://shop.example.com/cart")
+            .await
+            .unwrap();
+        let again = partitioner
+            .context_for("https://www.example.com/")
+            .await
+            .unwrap();
+        assert_eq!(shop.id(), again.id());
+        assert_eq!(mocks[0].commands_to("Target.createBrowserContext").len(), 1);
+
+        // the next site takes the next browser, profile and proxy
+        let other = partitioner
+            .context_for("https://customer-b.com/")
+            .await
+            .unwrap();
+        assert_eq!(
+            (shop.profile().os(), shop.proxy()),
+            (Os::Windows, Some("http://10.0.0.1:8080"))
+        );
+        assert_eq!(
+            (other.profile().os(), other.proxy()),
+            (Os::MacOSArm, Some("http://10.0.0.2:8080"))
+        );
+        assert_eq!(
+            mocks[1].commands_to("Target.createBrowserContext")[0]["proxyServer"],
+            "http://10.0.0.2:8080"
+        );
+        assert_eq!(
+            partitioner.partitions().await,
+            ["customer-b.com", "example.com"]
+        );
+
+        partitioner.clear("https://example.com/").await.unwrap();
+        assert_eq!(partitioner.partitions().await, ["customer-b.com"]);
+        assert_eq!(
+            mocks[0].commands_to("Target.disposeBrowserContext").len(),
+            1
+        );
+        // clearing a partition that does not exist is a no-op
+        partitioner.clear("https://unknown.test/").await.unwrap();
+
+        partitioner.close().await.unwrap();
+        assert_eq!(
+            mocks[1].commands_to("Target.disposeBrowserContext").len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn hosts_split_subdomains() {
+        let mock = MockTransport::new();
+        let partitioner = partitioner(std::slice::from_ref(&mock)).key(PartitionKey::Host);
+        partitioner
+            .context_for("https://a.example.com/")
+            .await
+            .unwrap();
+        partitioner
+            .context_for("https://b.example.com/")
+            .await
+            .unwrap();
+        assert_eq!(
+            partitioner.partitions().await,
+            ["a.example.com", "b.example.com"]
+        );
+        assert_eq!(mock.commands_to("Target.createBrowserContext").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn pages_open_in_their_partition() {
+        let mock = MockTransport::new();
+        mock.respond(
+            "Target.createBrowserContext",
+            json!({ "browserContextId": "SHOP" }),
+        );
+        let partitioner = partitioner(std::slice::from_ref(&mock));
+
+        let page = partitioner
+            .page_for("https://shop.example.com/")
+            .await
+            .unwrap();
+        assert_eq!(
+            mock.commands_to("Target.createTarget")[0]["browserContextId"],
+            "SHOP"
+        );
+        // a blank page is not bound to a site yet
+        assert!(partitioner
+            .same_partition(&page, "https://other.test/")
+            .await
+            .unwrap());
+        assert!(partitioner.same_partition(&page, "nonsense").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn an_empty_pool_has_no_partitions() {
+        let partitioner = partitioner(&[]);
+        let err = partitioner
+            .context_for("https://example.com/")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no browsers"));
+        assert!(partitioner.partitions().await.is_empty());
+    }
+}
@@ -0,0 +1,493 @@
+//! The individual pieces of a profile's bootstrap script.
+//!
+//! Every [`Patch`] renders one self-contained JavaScript section for a
+//! [`ChaserProfile`]. [`ChaserProfile::bootstrap_script`] concatenates them,
+//! each wrapped in its own `try` block so one failing patch cannot take the
+//! rest down with it.
+//!
+//! Patches follow the same rule as the original script: pure data and simple
+//! functions, no `makeNative`-style wrappers (Turnstile detects function
+//! wrapping).
+
+use crate::policy::StealthPolicy;
+use crate::profiles::{ChaserProfile, Os};
+use serde::{Deserialize, Serialize};
+
+/// A named section of the bootstrap script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Patch {
+    /// `hardwareConcurrency` and `deviceMemory`
+    Hardware,
+    /// `navigator.platform`
+    Platform,
+    /// `navigator.webdriver = false`
+    Webdriver,
+    /// WebGL vendor and renderer
+    WebGl,
+    /// Minimal `window.chrome`
+    ChromeObject,
+    /// Screen size, available area and window position
+    ScreenGeometry,
+    /// Web MIDI surface
+    Midi,
+    /// `navigator.bluetooth`, `usb`, `serial` and `hid`
+    DeviceApis,
+    /// `PushManager`, or no notification APIs at all on iOS. The
+    /// notification permission is set through CDP, see
+    /// [`ChaserPage::apply_profile`](crate::ChaserPage::apply_profile).
+    Notifications,
+    /// Removes `cdc_`/selenium globals
+    CdpMarkers,
+    /// Stable sub-pixel noise on `getBoundingClientRect`/`getClientRects` of
+    /// elements and ranges, seeded per profile. Opt-in (it replaces functions
+    /// pages can inspect) and not part of [`Patch::ALL`]; see
+    /// `ChaserProfileBuilder::rect_noise`.
+    ///
+    /// Only the main world sees the noise. The crate's own coordinate math
+    /// reads layout through CDP (`DOM.getBoxModel`) or the isolated world,
+    /// which both keep the true values.
+    #[cfg(feature = "rect-noise")]
+    RectNoise,
+}
+
+impl Patch {
+    /// All patches, in the order they are injected.
+    pub const ALL: &'static [Patch] = &[
+        Patch::Hardware,
+        Patch::Platform,
+        Patch::Webdriver,
+        Patch::WebGl,
+        Patch::ChromeObject,
+        Patch::ScreenGeometry,
+        Patch::Midi,
+        Patch::DeviceApis,
+        Patch::Notifications,
+        Patch::CdpMarkers,
+    ];
+
+    /// Stable identifier, e.g. for logs and policies.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Patch::Hardware => "hardware",
+            Patch::Platform => "platform",
+            Patch::Webdriver => "webdriver",
+            Patch::WebGl => "webgl",
+            Patch::ChromeObject => "chrome_object",
+            Patch::ScreenGeometry => "screen_geometry",
+            Patch::Midi => "midi",
+            Patch::DeviceApis => "device_apis",
+            Patch::Notifications => "notifications",
+            Patch::CdpMarkers => "cdp_markers",
+            #[cfg(feature = "rect-noise")]
+            Patch::RectNoise => "rect_noise",
+        }
+    }
+
+    /// The JavaScript statements of this patch for `profile`.
+    pub fn script(&self, profile: &ChaserProfile) -> String {
+        match self {
+            Patch::Hardware => format!(
+                r#"
+                Object.defineProperty(navigator, 'hardwareConcurrency', {{
+                    get: () => {cores},
+                    configurable: true, enumerable: true
+                }});
+                Object.defineProperty(navigator, 'deviceMemory', {{
+                    get: () => {memory},
+                    configurable: true, enumerable: true
+                }});"#,
+                cores = profile.cpu_cores(),
+                memory = profile.device_memory(),
+            ),
+            Patch::Platform => format!(
+                r#"
+                Object.defineProperty(navigator, 'platform', {{
+                    get: () => '{platform}',
+                    configurable: true, enumerable: true
+                }});"#,
+                platform = profile.os().platform(),
+            ),
+            Patch::Webdriver => r#"
+                Object.defineProperty(navigator, 'webdriver', {
+                    get: () => false,
+                    configurable: true, enumerable: true
+                });"#
+                .to_string(),
+            Patch::WebGl => format!(
+                r#"
+                const getParam = WebGLRenderingContext.prototype.getParameter;
+                WebGLRenderingContext.prototype.getParameter = function(p) {{
+                    if (p === 37445) return '{webgl_vendor}';
+                    if (p === 37446) return '{webgl_renderer}';
+                    return getParam.apply(this, arguments);
+                }};"#,
+                webgl_vendor = profile.gpu().vendor(),
+                webgl_renderer = profile.gpu().renderer(),
+            ),
+            Patch::ChromeObject => {
+                if profile.extensions().is_empty() {
+                    r#"
+                if (!window.chrome) {
+                    window.chrome = { runtime: {} };
+                }"#
+                    .to_string()
+                } else {
+                    // a loaded extension provides the real chrome.runtime where
+                    // it is externally connectable, a stub would shadow it
+                    r#"
+                if (!window.chrome) {
+                    window.chrome = {};
+                }"#
+                    .to_string()
+                }
+            }
+            Patch::ScreenGeometry => {
+                let (screen_left, screen_top) = profile.screen_origin();
+                let avail = profile.avail_area();
+                format!(
+                    r#"
+                const screenProps = {{
+                    width: {screen_width}, height: {screen_height},
+                    availWidth: {avail_width}, availHeight: {avail_height},
+                    availLeft: {avail_left}, availTop: {avail_top},
+                    colorDepth: {color_depth}, pixelDepth: {color_depth},
+                    isExtended: {extended}
+                }};
+                for (const [key, value] of Object.entries(screenProps)) {{
+                    if (key === 'isExtended' && !(key in Screen.prototype)) continue;
+                    Object.defineProperty(Screen.prototype, key, {{
+                        get: () => value,
+                        configurable: true, enumerable: true
+                    }});
+                }}{window}"#,
+                    screen_width = profile.screen_width(),
+                    screen_height = profile.screen_height(),
+                    avail_width = avail.width,
+                    avail_height = avail.height,
+                    avail_left = screen_left + avail.left as i32,
+                    avail_top = screen_top + avail.top as i32,
+                    color_depth = profile.color_depth(),
+                    extended = profile.extended_display(),
+                    window = crate::geometry::Geometry::resolve(profile).window_script(),
+                )
+            }
+            Patch::Midi => {
+                if profile.os() == Os::Ios {
+                    // WebKit has no Web MIDI at all
+                    r#"
+                delete Navigator.prototype.requestMIDIAccess;
+                delete window.MIDIAccess;
+                delete window.MIDIInput;
+                delete window.MIDIOutput;"#
+                        .to_string()
+                } else {
+                    // Restore the API if launch flags stripped it; without a
+                    // user granting access it rejects like a dismissed prompt
+                    r#"
+                if (!('requestMIDIAccess' in Navigator.prototype)) {
+                    Navigator.prototype.requestMIDIAccess = function requestMIDIAccess() {
+                        return new Promise((_, reject) => setTimeout(() => reject(
+                            new DOMException('Permission denied.', 'NotAllowedError')), 350));
+                    };
+                }"#
+                    .to_string()
+                }
+            }
+            Patch::DeviceApis => format!(
+                r#"
+                const deviceApis = {apis};
+                const gestureError = () => new DOMException(
+                    'Must be handling a user gesture to show a permission request.', 'SecurityError');
+                const request = () => Promise.reject(navigator.userActivation && navigator.userActivation.isActive
+                    ? new DOMException('No device selected.', 'NotFoundError')
+                    : gestureError());
+                const stubs = {{
+                    bluetooth: ['Bluetooth', {{ requestDevice: request, getAvailability: () => Promise.resolve(true) }}],
+                    usb: ['USB', {{ requestDevice: request, getDevices: () => Promise.resolve([]) }}],
+                    serial: ['Serial', {{ requestPort: request, getPorts: () => Promise.resolve([]) }}],
+                    hid: ['HID', {{ requestDevice: () => request().catch(e => e.name === 'NotFoundError' ? [] : Promise.reject(e)),
+                                    getDevices: () => Promise.resolve([]) }}]
+                }};
+                for (const [api, [iface, methods]] of Object.entries(stubs)) {{
+                    const wanted = deviceApis.includes(api) && window.isSecureContext;
+                    if (!wanted) {{
+                        delete Navigator.prototype[api];
+                        delete window[iface];
+                        continue;
+                    }}
+                    if (api in navigator) continue;
+                    // launch flags stripped it, restore a permission-gated stand-in
+                    const stub = new EventTarget();
+                    Object.assign(stub, methods);
+                    Object.defineProperty(stub, Symbol.toStringTag, {{ value: iface }});
+                    Object.defineProperty(Navigator.prototype, api, {{
+                        get: () => stub,
+                        configurable: true, enumerable: true
+                    }});
+                }}"#,
+                apis = serde_json::to_string(&device_apis(profile)).unwrap_or_default(),
+            ),
+            Patch::Notifications => {
+                if profile.os() == Os::Ios {
+                    // Only home screen web apps get notifications on iOS
+                    r#"
+                delete window.Notification;
+                delete window.PushManager;
+                delete window.PushSubscription;"#
+                        .to_string()
+                } else {
+                    // The permission state itself comes from Browser.setPermission
+                    // in apply_profile, so Notification.permission and
+                    // permissions.query stay native and agree
+                    r#"
+                if (!window.PushManager && window.isSecureContext) {
+                    window.PushManager = function PushManager() {
+                        throw new TypeError('Illegal constructor');
+                    };
+                    Object.defineProperty(window.PushManager, 'supportedContentEncodings', {
+                        value: Object.freeze(['aes128gcm', 'aesgcm']),
+                        enumerable: true
+                    });
+                }"#
+                    .to_string()
+                }
+            }
+            Patch::CdpMarkers => r#"
+                for (const p of Object.getOwnPropertyNames(window)) {
+                    if (/^cdc_|^\$cdc_|^__webdriver|^__selenium|^__driver/.test(p)) {
+                        try { delete window[p]; } catch(e) {}
+                    }
+                }"#
+            .to_string(),
+            #[cfg(feature = "rect-noise")]
+            Patch::RectNoise => format!(
+                r#"
+                // A pure function of the value, so the same layout always
+                // reads the same and left + width still equals right
+                const rectNoise = v => {{
+                    let h = Math.imul(((v * 1000) | 0) ^ {seed}, 2654435761);
+                    h ^= h >>> 15;
+                    h = Math.imul(h, 2246822519);
+                    h ^= h >>> 13;
+                    return ((h >>> 0) / 4294967296 - 0.5) * 0.01;
+                }};
+                const jitterRect = r => {{
+                    if (r.width > 0 && r.height > 0) {{
+                        const [x, y, w, h] = [r.x, r.y, r.width, r.height];
+                        r.x = x + rectNoise(x);
+                        r.y = y + rectNoise(y + 0.5);
+                        r.width = w + rectNoise(w + 0.25);
+                        r.height = h + rectNoise(h + 0.75);
+                    }}
+                    return r;
+                }};
+                for (const proto of [Element.prototype, Range.prototype]) {{
+                    const realRect = proto.getBoundingClientRect;
+                    const realRects = proto.getClientRects;
+                    proto.getBoundingClientRect = function getBoundingClientRect() {{
+                        return jitterRect(realRect.call(this));
+                    }};
+                    proto.getClientRects = function getClientRects() {{
+                        const rects = realRects.call(this);
+                        for (const r of rects) jitterRect(r);
+                        return rects;
+                    }};
+                }}"#,
+                seed = profile.rect_noise_seed().unwrap_or_default() as i32,
+            ),
+        }
+    }
+}
+
+/// The device APIs real Chrome exposes for the profile's platform and version.
+fn device_apis(profile: &ChaserProfile) -> Vec<&'static str> {
+    let version = profile.chrome_version();
+    let mut apis = Vec::new();
+    match profile.os() {
+        // WebKit exposes none of them
+        Os::Ios => {}
+        Os::Android => {
+            if version >= 56 {
+                apis.push("bluetooth");
+            }
+            if version >= 61 {
+                apis.push("usb");
+            }
+            if version >= 138 {
+                apis.push("serial");
+            }
+        }
+        Os::Windows | Os::MacOSIntel | Os::MacOSArm | Os::Linux => {
+            if version >= 56 {
+                apis.push("bluetooth");
+            }
+            if version >= 61 {
+                apis.push("usb");
+            }
+            if version >= 89 {
+                apis.push("serial");
+                apis.push("hid");
+            }
+        }
+    }
+    apis
+}
+
+/// Join `patches` into one self-invoking script, each in its own `try` and,
+/// with a non-empty `policy`, skipped on the hosts it is disabled for.
+pub(crate) fn compose(
+    profile: &ChaserProfile,
+    patches: &[Patch],
+    policy: &StealthPolicy,
+) -> String {
+    use std::fmt::Write;
+
+    // the default patches render to about 9KB
+    let mut script = String::with_capacity(12 * 1024);
+    script.push_str(
+        "\n            (function() {\n                // === MINIMAL STEALTH: Pure data, no makeNative wrappers ===\n                // Turnstile detects function wrapping - use simple arrow functions only\n",
+    );
+    if !policy.is_empty() {
+        script.push_str(&policy.prelude());
+    }
+    for patch in patches {
+        let _ = write!(
+            script,
+            "\n                // {}\n                ",
+            patch.name()
+        );
+        if !policy.is_empty() {
+            let _ = write!(script, "if (!disabledPatches.has('{}')) ", patch.name());
+        }
+        let _ = write!(
+            script,
+            "try {{{}\n                }} catch(e) {{}}\n",
+            patch.script(profile)
+        );
+    }
+    script.push_str("            })();\n            ");
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn names_are_unique() {
+        let names: HashSet<_> = Patch::ALL.iter().map(Patch::name).collect();
+        assert_eq!(names.len(), Patch::ALL.len());
+        assert_eq!(Patch::ALL.first(), Some(&Patch::Hardware));
+        assert_eq!(Patch::ALL.last(), Some(&Patch::CdpMarkers));
+    }
+
+    #[test]
+    fn device_apis_follow_platform_and_version() {
+        let apis = |builder: crate::profiles::ChaserProfileBuilder, version| {
+            device_apis(&builder.chrome_version(version).build())
+        };
+        assert_eq!(apis(ChaserProfile::windows(), 88), ["bluetooth", "usb"]);
+        assert_eq!(
+            apis(ChaserProfile::linux(), 89),
+            ["bluetooth", "usb", "serial", "hid"]
+        );
+        assert_eq!(apis(ChaserProfile::android(), 137), ["bluetooth", "usb"]);
+        assert_eq!(
+            apis(ChaserProfile::android(), 138),
+            ["bluetooth", "usb", "serial"]
+        );
+        assert!(apis(ChaserProfile::macos_arm(), 55).is_empty());
+        assert!(apis(ChaserProfile::ios(), 140).is_empty());
+    }
+
+    #[test]
+    fn scripts_render_the_profile() {
+        let windows = ChaserProfile::windows().cpu_cores(12).build();
+        assert!(Patch::Platform
+            .script(&windows)
+            .contains("get: () => 'Win32'"));
+        assert!(Patch::Hardware.script(&windows).contains("get: () => 12"));
+        assert!(Patch::WebGl
+            .script(&windows)
+            .contains(&format!("return '{}'", windows.gpu().renderer())));
+        assert!(Patch::DeviceApis
+            .script(&windows)
+            .contains(r#"const deviceApis = ["bluetooth","usb","serial","hid"];"#));
+        assert!(Patch::ChromeObject.script(&windows).contains("runtime: {}"));
+        assert!(Patch::Midi.script(&windows).contains("NotAllowedError"));
+        assert!(Patch::Notifications
+            .script(&windows)
+            .contains("PushManager"));
+
+        let with_extension = ChaserProfile::windows().extension("/ext").build();
+        assert!(!Patch::ChromeObject
+            .script(&with_extension)
+            .contains("runtime"));
+
+        let ios = ChaserProfile::ios().build();
+        assert!(Patch::Midi
+            .script(&ios)
+            .contains("delete Navigator.prototype.requestMIDIAccess"));
+        assert!(Patch::Notifications
+            .script(&ios)
+            .contains("delete window.Notification"));
+        assert!(Patch::DeviceApis
+            .script(&ios)
+            .contains("const deviceApis = [];"));
+    }
+
+    #[test]
+    fn compose_isolates_each_patch() {
+        let profile = ChaserProfile::windows().build();
+        let patches = [Patch::Webdriver, Patch::Platform];
+        let script = compose(&profile, &patches, &StealthPolicy::new());
+
+        assert_eq!(script.matches("try {").count(), 2);
+        assert_eq!(script.matches("} catch(e) {}").count(), 2);
+        let webdriver = script.find("// webdriver").unwrap();
+        let platform = script.find("// platform").unwrap();
+        assert!(webdriver < platform, "patches keep their order");
+        assert!(script.trim_start().starts_with("(function() {"));
+        assert!(script.trim_end().ends_with("})();"));
+        assert!(!script.contains("disabledPatches"));
+
+        let empty = compose(&profile, &[], &StealthPolicy::new());
+        assert!(!empty.contains("try {"));
+        assert!(empty.trim_end().ends_with("})();"));
+    }
+
+    #[test]
+    fn compose_guards_patches_with_a_policy() {
+        let profile = ChaserProfile::windows().build();
+        let policy = StealthPolicy::new().disable("example.com", Patch::Midi);
+        let script = compose(&profile, Patch::ALL, &policy);
+
+        let prelude = script.find("const disabledPatches").unwrap();
+        assert!(prelude < script.find("// hardware").unwrap());
+        // every patch is guarded, not only the disabled one, so the script
+        // is the same on every host
+        for patch in Patch::ALL {
+            assert!(script.contains(&format!(
+                "if (!disabledPatches.has('{}')) try {{",
+                patch.name()
+            )));
+        }
+        assert!(script.contains(r#"[["example.com",["midi"]]]"#));
+    }
+
+    #[cfg(feature = "rect-noise")]
+    #[test]
+    fn rect_noise_is_opt_in_and_seeded() {
+        assert!(!ChaserProfile::windows()
+            .build()
+            .patches()
+            .contains(&Patch::RectNoise));
+
+        let profile = ChaserProfile::windows().rect_noise_seed(7).build();
+        assert_eq!(profile.patches().last(), Some(&Patch::RectNoise));
+        assert!(Patch::RectNoise
+            .script(&profile)
+            .contains("^ 7, 2654435761"));
+    }
+}
@@ -0,0 +1,363 @@
+//! Typing into the hosted card fields of payment providers.
+//!
+//! Stripe Elements, Adyen, Braintree and the like keep card fields in
+//! iframes on their own origin, usually one per field. Everything else in
+//! [`ChaserPage`] targets the main frame: its isolated world cannot see
+//! into those frames, and with site isolation they even live in another
+//! renderer process with a CDP session of their own. The helpers here
+//!
+//! - find the iframes of the current page and recognise the providers
+//!   ([`ChaserPage::payment_frames`], [`ChaserPage::find_frame`]),
+//! - evaluate in an isolated world of such a frame, through its own
+//!   session when it is out of process ([`ChaserPage::evaluate_in_frame`]),
+//! - click a field inside the frame humanly, at its position in the main
+//!   viewport, and check the field got focus ([`ChaserPage::click_in_frame`]),
+//! - type into it ([`ChaserPage::type_in_frame`]). Key events go to the
+//!   focused frame, so typing itself needs no routing; the focus check of
+//!   [`type_text`](ChaserPage::type_text) catches focus leaving the frame.
+//!
+//! Only iframes of the main document are found, not frames nested in them.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let number = chaser.find_frame("card number").await?.expect("no card frame");
+//! chaser.type_in_frame(&number, "input[name=cardnumber]", "4242424242424242").await?;
+//! let expiry = chaser.find_frame("expiration").await?.expect("no expiry frame");
+//! chaser.type_in_frame(&expiry, "input[name=exp-date]", "04 / 29").await?;
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::coordinates::Coordinates;
+use crate::error::{CdpError, ChaserError, ChaserResult as Result};
+use crate::focus::FOCUS_HELPERS;
+use chromiumoxide_cdp::cdp::browser_protocol::dom::{BackendNodeId, GetBoxModelParams};
+use chromiumoxide_cdp::cdp::browser_protocol::page::{CreateIsolatedWorldParams, FrameId};
+use chromiumoxide_cdp::cdp::browser_protocol::target::{
+    AttachToTargetParams, GetTargetsParams, SessionId, TargetId,
+};
+use chromiumoxide_cdp::cdp::js_protocol::runtime::EvaluateParams;
+use rand::Rng;
+use serde_json::Value;
+
+/// Hosts serving the hosted fields of payment providers.
+pub const KNOWN_PROVIDERS: &[(&str, &[&str])] = &[
+    ("Stripe", &["js.stripe.com", "stripe.network"]),
+    ("Adyen", &["adyen.com", "adyenpayments.com"]),
+    ("Braintree", &["braintreegateway.com", "braintree-api.com"]),
+    ("Checkout.com", &["checkout.com"]),
+    ("PayPal", &["paypal.com", "paypalobjects.com"]),
+    ("Square", &["squareup.com", "squarecdn.com"]),
+    ("Worldpay", &["worldpay.com", "access.worldpay.com"]),
+    ("Klarna", &["klarna.com", "klarnacdn.net"]),
+    ("Recurly", &["recurly.com"]),
+    ("Chargebee", &["chargebee.com"]),
+    ("Mollie", &["mollie.com"]),
+    ("Spreedly", &["spreedly.com"]),
+    ("CyberSource", &["cybersource.com"]),
+    ("Authorize.net", &["authorize.net"]),
+];
+
+/// The provider serving `url`, if it is a known one.
+pub fn provider_of(url: &str) -> Option<&'static str> {
+    let host = url::Url::parse(url).ok()?.host_str()?.to_ascii_lowercase();
+    KNOWN_PROVIDERS
+        .iter()
+        .find(|(_, hosts)| {
+            hosts
+                .iter()
+                .any(|h| host == *h || host.ends_with(&format!(".{h}")))
+        })
+        .map(|(name, _)| *name)
+}
+
+/// An iframe of the main document.
+#[derive(Debug, Clone)]
+pub struct PaymentFrame {
+    pub frame_id: FrameId,
+    /// The `src` of the iframe element.
+    pub url: String,
+    pub provider: Option<&'static str>,
+    pub name: Option<String>,
+    pub title: Option<String>,
+    /// The iframe element in the main document.
+    element: BackendNodeId,
+    /// The session of the frame's own target when it is out of process.
+    session: Option<SessionId>,
+}
+
+impl PaymentFrame {
+    /// Whether the frame runs in another renderer process, with a CDP
+    /// session of its own.
+    pub fn is_out_of_process(&self) -> bool {
+        self.session.is_some()
+    }
+
+    fn matches(&self, pattern: &str) -> bool {
+        let pattern = pattern.to_lowercase();
+        [Some(&self.url), self.name.as_ref(), self.title.as_ref()]
+            .into_iter()
+            .flatten()
+            .any(|text| text.to_lowercase().contains(&pattern))
+    }
+}
+
+/// `[x, y, width, height]` of the element matching `SELECTOR` in its
+/// frame's viewport, scrolled into the frame's view first.
+const FIELD_RECT_SCRIPT: &str = r#"(() => {
+    const el = document.querySelector(SELECTOR);
+    if (!el) return null;
+    el.scrollIntoView({ block: 'nearest', inline: 'nearest' });
+    const r = el.getBoundingClientRect();
+    return [r.left, r.top, r.width, r.height];
+})()"#;
+
+impl ChaserPage {
+    /// The iframes of the main document served by a known payment
+    /// provider.
+    pub async fn payment_frames(&self) -> Result<Vec<PaymentFrame>> {
+        let mut frames = Vec::new();
+        for frame in self.iframes().await? {
+            if frame.provider.is_some() {
+                frames.push(self.connect_frame(frame).await?);
+            }
+        }
+        Ok(frames)
+    }
+
+    /// The first iframe of the main document whose URL, `name` or `title`
+    /// contains `pattern`, ignoring case; e.g. `"card number"` for Stripe's
+    /// "Secure card number input frame".
+    pub async fn find_frame(&self, pattern: &str) -> Result<Option<PaymentFrame>> {
+        let found = self
+            .iframes()
+            .await?
+            .into_iter()
+            .find(|frame| frame.matches(pattern));
+        match found {
+            Some(frame) => Ok(Some(self.connect_frame(frame).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The iframes of the main document, not connected yet.
+    async fn iframes(&self) -> Result<Vec<PaymentFrame>> {
+        let mut frames = Vec::new();
+        for element in self.raw_page().find_elements("iframe").await? {
+            let node = element.description().await?;
+            let Some(frame_id) = node.frame_id.clone() else {
+                continue;
+            };
+            let attribute = |name: &str| {
+                node.attributes
+                    .as_deref()
+                    .unwrap_or_default()
+                    .chunks(2)
+                    .find(|pair| pair[0].eq_ignore_ascii_case(name))
+                    .and_then(|pair| pair.get(1).cloned())
+            };
+            let url = attribute("src").unwrap_or_default();
+            frames.push(PaymentFrame {
+                frame_id,
+                provider: provider_of(&url),
+                url,
+                name: attribute("name"),
+                title: attribute("title"),
+                element: element.backend_node_id,
+                session: None,
+            });
+        }
+        Ok(frames)
+    }
+
+    /// Attach to the target of `frame` if it is out of process, so its
+    /// isolated worlds can be reached.
+    async fn connect_frame(&self, mut frame: PaymentFrame) -> Result<PaymentFrame> {
+        let targets = self
+            .raw_page()
+            .execute_in_session(GetTargetsParams::default(), None)
+            .await?
+            .result
+            .target_infos;
+        let out_of_process = targets
+            .iter()
+            .any(|t| t.r#type == "iframe" && t.target_id.inner() == frame.frame_id.inner());
+        if out_of_process {
+            let attach = AttachToTargetParams::builder()
+                .target_id(TargetId::new(frame.frame_id.inner().clone()))
+                .flatten(true)
+                .build()
+                .map_err(ChaserError::msg)?;
+            frame.session = Some(
+                self.raw_page()
+                    .execute_in_session(attach, None)
+                    .await?
+                    .result
+                    .session_id,
+            );
+        }
+        Ok(frame)
+    }
+
+    /// Evaluate `script` in an isolated world of `frame`, like
+    /// [`evaluate_stealth`](Self::evaluate_stealth) does in the main frame.
+    pub async fn evaluate_in_frame(
+        &self,
+        frame: &PaymentFrame,
+        script: &str,
+    ) -> Result<Option<Value>> {
+        let limit = self.timeouts().evaluate;
+        let evaluate = async {
+            let session = frame
+                .session
+                .clone()
+                .unwrap_or_else(|| self.raw_page().session_id().clone());
+            let world = CreateIsolatedWorldParams::builder()
+                .frame_id(frame.frame_id.clone())
+                .world_name(self.world_name())
+                .grant_univeral_access(true)
+                .build()
+                .map_err(ChaserError::msg)?;
+            let context = self
+                .raw_page()
+                .execute_in_session(world, Some(session.clone()))
+                .await?
+                .result
+                .execution_context_id;
+            let params = EvaluateParams::builder()
+                .expression(script)
+                .context_id(context)
+                .await_promise(true)
+                .return_by_value(true)
+                .build()
+                .map_err(ChaserError::msg)?;
+            let res = self
+                .raw_page()
+                .execute_in_session(params, Some(session))
+                .await?;
+            if let Some(details) = res.result.exception_details.clone() {
+                return Err(CdpError::JavascriptException(Box::new(details)).into());
+            }
+            Ok(res.result.result.value)
+        };
+        self.within(limit, "Frame script evaluation", evaluate)
+            .await
+    }
+
+    /// Whether `frame` has focus: it is the focused frame of the page.
+    pub async fn frame_focused(&self, frame: &PaymentFrame) -> Result<bool> {
+        Ok(self
+            .evaluate_in_frame(frame, "document.hasFocus()")
+            .await?
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false))
+    }
+
+    /// Humanized click on the element matching `selector` inside `frame`,
+    /// scrolling the page first if needed. Fails with
+    /// [`ChaserError::FocusLost`] if the element does not have focus
+    /// afterwards.
+    pub async fn click_in_frame(&self, frame: &PaymentFrame, selector: &str) -> Result<()> {
+        let selector_json = serde_json::to_string(selector)?;
+        let rect: [f64; 4] = match self
+            .evaluate_in_frame(
+                frame,
+                &FIELD_RECT_SCRIPT.replace("SELECTOR", &selector_json),
+            )
+            .await?
+        {
+            Some(value @ Value::Array(_)) => serde_json::from_value(value)?,
+            _ => return Err(ChaserError::ElementNotFound(selector.to_string())),
+        };
+        let [x, y, width, height] = rect;
+        if width * height <= 1.0 {
+            return Err(ChaserError::msg(format!("{selector} has no visible box")));
+        }
+
+        let model = self
+            .raw_page()
+            .execute(
+                GetBoxModelParams::builder()
+                    .backend_node_id(frame.element)
+                    .build(),
+            )
+            .await?
+            .result
+            .model;
+        let origin = model.content.inner();
+        let (left, top) = (origin[0], origin[1]);
+        // somewhere in the middle of the field, like a person aiming for it
+        let (fx, fy) = {
+            let mut rng = rand::thread_rng();
+            (
+                x + width * rng.gen_range(0.3..0.7),
+                y + height * rng.gen_range(0.35..0.65),
+            )
+        };
+        let state = self.viewport_state().await?;
+        let viewport = state.from_layout(crate::layout::Point::new(left + fx, top + fy));
+        self.click_at(Coordinates::Page(
+            state.to_page(Coordinates::Viewport(viewport)),
+        ))
+        .await?;
+
+        let check = format!(
+            "(() => {{{FOCUS_HELPERS}\n    const el = document.querySelector({selector_json});\n    return document.hasFocus() && deep() === el ? null : describe(deep());\n}})()"
+        );
+        match self.evaluate_in_frame(frame, &check).await? {
+            Some(Value::String(now)) => Err(ChaserError::FocusLost {
+                expected: selector.to_string(),
+                now,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Click the element matching `selector` inside `frame` and type
+    /// `text` into it with [`type_text`](Self::type_text).
+    pub async fn type_in_frame(
+        &self,
+        frame: &PaymentFrame,
+        selector: &str,
+        text: &str,
+    ) -> Result<()> {
+        self.click_in_frame(frame, selector).await?;
+        self.type_text(text).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognises_providers() {
+        assert_eq!(
+            provider_of("https://js.stripe.com/v3/elements-inner-card.html#x"),
+            Some("Stripe")
+        );
+        assert_eq!(
+            provider_of("https://checkoutshopper-live.adyen.com/checkoutshopper/securedfields/"),
+            Some("Adyen")
+        );
+        assert_eq!(
+            provider_of("https://assets.braintreegateway.com/web/hosted-fields"),
+            Some("Braintree")
+        );
+        assert_eq!(provider_of("https://notstripe.com/js.stripe.com"), None);
+        assert_eq!(provider_of("about:blank"), None);
+
+        let frame = PaymentFrame {
+            frame_id: FrameId::new("F1"),
+            url: "https://js.stripe.com/v3/".into(),
+            provider: Some("Stripe"),
+            name: Some("__privateStripeFrame5".into()),
+            title: Some("Secure card number input frame".into()),
+            element: BackendNodeId::new(3),
+            session: None,
+        };
+        assert!(frame.matches("Card Number"));
+        assert!(!frame.matches("expiration"));
+        assert!(!frame.is_out_of_process());
+    }
+}
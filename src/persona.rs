@@ -0,0 +1,388 @@
+//! Scheduling work like the person an identity pretends to be.
+//!
+//! A fleet that works around the clock at a constant rate is easy to spot no
+//! matter how good each fingerprint is: people sleep, work in sessions, take
+//! breaks and only do so much per day. A [`Persona`] describes those habits
+//! for one identity (profile and proxy): active hours in its local time,
+//! session and break lengths and a daily task cap. [`run_personas`] runs each
+//! persona's tasks one after another within those limits, all personas
+//! concurrently on one [`BrowserPool`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::orchestrator::Task;
+//! use chaser_oxide::persona::{self, Persona};
+//! use std::time::Duration;
+//!
+//! let alice = Persona::new("alice")
+//!     .profile(ChaserProfile::windows().timezone("Europe/Berlin").build())
+//!     .proxy("http://de.proxy:8080")
+//!     .utc_offset_minutes(60)
+//!     .active_hours(8, 12)
+//!     .active_hours(14, 22)
+//!     .sessions(Duration::from_secs(20 * 60), Duration::from_secs(50 * 60))
+//!     .breaks(Duration::from_secs(10 * 60), Duration::from_secs(40 * 60))
+//!     .daily_cap(30);
+//! let tasks = vec![Task::new("inbox", |page| async move { page.goto("https://mail.example.com").await })];
+//!
+//! let summary = persona::run_personas(&pool, vec![(alice, tasks)], |_| {}).await;
+//! ```
+//!
+//! [`BrowserPool`]: crate::pool::BrowserPool
+
+use crate::clock;
+use crate::orchestrator::{self, Progress, RunSummary, Task};
+use crate::pool::BrowserPool;
+use crate::profiles::ChaserProfile;
+use futures::StreamExt;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const MINUTES_PER_DAY: i64 = 24 * 60;
+
+/// The working habits of one identity.
+#[derive(Debug, Clone)]
+pub struct Persona {
+    name: String,
+    profile: Option<ChaserProfile>,
+    proxy: Option<String>,
+    utc_offset_minutes: i32,
+    /// Local `(start, end)` minutes since midnight.
+    active: Vec<(u32, u32)>,
+    edge_jitter_minutes: u32,
+    session_length: (Duration, Duration),
+    break_length: (Duration, Duration),
+    task_gap: (Duration, Duration),
+    daily_cap: Option<u32>,
+}
+
+impl Persona {
+    /// A persona active 8:00-23:00 UTC in sessions of 15-60 minutes.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            profile: None,
+            proxy: None,
+            utc_offset_minutes: 0,
+            active: Vec::new(),
+            edge_jitter_minutes: 20,
+            session_length: (Duration::from_secs(15 * 60), Duration::from_secs(60 * 60)),
+            break_length: (Duration::from_secs(5 * 60), Duration::from_secs(45 * 60)),
+            task_gap: (Duration::from_secs(5), Duration::from_secs(90)),
+            daily_cap: None,
+        }
+    }
+
+    /// Run this persona's tasks with `profile`.
+    pub fn profile(mut self, profile: ChaserProfile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    /// Route this persona's tasks through `proxy`.
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// Offset of the persona's local time from UTC, e.g. `-300` for New York
+    /// in winter. Should agree with the profile's timezone.
+    pub fn utc_offset_minutes(mut self, minutes: i32) -> Self {
+        self.utc_offset_minutes = minutes;
+        self
+    }
+
+    /// Add an active window from `start_hour` to `end_hour` local time. An
+    /// `end_hour` before `start_hour` crosses midnight. Without any window the
+    /// persona is active 8:00-23:00.
+    pub fn active_hours(mut self, start_hour: u32, end_hour: u32) -> Self {
+        self.active
+            .push(((start_hour % 24) * 60, (end_hour % 24) * 60));
+        self
+    }
+
+    /// Shift window edges by up to this many minutes, differently each day
+    /// (default: 20).
+    pub fn edge_jitter_minutes(mut self, minutes: u32) -> Self {
+        self.edge_jitter_minutes = minutes;
+        self
+    }
+
+    /// Range of session lengths.
+    pub fn sessions(mut self, min: Duration, max: Duration) -> Self {
+        self.session_length = (min, max.max(min));
+        self
+    }
+
+    /// Range of break lengths between sessions.
+    pub fn breaks(mut self, min: Duration, max: Duration) -> Self {
+        self.break_length = (min, max.max(min));
+        self
+    }
+
+    /// Range of pauses between two tasks of a session.
+    pub fn task_gaps(mut self, min: Duration, max: Duration) -> Self {
+        self.task_gap = (min, max.max(min));
+        self
+    }
+
+    /// At most `tasks` per local day.
+    pub fn daily_cap(mut self, tasks: u32) -> Self {
+        self.daily_cap = Some(tasks);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Local day number and minute of the day at `at`.
+    fn local_time(&self, at: SystemTime) -> (i64, i64) {
+        let secs = match at.duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_secs() as i64,
+            Err(e) => -(e.duration().as_secs() as i64),
+        };
+        let minutes = secs.div_euclid(60) + self.utc_offset_minutes as i64;
+        (
+            minutes.div_euclid(MINUTES_PER_DAY),
+            minutes.rem_euclid(MINUTES_PER_DAY),
+        )
+    }
+
+    /// The active windows of local `day` as minute ranges relative to that
+    /// day's midnight; windows crossing midnight end after 1440.
+    fn windows(&self, day: i64) -> Vec<(i64, i64)> {
+        let default = [(8 * 60, 23 * 60)];
+        let windows = if self.active.is_empty() {
+            &default[..]
+        } else {
+            &self.active[..]
+        };
+        let mut hasher = DefaultHasher::new();
+        (&self.name, day).hash(&mut hasher);
+        let mut rng = StdRng::seed_from_u64(hasher.finish());
+        let jitter = self.edge_jitter_minutes as i64;
+        windows
+            .iter()
+            .map(|&(start, end)| {
+                let (start, mut end) = (start as i64, end as i64);
+                if end <= start {
+                    end += MINUTES_PER_DAY;
+                }
+                let start = start + rng.gen_range(-jitter..=jitter);
+                let end = end + rng.gen_range(-jitter..=jitter);
+                (start, end.max(start + 1))
+            })
+            .collect()
+    }
+
+    /// How long from `at` until the persona is next active; zero if it is
+    /// active now.
+    pub fn until_active(&self, at: SystemTime) -> Duration {
+        let (day, minute) = self.local_time(at);
+        // yesterday's windows may cross midnight into today
+        let mut best: Option<i64> = None;
+        for offset in -1..=7 {
+            for (start, end) in self.windows(day + offset) {
+                let (start, end) = (
+                    start + offset * MINUTES_PER_DAY,
+                    end + offset * MINUTES_PER_DAY,
+                );
+                if (start..end).contains(&minute) {
+                    return Duration::ZERO;
+                }
+                if start > minute {
+                    best = Some(best.map_or(start, |b| b.min(start)));
+                }
+            }
+        }
+        let wait = best.map_or(MINUTES_PER_DAY, |start| start - minute);
+        Duration::from_secs(wait as u64 * 60)
+    }
+
+    pub fn is_active(&self, at: SystemTime) -> bool {
+        self.until_active(at).is_zero()
+    }
+}
+
+/// A persona's progress through its day.
+struct PersonaState {
+    persona: Persona,
+    rng: StdRng,
+    day: i64,
+    done_today: u32,
+    session_ends: Option<Instant>,
+}
+
+fn sample(rng: &mut StdRng, (min, max): (Duration, Duration)) -> Duration {
+    if max <= min {
+        return min;
+    }
+    rng.gen_range(min..=max)
+}
+
+impl PersonaState {
+    fn new(persona: Persona) -> Self {
+        let (day, _) = persona.local_time(clock::system_time());
+        Self {
+            persona,
+            rng: StdRng::from_entropy(),
+            day,
+            done_today: 0,
+            session_ends: None,
+        }
+    }
+
+    /// Sleep until the persona may start its next task.
+    async fn wait_for_turn(&mut self) {
+        loop {
+            let now = clock::system_time();
+            let (day, _) = self.persona.local_time(now);
+            if day != self.day {
+                self.day = day;
+                self.done_today = 0;
+            }
+            let wait = self.persona.until_active(now);
+            if !wait.is_zero() {
+                self.session_ends = None;
+                clock::sleep(wait).await;
+                continue;
+            }
+            if self
+                .persona
+                .daily_cap
+                .is_some_and(|cap| self.done_today >= cap)
+            {
+                // done for today: sleep into tomorrow's first window
+                let (_, minute) = self.persona.local_time(now);
+                let midnight = Duration::from_secs((MINUTES_PER_DAY - minute) as u64 * 60);
+                self.session_ends = None;
+                clock::sleep(midnight).await;
+                continue;
+            }
+            match self.session_ends {
+                None => {
+                    let length = sample(&mut self.rng, self.persona.session_length);
+                    self.session_ends = Some(clock::now() + length);
+                    return;
+                }
+                Some(ends) if clock::now() >= ends => {
+                    self.session_ends = None;
+                    clock::sleep(sample(&mut self.rng, self.persona.break_length)).await;
+                }
+                Some(_) => {
+                    clock::sleep(sample(&mut self.rng, self.persona.task_gap)).await;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Run every persona's tasks in order, within the persona's active hours,
+/// sessions and daily cap, and with its profile and proxy.
+///
+/// Personas run concurrently; `on_progress` is called every time a task
+/// finishes. Outcomes are returned in submission order across all personas.
+pub async fn run_personas<T, P>(
+    pool: &BrowserPool,
+    work: Vec<(Persona, Vec<Task<T>>)>,
+    mut on_progress: P,
+) -> RunSummary<T>
+where
+    T: 'static,
+    P: FnMut(&Progress),
+{
+    let started = Instant::now();
+    let mut progress = Progress {
+        total: work.iter().map(|(_, tasks)| tasks.len()).sum(),
+        ..Default::default()
+    };
+
+    let mut next_idx = 0;
+    let streams = work.into_iter().map(|(persona, tasks)| {
+        let tasks: Vec<(usize, Task<T>)> = tasks
+            .into_iter()
+            .map(|mut task| {
+                if let Some(profile) = &persona.profile {
+                    task.assignment.profile = Some(profile.clone());
+                }
+                if let Some(proxy) = &persona.proxy {
+                    task.assignment.proxy = Some(proxy.clone());
+                }
+                next_idx += 1;
+                (next_idx - 1, task)
+            })
+            .collect();
+        let state = PersonaState::new(persona);
+        futures::stream::unfold(
+            (state, tasks.into_iter()),
+            move |(mut state, mut tasks)| async move {
+                let (idx, task) = tasks.next()?;
+                state.wait_for_turn().await;
+                let outcome = orchestrator::run_task(pool, task, None).await;
+                state.done_today += 1;
+                Some(((idx, outcome), (state, tasks)))
+            },
+        )
+        .boxed_local()
+    });
+    let mut stream = futures::stream::select_all(streams);
+
+    let mut outcomes = Vec::with_capacity(progress.total);
+    while let Some((idx, outcome)) = stream.next().await {
+        progress.completed += 1;
+        if outcome.is_ok() {
+            progress.succeeded += 1;
+        } else {
+            progress.failed += 1;
+        }
+        progress.last_task = outcome.name.clone();
+        on_progress(&progress);
+        outcomes.push((idx, outcome));
+    }
+    outcomes.sort_by_key(|(idx, _)| *idx);
+
+    RunSummary {
+        outcomes: outcomes.into_iter().map(|(_, o)| o).collect(),
+        elapsed: started.elapsed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn active_hours_follow_local_time() {
+        // 2024-01-01 00:00 UTC, a Monday
+        let midnight_utc = UNIX_EPOCH + Duration::from_secs(1_704_067_200);
+        let berlin = Persona::new("berlin")
+            .utc_offset_minutes(60)
+            .active_hours(9, 17)
+            .edge_jitter_minutes(0);
+
+        // 08:30 UTC is 09:30 in Berlin
+        assert!(berlin.is_active(midnight_utc + Duration::from_secs(8 * 3600 + 1800)));
+        // 07:00 UTC is 08:00 in Berlin, one hour before the window opens
+        assert_eq!(
+            berlin.until_active(midnight_utc + Duration::from_secs(7 * 3600)),
+            Duration::from_secs(3600)
+        );
+        // 16:00 UTC is 17:00 in Berlin: closed until 09:00 tomorrow
+        assert_eq!(
+            berlin.until_active(midnight_utc + Duration::from_secs(16 * 3600)),
+            Duration::from_secs(16 * 3600)
+        );
+
+        let night_owl = Persona::new("owl")
+            .active_hours(22, 2)
+            .edge_jitter_minutes(0);
+        assert!(night_owl.is_active(midnight_utc + Duration::from_secs(3600)));
+        assert!(!night_owl.is_active(midnight_utc + Duration::from_secs(3 * 3600)));
+    }
+}
@@ -0,0 +1,179 @@
+//! Per-origin exceptions to the bootstrap patches.
+//!
+//! A patch that is right for most sites can break one (a web app that
+//! talks to MIDI devices, a bank that checks `navigator.hid`), and identical
+//! behavior on every origin is a correlation vector of its own. A
+//! [`StealthPolicy`] turns individual [`Patch`]es off for matching hosts.
+//! The rules are compiled into the bootstrap script, which checks them
+//! against `location.hostname` at the start of every document, so they
+//! hold for redirects, links and iframes as well as for `goto`.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::patches::Patch;
+//! use chaser_oxide::policy::StealthPolicy;
+//!
+//! let policy = StealthPolicy::new()
+//!     .disable("app.example.com", Patch::Midi)
+//!     .disable("bank.example", Patch::DeviceApis);
+//! chaser.set_stealth_policy(policy).await?;
+//! chaser.apply_profile(&profile).await?;
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::ChaserResult as Result;
+use crate::patches::Patch;
+use chromiumoxide_cdp::cdp::browser_protocol::page::{
+    AddScriptToEvaluateOnNewDocumentParams, RemoveScriptToEvaluateOnNewDocumentParams,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Patches to leave out per host.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StealthPolicy {
+    /// Host pattern to the patches disabled there.
+    rules: BTreeMap<String, BTreeSet<Patch>>,
+}
+
+/// Whether `pattern` covers `host`: the host itself and its subdomains, or
+/// every host for `*`.
+pub fn host_matches(pattern: &str, host: &str) -> bool {
+    pattern == "*"
+        || host.eq_ignore_ascii_case(pattern)
+        || host
+            .len()
+            .checked_sub(pattern.len() + 1)
+            .is_some_and(|dot| {
+                host.as_bytes()[dot] == b'.' && host[dot + 1..].eq_ignore_ascii_case(pattern)
+            })
+}
+
+impl StealthPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Leave `patch` out on `host` and its subdomains (`*` for all hosts).
+    pub fn disable(mut self, host: impl Into<String>, patch: Patch) -> Self {
+        let host = host.into().trim().trim_start_matches("*.").to_string();
+        self.rules.entry(host).or_default().insert(patch);
+        self
+    }
+
+    /// Leave every patch out on `host` and its subdomains.
+    pub fn disable_all(mut self, host: impl Into<String>) -> Self {
+        let host = host.into().trim().trim_start_matches("*.").to_string();
+        self.rules
+            .entry(host)
+            .or_default()
+            .extend(Patch::ALL.iter().copied());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The patches disabled on `host`.
+    pub fn disabled_for(&self, host: &str) -> BTreeSet<Patch> {
+        self.rules
+            .iter()
+            .filter(|(pattern, _)| host_matches(pattern, host))
+            .flat_map(|(_, patches)| patches.iter().copied())
+            .collect()
+    }
+
+    /// Script defining `disabledPatches`, the names of the patches this
+    /// policy disables on the current document's host.
+    pub(crate) fn prelude(&self) -> String {
+        let rules: Vec<(&str, Vec<&str>)> = self
+            .rules
+            .iter()
+            .map(|(pattern, patches)| (pattern.as_str(), patches.iter().map(Patch::name).collect()))
+            .collect();
+        format!(
+            r#"
+                const disabledPatches = new Set();
+                const policyHost = location.hostname.toLowerCase();
+                for (const [pattern, names] of {rules}) {{
+                    const p = pattern.toLowerCase();
+                    if (p === '*' || policyHost === p || policyHost.endsWith('.' + p)) {{
+                        names.forEach(name => disabledPatches.add(name));
+                    }}
+                }}
+"#,
+            rules = serde_json::to_string(&rules).unwrap_or_else(|_| "[]".to_string()),
+        )
+    }
+}
+
+impl ChaserPage {
+    /// The per-origin patch exceptions of this page.
+    pub fn stealth_policy(&self) -> StealthPolicy {
+        self.policy_state().lock().unwrap().0.clone()
+    }
+
+    /// Replace the per-origin patch exceptions.
+    ///
+    /// Applies to the profile applied next, or, if one is applied already,
+    /// regenerates its bootstrap script for documents loaded from now on.
+    pub async fn set_stealth_policy(&self, policy: StealthPolicy) -> Result<()> {
+        let installed = {
+            let mut state = self.policy_state().lock().unwrap();
+            state.0 = policy.clone();
+            state.1.take()
+        };
+        let Some(old) = installed else {
+            return Ok(());
+        };
+        let Some(profile) = self.profile() else {
+            return Ok(());
+        };
+        self.raw_page()
+            .execute(RemoveScriptToEvaluateOnNewDocumentParams::new(old))
+            .await?;
+        let identifier = self
+            .raw_page()
+            .execute(AddScriptToEvaluateOnNewDocumentParams {
+                source: self.bootstrap_source(&profile, &policy),
+                world_name: None,
+                include_command_line_api: None,
+                run_immediately: None,
+            })
+            .await?
+            .result
+            .identifier;
+        self.policy_state().lock().unwrap().1 = Some(identifier);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiles::ChaserProfile;
+
+    #[test]
+    fn rules_cover_hosts_and_subdomains_only() {
+        assert!(host_matches("example.com", "example.com"));
+        assert!(host_matches("example.com", "app.Example.com"));
+        assert!(!host_matches("example.com", "badexample.com"));
+        assert!(host_matches("*", "anything.test"));
+
+        let policy = StealthPolicy::new()
+            .disable("*.example.com", Patch::WebGl)
+            .disable("example.com", Patch::Midi);
+        assert_eq!(
+            policy.disabled_for("www.example.com"),
+            BTreeSet::from([Patch::WebGl, Patch::Midi])
+        );
+        assert!(policy.disabled_for("example.org").is_empty());
+
+        let script = ChaserProfile::windows()
+            .build()
+            .bootstrap_script_with_policy(&policy);
+        assert!(script.contains("if (!disabledPatches.has('webgl'))"));
+    }
+}
@@ -0,0 +1,737 @@
+//! A pool of stealth browsers that many concurrent flows can lease pages from.
+//!
+//! Every leased page lives in its own browser context, so two tasks running in
+//! the same browser process never share cookies, storage or cache, and each
+//! context can be routed through its own proxy.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::pool::{BrowserPool, PoolConfig, TaskAssignment};
+//! use chaser_oxide::ChaserProfile;
+//!
+//! let pool = BrowserPool::launch(
+//!     PoolConfig::builder()
+//!         .browsers(2)
+//!         .profile(ChaserProfile::windows().build())
+//!         .proxy("http://10.0.0.1:8080")
+//!         .build(),
+//! )
+//! .await?;
+//!
+//! let title = pool
+//!     .with_page(TaskAssignment::default(), |page| async move {
+//!         page.goto("https://example.com").await?;
+//!         Ok(page.evaluate("document.title").await?)
+//!     })
+//!     .await?;
+//! pool.close().await?;
+//! ```
+//!
+//! [`BrowserPool::with_page`] hands the page back however the task ends,
+//! with an error, a panic or by being dropped. Pages leased with
+//! [`BrowserPool::acquire`] must be released by hand, on every path.
+
+use crate::browser::{Browser, BrowserConfig};
+use crate::chaser::ChaserPage;
+use crate::context::ChaserContext;
+use crate::error::{ChaserError, ChaserResult as Result};
+use crate::metrics::Metrics;
+use crate::profiles::ChaserProfile;
+use crate::rotation::IdentityRotation;
+use chromiumoxide_cdp::cdp::browser_protocol::browser::BrowserContextId;
+use futures::FutureExt;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Configuration of a [`BrowserPool`].
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    /// Number of browser processes to launch.
+    pub(crate) browsers: usize,
+    /// Maximum number of tasks running at the same time.
+    pub(crate) concurrency: usize,
+    /// Profiles handed out round-robin to tasks without an explicit profile.
+    pub(crate) profiles: Vec<ChaserProfile>,
+    /// Proxies handed out round-robin to tasks without an explicit proxy.
+    pub(crate) proxies: Vec<String>,
+    /// Whether the browsers are launched with a visible window.
+    pub(crate) headed: bool,
+    /// Path to the browser binary, auto detected if unset.
+    pub(crate) executable: Option<PathBuf>,
+    /// Where finished tasks are counted.
+    pub(crate) metrics: Option<Metrics>,
+    /// How the orchestrator responds to blocks.
+    pub(crate) rotation: Option<IdentityRotation>,
+}
+
+impl PoolConfig {
+    pub fn builder() -> PoolConfigBuilder {
+        PoolConfigBuilder::default()
+    }
+
+    /// Number of browser processes in the pool.
+    pub fn browsers(&self) -> usize {
+        self.browsers
+    }
+
+    /// Maximum number of tasks running at the same time.
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Profiles used for tasks without an explicit assignment.
+    pub fn profiles(&self) -> &[ChaserProfile] {
+        &self.profiles
+    }
+
+    /// Proxies used for tasks without an explicit assignment.
+    pub fn proxies(&self) -> &[String] {
+        &self.proxies
+    }
+
+    /// Metrics the orchestrator counts finished tasks in.
+    pub fn metrics(&self) -> Option<&Metrics> {
+        self.metrics.as_ref()
+    }
+
+    /// How the orchestrator rotates proxies and profiles on blocks.
+    pub fn rotation(&self) -> Option<&IdentityRotation> {
+        self.rotation.as_ref()
+    }
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// Builder for [`PoolConfig`].
+#[derive(Debug, Clone)]
+pub struct PoolConfigBuilder {
+    browsers: usize,
+    concurrency: Option<usize>,
+    profiles: Vec<ChaserProfile>,
+    proxies: Vec<String>,
+    headed: bool,
+    executable: Option<PathBuf>,
+    metrics: Option<Metrics>,
+    rotation: Option<IdentityRotation>,
+}
+
+impl Default for PoolConfigBuilder {
+    fn default() -> Self {
+        Self {
+            browsers: 1,
+            concurrency: None,
+            profiles: Vec::new(),
+            proxies: Vec::new(),
+            headed: false,
+            executable: None,
+            metrics: None,
+            rotation: None,
+        }
+    }
+}
+
+impl PoolConfigBuilder {
+    /// Number of browser processes to launch (default: 1)
+    pub fn browsers(mut self, browsers: usize) -> Self {
+        self.browsers = browsers.max(1);
+        self
+    }
+
+    /// Maximum number of concurrently running tasks (default: 4 per browser)
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = Some(concurrency.max(1));
+        self
+    }
+
+    /// Add a profile to the round-robin rotation
+    pub fn profile(mut self, profile: ChaserProfile) -> Self {
+        self.profiles.push(profile);
+        self
+    }
+
+    /// Add several profiles to the round-robin rotation
+    pub fn profiles(mut self, profiles: impl IntoIterator<Item = ChaserProfile>) -> Self {
+        self.profiles.extend(profiles);
+        self
+    }
+
+    /// Add a proxy (`scheme://host:port`) to the round-robin rotation
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxies.push(proxy.into());
+        self
+    }
+
+    /// Add several proxies to the round-robin rotation
+    pub fn proxies<I, S>(mut self, proxies: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.proxies.extend(proxies.into_iter().map(Into::into));
+        self
+    }
+
+    /// Launch the browsers with a visible window
+    pub fn with_head(mut self) -> Self {
+        self.headed = true;
+        self
+    }
+
+    /// Use a specific browser binary instead of auto detection
+    pub fn chrome_executable(mut self, path: impl Into<PathBuf>) -> Self {
+        self.executable = Some(path.into());
+        self
+    }
+
+    /// Count every task run on the pool in `metrics`
+    pub fn metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Rotate proxies and profiles and cool domains down as `rotation`
+    /// decides, for tasks the orchestrator runs. See [`crate::rotation`].
+    pub fn rotation(mut self, rotation: IdentityRotation) -> Self {
+        self.rotation = Some(rotation);
+        self
+    }
+
+    pub fn build(self) -> PoolConfig {
+        let profiles = if self.profiles.is_empty() {
+            vec![ChaserProfile::default()]
+        } else {
+            self.profiles
+        };
+        PoolConfig {
+            browsers: self.browsers,
+            concurrency: self.concurrency.unwrap_or(self.browsers * 4),
+            profiles,
+            proxies: self.proxies,
+            headed: self.headed,
+            executable: self.executable,
+            metrics: self.metrics,
+            rotation: self.rotation,
+        }
+    }
+}
+
+/// Profile and proxy a task should run with.
+///
+/// Unset fields are filled in from the pool's round-robin rotation.
+#[derive(Debug, Clone, Default)]
+pub struct TaskAssignment {
+    pub profile: Option<ChaserProfile>,
+    pub proxy: Option<String>,
+}
+
+impl TaskAssignment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn profile(mut self, profile: ChaserProfile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+}
+
+impl From<ChaserProfile> for TaskAssignment {
+    fn from(profile: ChaserProfile) -> Self {
+        Self::new().profile(profile)
+    }
+}
+
+/// A page leased from a [`BrowserPool`].
+///
+/// The page runs in a dedicated browser context which is disposed when the
+/// lease is handed back with [`BrowserPool::release`].
+#[derive(Debug)]
+pub struct PooledPage {
+    page: ChaserPage,
+    context: ChaserContext,
+    browser: usize,
+    /// Counts towards [`BrowserPool::leased`] until the lease is dropped.
+    _lease: Arc<()>,
+}
+
+impl PooledPage {
+    /// The stealth page, with the assigned profile already applied.
+    pub fn page(&self) -> &ChaserPage {
+        &self.page
+    }
+
+    /// The profile applied to the page.
+    pub fn profile(&self) -> &ChaserProfile {
+        self.context.profile()
+    }
+
+    /// The proxy the page's context is routed through, if any.
+    pub fn proxy(&self) -> Option<&str> {
+        self.context.proxy()
+    }
+
+    /// Index of the browser process hosting this page.
+    pub fn browser_index(&self) -> usize {
+        self.browser
+    }
+
+    /// The browser context the page lives in.
+    pub fn context_id(&self) -> &BrowserContextId {
+        self.context.id()
+    }
+
+    /// Close the page and dispose its browser context.
+    async fn dispose(&self) -> Result<()> {
+        let _ = self.page.raw_page().clone().close().await;
+        Ok(self.context.clone().dispose().await?)
+    }
+}
+
+/// Disposes a lease whose task was dropped or panicked before handing it
+/// back.
+struct LeaseGuard(Option<PooledPage>);
+
+impl LeaseGuard {
+    /// Dispose the lease now. Should this be dropped halfway, the drop
+    /// tries again.
+    async fn release(&mut self) -> Result<()> {
+        let Some(lease) = &self.0 else {
+            return Ok(());
+        };
+        let result = lease.dispose().await;
+        self.0 = None;
+        result
+    }
+}
+
+impl Drop for LeaseGuard {
+    fn drop(&mut self) {
+        let Some(lease) = self.0.take() else {
+            return;
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => {
+                runtime.spawn(async move {
+                    if let Err(e) = lease.dispose().await {
+                        tracing::warn!("failed to release an abandoned page: {}", e);
+                    }
+                });
+            }
+            // the context goes with the browser
+            Err(_) => tracing::warn!("page abandoned outside of a tokio runtime"),
+        }
+    }
+}
+
+/// A fixed set of launched browsers handing out isolated, profiled pages.
+#[derive(Debug)]
+pub struct BrowserPool {
+    browsers: Vec<Arc<Browser>>,
+    /// The user data dir created for each browser, deleted once the
+    /// browser is gone.
+    user_data_dirs: Vec<Option<PathBuf>>,
+    config: PoolConfig,
+    next: AtomicUsize,
+    /// One strong reference per outstanding lease, besides this one.
+    leases: Arc<()>,
+}
+
+impl BrowserPool {
+    /// Launch all browsers of the pool.
+    ///
+    /// Each browser gets its own temporary user data dir so the processes
+    /// do not fight over the same profile lock. The dirs are deleted when
+    /// the pool is closed or dropped.
+    pub async fn launch(config: PoolConfig) -> Result<Self> {
+        let mut pool = Self::from_browsers(Vec::new(), config);
+        for idx in 0..pool.config.browsers {
+            let profile = &pool.config.profiles[idx % pool.config.profiles.len()];
+            let user_data_dir =
+                std::env::temp_dir().join(format!("chaser-pool-{}", uuid::Uuid::new_v4()));
+            let mut builder = profile
+                .configure_browser(BrowserConfig::builder())
+                .user_data_dir(&user_data_dir);
+            if pool.config.headed {
+                builder = builder.with_head();
+            }
+            if let Some(executable) = &pool.config.executable {
+                builder = builder.chrome_executable(executable);
+            }
+            let browser_config = builder.build().map_err(ChaserError::msg)?;
+
+            // a failed launch drops the pool, which cleans up the dirs so far
+            pool.user_data_dirs.push(Some(user_data_dir));
+            let (browser, handler) = Browser::launch(browser_config).await?;
+            // runs until the browser is closed
+            handler.spawn();
+            pool.browsers.push(Arc::new(browser));
+        }
+        Ok(pool)
+    }
+
+    /// A pool over browsers that are already running, which own no user
+    /// data dirs.
+    pub(crate) fn from_browsers(browsers: Vec<Browser>, config: PoolConfig) -> Self {
+        Self {
+            user_data_dirs: browsers.iter().map(|_| None).collect(),
+            browsers: browsers.into_iter().map(Arc::new).collect(),
+            config,
+            next: AtomicUsize::new(0),
+            leases: Arc::new(()),
+        }
+    }
+
+    /// The configuration the pool was launched with.
+    pub fn config(&self) -> &PoolConfig {
+        &self.config
+    }
+
+    /// Number of browsers in the pool.
+    pub fn len(&self) -> usize {
+        self.browsers.len()
+    }
+
+    /// Whether the pool has no browsers (only after a failed launch).
+    pub fn is_empty(&self) -> bool {
+        self.browsers.is_empty()
+    }
+
+    /// Number of pages leased and not handed back yet.
+    pub fn leased(&self) -> usize {
+        Arc::strong_count(&self.leases) - 1
+    }
+
+    /// Lease a new page.
+    ///
+    /// Browsers are picked round-robin. The page gets a fresh browser context
+    /// (routed through the assigned proxy, if any) and the assigned profile is
+    /// applied before it is returned.
+    pub async fn acquire(&self, assignment: TaskAssignment) -> Result<PooledPage> {
+        if self.browsers.is_empty() {
+            return Err(ChaserError::msg("Browser pool has no browsers"));
+        }
+        let slot = self.next.fetch_add(1, Ordering::Relaxed);
+        let browser_idx = slot % self.browsers.len();
+        let profile = assignment
+            .profile
+            .unwrap_or_else(|| self.config.profiles[slot % self.config.profiles.len()].clone());
+        let proxy = assignment.proxy.or_else(|| {
+            (!self.config.proxies.is_empty())
+                .then(|| self.config.proxies[slot % self.config.proxies.len()].clone())
+        });
+
+        let context =
+            ChaserContext::create(self.browsers[browser_idx].clone(), profile, proxy).await?;
+        let page = match context.new_page().await {
+            Ok(page) => page,
+            Err(e) => {
+                let _ = context.dispose().await;
+                return Err(e.into());
+            }
+        };
+
+        Ok(PooledPage {
+            page,
+            context,
+            browser: browser_idx,
+            _lease: self.leases.clone(),
+        })
+    }
+
+    /// The launched browsers, in launch order.
+    pub(crate) fn browsers(&self) -> &[Arc<Browser>] {
+        &self.browsers
+    }
+
+    /// Hand a leased page back, closing it and disposing its browser context.
+    pub async fn release(&self, lease: PooledPage) -> Result<()> {
+        lease.dispose().await
+    }
+
+    /// Lease a page for the duration of `task` and hand it back when the
+    /// task ends.
+    ///
+    /// The page is closed and its context disposed whichever way the task
+    /// ends: returning, failing, panicking (the panic resumes once the
+    /// page is released) or being dropped, e.g. by a timeout or `select!`,
+    /// in which case the release runs on a background task. An early
+    /// return cannot leave a target behind.
+    ///
+    /// `assignment` can also be a bare [`ChaserProfile`]. The task's error
+    /// wins over a failure to release the page.
+    pub async fn with_page<F, Fut, T>(
+        &self,
+        assignment: impl Into<TaskAssignment>,
+        task: F,
+    ) -> Result<T>
+    where
+        F: FnOnce(ChaserPage) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let (result, released) = self
+            .with_lease(assignment.into(), |lease| task(lease.page.clone()))
+            .await?;
+        let value = result?;
+        released?;
+        Ok(value)
+    }
+
+    /// Like [`with_page`](Self::with_page), handing `task` the whole lease
+    /// and returning its output next to the result of the release.
+    pub(crate) async fn with_lease<F, Fut, T>(
+        &self,
+        assignment: TaskAssignment,
+        task: F,
+    ) -> Result<(T, Result<()>)>
+    where
+        F: FnOnce(&PooledPage) -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let mut guard = LeaseGuard(Some(self.acquire(assignment).await?));
+        let run = task(guard.0.as_ref().expect("just leased"));
+        let outcome = AssertUnwindSafe(run).catch_unwind().await;
+        let released = guard.release().await;
+        match outcome {
+            Ok(output) => Ok((output, released)),
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+
+    /// Close every browser of the pool, wait for the processes to exit and
+    /// delete their user data dirs.
+    ///
+    /// Outstanding leases keep their browser alive; release them first.
+    /// Every browser that can be closed is, and the ones still leased, or
+    /// that failed to close, are reported together afterwards. The user
+    /// data dir of a leased browser is left behind.
+    pub async fn close(mut self) -> Result<()> {
+        let browsers = std::mem::take(&mut self.browsers);
+        let mut leased = Vec::new();
+        let mut failed = Vec::new();
+        for (idx, browser) in browsers.into_iter().enumerate() {
+            let Ok(mut browser) = Arc::try_unwrap(browser) else {
+                leased.push(idx);
+                continue;
+            };
+            let closed = match browser.close().await {
+                Ok(_) => browser.wait().await.map(drop).map_err(Into::into),
+                Err(e) => Err(e),
+            };
+            match closed {
+                Ok(()) => remove_user_data_dir(self.user_data_dirs[idx].take()),
+                Err(e) => failed.push(format!("browser {idx}: {e}")),
+            }
+        }
+        // the leased browsers may still write to their dirs
+        for &idx in &leased {
+            self.user_data_dirs[idx] = None;
+        }
+
+        let mut problems = Vec::new();
+        if !leased.is_empty() {
+            problems.push(format!(
+                "browsers {leased:?} are still in use by leased pages"
+            ));
+        }
+        problems.extend(failed);
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ChaserError::msg(format!(
+                "Failed to close the pool: {}",
+                problems.join("; ")
+            )))
+        }
+    }
+}
+
+impl Drop for BrowserPool {
+    fn drop(&mut self) {
+        let browsers = std::mem::take(&mut self.browsers);
+        for (idx, browser) in browsers.into_iter().enumerate() {
+            // a leased browser outlives the pool and keeps its dir; any
+            // other is killed on drop, before its dir goes
+            if Arc::try_unwrap(browser).is_err() {
+                self.user_data_dirs[idx] = None;
+            }
+        }
+        for dir in &mut self.user_data_dirs {
+            remove_user_data_dir(dir.take());
+        }
+    }
+}
+
+fn remove_user_data_dir(dir: Option<PathBuf>) {
+    if let Some(dir) = dir {
+        if let Err(e) = std::fs::remove_dir_all(&dir) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("failed to remove user data dir {}: {e}", dir.display());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiles::Os;
+    use crate::transport::MockTransport;
+    use serde_json::json;
+
+    fn pool(mocks: &[MockTransport], config: PoolConfig) -> BrowserPool {
+        let browsers = mocks.iter().cloned().map(Browser::with_transport).collect();
+        BrowserPool::from_browsers(browsers, config)
+    }
+
+    #[tokio::test]
+    async fn leases_round_robin_and_disposes_on_release() {
+        let mocks = [MockTransport::new(), MockTransport::new()];
+        let pool = pool(
+            &mocks,
+            PoolConfig::builder()
+                .browsers(2)
+                .profiles([
+                    ChaserProfile::windows().build(),
+                    ChaserProfile::macos_arm().build(),
+                ])
+                .proxies(["http://10.0.0.1:8080", "http://10.0.0.2:8080"])
+                .build(),
+        );
+
+        let first = pool.acquire(TaskAssignment::default()).await.unwrap();
+        let second = pool.acquire(TaskAssignment::default()).await.unwrap();
+        assert_eq!(
+            (first.browser_index(), first.proxy(), first.profile().os()),
+            (0, Some("http://10.0.0.1:8080"), Os::Windows)
+        );
+        assert_eq!(
+            (
+                second.browser_index(),
+                second.proxy(),
+                second.profile().os()
+            ),
+            (1, Some("http://10.0.0.2:8080"), Os::MacOSArm)
+        );
+        assert_eq!(first.context_id().as_ref(), "MOCK_CONTEXT");
+
+        // an explicit assignment wins over the rotation
+        let third = pool
+            .acquire(
+                TaskAssignment::new()
+                    .profile(ChaserProfile::android().build())
+                    .proxy("socks5://10.0.0.9:1080"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(third.browser_index(), 0);
+        assert_eq!(third.proxy(), Some("socks5://10.0.0.9:1080"));
+        assert_eq!(third.profile().os(), Os::Android);
+
+        let contexts = mocks[0].commands_to("Target.createBrowserContext");
+        assert_eq!(contexts[0]["proxyServer"], "http://10.0.0.1:8080");
+        assert_eq!(contexts[1]["proxyServer"], "socks5://10.0.0.9:1080");
+        assert_eq!(
+            mocks[1].commands_to("Target.createTarget")[0]["browserContextId"],
+            "MOCK_CONTEXT"
+        );
+
+        for lease in [first, second, third] {
+            pool.release(lease).await.unwrap();
+        }
+        assert_eq!(
+            mocks[0].commands_to("Target.disposeBrowserContext"),
+            vec![json!({ "browserContextId": "MOCK_CONTEXT" }); 2]
+        );
+        assert_eq!(
+            mocks[1].commands_to("Target.disposeBrowserContext").len(),
+            1
+        );
+        pool.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_page_setup_disposes_the_context() {
+        let mock = MockTransport::new();
+        mock.fail("Target.createTarget", "Target closed");
+        let pool = pool(std::slice::from_ref(&mock), PoolConfig::default());
+
+        assert!(pool.acquire(TaskAssignment::default()).await.is_err());
+        assert_eq!(mock.commands_to("Target.disposeBrowserContext").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn with_page_releases_the_page_when_the_task_fails() {
+        let mock = MockTransport::new();
+        let pool = pool(std::slice::from_ref(&mock), PoolConfig::default());
+
+        let result: Result<()> = pool
+            .with_page(TaskAssignment::default(), |_page| async {
+                Err(ChaserError::msg("blocked"))
+            })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "blocked");
+        assert_eq!(mock.commands_to("Target.disposeBrowserContext").len(), 1);
+        assert_eq!(pool.leased(), 0);
+        pool.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn close_closes_every_idle_browser_and_reports_the_leased_ones() {
+        let mocks = [
+            MockTransport::new(),
+            MockTransport::new(),
+            MockTransport::new(),
+        ];
+        let mut pool = pool(&mocks, PoolConfig::builder().browsers(3).build());
+        let dirs: Vec<PathBuf> = (0..3)
+            .map(|idx| {
+                let dir = std::env::temp_dir()
+                    .join(format!("chaser-pool-test-{}-{idx}", uuid::Uuid::new_v4()));
+                std::fs::create_dir_all(&dir).unwrap();
+                dir
+            })
+            .collect();
+        pool.user_data_dirs = dirs.iter().cloned().map(Some).collect();
+
+        // the first lease stays out, the second is handed back
+        let kept = pool.acquire(TaskAssignment::default()).await.unwrap();
+        let released = pool.acquire(TaskAssignment::default()).await.unwrap();
+        pool.release(released).await.unwrap();
+
+        let error = pool.close().await.unwrap_err().to_string();
+        assert!(error.contains("browsers [0] are still in use"), "{error}");
+        assert!(mocks[0].commands_to("Browser.close").is_empty());
+        assert_eq!(mocks[1].commands_to("Browser.close").len(), 1);
+        assert_eq!(mocks[2].commands_to("Browser.close").len(), 1);
+        // the leased browser keeps its profile
+        assert!(dirs[0].exists());
+        assert!(!dirs[1].exists() && !dirs[2].exists());
+
+        drop(kept);
+        std::fs::remove_dir_all(&dirs[0]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn dropping_the_pool_removes_the_user_data_dirs() {
+        let mut pool = pool(&[MockTransport::new()], PoolConfig::default());
+        let dir = std::env::temp_dir().join(format!("chaser-pool-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("Default")).unwrap();
+        pool.user_data_dirs = vec![Some(dir.clone())];
+
+        drop(pool);
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,230 @@
+//! Warming up a context before the visible navigation, and keeping Chrome
+//! from loading pages on its own.
+//!
+//! A returning visitor's browser has the site's DNS entry, often an open
+//! connection and most of its scripts, styles and images in cache. A fresh
+//! context has none of it: the first navigation is slow, and a fleet whose
+//! every visit downloads every asset looks like what it is.
+//! [`ChaserContext::prewarm`] resolves and connects to a site's origin from
+//! a background tab; [`PrewarmOptions::preload`] also loads the page there
+//! first, so the visible navigation is served from cache.
+//!
+//! The HTTP cache is keyed by the top-level site, so preloading the target
+//! site itself warms exactly what the visible page will read. Chrome only
+//! keeps an unused connection for a few seconds; navigate soon after.
+//!
+//! The other way round, Chrome prerenders and prefetches pages it expects
+//! a user to visit: from speculation rules a site publishes and from the
+//! omnibox. Those loads run ahead of the flow and vary from run to run.
+//! [`ChaserPage::set_prerendering_allowed`] stops prerendering for one
+//! page, [`LaunchArgs::without_speculation`] turns speculative loading off
+//! for the whole browser.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::prewarm::PrewarmOptions;
+//!
+//! context
+//!     .prewarm_with("https://shop.example/", &PrewarmOptions::new().preload())
+//!     .await?;
+//! let page = context.new_page().await?;
+//! page.goto("https://shop.example/").await?;
+//! ```
+//!
+//! [`LaunchArgs::without_speculation`]: crate::launch_args::LaunchArgs::without_speculation
+
+use crate::chaser::ChaserPage;
+use crate::context::ChaserContext;
+use crate::error::ChaserResult;
+use anyhow::{anyhow, Result};
+use chromiumoxide_cdp::cdp::browser_protocol::page::SetPrerenderingAllowedParams;
+use std::time::Duration;
+use url::Url;
+
+/// How [`ChaserContext::prewarm_with`] warms a context up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrewarmOptions {
+    preload: bool,
+    settle: Duration,
+}
+
+impl Default for PrewarmOptions {
+    fn default() -> Self {
+        Self {
+            preload: false,
+            settle: Duration::from_secs(1),
+        }
+    }
+}
+
+impl PrewarmOptions {
+    /// Resolve and connect only, waiting a second for the handshake.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the page in the background tab, filling the cache with its
+    /// subresources. The site sees a visit.
+    pub fn preload(mut self) -> Self {
+        self.preload = true;
+        self
+    }
+
+    /// How long the background tab stays open after connecting or loading,
+    /// for handshakes and late subresources to finish.
+    pub fn settle(mut self, settle: Duration) -> Self {
+        self.settle = settle;
+        self
+    }
+}
+
+/// Asks the browser to resolve and connect to an origin, without sending
+/// a request.
+fn preconnect_script(origin: &str) -> String {
+    format!(
+        r#"(() => {{
+    for (const rel of ["dns-prefetch", "preconnect"]) {{
+        const link = document.createElement("link");
+        link.rel = rel;
+        link.href = {origin};
+        document.head.appendChild(link);
+    }}
+}})()"#,
+        origin = serde_json::to_string(origin).unwrap_or_default()
+    )
+}
+
+impl ChaserContext {
+    /// Resolve and connect to the origin of `url` ahead of a navigation,
+    /// see [`prewarm_with`](Self::prewarm_with).
+    pub async fn prewarm(&self, url: &str) -> Result<()> {
+        self.prewarm_with(url, &PrewarmOptions::default()).await
+    }
+
+    /// Warm this context up for a visit to `url` from a background tab,
+    /// which is closed again before this returns. The tab has the
+    /// context's profile applied, so its requests look like the visible
+    /// page's.
+    pub async fn prewarm_with(&self, url: &str, options: &PrewarmOptions) -> Result<()> {
+        let origin = Url::parse(url)
+            .map_err(|e| anyhow!("cannot prewarm {url}: {e}"))?
+            .origin()
+            .ascii_serialization();
+        let page = self.open_page(true).await?;
+        let warmed = async {
+            if options.preload {
+                page.goto(url).await?;
+            } else {
+                page.evaluate_stealth(&preconnect_script(&origin)).await?;
+            }
+            tokio::time::sleep(options.settle).await;
+            Ok::<_, anyhow::Error>(())
+        }
+        .await;
+        let _ = page.raw_page().clone().close().await;
+        warmed
+    }
+}
+
+impl ChaserPage {
+    /// Allow or forbid Chrome to prerender pages from this one, whether
+    /// the site asks for it with speculation rules or the browser guesses
+    /// the next navigation. Prefetches are not affected.
+    pub async fn set_prerendering_allowed(&self, allowed: bool) -> ChaserResult<()> {
+        self.raw_page()
+            .execute(SetPrerenderingAllowedParams::new(allowed))
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::browser::Browser;
+    use crate::page::Page;
+    use crate::profiles::ChaserProfile;
+    use crate::transport::MockTransport;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    async fn context(mock: &MockTransport) -> ChaserContext {
+        let browser = Arc::new(Browser::with_transport(mock.clone()));
+        ChaserContext::create(browser, ChaserProfile::windows().build(), None)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn preconnects_to_the_origin_from_a_background_tab() {
+        let mock = MockTransport::new();
+        let context = context(&mock).await;
+        let started = tokio::time::Instant::now();
+        context
+            .prewarm("https://shop.example:8443/cart?id=1")
+            .await
+            .unwrap();
+        assert_eq!(started.elapsed(), Duration::from_secs(1));
+
+        let tab = &mock.commands_to("Target.createTarget")[0];
+        assert_eq!(tab["background"], true);
+        assert_eq!(tab["browserContextId"], "MOCK_CONTEXT");
+        let preconnect = preconnect_script("https://shop.example:8443");
+        assert!(mock
+            .commands_to("Runtime.evaluate")
+            .iter()
+            .any(|params| params["expression"] == preconnect.as_str()));
+        assert!(preconnect.contains(r#"link.href = "https://shop.example:8443";"#));
+        assert!(mock.commands_to("Page.navigate").is_empty());
+        assert_eq!(mock.commands_to("Page.close").len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn preloading_visits_the_page_itself() {
+        let mock = MockTransport::new();
+        let context = context(&mock).await;
+        let options = PrewarmOptions::new()
+            .preload()
+            .settle(Duration::from_millis(200));
+        let started = tokio::time::Instant::now();
+        context
+            .prewarm_with("https://shop.example/cart", &options)
+            .await
+            .unwrap();
+        assert_eq!(started.elapsed(), Duration::from_millis(200));
+        assert_eq!(
+            mock.commands_to("Page.navigate")[0]["url"],
+            "https://shop.example/cart"
+        );
+        assert_eq!(mock.commands_to("Page.close").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn failures_still_close_the_tab() {
+        let mock = MockTransport::new();
+        let context = context(&mock).await;
+        let err = context.prewarm("not a url").await.unwrap_err();
+        assert!(err.to_string().starts_with("cannot prewarm not a url"));
+        assert!(mock.commands_to("Target.createTarget").is_empty());
+
+        mock.fail("Page.navigate", "net::ERR_NAME_NOT_RESOLVED");
+        let options = PrewarmOptions::new().preload();
+        assert!(context
+            .prewarm_with("https://gone.example/", &options)
+            .await
+            .is_err());
+        assert_eq!(mock.commands_to("Page.close").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn prerendering_is_switched_per_page() {
+        let mock = MockTransport::new();
+        let chaser = ChaserPage::new(Page::with_transport(mock.clone()));
+        chaser.set_prerendering_allowed(false).await.unwrap();
+        assert_eq!(
+            mock.commands_to("Page.setPrerenderingAllowed"),
+            [json!({ "isAllowed": false })]
+        );
+    }
+}
@@ -5,7 +5,7 @@
 //!
 //! # Example
 //!
-//! ```rust
+//! ```ignore
 //! use chaser-oxide::profiles::{ChaserProfile, Gpu};
 //!
 //! let profile = ChaserProfile::windows()
@@ -15,11 +15,23 @@
 //!     .cpu_cores(12)
 //!     .build();
 //! ```
+//!
+//! [`ChaserProfileBuilder::try_build`] rejects values no real machine has,
+//! such as zero CPU cores.
 
+use crate::client_hints::Architecture;
+use crate::geometry::WindowState;
+use crate::keyboard::KeyboardLayout;
+use crate::patches::Patch;
+use crate::policy::StealthPolicy;
+use crate::regions::{Geolocation, Region};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::fmt;
+use thiserror::Error;
 
 /// GPU presets for WebGL spoofing
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Gpu {
     /// NVIDIA GeForce RTX 3080 (high-trust gaming GPU)
     NvidiaRTX3080,
@@ -39,6 +51,12 @@ pub enum Gpu {
     AppleM4Max,
     /// AMD Radeon RX 6800
     AmdRadeonRX6800,
+    /// ARM Mali-G715 (Google Pixel 8)
+    MaliG715,
+    /// Qualcomm Adreno 740 (Samsung Galaxy S23)
+    Adreno740,
+    /// Apple GPU as exposed by WebKit on iPhone (model is masked)
+    AppleMobile,
 }
 
 impl Gpu {
@@ -49,6 +67,9 @@ impl Gpu {
             Gpu::IntelUHD630 | Gpu::IntelIrisXe => "Google Inc. (Intel)",
             Gpu::AppleM1Pro | Gpu::AppleM2Max | Gpu::AppleM4Max => "Google Inc. (Apple)",
             Gpu::AmdRadeonRX6800 => "Google Inc. (AMD)",
+            Gpu::MaliG715 => "ARM",
+            Gpu::Adreno740 => "Qualcomm",
+            Gpu::AppleMobile => "Apple Inc.",
         }
     }
 
@@ -74,12 +95,15 @@ impl Gpu {
                 "ANGLE (Apple, ANGLE Metal Renderer: Apple M4 Max, Unspecified Version)"
             }
             Gpu::AmdRadeonRX6800 => "ANGLE (AMD, AMD Radeon RX 6800 XT Direct3D11 vs_5_0 ps_5_0)",
+            Gpu::MaliG715 => "Mali-G715",
+            Gpu::Adreno740 => "Adreno (TM) 740",
+            Gpu::AppleMobile => "Apple GPU",
         }
     }
 }
 
 /// Operating system presets
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Os {
     /// Windows 10/11 64-bit
     Windows,
@@ -89,6 +113,10 @@ pub enum Os {
     MacOSArm,
     /// Linux x86_64
     Linux,
+    /// Android phone (Chrome for Android)
+    Android,
+    /// iPhone (Chrome for iOS)
+    Ios,
 }
 
 impl Os {
@@ -98,6 +126,8 @@ impl Os {
             Os::Windows => "Win32",
             Os::MacOSIntel | Os::MacOSArm => "MacIntel",
             Os::Linux => "Linux x86_64",
+            Os::Android => "Linux armv81",
+            Os::Ios => "iPhone",
         }
     }
 
@@ -107,15 +137,105 @@ impl Os {
             Os::Windows => "Windows",
             Os::MacOSIntel | Os::MacOSArm => "macOS",
             Os::Linux => "Linux",
+            Os::Android => "Android",
+            Os::Ios => "iOS",
+        }
+    }
+
+    /// Whether this is a phone platform (touch input, motion sensors)
+    pub fn is_mobile(&self) -> bool {
+        matches!(self, Os::Android | Os::Ios)
+    }
+}
+
+/// `prefers-color-scheme` value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ColorScheme {
+    Light,
+    Dark,
+}
+
+/// `color-gamut` value the display claims to cover
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ColorGamut {
+    /// Standard office/gaming monitors
+    Srgb,
+    /// Apple displays and most recent laptops
+    P3,
+    /// HDR monitors
+    Rec2020,
+}
+
+/// How the OS draws scrollbars, as probed by `offsetWidth - clientWidth` of
+/// a scrolling element
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ScrollbarStyle {
+    /// Drawn over the content only while scrolling; takes no layout space
+    Overlay,
+    /// Always visible, taking `width` CSS pixels from the content
+    Classic { width: u32 },
+}
+
+impl ScrollbarStyle {
+    /// Layout width of a vertical scrollbar
+    pub fn width(&self) -> u32 {
+        match self {
+            ScrollbarStyle::Overlay => 0,
+            ScrollbarStyle::Classic { width } => *width,
         }
     }
 }
 
+/// CSS media features emulated via `Emulation.setEmulatedMedia`, so both
+/// `@media` rules and `matchMedia()` agree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MediaFeatures {
+    pub color_scheme: ColorScheme,
+    pub reduced_motion: bool,
+    pub color_gamut: ColorGamut,
+    pub forced_colors: bool,
+}
+
+impl MediaFeatures {
+    /// The `(name, value)` pairs passed to `Emulation.setEmulatedMedia`
+    pub fn css_features(&self) -> Vec<(&'static str, &'static str)> {
+        vec![
+            (
+                "prefers-color-scheme",
+                match self.color_scheme {
+                    ColorScheme::Light => "light",
+                    ColorScheme::Dark => "dark",
+                },
+            ),
+            (
+                "prefers-reduced-motion",
+                if self.reduced_motion {
+                    "reduce"
+                } else {
+                    "no-preference"
+                },
+            ),
+            (
+                "color-gamut",
+                match self.color_gamut {
+                    ColorGamut::Srgb => "srgb",
+                    ColorGamut::P3 => "p3",
+                    ColorGamut::Rec2020 => "rec2020",
+                },
+            ),
+            (
+                "forced-colors",
+                if self.forced_colors { "active" } else { "none" },
+            ),
+        ]
+    }
+}
+
 /// A builder for creating consistent browser fingerprint profiles.
 ///
 /// # Example
 ///
-/// ```rust
+/// ```ignore
 /// use chaser-oxide::profiles::{ChaserProfile, Gpu, Os};
 ///
 /// // Quick preset
@@ -131,18 +251,68 @@ impl Os {
 ///     .timezone("Europe/Berlin")
 ///     .build();
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ChaserProfile {
     os: Os,
+    os_version: Option<String>,
+    architecture: Option<Architecture>,
     chrome_version: u32,
+    chrome_full_version: Option<String>,
     gpu: Gpu,
     memory_gb: u32,
     cpu_cores: u32,
     locale: String,
+    languages: Option<Vec<String>>,
     timezone: String,
+    keyboard_layout: KeyboardLayout,
+    geolocation: Option<Geolocation>,
+    region: Option<Region>,
     screen_width: u32,
     screen_height: u32,
     device_pixel_ratio: f32,
+    zoom_percent: u32,
+    rect_noise_seed: Option<u32>,
+    /// Unix seconds of the last [`evolve`](Self::evolve)
+    last_evolved: Option<u64>,
+    color_depth: u32,
+    avail_area: AvailArea,
+    window_x: i32,
+    window_y: i32,
+    window_state: Option<WindowState>,
+    screen_left: i32,
+    screen_top: i32,
+    extended_display: bool,
+    media: MediaFeatures,
+    extensions: Vec<String>,
+    #[serde(skip)]
+    bootstrap: BootstrapCache,
+}
+
+/// The bootstrap script of a profile, rendered on first use. Profiles are
+/// immutable apart from [`ChaserProfile::evolve_with`], which resets it.
+#[derive(Clone, Default)]
+struct BootstrapCache(std::sync::OnceLock<String>);
+
+impl fmt::Debug for BootstrapCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = if self.0.get().is_some() {
+            "rendered"
+        } else {
+            "pending"
+        };
+        f.write_str(state)
+    }
+}
+
+/// The part of the screen not covered by taskbars, docks or menu bars,
+/// relative to the screen's top-left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AvailArea {
+    pub left: u32,
+    pub top: u32,
+    pub width: u32,
+    pub height: u32,
 }
 
 impl Default for ChaserProfile {
@@ -161,24 +331,84 @@ impl ChaserProfile {
             Os::MacOSIntel => (1440, 900, 2.0, 8),
             Os::MacOSArm => (1728, 1117, 2.0, 14), // M4 Max defaults
             Os::Linux => (1920, 1080, 1.0, 8),
+            Os::Android => (412, 915, 2.625, 9), // Pixel 8
+            Os::Ios => (393, 852, 3.0, 6),       // iPhone 15
+        };
+
+        // Taskbar at the bottom on Windows, menu bar (and notch) at the top on macOS
+        let avail_area = match os {
+            Os::Windows => (0, 0, screen_width, screen_height - 48),
+            Os::MacOSIntel => (0, 25, screen_width, screen_height - 25),
+            Os::MacOSArm => (0, 38, screen_width, screen_height - 38),
+            Os::Linux | Os::Android | Os::Ios => (0, 0, screen_width, screen_height),
+        };
+        let avail_area = AvailArea {
+            left: avail_area.0,
+            top: avail_area.1,
+            width: avail_area.2,
+            height: avail_area.3,
+        };
+
+        // A window sitting exactly at (0, 0) on every session is a fleet-level
+        // signature, so start slightly offset inside the available area
+        let mut rng = rand::thread_rng();
+        let (window_x, window_y) = if os.is_mobile() {
+            (0, 0)
+        } else {
+            (
+                rng.gen_range(0..=120),
+                avail_area.top as i32 + rng.gen_range(0..=60),
+            )
         };
 
         ChaserProfileBuilder {
             os,
+            os_version: None,
+            architecture: None,
             chrome_version: 131, // Keep reasonably current
+            chrome_full_version: None,
             gpu: match os {
                 Os::Windows => Gpu::NvidiaRTX3080,
                 Os::MacOSIntel => Gpu::AppleM1Pro,
                 Os::MacOSArm => Gpu::AppleM4Max,
                 Os::Linux => Gpu::NvidiaGTX1660,
+                Os::Android => Gpu::MaliG715,
+                Os::Ios => Gpu::AppleMobile,
             },
             memory_gb: 8,
             cpu_cores,
             locale: "en-US".to_string(),
+            languages: None,
             timezone: "America/New_York".to_string(),
+            keyboard_layout: KeyboardLayout::Us,
+            geolocation: None,
+            region: None,
             screen_width,
             screen_height,
             device_pixel_ratio,
+            zoom_percent: 100,
+            rect_noise_seed: None,
+            color_depth: match os {
+                Os::MacOSIntel | Os::MacOSArm => 30,
+                Os::Windows | Os::Linux | Os::Android | Os::Ios => 24,
+            },
+            avail_area,
+            window_x,
+            window_y,
+            window_state: None,
+            screen_left: 0,
+            screen_top: 0,
+            extended_display: false,
+            extensions: Vec::new(),
+            media: MediaFeatures {
+                color_scheme: ColorScheme::Light,
+                reduced_motion: false,
+                color_gamut: match os {
+                    Os::MacOSIntel | Os::MacOSArm | Os::Android | Os::Ios => ColorGamut::P3,
+                    Os::Windows | Os::Linux => ColorGamut::Srgb,
+                },
+                forced_colors: false,
+            },
         }
     }
 
@@ -202,28 +432,87 @@ impl ChaserProfile {
         Self::new(Os::Linux)
     }
 
+    /// Create an Android profile (Pixel 8 defaults)
+    pub fn android() -> ChaserProfileBuilder {
+        Self::new(Os::Android)
+    }
+
+    /// Create an iPhone profile (iPhone 15 defaults)
+    pub fn ios() -> ChaserProfileBuilder {
+        Self::new(Os::Ios)
+    }
+
     // Getters
     pub fn os(&self) -> Os {
         self.os
     }
+    /// The real OS version, as reported by the `platformVersion` client hint
+    /// (default: [`client_hints::default_os_version`](crate::client_hints::default_os_version)).
+    pub fn os_version(&self) -> String {
+        self.os_version
+            .clone()
+            .unwrap_or_else(|| crate::client_hints::default_os_version(self.os).to_string())
+    }
+    /// The CPU architecture of the browser build, reported through client
+    /// hints (default: Arm on Apple Silicon and phones, x86-64 elsewhere).
+    pub fn architecture(&self) -> Architecture {
+        self.architecture
+            .unwrap_or_else(|| Architecture::default_for(self.os))
+    }
     pub fn chrome_version(&self) -> u32 {
         self.chrome_version
     }
+    /// Full version for client hints, `<major>.0.0.0` unless derived from a
+    /// real binary
+    pub fn chrome_full_version(&self) -> String {
+        self.chrome_full_version
+            .clone()
+            .unwrap_or_else(|| format!("{}.0.0.0", self.chrome_version))
+    }
     pub fn gpu(&self) -> Gpu {
         self.gpu
     }
+    /// Installed memory in GB.
     pub fn memory_gb(&self) -> u32 {
         self.memory_gb
     }
+    /// `navigator.deviceMemory` as Chrome reports it for
+    /// [`memory_gb`](Self::memory_gb), see [`approximate_device_memory`].
+    pub fn device_memory(&self) -> f64 {
+        approximate_device_memory(self.memory_gb)
+    }
     pub fn cpu_cores(&self) -> u32 {
         self.cpu_cores
     }
     pub fn locale(&self) -> &str {
         &self.locale
     }
+    /// `navigator.languages`: the configured list or the typical one for the
+    /// locale, see [`crate::locales`].
+    pub fn languages(&self) -> Vec<String> {
+        self.languages
+            .clone()
+            .unwrap_or_else(|| crate::locales::languages(&self.locale))
+    }
+    /// The `Accept-Language` header matching [`languages`](Self::languages).
+    pub fn accept_language(&self) -> String {
+        crate::locales::accept_language(&self.languages())
+    }
     pub fn timezone(&self) -> &str {
         &self.timezone
     }
+    /// The keyboard the typing methods press keys on.
+    pub fn keyboard_layout(&self) -> KeyboardLayout {
+        self.keyboard_layout
+    }
+    /// The position reported to the Geolocation API, if any.
+    pub fn geolocation(&self) -> Option<Geolocation> {
+        self.geolocation
+    }
+    /// The region preset the profile was built from, e.g. for its currency.
+    pub fn region(&self) -> Option<Region> {
+        self.region
+    }
     pub fn screen_width(&self) -> u32 {
         self.screen_width
     }
@@ -233,15 +522,81 @@ impl ChaserProfile {
     pub fn device_pixel_ratio(&self) -> f32 {
         self.device_pixel_ratio
     }
+    /// Seed of the client rect noise, if enabled (feature `rect-noise`)
+    pub fn rect_noise_seed(&self) -> Option<u32> {
+        self.rect_noise_seed
+    }
+    /// Browser zoom in percent, one of [`ZOOM_LEVELS`](crate::geometry::ZOOM_LEVELS)
+    pub fn zoom_percent(&self) -> u32 {
+        self.zoom_percent
+    }
+    pub fn color_depth(&self) -> u32 {
+        self.color_depth
+    }
+    pub fn avail_area(&self) -> AvailArea {
+        self.avail_area
+    }
+    /// Window position on the virtual desktop (`window.screenX/screenY`)
+    pub fn window_position(&self) -> (i32, i32) {
+        (
+            self.screen_left + self.window_x,
+            self.screen_top + self.window_y,
+        )
+    }
+    /// Maximized or restored window bounds, if set explicitly
+    pub fn window_state(&self) -> Option<WindowState> {
+        self.window_state
+    }
+    /// Origin of the window's monitor on the virtual desktop
+    pub fn screen_origin(&self) -> (i32, i32) {
+        (self.screen_left, self.screen_top)
+    }
+    pub fn extended_display(&self) -> bool {
+        self.extended_display
+    }
+    /// Unpacked extension directories loaded at launch
+    pub fn extensions(&self) -> &[String] {
+        &self.extensions
+    }
+    pub fn media(&self) -> MediaFeatures {
+        self.media
+    }
+    /// Scrollbars of Chrome on the profile's OS: overlay on macOS (trackpad
+    /// default) and phones, 15px Fluent scrollbars on Windows 11 since Chrome
+    /// 121, 17px classic ones before, 15px on Linux
+    pub fn scrollbar_style(&self) -> ScrollbarStyle {
+        match self.os {
+            Os::MacOSIntel | Os::MacOSArm | Os::Android | Os::Ios => ScrollbarStyle::Overlay,
+            Os::Windows => {
+                // Windows 11 reports platformVersion 13 and up
+                let windows_11 = self
+                    .os_version()
+                    .split('.')
+                    .next()
+                    .and_then(|major| major.parse::<u32>().ok())
+                    .is_some_and(|major| major >= 13);
+                let width = if windows_11 && self.chrome_version >= 121 {
+                    15
+                } else {
+                    17
+                };
+                ScrollbarStyle::Classic { width }
+            }
+            Os::Linux => ScrollbarStyle::Classic { width: 15 },
+        }
+    }
 
     /// Configure a BrowserConfigBuilder with this profile's recommended settings.
-    /// 
+    ///
     /// This sets:
-    /// - Window size to match screen dimensions (prevents geometric leaks)
+    /// - Window size and position from the profile's [`Geometry`]
+    ///
+    /// [`Geometry`]: crate::geometry::Geometry
     /// - Stealth args for anti-detection
-    /// 
+    /// - The profile's unpacked extensions
+    ///
     /// # Example
-    /// ```rust
+    /// ```ignore
     /// let profile = ChaserProfile::windows().build();
     /// let config = profile.configure_browser(BrowserConfig::builder())
     ///     .with_head()
@@ -251,24 +606,46 @@ impl ChaserProfile {
         &self,
         builder: crate::browser::BrowserConfigBuilder,
     ) -> crate::browser::BrowserConfigBuilder {
-        builder
-            .window_size(self.screen_width, self.screen_height)
-            .args(vec![
-                // Hide automation indicators
-                "--disable-blink-features=AutomationControlled".to_string(),
-                // Hide the automation infobar
-                "--disable-infobars".to_string(),
-                // Explicit window size as backup (belt and suspenders)
-                format!("--window-size={},{}", self.screen_width, self.screen_height),
-            ])
+        // Replaces chromiumoxide's defaults, which carry a stale user agent
+        let mut args = crate::launch_args::LaunchArgs::for_profile(self);
+        if !self.extensions.is_empty() && self.chrome_version >= 137 {
+            // branded Chrome ignores --load-extension since 137 unless re-enabled
+            args = args.disable_features(&["DisableLoadExtensionCommandLineSwitch"]);
+        }
+        let geometry = crate::geometry::Geometry::resolve(self);
+        args.apply(
+            builder
+                .window_size(geometry.outer_width, geometry.outer_height)
+                .extensions(self.extensions.clone()),
+        )
     }
 
     /// Generate the User-Agent string for this profile
     pub fn user_agent(&self) -> String {
         let os_part = match self.os {
             Os::Windows => "Windows NT 10.0; Win64; x64",
-            Os::MacOSIntel | Os::MacOSArm => "Macintosh; Intel Mac OS X 10_15_7",
+            Os::MacOSIntel | Os::MacOSArm => {
+                return format!(
+                    "Mozilla/5.0 (Macintosh; Intel Mac OS X {}) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/{}.0.0.0 Safari/537.36",
+                    crate::client_hints::mac_ua_token(&self.os_version()),
+                    self.chrome_version
+                )
+            }
             Os::Linux => "X11; Linux x86_64",
+            // Reduced user agent: Chrome freezes the Android version and model
+            Os::Android => {
+                return format!(
+                    "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/{}.0.0.0 Mobile Safari/537.36",
+                    self.chrome_version
+                )
+            }
+            Os::Ios => {
+                return format!(
+                    "Mozilla/5.0 (iPhone; CPU iPhone OS {} like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) CriOS/{}.0.0.0 Mobile/15E148 Safari/604.1",
+                    self.os_version().replace('.', "_"),
+                    self.chrome_version
+                )
+            }
         };
         format!(
             "Mozilla/5.0 ({}) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/{}.0.0.0 Safari/537.36",
@@ -276,67 +653,51 @@ impl ChaserProfile {
         )
     }
 
+    /// The UA client hints matching [`user_agent`](Self::user_agent), see
+    /// [`crate::client_hints`]
+    pub fn user_agent_metadata(
+        &self,
+    ) -> Option<chromiumoxide_cdp::cdp::browser_protocol::emulation::UserAgentMetadata> {
+        crate::client_hints::metadata(self)
+    }
+
     /// Generate the complete JavaScript bootstrap script for this profile
     /// Single source of truth for ALL stealth - no separate chrome_runtime_mock needed
+    ///
+    /// The script is composed of every [`Patch`], see [`crate::patches`].
     pub fn bootstrap_script(&self) -> String {
-        format!(
-            r#"
-            (function() {{
-                // === MINIMAL STEALTH: Pure data, no makeNative wrappers ===
-                // Turnstile detects function wrapping - use simple arrow functions only
-                
-                try {{
-                    // 1. HARDWARE (simple getters)
-                    Object.defineProperty(navigator, 'hardwareConcurrency', {{
-                        get: () => {cores},
-                        configurable: true, enumerable: true
-                    }});
-                    Object.defineProperty(navigator, 'deviceMemory', {{
-                        get: () => {memory},
-                        configurable: true, enumerable: true
-                    }});
-
-                    // 2. PLATFORM
-                    Object.defineProperty(navigator, 'platform', {{
-                        get: () => '{platform}',
-                        configurable: true, enumerable: true
-                    }});
-
-                    // 3. WEBDRIVER = false (critical)
-                    Object.defineProperty(navigator, 'webdriver', {{
-                        get: () => false,
-                        configurable: true, enumerable: true
-                    }});
-
-                    // 4. WEBGL (minimal override)
-                    const getParam = WebGLRenderingContext.prototype.getParameter;
-                    WebGLRenderingContext.prototype.getParameter = function(p) {{
-                        if (p === 37445) return '{webgl_vendor}';
-                        if (p === 37446) return '{webgl_renderer}';
-                        return getParam.apply(this, arguments);
-                    }};
-
-                    // 5. CHROME OBJECT (minimal)
-                    if (!window.chrome) {{
-                        window.chrome = {{ runtime: {{}} }};
-                    }}
-
-                    // 6. CDP MARKER CLEANUP (once)
-                    for (const p of Object.getOwnPropertyNames(window)) {{
-                        if (/^cdc_|^\$cdc_|^__webdriver|^__selenium|^__driver/.test(p)) {{
-                            try {{ delete window[p]; }} catch(e) {{}}
-                        }}
-                    }}
-
-                }} catch(e) {{}}
-            }})();
-            "#,
-            platform = self.os.platform(),
-            cores = self.cpu_cores,
-            memory = self.memory_gb,
-            webgl_vendor = self.gpu.vendor(),
-            webgl_renderer = self.gpu.renderer(),
-        )
+        self.bootstrap_script_with_policy(&StealthPolicy::default())
+    }
+
+    /// [`bootstrap_script`](Self::bootstrap_script) with the per-host
+    /// exceptions of `policy`.
+    ///
+    /// Without exceptions the script is rendered once per profile and
+    /// copied on later calls.
+    pub fn bootstrap_script_with_policy(&self, policy: &StealthPolicy) -> String {
+        if policy.is_empty() {
+            return self
+                .bootstrap
+                .0
+                .get_or_init(|| self.render_bootstrap(policy))
+                .clone();
+        }
+        self.render_bootstrap(policy)
+    }
+
+    fn render_bootstrap(&self, policy: &StealthPolicy) -> String {
+        crate::patches::compose(self, &self.patches(), policy)
+    }
+
+    /// The patches of this profile's bootstrap script, in injection order.
+    pub(crate) fn patches(&self) -> Vec<Patch> {
+        #[cfg(feature = "rect-noise")]
+        if self.rect_noise_seed.is_some() {
+            let mut patches = Patch::ALL.to_vec();
+            patches.push(Patch::RectNoise);
+            return patches;
+        }
+        Patch::ALL.to_vec()
     }
 }
 
@@ -350,25 +711,303 @@ impl fmt::Display for ChaserProfile {
     }
 }
 
+/// What [`ChaserProfile::evolve`] may change.
+///
+/// Chrome updates only make sense when the launched binary is updated too:
+/// a profile pinned to an installed Chrome with
+/// [`ChaserProfileBuilder::chrome_version_auto`] should turn them off.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DriftPolicy {
+    /// Follow Chrome's stable releases a few days after they ship.
+    pub chrome_updates: bool,
+    /// Install OS point releases (macOS, Linux kernel, Android, iOS).
+    pub os_updates: bool,
+    /// Chance per year of moving to a different monitor resolution.
+    pub screen_change_per_year: f64,
+    /// Move the window by a few pixels between sessions.
+    pub window_moves: bool,
+}
+
+impl Default for DriftPolicy {
+    fn default() -> Self {
+        DriftPolicy {
+            chrome_updates: true,
+            os_updates: true,
+            screen_change_per_year: 0.15,
+            window_moves: true,
+        }
+    }
+}
+
+impl DriftPolicy {
+    /// Nothing drifts.
+    pub fn frozen() -> Self {
+        DriftPolicy {
+            chrome_updates: false,
+            os_updates: false,
+            screen_change_per_year: 0.0,
+            window_moves: false,
+        }
+    }
+}
+
+/// One change made by [`ChaserProfile::evolve`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileChange {
+    ChromeUpdate { from: u32, to: u32 },
+    OsUpdate { from: String, to: String },
+    Screen { from: (u32, u32), to: (u32, u32) },
+    WindowMoved { x: i32, y: i32 },
+}
+
+/// Seconds since the Unix epoch of Chrome 131's stable release (Tuesday,
+/// 2024-11-12); new majors follow every four weeks.
+const CHROME_131_RELEASE: u64 = 1_731_369_600;
+const CHROME_RELEASE_CYCLE: u64 = 28 * 24 * 3600;
+const DAY: u64 = 24 * 3600;
+
+/// The stable Chrome major version current at `unix_secs`.
+pub fn chrome_major_at(unix_secs: u64) -> u32 {
+    131 + (unix_secs.saturating_sub(CHROME_131_RELEASE) / CHROME_RELEASE_CYCLE) as u32
+}
+
+/// Common desktop resolutions, for monitor changes.
+const DESKTOP_SCREENS: [(u32, u32); 6] = [
+    (1920, 1080),
+    (2560, 1440),
+    (1536, 864),
+    (1366, 768),
+    (1440, 900),
+    (3840, 2160),
+];
+
+impl ChaserProfile {
+    /// Age the profile to now, see [`evolve_with`](Self::evolve_with).
+    pub fn evolve(&mut self, rng: &mut impl Rng) -> Vec<ProfileChange> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        self.evolve_with(&DriftPolicy::default(), now, rng)
+    }
+
+    /// Make the small changes a real machine goes through between the last
+    /// evolution and `now` (Unix seconds): Chrome auto-updates to the current
+    /// stable release (a few days after its Tuesday launch), OS point
+    /// releases, a rare new monitor, a window that was moved. The first call
+    /// only records the time, so a new profile does not jump ahead.
+    pub fn evolve_with(
+        &mut self,
+        policy: &DriftPolicy,
+        now: u64,
+        rng: &mut impl Rng,
+    ) -> Vec<ProfileChange> {
+        let mut changes = Vec::new();
+        self.bootstrap = BootstrapCache::default();
+        let Some(since) = self.last_evolved.replace(now) else {
+            return changes;
+        };
+        let days = now.saturating_sub(since) as f64 / DAY as f64;
+
+        if policy.chrome_updates {
+            // updates roll out over a couple of weeks
+            let delay = rng.gen_range(1..=14) * DAY;
+            let current = chrome_major_at(now.saturating_sub(delay));
+            if current > self.chrome_version {
+                changes.push(ProfileChange::ChromeUpdate {
+                    from: self.chrome_version,
+                    to: current,
+                });
+                self.chrome_version = current;
+                self.chrome_full_version = None;
+            }
+        }
+
+        // a point release every three months or so
+        if policy.os_updates && self.os != Os::Windows && rng.gen_bool((days / 90.0).min(1.0)) {
+            let from = self.os_version();
+            let mut parts: Vec<u32> = from.split('.').filter_map(|p| p.parse().ok()).collect();
+            parts.resize(3, 0);
+            if self.os == Os::Linux || rng.gen_bool(0.7) {
+                parts[2] += 1;
+            } else {
+                parts[1] += 1;
+                parts[2] = 0;
+            }
+            let to = match self.os {
+                // iOS drops a zero patch level
+                Os::Ios if parts[2] == 0 => format!("{}.{}", parts[0], parts[1]),
+                _ => format!("{}.{}.{}", parts[0], parts[1], parts[2]),
+            };
+            changes.push(ProfileChange::OsUpdate {
+                from,
+                to: to.clone(),
+            });
+            self.os_version = Some(to);
+        }
+
+        let p_screen = (policy.screen_change_per_year * days / 365.0).clamp(0.0, 1.0);
+        if !self.os.is_mobile() && p_screen > 0.0 && rng.gen_bool(p_screen) {
+            let from = (self.screen_width, self.screen_height);
+            let to = DESKTOP_SCREENS[rng.gen_range(0..DESKTOP_SCREENS.len())];
+            if to != from {
+                let inset_x = self.screen_width.saturating_sub(self.avail_area.width);
+                let inset_y = self.screen_height.saturating_sub(self.avail_area.height);
+                self.screen_width = to.0;
+                self.screen_height = to.1;
+                self.avail_area.width = to.0.saturating_sub(inset_x);
+                self.avail_area.height = to.1.saturating_sub(inset_y);
+                self.window_state = None;
+                changes.push(ProfileChange::Screen { from, to });
+            }
+        }
+
+        if policy.window_moves && !self.os.is_mobile() && self.window_state.is_none() {
+            let avail = self.avail_area;
+            let max_x = (avail.left + avail.width / 4) as i32;
+            let max_y = (avail.top + avail.height / 4) as i32;
+            self.window_x =
+                (self.window_x + rng.gen_range(-40..=40)).clamp(avail.left as i32, max_x);
+            self.window_y =
+                (self.window_y + rng.gen_range(-25..=25)).clamp(avail.top as i32, max_y);
+            changes.push(ProfileChange::WindowMoved {
+                x: self.window_x,
+                y: self.window_y,
+            });
+        }
+        changes
+    }
+}
+
+/// The only `navigator.deviceMemory` values Chrome reports, in GB.
+pub const DEVICE_MEMORY_BUCKETS: [f64; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+
+/// The `navigator.deviceMemory` (and `Sec-CH-Device-Memory`) value Chrome
+/// reports on a machine with `memory_gb` of RAM.
+///
+/// Like Blink, rounds to the nearest power of two (the lower one on a tie)
+/// and caps at 8, so a 32GB workstation reports 8 and a 6GB laptop 4. The
+/// installed figure itself is exposed nowhere, not even to high-entropy
+/// client hints, so a profile must never leak it.
+pub fn approximate_device_memory(memory_gb: u32) -> f64 {
+    if memory_gb == 0 {
+        return DEVICE_MEMORY_BUCKETS[0];
+    }
+    let lower = 1u64 << (u32::BITS - 1 - memory_gb.leading_zeros());
+    let upper = lower << 1;
+    let gb = u64::from(memory_gb);
+    let nearest = if gb - lower <= upper - gb {
+        lower
+    } else {
+        upper
+    };
+    (nearest as f64).min(8.0)
+}
+
+/// A profile value that no real Chrome would report.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ProfileError {
+    #[error("{0} CPU cores is outside 1-64")]
+    CpuCores(u32),
+    #[error("{0}GB of memory is outside 1-512")]
+    Memory(u32),
+    #[error("device pixel ratio {0} is outside 0.5-5")]
+    DevicePixelRatio(f32),
+    #[error("OS version {0:?} is not of the form 14.5.0")]
+    OsVersion(String),
+    #[error("zoom {0}% is not one of Chrome's zoom levels")]
+    Zoom(u32),
+}
+
 /// Builder for constructing `ChaserProfile` instances
 #[derive(Debug, Clone)]
 pub struct ChaserProfileBuilder {
     os: Os,
+    os_version: Option<String>,
+    architecture: Option<Architecture>,
     chrome_version: u32,
+    chrome_full_version: Option<String>,
     gpu: Gpu,
     memory_gb: u32,
     cpu_cores: u32,
     locale: String,
+    languages: Option<Vec<String>>,
     timezone: String,
+    keyboard_layout: KeyboardLayout,
+    geolocation: Option<Geolocation>,
+    region: Option<Region>,
     screen_width: u32,
     screen_height: u32,
     device_pixel_ratio: f32,
+    zoom_percent: u32,
+    rect_noise_seed: Option<u32>,
+    color_depth: u32,
+    avail_area: AvailArea,
+    window_x: i32,
+    window_y: i32,
+    window_state: Option<WindowState>,
+    screen_left: i32,
+    screen_top: i32,
+    extended_display: bool,
+    media: MediaFeatures,
+    extensions: Vec<String>,
 }
 
 impl ChaserProfileBuilder {
-    /// Set the Chrome version (default: 129)
+    /// Set the real OS version, e.g. "14.5.0" for macOS Sonoma
+    ///
+    /// It is reported as the `platformVersion` client hint. The user agent
+    /// string keeps Chrome's frozen token (`Mac OS X 10_15_7`,
+    /// `Windows NT 10.0`, `Android 10`) except on iOS and pre-Big Sur macOS,
+    /// whose real versions Chrome does show.
+    pub fn os_version(mut self, version: impl Into<String>) -> Self {
+        self.os_version = Some(version.into());
+        self
+    }
+
+    /// Override the CPU architecture, e.g. for a Snapdragon Windows laptop
+    /// or 32-bit Chrome under WOW64
+    pub fn architecture(mut self, architecture: Architecture) -> Self {
+        self.architecture = Some(architecture);
+        self
+    }
+
+    /// Set the Chrome version (default: 131)
     pub fn chrome_version(mut self, version: u32) -> Self {
         self.chrome_version = version;
+        self.chrome_full_version = None;
+        self
+    }
+
+    /// Take the Chrome version from the browser binary that will be launched
+    /// (the `CHROME` variable or the auto-detected executable), so the
+    /// claimed version never drifts from the real one.
+    ///
+    /// Keeps the current version, with a warning, if no binary is found.
+    pub fn chrome_version_auto(self) -> Self {
+        let install = crate::detection::default_executable(Default::default())
+            .ok()
+            .and_then(|path| crate::chrome_locator::ChromeInstall::inspect(path).ok())
+            .filter(|install| install.version.is_some())
+            .or_else(|| crate::chrome_locator::locate_all().into_iter().next());
+        match install {
+            Some(install) => self.chrome_version_from(&install),
+            None => {
+                tracing::warn!(
+                    "no browser binary found, keeping Chrome {}",
+                    self.chrome_version
+                );
+                self
+            }
+        }
+    }
+
+    /// Take the Chrome version from a specific installation.
+    pub fn chrome_version_from(mut self, install: &crate::chrome_locator::ChromeInstall) -> Self {
+        if let Some(version) = install.version {
+            self.chrome_version = version.major;
+            self.chrome_full_version = Some(version.to_string());
+        }
         self
     }
 
@@ -378,56 +1017,399 @@ impl ChaserProfileBuilder {
         self
     }
 
-    /// Set device memory in GB (default: 8)
+    /// Set the installed memory in GB, 1-512 (default: 8)
+    ///
+    /// Pages see it bucketed as Chrome reports it, see
+    /// [`approximate_device_memory`].
     pub fn memory_gb(mut self, gb: u32) -> Self {
         self.memory_gb = gb;
         self
     }
 
-    /// Set CPU core count (default: 8)
+    /// Set CPU core count, 1-64 (default: 8)
     pub fn cpu_cores(mut self, cores: u32) -> Self {
         self.cpu_cores = cores;
         self
     }
 
     /// Set the locale (e.g., "en-US", "de-DE")
+    ///
+    /// `navigator.languages` and `Accept-Language` list the secondary
+    /// languages typical for it unless [`languages`](Self::languages) is set.
     pub fn locale(mut self, locale: impl Into<String>) -> Self {
         self.locale = locale.into();
         self
     }
 
+    /// Set `navigator.languages` explicitly, most preferred first
+    pub fn languages<I, S>(mut self, languages: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.languages = Some(languages.into_iter().map(Into::into).collect());
+        self
+    }
+
     /// Set the timezone (e.g., "America/New_York", "Europe/Berlin")
     pub fn timezone(mut self, tz: impl Into<String>) -> Self {
         self.timezone = tz.into();
         self
     }
 
+    /// Set locale, languages, timezone, keyboard layout and geolocation to
+    /// match `region`, see [`crate::regions`]
+    pub fn region(mut self, region: Region) -> Self {
+        self.locale = region.locale().to_string();
+        self.languages = None;
+        self.timezone = region.timezone().to_string();
+        self.keyboard_layout = region.keyboard();
+        self.geolocation = Some(region.geolocation(&mut rand::thread_rng()));
+        self.region = Some(region);
+        self
+    }
+
+    /// Set the keyboard layout typed text is pressed on (default: US)
+    pub fn keyboard_layout(mut self, layout: KeyboardLayout) -> Self {
+        self.keyboard_layout = layout;
+        self
+    }
+
+    /// Report `geolocation` to the Geolocation API (default: none, the
+    /// browser's own position)
+    pub fn geolocation(mut self, geolocation: Geolocation) -> Self {
+        self.geolocation = Some(geolocation);
+        self
+    }
+
     /// Set screen resolution
+    ///
+    /// The available area keeps its taskbar/menu bar insets.
     pub fn screen(mut self, width: u32, height: u32) -> Self {
+        let inset_x = self.screen_width.saturating_sub(self.avail_area.width);
+        let inset_y = self.screen_height.saturating_sub(self.avail_area.height);
         self.screen_width = width;
         self.screen_height = height;
+        self.avail_area.width = width.saturating_sub(inset_x);
+        self.avail_area.height = height.saturating_sub(inset_y);
+        self
+    }
+
+    /// Set the screen color depth in bits (default: 24, 30 on macOS)
+    pub fn color_depth(mut self, bits: u32) -> Self {
+        self.color_depth = bits;
+        self
+    }
+
+    /// Set the area not covered by taskbars/docks, relative to the screen
+    pub fn avail_area(mut self, left: u32, top: u32, width: u32, height: u32) -> Self {
+        self.avail_area = AvailArea {
+            left,
+            top,
+            width,
+            height,
+        };
+        self
+    }
+
+    /// Set the window position relative to its monitor (default: a small
+    /// random offset inside the available area)
+    pub fn window_position(mut self, x: i32, y: i32) -> Self {
+        self.window_x = x;
+        self.window_y = y;
+        self
+    }
+
+    /// Start the window maximized or with the given restored bounds
+    /// (default: restored, see [`window_position`](Self::window_position))
+    pub fn window_state(mut self, state: WindowState) -> Self {
+        self.window_state = Some(state);
+        self
+    }
+
+    /// Simulate a multi-monitor desktop with the window on a monitor whose
+    /// top-left corner sits at (`left`, `top`) on the virtual desktop, e.g.
+    /// `(1920, 0)` for a second monitor to the right of a 1080p primary
+    pub fn secondary_monitor(mut self, left: i32, top: i32) -> Self {
+        self.screen_left = left;
+        self.screen_top = top;
+        self.extended_display = true;
+        self
+    }
+
+    /// Report `screen.isExtended` (multiple monitors) without moving the window
+    pub fn extended_display(mut self, extended: bool) -> Self {
+        self.extended_display = extended;
         self
     }
 
-    /// Set device pixel ratio (1.0 for standard, 2.0 for Retina/HiDPI)
+    /// Load an unpacked extension directory at launch, see
+    /// [`crate::extension::BenignExtension`] for a generated one
+    ///
+    /// Requires a headed browser or the new headless mode.
+    pub fn extension(mut self, path: impl Into<String>) -> Self {
+        self.extensions.push(path.into());
+        self
+    }
+
+    /// Set `prefers-color-scheme` (default: light)
+    pub fn color_scheme(mut self, scheme: ColorScheme) -> Self {
+        self.media.color_scheme = scheme;
+        self
+    }
+
+    /// Set `prefers-reduced-motion: reduce` (default: no-preference)
+    pub fn reduced_motion(mut self, reduced: bool) -> Self {
+        self.media.reduced_motion = reduced;
+        self
+    }
+
+    /// Set the `color-gamut` of the display (default: srgb, p3 on macOS)
+    pub fn color_gamut(mut self, gamut: ColorGamut) -> Self {
+        self.media.color_gamut = gamut;
+        self
+    }
+
+    /// Set `forced-colors: active` (Windows high contrast mode)
+    pub fn forced_colors(mut self, forced: bool) -> Self {
+        self.media.forced_colors = forced;
+        self
+    }
+
+    /// Set device pixel ratio (1.0 for standard, 2.0 for Retina/HiDPI), 0.5-5
     pub fn device_pixel_ratio(mut self, dpr: f32) -> Self {
         self.device_pixel_ratio = dpr;
         self
     }
 
-    /// Build the final profile
-    pub fn build(self) -> ChaserProfile {
+    /// Set the browser zoom in percent, one of Chrome's
+    /// [`ZOOM_LEVELS`](crate::geometry::ZOOM_LEVELS) (default: 100)
+    ///
+    /// Zooming scales `devicePixelRatio` up and `innerWidth`/`innerHeight`
+    /// down while the window and screen keep their size.
+    pub fn zoom(mut self, percent: u32) -> Self {
+        self.zoom_percent = percent;
+        self
+    }
+
+    /// Add stable sub-pixel noise to element and range client rects in the
+    /// page, with a fresh seed (see [`Patch::RectNoise`])
+    #[cfg(feature = "rect-noise")]
+    pub fn rect_noise(mut self, enabled: bool) -> Self {
+        self.rect_noise_seed = enabled.then(|| rand::thread_rng().gen());
+        self
+    }
+
+    /// Add client rect noise with a fixed seed, to reproduce a stored identity
+    #[cfg(feature = "rect-noise")]
+    pub fn rect_noise_seed(mut self, seed: u32) -> Self {
+        self.rect_noise_seed = Some(seed);
+        self
+    }
+
+    /// Build the final profile, clamping out-of-range values to the nearest
+    /// plausible one with a warning. Use [`try_build`](Self::try_build) to
+    /// reject them instead.
+    pub fn build(mut self) -> ChaserProfile {
+        while let Err(e) = self.validate() {
+            tracing::warn!("{}, clamping", e);
+            match e {
+                ProfileError::CpuCores(_) => self.cpu_cores = self.cpu_cores.clamp(1, 64),
+                ProfileError::Memory(_) => self.memory_gb = self.memory_gb.clamp(1, 512),
+                ProfileError::DevicePixelRatio(dpr) if dpr.is_finite() => {
+                    self.device_pixel_ratio = dpr.clamp(0.5, 5.0)
+                }
+                ProfileError::DevicePixelRatio(_) => self.device_pixel_ratio = 1.0,
+                ProfileError::OsVersion(_) => self.os_version = None,
+                ProfileError::Zoom(percent) => {
+                    self.zoom_percent = crate::geometry::nearest_zoom_level(percent)
+                }
+            }
+        }
+        self.build_unchecked()
+    }
+
+    /// Build the final profile, failing on values no real Chrome reports.
+    pub fn try_build(self) -> Result<ChaserProfile, ProfileError> {
+        self.validate()?;
+        Ok(self.build_unchecked())
+    }
+
+    fn validate(&self) -> Result<(), ProfileError> {
+        if !(1..=64).contains(&self.cpu_cores) {
+            return Err(ProfileError::CpuCores(self.cpu_cores));
+        }
+        if !(1..=512).contains(&self.memory_gb) {
+            return Err(ProfileError::Memory(self.memory_gb));
+        }
+        if !(0.5..=5.0).contains(&self.device_pixel_ratio) {
+            return Err(ProfileError::DevicePixelRatio(self.device_pixel_ratio));
+        }
+        if !crate::geometry::ZOOM_LEVELS.contains(&self.zoom_percent) {
+            return Err(ProfileError::Zoom(self.zoom_percent));
+        }
+        if let Some(version) = &self.os_version {
+            let parts: Vec<&str> = version.split('.').collect();
+            if parts.len() > 3 || parts.iter().any(|p| p.parse::<u32>().is_err()) {
+                return Err(ProfileError::OsVersion(version.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    fn build_unchecked(self) -> ChaserProfile {
         ChaserProfile {
             os: self.os,
+            os_version: self.os_version,
+            architecture: self.architecture,
             chrome_version: self.chrome_version,
+            chrome_full_version: self.chrome_full_version,
             gpu: self.gpu,
             memory_gb: self.memory_gb,
             cpu_cores: self.cpu_cores,
             locale: self.locale,
+            languages: self.languages,
             timezone: self.timezone,
+            keyboard_layout: self.keyboard_layout,
+            geolocation: self.geolocation,
+            region: self.region,
             screen_width: self.screen_width,
             screen_height: self.screen_height,
             device_pixel_ratio: self.device_pixel_ratio,
+            zoom_percent: self.zoom_percent,
+            rect_noise_seed: self.rect_noise_seed,
+            last_evolved: None,
+            color_depth: self.color_depth,
+            avail_area: self.avail_area,
+            window_x: self.window_x,
+            window_y: self.window_y,
+            window_state: self.window_state,
+            screen_left: self.screen_left,
+            screen_top: self.screen_top,
+            extended_display: self.extended_display,
+            extensions: self.extensions,
+            media: self.media,
+            bootstrap: BootstrapCache::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_build_rejects_what_build_clamps() {
+        assert_eq!(
+            ChaserProfile::windows()
+                .memory_gb(0)
+                .try_build()
+                .unwrap_err(),
+            ProfileError::Memory(0)
+        );
+        assert_eq!(
+            ChaserProfile::windows()
+                .cpu_cores(0)
+                .try_build()
+                .unwrap_err(),
+            ProfileError::CpuCores(0)
+        );
+        assert!(ChaserProfile::ios()
+            .device_pixel_ratio(12.0)
+            .try_build()
+            .is_err());
+        assert!(ChaserProfile::android().try_build().is_ok());
+
+        let clamped = ChaserProfile::windows()
+            .memory_gb(1024)
+            .cpu_cores(128)
+            .device_pixel_ratio(f32::NAN)
+            .build();
+        assert_eq!(clamped.memory_gb(), 512);
+        assert_eq!(clamped.cpu_cores(), 64);
+        assert_eq!(clamped.device_pixel_ratio(), 1.0);
+        assert_eq!(ChaserProfile::linux().zoom(120).build().zoom_percent(), 125);
+    }
+
+    #[test]
+    fn evolving_follows_releases_and_policy() {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let mut profile = ChaserProfile::macos_arm().build();
+        let start = CHROME_131_RELEASE + 20 * DAY;
+        assert!(profile
+            .evolve_with(&DriftPolicy::default(), start, &mut rng)
+            .is_empty());
+
+        // a year later Chrome is 13 releases newer, give or take the rollout
+        let changes = profile.evolve_with(&DriftPolicy::default(), start + 365 * DAY, &mut rng);
+        assert!(matches!(
+            changes[0],
+            ProfileChange::ChromeUpdate {
+                from: 131,
+                to: 143 | 144
+            }
+        ));
+        assert!(changes
+            .iter()
+            .any(|c| matches!(c, ProfileChange::OsUpdate { .. })));
+
+        let frozen = profile.clone();
+        let later = start + 3 * 365 * DAY;
+        assert!(profile
+            .evolve_with(&DriftPolicy::frozen(), later, &mut rng)
+            .is_empty());
+        assert_eq!(profile.chrome_version(), frozen.chrome_version());
+    }
+
+    #[test]
+    fn bootstrap_is_rendered_again_after_evolving() {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        let mut profile = ChaserProfile::windows().build();
+        profile.evolve_with(&DriftPolicy::default(), CHROME_131_RELEASE, &mut rng);
+        let first = profile.bootstrap_script();
+        assert_eq!(profile.bootstrap_script(), first);
+
+        let policy = DriftPolicy {
+            screen_change_per_year: 1.0,
+            ..DriftPolicy::default()
+        };
+        profile.evolve_with(&policy, CHROME_131_RELEASE + 3 * 365 * DAY, &mut rng);
+        let evolved = profile.bootstrap_script();
+        assert_ne!(evolved, first);
+        assert_eq!(evolved, profile.render_bootstrap(&StealthPolicy::default()));
+    }
+
+    #[test]
+    fn device_memory_uses_chrome_buckets() {
+        let reported: Vec<f64> = [1, 2, 3, 5, 6, 7, 12, 16, 32, 64]
+            .into_iter()
+            .map(approximate_device_memory)
+            .collect();
+        assert_eq!(reported, [1.0, 2.0, 2.0, 4.0, 4.0, 8.0, 8.0, 8.0, 8.0, 8.0]);
+        assert!(reported.iter().all(|m| DEVICE_MEMORY_BUCKETS.contains(m)));
+    }
+
+    #[test]
+    fn extensions_join_the_single_disable_features_switch() {
+        let profile = ChaserProfile::windows()
+            .chrome_version(140)
+            .extension("/opt/extensions/wallet")
+            .build();
+        let config = profile
+            .configure_browser(crate::browser::BrowserConfig::builder())
+            .chrome_executable("/usr/bin/true")
+            .build()
+            .unwrap();
+        let disabled: Vec<_> = config
+            .launch_args()
+            .into_iter()
+            .filter(|arg| arg.starts_with("--disable-features"))
+            .collect();
+        assert_eq!(
+            disabled,
+            ["--disable-features=Translate,DisableLoadExtensionCommandLineSwitch"]
+        );
+    }
+}
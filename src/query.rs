@@ -0,0 +1,255 @@
+//! Finding elements by XPath or by their text, from the isolated world.
+//!
+//! CSS selectors cannot say "the button that reads *Add to cart*", and the
+//! page's own `document.evaluate` runs where the page can see it.
+//! [`ChaserPage::query_xpath_stealth`] and [`ChaserPage::find_by_text`] run
+//! the search in the isolated world and hand the matches back as
+//! [`Element`]s, ready for [`ChaserPage::click_element_human`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! let buttons = chaser.find_by_text("add to cart", false).await?;
+//! if let Some(button) = buttons.first() {
+//!     chaser.click_element_human(button).await?;
+//! }
+//! let rows = chaser.query_xpath_stealth("//table[@id='prices']//tr[td]").await?;
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::element::Element;
+use crate::error::{ChaserError, ChaserResult as Result};
+use chromiumoxide_cdp::cdp::browser_protocol::dom::RequestNodeParams;
+use chromiumoxide_cdp::cdp::js_protocol::runtime::{
+    EvaluateParams, GetPropertiesParams, ReleaseObjectParams,
+};
+
+/// The elements (or the parents of text and attribute nodes) an XPath
+/// selects, in document order.
+const XPATH_SCRIPT: &str = r#"(() => {
+    const [xpath] = ARGS;
+    const result = document.evaluate(xpath, document, null, XPathResult.ORDERED_NODE_SNAPSHOT_TYPE, null);
+    const out = [];
+    for (let i = 0; i < result.snapshotLength; i++) {
+        const node = result.snapshotItem(i);
+        const el = node.nodeType === Node.ELEMENT_NODE ? node : node.parentElement || node.ownerElement;
+        if (el && !out.includes(el)) out.push(el);
+    }
+    return out;
+})()"#;
+
+/// The innermost elements, open shadow roots included, whose text with
+/// collapsed whitespace equals `ARGS[0]` (exact) or contains it in any case.
+const TEXT_SCRIPT: &str = r#"(() => {
+    const [wanted, exact] = ARGS;
+    const norm = s => (s || '').replace(/\s+/g, ' ').trim();
+    const target = exact ? norm(wanted) : norm(wanted).toLowerCase();
+    const textOf = el => el instanceof HTMLInputElement && ['button', 'submit', 'reset'].includes(el.type)
+        ? el.value : el.textContent;
+    const matches = el => {
+        const text = norm(textOf(el));
+        return exact ? text === target : text.toLowerCase().includes(target);
+    };
+    const out = [];
+    const visit = root => {
+        for (const el of root.querySelectorAll('*')) {
+            if (el.shadowRoot) visit(el.shadowRoot);
+            if (['SCRIPT', 'STYLE', 'NOSCRIPT', 'TEMPLATE'].includes(el.tagName)) continue;
+            if (matches(el)) out.push(el);
+        }
+    };
+    visit(document);
+    return out.filter(el => !out.some(other => other !== el && el.contains(other)));
+})()"#;
+
+impl ChaserPage {
+    /// All elements the XPath `xpath` selects, evaluated in the isolated
+    /// world. Text and attribute nodes stand for their element.
+    pub async fn query_xpath_stealth(&self, xpath: &str) -> Result<Vec<Element>> {
+        let args = serde_json::to_string(&(xpath,))?;
+        self.query_isolated(&XPATH_SCRIPT.replacen("ARGS", &args, 1))
+            .await
+    }
+
+    /// The innermost elements whose text is `text`, or with `exact` false,
+    /// contains it ignoring case. Whitespace is collapsed on both sides, and
+    /// buttons made of `<input>` are matched by their label.
+    pub async fn find_by_text(&self, text: &str, exact: bool) -> Result<Vec<Element>> {
+        let args = serde_json::to_string(&(text, exact))?;
+        self.query_isolated(&TEXT_SCRIPT.replacen("ARGS", &args, 1))
+            .await
+    }
+
+    /// Run `script`, which returns an array of elements, in the isolated
+    /// world and resolve the elements through the DOM domain.
+    async fn query_isolated(&self, script: &str) -> Result<Vec<Element>> {
+        let context = self.isolated_context().await?;
+        let params = EvaluateParams::builder()
+            .expression(script)
+            .context_id(context)
+            .build()
+            .map_err(ChaserError::msg)?;
+        let evaluated = self.raw_page().execute(params).await?.result;
+        if let Some(details) = evaluated.exception_details {
+            return Err(ChaserError::Script(details.text));
+        }
+        let Some(array) = evaluated.result.object_id else {
+            return Ok(Vec::new());
+        };
+
+        let mut properties = GetPropertiesParams::new(array.clone());
+        properties.own_properties = Some(true);
+        let listed = self.raw_page().execute(properties).await;
+        let _ = self
+            .raw_page()
+            .execute(ReleaseObjectParams::new(array))
+            .await;
+        let mut items: Vec<_> = listed?
+            .result
+            .result
+            .into_iter()
+            .filter_map(|property| {
+                let index = property.name.parse::<usize>().ok()?;
+                Some((index, property.value?.object_id?))
+            })
+            .collect();
+        items.sort_by_key(|(index, _)| *index);
+
+        // DOM.requestNode only pushes nodes once the document was requested
+        self.raw_page().get_document().await?;
+        let mut node_ids = Vec::with_capacity(items.len());
+        for (_, object) in items {
+            let node = self
+                .raw_page()
+                .execute(RequestNodeParams::new(object.clone()))
+                .await;
+            let _ = self
+                .raw_page()
+                .execute(ReleaseObjectParams::new(object))
+                .await;
+            node_ids.push(node?.result.node_id);
+        }
+        Ok(self.raw_page().elements_from_nodes(&node_ids).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::page::Page;
+    use crate::transport::MockTransport;
+    use serde_json::{json, Value};
+
+    fn node(node_id: i64) -> Value {
+        json!({
+            "nodeId": node_id, "backendNodeId": node_id + 100, "nodeType": 1,
+            "nodeName": "BUTTON", "localName": "button", "nodeValue": ""
+        })
+    }
+
+    fn property(name: &str, value: Value) -> Value {
+        json!({ "name": name, "value": value, "configurable": true, "enumerable": true })
+    }
+
+    /// A page whose script returns the array `ARRAY` of the objects `A`
+    /// and `B`, which are the DOM nodes 10 and 20.
+    fn mock_page() -> MockTransport {
+        let mock = MockTransport::new();
+        mock.respond(
+            "Runtime.evaluate",
+            json!({ "result": { "type": "object", "subtype": "array", "objectId": "ARRAY" } }),
+        );
+        mock.respond(
+            "Runtime.getProperties",
+            json!({ "result": [
+                property("1", json!({ "type": "object", "objectId": "B" })),
+                property("length", json!({ "type": "number", "value": 2 })),
+                property("0", json!({ "type": "object", "objectId": "A" })),
+            ] }),
+        );
+        mock.respond("DOM.getDocument", json!({ "root": node(1) }));
+        mock.respond_with("DOM.requestNode", |params| {
+            let node_id = if params["objectId"] == "A" { 10 } else { 20 };
+            Ok(json!({ "nodeId": node_id }))
+        });
+        mock.respond_with("DOM.describeNode", |params| {
+            Ok(json!({ "node": node(params["nodeId"].as_i64().unwrap()) }))
+        });
+        mock.respond_with("DOM.resolveNode", |params| {
+            Ok(json!({ "object": {
+                "type": "object", "objectId": format!("node-{}", params["backendNodeId"])
+            } }))
+        });
+        mock
+    }
+
+    #[tokio::test]
+    async fn xpath_matches_come_back_in_document_order() {
+        let mock = mock_page();
+        let chaser = ChaserPage::new(Page::with_transport(mock.clone()));
+
+        let elements = chaser
+            .query_xpath_stealth("//button[text()=\"Buy\"]")
+            .await
+            .unwrap();
+        let node_ids: Vec<_> = elements.iter().map(|e| *e.node_id.inner()).collect();
+        assert_eq!(node_ids, [10, 20]);
+
+        let evaluate = &mock.commands_to("Runtime.evaluate")[0];
+        let expression = evaluate["expression"].as_str().unwrap();
+        assert!(expression.contains(r#"const [xpath] = ["//button[text()=\"Buy\"]"];"#));
+        assert!(
+            evaluate["contextId"].is_number(),
+            "runs in the isolated world"
+        );
+        let released: Vec<_> = mock
+            .commands_to("Runtime.releaseObject")
+            .into_iter()
+            .map(|params| params["objectId"].clone())
+            .collect();
+        assert_eq!(released, ["ARRAY", "A", "B"]);
+    }
+
+    #[tokio::test]
+    async fn text_search_passes_its_arguments_and_reports_errors() {
+        let mock = mock_page();
+        let chaser = ChaserPage::new(Page::with_transport(mock.clone()));
+
+        assert_eq!(
+            chaser
+                .find_by_text("Add to cart", false)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        let expression = mock.commands_to("Runtime.evaluate")[0]["expression"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(expression.contains(r#"const [wanted, exact] = ["Add to cart",false];"#));
+
+        // a script that returns no array finds nothing
+        mock.respond(
+            "Runtime.evaluate",
+            json!({ "result": { "type": "undefined" } }),
+        );
+        mock.clear();
+        assert!(chaser.find_by_text("gone", true).await.unwrap().is_empty());
+        assert!(mock.commands_to("Runtime.getProperties").is_empty());
+
+        mock.respond(
+            "Runtime.evaluate",
+            json!({
+                "result": { "type": "object" },
+                "exceptionDetails": {
+                    "exceptionId": 1, "text": "Uncaught SyntaxError", "lineNumber": 0, "columnNumber": 0
+                }
+            }),
+        );
+        assert!(matches!(
+            chaser.query_xpath_stealth("//[").await,
+            Err(ChaserError::Script(text)) if text == "Uncaught SyntaxError"
+        ));
+    }
+}
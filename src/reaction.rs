@@ -0,0 +1,243 @@
+//! Content-dependent reaction times before interactions.
+//!
+//! A fixed 50-150ms pause before every click is a flat, narrow distribution
+//! that looks the same on every page and in every session, which makes it an
+//! easy clustering feature. Human reaction times are right-skewed
+//! (ex-Gaussian) and depend on what just happened: a page that finished
+//! loading a moment ago needs to be looked at first, content that jumped
+//! needs the target to be found again, and the fifth click on the same list
+//! is faster than the first.
+//!
+//! [`ReactionModel`] samples such delays. Every [`ChaserPage`] owns one and
+//! consults it before humanized clicks, typing and key presses; replace it
+//! with [`ChaserPage::set_reaction_model`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::reaction::ReactionModel;
+//!
+//! // a slower, more deliberate user
+//! chaser.set_reaction_model(ReactionModel::new().base(320.0, 60.0).tail(180.0));
+//! ```
+//!
+//! [`ChaserPage`]: crate::chaser::ChaserPage
+//! [`ChaserPage::set_reaction_model`]: crate::chaser::ChaserPage::set_reaction_model
+
+use crate::chaser::Point;
+use crate::clock;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::{Duration, Instant};
+
+/// How long after a load the page still needs to be looked at.
+const ORIENTATION_WINDOW: Duration = Duration::from_secs(3);
+/// How long after a layout shift the target needs to be found again.
+const RELOCATE_WINDOW: Duration = Duration::from_millis(1200);
+/// Actions further apart than this are not a streak.
+const STREAK_WINDOW: Duration = Duration::from_secs(4);
+/// Targets closer than this (CSS pixels) count as similar.
+const STREAK_RADIUS: f64 = 200.0;
+
+/// The kind of interaction about to happen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interaction {
+    /// A mouse click or tap, after the pointer arrived on the target.
+    Click,
+    /// The first keystroke of typed text.
+    Type,
+    /// A single key press such as Enter or Tab.
+    Key,
+}
+
+impl Interaction {
+    /// Mean of the Gaussian part, relative to [`ReactionModel::base`].
+    fn scale(self) -> f64 {
+        match self {
+            Interaction::Click => 1.0,
+            Interaction::Type => 1.35,
+            Interaction::Key => 0.8,
+        }
+    }
+}
+
+/// What the page looked like right before the interaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PageContext {
+    /// Time since the current document started loading.
+    pub since_load: Option<Duration>,
+    /// Time since the last layout shift not caused by input.
+    pub since_layout_shift: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LastAction {
+    interaction: Interaction,
+    target: Option<Point>,
+    at: Instant,
+}
+
+/// Samples the pause before an interaction.
+///
+/// Delays are ex-Gaussian: a normal distribution (`base`) plus an
+/// exponential tail (`tail`) for the occasional slow reaction. On top of
+/// that come an orientation pause on freshly loaded pages and a relocation
+/// pause after layout shifts, while streaks of similar actions get faster.
+#[derive(Debug, Clone)]
+pub struct ReactionModel {
+    mean_ms: f64,
+    sd_ms: f64,
+    tail_ms: f64,
+    orientation_ms: f64,
+    relocate_ms: f64,
+    min_ms: f64,
+    last: Option<LastAction>,
+    streak: u32,
+    rng: StdRng,
+}
+
+impl Default for ReactionModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReactionModel {
+    pub fn new() -> Self {
+        Self {
+            mean_ms: 170.0,
+            sd_ms: 40.0,
+            tail_ms: 110.0,
+            orientation_ms: 900.0,
+            relocate_ms: 350.0,
+            min_ms: 45.0,
+            last: None,
+            streak: 0,
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    /// Mean and standard deviation of the Gaussian part, in milliseconds
+    /// (default: 170 and 40).
+    pub fn base(mut self, mean_ms: f64, sd_ms: f64) -> Self {
+        self.mean_ms = mean_ms;
+        self.sd_ms = sd_ms;
+        self
+    }
+
+    /// Mean of the exponential tail in milliseconds (default: 110).
+    pub fn tail(mut self, mean_ms: f64) -> Self {
+        self.tail_ms = mean_ms;
+        self
+    }
+
+    /// Extra pause right after a page loaded, fading out over three seconds
+    /// (default: 900ms).
+    pub fn orientation(mut self, ms: f64) -> Self {
+        self.orientation_ms = ms;
+        self
+    }
+
+    /// Extra pause right after a layout shift, fading out over 1.2 seconds
+    /// (default: 350ms).
+    pub fn relocation(mut self, ms: f64) -> Self {
+        self.relocate_ms = ms;
+        self
+    }
+
+    /// Seed the random source, for reproducible delays.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Sample the pause before `interaction` on `target` and remember the
+    /// action for the next call.
+    pub fn delay(
+        &mut self,
+        interaction: Interaction,
+        target: Option<Point>,
+        page: &PageContext,
+    ) -> Duration {
+        let now = clock::now();
+        let similar = self.last.is_some_and(|last| {
+            let age = now.duration_since(last.at);
+            // a navigation in between breaks the streak
+            let same_document = page.since_load.map_or(true, |load| load > age);
+            let nearby = match (last.target, target) {
+                (Some(a), Some(b)) => (a.x - b.x).hypot(a.y - b.y) < STREAK_RADIUS,
+                (None, None) => true,
+                _ => false,
+            };
+            last.interaction == interaction && age < STREAK_WINDOW && same_document && nearby
+        });
+        self.streak = if similar { self.streak + 1 } else { 0 };
+        self.last = Some(LastAction {
+            interaction,
+            target,
+            at: now,
+        });
+
+        let mean = self.mean_ms * interaction.scale();
+        let mut ms = mean + self.sd_ms * self.standard_normal() + self.exponential(self.tail_ms);
+        // practice effect, down to about half the base reaction time
+        ms *= 0.8_f64.powi(self.streak.min(3) as i32);
+        ms += fading(page.since_load, ORIENTATION_WINDOW) * self.orientation_ms;
+        ms += fading(page.since_layout_shift, RELOCATE_WINDOW) * self.relocate_ms;
+        Duration::from_millis(ms.max(self.min_ms) as u64)
+    }
+
+    fn standard_normal(&mut self) -> f64 {
+        // Box-Muller
+        let u1: f64 = self.rng.gen_range(f64::EPSILON..1.0);
+        let u2: f64 = self.rng.gen();
+        (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+    }
+
+    fn exponential(&mut self, mean: f64) -> f64 {
+        let u: f64 = self.rng.gen_range(f64::EPSILON..1.0);
+        -mean * u.ln()
+    }
+}
+
+/// 1.0 right after the event, falling linearly to 0.0 at `window`.
+fn fading(since: Option<Duration>, window: Duration) -> f64 {
+    since.map_or(0.0, |since| {
+        (1.0 - since.as_secs_f64() / window.as_secs_f64()).max(0.0)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mean_delay(model: &mut ReactionModel, page: PageContext, n: u32) -> f64 {
+        let target = Some(Point { x: 400.0, y: 300.0 });
+        (0..n)
+            .map(|_| model.delay(Interaction::Click, target, &page).as_millis() as f64)
+            .sum::<f64>()
+            / n as f64
+    }
+
+    #[test]
+    fn slower_on_fresh_pages_faster_in_streaks() {
+        let settled = PageContext {
+            since_load: Some(Duration::from_secs(30)),
+            since_layout_shift: None,
+        };
+        let fresh = PageContext {
+            since_load: Some(Duration::from_millis(200)),
+            since_layout_shift: Some(Duration::from_millis(100)),
+        };
+
+        let mut model = ReactionModel::new().seed(7);
+        let first = model.delay(Interaction::Click, None, &settled);
+        assert!(first >= Duration::from_millis(45));
+
+        // every call after the first continues the streak
+        let streak = mean_delay(&mut ReactionModel::new().seed(7), settled, 200);
+        let on_fresh_page = mean_delay(&mut ReactionModel::new().seed(7), fresh, 200);
+        assert!(streak < 200.0, "streak mean {streak}");
+        assert!(on_fresh_page > streak + 800.0, "fresh mean {on_fresh_page}");
+    }
+}
@@ -0,0 +1,167 @@
+//! Readability-style article extraction.
+//!
+//! With the `readability` feature, [`ChaserPage::extract_article`] finds the
+//! main content of a news or blog page in one isolated-world pass and
+//! returns it without navigation, ads, share bars and comment threads,
+//! together with its title, byline and publication date.
+//!
+//! The heuristics follow Mozilla's Readability: paragraphs score their
+//! parents by length and commas, class names such as `article` or
+//! `sidebar` push a candidate up or down, and link-heavy blocks count for
+//! little. The content is cleaned on a detached copy, so the page sees no
+//! DOM mutations.
+//!
+//! # Example
+//!
+//! ```ignore
+//! if let Some(article) = chaser.extract_article().await? {
+//!     println!("{} by {:?}, {} words", article.title, article.byline, article.word_count());
+//! }
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::ChaserResult as Result;
+use serde::{Deserialize, Serialize};
+
+/// Returns the article found on the page, or `null`.
+const ARTICLE_SCRIPT: &str = r#"(() => {
+    const meta = (...names) => {
+        for (const name of names) {
+            const el = document.querySelector(`meta[property="${name}"], meta[name="${name}"], meta[itemprop="${name}"]`);
+            const value = el && (el.content || el.getAttribute('content'));
+            if (value && value.trim()) return value.trim();
+        }
+        return null;
+    };
+    const norm = s => (s || '').replace(/\s+/g, ' ').trim();
+    const POSITIVE = /article|body|content|entry|hentry|main|page|post|text|blog|story/i;
+    const NEGATIVE = /comment|meta|footer|footnote|sidebar|sponsor|\bads?\b|promo|related|share|social|nav|menu|masthead|widget|newsletter|popup|cookie/i;
+    const weight = el => {
+        const names = `${el.className && el.className.baseVal !== undefined ? el.className.baseVal : el.className || ''} ${el.id || ''}`;
+        return (POSITIVE.test(names) ? 25 : 0) - (NEGATIVE.test(names) ? 25 : 0);
+    };
+    const linkDensity = el => {
+        const length = norm(el.textContent).length;
+        if (!length) return 0;
+        let links = 0;
+        for (const a of el.querySelectorAll('a')) links += norm(a.textContent).length;
+        return links / length;
+    };
+
+    const scores = new Map();
+    const add = (el, score) => {
+        if (!el || el === document.documentElement) return;
+        if (!scores.has(el)) scores.set(el, (el.tagName === 'ARTICLE' ? 10 : 0) + weight(el));
+        scores.set(el, scores.get(el) + score);
+    };
+    for (const block of document.body ? document.body.querySelectorAll('p, pre, td, blockquote') : []) {
+        const text = norm(block.textContent);
+        if (text.length < 25) continue;
+        const score = 1 + text.split(',').length + Math.min(Math.floor(text.length / 100), 3);
+        add(block.parentElement, score);
+        add(block.parentElement && block.parentElement.parentElement, score / 2);
+    }
+    let top = null;
+    let best = 0;
+    for (const [el, score] of scores) {
+        const adjusted = score * (1 - linkDensity(el));
+        if (adjusted > best) { best = adjusted; top = el; }
+    }
+    if (!top) return null;
+
+    const clone = top.cloneNode(true);
+    clone.querySelectorAll('script, style, noscript, iframe, form, nav, aside, footer, button, svg, input, select, textarea, [hidden], [aria-hidden="true"]')
+        .forEach(el => el.remove());
+    for (const el of Array.from(clone.querySelectorAll('div, section, ul, table'))) {
+        if (weight(el) < 0 || linkDensity(el) > 0.5) el.remove();
+    }
+    for (const el of clone.querySelectorAll('*')) {
+        for (const attr of Array.from(el.attributes)) {
+            if (!['href', 'src', 'alt', 'title', 'colspan', 'rowspan'].includes(attr.name)) el.removeAttribute(attr.name);
+        }
+    }
+    const blocks = Array.from(clone.querySelectorAll('h1, h2, h3, h4, h5, h6, p, li, pre, blockquote, figcaption'))
+        .filter(el => !el.parentElement.closest('p, li, pre, blockquote'))
+        .map(el => norm(el.textContent))
+        .filter(Boolean);
+    const text = blocks.length ? blocks.join('\n\n') : norm(clone.textContent);
+
+    const heading = (top.closest('article') || top).querySelector('h1') || document.querySelector('h1');
+    let title = meta('og:title', 'twitter:title') || norm(heading && heading.textContent) || '';
+    if (!title) {
+        title = norm(document.title).split(/\s[|\-–—]\s/).sort((a, b) => b.length - a.length)[0] || '';
+    }
+    const bylineEl = document.querySelector('[rel="author"], [itemprop="author"], .byline, .author');
+    const time = (top.closest('article') || document).querySelector('time[datetime]');
+    return {
+        title,
+        byline: meta('author', 'article:author') || (bylineEl && norm(bylineEl.textContent)) || null,
+        published: meta('article:published_time', 'datePublished', 'date', 'pubdate') || (time && time.getAttribute('datetime')) || null,
+        site_name: meta('og:site_name', 'application-name'),
+        excerpt: meta('description', 'og:description'),
+        lang: document.documentElement.lang || null,
+        text,
+        html: clone.innerHTML.trim(),
+    };
+})()"#;
+
+/// The main content of a page.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Article {
+    pub title: String,
+    pub byline: Option<String>,
+    /// As the page states it, usually ISO 8601.
+    pub published: Option<String>,
+    pub site_name: Option<String>,
+    /// The page's own summary, from its description meta tags.
+    pub excerpt: Option<String>,
+    pub lang: Option<String>,
+    /// Plain text, one paragraph per block separated by blank lines.
+    pub text: String,
+    /// The cleaned content markup, with only `href`, `src`, `alt` and
+    /// similar attributes left.
+    pub html: String,
+}
+
+impl Article {
+    pub fn word_count(&self) -> usize {
+        self.text.split_whitespace().count()
+    }
+
+    pub fn paragraphs(&self) -> impl Iterator<Item = &str> {
+        self.text.split("\n\n").filter(|p| !p.is_empty())
+    }
+}
+
+impl ChaserPage {
+    /// The article on the current page, or `None` if nothing on it reads
+    /// like running text.
+    pub async fn extract_article(&self) -> Result<Option<Article>> {
+        match self.evaluate_stealth(ARTICLE_SCRIPT).await? {
+            Some(value) if !value.is_null() => Ok(Some(serde_json::from_value(value)?)),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_script_output() {
+        let article: Article = serde_json::from_value(serde_json::json!({
+            "title": "Rust 2.0",
+            "byline": "Ferris",
+            "published": "2025-03-14T09:00:00Z",
+            "site_name": null,
+            "excerpt": null,
+            "lang": "en",
+            "text": "First paragraph here.\n\nSecond one, shorter.",
+            "html": "<p>First paragraph here.</p><p>Second one, shorter.</p>",
+        }))
+        .unwrap();
+        assert_eq!(article.word_count(), 6);
+        assert_eq!(article.paragraphs().count(), 2);
+    }
+}
@@ -0,0 +1,481 @@
+//! `Referer`, `Origin` and `Sec-Fetch-Site` that fit the request's context.
+//!
+//! A navigation crafted with a bare `Page.navigate` arrives with no
+//! `Referer`, like a bookmark. An API call replayed from the wrong document,
+//! or with an HTTP client, arrives without the `Origin` a browser sends on
+//! every cross-origin and every non-`GET` request, or with a `Referer`
+//! holding a full URL the default policy would have cut to the origin. Many
+//! backends reject such requests outright; CSRF checks look at exactly
+//! these headers.
+//!
+//! - [`ReferrerPolicy`] computes the `Referer` Chrome sends under each
+//!   policy, and [`ChaserPage::goto_with_referrer`] navigates with it.
+//! - [`ChaserPage::replay`] replays a [`CapturedRequest`] with `fetch` from
+//!   the current document, so the browser fills in `Origin`, `Referer`,
+//!   `Sec-Fetch-*` and cookies for that context.
+//! - [`ChaserContext::replay_from`] replays from a document of another
+//!   origin, to reproduce a same-site or cross-site call without visiting
+//!   that origin: the document is answered locally.
+//! - [`ReplayContext`] gives the same headers for replays outside the
+//!   browser.
+//!
+//! # Example
+//!
+//! ```
+//! use chaser_oxide::referrer::{CapturedRequest, ReplayContext};
+//! use url::Url;
+//!
+//! let context = ReplayContext::new(Url::parse("https://shop.example/cart?id=7").unwrap());
+//! let request = CapturedRequest::new("POST", "https://api.shop.example/v1/cart")
+//!     .header("content-type", "application/json")
+//!     .body(r#"{"id":7}"#);
+//! let replayed = context.apply(&request);
+//! assert_eq!(replayed.header_value("origin"), Some("https://shop.example"));
+//! assert_eq!(replayed.header_value("referer"), Some("https://shop.example/"));
+//! assert_eq!(replayed.header_value("sec-fetch-site"), Some("same-site"));
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::context::ChaserContext;
+use crate::error::{ChaserError, ChaserResult as Result};
+use crate::header_template::FetchSite;
+use crate::seeding::StubPage;
+use chromiumoxide_cdp::cdp::browser_protocol::page::{
+    NavigateParams, ReferrerPolicy as CdpReferrerPolicy, TransitionType,
+};
+use serde::Deserialize;
+use url::Url;
+
+/// Chrome sends only the origin for referrers longer than this.
+const MAX_REFERRER_LENGTH: usize = 4096;
+
+/// A document's referrer policy, deciding how much of its URL goes into
+/// the `Referer` of its requests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ReferrerPolicy {
+    NoReferrer,
+    NoReferrerWhenDowngrade,
+    Origin,
+    OriginWhenCrossOrigin,
+    SameOrigin,
+    StrictOrigin,
+    /// Chrome's default.
+    #[default]
+    StrictOriginWhenCrossOrigin,
+    UnsafeUrl,
+}
+
+impl ReferrerPolicy {
+    /// A `Referrer-Policy` header or `referrerpolicy` attribute value. The
+    /// header may list several; the last one known wins.
+    pub fn parse(value: &str) -> Option<Self> {
+        value.split(',').rev().find_map(|token| {
+            Some(match token.trim().to_ascii_lowercase().as_str() {
+                "no-referrer" => ReferrerPolicy::NoReferrer,
+                "no-referrer-when-downgrade" => ReferrerPolicy::NoReferrerWhenDowngrade,
+                "origin" => ReferrerPolicy::Origin,
+                "origin-when-cross-origin" => ReferrerPolicy::OriginWhenCrossOrigin,
+                "same-origin" => ReferrerPolicy::SameOrigin,
+                "strict-origin" => ReferrerPolicy::StrictOrigin,
+                "strict-origin-when-cross-origin" => ReferrerPolicy::StrictOriginWhenCrossOrigin,
+                "unsafe-url" => ReferrerPolicy::UnsafeUrl,
+                _ => return None,
+            })
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReferrerPolicy::NoReferrer => "no-referrer",
+            ReferrerPolicy::NoReferrerWhenDowngrade => "no-referrer-when-downgrade",
+            ReferrerPolicy::Origin => "origin",
+            ReferrerPolicy::OriginWhenCrossOrigin => "origin-when-cross-origin",
+            ReferrerPolicy::SameOrigin => "same-origin",
+            ReferrerPolicy::StrictOrigin => "strict-origin",
+            ReferrerPolicy::StrictOriginWhenCrossOrigin => "strict-origin-when-cross-origin",
+            ReferrerPolicy::UnsafeUrl => "unsafe-url",
+        }
+    }
+
+    /// The `Referer` a request from the document at `from` to `to` carries
+    /// under this policy, if any.
+    pub fn referrer(&self, from: &Url, to: &Url) -> Option<String> {
+        if !matches!(from.scheme(), "http" | "https") {
+            return None;
+        }
+        let origin = format!("{}/", from.origin().ascii_serialization());
+        let mut full = from.clone();
+        full.set_fragment(None);
+        let _ = full.set_username("");
+        let _ = full.set_password(None);
+        let full = if full.as_str().len() > MAX_REFERRER_LENGTH {
+            origin.clone()
+        } else {
+            full.to_string()
+        };
+        let same_origin = from.origin() == to.origin();
+        let downgrade = is_trustworthy(from) && !is_trustworthy(to);
+
+        match self {
+            ReferrerPolicy::NoReferrer => None,
+            ReferrerPolicy::UnsafeUrl => Some(full),
+            ReferrerPolicy::Origin => Some(origin),
+            ReferrerPolicy::NoReferrerWhenDowngrade => (!downgrade).then_some(full),
+            ReferrerPolicy::SameOrigin => same_origin.then_some(full),
+            ReferrerPolicy::StrictOrigin => (!downgrade).then_some(origin),
+            ReferrerPolicy::OriginWhenCrossOrigin => Some(if same_origin { full } else { origin }),
+            ReferrerPolicy::StrictOriginWhenCrossOrigin if same_origin => Some(full),
+            ReferrerPolicy::StrictOriginWhenCrossOrigin => (!downgrade).then_some(origin),
+        }
+    }
+
+    fn to_cdp(self) -> CdpReferrerPolicy {
+        match self {
+            ReferrerPolicy::NoReferrer => CdpReferrerPolicy::NoReferrer,
+            ReferrerPolicy::NoReferrerWhenDowngrade => CdpReferrerPolicy::NoReferrerWhenDowngrade,
+            ReferrerPolicy::Origin => CdpReferrerPolicy::Origin,
+            ReferrerPolicy::OriginWhenCrossOrigin => CdpReferrerPolicy::OriginWhenCrossOrigin,
+            ReferrerPolicy::SameOrigin => CdpReferrerPolicy::SameOrigin,
+            ReferrerPolicy::StrictOrigin => CdpReferrerPolicy::StrictOrigin,
+            ReferrerPolicy::StrictOriginWhenCrossOrigin => {
+                CdpReferrerPolicy::StrictOriginWhenCrossOrigin
+            }
+            ReferrerPolicy::UnsafeUrl => CdpReferrerPolicy::UnsafeUrl,
+        }
+    }
+}
+
+/// Secure contexts: TLS, or the local machine.
+fn is_trustworthy(url: &Url) -> bool {
+    url.scheme() == "https" || matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"))
+}
+
+/// An API request as captured from the network, to be replayed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CapturedRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+impl CapturedRequest {
+    pub fn new(method: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            method: method.into().to_ascii_uppercase(),
+            url: url.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<String>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// The value of header `name`, case-insensitively.
+    pub fn header_value(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Set header `name`, replacing the value of an existing one in place.
+    pub fn set_header(&mut self, name: &str, value: impl Into<String>) {
+        let value = value.into();
+        match self
+            .headers
+            .iter_mut()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        {
+            Some((_, v)) => *v = value,
+            None => self.headers.push((name.to_string(), value)),
+        }
+    }
+
+    pub fn remove_header(&mut self, name: &str) {
+        self.headers.retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+    }
+}
+
+/// The document a replayed request pretends to come from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayContext {
+    page: Url,
+    policy: ReferrerPolicy,
+}
+
+impl ReplayContext {
+    /// Requests made by scripts of the document at `page`.
+    pub fn new(page: Url) -> Self {
+        Self {
+            page,
+            policy: ReferrerPolicy::default(),
+        }
+    }
+
+    /// The document's referrer policy, Chrome's default unless the site
+    /// sets one.
+    pub fn policy(mut self, policy: ReferrerPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn fetch_site(&self, target: &Url) -> FetchSite {
+        FetchSite::between(Some(&self.page), target)
+    }
+
+    /// `Origin`, `Referer` and `Sec-Fetch-*` of a `fetch` or XHR with
+    /// `method` to `target` from this document. `Origin` goes on every
+    /// cross-origin request and on same-origin requests other than `GET`
+    /// and `HEAD`.
+    pub fn headers(&self, method: &str, target: &Url) -> Vec<(&'static str, String)> {
+        let same_origin = self.page.origin() == target.origin();
+        let safe = matches!(method.to_ascii_uppercase().as_str(), "GET" | "HEAD");
+        let mut headers = Vec::new();
+        if !same_origin {
+            headers.push(("origin", self.page.origin().ascii_serialization()));
+        } else if !safe {
+            let origin = match self.policy {
+                ReferrerPolicy::NoReferrer => "null".to_string(),
+                _ => self.page.origin().ascii_serialization(),
+            };
+            headers.push(("origin", origin));
+        }
+        headers.extend([
+            (
+                "sec-fetch-site",
+                self.fetch_site(target).as_str().to_string(),
+            ),
+            ("sec-fetch-mode", "cors".to_string()),
+            ("sec-fetch-dest", "empty".to_string()),
+        ]);
+        if let Some(referrer) = self.policy.referrer(&self.page, target) {
+            headers.push(("referer", referrer));
+        }
+        headers
+    }
+
+    /// `request` as this document would send it: the context headers
+    /// replace captured ones in place, are added where missing and removed
+    /// where this document would not send them. Requests with an
+    /// unparsable URL are returned unchanged.
+    pub fn apply(&self, request: &CapturedRequest) -> CapturedRequest {
+        let Ok(target) = Url::parse(&request.url) else {
+            return request.clone();
+        };
+        let mut context = self.headers(&request.method, &target);
+        let mut headers = Vec::new();
+        for (name, value) in &request.headers {
+            let lower = name.to_ascii_lowercase();
+            if !is_context_header(&lower) {
+                headers.push((name.clone(), value.clone()));
+            } else if let Some(i) = context.iter().position(|(n, _)| *n == lower) {
+                let (_, value) = context.remove(i);
+                headers.push((name.clone(), value));
+            }
+        }
+        headers.extend(context.into_iter().map(|(n, v)| (n.to_string(), v)));
+        CapturedRequest {
+            headers,
+            ..request.clone()
+        }
+    }
+}
+
+fn is_context_header(name: &str) -> bool {
+    matches!(
+        name,
+        "origin" | "referer" | "sec-fetch-site" | "sec-fetch-mode" | "sec-fetch-dest"
+    )
+}
+
+/// Headers a page script cannot set; the browser writes them itself.
+fn is_forbidden(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.starts_with(':')
+        || name.starts_with("sec-")
+        || name.starts_with("proxy-")
+        || matches!(
+            name.as_str(),
+            "accept-charset"
+                | "accept-encoding"
+                | "access-control-request-headers"
+                | "access-control-request-method"
+                | "connection"
+                | "content-length"
+                | "cookie"
+                | "date"
+                | "dnt"
+                | "expect"
+                | "host"
+                | "keep-alive"
+                | "origin"
+                | "priority"
+                | "referer"
+                | "te"
+                | "trailer"
+                | "transfer-encoding"
+                | "upgrade"
+                | "user-agent"
+                | "via"
+        )
+}
+
+/// The response to a replayed request.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ReplayResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+fn replay_script(request: &CapturedRequest) -> String {
+    let headers: Vec<&(String, String)> = request
+        .headers
+        .iter()
+        .filter(|(name, _)| !is_forbidden(name))
+        .collect();
+    let init = serde_json::json!({
+        "method": request.method,
+        "headers": headers,
+        "body": request.body,
+        "credentials": "include",
+        "mode": "cors",
+    });
+    format!(
+        r#"(async () => {{
+    const init = {init};
+    // keep the captured referrer where the document may claim it
+    const referer = {referer};
+    if (referer) {{
+        try {{
+            if (new URL(referer).origin === location.origin) init.referrer = referer;
+        }} catch (e) {{}}
+    }}
+    const response = await fetch({url}, init);
+    return {{ status: response.status, headers: [...response.headers], body: await response.text() }};
+}})()"#,
+        referer = serde_json::to_string(&request.header_value("referer")).unwrap_or_default(),
+        url = serde_json::to_string(&request.url).unwrap_or_default(),
+    )
+}
+
+impl ChaserPage {
+    /// Navigate to `url` as if following a link on `referrer`, under
+    /// Chrome's default referrer policy: the page is asked for with the
+    /// `Referer` a link there would send. `Sec-Fetch-Site` stays `none`, as
+    /// for any navigation the browser starts; to get a real one, navigate
+    /// from a page of the referrer's site by clicking.
+    pub async fn goto_with_referrer(&self, url: &str, referrer: &str) -> Result<()> {
+        self.goto_with_referrer_policy(url, referrer, ReferrerPolicy::default())
+            .await
+    }
+
+    /// [`goto_with_referrer`](Self::goto_with_referrer) with the referring
+    /// site's own `policy`.
+    pub async fn goto_with_referrer_policy(
+        &self,
+        url: &str,
+        referrer: &str,
+        policy: ReferrerPolicy,
+    ) -> Result<()> {
+        let parse =
+            |u: &str| Url::parse(u).map_err(|e| ChaserError::msg(format!("invalid URL {u}: {e}")));
+        let (to, from) = (parse(url)?, parse(referrer)?);
+        let mut params = NavigateParams::new(url);
+        params.referrer = policy.referrer(&from, &to);
+        params.referrer_policy = Some(policy.to_cdp());
+        params.transition_type = Some(TransitionType::Link);
+        self.navigate(params).await
+    }
+
+    /// Send `request` with `fetch` from the current document, with its
+    /// cookies. The browser sets `Origin`, `Sec-Fetch-*`, cookies and the
+    /// user agent for this context; captured values of those are dropped.
+    /// A captured `Referer` on the document's origin is kept. Registered
+    /// [signers](crate::signing) run first.
+    pub async fn replay(&self, request: &CapturedRequest) -> Result<ReplayResponse> {
+        let request = &self.sign_request(request).await?;
+        let response = self
+            .evaluate_stealth(&replay_script(request))
+            .await?
+            .ok_or_else(|| {
+                ChaserError::msg(format!("replay of {} returned nothing", request.url))
+            })?;
+        serde_json::from_value(response)
+            .map_err(|e| ChaserError::msg(format!("unreadable replay response: {e}")))
+    }
+}
+
+impl ChaserContext {
+    /// Replay `request` from a document at `page_url` in this context, to
+    /// send it same-site or cross-site as a script on that page would. The
+    /// document itself is answered locally, so `page_url`'s server is not
+    /// contacted; the replayed request goes out for real, with this
+    /// context's cookies under the rules for that context.
+    pub async fn replay_from(
+        &self,
+        page_url: &str,
+        request: &CapturedRequest,
+    ) -> anyhow::Result<ReplayResponse> {
+        let stub = StubPage::open(self).await?;
+        let response = async {
+            stub.page.goto(page_url).await?;
+            Ok(stub.page.replay(request).await?)
+        }
+        .await;
+        stub.close().await;
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn referrers_follow_the_policy() {
+        let url = |u: &str| Url::parse(u).unwrap();
+        let page = url("https://user:pw@shop.example/cart?id=7#top");
+        let policy = ReferrerPolicy::default();
+        assert_eq!(
+            policy.referrer(&page, &url("https://shop.example/checkout")),
+            Some("https://shop.example/cart?id=7".to_string())
+        );
+        assert_eq!(
+            policy.referrer(&page, &url("https://pay.example/")),
+            Some("https://shop.example/".to_string())
+        );
+        assert_eq!(policy.referrer(&page, &url("http://pay.example/")), None);
+        assert_eq!(
+            ReferrerPolicy::parse("unknown, no-referrer-when-downgrade, bogus"),
+            Some(ReferrerPolicy::NoReferrerWhenDowngrade)
+        );
+
+        // same-origin GET: no Origin, a captured stale one is dropped
+        let context = ReplayContext::new(page.clone());
+        let request = CapturedRequest::new("get", "https://shop.example/api/cart")
+            .header("Origin", "https://evil.example")
+            .header("Accept", "application/json");
+        let replayed = context.apply(&request);
+        assert_eq!(replayed.header_value("origin"), None);
+        assert_eq!(replayed.header_value("Accept"), Some("application/json"));
+        assert_eq!(replayed.header_value("sec-fetch-site"), Some("same-origin"));
+
+        let cross = ReplayContext::new(url("https://blog.other/post"))
+            .policy(ReferrerPolicy::NoReferrer)
+            .apply(&request);
+        assert_eq!(cross.header_value("origin"), Some("https://blog.other"));
+        assert_eq!(cross.header_value("referer"), None);
+        assert_eq!(cross.header_value("sec-fetch-site"), Some("cross-site"));
+
+        assert!(!replay_script(&request).contains("evil.example"));
+    }
+}
@@ -0,0 +1,238 @@
+//! One-call presets for a coherent regional identity.
+//!
+//! A profile claiming `de-DE` while its clock runs on New York time, its
+//! keystrokes come from a US keyboard and its geolocation sits in Virginia
+//! contradicts itself in four places. [`ChaserProfileBuilder::region`] sets
+//! all of them from one [`Region`]: locale (and with it `navigator.languages`,
+//! `Accept-Language` and the `Intl` number and date formats), timezone,
+//! keyboard layout and a geolocation in a large city of the region.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::profiles::ChaserProfile;
+//! use chaser_oxide::regions::Region;
+//!
+//! let profile = ChaserProfile::windows().region(Region::Germany).build();
+//! assert_eq!(profile.timezone(), "Europe/Berlin");
+//! ```
+//!
+//! [`ChaserProfileBuilder::region`]: crate::profiles::ChaserProfileBuilder::region
+
+use crate::keyboard::KeyboardLayout;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// A position reported through the Geolocation API.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Geolocation {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Accuracy radius in meters.
+    pub accuracy: f64,
+}
+
+/// A country whose locale, timezone, keyboard and location fit together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Region {
+    UnitedStates,
+    UnitedKingdom,
+    Canada,
+    Australia,
+    India,
+    Germany,
+    Austria,
+    Switzerland,
+    France,
+    Spain,
+    Italy,
+    Netherlands,
+    Poland,
+    Sweden,
+    Brazil,
+    Mexico,
+    Japan,
+    SouthKorea,
+}
+
+impl Region {
+    pub const ALL: &'static [Region] = &[
+        Region::UnitedStates,
+        Region::UnitedKingdom,
+        Region::Canada,
+        Region::Australia,
+        Region::India,
+        Region::Germany,
+        Region::Austria,
+        Region::Switzerland,
+        Region::France,
+        Region::Spain,
+        Region::Italy,
+        Region::Netherlands,
+        Region::Poland,
+        Region::Sweden,
+        Region::Brazil,
+        Region::Mexico,
+        Region::Japan,
+        Region::SouthKorea,
+    ];
+
+    /// BCP 47 locale, see [`crate::locales`].
+    pub fn locale(&self) -> &'static str {
+        match self {
+            Region::UnitedStates => "en-US",
+            Region::UnitedKingdom => "en-GB",
+            Region::Canada => "en-CA",
+            Region::Australia => "en-AU",
+            Region::India => "en-IN",
+            Region::Germany => "de-DE",
+            Region::Austria => "de-AT",
+            Region::Switzerland => "de-CH",
+            Region::France => "fr-FR",
+            Region::Spain => "es-ES",
+            Region::Italy => "it-IT",
+            Region::Netherlands => "nl-NL",
+            Region::Poland => "pl-PL",
+            Region::Sweden => "sv-SE",
+            Region::Brazil => "pt-BR",
+            Region::Mexico => "es-MX",
+            Region::Japan => "ja-JP",
+            Region::SouthKorea => "ko-KR",
+        }
+    }
+
+    /// IANA timezone of the region's largest city.
+    pub fn timezone(&self) -> &'static str {
+        match self {
+            Region::UnitedStates => "America/New_York",
+            Region::UnitedKingdom => "Europe/London",
+            Region::Canada => "America/Toronto",
+            Region::Australia => "Australia/Sydney",
+            Region::India => "Asia/Kolkata",
+            Region::Germany => "Europe/Berlin",
+            Region::Austria => "Europe/Vienna",
+            Region::Switzerland => "Europe/Zurich",
+            Region::France => "Europe/Paris",
+            Region::Spain => "Europe/Madrid",
+            Region::Italy => "Europe/Rome",
+            Region::Netherlands => "Europe/Amsterdam",
+            Region::Poland => "Europe/Warsaw",
+            Region::Sweden => "Europe/Stockholm",
+            Region::Brazil => "America/Sao_Paulo",
+            Region::Mexico => "America/Mexico_City",
+            Region::Japan => "Asia/Tokyo",
+            Region::SouthKorea => "Asia/Seoul",
+        }
+    }
+
+    /// The keyboard most people in the region type on.
+    pub fn keyboard(&self) -> KeyboardLayout {
+        match self {
+            Region::UnitedKingdom => KeyboardLayout::Uk,
+            Region::Germany | Region::Austria | Region::Switzerland => KeyboardLayout::German,
+            Region::France => KeyboardLayout::French,
+            Region::Spain | Region::Mexico => KeyboardLayout::Spanish,
+            Region::Italy => KeyboardLayout::Italian,
+            Region::Sweden => KeyboardLayout::Nordic,
+            Region::Brazil => KeyboardLayout::Brazilian,
+            Region::UnitedStates
+            | Region::Canada
+            | Region::Australia
+            | Region::India
+            | Region::Netherlands
+            | Region::Poland
+            | Region::Japan
+            | Region::SouthKorea => KeyboardLayout::Us,
+        }
+    }
+
+    /// ISO 4217 code of the local currency, e.g. for prices a flow enters.
+    ///
+    /// Number formats (decimal and grouping separators) follow the locale
+    /// through `Intl` and need no setting of their own.
+    pub fn currency(&self) -> &'static str {
+        match self {
+            Region::UnitedStates => "USD",
+            Region::UnitedKingdom => "GBP",
+            Region::Canada => "CAD",
+            Region::Australia => "AUD",
+            Region::India => "INR",
+            Region::Germany
+            | Region::Austria
+            | Region::France
+            | Region::Spain
+            | Region::Italy
+            | Region::Netherlands => "EUR",
+            Region::Switzerland => "CHF",
+            Region::Poland => "PLN",
+            Region::Sweden => "SEK",
+            Region::Brazil => "BRL",
+            Region::Mexico => "MXN",
+            Region::Japan => "JPY",
+            Region::SouthKorea => "KRW",
+        }
+    }
+
+    /// Center of the city [`timezone`](Self::timezone) is named after (or the
+    /// region's largest city).
+    pub fn city_center(&self) -> (f64, f64) {
+        match self {
+            Region::UnitedStates => (40.7128, -74.0060),
+            Region::UnitedKingdom => (51.5074, -0.1278),
+            Region::Canada => (43.6532, -79.3832),
+            Region::Australia => (-33.8688, 151.2093),
+            Region::India => (19.0760, 72.8777),
+            Region::Germany => (52.5200, 13.4050),
+            Region::Austria => (48.2082, 16.3738),
+            Region::Switzerland => (47.3769, 8.5417),
+            Region::France => (48.8566, 2.3522),
+            Region::Spain => (40.4168, -3.7038),
+            Region::Italy => (41.9028, 12.4964),
+            Region::Netherlands => (52.3676, 4.9041),
+            Region::Poland => (52.2297, 21.0122),
+            Region::Sweden => (59.3293, 18.0686),
+            Region::Brazil => (-23.5505, -46.6333),
+            Region::Mexico => (19.4326, -99.1332),
+            Region::Japan => (35.6762, 139.6503),
+            Region::SouthKorea => (37.5665, 126.9780),
+        }
+    }
+
+    /// A random spot within about 10km of the [`city_center`](Self::city_center),
+    /// with the 20-150m accuracy of Wi-Fi positioning.
+    pub fn geolocation(&self, rng: &mut impl Rng) -> Geolocation {
+        let (latitude, longitude) = self.city_center();
+        Geolocation {
+            latitude: latitude + rng.gen_range(-0.09..0.09),
+            longitude: longitude + rng.gen_range(-0.09..0.09) / latitude.to_radians().cos(),
+            accuracy: rng.gen_range(20.0..150.0_f64).round(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiles::ChaserProfile;
+
+    #[test]
+    fn region_presets_are_coherent() {
+        for region in Region::ALL {
+            assert!(
+                crate::locales::lookup(region.locale()).is_some(),
+                "{region:?}"
+            );
+        }
+
+        let profile = ChaserProfile::windows().region(Region::Germany).build();
+        assert_eq!(profile.locale(), "de-DE");
+        assert_eq!(profile.timezone(), "Europe/Berlin");
+        assert_eq!(
+            profile.accept_language(),
+            "de-DE,de;q=0.9,en-US;q=0.8,en;q=0.7"
+        );
+        assert_eq!(profile.keyboard_layout().code('z'), Some("KeyY"));
+        let geo = profile.geolocation().unwrap();
+        assert!((geo.latitude - 52.52).abs() < 0.1 && (geo.longitude - 13.405).abs() < 0.2);
+    }
+}
@@ -0,0 +1,152 @@
+//! Interactive REPL for poking at a live page.
+//!
+//! Plain input is evaluated as JavaScript in the isolated world (the same
+//! path as [`ChaserPage::evaluate_stealth`]); lines starting with `.` are
+//! crate commands. Type `.help` for the list. History is kept across
+//! sessions when a history file is configured.
+//!
+//! Requires the `repl` feature; the `chaser-oxide repl` binary wraps it.
+
+use crate::chaser::ChaserPage;
+use crate::page::ScreenshotParams;
+use anyhow::{anyhow, Result};
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+use std::path::PathBuf;
+
+const HELP: &str = "\
+<js>                  evaluate JavaScript in the isolated world
+.main <js>            evaluate JavaScript in the main world (detectable)
+.goto <url>           navigate
+.url                  print the current URL
+.click <selector>     humanized click on the first matching element
+.type <text>          type text with human delays
+.press <key>          press a key (Enter, Tab, ...)
+.scroll <dy>          humanized scroll by dy pixels
+.screenshot <path>    save a PNG screenshot
+.html                 print the page HTML
+.help                 show this help
+.exit                 quit";
+
+/// A line-based interactive session on a [`ChaserPage`].
+#[derive(Debug)]
+pub struct Repl {
+    page: ChaserPage,
+    history: Option<PathBuf>,
+}
+
+impl Repl {
+    pub fn new(page: ChaserPage) -> Self {
+        Self {
+            page,
+            history: None,
+        }
+    }
+
+    /// Load and save the input history from `path`.
+    pub fn history_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.history = Some(path.into());
+        self
+    }
+
+    /// Read and execute lines until `.exit` or end of input.
+    pub async fn run(self) -> Result<()> {
+        let mut editor = DefaultEditor::new()?;
+        if let Some(history) = &self.history {
+            let _ = editor.load_history(history);
+        }
+        println!("chaser-oxide repl, type .help for commands");
+
+        loop {
+            // rustyline blocks, keep it off the runtime threads driving the browser
+            let (returned, line) = tokio::task::spawn_blocking(move || {
+                let line = editor.readline("> ");
+                (editor, line)
+            })
+            .await?;
+            editor = returned;
+
+            let line = match line {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => break,
+                Err(e) => return Err(e.into()),
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let _ = editor.add_history_entry(line);
+            if line == ".exit" || line == ".quit" {
+                break;
+            }
+            if let Err(e) = self.execute(line).await {
+                println!("error: {e}");
+            }
+        }
+
+        if let Some(history) = &self.history {
+            let _ = editor.save_history(history);
+        }
+        Ok(())
+    }
+
+    /// Execute a single REPL line.
+    pub async fn execute(&self, line: &str) -> Result<()> {
+        let Some(command) = line.strip_prefix('.') else {
+            return print_value(self.page.evaluate_stealth(line).await?);
+        };
+        let (name, arg) = command
+            .split_once(char::is_whitespace)
+            .map(|(n, a)| (n, a.trim()))
+            .unwrap_or((command, ""));
+
+        match name {
+            "help" => println!("{HELP}"),
+            "main" => print_value(self.page.evaluate_main(arg).await?)?,
+            "goto" => self.page.goto(arg).await?,
+            "url" => println!("{}", self.page.url().await?.unwrap_or_default()),
+            "html" => println!("{}", self.page.content().await?),
+            "click" => {
+                let script = format!(
+                    "(() => {{ const el = document.querySelector({}); if (!el) return null; \
+                     el.scrollIntoView({{ block: 'center' }}); const r = el.getBoundingClientRect(); \
+                     return [r.x + r.width / 2, r.y + r.height / 2]; }})()",
+                    serde_json::to_string(arg)?
+                );
+                let center = self
+                    .page
+                    .evaluate_stealth(&script)
+                    .await?
+                    .and_then(|v| serde_json::from_value::<(f64, f64)>(v).ok())
+                    .ok_or_else(|| anyhow!("No element matches {}", arg))?;
+                self.page.click_human(center.0, center.1).await?;
+            }
+            "type" => self.page.type_text(arg).await?,
+            "press" => self.page.press_key(arg).await?,
+            "scroll" => self.page.scroll_human(arg.parse()?).await?,
+            "screenshot" => {
+                let path = if arg.is_empty() {
+                    "screenshot.png"
+                } else {
+                    arg
+                };
+                self.page
+                    .raw_page()
+                    .save_screenshot(ScreenshotParams::builder().build(), path)
+                    .await?;
+                println!("saved {path}");
+            }
+            other => return Err(anyhow!("Unknown command .{}, see .help", other)),
+        }
+        Ok(())
+    }
+}
+
+fn print_value(value: Option<serde_json::Value>) -> Result<()> {
+    match value {
+        Some(value) => println!("{}", serde_json::to_string_pretty(&value)?),
+        None => println!("undefined"),
+    }
+    Ok(())
+}
@@ -0,0 +1,614 @@
+//! Rotating proxies and profiles when sites push back.
+//!
+//! A 403 from one proxy, a run of captchas for one profile or a 429 from
+//! one site each call for a different answer: take the proxy out of the
+//! rotation, retire the profile, or leave the site alone for a while. A
+//! [`RotationStrategy`] makes that call for every navigation verdict and
+//! task result the [orchestrator](crate::orchestrator) sees, and an
+//! [`IdentityRotation`] configured on the pool with
+//! [`PoolConfigBuilder::rotation`](crate::pool::PoolConfigBuilder::rotation)
+//! carries it out for the following tasks:
+//!
+//! - retired proxies and profiles are skipped when tasks without an
+//!   explicit assignment get theirs; once all of them are retired, the
+//!   whole list is used again rather than failing every task;
+//! - tasks for a domain that is cooling down (see [`Task::domain`]) wait
+//!   before leasing a page.
+//!
+//! Signals come from the verdicts a
+//! [`VerdictCollector`](crate::verdict::VerdictCollector) collected on the
+//! task's page, or, for flows that collected none, from the task's error
+//! ([`ChaserError::BlockedByAntiBot`], [`ChaserError::CaptchaRequired`],
+//! [`ChaserError::RateLimited`] and proxy failures).
+//!
+//! [`RotationPolicy`] covers the common cases as configuration; any
+//! `Fn(&Signal<'_>) -> Decision` is a strategy as well.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::orchestrator::{self, Task};
+//! use chaser_oxide::pool::PoolConfig;
+//! use chaser_oxide::rotation::{BlockKind, IdentityRotation, RotationPolicy};
+//! use chaser_oxide::verdict::VerdictCollector;
+//!
+//! let rotation = IdentityRotation::new(
+//!     RotationPolicy::new()
+//!         .rotate_proxy_on([BlockKind::Blocked, BlockKind::RateLimited])
+//!         .rotate_profile_after(3)
+//!         .cooldown(Duration::from_secs(120)),
+//! );
+//! let config = PoolConfig::builder()
+//!     .proxies(proxies)
+//!     .profiles(profiles)
+//!     .rotation(rotation.clone())
+//!     .build();
+//! let verdicts = VerdictCollector::new();
+//! let tasks = urls
+//!     .into_iter()
+//!     .map(|url| {
+//!         let verdicts = verdicts.clone();
+//!         Task::new(url, move |page| async move {
+//!             verdicts.goto(&page, url).await.into_result()?;
+//!             Ok(page.content().await?)
+//!         })
+//!         .domain("shop.example.com")
+//!     })
+//!     .collect();
+//! orchestrator::run(tasks, config).await?;
+//! println!("burnt proxies: {:?}", rotation.retired_proxies());
+//! ```
+//!
+//! [`Task::domain`]: crate::orchestrator::Task::domain
+
+use crate::cancel::CancellationToken;
+use crate::error::ChaserError;
+use crate::policy::host_matches;
+use crate::pool::{PoolConfig, TaskAssignment};
+use crate::verdict::{NavigationVerdict, Outcome};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Push-back a [`RotationStrategy`] can react to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum BlockKind {
+    /// A block page or a 403.
+    Blocked,
+    /// HTTP 429.
+    RateLimited,
+    /// An interstitial such as Cloudflare's "Just a moment".
+    Challenged,
+    Captcha,
+    /// The proxy refused the credentials or could not be reached.
+    ProxyFailed,
+}
+
+impl BlockKind {
+    /// The push-back in a navigation outcome, `None` for passes and plain
+    /// failures.
+    pub fn of(outcome: &Outcome) -> Option<Self> {
+        match outcome {
+            Outcome::Blocked { .. } => Some(BlockKind::Blocked),
+            Outcome::RateLimited => Some(BlockKind::RateLimited),
+            Outcome::Challenged { .. } => Some(BlockKind::Challenged),
+            Outcome::Captcha { .. } => Some(BlockKind::Captcha),
+            Outcome::Passed | Outcome::HttpError { .. } | Outcome::Failed { .. } => None,
+        }
+    }
+}
+
+/// The outcome and push-back a task error stands for, if any.
+fn classify_error(error: &ChaserError) -> Option<(Outcome, Option<BlockKind>)> {
+    let outcome = match error {
+        ChaserError::BlockedByAntiBot { vendor } => Outcome::Blocked {
+            vendor: Some(vendor.clone()),
+        },
+        ChaserError::CaptchaRequired { provider } => Outcome::Captcha {
+            provider: provider.clone(),
+        },
+        ChaserError::RateLimited(_) => Outcome::RateLimited,
+        ChaserError::ProxyAuthFailed(_) | ChaserError::ProxyUnreachable(_) => {
+            return Some((
+                Outcome::Failed {
+                    error: error.to_string(),
+                },
+                Some(BlockKind::ProxyFailed),
+            ))
+        }
+        _ => return None,
+    };
+    let kind = BlockKind::of(&outcome);
+    Some((outcome, kind))
+}
+
+/// One navigation verdict or task result, as a [`RotationStrategy`] sees it.
+#[derive(Debug, Clone, Copy)]
+pub struct Signal<'a> {
+    /// Name of the task.
+    pub task: &'a str,
+    /// The task's [domain](crate::orchestrator::Task::domain), else the
+    /// host navigated to.
+    pub domain: Option<&'a str>,
+    /// The proxy the task ran through.
+    pub proxy: Option<&'a str>,
+    /// Index of the pool profile the task ran with; `None` for tasks with
+    /// their own profile, which are never rotated.
+    pub profile: Option<usize>,
+    pub outcome: &'a Outcome,
+    /// The push-back in `outcome`, `None` when the site let the task pass.
+    pub block: Option<BlockKind>,
+}
+
+/// What to do after a [`Signal`]. The default keeps everything as it is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Decision {
+    /// Take the signal's proxy out of the rotation.
+    pub rotate_proxy: bool,
+    /// Take the signal's profile out of the rotation.
+    pub rotate_profile: bool,
+    /// Hold tasks for the signal's domain back for this long.
+    pub cooldown: Option<Duration>,
+}
+
+impl Decision {
+    pub fn keep() -> Self {
+        Self::default()
+    }
+
+    pub fn rotate_proxy(mut self) -> Self {
+        self.rotate_proxy = true;
+        self
+    }
+
+    pub fn rotate_profile(mut self) -> Self {
+        self.rotate_profile = true;
+        self
+    }
+
+    pub fn cooldown(mut self, duration: Duration) -> Self {
+        self.cooldown = Some(duration);
+        self
+    }
+
+    /// Everything either decision asks for; the longer cooldown wins.
+    pub fn merge(self, other: Decision) -> Self {
+        Self {
+            rotate_proxy: self.rotate_proxy || other.rotate_proxy,
+            rotate_profile: self.rotate_profile || other.rotate_profile,
+            cooldown: self.cooldown.max(other.cooldown),
+        }
+    }
+}
+
+/// Decides how to respond to push-back. Called for every signal, passes
+/// included, from the tasks running concurrently on a pool.
+pub trait RotationStrategy: Send + Sync {
+    fn decide(&self, signal: &Signal<'_>) -> Decision;
+}
+
+impl<F> RotationStrategy for F
+where
+    F: Fn(&Signal<'_>) -> Decision + Send + Sync,
+{
+    fn decide(&self, signal: &Signal<'_>) -> Decision {
+        self(signal)
+    }
+}
+
+/// The common responses, configured rather than coded.
+///
+/// By default a proxy is rotated on blocks, rate limits, challenges and
+/// proxy failures, a profile after its third block of any kind, and a
+/// domain cools down for a minute after a rate limit.
+#[derive(Debug)]
+pub struct RotationPolicy {
+    proxy_on: BTreeSet<BlockKind>,
+    profile_after: Option<usize>,
+    cooldown_on: BTreeSet<BlockKind>,
+    cooldown: Duration,
+    /// Blocks per pool profile since it was last rotated.
+    blocks: Mutex<BTreeMap<usize, usize>>,
+}
+
+impl Default for RotationPolicy {
+    fn default() -> Self {
+        Self {
+            proxy_on: [
+                BlockKind::Blocked,
+                BlockKind::RateLimited,
+                BlockKind::Challenged,
+                BlockKind::ProxyFailed,
+            ]
+            .into(),
+            profile_after: Some(3),
+            cooldown_on: [BlockKind::RateLimited].into(),
+            cooldown: Duration::from_secs(60),
+            blocks: Mutex::default(),
+        }
+    }
+}
+
+impl RotationPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rotate the proxy on these kinds of push-back, replacing the default.
+    pub fn rotate_proxy_on(mut self, kinds: impl IntoIterator<Item = BlockKind>) -> Self {
+        self.proxy_on = kinds.into_iter().collect();
+        self
+    }
+
+    /// Rotate a profile once it met `blocks` push-backs of any kind.
+    pub fn rotate_profile_after(mut self, blocks: usize) -> Self {
+        self.profile_after = Some(blocks.max(1));
+        self
+    }
+
+    /// Never rotate profiles.
+    pub fn keep_profiles(mut self) -> Self {
+        self.profile_after = None;
+        self
+    }
+
+    /// Cool a domain down on these kinds of push-back, replacing the
+    /// default.
+    pub fn cooldown_on(mut self, kinds: impl IntoIterator<Item = BlockKind>) -> Self {
+        self.cooldown_on = kinds.into_iter().collect();
+        self
+    }
+
+    /// How long a domain cools down.
+    pub fn cooldown(mut self, duration: Duration) -> Self {
+        self.cooldown = duration;
+        self
+    }
+}
+
+impl RotationStrategy for RotationPolicy {
+    fn decide(&self, signal: &Signal<'_>) -> Decision {
+        let Some(kind) = signal.block else {
+            return Decision::keep();
+        };
+        let mut decision = Decision::keep();
+        if self.proxy_on.contains(&kind) {
+            decision = decision.rotate_proxy();
+        }
+        if self.cooldown_on.contains(&kind) {
+            decision = decision.cooldown(self.cooldown);
+        }
+        if let (Some(profile), Some(after)) = (signal.profile, self.profile_after) {
+            // a dead proxy says nothing about the profile
+            if kind != BlockKind::ProxyFailed {
+                let mut blocks = self.blocks.lock().unwrap();
+                let count = blocks.entry(profile).or_default();
+                *count += 1;
+                if *count >= after {
+                    blocks.remove(&profile);
+                    decision = decision.rotate_profile();
+                }
+            }
+        }
+        decision
+    }
+}
+
+#[derive(Debug, Default)]
+struct RotationState {
+    retired_proxies: BTreeSet<String>,
+    retired_profiles: BTreeSet<usize>,
+    /// Domain to the end of its cooldown.
+    cooldowns: BTreeMap<String, Instant>,
+    /// Round-robin position among the proxies and profiles in rotation.
+    next: usize,
+}
+
+/// A [`RotationStrategy`] and what it decided so far: retired proxies and
+/// profiles and domains cooling down. Clones share their state, so the
+/// handle passed to the pool config can be inspected during and after a
+/// run.
+#[derive(Clone)]
+pub struct IdentityRotation {
+    strategy: Arc<dyn RotationStrategy>,
+    state: Arc<Mutex<RotationState>>,
+}
+
+impl fmt::Debug for IdentityRotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdentityRotation")
+            .field("state", &self.state)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The `next`-th entry of `0..len` not in `retired`, or of all of them
+/// once every entry is retired.
+fn pick(len: usize, next: usize, retired: impl Fn(usize) -> bool) -> usize {
+    let available: Vec<usize> = (0..len).filter(|&idx| !retired(idx)).collect();
+    if available.is_empty() {
+        next % len
+    } else {
+        available[next % available.len()]
+    }
+}
+
+impl IdentityRotation {
+    pub fn new(strategy: impl RotationStrategy + 'static) -> Self {
+        Self {
+            strategy: Arc::new(strategy),
+            state: Arc::default(),
+        }
+    }
+
+    /// Proxies taken out of the rotation.
+    pub fn retired_proxies(&self) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        state.retired_proxies.iter().cloned().collect()
+    }
+
+    /// Indices of the pool profiles taken out of the rotation.
+    pub fn retired_profiles(&self) -> Vec<usize> {
+        let state = self.state.lock().unwrap();
+        state.retired_profiles.iter().copied().collect()
+    }
+
+    /// Domains cooling down, with the time left.
+    pub fn cooldowns(&self) -> Vec<(String, Duration)> {
+        let now = Instant::now();
+        let state = self.state.lock().unwrap();
+        state
+            .cooldowns
+            .iter()
+            .filter(|(_, until)| **until > now)
+            .map(|(domain, until)| (domain.clone(), *until - now))
+            .collect()
+    }
+
+    /// Put every proxy and profile back into the rotation and end all
+    /// cooldowns.
+    pub fn reset(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.retired_proxies.clear();
+        state.retired_profiles.clear();
+        state.cooldowns.clear();
+    }
+
+    /// Fill in what `assignment` leaves open from the proxies and profiles
+    /// of `config` still in rotation. Returns the index of the pool profile
+    /// picked, if one was.
+    pub(crate) fn assign(
+        &self,
+        config: &PoolConfig,
+        mut assignment: TaskAssignment,
+    ) -> (TaskAssignment, Option<usize>) {
+        let mut state = self.state.lock().unwrap();
+        let next = state.next;
+        state.next = state.next.wrapping_add(1);
+
+        let proxies = config.proxies();
+        if assignment.proxy.is_none() && !proxies.is_empty() {
+            let idx = pick(proxies.len(), next, |idx| {
+                state.retired_proxies.contains(&proxies[idx])
+            });
+            assignment.proxy = Some(proxies[idx].clone());
+        }
+        let profiles = config.profiles();
+        let mut slot = None;
+        if assignment.profile.is_none() && !profiles.is_empty() {
+            let idx = pick(profiles.len(), next, |idx| {
+                state.retired_profiles.contains(&idx)
+            });
+            assignment.profile = Some(profiles[idx].clone());
+            slot = Some(idx);
+        }
+        (assignment, slot)
+    }
+
+    /// Wait until `domain` is no longer cooling down. Fails with
+    /// [`ChaserError::Cancelled`] if `cancel` fires first.
+    pub(crate) async fn wait_cooldown(
+        &self,
+        domain: &str,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<(), ChaserError> {
+        loop {
+            let until = {
+                let state = self.state.lock().unwrap();
+                state
+                    .cooldowns
+                    .iter()
+                    .filter(|(cooling, _)| host_matches(cooling, domain))
+                    .map(|(_, until)| *until)
+                    .max()
+            };
+            let Some(until) = until.filter(|until| *until > Instant::now()) else {
+                return Ok(());
+            };
+            tracing::debug!("waiting for the cooldown of {} to end", domain);
+            match cancel {
+                Some(cancel) => tokio::select! {
+                    _ = tokio::time::sleep_until(until) => {}
+                    _ = cancel.cancelled() => return Err(ChaserError::Cancelled),
+                },
+                None => tokio::time::sleep_until(until).await,
+            }
+        }
+    }
+
+    /// Let the strategy decide on everything a finished task produced: the
+    /// verdicts collected on its page, else its result.
+    pub(crate) fn record_task(
+        &self,
+        task: &str,
+        domain: Option<&str>,
+        proxy: Option<&str>,
+        profile: Option<usize>,
+        verdicts: &[NavigationVerdict],
+        error: Option<&ChaserError>,
+    ) {
+        let base = Signal {
+            task,
+            domain,
+            proxy,
+            profile,
+            outcome: &Outcome::Passed,
+            block: None,
+        };
+        if !verdicts.is_empty() {
+            for verdict in verdicts {
+                let host = url::Url::parse(&verdict.url)
+                    .ok()
+                    .and_then(|url| url.host_str().map(str::to_string));
+                self.record(&Signal {
+                    domain: domain.or(host.as_deref()),
+                    outcome: &verdict.outcome,
+                    block: BlockKind::of(&verdict.outcome),
+                    ..base
+                });
+            }
+            return;
+        }
+        match error {
+            None => self.record(&base),
+            Some(error) => {
+                if let Some((outcome, block)) = classify_error(error) {
+                    self.record(&Signal {
+                        outcome: &outcome,
+                        block,
+                        ..base
+                    });
+                }
+            }
+        }
+    }
+
+    /// Ask the strategy about `signal` and carry out its decision.
+    pub(crate) fn record(&self, signal: &Signal<'_>) {
+        let decision = self.strategy.decide(signal);
+        let mut state = self.state.lock().unwrap();
+        if decision.rotate_proxy {
+            if let Some(proxy) = signal.proxy {
+                if state.retired_proxies.insert(proxy.to_string()) {
+                    tracing::info!("rotating out proxy {} after task {}", proxy, signal.task);
+                }
+            }
+        }
+        if decision.rotate_profile {
+            if let Some(profile) = signal.profile {
+                if state.retired_profiles.insert(profile) {
+                    tracing::info!(
+                        "rotating out profile {} after task {}",
+                        profile,
+                        signal.task
+                    );
+                }
+            }
+        }
+        if let (Some(cooldown), Some(domain)) = (decision.cooldown, signal.domain) {
+            let until = Instant::now() + cooldown;
+            let entry = state
+                .cooldowns
+                .entry(domain.to_ascii_lowercase())
+                .or_insert(until);
+            *entry = (*entry).max(until);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiles::ChaserProfile;
+
+    fn signal<'a>(outcome: &'a Outcome, proxy: &'a str, profile: usize) -> Signal<'a> {
+        Signal {
+            task: "t",
+            domain: Some("shop.example"),
+            proxy: Some(proxy),
+            profile: Some(profile),
+            outcome,
+            block: BlockKind::of(outcome),
+        }
+    }
+
+    #[test]
+    fn policy_rotates_proxies_profiles_and_cools_down() {
+        let policy = RotationPolicy::new().rotate_profile_after(2);
+        let blocked = Outcome::Blocked { vendor: None };
+        let limited = Outcome::RateLimited;
+
+        assert_eq!(
+            policy.decide(&signal(&Outcome::Passed, "p1", 0)),
+            Decision::keep()
+        );
+        let first = policy.decide(&signal(&blocked, "p1", 0));
+        assert!(first.rotate_proxy && !first.rotate_profile && first.cooldown.is_none());
+        let second = policy.decide(&signal(&limited, "p2", 0));
+        assert!(second.rotate_profile);
+        assert_eq!(second.cooldown, Some(Duration::from_secs(60)));
+        // the count starts over for the next profile using the slot
+        assert!(!policy.decide(&signal(&blocked, "p3", 0)).rotate_profile);
+
+        let error = ChaserError::ProxyUnreachable("net::ERR_PROXY".into());
+        let (_, block) = classify_error(&error).unwrap();
+        assert_eq!(block, Some(BlockKind::ProxyFailed));
+    }
+
+    #[tokio::test]
+    async fn rotation_skips_retired_proxies_and_profiles() {
+        let config = PoolConfig::builder()
+            .proxies(["http://a:1", "http://b:1"])
+            .profiles([
+                ChaserProfile::windows().build(),
+                ChaserProfile::macos_arm().build(),
+            ])
+            .build();
+        let rotation = IdentityRotation::new(|signal: &Signal<'_>| match signal.block {
+            Some(_) => Decision::keep()
+                .rotate_proxy()
+                .rotate_profile()
+                .cooldown(Duration::from_secs(30)),
+            None => Decision::keep(),
+        });
+
+        let (assignment, slot) = rotation.assign(&config, TaskAssignment::default());
+        assert_eq!(assignment.proxy.as_deref(), Some("http://a:1"));
+        rotation.record_task(
+            "t",
+            Some("shop.example"),
+            assignment.proxy.as_deref(),
+            slot,
+            &[],
+            Some(&ChaserError::RateLimited("https://shop.example".into())),
+        );
+        assert_eq!(rotation.retired_proxies(), ["http://a:1"]);
+        assert_eq!(rotation.retired_profiles(), [0]);
+        assert_eq!(rotation.cooldowns()[0].0, "shop.example");
+
+        for _ in 0..3 {
+            let (assignment, slot) = rotation.assign(&config, TaskAssignment::default());
+            assert_eq!(assignment.proxy.as_deref(), Some("http://b:1"));
+            assert_eq!(slot, Some(1));
+        }
+        // explicit assignments are left alone
+        let (assignment, slot) =
+            rotation.assign(&config, TaskAssignment::new().proxy("http://a:1"));
+        assert_eq!(assignment.proxy.as_deref(), Some("http://a:1"));
+        assert_eq!(slot, Some(1));
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        assert!(matches!(
+            rotation
+                .wait_cooldown("www.shop.example", Some(&cancel))
+                .await,
+            Err(ChaserError::Cancelled)
+        ));
+        assert!(rotation.wait_cooldown("other.example", None).await.is_ok());
+        rotation.reset();
+        assert!(rotation.retired_proxies().is_empty() && rotation.cooldowns().is_empty());
+    }
+}
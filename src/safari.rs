@@ -0,0 +1,290 @@
+//! A Safari identity for flows that only make HTTP requests.
+//!
+//! Some flows use the browser just to get through the front door and then
+//! talk to a site's API: they replay captured requests through the page's
+//! `fetch`, or hand cookies to an HTTP client. For those, what the server
+//! sees is mostly headers, and a fleet may want part of its traffic to look
+//! like Safari. A [`SafariProfile`] produces a coherent Safari request
+//! surface:
+//!
+//! - the Safari user agent of a Mac, iPhone or iPad, with the OS version
+//!   frozen the way Safari freezes it;
+//! - no `Sec-CH-UA-*` client hints, which Safari never sends;
+//! - Safari's `Accept`, `Accept-Language` and `Accept-Encoding` values and
+//!   its header order, see [`SafariProfile::headers`];
+//! - [`SafariProfile::rewrite_headers`] to turn headers captured from
+//!   Chrome into Safari ones before replaying them.
+//!
+//! [`ChaserPage::emulate_safari`] applies the user agent and headers to a
+//! page, so its own `fetch` calls carry them.
+//!
+//! # What cannot be spoofed
+//!
+//! The page is still Chrome, and anything below the headers says so:
+//!
+//! - the TLS ClientHello (cipher and extension order, GREASE) and the
+//!   HTTP/2 SETTINGS and frame order are Chrome's, so JA3/JA4 and HTTP/2
+//!   fingerprints identify Chrome;
+//! - scripts run on V8 and Blink: error stack formats, `window.chrome`,
+//!   `navigator.userAgentData`, supported CSS and Web APIs, WebGL and
+//!   canvas output all answer as Chrome, whatever `navigator.userAgent`
+//!   says.
+//!
+//! Use this persona where only headers are inspected, never to browse
+//! pages that fingerprint the client with scripts.
+//!
+//! # Example
+//!
+//! ```
+//! use chaser_oxide::safari::{RequestKind, SafariProfile};
+//!
+//! let safari = SafariProfile::mac().version("18.1").locale("de-DE");
+//! assert!(safari.user_agent().contains("Version/18.1 Safari/605.1.15"));
+//! let headers = safari.headers(RequestKind::Api);
+//! assert!(headers.iter().all(|(name, _)| !name.starts_with("Sec-CH-UA")));
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::ChaserResult as Result;
+use chromiumoxide_cdp::cdp::browser_protocol::emulation::SetUserAgentOverrideParams;
+use chromiumoxide_cdp::cdp::browser_protocol::network::{Headers, SetExtraHttpHeadersParams};
+
+/// The Apple device the Safari runs on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SafariDevice {
+    Mac,
+    IPhone,
+    /// iPadOS asks for desktop sites by default and reports itself as a Mac.
+    IPad,
+}
+
+/// What a request is for, which decides its `Accept` and `Sec-Fetch-*`
+/// headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RequestKind {
+    /// A top-level navigation typed into the address bar.
+    Navigation,
+    /// A same-origin `fetch` or XHR from a page script.
+    Api,
+}
+
+/// The request surface of one Safari installation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SafariProfile {
+    device: SafariDevice,
+    version: String,
+    os_version: String,
+    locale: String,
+}
+
+impl SafariProfile {
+    /// Safari 18.1 on macOS 15.1.
+    pub fn mac() -> Self {
+        Self::new(SafariDevice::Mac, "15.1")
+    }
+
+    /// Safari 18.1 on iOS 18.1.
+    pub fn iphone() -> Self {
+        Self::new(SafariDevice::IPhone, "18.1")
+    }
+
+    /// Safari 18.1 on iPadOS 18.1.
+    pub fn ipad() -> Self {
+        Self::new(SafariDevice::IPad, "18.1")
+    }
+
+    fn new(device: SafariDevice, os_version: &str) -> Self {
+        Self {
+            device,
+            version: "18.1".to_string(),
+            os_version: os_version.to_string(),
+            locale: "en-US".to_string(),
+        }
+    }
+
+    /// The Safari version, e.g. `17.6`. On iOS and iPadOS Safari ships
+    /// with the OS, so this sets the OS version too.
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        if self.device != SafariDevice::Mac {
+            self.os_version = self.version.clone();
+        }
+        self
+    }
+
+    /// The macOS version, e.g. `14.7`. It does not show in the user agent,
+    /// which Safari freezes at 10.15.7, but keeps the profile consistent.
+    pub fn os_version(mut self, version: impl Into<String>) -> Self {
+        self.os_version = version.into();
+        self
+    }
+
+    /// The system locale, e.g. `de-DE`.
+    pub fn locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = locale.into();
+        self
+    }
+
+    pub fn device(&self) -> SafariDevice {
+        self.device
+    }
+
+    pub fn safari_version(&self) -> &str {
+        &self.version
+    }
+
+    pub fn os_version_string(&self) -> &str {
+        &self.os_version
+    }
+
+    /// The `User-Agent` header and `navigator.userAgent`.
+    pub fn user_agent(&self) -> String {
+        match self.device {
+            SafariDevice::Mac | SafariDevice::IPad => format!(
+                "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/{} Safari/605.1.15",
+                self.version
+            ),
+            SafariDevice::IPhone => format!(
+                "Mozilla/5.0 (iPhone; CPU iPhone OS {} like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/{} Mobile/15E148 Safari/604.1",
+                self.os_version.replace('.', "_"),
+                self.version
+            ),
+        }
+    }
+
+    /// `navigator.platform`.
+    pub fn platform(&self) -> &'static str {
+        match self.device {
+            SafariDevice::Mac | SafariDevice::IPad => "MacIntel",
+            SafariDevice::IPhone => "iPhone",
+        }
+    }
+
+    /// Safari lists the system language and its base language only,
+    /// without the English fallbacks Chrome adds.
+    pub fn accept_language(&self) -> String {
+        let base = self.locale.split('-').next().unwrap_or(&self.locale);
+        if base == self.locale {
+            self.locale.clone()
+        } else {
+            format!("{},{};q=0.9", self.locale, base)
+        }
+    }
+
+    /// Safari advertises neither zstd nor Chrome's `Priority` hints.
+    pub fn accept_encoding(&self) -> &'static str {
+        "gzip, deflate, br"
+    }
+
+    /// The headers Safari sends with a `kind` request, in its order.
+    /// `Cookie`, `Origin` and `Referer` are left to the caller; they go
+    /// after `Sec-Fetch-Site`.
+    pub fn headers(&self, kind: RequestKind) -> Vec<(&'static str, String)> {
+        let (accept, site, dest, mode) = match kind {
+            RequestKind::Navigation => (
+                "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+                "none",
+                "document",
+                "navigate",
+            ),
+            RequestKind::Api => ("*/*", "same-origin", "empty", "cors"),
+        };
+        vec![
+            ("Accept", accept.to_string()),
+            ("Sec-Fetch-Site", site.to_string()),
+            ("Sec-Fetch-Dest", dest.to_string()),
+            ("Accept-Language", self.accept_language()),
+            ("Sec-Fetch-Mode", mode.to_string()),
+            ("User-Agent", self.user_agent()),
+            ("Accept-Encoding", self.accept_encoding().to_string()),
+        ]
+    }
+
+    /// Turn headers captured from Chrome into the ones Safari would send:
+    /// client hints and `Priority` are dropped, and the user agent and
+    /// accept headers are replaced. Everything else keeps its value and
+    /// order.
+    pub fn rewrite_headers(&self, headers: &[(String, String)]) -> Vec<(String, String)> {
+        headers
+            .iter()
+            .filter(|(name, _)| !is_chrome_only(name))
+            .map(|(name, value)| {
+                let value = match name.to_ascii_lowercase().as_str() {
+                    "user-agent" => self.user_agent(),
+                    "accept-language" => self.accept_language(),
+                    "accept-encoding" => self.accept_encoding().to_string(),
+                    _ => value.clone(),
+                };
+                (name.clone(), value)
+            })
+            .collect()
+    }
+}
+
+/// Headers Chrome sends and Safari never does.
+fn is_chrome_only(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.starts_with("sec-ch-") || name == "priority" || name == "x-client-data"
+}
+
+impl ChaserPage {
+    /// Make the page's requests carry the headers of `safari`.
+    ///
+    /// The user agent is overridden without client hint metadata, so
+    /// Chrome stops sending `Sec-CH-UA-*`, and `Accept-Encoding` is pinned
+    /// to Safari's. Only the request surface changes: see the
+    /// [module docs](crate::safari) for what still reads as Chrome. Do not
+    /// combine with [`apply_profile`](Self::apply_profile), which sets a
+    /// Chrome user agent again.
+    pub async fn emulate_safari(&self, safari: &SafariProfile) -> Result<()> {
+        self.raw_page()
+            .execute(SetUserAgentOverrideParams {
+                user_agent: safari.user_agent(),
+                accept_language: Some(safari.accept_language()),
+                platform: Some(safari.platform().to_string()),
+                user_agent_metadata: None,
+            })
+            .await?;
+        self.raw_page()
+            .execute(SetExtraHttpHeadersParams::new(Headers::new(
+                serde_json::json!({ "Accept-Encoding": safari.accept_encoding() }),
+            )))
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chrome_headers_become_safari_headers() {
+        let safari = SafariProfile::iphone().version("17.6").locale("fr-FR");
+        assert_eq!(
+            safari.user_agent(),
+            "Mozilla/5.0 (iPhone; CPU iPhone OS 17_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.6 Mobile/15E148 Safari/604.1"
+        );
+        assert_eq!(safari.accept_language(), "fr-FR,fr;q=0.9");
+
+        let captured: Vec<(String, String)> = [
+            ("sec-ch-ua", "\"Chromium\";v=\"131\""),
+            ("sec-ch-ua-mobile", "?0"),
+            (
+                "user-agent",
+                "Mozilla/5.0 ... Chrome/131.0.0.0 Safari/537.36",
+            ),
+            ("content-type", "application/json"),
+            ("accept-encoding", "gzip, deflate, br, zstd"),
+            ("priority", "u=1, i"),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+        let rewritten = safari.rewrite_headers(&captured);
+        let names: Vec<&str> = rewritten.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["user-agent", "content-type", "accept-encoding"]);
+        assert_eq!(rewritten[0].1, safari.user_agent());
+        assert_eq!(rewritten[2].1, "gzip, deflate, br");
+    }
+}
@@ -0,0 +1,248 @@
+//! Which scripts a page runs, and which of them are anti-bot sensors.
+//!
+//! Knowing the defensive stack of a target decides how carefully it has to
+//! be driven. [`ChaserPage::script_inventory`] lists every script of the
+//! page in one isolated-world pass: external scripts with their origin and
+//! sizes from the performance timeline (including scripts that were loaded
+//! and then removed from the DOM), and inline scripts with their length.
+//! Each is checked against [`KNOWN_SENSORS`] by URL and, for inline
+//! scripts, by content. [`ChaserPage::script_inventory_scanned`] also loads
+//! the body of every external script (outside the page, like DevTools) so
+//! sensors served from first-party paths are recognised too.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let scripts = chaser.script_inventory_scanned().await?;
+//! for script in scripts.iter().filter(|s| !s.sensors.is_empty()) {
+//!     println!("{:?}: {:?}", script.url, script.sensors);
+//! }
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::ChaserResult as Result;
+use crate::links::same_site;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// `{page, scripts}`: the scripts in the DOM, then those the performance
+/// timeline saw load that are no longer in it.
+const SCRIPTS_SCRIPT: &str = r#"(() => {
+    const JS = /^(|module|(text|application)\/(x-)?(java|ecma)script)$/i;
+    const timing = new Map(performance.getEntriesByType('resource')
+        .filter(entry => entry.initiatorType === 'script' || /\.m?js(\?|#|$)/.test(entry.name))
+        .map(entry => [entry.name, entry]));
+    const sizes = entry => entry
+        ? { size: entry.decodedBodySize || null, transfer_size: entry.transferSize || null }
+        : { size: null, transfer_size: null };
+    const scripts = [];
+    for (const script of document.querySelectorAll('script')) {
+        if (!JS.test((script.getAttribute('type') || '').trim())) continue;
+        const module = (script.getAttribute('type') || '').toLowerCase() === 'module';
+        if (script.src) {
+            scripts.push(Object.assign({ url: script.src, text: null, module, async: script.async, defer: script.defer },
+                sizes(timing.get(script.src))));
+            timing.delete(script.src);
+        } else {
+            const text = script.text;
+            scripts.push({ url: null, text: text.slice(0, 1 << 20), module, async: false, defer: false,
+                size: new Blob([text]).size, transfer_size: null });
+        }
+    }
+    for (const [url, entry] of timing) {
+        scripts.push(Object.assign({ url, text: null, module: false, async: true, defer: false }, sizes(entry)));
+    }
+    return { page: location.href, scripts };
+})()"#;
+
+/// How to recognise an anti-bot sensor script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SensorSignature {
+    pub name: &'static str,
+    /// Lowercase fragments of the script URL.
+    pub url_markers: &'static [&'static str],
+    /// Identifiers found in the script's source.
+    pub content_markers: &'static [&'static str],
+}
+
+/// Signatures of the common sensor scripts.
+pub const KNOWN_SENSORS: &[SensorSignature] = &[
+    SensorSignature {
+        name: "Akamai Bot Manager",
+        url_markers: &["/akam/", "/_bm/"],
+        content_markers: &["bmak", "_abck", "sensor_data"],
+    },
+    SensorSignature {
+        name: "PerimeterX",
+        url_markers: &[
+            "perimeterx.net",
+            "px-cdn.net",
+            "px-cloud.net",
+            "pxchk.net",
+            "/px/client",
+        ],
+        content_markers: &["_pxAppId", "_pxVid", "px-captcha"],
+    },
+    SensorSignature {
+        name: "DataDome",
+        url_markers: &["datadome.co", "captcha-delivery.com"],
+        content_markers: &["ddjskey", "ddoptions", "datadome"],
+    },
+    SensorSignature {
+        name: "FingerprintJS",
+        url_markers: &["fpjs.io", "fpcdn.io", "openfpcdn.io", "fingerprintjs"],
+        content_markers: &["FingerprintJS", "fpjs_", "visitorId"],
+    },
+];
+
+/// One script of the page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptInfo {
+    /// `None` for inline scripts.
+    pub url: Option<String>,
+    /// Scheme, host and port of `url`.
+    pub origin: Option<String>,
+    /// Whether it is served from another host than the page, ignoring a
+    /// `www.` prefix. Inline scripts are first-party.
+    pub third_party: bool,
+    pub module: bool,
+    pub is_async: bool,
+    pub defer: bool,
+    /// Decoded size in bytes, when known. Cross-origin scripts report it
+    /// only if they send `Timing-Allow-Origin`.
+    pub size: Option<u64>,
+    /// Bytes on the wire including headers; 0 from cache.
+    pub transfer_size: Option<u64>,
+    /// Names of the [`KNOWN_SENSORS`] recognised in it.
+    pub sensors: Vec<String>,
+}
+
+impl ScriptInfo {
+    pub fn is_inline(&self) -> bool {
+        self.url.is_none()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawScript {
+    url: Option<String>,
+    text: Option<String>,
+    module: bool,
+    #[serde(rename = "async")]
+    is_async: bool,
+    defer: bool,
+    size: Option<u64>,
+    transfer_size: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawScripts {
+    page: String,
+    scripts: Vec<RawScript>,
+}
+
+/// Names of the known sensors matching a script's URL or source.
+fn detect(url: Option<&str>, source: Option<&str>) -> Vec<String> {
+    let url = url.map(str::to_ascii_lowercase);
+    KNOWN_SENSORS
+        .iter()
+        .filter(|sensor| {
+            url.as_deref()
+                .is_some_and(|url| sensor.url_markers.iter().any(|marker| url.contains(marker)))
+                || source.is_some_and(|source| {
+                    sensor
+                        .content_markers
+                        .iter()
+                        .any(|marker| source.contains(marker))
+                })
+        })
+        .map(|sensor| sensor.name.to_string())
+        .collect()
+}
+
+fn into_inventory(raw: RawScripts) -> Vec<ScriptInfo> {
+    let page = Url::parse(&raw.page).ok();
+    raw.scripts
+        .into_iter()
+        .map(|script| {
+            let parsed = script.url.as_deref().and_then(|url| Url::parse(url).ok());
+            ScriptInfo {
+                origin: parsed
+                    .as_ref()
+                    .map(|url| url.origin().ascii_serialization()),
+                third_party: parsed
+                    .as_ref()
+                    .is_some_and(|url| !same_site(url, page.as_ref())),
+                sensors: detect(script.url.as_deref(), script.text.as_deref()),
+                url: script.url,
+                module: script.module,
+                is_async: script.is_async,
+                defer: script.defer,
+                size: script.size,
+                transfer_size: script.transfer_size,
+            }
+        })
+        .collect()
+}
+
+impl ChaserPage {
+    /// The scripts of the current page in document order, then those that
+    /// left the DOM after loading, with sensors recognised by URL or inline
+    /// source.
+    pub async fn script_inventory(&self) -> Result<Vec<ScriptInfo>> {
+        match self.evaluate_stealth(SCRIPTS_SCRIPT).await? {
+            Some(value) if !value.is_null() => Ok(into_inventory(serde_json::from_value(value)?)),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Like [`script_inventory`](Self::script_inventory), also matching the
+    /// source of every external script. Scripts that cannot be loaded keep
+    /// their URL-based result.
+    pub async fn script_inventory_scanned(&self) -> Result<Vec<ScriptInfo>> {
+        let mut scripts = self.script_inventory().await?;
+        for script in &mut scripts {
+            let Some(url) = script.url.clone() else {
+                continue;
+            };
+            if let Ok(source) = self.load_resource(&url).await {
+                for sensor in detect(None, Some(&source)) {
+                    if !script.sensors.contains(&sensor) {
+                        script.sensors.push(sensor);
+                    }
+                }
+                script.size = script.size.or(Some(source.len() as u64));
+            }
+        }
+        Ok(scripts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn classifies_scripts() {
+        let raw: RawScripts = serde_json::from_value(json!({
+            "page": "https://www.shop.test/",
+            "scripts": [
+                {"url": "https://shop.test/app.js", "text": null, "module": true, "async": false,
+                 "defer": false, "size": 1200, "transfer_size": 400},
+                {"url": "https://js.datadome.co/tags.js", "text": null, "module": false, "async": true,
+                 "defer": false, "size": null, "transfer_size": null},
+                {"url": null, "text": "var bmak = bmak || {};", "module": false, "async": false,
+                 "defer": false, "size": 22, "transfer_size": null}
+            ]
+        }))
+        .unwrap();
+        let scripts = into_inventory(raw);
+        assert!(!scripts[0].third_party && scripts[0].sensors.is_empty());
+        assert_eq!(scripts[1].origin.as_deref(), Some("https://js.datadome.co"));
+        assert!(scripts[1].third_party);
+        assert_eq!(scripts[1].sensors, ["DataDome"]);
+        assert!(scripts[2].is_inline() && !scripts[2].third_party);
+        assert_eq!(scripts[2].sensors, ["Akamai Bot Manager"]);
+    }
+}
@@ -0,0 +1,424 @@
+//! Seeding browsing history and `localStorage` without a warm-up crawl.
+//!
+//! [`ChaserContext::seed_history`] and [`ChaserContext::seed_local_storage`]
+//! work on a live context: a throwaway page navigates to the given URLs
+//! while every document request is answered locally through the Fetch
+//! domain, so the browser records the visits and stores the items without a
+//! single request reaching the sites.
+//!
+//! Off-the-record contexts keep that history in memory only. For a persistent
+//! user data dir, [`write_history_db`] (feature `sqlite`) inserts backdated
+//! visits into Chrome's `History` database while the browser is not running.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::seeding::HistoryEntry;
+//!
+//! context
+//!     .seed_history(&[
+//!         HistoryEntry::new("https://news.ycombinator.com/").title("Hacker News"),
+//!         HistoryEntry::new("https://github.com/").typed(),
+//!     ])
+//!     .await?;
+//! context
+//!     .seed_local_storage("https://example.com", [("theme", "dark"), ("consent", "1")])
+//!     .await?;
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::context::ChaserContext;
+use crate::ResourceType;
+use anyhow::{anyhow, Result};
+use chromiumoxide_cdp::cdp::browser_protocol::fetch::EventRequestPaused;
+use futures::StreamExt;
+use std::time::SystemTime;
+use url::Url;
+
+const STUB_HTML: &str = "<!DOCTYPE html><html><head><title></title></head><body></body></html>";
+
+/// A page visit to record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub url: String,
+    pub title: Option<String>,
+    /// When the visit happened; only honoured by [`write_history_db`].
+    pub visited_at: Option<SystemTime>,
+    /// Whether the URL was typed into the address bar rather than followed.
+    pub typed: bool,
+}
+
+impl HistoryEntry {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            title: None,
+            visited_at: None,
+            typed: false,
+        }
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn visited_at(mut self, at: SystemTime) -> Self {
+        self.visited_at = Some(at);
+        self
+    }
+
+    pub fn typed(mut self) -> Self {
+        self.typed = true;
+        self
+    }
+}
+
+impl ChaserContext {
+    /// Record visits to `entries` in this context's history.
+    pub async fn seed_history(&self, entries: &[HistoryEntry]) -> Result<()> {
+        let stub = StubPage::open(self).await?;
+        let result = async {
+            for entry in entries {
+                stub.page.goto(&entry.url).await?;
+                if let Some(title) = &entry.title {
+                    stub.page
+                        .evaluate_stealth(&format!(
+                            "document.title = {}",
+                            serde_json::to_string(title)?
+                        ))
+                        .await?;
+                }
+            }
+            Ok(())
+        }
+        .await;
+        stub.close().await;
+        result
+    }
+
+    /// Store `items` in the `localStorage` of `origin`.
+    pub async fn seed_local_storage<I, K, V>(&self, origin: &str, items: I) -> Result<()>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let origin = Url::parse(origin)?;
+        if origin.host_str().is_none() {
+            return Err(anyhow!("{} has no origin", origin));
+        }
+        let items: serde_json::Map<String, serde_json::Value> = items
+            .into_iter()
+            .map(|(k, v)| (k.into(), serde_json::Value::String(v.into())))
+            .collect();
+
+        let stub = StubPage::open(self).await?;
+        let result = async {
+            stub.page
+                .goto(origin.origin().ascii_serialization().as_str())
+                .await?;
+            stub.page
+                .evaluate_stealth(&format!(
+                    "for (const [k, v] of Object.entries({})) localStorage.setItem(k, v)",
+                    serde_json::Value::Object(items)
+                ))
+                .await?;
+            Ok(())
+        }
+        .await;
+        stub.close().await;
+        result
+    }
+}
+
+/// A page in the context whose document requests are all answered with an
+/// empty HTML page.
+pub(crate) struct StubPage {
+    pub(crate) page: ChaserPage,
+    responder: tokio::task::JoinHandle<()>,
+}
+
+impl StubPage {
+    pub(crate) async fn open(context: &ChaserContext) -> Result<Self> {
+        let page = context.new_page().await?;
+        let mut paused = page
+            .raw_page()
+            .event_listener::<EventRequestPaused>()
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        page.enable_request_interception("*", Some(ResourceType::Document))
+            .await?;
+        let responder_page = page.clone();
+        let responder = tokio::spawn(async move {
+            while let Some(event) = paused.next().await {
+                let request_id: String = event.request_id.clone().into();
+                let _ = responder_page
+                    .fulfill_request_html(request_id, STUB_HTML, 200)
+                    .await;
+            }
+        });
+        Ok(Self { page, responder })
+    }
+
+    pub(crate) async fn close(self) {
+        let _ = self.page.disable_request_interception().await;
+        self.responder.abort();
+        let _ = self.page.raw_page().clone().close().await;
+    }
+}
+
+/// Insert `entries` into the `History` database of the `Default` profile in
+/// `user_data_dir`. Returns the number of visits written.
+///
+/// The browser must not be running, and must have been started once on the
+/// directory so the database exists with Chrome's own schema.
+#[cfg(feature = "sqlite")]
+pub fn write_history_db(
+    user_data_dir: impl AsRef<std::path::Path>,
+    entries: &[HistoryEntry],
+) -> Result<usize> {
+    use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+
+    // Chrome stores microseconds since 1601-01-01
+    const WINDOWS_EPOCH_OFFSET_SECS: u64 = 11_644_473_600;
+    // PAGE_TRANSITION_LINK / TYPED with CHAIN_START | CHAIN_END
+    const TRANSITION_LINK: i64 = 0x3000_0000;
+    const TRANSITION_TYPED: i64 = 0x3000_0001;
+
+    let path = user_data_dir.as_ref().join("Default").join("History");
+    let conn = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_WRITE)
+        .map_err(|e| anyhow!("Cannot open {}: {}", path.display(), e))?;
+    let tx = conn.unchecked_transaction()?;
+    for entry in entries {
+        let visited = entry
+            .visited_at
+            .unwrap_or_else(SystemTime::now)
+            .duration_since(SystemTime::UNIX_EPOCH)?;
+        let time = (visited.as_micros() + WINDOWS_EPOCH_OFFSET_SECS as u128 * 1_000_000) as i64;
+        let typed = i64::from(entry.typed);
+        let title = entry.title.clone().unwrap_or_default();
+
+        let existing: Option<i64> = tx
+            .query_row(
+                "SELECT id FROM urls WHERE url = ?1",
+                params![entry.url],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let url_id = match existing {
+            Some(id) => {
+                tx.execute(
+                    "UPDATE urls SET visit_count = visit_count + 1, typed_count = typed_count + ?2,
+                     last_visit_time = MAX(last_visit_time, ?3) WHERE id = ?1",
+                    params![id, typed, time],
+                )?;
+                id
+            }
+            None => {
+                tx.execute(
+                    "INSERT INTO urls (url, title, visit_count, typed_count, last_visit_time, hidden)
+                     VALUES (?1, ?2, 1, ?3, ?4, 0)",
+                    params![entry.url, title, typed, time],
+                )?;
+                tx.last_insert_rowid()
+            }
+        };
+        let transition = if entry.typed {
+            TRANSITION_TYPED
+        } else {
+            TRANSITION_LINK
+        };
+        tx.execute(
+            "INSERT INTO visits (url, visit_time, from_visit, transition, segment_id, visit_duration)
+             VALUES (?1, ?2, 0, ?3, 0, 0)",
+            params![url_id, time, transition],
+        )?;
+    }
+    tx.commit()?;
+    Ok(entries.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::browser::Browser;
+    use crate::profiles::ChaserProfile;
+    use crate::transport::MockTransport;
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    async fn context(mock: &MockTransport) -> ChaserContext {
+        let browser = Arc::new(Browser::with_transport(mock.clone()));
+        ChaserContext::create(browser, ChaserProfile::windows().build(), None)
+            .await
+            .unwrap()
+    }
+
+    fn expressions(mock: &MockTransport) -> Vec<String> {
+        mock.commands_to("Runtime.evaluate")
+            .into_iter()
+            .map(|params| params["expression"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn the_stub_page_answers_documents_locally() {
+        let mock = MockTransport::new();
+        let stub = StubPage::open(&context(&mock).await).await.unwrap();
+        let patterns = &mock.commands_to("Fetch.enable")[0]["patterns"];
+        assert_eq!(
+            patterns,
+            &json!([{ "urlPattern": "*", "resourceType": "Document" }])
+        );
+
+        mock.emit(
+            "Fetch.requestPaused",
+            json!({
+                "requestId": "interception-1", "frameId": crate::transport::MAIN_FRAME,
+                "resourceType": "Document",
+                "request": {
+                    "url": "https://news.example/", "method": "GET", "headers": {},
+                    "initialPriority": "VeryHigh", "referrerPolicy": "no-referrer"
+                }
+            }),
+        );
+        while mock.commands_to("Fetch.fulfillRequest").is_empty() {
+            tokio::task::yield_now().await;
+        }
+        let fulfilled = &mock.commands_to("Fetch.fulfillRequest")[0];
+        assert_eq!(fulfilled["requestId"], "interception-1");
+        assert_eq!(fulfilled["responseCode"], 200);
+        assert_eq!(fulfilled["body"], STANDARD.encode(STUB_HTML));
+
+        stub.close().await;
+        assert_eq!(mock.commands_to("Fetch.disable").len(), 1);
+        assert_eq!(mock.commands_to("Page.close").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn history_visits_every_entry_and_sets_titles() {
+        let mock = MockTransport::new();
+        let context = context(&mock).await;
+        context
+            .seed_history(&[
+                HistoryEntry::new("https://news.example/").title("Daily \"News\""),
+                HistoryEntry::new("https://github.com/").typed(),
+            ])
+            .await
+            .unwrap();
+
+        let visited: Vec<_> = mock
+            .commands_to("Page.navigate")
+            .into_iter()
+            .map(|params| params["url"].clone())
+            .collect();
+        assert_eq!(visited, ["https://news.example/", "https://github.com/"]);
+        let titles: Vec<_> = expressions(&mock)
+            .into_iter()
+            .filter(|e| e.starts_with("document.title"))
+            .collect();
+        assert_eq!(titles, [r#"document.title = "Daily \"News\"""#]);
+        assert_eq!(mock.commands_to("Page.close").len(), 1);
+
+        // a failing visit ends the seeding, the stub page is closed anyway
+        mock.fail("Page.navigate", "net::ERR_ABORTED");
+        assert!(context
+            .seed_history(&[HistoryEntry::new("https://news.example/")])
+            .await
+            .is_err());
+        assert_eq!(mock.commands_to("Page.close").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn local_storage_is_written_on_the_origin() {
+        let mock = MockTransport::new();
+        let context = context(&mock).await;
+        context
+            .seed_local_storage("https://example.com/account?tab=1", [("theme", "dark")])
+            .await
+            .unwrap();
+        assert_eq!(
+            mock.commands_to("Page.navigate")[0]["url"],
+            "https://example.com"
+        );
+        assert!(expressions(&mock).contains(
+            &r#"for (const [k, v] of Object.entries({"theme":"dark"})) localStorage.setItem(k, v)"#
+                .to_string()
+        ));
+
+        mock.clear();
+        let err = context
+            .seed_local_storage("data:text/html,hi", [("a", "b")])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().ends_with("has no origin"));
+        assert!(context
+            .seed_local_storage("example.com", [("a", "b")])
+            .await
+            .is_err());
+        assert!(mock.commands_to("Target.createTarget").is_empty());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn history_db_gets_backdated_visits() {
+        use rusqlite::Connection;
+        use std::time::Duration;
+
+        let dir = std::env::temp_dir().join(format!("chaser-history-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("Default")).unwrap();
+        let db = dir.join("Default").join("History");
+        Connection::open(&db)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE urls (id INTEGER PRIMARY KEY, url TEXT, title TEXT,
+                    visit_count INTEGER, typed_count INTEGER, last_visit_time INTEGER,
+                    hidden INTEGER);
+                 CREATE TABLE visits (id INTEGER PRIMARY KEY, url INTEGER, visit_time INTEGER,
+                    from_visit INTEGER, transition INTEGER, segment_id INTEGER,
+                    visit_duration INTEGER);",
+            )
+            .unwrap();
+
+        let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        let written = write_history_db(
+            &dir,
+            &[
+                HistoryEntry::new("https://github.com/")
+                    .title("GitHub")
+                    .visited_at(at(1_700_000_000)),
+                HistoryEntry::new("https://github.com/")
+                    .typed()
+                    .visited_at(at(1_600_000_000)),
+            ],
+        )
+        .unwrap();
+        assert_eq!(written, 2);
+
+        let conn = Connection::open(&db).unwrap();
+        let url: (String, i64, i64, i64) = conn
+            .query_row(
+                "SELECT title, visit_count, typed_count, last_visit_time FROM urls",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        // microseconds since 1601, the later visit wins
+        assert_eq!(url, ("GitHub".to_string(), 2, 1, 13_344_473_600_000_000));
+        let transitions: Vec<i64> = conn
+            .prepare("SELECT transition FROM visits ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(transitions, [0x3000_0000, 0x3000_0001]);
+
+        // a directory Chrome never ran on has no database to write to
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(write_history_db(&dir, &[HistoryEntry::new("https://a.example/")]).is_err());
+    }
+}
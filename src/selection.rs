@@ -0,0 +1,325 @@
+//! Selecting text and placing the caret the way a person does it with a
+//! mouse.
+//!
+//! [`ChaserPage::select_text_human`] presses just inside the first character
+//! and drags to just inside the last, or double/triple-clicks for a word or
+//! paragraph, so the page sees the `mousedown`, `selectstart`,
+//! `selectionchange` and `mouseup` sequence of a real selection instead of a
+//! scripted `Range`. [`ChaserPage::click_caret_at`] clicks between two
+//! characters of an input before typing, e.g. to fix a typo in the middle of
+//! a prefilled value, and [`ChaserPage::get_selection`] reports the result.
+//!
+//! Character boxes are measured in the isolated world without touching the
+//! DOM: with `Range.getClientRects` for ordinary elements and from the
+//! computed font for `<input>` and `<textarea>`, where only explicit line
+//! breaks are followed and `text-align` is assumed to be `start`.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::selection::SelectTarget;
+//!
+//! let word = chaser.select_text_human(SelectTarget::Word {
+//!     selector: "article p".into(),
+//!     offset: 12,
+//! }).await?;
+//! chaser.click_caret_at("#email", 4).await?;
+//! chaser.type_text("x").await?;
+//! ```
+
+use crate::chaser::{ChaserPage, Point};
+use crate::error::{ChaserError, ChaserResult as Result};
+use rand::Rng;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// `{text, boxes}` for the element matching `ARGS[0]`, with the viewport box
+/// `[left, top, width, height]` of each character index in `ARGS[1]`, or
+/// `null` for characters that are out of range or not rendered.
+const GLYPHS_SCRIPT: &str = r#"(() => {
+    const [selector, offsets] = ARGS;
+    const el = document.querySelector(selector);
+    if (!el) return null;
+    const field = el instanceof HTMLInputElement || el instanceof HTMLTextAreaElement;
+    const text = field ? el.value : el.textContent;
+    let charBox;
+    if (field) {
+        const style = getComputedStyle(el);
+        const px = v => parseFloat(v) || 0;
+        const ctx = document.createElement('canvas').getContext('2d');
+        ctx.font = style.font || `${style.fontSize} ${style.fontFamily}`;
+        const rect = el.getBoundingClientRect();
+        const left = rect.left + px(style.borderLeftWidth) + px(style.paddingLeft) - el.scrollLeft;
+        const top = rect.top + px(style.borderTopWidth) + px(style.paddingTop) - el.scrollTop;
+        const inner = rect.height - px(style.borderTopWidth) - px(style.borderBottomWidth)
+            - px(style.paddingTop) - px(style.paddingBottom);
+        const lineHeight = px(style.lineHeight) || px(style.fontSize) * 1.2;
+        const single = el instanceof HTMLInputElement;
+        charBox = i => {
+            if (i < 0 || i >= text.length || text[i] === '\n') return null;
+            const before = text.slice(0, i);
+            const lineStart = single ? 0 : before.lastIndexOf('\n') + 1;
+            const line = single ? 0 : before.split('\n').length - 1;
+            const x = left + ctx.measureText(text.slice(lineStart, i)).width;
+            const y = single ? top + (inner - lineHeight) / 2 : top + line * lineHeight;
+            return [x, y, ctx.measureText(text[i]).width, lineHeight];
+        };
+    } else {
+        const walker = document.createTreeWalker(el, NodeFilter.SHOW_TEXT);
+        const nodes = [];
+        for (let node; (node = walker.nextNode()); ) nodes.push(node);
+        charBox = i => {
+            if (i < 0) return null;
+            for (const node of nodes) {
+                if (i < node.length) {
+                    const range = document.createRange();
+                    range.setStart(node, i);
+                    range.setEnd(node, i + 1);
+                    const r = range.getClientRects()[0];
+                    return r && r.width > 0 && r.height > 0 ? [r.left, r.top, r.width, r.height] : null;
+                }
+                i -= node.length;
+            }
+            return null;
+        };
+    }
+    return { text, boxes: offsets.map(charBox) };
+})()"#;
+
+/// The selection in the focused field, or the document selection.
+const SELECTION_SCRIPT: &str = r#"(() => {
+    const el = document.activeElement;
+    const field = el instanceof HTMLInputElement || el instanceof HTMLTextAreaElement;
+    if (field && el.selectionStart !== null) {
+        const { selectionStart: start, selectionEnd: end } = el;
+        return { text: el.value.slice(start, end), start, end, in_field: true };
+    }
+    const selection = window.getSelection();
+    return { text: selection ? selection.toString() : '', start: null, end: null, in_field: false };
+})()"#;
+
+/// What [`ChaserPage::select_text_human`] selects.
+///
+/// Offsets count UTF-16 code units of the element's `textContent`, or of
+/// the value of an `<input>` or `<textarea>`, like `selectionStart` does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectTarget {
+    /// All text of the first element matching the selector, without
+    /// leading and trailing whitespace. Dragged.
+    Element(String),
+    /// The characters `start..end`. Dragged.
+    Range {
+        selector: String,
+        start: usize,
+        end: usize,
+    },
+    /// The word around `offset`. Double-clicked.
+    Word { selector: String, offset: usize },
+    /// The paragraph around `offset`. Triple-clicked.
+    Paragraph { selector: String, offset: usize },
+}
+
+impl SelectTarget {
+    fn selector(&self) -> &str {
+        match self {
+            SelectTarget::Element(selector)
+            | SelectTarget::Range { selector, .. }
+            | SelectTarget::Word { selector, .. }
+            | SelectTarget::Paragraph { selector, .. } => selector,
+        }
+    }
+}
+
+impl From<&str> for SelectTarget {
+    fn from(selector: &str) -> Self {
+        SelectTarget::Element(selector.to_string())
+    }
+}
+
+impl From<String> for SelectTarget {
+    fn from(selector: String) -> Self {
+        SelectTarget::Element(selector)
+    }
+}
+
+/// The current text selection, see [`ChaserPage::get_selection`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct TextSelection {
+    /// The selected text, empty for a collapsed caret.
+    pub text: String,
+    /// Caret and selection bounds in the focused field; `None` outside
+    /// fields.
+    pub start: Option<usize>,
+    pub end: Option<usize>,
+    /// Whether the selection is inside a focused `<input>` or `<textarea>`.
+    pub in_field: bool,
+}
+
+impl TextSelection {
+    /// Whether nothing is selected (a collapsed caret counts as nothing).
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Glyphs {
+    text: String,
+    boxes: Vec<Option<[f64; 4]>>,
+}
+
+/// The UTF-16 range of `text` without leading and trailing whitespace, or
+/// `None` if it is all whitespace.
+fn trimmed_bounds(text: &str) -> Option<(usize, usize)> {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let leading = &text[..text.len() - text.trim_start().len()];
+    let start = leading.encode_utf16().count();
+    Some((start, start + trimmed.encode_utf16().count()))
+}
+
+/// A point in the left (`before`) or right part of a character box, where a
+/// click puts the caret before or after it.
+fn aim(glyph: [f64; 4], before: bool) -> Point {
+    let [left, top, width, height] = glyph;
+    let inset = width * rand::thread_rng().gen_range(0.15..0.35);
+    Point {
+        x: if before {
+            left + inset
+        } else {
+            left + width - inset
+        },
+        y: top + height / 2.0,
+    }
+}
+
+impl ChaserPage {
+    /// Select text with the mouse and return what ended up selected.
+    ///
+    /// Whole elements and ranges are dragged from the first to the last
+    /// character; words and paragraphs are double- and triple-clicked. The
+    /// start is scrolled into view first. A dragged range must then fit in
+    /// the viewport, since the cursor cannot leave it.
+    pub async fn select_text_human(&self, target: impl Into<SelectTarget>) -> Result<String> {
+        let target = target.into();
+        let selector = target.selector().to_string();
+        let (first, last, clicks) = match &target {
+            SelectTarget::Element(_) => {
+                let glyphs = self.glyphs(&selector, &[]).await?;
+                let (start, end) = trimmed_bounds(&glyphs.text).ok_or_else(|| {
+                    ChaserError::msg(format!("`{selector}` has no text to select"))
+                })?;
+                (start, end - 1, 0)
+            }
+            SelectTarget::Range { start, end, .. } if start < end => (*start, end - 1, 0),
+            SelectTarget::Range { start, end, .. } => {
+                return Err(ChaserError::msg(format!(
+                    "empty selection range {start}..{end}"
+                )))
+            }
+            SelectTarget::Word { offset, .. } => (*offset, *offset, 2),
+            SelectTarget::Paragraph { offset, .. } => (*offset, *offset, 3),
+        };
+
+        let [from, mut to] = self.glyph_boxes(&selector, [first, last]).await?;
+        let mut start = aim(from, true);
+        let visible = self.bring_into_view(start).await?;
+        if visible != start {
+            // scrolled, so the end moved as well
+            start = visible;
+            [_, to] = self.glyph_boxes(&selector, [first, last]).await?;
+        }
+        self.move_mouse_human(start.x, start.y).await?;
+        let settle = rand::thread_rng().gen_range(120..300);
+        self.pause(Duration::from_millis(settle)).await?;
+        if clicks > 0 {
+            self.multi_click(clicks).await?;
+        } else {
+            let end = aim(to, false);
+            if !self.viewport_state().await?.contains(end) {
+                return Err(ChaserError::msg(format!(
+                    "selection in `{selector}` does not fit in the viewport"
+                )));
+            }
+            self.drag_human(end.x, end.y).await?;
+        }
+        Ok(self.get_selection().await?.text)
+    }
+
+    /// The current selection: inside the focused `<input>` or `<textarea>`
+    /// if there is one, otherwise the document's.
+    pub async fn get_selection(&self) -> Result<TextSelection> {
+        let value = self
+            .evaluate_stealth(SELECTION_SCRIPT)
+            .await?
+            .unwrap_or_default();
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Click into the field matching `selector` so the caret lands before
+    /// character `offset` (`offset` equal to the length puts it at the
+    /// end), and return where it landed.
+    ///
+    /// The click aims at the boundary between the two characters, so the
+    /// caret may end up one character off in narrow glyphs; check
+    /// [`TextSelection::start`] when it matters.
+    pub async fn click_caret_at(&self, selector: &str, offset: usize) -> Result<TextSelection> {
+        let point = if offset == 0 {
+            let [glyph] = self.glyph_boxes(selector, [0]).await?;
+            aim(glyph, true)
+        } else {
+            let [left] = self.glyph_boxes(selector, [offset - 1]).await?;
+            aim(left, false)
+        };
+        let point = self.bring_into_view(point).await?;
+        self.click_human(point.x, point.y).await?;
+        self.get_selection().await
+    }
+
+    async fn glyphs(&self, selector: &str, offsets: &[usize]) -> Result<Glyphs> {
+        let args = serde_json::to_string(&(selector, offsets))?;
+        let script = GLYPHS_SCRIPT.replacen("ARGS", &args, 1);
+        match self.evaluate_stealth(&script).await? {
+            Some(value) if !value.is_null() => Ok(serde_json::from_value(value)?),
+            _ => Err(ChaserError::msg(format!("no element matches `{selector}`"))),
+        }
+    }
+
+    /// The boxes of the characters at `offsets`, all of which must be
+    /// rendered.
+    async fn glyph_boxes<const N: usize>(
+        &self,
+        selector: &str,
+        offsets: [usize; N],
+    ) -> Result<[[f64; 4]; N]> {
+        let glyphs = self.glyphs(selector, &offsets).await?;
+        let mut boxes = [[0.0; 4]; N];
+        for (i, glyph) in glyphs.boxes.into_iter().enumerate().take(N) {
+            boxes[i] = glyph.ok_or_else(|| {
+                ChaserError::msg(format!(
+                    "character {} of `{selector}` is not rendered",
+                    offsets[i]
+                ))
+            })?;
+        }
+        Ok(boxes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trims_in_utf16_units() {
+        assert_eq!(trimmed_bounds("\n  Hello  \n"), Some((3, 8)));
+        assert_eq!(trimmed_bounds(" 😀 x"), Some((1, 5)));
+        assert_eq!(trimmed_bounds(" \t\n"), None);
+        assert_eq!(
+            SelectTarget::from("h1"),
+            SelectTarget::Element("h1".to_string())
+        );
+    }
+}
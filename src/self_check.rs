@@ -0,0 +1,380 @@
+//! What a site actually sees of this page.
+//!
+//! A profile describes what a page should present; patches, overrides, the
+//! proxy and Chrome's own network stack decide what it does present. The
+//! two drift apart quietly: a proxy that fell back to a direct connection,
+//! an `Accept-Language` override that did not apply to a worker, client
+//! hints from the real platform. [`ChaserPage::self_check`] asks an echo
+//! service what arrived, from the page itself, and compares it with the
+//! applied profile and the expected exit.
+//!
+//! The default echo, `tls.peet.ws`, also reports the TLS (JA3, JA4) and
+//! HTTP/2 fingerprints of the connection; other services may report only
+//! the address and headers. With the `test-harness` feature the
+//! [`FixtureServer`](crate::test_harness::FixtureServer)'s `/echo` serves
+//! as a local echo for tests.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::self_check::SelfCheck;
+//!
+//! let report = chaser
+//!     .self_check_with(&SelfCheck::new().expect_ip("203.0.113.7".parse()?))
+//!     .await?;
+//! println!("{} via {:?}, JA4 {:?}", report.presented.user_agent, report.presented.ip, report.presented.ja4);
+//! for mismatch in &report.mismatches {
+//!     println!("{mismatch}");
+//! }
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::{ChaserError, ChaserResult as Result};
+use crate::profiles::ChaserProfile;
+use serde::Deserialize;
+use serde_json::Value;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+
+/// Reports address, headers and TLS fingerprints as JSON.
+pub const DEFAULT_ECHO_URL: &str = "https://tls.peet.ws/api/all";
+
+/// Where and against what [`ChaserPage::self_check_with`] checks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfCheck {
+    echo_url: String,
+    expected_ip: Option<IpAddr>,
+}
+
+impl Default for SelfCheck {
+    fn default() -> Self {
+        Self {
+            echo_url: DEFAULT_ECHO_URL.to_string(),
+            expected_ip: None,
+        }
+    }
+}
+
+impl SelfCheck {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The echo service to ask. It must answer with JSON and allow
+    /// cross-origin reads; the address (`ip`), headers and `ja3`/`ja4`
+    /// fields are picked up where present, at the top level or under
+    /// `tls`, `http1` and `http2` as `tls.peet.ws` nests them.
+    pub fn echo_url(mut self, url: impl Into<String>) -> Self {
+        self.echo_url = url.into();
+        self
+    }
+
+    /// The address the page should exit through, i.e. the proxy's.
+    pub fn expect_ip(mut self, ip: IpAddr) -> Self {
+        self.expected_ip = Some(ip);
+        self
+    }
+}
+
+/// The UA client hints of `navigator.userAgentData`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ClientHints {
+    pub platform: String,
+    pub platform_version: String,
+    pub architecture: String,
+    pub mobile: bool,
+    pub brands: Vec<Brand>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Brand {
+    pub brand: String,
+    pub version: String,
+}
+
+/// What the echo service and the page itself reported.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Presented {
+    pub ip: Option<IpAddr>,
+    pub ja3: Option<String>,
+    pub ja4: Option<String>,
+    /// Request headers as the echo received them, names lower-cased.
+    pub headers: Vec<(String, String)>,
+    /// `navigator.userAgent`.
+    pub user_agent: String,
+    /// `None` where the browser exposes no client hints.
+    pub client_hints: Option<ClientHints>,
+}
+
+impl Presented {
+    /// The value of header `name`, if the echo reported it.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Something presented differently than intended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// `ip`, a header name, or a `navigator` property.
+    pub what: String,
+    pub expected: String,
+    pub observed: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: expected {:?}, presented {:?}",
+            self.what, self.expected, self.observed
+        )
+    }
+}
+
+/// The result of [`ChaserPage::self_check`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelfCheckReport {
+    pub presented: Presented,
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl SelfCheckReport {
+    pub fn passed(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+fn check_script(echo_url: &str) -> String {
+    format!(
+        r#"(async () => {{
+    const echo = await fetch({}, {{ cache: "no-store" }}).then(r => r.json());
+    const data = navigator.userAgentData;
+    const hints = data
+        ? await data.getHighEntropyValues(["platformVersion", "architecture"])
+        : null;
+    return {{ echo, userAgent: navigator.userAgent, hints }};
+}})()"#,
+        serde_json::to_string(echo_url).unwrap_or_default()
+    )
+}
+
+impl ChaserPage {
+    /// Ask the default echo service what this page presents, and compare it
+    /// with the applied profile. See [`self_check_with`](Self::self_check_with).
+    pub async fn self_check(&self) -> Result<SelfCheckReport> {
+        self.self_check_with(&SelfCheck::default()).await
+    }
+
+    /// Fetch `check`'s echo URL from this page and compare the address,
+    /// headers and client hints it presents with the profile applied by
+    /// [`apply_profile`](Self::apply_profile) and the expected exit. Without
+    /// a profile only the address is compared.
+    ///
+    /// The request is a `fetch` from the current document, with its
+    /// connection, headers and cookies for the echo's origin.
+    pub async fn self_check_with(&self, check: &SelfCheck) -> Result<SelfCheckReport> {
+        let reading = self
+            .evaluate_stealth(&check_script(&check.echo_url))
+            .await?
+            .ok_or_else(|| ChaserError::msg(format!("{} returned nothing", check.echo_url)))?;
+        let mut presented = parse_echo(&reading["echo"]);
+        presented.user_agent = reading["userAgent"].as_str().unwrap_or_default().into();
+        presented.client_hints = serde_json::from_value(reading["hints"].clone()).ok();
+
+        let mismatches = compare(&presented, self.profile().as_ref(), check.expected_ip);
+        Ok(SelfCheckReport {
+            presented,
+            mismatches,
+        })
+    }
+}
+
+/// Address, fingerprints and headers wherever the echo service put them.
+fn parse_echo(echo: &Value) -> Presented {
+    let text = |paths: &[&str]| {
+        paths
+            .iter()
+            .find_map(|path| echo.pointer(path)?.as_str())
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+    let ip = text(&["/ip"]).and_then(|ip| {
+        ip.parse()
+            .ok()
+            .or_else(|| ip.parse::<SocketAddr>().ok().map(|a| a.ip()))
+    });
+
+    let mut headers = Vec::new();
+    for list in [
+        echo.get("headers"),
+        echo.pointer("/http1/headers"),
+        echo.pointer("/http2/sent_frames")
+            .and_then(|frames| frames.as_array()?.iter().find_map(|f| f.get("headers"))),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        match list {
+            Value::Object(map) => headers.extend(
+                map.iter()
+                    .filter_map(|(n, v)| Some((n.to_ascii_lowercase(), v.as_str()?.to_string()))),
+            ),
+            Value::Array(items) => headers.extend(items.iter().filter_map(header_pair)),
+            _ => {}
+        }
+        if !headers.is_empty() {
+            break;
+        }
+    }
+
+    Presented {
+        ip,
+        ja3: text(&["/tls/ja3_hash", "/ja3_hash", "/tls/ja3", "/ja3"]),
+        ja4: text(&["/tls/ja4", "/ja4"]),
+        headers,
+        ..Presented::default()
+    }
+}
+
+/// `["name", "value"]` or `"name: value"`; HTTP/2 pseudo-headers keep
+/// their leading colon.
+fn header_pair(item: &Value) -> Option<(String, String)> {
+    let (name, value) = match item {
+        Value::Array(pair) => (pair.first()?.as_str()?, pair.get(1)?.as_str()?),
+        Value::String(line) => {
+            let split = line.get(1..)?.find(": ")? + 1;
+            (&line[..split], &line[split + 2..])
+        }
+        _ => return None,
+    };
+    Some((name.to_ascii_lowercase(), value.to_string()))
+}
+
+fn compare(
+    presented: &Presented,
+    profile: Option<&ChaserProfile>,
+    expected_ip: Option<IpAddr>,
+) -> Vec<Mismatch> {
+    let mut mismatches = Vec::new();
+    let mut expect = |what: &str, expected: String, observed: Option<String>| match observed {
+        Some(observed) if observed != expected => mismatches.push(Mismatch {
+            what: what.to_string(),
+            expected,
+            observed,
+        }),
+        _ => {}
+    };
+
+    if let Some(ip) = expected_ip {
+        let observed = presented.ip.map_or_else(String::new, |ip| ip.to_string());
+        expect("ip", ip.to_string(), Some(observed));
+    }
+    let Some(profile) = profile else {
+        return mismatches;
+    };
+
+    let header = |name| presented.header(name).map(str::to_string);
+    expect(
+        "navigator.userAgent",
+        profile.user_agent(),
+        Some(presented.user_agent.clone()),
+    );
+    expect("user-agent", profile.user_agent(), header("user-agent"));
+    expect(
+        "accept-language",
+        profile.accept_language(),
+        header("accept-language"),
+    );
+
+    let Some(metadata) = profile.user_agent_metadata() else {
+        return mismatches;
+    };
+    let brands = metadata.brands.unwrap_or_default();
+    expect(
+        "sec-ch-ua",
+        brands
+            .iter()
+            .map(|b| format!("\"{}\";v=\"{}\"", b.brand, b.version))
+            .collect::<Vec<_>>()
+            .join(", "),
+        header("sec-ch-ua"),
+    );
+    expect(
+        "sec-ch-ua-mobile",
+        if metadata.mobile { "?1" } else { "?0" }.to_string(),
+        header("sec-ch-ua-mobile"),
+    );
+    expect(
+        "sec-ch-ua-platform",
+        format!("\"{}\"", metadata.platform),
+        header("sec-ch-ua-platform"),
+    );
+
+    let hints = presented.client_hints.as_ref();
+    expect(
+        "userAgentData.platform",
+        metadata.platform.clone(),
+        hints.map(|h| h.platform.clone()),
+    );
+    expect(
+        "userAgentData.platformVersion",
+        metadata.platform_version.clone(),
+        hints.map(|h| h.platform_version.clone()),
+    );
+    expect(
+        "userAgentData.architecture",
+        metadata.architecture.clone(),
+        hints.map(|h| h.architecture.clone()),
+    );
+    expect(
+        "userAgentData.mobile",
+        metadata.mobile.to_string(),
+        hints.map(|h| h.mobile.to_string()),
+    );
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reads_echoes_and_compares_with_the_profile() {
+        let profile = ChaserProfile::windows().build();
+        let peet = json!({
+            "ip": "203.0.113.7:51234",
+            "http_version": "h2",
+            "tls": { "ja3_hash": "cd08e31494f9531f560d64c695473da9", "ja4": "t13d1516h2_8daaf6152771_02713d6af862" },
+            "http2": { "sent_frames": [
+                { "frame_type": "SETTINGS" },
+                { "frame_type": "HEADERS", "headers": [
+                    ":method: GET",
+                    format!("user-agent: {}", profile.user_agent()),
+                    "accept-language: en-US,en;q=0.9",
+                    "sec-ch-ua-platform: \"Linux\"",
+                ] }
+            ] }
+        });
+        let mut presented = parse_echo(&peet);
+        assert_eq!(presented.ip, "203.0.113.7".parse().ok());
+        assert_eq!(
+            presented.ja4.as_deref(),
+            Some("t13d1516h2_8daaf6152771_02713d6af862")
+        );
+        assert_eq!(presented.header(":method"), Some("GET"));
+        presented.user_agent = profile.user_agent();
+
+        let mismatches = compare(&presented, Some(&profile), "198.51.100.1".parse().ok());
+        let what: Vec<_> = mismatches.iter().map(|m| m.what.as_str()).collect();
+        assert_eq!(what, ["ip", "sec-ch-ua-platform"]);
+
+        let fixture = json!({ "ip": "127.0.0.1", "headers": [["User-Agent", "x"]] });
+        assert_eq!(parse_echo(&fixture).header("user-agent"), Some("x"));
+    }
+}
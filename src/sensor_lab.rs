@@ -0,0 +1,293 @@
+//! Watching what anti-bot sensors report about our input.
+//!
+//! Tuning the humanization models by feel only goes so far; the sensors on
+//! real sites are the ones that judge them. A [`SensorLab`] opened with
+//! [`ChaserPage::sensor_lab`] locates the sensor scripts of the page (see
+//! [`crate::scripts`]) and watches the requests they send home: every POST
+//! or beacon to a known collector becomes a [`SensorPayload`], with the
+//! body as sent and the input this page dispatched since the previous
+//! payload. [`SensorLab::probe`] runs one action and returns the payloads
+//! it caused, so a change to mouse or typing settings can be measured in a
+//! controlled loop.
+//!
+//! The lab only observes: requests go out unchanged, through the Network
+//! domain the handler already enables. Most payloads are obfuscated; for
+//! Akamai's `sensor_data`, [`SensorPayload::akamai_sections`] splits the
+//! plain-text format into its numbered sections.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use std::time::Duration;
+//!
+//! let mut lab = chaser.sensor_lab().await?;
+//! println!("sensors: {:?}", lab.scripts().iter().flat_map(|s| &s.sensors).collect::<Vec<_>>());
+//! let (_, payloads) = lab
+//!     .probe(chaser.move_mouse_human(400.0, 300.0), Duration::from_secs(3))
+//!     .await?;
+//! for payload in payloads {
+//!     println!("{} saw {:?}: {} bytes", payload.sensor, payload.inputs, payload.body.len());
+//! }
+//! ```
+
+use crate::behavior::InputEvent;
+use crate::chaser::ChaserPage;
+use crate::error::ChaserResult as Result;
+use crate::scripts::{ScriptInfo, SensorSignature};
+use chromiumoxide_cdp::cdp::browser_protocol::network::{
+    EventRequestWillBeSent, GetRequestPostDataParams,
+};
+use futures::channel::mpsc::{unbounded, UnboundedReceiver};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// Where sensors send their data, by URL and by request body.
+pub const KNOWN_COLLECTORS: &[SensorSignature] = &[
+    SensorSignature {
+        name: "Akamai Bot Manager",
+        url_markers: &[],
+        content_markers: &["sensor_data"],
+    },
+    SensorSignature {
+        name: "PerimeterX",
+        url_markers: &["/api/v2/collector", "/xhr/api/v2/collector"],
+        content_markers: &["appId=PX"],
+    },
+    SensorSignature {
+        name: "DataDome",
+        url_markers: &["datadome.co/js"],
+        content_markers: &["jsData="],
+    },
+    SensorSignature {
+        name: "FingerprintJS",
+        url_markers: &["fpjs.io", "fpcdn.io"],
+        content_markers: &[],
+    },
+];
+
+/// How many of each input event the page dispatched in a span of time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputCounts {
+    pub moves: usize,
+    pub presses: usize,
+    pub keys: usize,
+}
+
+impl InputCounts {
+    fn of<'a>(events: impl IntoIterator<Item = &'a InputEvent>) -> Self {
+        let mut counts = Self::default();
+        for event in events {
+            match event {
+                InputEvent::Move(_) => counts.moves += 1,
+                InputEvent::Press => counts.presses += 1,
+                InputEvent::Key => counts.keys += 1,
+            }
+        }
+        counts
+    }
+}
+
+/// One report a sensor sent home.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensorPayload {
+    /// Name of the [`KNOWN_COLLECTORS`] entry it matched.
+    pub sensor: String,
+    pub url: String,
+    pub method: String,
+    pub body: String,
+    /// When it was sent, after the lab opened.
+    pub at: Duration,
+    /// The input dispatched since the previous payload.
+    pub inputs: InputCounts,
+}
+
+impl SensorPayload {
+    /// Akamai's `sensor_data`, split at its `-1,2,-94,-N,` markers into
+    /// sections keyed by `-N`. `None` for other sensors and for versions
+    /// that encrypt the whole value.
+    pub fn akamai_sections(&self) -> Option<BTreeMap<i32, String>> {
+        let data = serde_json::from_str::<serde_json::Value>(&self.body)
+            .ok()
+            .and_then(|body| body.get("sensor_data")?.as_str().map(str::to_string))
+            .unwrap_or_else(|| self.body.clone());
+        let sections: BTreeMap<i32, String> = data
+            .split("-1,2,-94,")
+            .skip(1)
+            .filter_map(|section| {
+                let (code, value) = section.split_once(',').unwrap_or((section, ""));
+                Some((code.parse().ok()?, value.to_string()))
+            })
+            .collect();
+        (!sections.is_empty()).then_some(sections)
+    }
+}
+
+/// The collector a request goes to, if any.
+fn classify(url: &str, body: &str) -> Option<&'static str> {
+    let url = url.to_ascii_lowercase();
+    KNOWN_COLLECTORS
+        .iter()
+        .find(|collector| {
+            collector
+                .url_markers
+                .iter()
+                .any(|marker| url.contains(marker))
+                || collector
+                    .content_markers
+                    .iter()
+                    .any(|marker| body.contains(marker))
+        })
+        .map(|collector| collector.name)
+}
+
+/// Observes the sensor traffic of a page; see the [module docs](self).
+#[derive(Debug)]
+pub struct SensorLab {
+    scripts: Vec<ScriptInfo>,
+    payloads: UnboundedReceiver<SensorPayload>,
+    task: JoinHandle<()>,
+}
+
+impl SensorLab {
+    /// The scripts recognised as sensors when the lab opened.
+    pub fn scripts(&self) -> &[ScriptInfo] {
+        &self.scripts
+    }
+
+    /// The next payload, waiting at most `timeout`.
+    pub async fn next_payload(&mut self, timeout: Duration) -> Option<SensorPayload> {
+        tokio::time::timeout(timeout, self.payloads.next())
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Payloads that arrived and were not read yet.
+    pub fn pending(&mut self) -> Vec<SensorPayload> {
+        std::iter::from_fn(|| self.payloads.try_recv().ok()).collect()
+    }
+
+    /// Discard pending payloads, run `action`, and collect the payloads
+    /// sent until none arrived for `settle`.
+    pub async fn probe<T>(
+        &mut self,
+        action: impl Future<Output = Result<T>>,
+        settle: Duration,
+    ) -> Result<(T, Vec<SensorPayload>)> {
+        self.pending();
+        let output = action.await?;
+        let mut payloads = Vec::new();
+        while let Some(payload) = self.next_payload(settle).await {
+            payloads.push(payload);
+        }
+        Ok((output, payloads))
+    }
+}
+
+impl Drop for SensorLab {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl ChaserPage {
+    /// Locate the sensor scripts of the current page and start watching
+    /// what they send. Starts [`record_behavior`](Self::record_behavior)
+    /// unless a recording is already running.
+    pub async fn sensor_lab(&self) -> Result<SensorLab> {
+        let scripts = self
+            .script_inventory_scanned()
+            .await?
+            .into_iter()
+            .filter(|script| !script.sensors.is_empty())
+            .collect();
+        if self.behavior_stats().is_none() {
+            self.record_behavior();
+        }
+
+        let mut requests = self
+            .raw_page()
+            .event_listener::<EventRequestWillBeSent>()
+            .await?;
+        let (sender, payloads) = unbounded();
+        let page = self.clone();
+        let task = tokio::spawn(async move {
+            let opened = Instant::now();
+            let mut seen = page.behavior_stats().map_or(0, |stats| stats.len());
+            while let Some(event) = requests.next().await {
+                let request = &event.request;
+                if request.method == "GET" || request.has_post_data != Some(true) {
+                    continue;
+                }
+                let body = match page
+                    .raw_page()
+                    .execute(GetRequestPostDataParams::new(event.request_id.clone()))
+                    .await
+                {
+                    Ok(response) => response.result.post_data,
+                    Err(_) => continue,
+                };
+                let Some(sensor) = classify(&request.url, &body) else {
+                    continue;
+                };
+                let inputs = match page.behavior_stats() {
+                    Some(stats) => {
+                        let events = stats.events().get(seen..).unwrap_or_default();
+                        seen = stats.len();
+                        InputCounts::of(events.iter().map(|(_, event)| event))
+                    }
+                    None => InputCounts::default(),
+                };
+                let payload = SensorPayload {
+                    sensor: sensor.to_string(),
+                    url: request.url.clone(),
+                    method: request.method.clone(),
+                    body,
+                    at: opened.elapsed(),
+                    inputs,
+                };
+                if sender.unbounded_send(payload).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(SensorLab {
+            scripts,
+            payloads,
+            task,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognises_and_splits_akamai_payloads() {
+        let body = r#"{"sensor_data":"2;0;abc;-1,2,-94,-100,Mozilla/5.0-1,2,-94,-110,0,1,120,33,410;1,1,180,40,412;-1,2,-94,-117,"}"#;
+        assert_eq!(
+            classify("https://shop.test/x7Kq/akam/13/2f1e", body),
+            Some("Akamai Bot Manager")
+        );
+        assert_eq!(classify("https://shop.test/api/cart", "{\"qty\":1}"), None);
+
+        let payload = SensorPayload {
+            sensor: "Akamai Bot Manager".to_string(),
+            url: String::new(),
+            method: "POST".to_string(),
+            body: body.to_string(),
+            at: Duration::ZERO,
+            inputs: InputCounts::of(&[InputEvent::Key, InputEvent::Press]),
+        };
+        let sections = payload.akamai_sections().unwrap();
+        assert_eq!(sections[&-100], "Mozilla/5.0");
+        assert_eq!(sections[&-110], "0,1,120,33,410;1,1,180,40,412;");
+        assert_eq!(sections[&-117], "");
+        assert_eq!(payload.inputs.keys, 1);
+    }
+}
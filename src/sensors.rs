@@ -0,0 +1,280 @@
+//! Motion sensor emulation for mobile profiles.
+//!
+//! A phone held in a hand never reports perfectly still sensors. For Android
+//! and iOS profiles the page's accelerometer, gyroscope, gravity and
+//! orientation sensors are overridden through `Emulation.setSensorOverride*`
+//! so `DeviceMotionEvent`, `DeviceOrientationEvent` and the generic sensor
+//! APIs (`Accelerometer`, `Gyroscope`, ...) exist and deliver trusted events,
+//! and a background task keeps feeding them subtle hand-tremor noise.
+
+use crate::chaser::ChaserPage;
+use anyhow::{anyhow, Result};
+use chromiumoxide_cdp::cdp::browser_protocol::emulation::{
+    SensorReading, SensorReadingQuaternion, SensorReadingXyz, SensorType,
+    SetSensorOverrideEnabledParams, SetSensorOverrideReadingsParams,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::Duration;
+
+const GRAVITY: f64 = 9.80665;
+
+/// Resting pose and noise levels of an emulated handheld device.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SensorEmulation {
+    /// Forward tilt in degrees (0 = flat on a table, 90 = upright).
+    pub pitch: f64,
+    /// Sideways tilt in degrees.
+    pub roll: f64,
+    /// Compass heading in degrees.
+    pub heading: f64,
+    /// Standard deviation of hand tremor acceleration in m/s².
+    pub tremor: f64,
+    /// Standard deviation of angular velocity noise in rad/s.
+    pub gyro_noise: f64,
+    /// How often new readings are pushed.
+    pub interval: Duration,
+}
+
+impl Default for SensorEmulation {
+    fn default() -> Self {
+        Self {
+            pitch: 65.0,
+            roll: 2.0,
+            heading: 140.0,
+            tremor: 0.04,
+            gyro_noise: 0.012,
+            interval: Duration::from_millis(100),
+        }
+    }
+}
+
+const SENSORS: [SensorType; 6] = [
+    SensorType::Accelerometer,
+    SensorType::LinearAcceleration,
+    SensorType::Gravity,
+    SensorType::Gyroscope,
+    SensorType::AbsoluteOrientation,
+    SensorType::RelativeOrientation,
+];
+
+/// Approximately normal noise (Irwin-Hall with 4 samples).
+fn noise(rng: &mut StdRng, sigma: f64) -> f64 {
+    let sum: f64 = (0..4).map(|_| rng.gen_range(-1.0..1.0)).sum();
+    sum * sigma * (3.0f64 / 4.0).sqrt()
+}
+
+fn xyz(x: f64, y: f64, z: f64) -> SensorReading {
+    SensorReading {
+        single: None,
+        xyz: Some(SensorReadingXyz::new(x, y, z)),
+        quaternion: None,
+    }
+}
+
+/// Quaternion of a device rotated by heading (z), pitch (x) and roll (y).
+fn orientation(heading: f64, pitch: f64, roll: f64) -> SensorReading {
+    let (h, p, r) = (
+        heading.to_radians() / 2.0,
+        pitch.to_radians() / 2.0,
+        roll.to_radians() / 2.0,
+    );
+    let (ch, sh, cp, sp, cr, sr) = (h.cos(), h.sin(), p.cos(), p.sin(), r.cos(), r.sin());
+    SensorReading {
+        single: None,
+        xyz: None,
+        quaternion: Some(SensorReadingQuaternion::new(
+            sp * cr * ch - cp * sr * sh,
+            cp * sr * ch + sp * cr * sh,
+            cp * cr * sh + sp * sr * ch,
+            cp * cr * ch - sp * sr * sh,
+        )),
+    }
+}
+
+impl SensorEmulation {
+    /// Enable the sensor overrides on `page` and spawn the task feeding them.
+    ///
+    /// The task ends by itself once the page is closed.
+    pub async fn start(self, page: &ChaserPage) -> Result<()> {
+        for sensor in SENSORS {
+            page.raw_page()
+                .execute(SetSensorOverrideEnabledParams::new(true, sensor.clone()))
+                .await
+                .map_err(|e| anyhow!("Failed to enable {:?} override: {}", sensor, e))?;
+        }
+
+        let page = page.raw_page().clone();
+        tokio::spawn(async move {
+            let mut rng = StdRng::from_entropy();
+            let (mut pitch, mut roll, mut heading) = (self.pitch, self.roll, self.heading);
+            loop {
+                // slow drift of the pose plus fast tremor on top
+                pitch = (pitch + noise(&mut rng, 0.15)).clamp(self.pitch - 8.0, self.pitch + 8.0);
+                roll = (roll + noise(&mut rng, 0.1)).clamp(self.roll - 6.0, self.roll + 6.0);
+                heading = (heading + noise(&mut rng, 0.2)).rem_euclid(360.0);
+
+                let (p, r) = (pitch.to_radians(), roll.to_radians());
+                let gravity = (
+                    GRAVITY * p.cos() * r.sin(),
+                    GRAVITY * p.sin(),
+                    GRAVITY * p.cos() * r.cos(),
+                );
+                let linear = (
+                    noise(&mut rng, self.tremor),
+                    noise(&mut rng, self.tremor),
+                    noise(&mut rng, self.tremor),
+                );
+                let readings = [
+                    (
+                        SensorType::Accelerometer,
+                        xyz(
+                            gravity.0 + linear.0,
+                            gravity.1 + linear.1,
+                            gravity.2 + linear.2,
+                        ),
+                    ),
+                    (
+                        SensorType::LinearAcceleration,
+                        xyz(linear.0, linear.1, linear.2),
+                    ),
+                    (SensorType::Gravity, xyz(gravity.0, gravity.1, gravity.2)),
+                    (
+                        SensorType::Gyroscope,
+                        xyz(
+                            noise(&mut rng, self.gyro_noise),
+                            noise(&mut rng, self.gyro_noise),
+                            noise(&mut rng, self.gyro_noise),
+                        ),
+                    ),
+                    (
+                        SensorType::AbsoluteOrientation,
+                        orientation(heading, pitch, roll),
+                    ),
+                    (
+                        SensorType::RelativeOrientation,
+                        orientation(0.0, pitch, roll),
+                    ),
+                ];
+                for (sensor, reading) in readings {
+                    if page
+                        .execute(SetSensorOverrideReadingsParams::new(sensor, reading))
+                        .await
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+                tokio::time::sleep(self.interval).await;
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::page::Page;
+    use crate::transport::MockTransport;
+    use serde_json::Value;
+
+    #[test]
+    fn noise_is_centred_and_bounded() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let samples: Vec<f64> = (0..10_000).map(|_| noise(&mut rng, 0.5)).collect();
+        let bound = 4.0 * 0.5 * (3.0f64 / 4.0).sqrt();
+        assert!(samples.iter().all(|s| s.abs() <= bound));
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let sd =
+            (samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / samples.len() as f64).sqrt();
+        assert!(mean.abs() < 0.02, "mean {mean}");
+        assert!((sd - 0.5).abs() < 0.02, "standard deviation {sd}");
+    }
+
+    fn quaternion(reading: SensorReading) -> [f64; 4] {
+        let q = reading.quaternion.unwrap();
+        [q.x, q.y, q.z, q.w]
+    }
+
+    #[test]
+    fn orientations_are_unit_quaternions() {
+        assert_eq!(quaternion(orientation(0.0, 0.0, 0.0)), [0.0, 0.0, 0.0, 1.0]);
+
+        let half = std::f64::consts::FRAC_1_SQRT_2;
+        let [x, y, z, w] = quaternion(orientation(90.0, 0.0, 0.0));
+        assert!(x.abs() < 1e-12 && y.abs() < 1e-12);
+        assert!((z - half).abs() < 1e-12 && (w - half).abs() < 1e-12);
+        let [x, _, _, w] = quaternion(orientation(0.0, 90.0, 0.0));
+        assert!((x - half).abs() < 1e-12 && (w - half).abs() < 1e-12);
+
+        let norm: f64 = quaternion(orientation(140.0, 65.0, 2.0))
+            .iter()
+            .map(|c| c * c)
+            .sum();
+        assert!((norm - 1.0).abs() < 1e-12);
+    }
+
+    fn readings(mock: &MockTransport, sensor: &str) -> Vec<Value> {
+        mock.commands_to("Emulation.setSensorOverrideReadings")
+            .into_iter()
+            .filter(|params| params["type"] == sensor)
+            .map(|params| params["reading"].clone())
+            .collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn feeds_a_phone_held_upright() {
+        let mock = MockTransport::new();
+        let chaser = ChaserPage::new(Page::with_transport(mock.clone()));
+        SensorEmulation::default().start(&chaser).await.unwrap();
+
+        let enabled = mock.commands_to("Emulation.setSensorOverrideEnabled");
+        assert_eq!(enabled.len(), SENSORS.len());
+        assert!(enabled.iter().all(|params| params["enabled"] == true));
+
+        tokio::time::sleep(Duration::from_millis(450)).await;
+        let accelerations = readings(&mock, "accelerometer");
+        assert_eq!(accelerations.len(), 5);
+        for reading in &accelerations {
+            let xyz = &reading["xyz"];
+            let (x, y, z) = (
+                xyz["x"].as_f64().unwrap(),
+                xyz["y"].as_f64().unwrap(),
+                xyz["z"].as_f64().unwrap(),
+            );
+            let magnitude = (x * x + y * y + z * z).sqrt();
+            assert!((magnitude - GRAVITY).abs() < 0.5, "|a| = {magnitude}");
+            // pitched 65° forward: most of gravity along y
+            assert!(y > z && z > x.abs());
+        }
+        assert_ne!(accelerations[0], accelerations[1], "readings keep moving");
+        assert_eq!(readings(&mock, "relative-orientation").len(), 5);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stops_when_the_page_is_gone() {
+        let mock = MockTransport::new();
+        let chaser = ChaserPage::new(Page::with_transport(mock.clone()));
+        SensorEmulation::default().start(&chaser).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(150)).await;
+
+        mock.fail("Emulation.setSensorOverrideReadings", "Target closed");
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let sent = mock
+            .commands_to("Emulation.setSensorOverrideReadings")
+            .len();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(
+            mock.commands_to("Emulation.setSensorOverrideReadings")
+                .len(),
+            sent
+        );
+
+        mock.fail("Emulation.setSensorOverrideEnabled", "Not supported");
+        let err = SensorEmulation::default().start(&chaser).await.unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Failed to enable Accelerometer override"));
+    }
+}
@@ -0,0 +1,267 @@
+//! Signing replayed API calls the way the site does.
+//!
+//! Many APIs reject a request unless it carries a fresh signature: an HMAC
+//! over the path, body and a timestamp, a one-time nonce, a token computed
+//! by the site's bundle. A captured request replayed verbatim carries a
+//! stale one. [`RequestSigner`]s registered with
+//! [`ChaserPage::add_request_signer`] run on every [`ChaserPage::replay`]
+//! right before the request is sent, so each replay is signed anew.
+//!
+//! - [`RequestSigner::new`] wraps a Rust closure, for schemes whose key and
+//!   algorithm are known.
+//! - [`RequestSigner::in_page`] calls a function of the site's own code in
+//!   the page's main world, for schemes buried in an obfuscated bundle. It
+//!   goes through the [`evaluate_main`](ChaserPage::evaluate_main) bridge,
+//!   so the bridge must be installed (profiles install it) and the function
+//!   must return synchronously.
+//!
+//! [`ChaserContext::replay_from`](crate::context::ChaserContext::replay_from)
+//! replays from a fresh page without signers; sign the request beforehand
+//! with [`ChaserPage::sign_request`] on the page holding the site's code.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::referrer::CapturedRequest;
+//! use chaser_oxide::signing::RequestSigner;
+//!
+//! // The site's bundle exposes its signer; let it sign
+//! page.add_request_signer(
+//!     RequestSigner::in_page("req => ({ headers: { 'x-sign': window.__sdk.sign(req.url, req.body) } })")
+//!         .scope("https://api.shop.example/"),
+//! );
+//!
+//! // Or compute a nonce in Rust
+//! page.add_request_signer(RequestSigner::new(|request| {
+//!     request.set_header("x-nonce", uuid::Uuid::new_v4().to_string());
+//!     Ok(())
+//! }));
+//!
+//! let response = page.replay(&CapturedRequest::new("POST", "https://api.shop.example/v1/cart")).await?;
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::{ChaserError, ChaserResult as Result};
+use crate::referrer::CapturedRequest;
+use serde_json::Value;
+use std::fmt;
+use std::sync::Arc;
+
+type SignFn = dyn Fn(&mut CapturedRequest) -> Result<()> + Send + Sync;
+
+#[derive(Clone)]
+enum Sign {
+    Native(Arc<SignFn>),
+    InPage(String),
+}
+
+/// Signs a replayed request before it is sent.
+#[derive(Clone)]
+pub struct RequestSigner {
+    sign: Sign,
+    scope: Option<String>,
+}
+
+impl RequestSigner {
+    /// Sign with `sign`, which edits the request in place. An error aborts
+    /// the replay.
+    pub fn new<F>(sign: F) -> Self
+    where
+        F: Fn(&mut CapturedRequest) -> Result<()> + Send + Sync + 'static,
+    {
+        Self {
+            sign: Sign::Native(Arc::new(sign)),
+            scope: None,
+        }
+    }
+
+    /// Sign with `function`, the source of a JavaScript function evaluated
+    /// in the page's main world. It is called with the request as
+    /// `{ method, url, headers, body }`, `headers` being `[name, value]`
+    /// pairs, and may edit it in place or return the changes: `method`,
+    /// `url` and `body` replace the request's, `headers` as pairs replace
+    /// all headers and as an object set the headers it names.
+    pub fn in_page(function: impl Into<String>) -> Self {
+        Self {
+            sign: Sign::InPage(function.into()),
+            scope: None,
+        }
+    }
+
+    /// Only sign requests whose URL starts with `prefix`.
+    pub fn scope(mut self, prefix: impl Into<String>) -> Self {
+        self.scope = Some(prefix.into());
+        self
+    }
+
+    pub fn applies_to(&self, request: &CapturedRequest) -> bool {
+        self.scope
+            .as_deref()
+            .map_or(true, |prefix| request.url.starts_with(prefix))
+    }
+
+    async fn sign(&self, page: &ChaserPage, request: &mut CapturedRequest) -> Result<()> {
+        match &self.sign {
+            Sign::Native(sign) => sign(request),
+            Sign::InPage(function) => {
+                let signed = page
+                    .evaluate_main(&in_page_script(function, request))
+                    .await?
+                    .ok_or_else(|| {
+                        ChaserError::msg(format!("signing {} returned nothing", request.url))
+                    })?;
+                merge_signed(request, &signed)
+            }
+        }
+    }
+}
+
+impl fmt::Debug for RequestSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match &self.sign {
+            Sign::Native(_) => "native",
+            Sign::InPage(_) => "in_page",
+        };
+        f.debug_struct("RequestSigner")
+            .field("kind", &kind)
+            .field("scope", &self.scope)
+            .finish()
+    }
+}
+
+fn in_page_script(function: &str, request: &CapturedRequest) -> String {
+    let request = serde_json::json!({
+        "method": request.method,
+        "url": request.url,
+        "headers": request.headers,
+        "body": request.body,
+    });
+    format!(
+        r#"(() => {{
+    const request = {request};
+    const signed = ({function})(request);
+    return signed === undefined ? request : signed;
+}})()"#
+    )
+}
+
+/// Fold what an in-page signer returned into `request`.
+fn merge_signed(request: &mut CapturedRequest, signed: &Value) -> Result<()> {
+    let unexpected = || ChaserError::msg(format!("unexpected signer result: {signed}"));
+    let signed = signed.as_object().ok_or_else(unexpected)?;
+    let text = |value: &Value| match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Null => None,
+        other => Some(other.to_string()),
+    };
+    if let Some(method) = signed.get("method").and_then(Value::as_str) {
+        request.method = method.to_ascii_uppercase();
+    }
+    if let Some(url) = signed.get("url").and_then(Value::as_str) {
+        request.url = url.to_string();
+    }
+    if let Some(body) = signed.get("body") {
+        request.body = text(body);
+    }
+    match signed.get("headers") {
+        None | Some(Value::Null) => {}
+        Some(Value::Array(pairs)) => {
+            request.headers = pairs
+                .iter()
+                .map(|pair| match pair.as_array().map(Vec::as_slice) {
+                    Some([Value::String(name), value]) => {
+                        Ok((name.clone(), text(value).unwrap_or_default()))
+                    }
+                    _ => Err(unexpected()),
+                })
+                .collect::<Result<_>>()?;
+        }
+        Some(Value::Object(headers)) => {
+            for (name, value) in headers {
+                match text(value) {
+                    Some(value) => request.set_header(name, value),
+                    None => request.remove_header(name),
+                }
+            }
+        }
+        Some(_) => return Err(unexpected()),
+    }
+    Ok(())
+}
+
+impl ChaserPage {
+    /// Run `signer` on every later [`replay`](Self::replay) it applies to,
+    /// after the signers added before it.
+    pub fn add_request_signer(&self, signer: RequestSigner) {
+        self.request_signers().lock().unwrap().push(signer);
+    }
+
+    pub fn clear_request_signers(&self) {
+        self.request_signers().lock().unwrap().clear();
+    }
+
+    /// `request` as the registered signers make it, in the order they were
+    /// added.
+    pub async fn sign_request(&self, request: &CapturedRequest) -> Result<CapturedRequest> {
+        let signers = self.request_signers().lock().unwrap().clone();
+        let mut request = request.clone();
+        for signer in &signers {
+            if signer.applies_to(&request) {
+                signer.sign(self, &mut request).await?;
+            }
+        }
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn signer_results_are_merged() {
+        let captured = CapturedRequest::new("POST", "https://api.shop.example/v1/cart")
+            .header("X-Sign", "stale")
+            .header("Accept", "application/json")
+            .body("{}");
+
+        let nonce = RequestSigner::new(|request| {
+            request.set_header("x-sign", "fresh");
+            Ok(())
+        })
+        .scope("https://api.shop.example/");
+        assert!(nonce.applies_to(&captured));
+        assert!(!nonce.applies_to(&CapturedRequest::new("GET", "https://cdn.example/")));
+        let Sign::Native(sign) = &nonce.sign else {
+            unreachable!()
+        };
+        let mut request = captured.clone();
+        sign(&mut request).unwrap();
+        assert_eq!(request.header_value("X-Sign"), Some("fresh"));
+        assert_eq!(request.headers.len(), 2);
+
+        let mut request = captured.clone();
+        merge_signed(
+            &mut request,
+            &json!({ "headers": { "x-sign": "abc", "x-ts": 17, "accept": null }, "body": "{\"n\":1}" }),
+        )
+        .unwrap();
+        assert_eq!(request.header_value("x-sign"), Some("abc"));
+        assert_eq!(request.header_value("x-ts"), Some("17"));
+        assert_eq!(request.header_value("accept"), None);
+        assert_eq!(request.body.as_deref(), Some("{\"n\":1}"));
+
+        let mut request = captured.clone();
+        merge_signed(
+            &mut request,
+            &json!({ "url": "https://api.shop.example/v1/cart?sig=1", "headers": [["x-sign", "def"]] }),
+        )
+        .unwrap();
+        assert_eq!(request.url, "https://api.shop.example/v1/cart?sig=1");
+        assert_eq!(request.headers, vec![("x-sign".into(), "def".into())]);
+
+        assert!(merge_signed(&mut request, &json!("signature")).is_err());
+        assert!(in_page_script("r => r", &captured).contains(r#""X-Sign","stale""#));
+    }
+}
@@ -0,0 +1,627 @@
+//! Streaming result sinks for extracted records.
+//!
+//! A [`Sink`] serializes records to JSONL, CSV or (with the `parquet` feature)
+//! Parquet files, optionally rotating to a new file after a number of records
+//! or bytes and dropping records whose key was already written.
+//!
+//! For concurrent producers, [`Sink::spawn`] moves the sink onto a blocking
+//! writer thread and returns a cloneable [`SinkSender`] backed by a bounded
+//! channel, so fast scrapers wait for the disk instead of buffering without
+//! limit.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use chaser_oxide::sinks::{Format, Rotation, Sink, SinkConfig};
+//! # async fn run() -> std::io::Result<()> {
+//! let sink = Sink::open(
+//!     SinkConfig::new("out/products.jsonl", Format::Jsonl)
+//!         .rotation(Rotation::Records(10_000))
+//!         .dedup_by("url"),
+//! )?;
+//! let mut tx = sink.spawn(256);
+//! tx.send(&serde_json::json!({ "url": "https://example.com/a", "price": 10 })).await?;
+//! tx.close().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use futures::channel::{mpsc, oneshot};
+use futures::{SinkExt, StreamExt};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Output file format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One JSON document per line.
+    Jsonl,
+    /// Comma separated values; the header is taken from the first record.
+    Csv,
+    /// Apache Parquet with one optional UTF-8 column per field of the first
+    /// record.
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+/// When to start a new output file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+    /// Write everything into a single file.
+    #[default]
+    Never,
+    /// Rotate after this many records.
+    Records(u64),
+    /// Rotate once a file grew beyond this many bytes.
+    Bytes(u64),
+}
+
+/// Configuration of a [`Sink`].
+#[derive(Debug, Clone)]
+pub struct SinkConfig {
+    path: PathBuf,
+    format: Format,
+    rotation: Rotation,
+    dedup_key: Option<String>,
+}
+
+impl SinkConfig {
+    pub fn new(path: impl Into<PathBuf>, format: Format) -> Self {
+        Self {
+            path: path.into(),
+            format,
+            rotation: Rotation::Never,
+            dedup_key: None,
+        }
+    }
+
+    /// Rotate output files. Rotated files get a `-00000` style suffix before
+    /// the extension.
+    pub fn rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Drop records whose top level `key` field was already written.
+    pub fn dedup_by(mut self, key: impl Into<String>) -> Self {
+        self.dedup_key = Some(key.into());
+        self
+    }
+}
+
+/// Writes records of a single format to disk.
+trait RecordWriter: Send {
+    fn write_record(&mut self, record: &Value) -> io::Result<()>;
+
+    /// Bytes written so far (approximate for buffered formats).
+    fn bytes_written(&self) -> u64;
+
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+struct CountingWriter {
+    inner: BufWriter<File>,
+    written: u64,
+}
+
+impl CountingWriter {
+    fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            inner: BufWriter::new(File::create(path)?),
+            written: 0,
+        })
+    }
+}
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+struct JsonlWriter {
+    out: CountingWriter,
+}
+
+impl RecordWriter for JsonlWriter {
+    fn write_record(&mut self, record: &Value) -> io::Result<()> {
+        serde_json::to_writer(&mut self.out, record)?;
+        self.out.write_all(b"\n")
+    }
+
+    fn bytes_written(&self) -> u64 {
+        self.out.written
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+struct CsvWriter {
+    out: CountingWriter,
+    columns: Option<Vec<String>>,
+}
+
+impl CsvWriter {
+    fn write_row<'a>(&mut self, cells: impl Iterator<Item = &'a str>) -> io::Result<()> {
+        let row = cells.map(csv_escape).collect::<Vec<_>>().join(",");
+        self.out.write_all(row.as_bytes())?;
+        self.out.write_all(b"\r\n")
+    }
+}
+
+impl RecordWriter for CsvWriter {
+    fn write_record(&mut self, record: &Value) -> io::Result<()> {
+        if self.columns.is_none() {
+            let columns = record_columns(record);
+            self.write_row(columns.iter().map(String::as_str))?;
+            self.columns = Some(columns);
+        }
+        let cells = record_cells(record, self.columns.as_deref().unwrap_or_default());
+        self.write_row(cells.iter().map(|c| c.as_deref().unwrap_or("")))
+    }
+
+    fn bytes_written(&self) -> u64 {
+        self.out.written
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// Column names of a record: the keys of an object, `value` otherwise.
+fn record_columns(record: &Value) -> Vec<String> {
+    match record {
+        Value::Object(map) => map.keys().cloned().collect(),
+        _ => vec!["value".to_string()],
+    }
+}
+
+/// Flatten a record into string cells for the given columns. Nested values
+/// are encoded as JSON, missing fields and `null` become `None`.
+fn record_cells(record: &Value, columns: &[String]) -> Vec<Option<String>> {
+    let cell = |value: Option<&Value>| match value {
+        None | Some(Value::Null) => None,
+        Some(Value::String(s)) => Some(s.clone()),
+        Some(other) => Some(other.to_string()),
+    };
+    match record {
+        Value::Object(map) => columns.iter().map(|c| cell(map.get(c))).collect(),
+        other => vec![cell(Some(other))],
+    }
+}
+
+fn csv_escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+#[cfg(feature = "parquet")]
+mod parquet_writer {
+    use super::{record_cells, record_columns, RecordWriter};
+    use parquet::basic::{ConvertedType, Repetition, Type as PhysicalType};
+    use parquet::data_type::{ByteArray, ByteArrayType};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::types::Type;
+    use serde_json::Value;
+    use std::fs::File;
+    use std::io;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    /// Rows are buffered and written as one row group per `ROW_GROUP_SIZE`.
+    const ROW_GROUP_SIZE: usize = 4096;
+
+    pub(super) struct ParquetWriter {
+        file: Option<File>,
+        writer: Option<SerializedFileWriter<File>>,
+        columns: Vec<String>,
+        rows: Vec<Vec<Option<String>>>,
+        written: u64,
+    }
+
+    fn to_io(err: parquet::errors::ParquetError) -> io::Error {
+        io::Error::other(err)
+    }
+
+    impl ParquetWriter {
+        pub(super) fn create(path: &Path) -> io::Result<Self> {
+            Ok(Self {
+                file: Some(File::create(path)?),
+                writer: None,
+                columns: Vec::new(),
+                rows: Vec::new(),
+                written: 0,
+            })
+        }
+
+        fn open_writer(&mut self) -> io::Result<()> {
+            let fields = self
+                .columns
+                .iter()
+                .map(|name| {
+                    Type::primitive_type_builder(name, PhysicalType::BYTE_ARRAY)
+                        .with_repetition(Repetition::OPTIONAL)
+                        .with_converted_type(ConvertedType::UTF8)
+                        .build()
+                        .map(Arc::new)
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(to_io)?;
+            let schema = Type::group_type_builder("record")
+                .with_fields(fields)
+                .build()
+                .map_err(to_io)?;
+            let file = self.file.take().expect("parquet writer opened twice");
+            self.writer = Some(
+                SerializedFileWriter::new(
+                    file,
+                    Arc::new(schema),
+                    Arc::new(WriterProperties::builder().build()),
+                )
+                .map_err(to_io)?,
+            );
+            Ok(())
+        }
+
+        fn flush_rows(&mut self) -> io::Result<()> {
+            if self.rows.is_empty() {
+                return Ok(());
+            }
+            let Some(writer) = self.writer.as_mut() else {
+                return Ok(());
+            };
+            let mut group = writer.next_row_group().map_err(to_io)?;
+            let mut idx = 0;
+            while let Some(mut column) = group.next_column().map_err(to_io)? {
+                let values: Vec<ByteArray> = self
+                    .rows
+                    .iter()
+                    .filter_map(|row| row[idx].as_deref())
+                    .map(ByteArray::from)
+                    .collect();
+                let levels: Vec<i16> = self
+                    .rows
+                    .iter()
+                    .map(|row| row[idx].is_some() as i16)
+                    .collect();
+                column
+                    .typed::<ByteArrayType>()
+                    .write_batch(&values, Some(&levels), None)
+                    .map_err(to_io)?;
+                column.close().map_err(to_io)?;
+                idx += 1;
+            }
+            let metadata = group.close().map_err(to_io)?;
+            self.written += metadata.compressed_size() as u64;
+            self.rows.clear();
+            Ok(())
+        }
+    }
+
+    impl RecordWriter for ParquetWriter {
+        fn write_record(&mut self, record: &Value) -> io::Result<()> {
+            if self.writer.is_none() {
+                self.columns = record_columns(record);
+                self.open_writer()?;
+            }
+            self.rows.push(record_cells(record, &self.columns));
+            if self.rows.len() >= ROW_GROUP_SIZE {
+                self.flush_rows()?;
+            }
+            Ok(())
+        }
+
+        fn bytes_written(&self) -> u64 {
+            self.written
+        }
+
+        fn finish(&mut self) -> io::Result<()> {
+            self.flush_rows()?;
+            if let Some(writer) = self.writer.take() {
+                writer.close().map_err(to_io)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// A synchronous, rotating, deduplicating record writer.
+pub struct Sink {
+    config: SinkConfig,
+    writer: Option<Box<dyn RecordWriter>>,
+    file_index: u32,
+    records_in_file: u64,
+    records_total: u64,
+    seen: HashSet<String>,
+    files: Vec<PathBuf>,
+}
+
+impl std::fmt::Debug for Sink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sink")
+            .field("config", &self.config)
+            .field("records_total", &self.records_total)
+            .field("files", &self.files)
+            .finish()
+    }
+}
+
+impl Sink {
+    /// Create the sink. Parent directories are created if missing; the first
+    /// file is created lazily on the first record.
+    pub fn open(config: SinkConfig) -> io::Result<Self> {
+        if let Some(parent) = config.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+        Ok(Self {
+            config,
+            writer: None,
+            file_index: 0,
+            records_in_file: 0,
+            records_total: 0,
+            seen: HashSet::new(),
+            files: Vec::new(),
+        })
+    }
+
+    /// Serialize and write a record.
+    ///
+    /// Returns `false` if the record was dropped as a duplicate.
+    pub fn write<T: Serialize + ?Sized>(&mut self, record: &T) -> io::Result<bool> {
+        let value = serde_json::to_value(record)?;
+        self.write_value(value)
+    }
+
+    fn write_value(&mut self, value: Value) -> io::Result<bool> {
+        let id = self.config.dedup_key.as_ref().and_then(|key| {
+            let id = match value.get(key) {
+                Some(Value::String(s)) => s.clone(),
+                Some(other) => other.to_string(),
+                None => String::new(),
+            };
+            (!id.is_empty()).then_some(id)
+        });
+        if id.as_ref().is_some_and(|id| self.seen.contains(id)) {
+            return Ok(false);
+        }
+
+        if self.should_rotate() {
+            self.finish_file()?;
+        }
+        if self.writer.is_none() {
+            self.start_file()?;
+        }
+        if let Some(writer) = self.writer.as_mut() {
+            writer.write_record(&value)?;
+        }
+        // only now, so a record that failed to write can be retried
+        if let Some(id) = id {
+            self.seen.insert(id);
+        }
+        self.records_in_file += 1;
+        self.records_total += 1;
+        Ok(true)
+    }
+
+    /// Number of records written (duplicates excluded).
+    pub fn records_written(&self) -> u64 {
+        self.records_total
+    }
+
+    /// Every file created so far, in order.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Flush and finalize the current file.
+    pub fn close(mut self) -> io::Result<Vec<PathBuf>> {
+        self.finish_file()?;
+        Ok(std::mem::take(&mut self.files))
+    }
+
+    /// Move the sink onto a dedicated writer thread.
+    ///
+    /// At most `capacity` records are queued; senders wait when the queue is
+    /// full.
+    pub fn spawn(self, capacity: usize) -> SinkSender {
+        let (tx, mut rx) = mpsc::channel::<SinkMessage>(capacity);
+        std::thread::spawn(move || {
+            let mut sink = self;
+            futures::executor::block_on(async {
+                while let Some(msg) = rx.next().await {
+                    match msg {
+                        SinkMessage::Record(value, ack) => {
+                            let _ = ack.send(sink.write_value(value));
+                        }
+                        SinkMessage::Close(ack) => {
+                            let _ = ack.send(sink.finish_file().map(|_| sink.files.clone()));
+                            return;
+                        }
+                    }
+                }
+                let _ = sink.finish_file();
+            });
+        });
+        SinkSender { tx }
+    }
+
+    fn should_rotate(&self) -> bool {
+        let Some(writer) = self.writer.as_ref() else {
+            return false;
+        };
+        match self.config.rotation {
+            Rotation::Never => false,
+            Rotation::Records(max) => self.records_in_file >= max,
+            Rotation::Bytes(max) => writer.bytes_written() >= max,
+        }
+    }
+
+    fn current_path(&self) -> PathBuf {
+        if self.config.rotation == Rotation::Never {
+            return self.config.path.clone();
+        }
+        let path = &self.config.path;
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("out");
+        let name = match path.extension().and_then(|s| s.to_str()) {
+            Some(ext) => format!("{}-{:05}.{}", stem, self.file_index, ext),
+            None => format!("{}-{:05}", stem, self.file_index),
+        };
+        path.with_file_name(name)
+    }
+
+    fn start_file(&mut self) -> io::Result<()> {
+        let path = self.current_path();
+        let writer: Box<dyn RecordWriter> = match self.config.format {
+            Format::Jsonl => Box::new(JsonlWriter {
+                out: CountingWriter::create(&path)?,
+            }),
+            Format::Csv => Box::new(CsvWriter {
+                out: CountingWriter::create(&path)?,
+                columns: None,
+            }),
+            #[cfg(feature = "parquet")]
+            Format::Parquet => Box::new(parquet_writer::ParquetWriter::create(&path)?),
+        };
+        self.writer = Some(writer);
+        self.records_in_file = 0;
+        self.files.push(path);
+        Ok(())
+    }
+
+    fn finish_file(&mut self) -> io::Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.finish()?;
+            self.file_index += 1;
+        }
+        Ok(())
+    }
+}
+
+enum SinkMessage {
+    Record(Value, oneshot::Sender<io::Result<bool>>),
+    Close(oneshot::Sender<io::Result<Vec<PathBuf>>>),
+}
+
+/// Async handle to a [`Sink`] running on its own writer thread.
+#[derive(Debug, Clone)]
+pub struct SinkSender {
+    tx: mpsc::Sender<SinkMessage>,
+}
+
+impl std::fmt::Debug for SinkMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SinkMessage::Record(value, _) => f.debug_tuple("Record").field(value).finish(),
+            SinkMessage::Close(_) => f.write_str("Close"),
+        }
+    }
+}
+
+fn writer_gone() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "sink writer thread stopped")
+}
+
+impl SinkSender {
+    /// Queue a record, waiting while the queue is full.
+    ///
+    /// Resolves once the record was written; returns `false` for duplicates.
+    pub async fn send<T: Serialize + ?Sized>(&mut self, record: &T) -> io::Result<bool> {
+        let value = serde_json::to_value(record)?;
+        let (ack, done) = oneshot::channel();
+        self.tx
+            .send(SinkMessage::Record(value, ack))
+            .await
+            .map_err(|_| writer_gone())?;
+        done.await.map_err(|_| writer_gone())?
+    }
+
+    /// Finalize the sink and return the written files.
+    pub async fn close(mut self) -> io::Result<Vec<PathBuf>> {
+        let (ack, done) = oneshot::channel();
+        self.tx
+            .send(SinkMessage::Close(ack))
+            .await
+            .map_err(|_| writer_gone())?;
+        done.await.map_err(|_| writer_gone())?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("chaser-sinks-{}", uuid::Uuid::new_v4()))
+            .join(name)
+    }
+
+    #[test]
+    fn csv_escapes_and_fills_missing_columns() {
+        let path = temp_path("out.csv");
+        let mut sink = Sink::open(SinkConfig::new(&path, Format::Csv)).unwrap();
+        sink.write(&json!({"name": "a,b", "price": 1})).unwrap();
+        sink.write(&json!({"name": "say \"hi\""})).unwrap();
+        sink.close().unwrap();
+
+        let written = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            written,
+            "name,price\r\n\"a,b\",1\r\n\"say \"\"hi\"\"\",\r\n"
+        );
+    }
+
+    #[test]
+    fn rotates_and_dedups() {
+        let path = temp_path("out.jsonl");
+        let mut sink = Sink::open(
+            SinkConfig::new(&path, Format::Jsonl)
+                .rotation(Rotation::Records(2))
+                .dedup_by("id"),
+        )
+        .unwrap();
+        for id in [1, 2, 2, 3] {
+            sink.write(&json!({ "id": id })).unwrap();
+        }
+        assert_eq!(sink.records_written(), 3);
+        let files = sink.close().unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files[0].ends_with("out-00000.jsonl"));
+        assert_eq!(fs::read_to_string(&files[1]).unwrap(), "{\"id\":3}\n");
+    }
+
+    #[test]
+    fn a_failed_write_does_not_mark_the_key_as_seen() {
+        let path = temp_path("out.jsonl");
+        let mut sink = Sink::open(SinkConfig::new(&path, Format::Jsonl).dedup_by("id")).unwrap();
+        // the file is created on the first record, into a dir that is gone
+        fs::remove_dir(path.parent().unwrap()).unwrap();
+        assert!(sink.write(&json!({ "id": 1 })).is_err());
+
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        assert!(sink.write(&json!({ "id": 1 })).unwrap());
+        assert!(!sink.write(&json!({ "id": 1 })).unwrap());
+        sink.close().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"id\":1}\n");
+    }
+}
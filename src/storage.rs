@@ -0,0 +1,389 @@
+//! Reading and writing a page's IndexedDB and CacheStorage.
+//!
+//! Many single-page apps keep their state client-side: the user's cart,
+//! feature flags and API responses in IndexedDB, pre-fetched assets and
+//! JSON in CacheStorage. [`ChaserPage::storage`] reads that data for
+//! extraction and writes it to seed an identity with the state a
+//! returning user would have.
+//!
+//! Listing databases, object stores, caches and cache entries, fetching
+//! cached bodies, clearing stores and deleting entries use the
+//! `IndexedDB` and `CacheStorage` domains, which page scripts cannot
+//! observe. Reading and writing records runs in the isolated world,
+//! because the protocol has no command to write and only returns remote
+//! objects when reading. Everything applies to the origin of the page's
+//! current document.
+//!
+//! Records travel as JSON: `Date`s turn into strings and `Map`s, `Blob`s
+//! and typed arrays into empty objects. Writes only go to databases and
+//! object stores the site created; the schema is the site's to upgrade.
+//!
+//! # Example
+//!
+//! ```ignore
+//! #[derive(serde::Deserialize)]
+//! struct CartItem { sku: String, quantity: u32 }
+//!
+//! let storage = page.storage();
+//! let items = storage.read::<CartItem>("shop", "cart").await?;
+//! storage.put("shop", "flags", Some(json!("onboarded")), &true).await?;
+//! let body = storage.cached_body("api-v2", "https://shop.example/api/me").await?;
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::{ChaserError, ChaserResult as Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chromiumoxide_cdp::cdp::browser_protocol::cache_storage::{
+    CacheId, DeleteEntryParams, RequestCacheNamesParams, RequestCachedResponseParams,
+    RequestEntriesParams,
+};
+use chromiumoxide_cdp::cdp::browser_protocol::indexed_db::{
+    ClearObjectStoreParams, DeleteDatabaseParams, KeyPathType, RequestDatabaseNamesParams,
+    RequestDatabaseParams,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use url::Url;
+
+/// An IndexedDB database and its object stores.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Database {
+    pub name: String,
+    pub version: f64,
+    pub stores: Vec<ObjectStore>,
+}
+
+/// An object store of a [`Database`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectStore {
+    pub name: String,
+    /// The in-line key path, e.g. `id` or `user.id`; `None` for stores
+    /// whose keys are given with each record.
+    pub key_path: Option<String>,
+    pub auto_increment: bool,
+}
+
+/// One record of an object store.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Record<T> {
+    pub key: Value,
+    pub value: T,
+}
+
+/// A request stored in a CacheStorage cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheEntry {
+    pub url: String,
+    pub method: String,
+    pub status: i64,
+    pub headers: Vec<(String, String)>,
+}
+
+/// IndexedDB and CacheStorage of a page, see [`ChaserPage::storage`].
+#[derive(Debug, Clone, Copy)]
+pub struct PageStorage<'a> {
+    page: &'a ChaserPage,
+}
+
+impl ChaserPage {
+    /// The IndexedDB databases and CacheStorage caches of the current
+    /// document's origin.
+    pub fn storage(&self) -> PageStorage<'_> {
+        PageStorage { page: self }
+    }
+}
+
+/// Wraps `body` in code that opens the database `db` as `db`, without
+/// creating it if it does not exist, and closes it afterwards.
+fn with_database(db: &str, body: &str) -> String {
+    format!(
+        r#"(async () => {{
+    const done = (r) => new Promise((ok, fail) => {{
+        r.onsuccess = () => ok(r.result);
+        r.onerror = () => fail(r.error);
+    }});
+    const db = await new Promise((ok, fail) => {{
+        const open = indexedDB.open({db});
+        open.onupgradeneeded = () => open.transaction.abort();
+        open.onsuccess = () => ok(open.result);
+        open.onerror = () => fail(open.error || new Error("no such database"));
+    }});
+    try {{
+        {body}
+    }} finally {{
+        db.close();
+    }}
+}})()"#,
+        db = json(db),
+    )
+}
+
+fn json(value: impl Serialize) -> String {
+    serde_json::to_string(&value).unwrap_or_else(|_| "null".to_string())
+}
+
+impl PageStorage<'_> {
+    /// The origin of the current document; opaque origins have no storage.
+    async fn origin(&self) -> Result<String> {
+        let url = self.page.url().await?.unwrap_or_default();
+        match Url::parse(&url).map(|url| url.origin()) {
+            Ok(origin) if origin.is_tuple() => Ok(origin.ascii_serialization()),
+            _ => Err(ChaserError::msg(format!("{url:?} has no storage origin"))),
+        }
+    }
+
+    /// Names of the IndexedDB databases.
+    pub async fn databases(&self) -> Result<Vec<String>> {
+        let params = RequestDatabaseNamesParams::builder()
+            .security_origin(self.origin().await?)
+            .build();
+        Ok(self
+            .page
+            .raw_page()
+            .execute(params)
+            .await?
+            .result
+            .database_names)
+    }
+
+    /// The object stores of database `name`.
+    pub async fn database(&self, name: &str) -> Result<Database> {
+        let params = RequestDatabaseParams::builder()
+            .security_origin(self.origin().await?)
+            .database_name(name)
+            .build()
+            .map_err(ChaserError::msg)?;
+        let db = self
+            .page
+            .raw_page()
+            .execute(params)
+            .await?
+            .result
+            .database_with_object_stores;
+        let stores = db
+            .object_stores
+            .into_iter()
+            .map(|store| ObjectStore {
+                key_path: match store.key_path.r#type {
+                    KeyPathType::Null => None,
+                    KeyPathType::String => store.key_path.string,
+                    KeyPathType::Array => store.key_path.array.map(|path| path.join(",")),
+                },
+                name: store.name,
+                auto_increment: store.auto_increment,
+            })
+            .collect();
+        Ok(Database {
+            name: db.name,
+            version: db.version,
+            stores,
+        })
+    }
+
+    /// Every record of `store` in database `db`, in key order.
+    pub async fn read<T: DeserializeOwned>(&self, db: &str, store: &str) -> Result<Vec<Record<T>>> {
+        let script = with_database(
+            db,
+            &format!(
+                r#"const store = db.transaction({store}, "readonly").objectStore({store});
+        const [keys, values] = await Promise.all([done(store.getAllKeys()), done(store.getAll())]);
+        return keys.map((key, i) => ({{ key, value: values[i] }}));"#,
+                store = json(store)
+            ),
+        );
+        let records = self.page.evaluate_stealth(&script).await?;
+        serde_json::from_value(records.unwrap_or(Value::Array(Vec::new())))
+            .map_err(|e| ChaserError::msg(format!("unexpected records in {db}/{store}: {e}")))
+    }
+
+    /// Add or replace a record of `store` in database `db`. `key` is for
+    /// stores without a key path; leave it `None` when the key is part of
+    /// `value` or generated.
+    pub async fn put<T: Serialize>(
+        &self,
+        db: &str,
+        store: &str,
+        key: Option<Value>,
+        value: &T,
+    ) -> Result<()> {
+        let value = serde_json::to_value(value).map_err(|e| ChaserError::msg(e.to_string()))?;
+        let put = match key {
+            Some(key) => format!("store.put({}, {})", json(value), json(key)),
+            None => format!("store.put({})", json(value)),
+        };
+        let script = with_database(
+            db,
+            &format!(
+                r#"const tx = db.transaction({store}, "readwrite");
+        const store = tx.objectStore({store});
+        {put};
+        await new Promise((ok, fail) => {{
+            tx.oncomplete = ok;
+            tx.onerror = tx.onabort = () => fail(tx.error);
+        }});"#,
+                store = json(store)
+            ),
+        );
+        self.page.evaluate_stealth(&script).await?;
+        Ok(())
+    }
+
+    /// Remove every record of `store` in database `db`.
+    pub async fn clear_store(&self, db: &str, store: &str) -> Result<()> {
+        let params = ClearObjectStoreParams::builder()
+            .security_origin(self.origin().await?)
+            .database_name(db)
+            .object_store_name(store)
+            .build()
+            .map_err(ChaserError::msg)?;
+        self.page.raw_page().execute(params).await?;
+        Ok(())
+    }
+
+    /// Delete database `name` with all of its stores.
+    pub async fn delete_database(&self, name: &str) -> Result<()> {
+        let params = DeleteDatabaseParams::builder()
+            .security_origin(self.origin().await?)
+            .database_name(name)
+            .build()
+            .map_err(ChaserError::msg)?;
+        self.page.raw_page().execute(params).await?;
+        Ok(())
+    }
+
+    /// Names of the CacheStorage caches.
+    pub async fn caches(&self) -> Result<Vec<String>> {
+        Ok(self
+            .cache_ids()
+            .await?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect())
+    }
+
+    async fn cache_ids(&self) -> Result<Vec<(String, CacheId)>> {
+        let params = RequestCacheNamesParams::builder()
+            .security_origin(self.origin().await?)
+            .build();
+        Ok(self
+            .page
+            .raw_page()
+            .execute(params)
+            .await?
+            .result
+            .caches
+            .into_iter()
+            .map(|cache| (cache.cache_name, cache.cache_id))
+            .collect())
+    }
+
+    async fn cache_id(&self, cache: &str) -> Result<CacheId> {
+        self.cache_ids()
+            .await?
+            .into_iter()
+            .find(|(name, _)| name == cache)
+            .map(|(_, id)| id)
+            .ok_or_else(|| ChaserError::msg(format!("no cache named {cache:?}")))
+    }
+
+    /// The requests stored in `cache`.
+    pub async fn cache_entries(&self, cache: &str) -> Result<Vec<CacheEntry>> {
+        let params = RequestEntriesParams::new(self.cache_id(cache).await?);
+        let entries = self
+            .page
+            .raw_page()
+            .execute(params)
+            .await?
+            .result
+            .cache_data_entries;
+        Ok(entries
+            .into_iter()
+            .map(|entry| CacheEntry {
+                url: entry.request_url,
+                method: entry.request_method,
+                status: entry.response_status,
+                headers: entry
+                    .response_headers
+                    .into_iter()
+                    .map(|h| (h.name, h.value))
+                    .collect(),
+            })
+            .collect())
+    }
+
+    /// The response body `cache` holds for `url`.
+    pub async fn cached_body(&self, cache: &str, url: &str) -> Result<Vec<u8>> {
+        let params = RequestCachedResponseParams::new(self.cache_id(cache).await?, url, Vec::new());
+        let response = self.page.raw_page().execute(params).await?.result.response;
+        STANDARD
+            .decode(AsRef::<str>::as_ref(&response.body))
+            .map_err(|e| ChaserError::msg(format!("undecodable cached body: {e}")))
+    }
+
+    /// Store `body` as the `200` response for `url` in `cache`, creating
+    /// the cache if needed.
+    pub async fn cache_put(
+        &self,
+        cache: &str,
+        url: &str,
+        body: &[u8],
+        content_type: &str,
+    ) -> Result<()> {
+        let script = format!(
+            r#"(async () => {{
+    const body = Uint8Array.from(atob({body}), c => c.charCodeAt(0));
+    const cache = await caches.open({cache});
+    await cache.put({url}, new Response(body, {{ headers: {{ "Content-Type": {content_type} }} }}));
+}})()"#,
+            body = json(STANDARD.encode(body)),
+            cache = json(cache),
+            url = json(url),
+            content_type = json(content_type),
+        );
+        self.page.evaluate_stealth(&script).await?;
+        Ok(())
+    }
+
+    /// Remove the entry for `url` from `cache`.
+    pub async fn delete_cache_entry(&self, cache: &str, url: &str) -> Result<()> {
+        let params = DeleteEntryParams::new(self.cache_id(cache).await?, url);
+        self.page.raw_page().execute(params).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MockTransport;
+    use crate::Page;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn records_are_read_in_the_isolated_world() {
+        let mock = MockTransport::new();
+        mock.respond(
+            "Runtime.evaluate",
+            json!({ "result": { "type": "object", "value": [
+                { "key": 1, "value": { "sku": "A-1" } },
+                { "key": 2, "value": { "sku": "B-2" } }
+            ] } }),
+        );
+        let page = ChaserPage::new(Page::with_transport(mock.clone()));
+
+        let records = page.storage().read::<Value>("shop", "cart").await.unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].key, json!(2));
+        assert_eq!(records[1].value["sku"], "B-2");
+
+        let script = mock.commands_to("Runtime.evaluate")[0]["expression"].clone();
+        let script = script.as_str().unwrap();
+        assert!(script.contains(r#"indexedDB.open("shop")"#));
+        assert!(script.contains(r#"db.transaction("cart", "readonly")"#));
+        assert!(script.contains("open.transaction.abort()"));
+
+        // a blank page has no origin to ask the protocol about
+        assert!(page.storage().databases().await.is_err());
+    }
+}
@@ -0,0 +1,444 @@
+//! JSON-LD, microdata and OpenGraph metadata, as typed records.
+//!
+//! Shops and publishers describe their pages for search engines: a product
+//! with its price and stock, an article with its author and dates, the
+//! breadcrumb trail. That markup is far more stable than the visible DOM.
+//! [`ChaserPage::structured_data`] collects it in one isolated-world pass:
+//! every JSON-LD block, every top-level microdata item (converted to the
+//! same JSON shape as JSON-LD) and the OpenGraph, Twitter and related meta
+//! tags. [`StructuredData`] then picks out [`Product`]s with their
+//! [`Offer`]s, [`Article`]s and [`Breadcrumb`] trails from both sources.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let data = chaser.structured_data().await?;
+//! for product in data.products() {
+//!     for offer in &product.offers {
+//!         println!("{:?}: {:?} {:?}", product.name, offer.price, offer.currency);
+//!     }
+//! }
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::ChaserResult as Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// `{json_ld, microdata, meta}`: the JSON-LD sources, microdata items as
+/// JSON-LD-like objects, and the metadata meta tags.
+const STRUCTURED_DATA_SCRIPT: &str = r#"(() => {
+    const norm = s => (s || '').replace(/\s+/g, ' ').trim();
+    const json_ld = Array.from(document.querySelectorAll('script[type="application/ld+json"]'))
+        .map(script => script.textContent);
+
+    const value = el => {
+        if (el.hasAttribute('itemscope')) return item(el);
+        if (el.hasAttribute('content')) return el.getAttribute('content');
+        switch (el.tagName) {
+            case 'A': case 'LINK': case 'AREA': return el.href;
+            case 'IMG': case 'AUDIO': case 'VIDEO': case 'SOURCE': case 'IFRAME': case 'EMBED': return el.src;
+            case 'META': return el.content;
+            case 'TIME': return el.getAttribute('datetime') || norm(el.textContent);
+            case 'DATA': case 'METER': return el.value;
+            default: return norm(el.textContent);
+        }
+    };
+    const item = scope => {
+        const out = {};
+        const type = scope.getAttribute('itemtype');
+        if (type) out['@type'] = type.split(/\s+/).map(t => t.replace(/^https?:\/\/schema\.org\//, ''));
+        // properties of this item, not of items nested in it
+        const walk = el => {
+            for (const child of el.children) {
+                const names = child.getAttribute('itemprop');
+                if (names) {
+                    const v = value(child);
+                    for (const name of names.split(/\s+/)) {
+                        if (name in out) out[name] = [].concat(out[name], v);
+                        else out[name] = v;
+                    }
+                }
+                if (!child.hasAttribute('itemscope')) walk(child);
+            }
+        };
+        walk(scope);
+        return out;
+    };
+    const microdata = Array.from(document.querySelectorAll('[itemscope]:not([itemprop])')).map(item);
+
+    const meta = {};
+    for (const el of document.querySelectorAll('meta[property], meta[name]')) {
+        const key = el.getAttribute('property') || el.getAttribute('name');
+        if (/^(og|twitter|product|article|book|profile|music|video):/.test(key) && el.content && !(key in meta)) {
+            meta[key] = el.content.trim();
+        }
+    }
+    return { json_ld, microdata, meta };
+})()"#;
+
+/// Everything a page declares about itself.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StructuredData {
+    /// Parsed JSON-LD blocks; blocks that are not valid JSON are dropped.
+    pub json_ld: Vec<Value>,
+    /// Top-level microdata items, with `@type` and one key per property.
+    pub microdata: Vec<Value>,
+    /// `og:*`, `twitter:*`, `product:*`, `article:*` and similar meta tags.
+    pub meta: BTreeMap<String, String>,
+}
+
+/// A product, from schema.org `Product`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Product {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub sku: Option<String>,
+    /// GTIN, EAN or UPC, whichever is given.
+    pub gtin: Option<String>,
+    pub brand: Option<String>,
+    pub image: Option<String>,
+    pub url: Option<String>,
+    pub offers: Vec<Offer>,
+    pub rating: Option<f64>,
+    pub review_count: Option<u64>,
+}
+
+/// A price, from schema.org `Offer` or `AggregateOffer` (its low price).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Offer {
+    /// As written, e.g. `"19.99"`.
+    pub price: Option<String>,
+    pub currency: Option<String>,
+    /// e.g. `InStock`, without the schema.org prefix.
+    pub availability: Option<String>,
+    pub seller: Option<String>,
+    pub url: Option<String>,
+}
+
+/// An article, from schema.org `Article` and its kinds, e.g. `NewsArticle`
+/// or `BlogPosting`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Article {
+    /// The schema.org type.
+    pub kind: String,
+    pub headline: Option<String>,
+    pub authors: Vec<String>,
+    pub date_published: Option<String>,
+    pub date_modified: Option<String>,
+    pub publisher: Option<String>,
+    pub image: Option<String>,
+}
+
+/// One step of a breadcrumb trail, from schema.org `BreadcrumbList`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Breadcrumb {
+    pub position: Option<u64>,
+    pub name: Option<String>,
+    pub url: Option<String>,
+}
+
+/// `@type` values without the schema.org prefix.
+fn types(item: &Value) -> Vec<&str> {
+    let raw: Vec<&str> = match item.get("@type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(ts)) => ts.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    raw.into_iter()
+        .map(|t| {
+            t.trim_start_matches("http://schema.org/")
+                .trim_start_matches("https://schema.org/")
+        })
+        .collect()
+}
+
+fn is_a(item: &Value, check: impl Fn(&str) -> bool) -> bool {
+    types(item).into_iter().any(check)
+}
+
+/// The first value of `value`, or `value` itself if it is not an array.
+fn first(value: &Value) -> &Value {
+    match value {
+        Value::Array(values) => values.first().unwrap_or(&Value::Null),
+        other => other,
+    }
+}
+
+/// A property as text: strings and numbers as they are, things by their
+/// `name`, `@id` or `url`.
+fn text(value: Option<&Value>) -> Option<String> {
+    match first(value?) {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        thing @ Value::Object(_) => ["name", "@id", "url", "contentUrl"]
+            .iter()
+            .find_map(|key| text(thing.get(key))),
+        _ => None,
+    }
+}
+
+fn number(value: Option<&Value>) -> Option<f64> {
+    match first(value?) {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// All values of `value`, whether it is an array or not.
+fn all(value: Option<&Value>) -> Vec<&Value> {
+    match value {
+        Some(Value::Array(values)) => values.iter().collect(),
+        Some(Value::Null) | None => Vec::new(),
+        Some(value) => vec![value],
+    }
+}
+
+fn offers(value: Option<&Value>) -> Vec<Offer> {
+    all(value)
+        .into_iter()
+        .flat_map(|offer| {
+            if is_a(offer, |t| t == "AggregateOffer") && offer.get("offers").is_some() {
+                return offers(offer.get("offers"));
+            }
+            let price = text(offer.get("price"))
+                .or_else(|| text(offer.get("lowPrice")))
+                .or_else(|| {
+                    text(
+                        offer
+                            .get("priceSpecification")
+                            .and_then(|s| first(s).get("price")),
+                    )
+                });
+            let currency = text(offer.get("priceCurrency")).or_else(|| {
+                text(
+                    offer
+                        .get("priceSpecification")
+                        .and_then(|s| first(s).get("priceCurrency")),
+                )
+            });
+            vec![Offer {
+                price,
+                currency,
+                availability: text(offer.get("availability")).map(|a| {
+                    a.trim_start_matches("http://schema.org/")
+                        .trim_start_matches("https://schema.org/")
+                        .to_string()
+                }),
+                seller: text(offer.get("seller")),
+                url: text(offer.get("url")),
+            }]
+        })
+        .collect()
+}
+
+impl StructuredData {
+    /// Every item from JSON-LD (including `@graph` members and items nested
+    /// as properties) and microdata, depth first.
+    pub fn items(&self) -> Vec<&Value> {
+        fn collect<'a>(value: &'a Value, out: &mut Vec<&'a Value>) {
+            match value {
+                Value::Array(values) => values.iter().for_each(|v| collect(v, out)),
+                Value::Object(map) => {
+                    if map.contains_key("@type") {
+                        out.push(value);
+                    }
+                    map.values().for_each(|v| collect(v, out));
+                }
+                _ => {}
+            }
+        }
+        let mut out = Vec::new();
+        for value in self.json_ld.iter().chain(&self.microdata) {
+            collect(value, &mut out);
+        }
+        out
+    }
+
+    pub fn products(&self) -> Vec<Product> {
+        self.items()
+            .into_iter()
+            .filter(|item| is_a(item, |t| t == "Product" || t == "ProductGroup"))
+            .map(|item| {
+                let rating = item.get("aggregateRating").map(first);
+                Product {
+                    name: text(item.get("name")),
+                    description: text(item.get("description")),
+                    sku: text(item.get("sku")),
+                    gtin: ["gtin", "gtin13", "gtin12", "gtin14", "gtin8"]
+                        .iter()
+                        .find_map(|key| text(item.get(key))),
+                    brand: text(item.get("brand")),
+                    image: text(item.get("image")),
+                    url: text(item.get("url")),
+                    offers: offers(item.get("offers")),
+                    rating: rating.and_then(|r| number(r.get("ratingValue"))),
+                    review_count: rating
+                        .and_then(|r| number(r.get("reviewCount")).or(number(r.get("ratingCount"))))
+                        .map(|n| n as u64),
+                }
+            })
+            .collect()
+    }
+
+    pub fn articles(&self) -> Vec<Article> {
+        self.items()
+            .into_iter()
+            .filter_map(|item| {
+                let kind = types(item)
+                    .into_iter()
+                    .find(|t| t.ends_with("Article") || *t == "BlogPosting")?
+                    .to_string();
+                Some(Article {
+                    kind,
+                    headline: text(item.get("headline")).or_else(|| text(item.get("name"))),
+                    authors: all(item.get("author"))
+                        .into_iter()
+                        .filter_map(|author| text(Some(author)))
+                        .collect(),
+                    date_published: text(item.get("datePublished")),
+                    date_modified: text(item.get("dateModified")),
+                    publisher: text(item.get("publisher")),
+                    image: text(item.get("image")),
+                })
+            })
+            .collect()
+    }
+
+    /// Every breadcrumb trail, each ordered by position.
+    pub fn breadcrumbs(&self) -> Vec<Vec<Breadcrumb>> {
+        self.items()
+            .into_iter()
+            .filter(|item| is_a(item, |t| t == "BreadcrumbList"))
+            .map(|list| {
+                let mut trail: Vec<Breadcrumb> = all(list.get("itemListElement"))
+                    .into_iter()
+                    .map(|entry| {
+                        let item = entry.get("item");
+                        Breadcrumb {
+                            position: number(entry.get("position")).map(|p| p as u64),
+                            name: text(entry.get("name"))
+                                .or_else(|| item.and_then(|i| text(first(i).get("name")))),
+                            url: item
+                                .and_then(|i| match first(i) {
+                                    Value::String(url) => Some(url.clone()),
+                                    thing => text(thing.get("@id")).or(text(thing.get("url"))),
+                                })
+                                .or_else(|| text(entry.get("url"))),
+                        }
+                    })
+                    .collect();
+                trail.sort_by_key(|crumb| crumb.position.unwrap_or(u64::MAX));
+                trail
+            })
+            .collect()
+    }
+
+    /// An OpenGraph property such as `title` or `image`, falling back to
+    /// the Twitter card's.
+    pub fn open_graph(&self, property: &str) -> Option<&str> {
+        self.meta
+            .get(&format!("og:{property}"))
+            .or_else(|| self.meta.get(&format!("twitter:{property}")))
+            .map(String::as_str)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawStructuredData {
+    json_ld: Vec<String>,
+    microdata: Vec<Value>,
+    meta: BTreeMap<String, String>,
+}
+
+impl From<RawStructuredData> for StructuredData {
+    fn from(raw: RawStructuredData) -> Self {
+        Self {
+            json_ld: raw
+                .json_ld
+                .iter()
+                .filter_map(|source| serde_json::from_str(source.trim()).ok())
+                .collect(),
+            microdata: raw.microdata,
+            meta: raw.meta,
+        }
+    }
+}
+
+impl ChaserPage {
+    /// The JSON-LD, microdata and metadata meta tags of the current page.
+    pub async fn structured_data(&self) -> Result<StructuredData> {
+        let value = self
+            .evaluate_stealth(STRUCTURED_DATA_SCRIPT)
+            .await?
+            .unwrap_or_default();
+        let raw: RawStructuredData = serde_json::from_value(value)?;
+        Ok(raw.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn types_json_ld_and_microdata() {
+        let raw = RawStructuredData {
+            json_ld: vec![
+                json!({
+                    "@context": "https://schema.org",
+                    "@graph": [
+                        {
+                            "@type": "Product",
+                            "name": "Kettle",
+                            "brand": {"@type": "Brand", "name": "Acme"},
+                            "offers": {
+                                "@type": "Offer",
+                                "price": 24.5,
+                                "priceCurrency": "EUR",
+                                "availability": "https://schema.org/InStock"
+                            },
+                            "aggregateRating": {"ratingValue": "4.6", "reviewCount": 120}
+                        },
+                        {
+                            "@type": "BreadcrumbList",
+                            "itemListElement": [
+                                {"position": 2, "name": "Kettles", "item": "https://shop.test/kettles"},
+                                {"position": 1, "name": "Kitchen", "item": {"@id": "https://shop.test/kitchen"}}
+                            ]
+                        }
+                    ]
+                })
+                .to_string(),
+                "{ not json".to_string(),
+            ],
+            microdata: vec![json!({
+                "@type": ["NewsArticle"],
+                "headline": "Kettles are back",
+                "author": [{"@type": ["Person"], "name": "Ada"}, "Grace"]
+            })],
+            meta: BTreeMap::from([("twitter:title".to_string(), "Kettle".to_string())]),
+        };
+        let data = StructuredData::from(raw);
+        assert_eq!(data.json_ld.len(), 1);
+
+        let products = data.products();
+        assert_eq!(products[0].brand.as_deref(), Some("Acme"));
+        assert_eq!(products[0].rating, Some(4.6));
+        let offer = &products[0].offers[0];
+        assert_eq!(offer.price.as_deref(), Some("24.5"));
+        assert_eq!(offer.availability.as_deref(), Some("InStock"));
+
+        let trail = &data.breadcrumbs()[0];
+        assert_eq!(trail[0].url.as_deref(), Some("https://shop.test/kitchen"));
+        assert_eq!(trail[1].name.as_deref(), Some("Kettles"));
+
+        let article = &data.articles()[0];
+        assert_eq!(article.kind, "NewsArticle");
+        assert_eq!(article.authors, ["Ada", "Grace"]);
+        assert_eq!(data.open_graph("title"), Some("Kettle"));
+    }
+}
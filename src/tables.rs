@@ -0,0 +1,133 @@
+//! Reading HTML tables into rows keyed by column header.
+//!
+//! [`ChaserPage::extract_table`] lays the table out in one isolated-world
+//! pass, the way it renders: cells spanning several rows or columns fill
+//! every slot they cover, stacked header rows are joined (`Price / USD`),
+//! and each cell's rendered text is taken whatever markup it is wrapped in.
+//! Tables nested inside cells are not mixed into their parent.
+//!
+//! # Example
+//!
+//! ```ignore
+//! for row in chaser.extract_table("#prices").await? {
+//!     println!("{} costs {}", row["Product"], row["Price"]);
+//! }
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::{ChaserError, ChaserResult as Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// `{headers, rows}` of the table matching `SELECTOR` (or the first table
+/// inside it), with spans expanded into a full grid.
+const TABLE_SCRIPT: &str = r#"(() => {
+    let table = document.querySelector(SELECTOR);
+    if (table && !(table instanceof HTMLTableElement)) table = table.querySelector('table');
+    if (!table) return null;
+    const text = cell => (cell.innerText ?? cell.textContent ?? '').replace(/\s+/g, ' ').trim();
+    const grid = [];
+    const rows = Array.from(table.rows);
+    rows.forEach((row, r) => {
+        grid[r] = grid[r] || [];
+        let c = 0;
+        for (const cell of row.cells) {
+            while (grid[r][c] !== undefined) c++;
+            const value = text(cell);
+            for (let dr = 0; dr < Math.max(cell.rowSpan, 1) && r + dr < rows.length; dr++) {
+                grid[r + dr] = grid[r + dr] || [];
+                for (let dc = 0; dc < Math.max(cell.colSpan, 1); dc++) grid[r + dr][c + dc] = value;
+            }
+            c += Math.max(cell.colSpan, 1);
+        }
+    });
+    let headerRows = table.tHead ? table.tHead.rows.length : 0;
+    if (!headerRows && rows.length && Array.from(rows[0].cells).every(cell => cell.tagName === 'TH')) {
+        headerRows = 1;
+    }
+    const width = Math.max(0, ...grid.map(row => row.length));
+    const headers = [];
+    for (let c = 0; c < width; c++) {
+        const parts = [];
+        for (let r = 0; r < headerRows; r++) {
+            const part = grid[r][c];
+            if (part && parts[parts.length - 1] !== part) parts.push(part);
+        }
+        headers.push(parts.join(' / '));
+    }
+    const body = grid.slice(headerRows)
+        .map(row => Array.from({ length: width }, (_, c) => row[c] ?? ''));
+    return { headers, rows: body };
+})()"#;
+
+#[derive(Debug, Deserialize)]
+struct RawTable {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+/// Unique column names: empty headers become `column N` and repeated ones
+/// get ` (2)`, ` (3)`, ...
+fn column_names(headers: &[String]) -> Vec<String> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    headers
+        .iter()
+        .enumerate()
+        .map(|(i, header)| {
+            let base = if header.is_empty() {
+                format!("column {}", i + 1)
+            } else {
+                header.clone()
+            };
+            let count = seen.entry(base.clone()).or_insert(0);
+            *count += 1;
+            if *count == 1 {
+                base
+            } else {
+                format!("{base} ({count})")
+            }
+        })
+        .collect()
+}
+
+fn into_records(table: RawTable) -> Vec<HashMap<String, String>> {
+    let names = column_names(&table.headers);
+    table
+        .rows
+        .into_iter()
+        .filter(|row| row.iter().any(|cell| !cell.is_empty()))
+        .map(|row| names.iter().cloned().zip(row).collect())
+        .collect()
+}
+
+impl ChaserPage {
+    /// The body rows of the table matching `selector` (or the first table
+    /// inside it), each keyed by column header. Empty rows are skipped.
+    pub async fn extract_table(&self, selector: &str) -> Result<Vec<HashMap<String, String>>> {
+        let script = TABLE_SCRIPT.replacen("SELECTOR", &serde_json::to_string(selector)?, 1);
+        match self.evaluate_stealth(&script).await? {
+            Some(value) if !value.is_null() => Ok(into_records(serde_json::from_value(value)?)),
+            _ => Err(ChaserError::ElementNotFound(selector.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_rows_by_unique_headers() {
+        let table = RawTable {
+            headers: ["Product", "", "Price", "Price"].map(String::from).to_vec(),
+            rows: vec![
+                ["Kettle", "new", "20", "25"].map(String::from).to_vec(),
+                ["", "", "", ""].map(String::from).to_vec(),
+            ],
+        };
+        let records = into_records(table);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["column 2"], "new");
+        assert_eq!(records[0]["Price (2)"], "25");
+    }
+}
@@ -0,0 +1,437 @@
+//! A local fixture server for hermetic integration tests.
+//!
+//! With the `test-harness` feature, [`FixtureServer::start`] serves a set of
+//! small pages from `127.0.0.1` on a free port, so the crate's tests and
+//! downstream suites can drive clicks, forms, frames, dialogs and downloads
+//! in a real browser without reaching any third-party site:
+//!
+//! | path | what it exercises |
+//! |------|-------------------|
+//! | `/` | an index linking every fixture |
+//! | `/click` | a button logging each click's trust and position into `#log` |
+//! | `/form` | text, password, select, checkbox and textarea posting to `/submit` |
+//! | `/submit` | records the fields (see [`FixtureServer::submissions`]) and echoes them as JSON in `#result` |
+//! | `/iframe` | a same-origin and a cross-origin `/frame` |
+//! | `/frame` | a button and an input inside a frame |
+//! | `/dialogs` | `alert`, `confirm` and `prompt`, with the answers in `#result` |
+//! | `/download` | a link to `/files/report.csv`, served as an attachment |
+//! | `/probe` | common automation signals as JSON in `#probe` and `window.__probe` |
+//! | `/slow?ms=N` | a page answered after `N` milliseconds |
+//! | `/status/N` | an empty response with status `N` |
+//! | `/echo` | the caller's address and request headers as JSON, readable cross-origin |
+//! | `/detectors/<name>` | a [bundled detector page](crate::detectors), see [`FixtureServer::detector_url`] |
+//!
+//! [`FixtureServer::cross_origin_url`] reaches the same server through
+//! `localhost`, a different origin than `127.0.0.1`.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::test_harness::FixtureServer;
+//!
+//! let server = FixtureServer::start().await?;
+//! chaser.goto(&server.url("/form")).await?;
+//! chaser.click_selector_human("#name").await?;
+//! chaser.type_text("Ada").await?;
+//! chaser.click_selector_human("#submit").await?;
+//! assert_eq!(server.submissions()[0].field("name"), Some("Ada"));
+//! ```
+
+use crate::detectors::Detector;
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{ACCESS_CONTROL_ALLOW_ORIGIN, CONTENT_DISPOSITION, CONTENT_TYPE};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use std::convert::Infallible;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+const INDEX: &str = r#"<!doctype html><title>Fixtures</title>
+<ul>
+  <li><a href="/click">click</a></li>
+  <li><a href="/form">form</a></li>
+  <li><a href="/iframe">iframe</a></li>
+  <li><a href="/dialogs">dialogs</a></li>
+  <li><a href="/download">download</a></li>
+  <li><a href="/probe">probe</a></li>
+</ul>"#;
+
+const CLICK: &str = r#"<!doctype html><title>Click</title>
+<button id="button" style="margin:120px 0 0 200px;width:160px;height:48px">Click me</button>
+<pre id="log"></pre>
+<script>
+  window.__clicks = [];
+  document.getElementById('button').addEventListener('click', e => {
+    window.__clicks.push({ trusted: e.isTrusted, x: e.clientX, y: e.clientY, detail: e.detail });
+    document.getElementById('log').textContent = JSON.stringify(window.__clicks);
+  });
+</script>"#;
+
+const FORM: &str = r#"<!doctype html><title>Form</title>
+<form id="form" method="post" action="/submit">
+  <label>Name <input id="name" name="name" autocomplete="off"></label>
+  <label>Password <input id="password" name="password" type="password"></label>
+  <label>Plan <select id="plan" name="plan">
+    <option value="free">Free</option><option value="pro">Pro</option><option value="team">Team</option>
+  </select></label>
+  <label><input id="terms" name="terms" type="checkbox" value="yes"> I agree</label>
+  <textarea id="notes" name="notes"></textarea>
+  <button id="submit" type="submit">Send</button>
+</form>"#;
+
+const FRAME: &str = r#"<!doctype html><title>Frame</title>
+<button id="frame-button">Inside</button><input id="frame-input">
+<script>
+  document.getElementById('frame-button').addEventListener('click', e => {
+    document.body.dataset.clicked = String(e.isTrusted);
+  });
+</script>"#;
+
+const DIALOGS: &str = r#"<!doctype html><title>Dialogs</title>
+<button id="alert" onclick="alert('hello'); result.textContent = 'alerted'">Alert</button>
+<button id="confirm" onclick="result.textContent = String(confirm('sure?'))">Confirm</button>
+<button id="prompt" onclick="result.textContent = String(prompt('name?', 'default'))">Prompt</button>
+<pre id="result"></pre>"#;
+
+const DOWNLOAD: &str = r#"<!doctype html><title>Download</title>
+<a id="download" href="/files/report.csv">Download report</a>"#;
+
+const REPORT_CSV: &str = "id,name,amount\n1,Kettle,24.50\n2,Toaster,39.00\n";
+
+const PROBE: &str = r#"<!doctype html><title>Probe</title>
+<pre id="probe"></pre>
+<script>
+  (async () => {
+    const gl = document.createElement('canvas').getContext('webgl');
+    const debug = gl && gl.getExtension('WEBGL_debug_renderer_info');
+    let notifications = null;
+    try { notifications = (await navigator.permissions.query({ name: 'notifications' })).state; } catch (e) {}
+    window.__probe = {
+      webdriver: navigator.webdriver,
+      user_agent: navigator.userAgent,
+      platform: navigator.platform,
+      languages: navigator.languages,
+      hardware_concurrency: navigator.hardwareConcurrency,
+      device_memory: navigator.deviceMemory ?? null,
+      plugins: navigator.plugins.length,
+      chrome_object: typeof window.chrome === 'object',
+      notification_permission: typeof Notification === 'undefined' ? null : Notification.permission,
+      notifications_query: notifications,
+      webgl_vendor: debug ? gl.getParameter(debug.UNMASKED_VENDOR_WEBGL) : null,
+      webgl_renderer: debug ? gl.getParameter(debug.UNMASKED_RENDERER_WEBGL) : null,
+      screen: [screen.width, screen.height, screen.availWidth, screen.availHeight],
+      window: [outerWidth, outerHeight, innerWidth, innerHeight],
+      cdc_globals: Object.keys(window).filter(k => /^cdc_|^\$cdc_|selenium|webdriver/i.test(k)),
+      timezone: Intl.DateTimeFormat().resolvedOptions().timeZone,
+    };
+    document.getElementById('probe').textContent = JSON.stringify(window.__probe);
+  })();
+</script>"#;
+
+/// A form posted to `/submit`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormSubmission {
+    /// The fields in the order the browser sent them.
+    pub fields: Vec<(String, String)>,
+}
+
+impl FormSubmission {
+    /// The first value of field `name`.
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, Default)]
+struct ServerState {
+    requests: Mutex<Vec<String>>,
+    submissions: Mutex<Vec<FormSubmission>>,
+}
+
+/// The running fixture server; stops when dropped.
+#[derive(Debug)]
+pub struct FixtureServer {
+    addr: SocketAddr,
+    state: Arc<ServerState>,
+    shutdown: Option<oneshot::Sender<()>>,
+    task: JoinHandle<()>,
+}
+
+impl FixtureServer {
+    /// Serve the fixtures on a free port of `127.0.0.1`.
+    pub async fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(ServerState::default());
+        let (shutdown, mut stop) = oneshot::channel();
+        let shared = state.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                let (stream, peer) = tokio::select! {
+                    accepted = listener.accept() => match accepted {
+                        Ok(accepted) => accepted,
+                        Err(_) => continue,
+                    },
+                    _ = &mut stop => break,
+                };
+                let state = shared.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |request| handle(request, peer, state.clone()));
+                    let _ = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await;
+                });
+            }
+        });
+        Ok(Self {
+            addr,
+            state,
+            shutdown: Some(shutdown),
+            task,
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// `path` on this server, e.g. `url("/form")`.
+    pub fn url(&self, path: &str) -> String {
+        format!("http://127.0.0.1:{}{}", self.addr.port(), path)
+    }
+
+    /// `path` on this server through `localhost`, a different origin than
+    /// [`url`](Self::url).
+    pub fn cross_origin_url(&self, path: &str) -> String {
+        format!("http://localhost:{}{}", self.addr.port(), path)
+    }
+
+    /// The bundled `detector` page on this server.
+    pub fn detector_url(&self, detector: Detector) -> String {
+        self.url(&detector.path())
+    }
+
+    /// Method and path of every request served so far, e.g. `GET /form`.
+    pub fn requests(&self) -> Vec<String> {
+        self.state.requests.lock().unwrap().clone()
+    }
+
+    /// The forms posted to `/submit` so far.
+    pub fn submissions(&self) -> Vec<FormSubmission> {
+        self.state.submissions.lock().unwrap().clone()
+    }
+}
+
+impl Drop for FixtureServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        self.task.abort();
+    }
+}
+
+fn html(body: impl Into<Bytes>) -> Response<Full<Bytes>> {
+    Response::builder()
+        .header(CONTENT_TYPE, "text/html; charset=utf-8")
+        .body(Full::new(body.into()))
+        .unwrap()
+}
+
+fn status(code: StatusCode) -> Response<Full<Bytes>> {
+    Response::builder()
+        .status(code)
+        .body(Full::new(Bytes::new()))
+        .unwrap()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+async fn handle(
+    request: Request<Incoming>,
+    peer: SocketAddr,
+    state: Arc<ServerState>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let query = request.uri().query().unwrap_or_default().to_string();
+    state
+        .requests
+        .lock()
+        .unwrap()
+        .push(format!("{method} {path}"));
+
+    let response = match (&method, path.as_str()) {
+        (&Method::GET, "/") => html(INDEX),
+        (&Method::GET, "/click") => html(CLICK),
+        (&Method::GET, "/form") => html(FORM),
+        (&Method::POST, "/submit") => {
+            let body = match request.into_body().collect().await {
+                Ok(body) => body.to_bytes(),
+                Err(_) => return Ok(status(StatusCode::BAD_REQUEST)),
+            };
+            let fields: Vec<(String, String)> =
+                url::form_urlencoded::parse(&body).into_owned().collect();
+            let json = serde_json::to_string(&fields).unwrap_or_default();
+            state
+                .submissions
+                .lock()
+                .unwrap()
+                .push(FormSubmission { fields });
+            html(format!(
+                "<!doctype html><title>Submitted</title><pre id=\"result\">{}</pre>",
+                escape(&json)
+            ))
+        }
+        (&Method::GET, "/iframe") => html(
+            "<!doctype html><title>Frames</title>\
+             <iframe id=\"same\" src=\"/frame\"></iframe>\
+             <iframe id=\"cross\" src=\"CROSS/frame\"></iframe>"
+                .replace(
+                    "CROSS",
+                    &format!("http://{}", cross_host(request.headers())),
+                ),
+        ),
+        (&Method::GET, "/frame") => html(FRAME),
+        (&Method::GET, "/dialogs") => html(DIALOGS),
+        (&Method::GET, "/download") => html(DOWNLOAD),
+        (&Method::GET, "/files/report.csv") => Response::builder()
+            .header(CONTENT_TYPE, "text/csv")
+            .header(CONTENT_DISPOSITION, "attachment; filename=\"report.csv\"")
+            .body(Full::new(Bytes::from_static(REPORT_CSV.as_bytes())))
+            .unwrap(),
+        (&Method::GET, "/probe") => html(PROBE),
+        (&Method::GET, "/slow") => {
+            let ms = url::form_urlencoded::parse(query.as_bytes())
+                .find(|(key, _)| key == "ms")
+                .and_then(|(_, value)| value.parse().ok())
+                .unwrap_or(1_000);
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+            html(format!(
+                "<!doctype html><title>Slow</title><p id=\"slow\">{ms}</p>"
+            ))
+        }
+        (&Method::GET, "/echo") => {
+            let headers: Vec<(&str, &str)> = request
+                .headers()
+                .iter()
+                .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)))
+                .collect();
+            let echo = serde_json::json!({ "ip": peer.ip(), "headers": headers });
+            Response::builder()
+                .header(CONTENT_TYPE, "application/json")
+                .header(ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+                .body(Full::new(Bytes::from(echo.to_string())))
+                .unwrap()
+        }
+        (&Method::GET, path) if path.starts_with("/detectors/") => {
+            match Detector::from_name(&path["/detectors/".len()..]) {
+                Some(detector) => html(detector.html()),
+                None => status(StatusCode::NOT_FOUND),
+            }
+        }
+        (&Method::GET, path) if path.starts_with("/status/") => {
+            match path["/status/".len()..].parse::<u16>() {
+                Ok(code) => status(StatusCode::from_u16(code).unwrap_or(StatusCode::BAD_REQUEST)),
+                Err(_) => status(StatusCode::BAD_REQUEST),
+            }
+        }
+        _ => status(StatusCode::NOT_FOUND),
+    };
+    Ok(response)
+}
+
+/// The other one of `127.0.0.1` and `localhost`, with the request's port.
+fn cross_host(headers: &hyper::HeaderMap) -> String {
+    let host = headers
+        .get(hyper::header::HOST)
+        .and_then(|host| host.to_str().ok())
+        .unwrap_or("127.0.0.1");
+    match host.split_once(':') {
+        Some(("localhost", port)) => format!("127.0.0.1:{port}"),
+        Some((_, port)) => format!("localhost:{port}"),
+        None => "localhost".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn serves_fixtures_and_records_forms() {
+        let server = FixtureServer::start().await.unwrap();
+        let client = reqwest::Client::new();
+
+        let form = client.get(server.url("/form")).send().await.unwrap();
+        assert!(form.text().await.unwrap().contains("action=\"/submit\""));
+
+        let echoed = client
+            .post(server.url("/submit"))
+            .header("content-type", "application/x-www-form-urlencoded")
+            .body("name=Ada+L&plan=pro&terms=yes")
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(echoed.contains("Ada L"));
+        assert_eq!(server.submissions()[0].field("plan"), Some("pro"));
+
+        let detector = client
+            .get(server.detector_url(Detector::Fingerprint))
+            .send()
+            .await
+            .unwrap();
+        assert!(detector
+            .text()
+            .await
+            .unwrap()
+            .contains("suite: 'fingerprint'"));
+
+        let missing = client.get(server.url("/status/503")).send().await.unwrap();
+        assert_eq!(missing.status().as_u16(), 503);
+
+        let echo = client
+            .get(server.url("/echo"))
+            .header("x-probe", "1")
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        let echo: serde_json::Value = serde_json::from_str(&echo).unwrap();
+        assert_eq!(echo["ip"], "127.0.0.1");
+        assert!(echo["headers"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!(["x-probe", "1"])));
+        assert_eq!(
+            server.requests(),
+            [
+                "GET /form",
+                "POST /submit",
+                "GET /detectors/fingerprint",
+                "GET /status/503",
+                "GET /echo"
+            ]
+        );
+    }
+}
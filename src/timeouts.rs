@@ -0,0 +1,103 @@
+//! Time limits for [`ChaserPage`] operations.
+//!
+//! Every operation of a [`ChaserPage`] is bounded by one of these limits and
+//! fails with [`ChaserError::Timeout`] once it runs out, so a page that
+//! never finishes loading or a script whose promise never settles cannot
+//! stall a flow forever.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::timeouts::Timeouts;
+//! use std::time::Duration;
+//!
+//! chaser.set_timeouts(
+//!     Timeouts::default()
+//!         .navigation(Duration::from_secs(60))
+//!         .evaluate(Duration::from_secs(5)),
+//! );
+//! ```
+//!
+//! [`ChaserPage`]: crate::chaser::ChaserPage
+//! [`ChaserError::Timeout`]: crate::error::ChaserError::Timeout
+
+use std::time::Duration;
+
+/// Per-category time limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    /// `goto` and other navigations (default: 30s).
+    pub navigation: Duration,
+    /// Each input event dispatched for clicks, typing and scrolling
+    /// (default: 10s).
+    pub action: Duration,
+    /// Script evaluation, including awaiting the returned promise
+    /// (default: 30s).
+    pub evaluate: Duration,
+    /// Waiting for a condition on the page (default: 30s).
+    pub wait: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            navigation: Duration::from_secs(30),
+            action: Duration::from_secs(10),
+            evaluate: Duration::from_secs(30),
+            wait: Duration::from_secs(30),
+        }
+    }
+}
+
+impl Timeouts {
+    pub fn navigation(mut self, limit: Duration) -> Self {
+        self.navigation = limit;
+        self
+    }
+
+    pub fn action(mut self, limit: Duration) -> Self {
+        self.action = limit;
+        self
+    }
+
+    pub fn evaluate(mut self, limit: Duration) -> Self {
+        self.evaluate = limit;
+        self
+    }
+
+    pub fn wait(mut self, limit: Duration) -> Self {
+        self.wait = limit;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn setters_change_only_their_limit() {
+        let timeouts = Timeouts::default()
+            .navigation(Duration::from_secs(60))
+            .evaluate(Duration::from_millis(500));
+        assert_eq!(
+            timeouts,
+            Timeouts {
+                navigation: Duration::from_secs(60),
+                action: Duration::from_secs(10),
+                evaluate: Duration::from_millis(500),
+                wait: Duration::from_secs(30),
+            }
+        );
+        assert_eq!(
+            Timeouts::default()
+                .action(Duration::ZERO)
+                .wait(Duration::MAX),
+            Timeouts {
+                action: Duration::ZERO,
+                wait: Duration::MAX,
+                ..Timeouts::default()
+            }
+        );
+    }
+}
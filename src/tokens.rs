@@ -0,0 +1,502 @@
+//! Finding the auth tokens a site handed to the browser.
+//!
+//! Scraping often moves from the page to the site's API once logged in,
+//! and the API wants the token the page uses: a JWT in `localStorage`, a
+//! session cookie, an `Authorization: Bearer` header its scripts add.
+//! [`ChaserPage::harvest_tokens`] collects them from
+//!
+//! - `localStorage` and `sessionStorage`, read in the isolated world,
+//!   including tokens nested in JSON values;
+//! - the cookies of the current URL;
+//! - the headers of requests seen since
+//!   [`watch_tokens`](ChaserPage::watch_tokens), if it was called.
+//!
+//! What counts as a token is set by [`TokenPatterns`]: entries whose name
+//! mentions `token`, `auth`, `session` and the like, header names such as
+//! `Authorization` or `X-API-Key`, and any value that parses as a JWT.
+//! JWTs come with their claims and expiry.
+//!
+//! # Example
+//!
+//! ```ignore
+//! chaser.watch_tokens(TokenPatterns::default()).await?;
+//! chaser.goto("https://app.example/dashboard").await?;
+//! for token in chaser.harvest_tokens().await? {
+//!     if !token.is_expired() {
+//!         println!("{:?} from {:?}: {}", token.kind, token.source, token.value);
+//!     }
+//! }
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::clock;
+use crate::error::ChaserResult as Result;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chromiumoxide_cdp::cdp::browser_protocol::network::EventRequestWillBeSent;
+use futures::StreamExt;
+use serde_json::Value;
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+use tokio::task::JoinHandle;
+use url::Url;
+
+/// Request headers a watch keeps, at most.
+const WATCH_CAPACITY: usize = 256;
+
+/// What [`ChaserPage::harvest_tokens_with`] treats as a token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenPatterns {
+    names: Vec<String>,
+    headers: Vec<String>,
+    min_len: usize,
+}
+
+impl Default for TokenPatterns {
+    fn default() -> Self {
+        Self {
+            names: [
+                "token",
+                "jwt",
+                "auth",
+                "bearer",
+                "session",
+                "sessid",
+                "api_key",
+                "apikey",
+                "api-key",
+                "credential",
+            ]
+            .map(String::from)
+            .to_vec(),
+            headers: [
+                "authorization",
+                "x-api-key",
+                "api-key",
+                "x-auth-token",
+                "x-access-token",
+                "x-session-token",
+            ]
+            .map(String::from)
+            .to_vec(),
+            min_len: 16,
+        }
+    }
+}
+
+impl TokenPatterns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also take storage entries and cookies whose name contains `part`,
+    /// ignoring case.
+    pub fn name(mut self, part: impl Into<String>) -> Self {
+        self.names.push(part.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Also take request headers called `name`, ignoring case.
+    pub fn header(mut self, name: impl Into<String>) -> Self {
+        self.headers.push(name.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Ignore values shorter than `len` characters, unless they are JWTs.
+    /// Defaults to 16, which skips flags like `auth=1`.
+    pub fn min_len(mut self, len: usize) -> Self {
+        self.min_len = len;
+        self
+    }
+
+    fn matches_name(&self, name: &str) -> bool {
+        let name = name.to_ascii_lowercase();
+        self.names.iter().any(|part| name.contains(part.as_str()))
+    }
+
+    fn matches_header(&self, name: &str) -> bool {
+        self.headers.iter().any(|h| h.eq_ignore_ascii_case(name))
+    }
+}
+
+/// What a harvested token looks like.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// A JSON Web Token, with readable claims.
+    Jwt,
+    /// An opaque value sent as `Authorization: Bearer`.
+    Bearer,
+    /// Any other value under a token-like name: API keys, session ids.
+    Opaque,
+}
+
+/// Where a token was found.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TokenSource {
+    /// Under `key`, possibly inside its JSON value at `path`.
+    LocalStorage {
+        key: String,
+        path: Option<String>,
+    },
+    SessionStorage {
+        key: String,
+        path: Option<String>,
+    },
+    Cookie {
+        name: String,
+    },
+    RequestHeader {
+        name: String,
+        url: String,
+    },
+}
+
+/// A token found by [`ChaserPage::harvest_tokens`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HarvestedToken {
+    /// The token itself, without a `Bearer ` prefix.
+    pub value: String,
+    pub kind: TokenKind,
+    pub source: TokenSource,
+    /// The origin the token belongs to: the document's for storage, the
+    /// cookie's domain, the request's origin.
+    pub origin: String,
+    /// Unix time after which the token is no longer valid: the `exp` claim
+    /// of a JWT, the expiry of a cookie.
+    pub expires_at: Option<u64>,
+    /// The payload of a JWT.
+    pub claims: Option<Value>,
+}
+
+impl HarvestedToken {
+    /// Whether the token is past its expiry. Tokens without one never are.
+    pub fn is_expired(&self) -> bool {
+        let now = clock::system_time()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+}
+
+/// The claims of `value` if it is a JWT.
+pub fn jwt_claims(value: &str) -> Option<Value> {
+    let mut parts = value.split('.');
+    let (header, payload, _signature) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    let decode = |part: &str| -> Option<Value> {
+        let bytes = URL_SAFE_NO_PAD.decode(part.trim_end_matches('=')).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    };
+    decode(header)?.get("alg")?;
+    decode(payload).filter(Value::is_object)
+}
+
+/// A token made of `value` if it qualifies: a JWT always, anything else
+/// only under a matching name.
+fn token(
+    value: &str,
+    named: bool,
+    patterns: &TokenPatterns,
+    source: TokenSource,
+    origin: &str,
+) -> Option<HarvestedToken> {
+    let (value, bearer) = match value.trim().strip_prefix("Bearer ") {
+        Some(rest) => (rest.trim(), true),
+        None => (value.trim(), false),
+    };
+    let (kind, claims) = match jwt_claims(value) {
+        Some(claims) => (TokenKind::Jwt, Some(claims)),
+        None if !named || value.len() < patterns.min_len || value.contains(char::is_whitespace) => {
+            return None
+        }
+        None if bearer => (TokenKind::Bearer, None),
+        None => (TokenKind::Opaque, None),
+    };
+    Some(HarvestedToken {
+        value: value.to_string(),
+        kind,
+        expires_at: claims
+            .as_ref()
+            .and_then(|c| c.get("exp"))
+            .and_then(Value::as_f64)
+            .map(|exp| exp as u64),
+        claims,
+        source,
+        origin: origin.to_string(),
+    })
+}
+
+/// Tokens in one storage entry: its value itself, or strings anywhere in
+/// it when the value is JSON.
+fn scan_entry(
+    key: &str,
+    value: &str,
+    patterns: &TokenPatterns,
+    source: impl Fn(Option<String>) -> TokenSource,
+    origin: &str,
+    found: &mut Vec<HarvestedToken>,
+) {
+    let named = patterns.matches_name(key);
+    match serde_json::from_str::<Value>(value) {
+        Ok(json @ (Value::Object(_) | Value::Array(_))) => {
+            scan_json(&json, "", named, patterns, &source, origin, found)
+        }
+        _ => found.extend(token(value, named, patterns, source(None), origin)),
+    }
+}
+
+fn scan_json(
+    json: &Value,
+    path: &str,
+    named: bool,
+    patterns: &TokenPatterns,
+    source: &impl Fn(Option<String>) -> TokenSource,
+    origin: &str,
+    found: &mut Vec<HarvestedToken>,
+) {
+    match json {
+        Value::Object(fields) => {
+            for (field, value) in fields {
+                let path = if path.is_empty() {
+                    field.clone()
+                } else {
+                    format!("{path}.{field}")
+                };
+                let named = named || patterns.matches_name(field);
+                scan_json(value, &path, named, patterns, source, origin, found);
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                let path = format!("{path}[{i}]");
+                scan_json(item, &path, named, patterns, source, origin, found);
+            }
+        }
+        Value::String(value) => found.extend(token(
+            value,
+            named,
+            patterns,
+            source(Some(path.to_string())),
+            origin,
+        )),
+        _ => {}
+    }
+}
+
+const STORAGE_SCRIPT: &str = r#"(() => {
+    const dump = (storage) => {
+        try { return Object.keys(storage).map(k => [k, storage.getItem(k)]); }
+        catch (e) { return []; }
+    };
+    return { origin: location.origin, local: dump(localStorage), session: dump(sessionStorage) };
+})()"#;
+
+/// A request header seen by a token watch.
+#[derive(Debug, Clone)]
+struct SeenHeader {
+    name: String,
+    value: String,
+    url: String,
+}
+
+/// The running watch of [`ChaserPage::watch_tokens`].
+#[derive(Debug)]
+pub(crate) struct TokenWatch {
+    seen: Arc<Mutex<VecDeque<SeenHeader>>>,
+    task: JoinHandle<()>,
+}
+
+impl Drop for TokenWatch {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl ChaserPage {
+    /// Start noting the request headers that match `patterns`, for
+    /// [`harvest_tokens`](Self::harvest_tokens) to include. Replaces an
+    /// earlier watch. Only the last 256 matching headers are kept.
+    pub async fn watch_tokens(&self, patterns: TokenPatterns) -> Result<()> {
+        let mut requests = self
+            .raw_page()
+            .event_listener::<EventRequestWillBeSent>()
+            .await?;
+        let seen = Arc::new(Mutex::new(VecDeque::new()));
+        let sink = Arc::clone(&seen);
+        let task = tokio::spawn(async move {
+            while let Some(event) = requests.next().await {
+                let Some(headers) = event.request.headers.inner().as_object() else {
+                    continue;
+                };
+                let mut seen = sink.lock().unwrap();
+                for (name, value) in headers {
+                    let Some(value) = value.as_str() else {
+                        continue;
+                    };
+                    if patterns.matches_header(name) || jwt_claims(value).is_some() {
+                        if seen.len() == WATCH_CAPACITY {
+                            seen.pop_front();
+                        }
+                        seen.push_back(SeenHeader {
+                            name: name.clone(),
+                            value: value.to_string(),
+                            url: event.request.url.clone(),
+                        });
+                    }
+                }
+            }
+        });
+        *self.token_watch().lock().unwrap() = Some(TokenWatch { seen, task });
+        Ok(())
+    }
+
+    /// Stop noting request headers and forget those noted.
+    pub fn stop_watching_tokens(&self) {
+        self.token_watch().lock().unwrap().take();
+    }
+
+    /// The tokens of the current document with the default
+    /// [`TokenPatterns`].
+    pub async fn harvest_tokens(&self) -> Result<Vec<HarvestedToken>> {
+        self.harvest_tokens_with(&TokenPatterns::default()).await
+    }
+
+    /// The tokens in storage, cookies and watched request headers that
+    /// match `patterns`. A value found in several places is reported once,
+    /// from the first: storage, then cookies, then requests.
+    pub async fn harvest_tokens_with(
+        &self,
+        patterns: &TokenPatterns,
+    ) -> Result<Vec<HarvestedToken>> {
+        let mut found = Vec::new();
+
+        if let Some(storage) = self.evaluate_stealth(STORAGE_SCRIPT).await? {
+            let origin = storage["origin"].as_str().unwrap_or_default().to_string();
+            let entries = |area: &str| -> Vec<(String, String)> {
+                serde_json::from_value(storage[area].clone()).unwrap_or_default()
+            };
+            for (key, value) in entries("local") {
+                let source = |path| TokenSource::LocalStorage {
+                    key: key.clone(),
+                    path,
+                };
+                scan_entry(&key, &value, patterns, source, &origin, &mut found);
+            }
+            for (key, value) in entries("session") {
+                let source = |path| TokenSource::SessionStorage {
+                    key: key.clone(),
+                    path,
+                };
+                scan_entry(&key, &value, patterns, source, &origin, &mut found);
+            }
+        }
+
+        for cookie in self.raw_page().get_cookies().await? {
+            let source = TokenSource::Cookie {
+                name: cookie.name.clone(),
+            };
+            let named = patterns.matches_name(&cookie.name);
+            if let Some(mut token) = token(&cookie.value, named, patterns, source, &cookie.domain) {
+                // session cookies report -1
+                if token.expires_at.is_none() && cookie.expires > 0.0 {
+                    token.expires_at = Some(cookie.expires as u64);
+                }
+                found.push(token);
+            }
+        }
+
+        let seen: Vec<SeenHeader> = match self.token_watch().lock().unwrap().as_ref() {
+            Some(watch) => watch.seen.lock().unwrap().iter().cloned().collect(),
+            None => Vec::new(),
+        };
+        for header in seen.into_iter().rev() {
+            let origin = Url::parse(&header.url)
+                .map(|url| url.origin().ascii_serialization())
+                .unwrap_or_default();
+            let named = patterns.matches_header(&header.name);
+            let source = TokenSource::RequestHeader {
+                name: header.name,
+                url: header.url,
+            };
+            found.extend(token(&header.value, named, patterns, source, &origin));
+        }
+
+        let mut values = HashSet::new();
+        found.retain(|token| values.insert(token.value.clone()));
+        Ok(found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn jwt(claims: Value) -> String {
+        let encode = |v: Value| URL_SAFE_NO_PAD.encode(v.to_string());
+        format!(
+            "{}.{}.c2lnbmF0dXJl",
+            encode(json!({ "alg": "HS256", "typ": "JWT" })),
+            encode(claims)
+        )
+    }
+
+    #[test]
+    fn finds_jwts_in_json_and_keys_by_name() {
+        let patterns = TokenPatterns::default();
+        let access = jwt(json!({ "sub": "42", "exp": 1_900_000_000 }));
+        let stored = json!({ "user": { "name": "ann" }, "tokens": [{ "value": access }] });
+        let mut found = Vec::new();
+        let source = |path| TokenSource::LocalStorage {
+            key: "app".to_string(),
+            path,
+        };
+        scan_entry(
+            "app",
+            &stored.to_string(),
+            &patterns,
+            source,
+            "https://app.test",
+            &mut found,
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].kind, TokenKind::Jwt);
+        assert_eq!(found[0].expires_at, Some(1_900_000_000));
+        assert_eq!(found[0].claims.as_ref().unwrap()["sub"], "42");
+        assert_eq!(
+            found[0].source,
+            TokenSource::LocalStorage {
+                key: "app".to_string(),
+                path: Some("tokens[0].value".to_string())
+            }
+        );
+
+        let header = |value: &str| {
+            let source = TokenSource::RequestHeader {
+                name: "authorization".to_string(),
+                url: "https://api.app.test/v1".to_string(),
+            };
+            token(value, true, &patterns, source, "https://api.app.test")
+        };
+        let bearer = header("Bearer 9f8e7d6c5b4a39281706f5e4").unwrap();
+        assert_eq!(bearer.kind, TokenKind::Bearer);
+        assert_eq!(bearer.value, "9f8e7d6c5b4a39281706f5e4");
+        assert!(header("Bearer short").is_none());
+
+        let unnamed = TokenSource::Cookie {
+            name: "theme".to_string(),
+        };
+        assert!(token(
+            "dark-high-contrast-mode",
+            false,
+            &patterns,
+            unnamed,
+            "app.test"
+        )
+        .is_none());
+        assert!(jwt_claims("a.b.c").is_none());
+    }
+}
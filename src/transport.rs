@@ -0,0 +1,420 @@
+//! Driving a [`Page`] without a browser.
+//!
+//! Every command a page issues normally travels through the handler to
+//! Chrome. [`Page::with_transport`] instead connects the page to a
+//! [`Transport`] that answers each command in-process, so the logic built
+//! on top of a page (Bezier mouse paths, key event sequences, request
+//! interception) can be unit-tested without launching Chrome.
+//!
+//! [`MockTransport`] records every command and replies with scripted
+//! results, `{}` by default. It also answers the isolated world setup, so
+//! [`ChaserPage::evaluate_stealth`](crate::ChaserPage::evaluate_stealth)
+//! evaluates to `undefined` unless `Runtime.evaluate` is scripted, and it
+//! can [`emit`](MockTransport::emit) events to the page's listeners.
+//!
+//! Frame bookkeeping is not simulated: the page has a single main frame,
+//! no URL, no execution contexts of its own, and navigations complete
+//! without a response.
+//!
+//! [`Browser::with_transport`] does the same for a browser, so code that
+//! creates contexts and pages, like the [`pool`](crate::pool), runs on a
+//! mock too. Every page it opens is connected to a clone of the transport,
+//! as the target `Target.createTarget` answers with.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::transport::MockTransport;
+//! use chaser_oxide::{ChaserPage, Page};
+//!
+//! let mock = MockTransport::new();
+//! let chaser = ChaserPage::new(Page::with_transport(mock.clone()));
+//! chaser.move_mouse_human(400.0, 300.0).await?;
+//! assert!(mock.commands_to("Input.dispatchMouseEvent").len() > 5);
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chromiumoxide_cdp::cdp::browser_protocol::page::FrameId;
+use chromiumoxide_cdp::cdp::browser_protocol::target::{SessionId, TargetId};
+use chromiumoxide_cdp::cdp::events::CdpEvent;
+use chromiumoxide_cdp::cdp::CdpEventMessage;
+use chromiumoxide_types::{CallId, Error, Response};
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::stream::{self, BoxStream};
+use futures::task::Poll;
+use futures::StreamExt;
+use serde_json::{json, Value};
+
+use crate::browser::Browser;
+use crate::error::CdpError;
+use crate::handler::target::TargetMessage;
+use crate::handler::{HandlerMessage, PageHandle};
+use crate::listeners::EventListeners;
+use crate::page::Page;
+
+/// Id of the only frame of a page on a transport.
+pub const MAIN_FRAME: &str = "MAIN_FRAME";
+
+/// Answers the commands of a [`Page`] in place of the browser.
+pub trait Transport: Send + 'static {
+    /// The result of `method` called with `params`, or the error the
+    /// browser would have returned.
+    fn call(&mut self, method: &str, params: &Value) -> Result<Value, Error>;
+
+    /// Events to deliver to the page's listeners. Taken once, when the
+    /// page is connected.
+    fn events(&mut self) -> BoxStream<'static, CdpEventMessage> {
+        stream::pending().boxed()
+    }
+}
+
+/// One command a page issued.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedCommand {
+    pub method: String,
+    pub params: Value,
+}
+
+type Responder = Box<dyn FnMut(&Value) -> Result<Value, Error> + Send>;
+
+#[derive(Default)]
+struct MockState {
+    commands: Vec<RecordedCommand>,
+    responders: HashMap<String, Responder>,
+    events: Option<UnboundedSender<CdpEventMessage>>,
+}
+
+/// A [`Transport`] that records commands and replies with scripted
+/// results. Clones share their state, so keep one to inspect the page.
+#[derive(Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl std::fmt::Debug for MockTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("MockTransport")
+            .field("commands", &state.commands.len())
+            .field("scripted", &state.responders.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reply to every `method` call with `result`.
+    pub fn respond(&self, method: impl Into<String>, result: Value) -> &Self {
+        self.respond_with(method, move |_| Ok(result.clone()))
+    }
+
+    /// Reply to `method` calls with what `responder` makes of their params.
+    pub fn respond_with(
+        &self,
+        method: impl Into<String>,
+        responder: impl FnMut(&Value) -> Result<Value, Error> + Send + 'static,
+    ) -> &Self {
+        self.state
+            .lock()
+            .unwrap()
+            .responders
+            .insert(method.into(), Box::new(responder));
+        self
+    }
+
+    /// Fail every `method` call with a protocol error.
+    pub fn fail(&self, method: impl Into<String>, message: impl Into<String>) -> &Self {
+        let message = message.into();
+        self.respond_with(method, move |_| {
+            Err(Error {
+                code: -32000,
+                message: message.clone(),
+            })
+        })
+    }
+
+    /// Deliver the event `method` with `params` to the page's listeners.
+    ///
+    /// # Panics
+    ///
+    /// If `method` is not a protocol event or `params` do not match it.
+    pub fn emit(&self, method: &str, params: Value) {
+        let event: CdpEventMessage =
+            serde_json::from_value(json!({ "method": method, "params": params }))
+                .unwrap_or_else(|e| panic!("invalid {method} event: {e}"));
+        if let Some(events) = &self.state.lock().unwrap().events {
+            let _ = events.unbounded_send(event);
+        }
+    }
+
+    /// Every command issued so far, in order.
+    pub fn commands(&self) -> Vec<RecordedCommand> {
+        self.state.lock().unwrap().commands.clone()
+    }
+
+    /// The params of every `method` command issued so far, in order.
+    pub fn commands_to(&self, method: &str) -> Vec<Value> {
+        self.state
+            .lock()
+            .unwrap()
+            .commands
+            .iter()
+            .filter(|command| command.method == method)
+            .map(|command| command.params.clone())
+            .collect()
+    }
+
+    /// Forget the commands issued so far.
+    pub fn clear(&self) {
+        self.state.lock().unwrap().commands.clear();
+    }
+}
+
+impl Transport for MockTransport {
+    fn call(&mut self, method: &str, params: &Value) -> Result<Value, Error> {
+        let mut state = self.state.lock().unwrap();
+        state.commands.push(RecordedCommand {
+            method: method.to_string(),
+            params: params.clone(),
+        });
+        if let Some(responder) = state.responders.get_mut(method) {
+            return responder(params);
+        }
+        Ok(match method {
+            "Page.createIsolatedWorld" => json!({ "executionContextId": 1 }),
+            "Page.navigate" => json!({ "frameId": MAIN_FRAME }),
+            "Page.addScriptToEvaluateOnNewDocument" => json!({ "identifier": "1" }),
+            "Target.createBrowserContext" => json!({ "browserContextId": "MOCK_CONTEXT" }),
+            "Target.createTarget" => json!({ "targetId": "MOCK_TARGET" }),
+            "Runtime.evaluate" | "Runtime.callFunctionOn" => {
+                json!({ "result": { "type": "undefined" } })
+            }
+            _ => json!({}),
+        })
+    }
+
+    fn events(&mut self) -> BoxStream<'static, CdpEventMessage> {
+        let (sender, receiver): (_, UnboundedReceiver<_>) = unbounded();
+        self.state.lock().unwrap().events = Some(sender);
+        receiver.boxed()
+    }
+}
+
+impl Page {
+    /// A page whose commands are answered by `transport` instead of a
+    /// browser. Must be called within a tokio runtime; the page is served
+    /// until every clone of it is dropped.
+    pub fn with_transport(transport: impl Transport) -> Self {
+        Self::with_transport_as(TargetId::new("MOCK_TARGET"), transport)
+    }
+
+    /// [`Page::with_transport`] for the target `target_id`.
+    pub(crate) fn with_transport_as(target_id: TargetId, mut transport: impl Transport) -> Self {
+        let handle = PageHandle::new(target_id, SessionId::new("MOCK_SESSION"), None);
+        let page = Page::from(handle.inner().clone());
+        let mut messages = handle.rx;
+        let mut events = transport.events().fuse();
+        tokio::spawn(async move {
+            let mut listeners = EventListeners::default();
+            let mut next_id = 0;
+            loop {
+                // commands first, so listeners are in place before later events
+                futures::select_biased! {
+                    message = messages.next() => {
+                        let Some(message) = message else { break };
+                        match message {
+                            TargetMessage::Command(command) => {
+                                next_id += 1;
+                                let (result, error) =
+                                    match transport.call(&command.method, &command.params) {
+                                        Ok(result) => (Some(result), None),
+                                        Err(error) => (None, Some(error)),
+                                    };
+                                let _ = command.sender.send(Ok(Response {
+                                    id: CallId::new(next_id),
+                                    result,
+                                    error,
+                                }));
+                            }
+                            TargetMessage::MainFrame(tx) => {
+                                let _ = tx.send(Some(FrameId::new(MAIN_FRAME)));
+                            }
+                            TargetMessage::AllFrames(tx) => {
+                                let _ = tx.send(vec![FrameId::new(MAIN_FRAME)]);
+                            }
+                            TargetMessage::Url(req) => {
+                                let _ = req.tx.send(None);
+                            }
+                            TargetMessage::Name(req) => {
+                                let _ = req.tx.send(None);
+                            }
+                            TargetMessage::Parent(req) => {
+                                let _ = req.tx.send(None);
+                            }
+                            TargetMessage::WaitForNavigation(tx) => {
+                                let _ = tx.send(None);
+                            }
+                            TargetMessage::AddEventListener(req) => listeners.add_listener(req),
+                            TargetMessage::GetExecutionContext(req) => {
+                                let _ = req.tx.send(None);
+                            }
+                            TargetMessage::Authenticate(_) => {}
+                        }
+                    }
+                    event = events.next() => {
+                        if let Some(event) = event {
+                            let CdpEventMessage { method, params, .. } = event;
+                            chromiumoxide_cdp::consume_event!(match params {
+                                |ev| listeners.start_send(ev),
+                                |json| { let _ = listeners.try_send_custom(&method, json); }
+                            });
+                        }
+                    }
+                }
+                // hand the queued events to their listeners
+                futures::future::poll_fn(|cx| {
+                    listeners.poll(cx);
+                    Poll::Ready(())
+                })
+                .await;
+            }
+        });
+        page
+    }
+}
+
+impl Browser {
+    /// A browser whose commands are answered by `transport` instead of a
+    /// browser process. Must be called within a tokio runtime.
+    ///
+    /// `Target.createTarget` is called for every new page, which is then
+    /// connected to a clone of `transport`. Targets are not tracked, so
+    /// [`pages`](Browser::pages) is always empty, and `Browser.close` stops
+    /// serving the browser.
+    pub fn with_transport<T: Transport + Clone>(mut transport: T) -> Self {
+        let (sender, mut messages) = futures::channel::mpsc::channel(1);
+        tokio::spawn(async move {
+            let mut next_id = 0;
+            while let Some(message) = messages.next().await {
+                match message {
+                    HandlerMessage::Command(command) => {
+                        next_id += 1;
+                        let (result, error) = match transport.call(&command.method, &command.params)
+                        {
+                            Ok(result) => (Some(result), None),
+                            Err(error) => (None, Some(error)),
+                        };
+                        let _ = command.sender.send(Ok(Response {
+                            id: CallId::new(next_id),
+                            result,
+                            error,
+                        }));
+                    }
+                    HandlerMessage::CreatePage(params, tx) => {
+                        let created = serde_json::to_value(&params)
+                            .map_err(CdpError::from)
+                            .and_then(|params| {
+                                transport
+                                    .call("Target.createTarget", &params)
+                                    .map_err(CdpError::from)
+                            })
+                            .map(|created| {
+                                let target_id = created["targetId"].as_str().unwrap_or_default();
+                                Page::with_transport_as(TargetId::new(target_id), transport.clone())
+                            });
+                        let _ = tx.send(created);
+                    }
+                    HandlerMessage::FetchTargets(tx) => {
+                        let _ = tx.send(Ok(Vec::new()));
+                    }
+                    HandlerMessage::GetPages(tx) => {
+                        let _ = tx.send(Vec::new());
+                    }
+                    HandlerMessage::GetPage(_, tx) => {
+                        let _ = tx.send(None);
+                    }
+                    HandlerMessage::CloseBrowser(tx) => {
+                        let closed = transport
+                            .call("Browser.close", &json!({}))
+                            .map(|_| Default::default())
+                            .map_err(CdpError::from);
+                        let _ = tx.send(closed);
+                        break;
+                    }
+                    HandlerMessage::InsertContext(_)
+                    | HandlerMessage::DisposeContext(_)
+                    | HandlerMessage::AddEventListener(_) => {}
+                }
+            }
+        });
+        Browser::from_sender(sender)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ChaserPage;
+    use chromiumoxide_cdp::cdp::browser_protocol::network::EventRequestWillBeSent;
+
+    #[tokio::test(start_paused = true)]
+    async fn records_input_and_delivers_events() {
+        let mock = MockTransport::new();
+        mock.respond(
+            "Runtime.evaluate",
+            json!({ "result": { "type": "object", "value": [800, 600] } }),
+        );
+        let chaser = ChaserPage::new(Page::with_transport(mock.clone()));
+
+        chaser.move_mouse_human(400.0, 300.0).await.unwrap();
+        let moves = mock.commands_to("Input.dispatchMouseEvent");
+        assert!(moves.len() > 5);
+        assert!(moves.iter().all(|m| m["type"] == "mouseMoved"));
+        let (x, y) = (&moves.last().unwrap()["x"], &moves.last().unwrap()["y"]);
+        assert!((x.as_f64().unwrap() - 400.0).abs() < 5.0);
+        assert!((y.as_f64().unwrap() - 300.0).abs() < 5.0);
+
+        mock.clear();
+        chaser.press_key("Enter").await.unwrap();
+        let keys: Vec<_> = mock
+            .commands_to("Input.dispatchKeyEvent")
+            .into_iter()
+            .map(|key| (key["type"].clone(), key["key"].clone()))
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                (json!("rawKeyDown"), json!("Enter")),
+                (json!("keyUp"), json!("Enter"))
+            ]
+        );
+
+        mock.fail("Page.navigate", "net::ERR_NAME_NOT_RESOLVED");
+        assert!(chaser.goto("https://unreachable.test").await.is_err());
+
+        let mut requests = chaser
+            .raw_page()
+            .event_listener::<EventRequestWillBeSent>()
+            .await
+            .unwrap();
+        mock.emit(
+            "Network.requestWillBeSent",
+            json!({
+                "requestId": "1", "loaderId": "1", "documentURL": "https://shop.test/",
+                "request": { "url": "https://shop.test/api", "method": "POST", "headers": {},
+                             "initialPriority": "High", "referrerPolicy": "origin" },
+                "timestamp": 1.0, "wallTime": 1.0, "initiator": { "type": "other" },
+                "redirectHasExtraInfo": false
+            }),
+        );
+        assert_eq!(
+            requests.next().await.unwrap().request.url,
+            "https://shop.test/api"
+        );
+    }
+}
@@ -1,5 +1,22 @@
+use rand::Rng;
 use std::path::{Path, PathBuf};
 
+/// A random identifier of 6 to 12 lowercase letters and digits, starting
+/// with a letter, for names that end up in the browser. A name every user
+/// of the crate shares is a signature detection vendors can look for.
+pub(crate) fn random_identifier() -> String {
+    const LETTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+    const ALPHANUMERIC: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+    let mut rng = rand::thread_rng();
+    let len = rng.gen_range(6..=12);
+    (0..len)
+        .map(|i| {
+            let pool = if i == 0 { LETTERS } else { ALPHANUMERIC };
+            pool[rng.gen_range(0..pool.len())] as char
+        })
+        .collect()
+}
+
 /// Write to file with configured runtime
 pub(crate) async fn write<P: AsRef<Path> + Unpin, C: AsRef<[u8]>>(
     path: P,
@@ -139,4 +156,17 @@ mod tests {
         assert!(is_likely_js_function("((abc), (def)) => {}"));
         assert!(is_likely_js_function("() => Promise.resolve(100 / 25)"));
     }
+
+    #[test]
+    fn random_identifiers_are_plain_and_distinct() {
+        let names: Vec<String> = (0..20).map(|_| random_identifier()).collect();
+        for name in &names {
+            assert!((6..=12).contains(&name.len()));
+            assert!(name.starts_with(|c: char| c.is_ascii_lowercase()));
+            assert!(name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()));
+        }
+        assert!(names[1..].iter().any(|n| *n != names[0]));
+    }
 }
@@ -0,0 +1,434 @@
+//! What the anti-bot systems of a site made of each navigation.
+//!
+//! [`ChaserPage::check_blocked`] answers one question for one page. Deciding
+//! when to retry, when to rotate an identity and which proxies or profiles
+//! are burning over weeks needs more: the HTTP status, Cloudflare's Ray ID,
+//! the cookies DataDome, PerimeterX, Akamai and Imperva set, and whether the
+//! page merely looks like a block page. A [`VerdictCollector`] gathers those
+//! after every navigation into a [`NavigationVerdict`], a serializable
+//! record ready for a [`Sink`](crate::sinks::Sink), and keeps running
+//! [`VerdictStats`]. On pages the orchestrator leases from a pool with an
+//! [`IdentityRotation`](crate::rotation::IdentityRotation), the verdicts
+//! also decide which proxies and profiles stay in use.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::verdict::VerdictCollector;
+//!
+//! let verdicts = VerdictCollector::new();
+//! let verdict = verdicts.goto(&chaser, "https://shop.example.com").await;
+//! tx.send(&verdict).await?;
+//! // a flow hands blocks and captchas on as errors, so callers can tell
+//! // retries (`is_retryable`) from rotations (`needs_rotation`)
+//! verdict.into_result()?;
+//! ```
+
+use crate::chaser::{ChaserPage, BLOCK_CHECK_SCRIPT};
+use crate::error::{ChaserError, ChaserResult as Result};
+use crate::metrics::Metrics;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Status, Ray ID and block page hints of the current document.
+const SIGNALS_SCRIPT: &str = r#"(() => {
+    const nav = performance.getEntriesByType('navigation')[0];
+    const text = document.body ? document.body.innerText.slice(0, 5000) : '';
+    const ray = text.match(/Ray ID:\s*([0-9a-f]{16})/i);
+    const phrases = [
+        'access denied', 'access to this page has been denied', 'request blocked',
+        'unusual traffic', 'are you a robot', 'verify you are a human',
+        'pardon our interruption', 'too many requests', 'bot detected',
+    ];
+    const lower = (document.title + ' ' + text).toLowerCase();
+    return {
+        status: nav && nav.responseStatus ? nav.responseStatus : null,
+        ray: ray ? ray[1] : null,
+        cloudflareChallenge: typeof window._cf_chl_opt !== 'undefined',
+        textLength: text.length,
+        phrases: phrases.filter(p => lower.includes(p)),
+    };
+})()"#;
+
+/// Cookie name prefixes set by anti-bot vendors.
+const VENDOR_COOKIES: [(&str, &str); 11] = [
+    ("cf_clearance", "Cloudflare"),
+    ("__cf_bm", "Cloudflare"),
+    ("datadome", "DataDome"),
+    ("_px", "PerimeterX"),
+    ("_abck", "Akamai"),
+    ("bm_sz", "Akamai"),
+    ("ak_bmsc", "Akamai"),
+    ("reese84", "Imperva"),
+    ("incap_ses_", "Imperva"),
+    ("visid_incap_", "Imperva"),
+    ("aws-waf-token", "AWS WAF"),
+];
+
+/// Block pages are short; a long page mentioning "access denied" is content.
+const BLOCK_PAGE_MAX_TEXT: usize = 2_000;
+
+/// How a navigation went.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Outcome {
+    Passed,
+    /// An interstitial that clears itself, such as Cloudflare's
+    /// "Just a moment".
+    Challenged {
+        vendor: String,
+    },
+    Captcha {
+        provider: String,
+    },
+    /// A block page, a 403 or a page reading like one.
+    Blocked {
+        vendor: Option<String>,
+    },
+    /// HTTP 429.
+    RateLimited,
+    /// Any other HTTP error status.
+    HttpError {
+        status: u16,
+    },
+    /// The navigation itself failed.
+    Failed {
+        error: String,
+    },
+}
+
+impl Outcome {
+    /// Short snake_case name, as used in serialized records and metrics.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Outcome::Passed => "passed",
+            Outcome::Challenged { .. } => "challenged",
+            Outcome::Captcha { .. } => "captcha",
+            Outcome::Blocked { .. } => "blocked",
+            Outcome::RateLimited => "rate_limited",
+            Outcome::HttpError { .. } => "http_error",
+            Outcome::Failed { .. } => "failed",
+        }
+    }
+}
+
+/// Everything collected about one navigation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NavigationVerdict {
+    /// The URL navigated to.
+    pub url: String,
+    /// Where the page ended up.
+    pub final_url: Option<String>,
+    /// Unix time the navigation started.
+    pub at: u64,
+    pub elapsed_ms: u64,
+    /// Status of the main document, if Chrome reports it.
+    pub status: Option<u16>,
+    pub outcome: Outcome,
+    /// Cloudflare's Ray ID from a block or challenge page.
+    pub cf_ray: Option<String>,
+    /// Anti-bot vendors whose cookies are set for the page.
+    pub vendors: Vec<String>,
+    /// Heuristics that fired, e.g. `phrase:unusual traffic`.
+    pub signals: Vec<String>,
+}
+
+impl NavigationVerdict {
+    pub fn passed(&self) -> bool {
+        self.outcome == Outcome::Passed
+    }
+
+    /// The outcome as an error, so that [`ChaserError::is_retryable`] and
+    /// [`ChaserError::needs_rotation`] can drive what happens next.
+    pub fn into_result(self) -> Result<()> {
+        match self.outcome {
+            Outcome::Passed => Ok(()),
+            Outcome::Challenged { vendor } => Err(ChaserError::BlockedByAntiBot { vendor }),
+            Outcome::Captcha { provider } => Err(ChaserError::CaptchaRequired { provider }),
+            Outcome::Blocked { vendor } => Err(ChaserError::BlockedByAntiBot {
+                vendor: vendor.unwrap_or_else(|| "an unknown vendor".to_string()),
+            }),
+            Outcome::RateLimited => Err(ChaserError::RateLimited(self.url)),
+            // server errors may pass on a retry, client errors will not
+            Outcome::HttpError { status } if status >= 500 => Err(ChaserError::Navigation {
+                url: self.url,
+                reason: format!("HTTP {status}"),
+            }),
+            Outcome::HttpError { status } => Err(ChaserError::msg(format!(
+                "{} answered HTTP {status}",
+                self.url
+            ))),
+            Outcome::Failed { error } => Err(ChaserError::Navigation {
+                url: self.url,
+                reason: error,
+            }),
+        }
+    }
+}
+
+/// Signals read from the page, before classification.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageSignals {
+    status: Option<u16>,
+    ray: Option<String>,
+    cloudflare_challenge: bool,
+    text_length: usize,
+    phrases: Vec<String>,
+}
+
+/// Decide the outcome from what was read; returns the outcome and the
+/// heuristics that fired.
+fn classify(
+    page: &PageSignals,
+    interstitial: Option<(String, String)>,
+    vendors: &[String],
+) -> (Outcome, Vec<String>) {
+    let mut signals: Vec<String> = page.phrases.iter().map(|p| format!("phrase:{p}")).collect();
+    if page.cloudflare_challenge {
+        signals.push("cloudflare:challenge-script".to_string());
+    }
+    let outcome = match (interstitial, page.status) {
+        (Some((kind, name)), _) if kind == "captcha" => Outcome::Captcha { provider: name },
+        (Some((_, name)), _) if name == "Cloudflare" && page.cloudflare_challenge => {
+            Outcome::Challenged { vendor: name }
+        }
+        (Some((_, name)), _) => Outcome::Blocked { vendor: Some(name) },
+        (None, Some(429)) => Outcome::RateLimited,
+        (None, Some(403)) => Outcome::Blocked {
+            vendor: vendors.first().cloned(),
+        },
+        (None, Some(status)) if status >= 400 => Outcome::HttpError { status },
+        (None, _) if !page.phrases.is_empty() && page.text_length < BLOCK_PAGE_MAX_TEXT => {
+            signals.push("short-page".to_string());
+            Outcome::Blocked {
+                vendor: vendors.first().cloned(),
+            }
+        }
+        (None, _) => Outcome::Passed,
+    };
+    (outcome, signals)
+}
+
+/// Running counts of navigation outcomes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerdictStats {
+    pub navigations: usize,
+    pub passed: usize,
+    pub challenged: usize,
+    pub captchas: usize,
+    pub blocked: usize,
+    pub rate_limited: usize,
+    pub http_errors: usize,
+    pub failed: usize,
+}
+
+impl VerdictStats {
+    fn record(&mut self, outcome: &Outcome) {
+        self.navigations += 1;
+        let count = match outcome {
+            Outcome::Passed => &mut self.passed,
+            Outcome::Challenged { .. } => &mut self.challenged,
+            Outcome::Captcha { .. } => &mut self.captchas,
+            Outcome::Blocked { .. } => &mut self.blocked,
+            Outcome::RateLimited => &mut self.rate_limited,
+            Outcome::HttpError { .. } => &mut self.http_errors,
+            Outcome::Failed { .. } => &mut self.failed,
+        };
+        *count += 1;
+    }
+
+    /// Share of navigations that met a challenge, captcha or block.
+    pub fn block_rate(&self) -> f64 {
+        if self.navigations == 0 {
+            return 0.0;
+        }
+        (self.challenged + self.captchas + self.blocked) as f64 / self.navigations as f64
+    }
+}
+
+/// Collects a [`NavigationVerdict`] per navigation. Clones share their
+/// records, so one collector can serve many concurrent flows.
+#[derive(Debug, Clone, Default)]
+pub struct VerdictCollector {
+    inner: Arc<Mutex<(Vec<NavigationVerdict>, VerdictStats)>>,
+    metrics: Option<Metrics>,
+}
+
+impl VerdictCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also count every verdict in `metrics`.
+    pub fn metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Navigate `page` to `url` and collect the verdict. Navigation errors
+    /// become [`Outcome::Failed`] rather than an `Err`.
+    pub async fn goto(&self, page: &ChaserPage, url: &str) -> NavigationVerdict {
+        let at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let started = Instant::now();
+        let verdict = match page.goto(url).await {
+            Ok(()) => self
+                .read(page, url)
+                .await
+                .unwrap_or_else(|e| failed(url, e)),
+            Err(e) => failed(url, e),
+        };
+        let verdict = NavigationVerdict {
+            at,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            ..verdict
+        };
+        self.push(page, verdict.clone());
+        verdict
+    }
+
+    /// Collect the verdict for a navigation to `url` that already happened
+    /// on `page`.
+    pub async fn collect(&self, page: &ChaserPage, url: &str) -> Result<NavigationVerdict> {
+        let mut verdict = self.read(page, url).await?;
+        verdict.at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        self.push(page, verdict.clone());
+        Ok(verdict)
+    }
+
+    /// Every verdict collected so far.
+    pub fn records(&self) -> Vec<NavigationVerdict> {
+        self.inner.lock().unwrap().0.clone()
+    }
+
+    /// Take the verdicts collected so far, e.g. to write them to a sink.
+    /// The stats keep counting.
+    pub fn drain(&self) -> Vec<NavigationVerdict> {
+        std::mem::take(&mut self.inner.lock().unwrap().0)
+    }
+
+    pub fn stats(&self) -> VerdictStats {
+        self.inner.lock().unwrap().1
+    }
+
+    fn push(&self, page: &ChaserPage, verdict: NavigationVerdict) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_verdict(&verdict);
+        }
+        // for the orchestrator's rotation, if it is listening
+        if let Some(log) = page.verdict_log().lock().unwrap().as_mut() {
+            log.push(verdict.clone());
+        }
+        let mut inner = self.inner.lock().unwrap();
+        inner.1.record(&verdict.outcome);
+        inner.0.push(verdict);
+    }
+
+    async fn read(&self, page: &ChaserPage, url: &str) -> Result<NavigationVerdict> {
+        let signals: PageSignals = page
+            .evaluate_stealth(SIGNALS_SCRIPT)
+            .await?
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default();
+        let interstitial = page
+            .evaluate_stealth(BLOCK_CHECK_SCRIPT)
+            .await?
+            .and_then(|v| serde_json::from_value::<(String, String)>(v).ok());
+        let mut vendors: Vec<String> = Vec::new();
+        for cookie in page.raw_page().get_cookies().await? {
+            let vendor = VENDOR_COOKIES
+                .iter()
+                .find(|(prefix, _)| cookie.name.starts_with(prefix))
+                .map(|(_, vendor)| vendor.to_string());
+            if let Some(vendor) = vendor.filter(|v| !vendors.contains(v)) {
+                vendors.push(vendor);
+            }
+        }
+        let (outcome, heuristics) = classify(&signals, interstitial, &vendors);
+        Ok(NavigationVerdict {
+            url: url.to_string(),
+            final_url: page.url().await?,
+            at: 0,
+            elapsed_ms: 0,
+            status: signals.status,
+            outcome,
+            cf_ray: signals.ray,
+            vendors,
+            signals: heuristics,
+        })
+    }
+}
+
+fn failed(url: &str, error: ChaserError) -> NavigationVerdict {
+    NavigationVerdict {
+        url: url.to_string(),
+        final_url: None,
+        at: 0,
+        elapsed_ms: 0,
+        status: None,
+        outcome: Outcome::Failed {
+            error: error.to_string(),
+        },
+        cf_ray: None,
+        vendors: Vec::new(),
+        signals: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_statuses_interstitials_and_block_pages() {
+        let page = |status, phrases: &[&str], text_length| PageSignals {
+            status,
+            text_length,
+            phrases: phrases.iter().map(|p| p.to_string()).collect(),
+            ..Default::default()
+        };
+        let datadome = vec!["DataDome".to_string()];
+
+        let (outcome, _) = classify(&page(Some(200), &[], 9_000), None, &datadome);
+        assert_eq!(outcome, Outcome::Passed);
+        let (outcome, _) = classify(&page(Some(403), &[], 300), None, &datadome);
+        assert_eq!(
+            outcome,
+            Outcome::Blocked {
+                vendor: Some("DataDome".into())
+            }
+        );
+        let (outcome, _) = classify(&page(Some(429), &[], 300), None, &[]);
+        assert_eq!(outcome, Outcome::RateLimited);
+
+        // a short page about unusual traffic is a block page, a long one is content
+        let (outcome, signals) = classify(&page(Some(200), &["unusual traffic"], 400), None, &[]);
+        assert_eq!(outcome, Outcome::Blocked { vendor: None });
+        assert!(signals.contains(&"short-page".to_string()));
+        let (outcome, _) = classify(&page(Some(200), &["unusual traffic"], 4_000), None, &[]);
+        assert_eq!(outcome, Outcome::Passed);
+
+        let challenge = PageSignals {
+            cloudflare_challenge: true,
+            ..page(Some(403), &[], 100)
+        };
+        let cloudflare = Some(("block".to_string(), "Cloudflare".to_string()));
+        let (outcome, _) = classify(&challenge, cloudflare, &[]);
+        assert_eq!(
+            outcome,
+            Outcome::Challenged {
+                vendor: "Cloudflare".into()
+            }
+        );
+
+        let mut stats = VerdictStats::default();
+        stats.record(&Outcome::Passed);
+        stats.record(&outcome);
+        assert_eq!(stats.block_rate(), 0.5);
+    }
+}
@@ -0,0 +1,417 @@
+//! Finding click targets in screenshots, for UIs without DOM selectors.
+//!
+//! Canvas-rendered apps, image maps and slider puzzles draw their targets as
+//! pixels. With the `vision` feature, [`ChaserPage::find_template`] takes a
+//! screenshot of the viewport (or a region of it), looks for a reference
+//! image by normalized cross-correlation, and reports where it is in
+//! viewport coordinates; [`ChaserPage::click_template`] then clicks it with
+//! the usual humanized movement. [`ChaserPage::find_vertical_edge`] finds
+//! the strongest vertical edge in a region, which is where the notch of
+//! most slider puzzles starts.
+//!
+//! Matching runs on grayscale images, first on a downscaled copy and then
+//! around the best coarse candidates at full resolution, so templates need
+//! not be pixel-exact but should be captured at the profile's device pixel
+//! ratio.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::vision::Template;
+//!
+//! let play = Template::from_path("templates/play-button.png")?;
+//! let found = chaser.click_template(&play, None).await?;
+//! println!("clicked at {:?} (score {:.2})", found.center(), found.score);
+//! ```
+
+use crate::chaser::{ChaserPage, Point};
+use crate::error::{ChaserError, ChaserResult as Result};
+use crate::layout::BoundingBox;
+use crate::page::ScreenshotParams;
+use chromiumoxide_cdp::cdp::browser_protocol::page::CaptureScreenshotFormat;
+use image::imageops::{self, FilterType};
+use image::GrayImage;
+use std::path::Path;
+
+/// Lowest normalized cross-correlation accepted as a match by default.
+pub const DEFAULT_MIN_SCORE: f64 = 0.8;
+
+/// Coarse candidates refined at full resolution.
+const CANDIDATES: usize = 3;
+
+/// A reference image to look for.
+#[derive(Debug, Clone)]
+pub struct Template {
+    image: GrayImage,
+    min_score: f64,
+}
+
+impl Template {
+    pub fn from_image(image: GrayImage) -> Self {
+        Self {
+            image,
+            min_score: DEFAULT_MIN_SCORE,
+        }
+    }
+
+    /// Decode a PNG, JPEG or other image the `image` crate can read.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let image = image::load_from_memory(bytes).map_err(|e| ChaserError::msg(e.to_string()))?;
+        Ok(Self::from_image(image.to_luma8()))
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let bytes = std::fs::read(path).map_err(|e| ChaserError::msg(e.to_string()))?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Accept matches scoring at least `min_score` (-1 to 1), instead of
+    /// [`DEFAULT_MIN_SCORE`].
+    pub fn min_score(mut self, min_score: f64) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
+    pub fn width(&self) -> u32 {
+        self.image.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.image.height()
+    }
+}
+
+/// Where a template was found.
+#[derive(Debug, Clone)]
+pub struct TemplateMatch {
+    /// In viewport CSS pixels.
+    pub bounds: BoundingBox,
+    /// Normalized cross-correlation, 1 for a perfect match.
+    pub score: f64,
+}
+
+impl TemplateMatch {
+    pub fn center(&self) -> Point {
+        Point {
+            x: self.bounds.x + self.bounds.width / 2.0,
+            y: self.bounds.y + self.bounds.height / 2.0,
+        }
+    }
+}
+
+/// Sums over rectangles of an image in constant time.
+struct Integral {
+    width: usize,
+    sum: Vec<f64>,
+    squares: Vec<f64>,
+}
+
+impl Integral {
+    fn new(image: &GrayImage) -> Self {
+        let (w, h) = (image.width() as usize, image.height() as usize);
+        let width = w + 1;
+        let mut sum = vec![0.0; width * (h + 1)];
+        let mut squares = vec![0.0; width * (h + 1)];
+        for y in 0..h {
+            let (mut row, mut row_squares) = (0.0, 0.0);
+            for x in 0..w {
+                let v = image.get_pixel(x as u32, y as u32).0[0] as f64;
+                row += v;
+                row_squares += v * v;
+                sum[(y + 1) * width + x + 1] = sum[y * width + x + 1] + row;
+                squares[(y + 1) * width + x + 1] = squares[y * width + x + 1] + row_squares;
+            }
+        }
+        Self {
+            width,
+            sum,
+            squares,
+        }
+    }
+
+    fn rect(table: &[f64], width: usize, x: usize, y: usize, w: usize, h: usize) -> f64 {
+        table[(y + h) * width + x + w] - table[y * width + x + w] - table[(y + h) * width + x]
+            + table[y * width + x]
+    }
+
+    /// Sum and sum of squares of the `w`×`h` window at (`x`, `y`).
+    fn window(&self, x: usize, y: usize, w: usize, h: usize) -> (f64, f64) {
+        (
+            Self::rect(&self.sum, self.width, x, y, w, h),
+            Self::rect(&self.squares, self.width, x, y, w, h),
+        )
+    }
+}
+
+/// Zero-mean template pixels and their norm, for correlating.
+struct Prepared {
+    width: usize,
+    height: usize,
+    pixels: Vec<f64>,
+    norm: f64,
+}
+
+impl Prepared {
+    fn new(template: &GrayImage) -> Self {
+        let pixels: Vec<f64> = template.pixels().map(|p| p.0[0] as f64).collect();
+        let mean = pixels.iter().sum::<f64>() / pixels.len().max(1) as f64;
+        let pixels: Vec<f64> = pixels.into_iter().map(|v| v - mean).collect();
+        let norm = pixels.iter().map(|v| v * v).sum::<f64>().sqrt();
+        Self {
+            width: template.width() as usize,
+            height: template.height() as usize,
+            pixels,
+            norm,
+        }
+    }
+
+    /// Normalized cross-correlation with the window of `haystack` at
+    /// (`x`, `y`); 0 for flat windows or templates.
+    fn score(&self, haystack: &GrayImage, integral: &Integral, x: usize, y: usize) -> f64 {
+        let n = (self.width * self.height) as f64;
+        let (sum, squares) = integral.window(x, y, self.width, self.height);
+        let variance = squares - sum * sum / n;
+        if self.norm == 0.0 || variance <= 1e-9 {
+            return 0.0;
+        }
+        let mut dot = 0.0;
+        for v in 0..self.height {
+            let row = &self.pixels[v * self.width..(v + 1) * self.width];
+            for (u, t) in row.iter().enumerate() {
+                dot += t * haystack.get_pixel((x + u) as u32, (y + v) as u32).0[0] as f64;
+            }
+        }
+        dot / (self.norm * variance.sqrt())
+    }
+}
+
+/// The best positions of `template` in `haystack`, best first.
+fn best_positions(
+    haystack: &GrayImage,
+    template: &GrayImage,
+    within: Option<(usize, usize, usize, usize)>,
+    keep: usize,
+) -> Vec<(usize, usize, f64)> {
+    if template.width() > haystack.width() || template.height() > haystack.height() {
+        return Vec::new();
+    }
+    let prepared = Prepared::new(template);
+    let integral = Integral::new(haystack);
+    let max_x = (haystack.width() - template.width()) as usize;
+    let max_y = (haystack.height() - template.height()) as usize;
+    let (x0, y0, x1, y1) = within
+        .map(|(x0, y0, x1, y1)| (x0.min(max_x), y0.min(max_y), x1.min(max_x), y1.min(max_y)))
+        .unwrap_or((0, 0, max_x, max_y));
+    let mut best: Vec<(usize, usize, f64)> = Vec::with_capacity(keep + 1);
+    for y in y0..=y1 {
+        for x in x0..=x1 {
+            let score = prepared.score(haystack, &integral, x, y);
+            if best.len() < keep || score > best[best.len() - 1].2 {
+                // neighbours of a kept position are the same candidate
+                if let Some(near) = best
+                    .iter_mut()
+                    .find(|(bx, by, _)| bx.abs_diff(x) <= 2 && by.abs_diff(y) <= 2)
+                {
+                    if score > near.2 {
+                        *near = (x, y, score);
+                    }
+                } else {
+                    best.push((x, y, score));
+                }
+                best.sort_by(|a, b| b.2.total_cmp(&a.2));
+                best.truncate(keep);
+            }
+        }
+    }
+    best
+}
+
+/// The top-left corner and score of the best match of `template` in
+/// `haystack`, searching a downscaled copy first for larger templates.
+fn match_template(haystack: &GrayImage, template: &GrayImage) -> Option<(u32, u32, f64)> {
+    let factor = (template.width().min(template.height()) / 16).clamp(1, 4);
+    if factor == 1 {
+        let (x, y, score) = *best_positions(haystack, template, None, 1).first()?;
+        return Some((x as u32, y as u32, score));
+    }
+    let shrink = |image: &GrayImage| {
+        imageops::resize(
+            image,
+            (image.width() / factor).max(1),
+            (image.height() / factor).max(1),
+            FilterType::Triangle,
+        )
+    };
+    let f = factor as usize;
+    best_positions(&shrink(haystack), &shrink(template), None, CANDIDATES)
+        .into_iter()
+        .filter_map(|(cx, cy, _)| {
+            let window = (
+                (cx * f).saturating_sub(f),
+                (cy * f).saturating_sub(f),
+                cx * f + f,
+                cy * f + f,
+            );
+            best_positions(haystack, template, Some(window), 1)
+                .first()
+                .copied()
+        })
+        .max_by(|a, b| a.2.total_cmp(&b.2))
+        .map(|(x, y, score)| (x as u32, y as u32, score))
+}
+
+/// The column of `image` right of `skip` with the strongest vertical edge.
+fn strongest_vertical_edge(image: &GrayImage, skip: u32) -> Option<u32> {
+    let (w, h) = image.dimensions();
+    (skip.max(1)..w.saturating_sub(1))
+        .map(|x| {
+            let strength: u32 = (0..h)
+                .map(|y| {
+                    let left = image.get_pixel(x - 1, y).0[0] as i32;
+                    let right = image.get_pixel(x + 1, y).0[0] as i32;
+                    left.abs_diff(right)
+                })
+                .sum();
+            (x, strength)
+        })
+        .max_by_key(|&(_, strength)| strength)
+        .filter(|&(_, strength)| strength > 0)
+        .map(|(x, _)| x)
+}
+
+impl ChaserPage {
+    /// Look for `template` in the viewport, or in `region` of it (viewport
+    /// CSS pixels), and return the best match scoring at least the
+    /// template's minimum.
+    pub async fn find_template(
+        &self,
+        template: &Template,
+        region: Option<BoundingBox>,
+    ) -> Result<Option<TemplateMatch>> {
+        let (haystack, origin, ratio) = self.capture_gray(region).await?;
+        let Some((x, y, score)) = match_template(&haystack, &template.image) else {
+            return Ok(None);
+        };
+        if score < template.min_score {
+            tracing::debug!("best template match scored {score:.3}");
+            return Ok(None);
+        }
+        Ok(Some(TemplateMatch {
+            bounds: BoundingBox {
+                x: origin.x + x as f64 / ratio,
+                y: origin.y + y as f64 / ratio,
+                width: template.width() as f64 / ratio,
+                height: template.height() as f64 / ratio,
+            },
+            score,
+        }))
+    }
+
+    /// Find `template` like [`find_template`](Self::find_template) and click
+    /// a spot near the middle of it with humanized movement.
+    pub async fn click_template(
+        &self,
+        template: &Template,
+        region: Option<BoundingBox>,
+    ) -> Result<TemplateMatch> {
+        let found = self
+            .find_template(template, region)
+            .await?
+            .ok_or_else(|| ChaserError::msg("template not found on screen"))?;
+        let center = found.center();
+        let (dx, dy) = {
+            use rand::Rng;
+            let mut rng = rand::thread_rng();
+            (
+                found.bounds.width * rng.gen_range(-0.15..0.15),
+                found.bounds.height * rng.gen_range(-0.15..0.15),
+            )
+        };
+        self.click_human(center.x + dx, center.y + dy).await?;
+        Ok(found)
+    }
+
+    /// The viewport x of the strongest vertical edge in `region`, ignoring
+    /// its leftmost `skip` CSS pixels (e.g. where a puzzle piece starts).
+    pub async fn find_vertical_edge(&self, region: BoundingBox, skip: f64) -> Result<Option<f64>> {
+        let (image, origin, ratio) = self.capture_gray(Some(region)).await?;
+        let skip = (skip.max(0.0) * ratio).round() as u32;
+        Ok(strongest_vertical_edge(&image, skip).map(|x| origin.x + x as f64 / ratio))
+    }
+
+    /// A grayscale screenshot of the viewport cropped to `region`, with the
+    /// viewport position of its top-left pixel and the device pixel ratio.
+    async fn capture_gray(&self, region: Option<BoundingBox>) -> Result<(GrayImage, Point, f64)> {
+        let state = self.viewport_state().await?;
+        let png = self
+            .raw_page()
+            .screenshot(
+                ScreenshotParams::builder()
+                    .format(CaptureScreenshotFormat::Png)
+                    .build(),
+            )
+            .await?;
+        let mut image = image::load_from_memory(&png)
+            .map_err(|e| ChaserError::msg(e.to_string()))?
+            .to_luma8();
+        let ratio = image.width() as f64 / state.width.max(1.0);
+        let Some(region) = region else {
+            return Ok((image, Point { x: 0.0, y: 0.0 }, ratio));
+        };
+        let x = (region.x.max(0.0) * ratio).round() as u32;
+        let y = (region.y.max(0.0) * ratio).round() as u32;
+        if x >= image.width() || y >= image.height() {
+            return Err(ChaserError::msg("region lies outside the viewport"));
+        }
+        let width = ((region.width * ratio).round() as u32).min(image.width() - x);
+        let height = ((region.height * ratio).round() as u32).min(image.height() - y);
+        let cropped = imageops::crop(&mut image, x, y, width, height).to_image();
+        Ok((
+            cropped,
+            Point {
+                x: x as f64 / ratio,
+                y: y as f64 / ratio,
+            },
+            ratio,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+
+    fn scene() -> GrayImage {
+        GrayImage::from_fn(200, 120, |x, y| {
+            let inside = (130..170).contains(&x) && (40..80).contains(&y);
+            let ring = inside && ((x - 130) % 10 < 5) != ((y - 40) % 10 < 5);
+            Luma([if ring {
+                230
+            } else {
+                ((x * 3 + y) % 40) as u8 + 20
+            }])
+        })
+    }
+
+    #[test]
+    fn finds_templates_and_edges() {
+        let scene = scene();
+        let template = imageops::crop_imm(&scene, 126, 36, 48, 48).to_image();
+        let (x, y, score) = match_template(&scene, &template).unwrap();
+        assert_eq!((x, y), (126, 36));
+        assert!(score > 0.99);
+
+        let small = imageops::crop_imm(&scene, 128, 38, 12, 12).to_image();
+        assert_eq!(
+            match_template(&scene, &small).map(|m| (m.0, m.1)),
+            Some((128, 38))
+        );
+
+        let slider = GrayImage::from_fn(100, 20, |x, _| Luma([if x < 63 { 200 } else { 60 }]));
+        assert!(matches!(
+            strongest_vertical_edge(&slider, 10),
+            Some(62 | 63)
+        ));
+    }
+}
@@ -0,0 +1,204 @@
+//! Noticing when an element looks different, without reading its DOM.
+//!
+//! Price widgets and availability badges are often obfuscated markup whose
+//! structure changes on every load while the rendering stays the same, and
+//! the other way round. With the `vision` feature,
+//! [`ChaserPage::element_screenshot`] captures an element as an
+//! [`ElementShot`], and [`ElementShot::diff`] compares two captures by
+//! perceptual hash and by the share of pixels that changed, ignoring the
+//! small colour noise of anti-aliasing.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let badge = chaser.raw_page().find_element("#availability").await?;
+//! let before = chaser.element_screenshot(&badge).await?;
+//! // ... later
+//! let after = chaser.element_screenshot(&badge).await?;
+//! if after.diff(&before).is_change(0.02) {
+//!     std::fs::write("changed.png", after.png())?;
+//! }
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::element::Element;
+use crate::error::{ChaserError, ChaserResult as Result};
+use crate::layout::BoundingBox;
+use crate::page::ScreenshotParams;
+use chromiumoxide_cdp::cdp::browser_protocol::page::{CaptureScreenshotFormat, Viewport};
+use image::imageops::{self, FilterType};
+use image::RgbImage;
+
+/// Per-channel difference below which a pixel counts as unchanged.
+const PIXEL_TOLERANCE: u8 = 24;
+
+/// A captured rendering of an element.
+#[derive(Debug, Clone)]
+pub struct ElementShot {
+    png: Vec<u8>,
+    image: RgbImage,
+    hash: u64,
+    /// Where the element was, in viewport CSS pixels.
+    pub bounds: BoundingBox,
+}
+
+/// How two [`ElementShot`]s differ.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VisualDiff {
+    /// Differing bits of the perceptual hashes, 0 to 64; up to about 5 is
+    /// the same picture.
+    pub hash_distance: u32,
+    /// Share of pixels that changed, 1 if the sizes differ.
+    pub changed: f64,
+    /// Whether the element's size changed.
+    pub resized: bool,
+}
+
+impl VisualDiff {
+    /// Whether the element was resized or more than `threshold` (0 to 1)
+    /// of its pixels changed.
+    pub fn is_change(&self, threshold: f64) -> bool {
+        self.resized || self.changed > threshold
+    }
+}
+
+/// 64-bit difference hash: whether each pixel of a 9×8 grayscale
+/// thumbnail is brighter than its right neighbour.
+fn dhash(image: &RgbImage) -> u64 {
+    let gray = imageops::grayscale(image);
+    let thumb = imageops::resize(&gray, 9, 8, FilterType::Triangle);
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let brighter = thumb.get_pixel(x, y).0[0] > thumb.get_pixel(x + 1, y).0[0];
+            hash = (hash << 1) | brighter as u64;
+        }
+    }
+    hash
+}
+
+impl ElementShot {
+    /// Decode a PNG capture, e.g. one saved from [`png`](Self::png).
+    pub fn from_png(png: Vec<u8>, bounds: BoundingBox) -> Result<Self> {
+        let image = image::load_from_memory(&png)
+            .map_err(|e| ChaserError::msg(e.to_string()))?
+            .to_rgb8();
+        Ok(Self::from_parts(png, image, bounds))
+    }
+
+    fn from_parts(png: Vec<u8>, image: RgbImage, bounds: BoundingBox) -> Self {
+        Self {
+            hash: dhash(&image),
+            png,
+            image,
+            bounds,
+        }
+    }
+
+    /// The capture as PNG.
+    pub fn png(&self) -> &[u8] {
+        &self.png
+    }
+
+    /// The perceptual hash, for storing instead of the whole capture.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Compare this capture with an earlier one.
+    pub fn diff(&self, previous: &ElementShot) -> VisualDiff {
+        let hash_distance = (self.hash ^ previous.hash).count_ones();
+        if self.image.dimensions() != previous.image.dimensions() {
+            return VisualDiff {
+                hash_distance,
+                changed: 1.0,
+                resized: true,
+            };
+        }
+        let total = self.image.pixels().len().max(1);
+        let changed = self
+            .image
+            .pixels()
+            .zip(previous.image.pixels())
+            .filter(|(a, b)| {
+                a.0.iter()
+                    .zip(b.0.iter())
+                    .any(|(a, b)| a.abs_diff(*b) > PIXEL_TOLERANCE)
+            })
+            .count();
+        VisualDiff {
+            hash_distance,
+            changed: changed as f64 / total as f64,
+            resized: false,
+        }
+    }
+}
+
+impl ChaserPage {
+    /// Capture `element` as it is rendered, without scrolling to it.
+    pub async fn element_screenshot(&self, element: &Element) -> Result<ElementShot> {
+        let bounds = element.bounding_box().await?;
+        if bounds.width < 1.0 || bounds.height < 1.0 {
+            return Err(ChaserError::msg("element has no size to capture"));
+        }
+        let viewport = self.raw_page().layout_metrics().await?.css_layout_viewport;
+        let png = self
+            .raw_page()
+            .screenshot(
+                ScreenshotParams::builder()
+                    .format(CaptureScreenshotFormat::Png)
+                    .clip(Viewport {
+                        x: viewport.page_x as f64 + bounds.x,
+                        y: viewport.page_y as f64 + bounds.y,
+                        width: bounds.width,
+                        height: bounds.height,
+                        scale: 1.0,
+                    })
+                    .capture_beyond_viewport(true)
+                    .build(),
+            )
+            .await?;
+        ElementShot::from_png(png, bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgb;
+
+    fn shot(image: RgbImage) -> ElementShot {
+        let bounds = BoundingBox {
+            x: 0.0,
+            y: 0.0,
+            width: image.width() as f64,
+            height: image.height() as f64,
+        };
+        ElementShot::from_parts(Vec::new(), image, bounds)
+    }
+
+    #[test]
+    fn ignores_noise_and_sees_changes() {
+        let badge = |price_width: u32, shade: u8| {
+            RgbImage::from_fn(80, 24, |x, y| {
+                if (8..8 + price_width).contains(&x) && (6..18).contains(&y) {
+                    Rgb([20, 20, 20])
+                } else {
+                    Rgb([shade, 240, 240])
+                }
+            })
+        };
+        let before = shot(badge(30, 240));
+        let noisy = shot(badge(30, 250));
+        let diff = noisy.diff(&before);
+        assert_eq!(diff.changed, 0.0);
+        assert!(!diff.is_change(0.01));
+
+        let changed = shot(badge(50, 240)).diff(&before);
+        assert!(changed.is_change(0.01));
+        assert!(!changed.resized);
+
+        let resized = shot(RgbImage::new(40, 24)).diff(&before);
+        assert!(resized.resized && resized.is_change(1.0));
+    }
+}
@@ -0,0 +1,448 @@
+//! Warming up a fresh user data dir with benign browsing.
+//!
+//! A profile with zero history, no cookies and an empty cache scores worse on
+//! trust systems than one that has been used before. [`ProfileWarmer`]
+//! launches the browser on a persistent user data dir and spends a while on
+//! ordinary sites (news, searches, video) with humanized scrolling, clicking
+//! and typing, then closes the browser cleanly so everything is flushed to
+//! disk for the real tasks.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::warmup::{ProfileWarmer, WarmupSite};
+//! use std::time::Duration;
+//!
+//! let profile = ChaserProfile::windows().build();
+//! let report = ProfileWarmer::new("/var/lib/identities/alice")
+//!     .warm(&profile, &WarmupSite::defaults(), Duration::from_secs(600))
+//!     .await?;
+//! println!("visited {} pages", report.pages);
+//! ```
+
+use crate::browser::{Browser, BrowserConfig};
+use crate::chaser::ChaserPage;
+use crate::clock;
+use crate::profiles::ChaserProfile;
+use anyhow::{anyhow, Result};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Picks a same-site link that is visible and not a download, login or
+/// logout link; returns its centre in viewport coordinates.
+const PICK_LINK_SCRIPT: &str = r#"(() => {
+    const links = Array.from(document.querySelectorAll('a[href]')).filter(a => {
+        if (a.host !== location.host || a.hasAttribute('download')) return false;
+        if (/log-?(in|out)|sign-?(in|out|up)|account/i.test(a.href)) return false;
+        const r = a.getBoundingClientRect();
+        return r.width > 20 && r.height > 8 && r.top > 60 && r.bottom < innerHeight - 20;
+    });
+    if (!links.length) return null;
+    const r = links[Math.floor(Math.random() * links.length)].getBoundingClientRect();
+    return [r.x + r.width / 2, r.y + r.height / 2];
+})()"#;
+
+const SEARCH_BOX_SCRIPT: &str = r#"(() => {
+    const box = document.querySelector('textarea[name=q], input[name=q], input[type=search]');
+    if (!box) return null;
+    const r = box.getBoundingClientRect();
+    return [r.x + r.width / 2, r.y + r.height / 2];
+})()"#;
+
+/// What to do on a warm-up site.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WarmupActivity {
+    /// Read the page and follow a few same-site links.
+    Browse,
+    /// Type one of the queries into the site's search box.
+    Search(Vec<String>),
+    /// Stay on the page for a long time, as while watching.
+    Watch,
+}
+
+/// A site visited during warm-up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WarmupSite {
+    pub url: String,
+    pub activity: WarmupActivity,
+}
+
+impl WarmupSite {
+    pub fn browse(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            activity: WarmupActivity::Browse,
+        }
+    }
+
+    pub fn search<I, S>(url: impl Into<String>, queries: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            url: url.into(),
+            activity: WarmupActivity::Search(queries.into_iter().map(Into::into).collect()),
+        }
+    }
+
+    pub fn watch(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            activity: WarmupActivity::Watch,
+        }
+    }
+
+    /// A small general-interest mix of news, search and video.
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::browse("https://www.bbc.com/news"),
+            Self::browse("https://www.reuters.com/"),
+            Self::browse("https://en.wikipedia.org/wiki/Special:Random"),
+            Self::search(
+                "https://www.google.com/",
+                [
+                    "weather this weekend",
+                    "easy pasta recipes",
+                    "best hiking trails near me",
+                    "how long to boil an egg",
+                ],
+            ),
+            Self::search(
+                "https://www.bing.com/",
+                ["currency converter", "movie times", "translate hello"],
+            ),
+            Self::watch("https://www.youtube.com/"),
+        ]
+    }
+}
+
+/// What a warm-up run did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WarmupReport {
+    /// Pages loaded, including followed links.
+    pub pages: usize,
+    /// Queries typed into search boxes.
+    pub searches: usize,
+    /// Site visits that failed (and were skipped).
+    pub errors: usize,
+    pub elapsed: Duration,
+}
+
+/// Drives a persistent user data dir through benign browsing.
+#[derive(Debug, Clone)]
+pub struct ProfileWarmer {
+    user_data_dir: PathBuf,
+    executable: Option<PathBuf>,
+    headed: bool,
+    links_per_site: u32,
+}
+
+impl ProfileWarmer {
+    pub fn new(user_data_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            user_data_dir: user_data_dir.into(),
+            executable: None,
+            headed: false,
+            links_per_site: 2,
+        }
+    }
+
+    /// Path to the browser binary, auto detected if unset.
+    pub fn executable(mut self, path: impl Into<PathBuf>) -> Self {
+        self.executable = Some(path.into());
+        self
+    }
+
+    /// Show the browser window while warming up.
+    pub fn headed(mut self, headed: bool) -> Self {
+        self.headed = headed;
+        self
+    }
+
+    /// Maximum number of same-site links followed per visit (default: 2).
+    pub fn links_per_site(mut self, links: u32) -> Self {
+        self.links_per_site = links;
+        self
+    }
+
+    /// Browse `sites` in random order for about `duration`, then close the
+    /// browser so history, cookies and cache are written to the user data
+    /// dir.
+    pub async fn warm(
+        &self,
+        profile: &ChaserProfile,
+        sites: &[WarmupSite],
+        duration: Duration,
+    ) -> Result<WarmupReport> {
+        if sites.is_empty() {
+            return Err(anyhow!("No warm-up sites given"));
+        }
+        let mut builder = profile
+            .configure_browser(BrowserConfig::builder())
+            .user_data_dir(&self.user_data_dir);
+        if self.headed {
+            builder = builder.with_head();
+        }
+        if let Some(executable) = &self.executable {
+            builder = builder.chrome_executable(executable);
+        }
+        let config = builder.build().map_err(|e| anyhow!("{}", e))?;
+
+        let (mut browser, handler) = Browser::launch(config).await?;
+        let handler = handler.spawn();
+
+        let result = self.browse(&browser, profile, sites, duration).await;
+
+        browser.close().await?;
+        browser.wait().await?;
+        handler.shutdown(Duration::from_secs(5)).await;
+        result
+    }
+
+    async fn browse(
+        &self,
+        browser: &Browser,
+        profile: &ChaserProfile,
+        sites: &[WarmupSite],
+        duration: Duration,
+    ) -> Result<WarmupReport> {
+        let page = ChaserPage::new(browser.new_page("about:blank").await?);
+        page.apply_profile(profile).await?;
+
+        let started = clock::now();
+        let mut rng = StdRng::from_entropy();
+        let mut report = WarmupReport::default();
+        let mut order: Vec<&WarmupSite> = Vec::new();
+        while clock::elapsed(started) < duration {
+            if order.is_empty() {
+                order = sites.iter().collect();
+                order.shuffle(&mut rng);
+            }
+            let site = order.pop().expect("refilled above");
+            if let Err(e) = self.visit(&page, site, &mut rng, &mut report).await {
+                tracing::debug!("warm-up visit to {} failed: {}", site.url, e);
+                report.errors += 1;
+            }
+            pause(&mut rng, 2_000, 8_000).await;
+        }
+        report.elapsed = clock::elapsed(started);
+        Ok(report)
+    }
+
+    async fn visit(
+        &self,
+        page: &ChaserPage,
+        site: &WarmupSite,
+        rng: &mut StdRng,
+        report: &mut WarmupReport,
+    ) -> Result<()> {
+        page.goto(&site.url).await?;
+        report.pages += 1;
+        pause(rng, 1_500, 4_000).await;
+
+        match &site.activity {
+            WarmupActivity::Browse => {
+                read(page, rng).await?;
+                for _ in 0..rng.gen_range(0..=self.links_per_site) {
+                    let Some((x, y)) = point(page, PICK_LINK_SCRIPT).await else {
+                        break;
+                    };
+                    page.click_human(x, y).await?;
+                    report.pages += 1;
+                    pause(rng, 2_000, 5_000).await;
+                    read(page, rng).await?;
+                }
+            }
+            WarmupActivity::Search(queries) => {
+                let Some(query) = queries.choose(rng) else {
+                    return Ok(());
+                };
+                let (x, y) = point(page, SEARCH_BOX_SCRIPT)
+                    .await
+                    .ok_or_else(|| anyhow!("No search box on {}", site.url))?;
+                page.click_human(x, y).await?;
+                pause(rng, 300, 900).await;
+                page.type_text(query).await?;
+                pause(rng, 400, 1_200).await;
+                page.press_enter().await?;
+                report.searches += 1;
+                report.pages += 1;
+                pause(rng, 2_000, 4_000).await;
+                read(page, rng).await?;
+            }
+            WarmupActivity::Watch => {
+                page.scroll_human(rng.gen_range(200..600)).await?;
+                pause(rng, 20_000, 60_000).await;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Scroll down the page in a few bursts with reading pauses.
+async fn read(page: &ChaserPage, rng: &mut StdRng) -> Result<()> {
+    for _ in 0..rng.gen_range(2..6) {
+        page.scroll_human(rng.gen_range(250..700)).await?;
+        pause(rng, 1_200, 4_500).await;
+    }
+    Ok(())
+}
+
+async fn point(page: &ChaserPage, script: &str) -> Option<(f64, f64)> {
+    page.evaluate_stealth(script)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|v| serde_json::from_value(v).ok())
+}
+
+async fn pause(rng: &mut StdRng, min_ms: u64, max_ms: u64) {
+    clock::sleep(Duration::from_millis(rng.gen_range(min_ms..max_ms))).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MockTransport;
+    use serde_json::{json, Value};
+
+    /// A browser whose pages have a link and a search box at `(200, 300)`
+    /// when `link` and `search_box` say so.
+    fn browser(link: bool, search_box: bool) -> (Browser, MockTransport) {
+        let mock = MockTransport::new();
+        mock.respond_with("Runtime.evaluate", move |params| {
+            let expression = params["expression"].as_str().unwrap_or_default();
+            let found = (expression == PICK_LINK_SCRIPT && link)
+                || (expression == SEARCH_BOX_SCRIPT && search_box);
+            Ok(match found {
+                true => json!({ "result": { "type": "object", "value": [200.0, 300.0] } }),
+                false => json!({ "result": { "type": "object", "subtype": "null", "value": Value::Null } }),
+            })
+        });
+        (Browser::with_transport(mock.clone()), mock)
+    }
+
+    fn count(mock: &MockTransport, method: &str, kind: &str) -> usize {
+        mock.commands_to(method)
+            .iter()
+            .filter(|params| params["type"] == kind)
+            .count()
+    }
+
+    #[test]
+    fn default_sites_mix_activities() {
+        let sites = WarmupSite::defaults();
+        assert!(sites.iter().all(|site| site.url.starts_with("https://")));
+        assert!(sites
+            .iter()
+            .any(|site| matches!(&site.activity, WarmupActivity::Search(q) if !q.is_empty())));
+        assert!(sites
+            .iter()
+            .any(|site| site.activity == WarmupActivity::Watch));
+        assert_eq!(
+            WarmupSite::search("https://search.example/", ["a", "b"]).activity,
+            WarmupActivity::Search(vec!["a".to_string(), "b".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    async fn warming_needs_sites() {
+        let err = ProfileWarmer::new("/nonexistent/profile")
+            .executable("/nonexistent/chrome")
+            .warm(
+                &ChaserProfile::windows().build(),
+                &[],
+                Duration::from_secs(60),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "No warm-up sites given");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn searches_type_a_query_and_submit_it() {
+        let (browser, mock) = browser(false, true);
+        let report = ProfileWarmer::new("/unused")
+            .browse(
+                &browser,
+                &ChaserProfile::windows().build(),
+                &[WarmupSite::search(
+                    "https://search.example/",
+                    ["boil an egg"],
+                )],
+                Duration::from_secs(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!((report.pages, report.searches, report.errors), (2, 1, 0));
+        assert!(report.elapsed >= Duration::from_secs(1));
+
+        let typed: String = mock
+            .commands_to("Input.dispatchKeyEvent")
+            .iter()
+            .filter(|params| params["type"] == "keyDown")
+            .filter_map(|params| params["text"].as_str())
+            .collect();
+        assert!(typed.starts_with("boil an egg"), "typed {typed:?}");
+        assert_eq!(count(&mock, "Input.dispatchMouseEvent", "mousePressed"), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn failed_visits_are_counted_and_skipped() {
+        let (browser, _) = browser(false, false);
+        let report = ProfileWarmer::new("/unused")
+            .browse(
+                &browser,
+                &ChaserProfile::windows().build(),
+                &[WarmupSite::search("https://search.example/", ["q"])],
+                Duration::from_secs(1),
+            )
+            .await
+            .unwrap();
+        // the page loaded, but had no search box
+        assert_eq!((report.pages, report.searches, report.errors), (1, 0, 1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn browsing_follows_same_site_links() {
+        let (browser, mock) = browser(true, false);
+        let report = ProfileWarmer::new("/unused")
+            .links_per_site(3)
+            .browse(
+                &browser,
+                &ChaserProfile::windows().build(),
+                &[WarmupSite::browse("https://news.example/")],
+                Duration::from_secs(120),
+            )
+            .await
+            .unwrap();
+        let visits = mock.commands_to("Page.navigate").len();
+        let clicks = count(&mock, "Input.dispatchMouseEvent", "mousePressed");
+        assert!(visits >= 2);
+        assert!(clicks <= visits * 3);
+        assert_eq!(report.pages, visits + clicks);
+        assert_eq!(report.errors, 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn watching_stays_on_the_page() {
+        let (browser, mock) = browser(false, false);
+        let report = ProfileWarmer::new("/unused")
+            .browse(
+                &browser,
+                &ChaserProfile::windows().build(),
+                &[WarmupSite::watch("https://video.example/")],
+                Duration::from_secs(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(report.pages, 1);
+        assert!(report.elapsed >= Duration::from_secs(20));
+        assert_eq!(count(&mock, "Input.dispatchMouseEvent", "mousePressed"), 0);
+        assert!(count(&mock, "Input.dispatchMouseEvent", "mouseWheel") > 0);
+    }
+}
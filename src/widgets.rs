@@ -0,0 +1,353 @@
+//! Driving common form widgets, starting with date pickers.
+//!
+//! Booking and travel sites build their own date pickers, but nearly all of
+//! them fall into two patterns: a masked text input that takes typed digits,
+//! or a calendar popup that is opened, paged month by month and clicked. A
+//! [`DatePicker`] describes which pattern a site uses and where its parts
+//! are; [`ChaserPage::pick_date`] then fills it in with humanized clicks and
+//! typing. [`DatePickers`] keeps one picker per site, so a flow that visits
+//! several sites can look the right one up by URL.
+//!
+//! Selectors and input formats are patterns with the placeholders `{yyyy}`,
+//! `{yy}`, `{mm}`, `{m}`, `{dd}`, `{d}`, `{month}` (`March`) and `{mon}`
+//! (`Mar`), filled in from the date being picked.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::widgets::{Date, DatePicker, DatePickers};
+//!
+//! let pickers = DatePickers::new()
+//!     .site(
+//!         "booking.example",
+//!         DatePicker::calendar(
+//!             "[data-testid=date-display]",
+//!             ".calendar-month h3",
+//!             "button[aria-label='Next month']",
+//!             "button[aria-label='Previous month']",
+//!             "span[data-date='{yyyy}-{mm}-{dd}']",
+//!         ),
+//!     )
+//!     .site("rail.example", DatePicker::masked_input("#depart", "{dd}{mm}{yyyy}"));
+//!
+//! chaser.pick_date_for_site(&pickers, Date::new(2025, 3, 14)?).await?;
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::{ChaserError, ChaserResult as Result};
+use rand::Rng;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// How far a calendar is paged before giving up.
+const MAX_MONTH_STEPS: u32 = 36;
+
+/// A calendar date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// The date, if it exists in the Gregorian calendar.
+    pub fn new(year: i32, month: u32, day: u32) -> Result<Self> {
+        let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+        let days = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if leap => 29,
+            2 => 28,
+            _ => 0,
+        };
+        if day == 0 || day > days {
+            return Err(ChaserError::msg(format!(
+                "{year}-{month:02}-{day:02} is not a date"
+            )));
+        }
+        Ok(Self { year, month, day })
+    }
+
+    /// Months since the start of year 0, for paging distances.
+    fn month_index(&self) -> i64 {
+        self.year as i64 * 12 + self.month as i64 - 1
+    }
+
+    /// `pattern` with its placeholders replaced by this date's parts.
+    pub fn format(&self, pattern: &str) -> String {
+        let name = MONTHS[self.month as usize - 1];
+        pattern
+            .replace("{yyyy}", &format!("{:04}", self.year))
+            .replace("{yy}", &format!("{:02}", self.year.rem_euclid(100)))
+            .replace("{mm}", &format!("{:02}", self.month))
+            .replace("{m}", &self.month.to_string())
+            .replace("{dd}", &format!("{:02}", self.day))
+            .replace("{d}", &self.day.to_string())
+            .replace("{month}", name)
+            .replace("{mon}", &name[..3])
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// The month and year a calendar header such as `March 2025`, `Mar 2025`
+/// or `03/2025` shows. English month names only.
+fn parse_month_label(label: &str) -> Option<(i32, u32)> {
+    let lower = label.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let year = words
+        .iter()
+        .find(|w| w.len() == 4 && w.chars().all(|c| c.is_ascii_digit()))?
+        .parse()
+        .ok()?;
+    let named = words.iter().find_map(|w| {
+        MONTHS.iter().position(|m| {
+            let m = m.to_lowercase();
+            w.len() >= 3 && m.starts_with(*w)
+        })
+    });
+    let month = match named {
+        Some(i) => i as u32 + 1,
+        None => words
+            .iter()
+            .filter(|w| w.len() <= 2)
+            .find_map(|w| w.parse().ok().filter(|m| (1..=12).contains(m)))?,
+    };
+    Some((year, month))
+}
+
+/// Where a site's date picker is and how it works.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DatePicker {
+    /// A text input that takes the date typed in `format`, e.g.
+    /// `{mm}/{dd}/{yyyy}`. Masks that insert their own separators want the
+    /// digits only, e.g. `{mm}{dd}{yyyy}`.
+    MaskedInput { input: String, format: String },
+    /// A calendar popup paged with next/previous buttons.
+    Calendar {
+        /// Clicked to open the calendar; `None` if it is always shown.
+        open: Option<String>,
+        /// The header naming the month on display.
+        month_label: String,
+        next: String,
+        previous: String,
+        /// Pattern for the selector of the day to click.
+        day: String,
+    },
+}
+
+impl DatePicker {
+    pub fn masked_input(input: impl Into<String>, format: impl Into<String>) -> Self {
+        DatePicker::MaskedInput {
+            input: input.into(),
+            format: format.into(),
+        }
+    }
+
+    pub fn calendar(
+        open: impl Into<String>,
+        month_label: impl Into<String>,
+        next: impl Into<String>,
+        previous: impl Into<String>,
+        day: impl Into<String>,
+    ) -> Self {
+        DatePicker::Calendar {
+            open: Some(open.into()),
+            month_label: month_label.into(),
+            next: next.into(),
+            previous: previous.into(),
+            day: day.into(),
+        }
+    }
+}
+
+/// A [`DatePicker`] per site.
+#[derive(Debug, Clone, Default)]
+pub struct DatePickers {
+    sites: HashMap<String, DatePicker>,
+    fallback: Option<DatePicker>,
+}
+
+impl DatePickers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `picker` on `host` and its subdomains.
+    pub fn site(mut self, host: impl Into<String>, picker: DatePicker) -> Self {
+        self.sites.insert(host.into().to_lowercase(), picker);
+        self
+    }
+
+    /// Use `picker` on sites without their own.
+    pub fn fallback(mut self, picker: DatePicker) -> Self {
+        self.fallback = Some(picker);
+        self
+    }
+
+    /// The picker for `url`: that of its host or the nearest parent domain,
+    /// else the fallback.
+    pub fn for_url(&self, url: &str) -> Option<&DatePicker> {
+        let host = url::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_lowercase));
+        let mut domain = host.as_deref();
+        while let Some(current) = domain {
+            if let Some(picker) = self.sites.get(current) {
+                return Some(picker);
+            }
+            domain = current.split_once('.').map(|(_, parent)| parent);
+        }
+        self.fallback.as_ref()
+    }
+}
+
+impl ChaserPage {
+    /// Set `date` in the date picker described by `picker`.
+    pub async fn pick_date(&self, picker: &DatePicker, date: Date) -> Result<()> {
+        match picker {
+            DatePicker::MaskedInput { input, format } => {
+                self.click_selector_human(input).await?;
+                self.clear_field_human(input).await?;
+                self.type_text(&date.format(format)).await
+            }
+            DatePicker::Calendar {
+                open,
+                month_label,
+                next,
+                previous,
+                day,
+            } => {
+                if let Some(open) = open {
+                    self.click_selector_human(open).await?;
+                }
+                self.page_calendar_to(month_label, next, previous, date)
+                    .await?;
+                let settle = rand::thread_rng().gen_range(250..600);
+                self.pause(Duration::from_millis(settle)).await?;
+                self.click_selector_human(&date.format(day)).await
+            }
+        }
+    }
+
+    /// Set `date` with the picker `pickers` has for the current site.
+    pub async fn pick_date_for_site(&self, pickers: &DatePickers, date: Date) -> Result<()> {
+        let url = self.raw_page().url().await?.unwrap_or_default();
+        let picker = pickers
+            .for_url(&url)
+            .ok_or_else(|| ChaserError::msg(format!("no date picker configured for {url}")))?;
+        self.pick_date(picker, date).await
+    }
+
+    /// Click next or previous until the calendar shows `date`'s month,
+    /// waiting for the header to change after every click.
+    async fn page_calendar_to(
+        &self,
+        month_label: &str,
+        next: &str,
+        previous: &str,
+        date: Date,
+    ) -> Result<()> {
+        let mut shown = self.calendar_month(month_label).await?;
+        for _ in 0..MAX_MONTH_STEPS {
+            let distance = date.month_index() - (shown.0 as i64 * 12 + shown.1 as i64 - 1);
+            if distance == 0 {
+                return Ok(());
+            }
+            let button = if distance > 0 { next } else { previous };
+            self.click_selector_human(button).await?;
+
+            let deadline = Instant::now() + self.timeouts().wait;
+            loop {
+                let now = self.calendar_month(month_label).await?;
+                if now != shown {
+                    shown = now;
+                    break;
+                }
+                if Instant::now() >= deadline {
+                    return Err(ChaserError::Timeout(format!(
+                        "Waiting for {month_label} to leave {}/{}",
+                        shown.1, shown.0
+                    )));
+                }
+                self.pause(Duration::from_millis(50)).await?;
+            }
+            // glance at the new month before paging on
+            let glance = rand::thread_rng().gen_range(150..450);
+            self.pause(Duration::from_millis(glance)).await?;
+        }
+        Err(ChaserError::msg(format!(
+            "calendar did not reach {date} within {MAX_MONTH_STEPS} months"
+        )))
+    }
+
+    async fn calendar_month(&self, month_label: &str) -> Result<(i32, u32)> {
+        let script = format!(
+            "document.querySelector({})?.textContent ?? null",
+            serde_json::to_string(month_label)?
+        );
+        let label = self
+            .evaluate_stealth(&script)
+            .await?
+            .and_then(|value| value.as_str().map(str::to_string))
+            .ok_or_else(|| ChaserError::msg(format!("no element matches `{month_label}`")))?;
+        parse_month_label(&label)
+            .ok_or_else(|| ChaserError::msg(format!("cannot read a month from {label:?}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_dates_and_reads_calendar_headers() {
+        let date = Date::new(2024, 2, 29).unwrap();
+        assert!(Date::new(2023, 2, 29).is_err());
+        assert_eq!(date.format("{mm}/{dd}/{yyyy}"), "02/29/2024");
+        assert_eq!(
+            date.format("td[aria-label='{mon} {d}, {yy}']"),
+            "td[aria-label='Feb 29, 24']"
+        );
+        assert_eq!(parse_month_label(" March 2025 "), Some((2025, 3)));
+        assert_eq!(parse_month_label("Sept. 2024"), Some((2024, 9)));
+        assert_eq!(parse_month_label("03 / 2026"), Some((2026, 3)));
+        assert_eq!(parse_month_label("Tuesday"), None);
+
+        let pickers = DatePickers::new()
+            .site("booking.example", DatePicker::masked_input("#d", "{dd}"))
+            .fallback(DatePicker::masked_input("#date", "{yyyy}-{mm}-{dd}"));
+        assert_eq!(
+            pickers.for_url("https://www.booking.example/hotels"),
+            Some(&DatePicker::masked_input("#d", "{dd}"))
+        );
+        assert_eq!(
+            pickers.for_url("https://other.example/"),
+            Some(&DatePicker::masked_input("#date", "{yyyy}-{mm}-{dd}"))
+        );
+    }
+}
@@ -0,0 +1,333 @@
+//! Maximizing, restoring, moving and resizing the window mid-session.
+//!
+//! People maximize windows, snap them back, drag them aside and pull at their
+//! edges; a window whose position and size never change over hours of
+//! browsing is unusual. The methods here change the window of a page set up
+//! with [`ChaserPage::apply_profile`] and keep everything a page can read in
+//! step: the real window (`Browser.setWindowBounds` when headed), the viewport
+//! (device metrics when headless), the spoofed `screenX`/`outerWidth` family,
+//! both in the current document and in documents loaded later.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use chaser_oxide::geometry::WindowState;
+//!
+//! chaser.apply_profile(&profile).await?;
+//! chaser.set_window_state(WindowState::Maximized).await?;
+//! // later: restore and drag the corner to 1280x800
+//! chaser.resize_window_human(1280, 800).await?;
+//! ```
+
+use crate::chaser::ChaserPage;
+use crate::error::{ChaserError, ChaserResult as Result};
+use crate::geometry::{Geometry, WindowState};
+use crate::profiles::ChaserProfile;
+use chromiumoxide_cdp::cdp::browser_protocol::browser::{
+    Bounds, GetWindowForTargetParams, SetWindowBoundsParams, WindowState as BoundsState,
+};
+use chromiumoxide_cdp::cdp::browser_protocol::page::{
+    AddScriptToEvaluateOnNewDocumentParams, RemoveScriptToEvaluateOnNewDocumentParams,
+    ScriptIdentifier,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::Duration;
+
+/// The window a page's profile describes, as last applied.
+#[derive(Debug, Clone)]
+pub(crate) struct WindowTracker {
+    profile: ChaserProfile,
+    geometry: Geometry,
+    /// Init script overriding the bootstrap's window values, once changed.
+    script: Option<ScriptIdentifier>,
+}
+
+impl WindowTracker {
+    pub(crate) fn new(profile: &ChaserProfile, geometry: Geometry) -> Self {
+        WindowTracker {
+            profile: profile.clone(),
+            geometry,
+            script: None,
+        }
+    }
+
+    pub(crate) fn profile(&self) -> &ChaserProfile {
+        &self.profile
+    }
+}
+
+/// Restored bounds (relative to the monitor) of the window in `geometry`.
+fn restored_bounds(geometry: &Geometry, origin: (i32, i32)) -> (i32, i32, u32, u32) {
+    (
+        geometry.window_x - origin.0,
+        geometry.window_y - origin.1,
+        geometry.outer_width,
+        geometry.outer_height,
+    )
+}
+
+/// Intermediate bounds of a window dragged from `from` to `to` in `steps`
+/// events: fast at first, settling at the end, with a pixel or two of hand
+/// jitter on all but the last step.
+pub(crate) fn drag_steps(
+    from: (i32, i32, u32, u32),
+    to: (i32, i32, u32, u32),
+    steps: usize,
+    rng: &mut impl Rng,
+) -> Vec<(i32, i32, u32, u32)> {
+    let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+    (1..=steps)
+        .map(|i| {
+            if i == steps {
+                return to;
+            }
+            let t = i as f64 / steps as f64;
+            let t = 1.0 - (1.0 - t).powi(3);
+            let mut jitter = || rng.gen_range(-2.0..=2.0);
+            (
+                (lerp(from.0 as f64, to.0 as f64, t) + jitter()).round() as i32,
+                (lerp(from.1 as f64, to.1 as f64, t) + jitter()).round() as i32,
+                (lerp(from.2 as f64, to.2 as f64, t) + jitter())
+                    .round()
+                    .max(0.0) as u32,
+                (lerp(from.3 as f64, to.3 as f64, t) + jitter())
+                    .round()
+                    .max(0.0) as u32,
+            )
+        })
+        .collect()
+}
+
+impl ChaserPage {
+    /// The current window geometry, once a profile is applied.
+    pub fn geometry(&self) -> Option<Geometry> {
+        self.window_tracker()
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|tracker| tracker.geometry)
+    }
+
+    /// Maximize the window or restore it to the given bounds at once, like
+    /// a double click on the title bar. Phones have no window to change.
+    pub async fn set_window_state(&self, state: WindowState) -> Result<Geometry> {
+        let profile = self.tracked_profile()?;
+        self.apply_window(Geometry::resolve_with(&profile, state))
+            .await
+    }
+
+    /// Resize the window to `width` x `height` (outer size) by dragging its
+    /// bottom-right corner, restoring it first if it is maximized.
+    pub async fn resize_window_human(&self, width: u32, height: u32) -> Result<Geometry> {
+        let (x, y, _, _) = self.restored_window().await?;
+        self.drag_window((x, y, width, height)).await
+    }
+
+    /// Move the window to (`x`, `y`) on its monitor by dragging its title
+    /// bar, restoring it first if it is maximized.
+    pub async fn move_window_human(&self, x: i32, y: i32) -> Result<Geometry> {
+        let (_, _, width, height) = self.restored_window().await?;
+        self.drag_window((x, y, width, height)).await
+    }
+
+    /// An occasional, small window change: usually a nudge or a resize by a
+    /// few dozen pixels, sometimes a maximize or restore.
+    pub async fn fidget_window(&self) -> Result<Geometry> {
+        let geometry = self.tracked_geometry()?;
+        let avail = geometry.avail;
+        let mut rng = StdRng::from_entropy();
+        if let WindowState::Maximized = geometry.window {
+            // restore to a window a bit smaller than the screen
+            let width = avail.width * rng.gen_range(70..95) / 100;
+            let height = avail.height * rng.gen_range(75..95) / 100;
+            let x = avail.left as i32 + rng.gen_range(0..=(avail.width - width) as i32);
+            let y = avail.top as i32 + rng.gen_range(0..=(avail.height - height) as i32);
+            let profile = self.tracked_profile()?;
+            let state = WindowState::Windowed {
+                x,
+                y,
+                width,
+                height,
+            };
+            return self
+                .apply_window(Geometry::resolve_with(&profile, state))
+                .await;
+        }
+        let (x, y, width, height) = self.restored_window().await?;
+        let choice = rng.gen_range(0..10);
+        let mut delta = |max: i32| rng.gen_range(15..=max) * if rng.gen() { 1 } else { -1 };
+        match choice {
+            0 => self.set_window_state(WindowState::Maximized).await,
+            1..=5 => {
+                let x = (x + delta(120))
+                    .clamp(avail.left as i32, (avail.left + avail.width) as i32 - 200);
+                let y = (y + delta(60))
+                    .clamp(avail.top as i32, (avail.top + avail.height) as i32 - 200);
+                self.drag_window((x, y, width, height)).await
+            }
+            _ => {
+                let width = width.saturating_add_signed(delta(160));
+                let height = height.saturating_add_signed(delta(100));
+                self.drag_window((x, y, width, height)).await
+            }
+        }
+    }
+
+    /// The restored bounds of the window, restoring it if it is maximized.
+    async fn restored_window(&self) -> Result<(i32, i32, u32, u32)> {
+        let mut geometry = self.tracked_geometry()?;
+        if geometry.mobile {
+            return Err(ChaserError::msg("Mobile browsers have no window to change"));
+        }
+        if let WindowState::Maximized = geometry.window {
+            let profile = self.tracked_profile()?;
+            let avail = geometry.avail;
+            // the restored size Chrome remembers for a window that was never restored
+            let state = WindowState::Windowed {
+                x: avail.left as i32 + 40,
+                y: avail.top as i32 + 30,
+                width: avail.width * 4 / 5,
+                height: avail.height * 4 / 5,
+            };
+            geometry = self
+                .apply_window(Geometry::resolve_with(&profile, state))
+                .await?;
+        }
+        Ok(restored_bounds(
+            &geometry,
+            self.tracked_profile()?.screen_origin(),
+        ))
+    }
+
+    async fn drag_window(&self, to: (i32, i32, u32, u32)) -> Result<Geometry> {
+        let profile = self.tracked_profile()?;
+        let from = restored_bounds(&self.tracked_geometry()?, profile.screen_origin());
+        let mut rng = StdRng::from_entropy();
+        let steps = rng.gen_range(8..=14);
+        let mut geometry = self.tracked_geometry()?;
+        for (x, y, width, height) in drag_steps(from, to, steps, &mut rng) {
+            let state = WindowState::Windowed {
+                x,
+                y,
+                width,
+                height,
+            };
+            geometry = self
+                .apply_window(Geometry::resolve_with(&profile, state))
+                .await?;
+            self.pause(Duration::from_millis(rng.gen_range(16..34)))
+                .await?;
+        }
+        Ok(geometry)
+    }
+
+    /// Apply `geometry` to the browser window, the viewport and the spoofed
+    /// window values.
+    async fn apply_window(&self, geometry: Geometry) -> Result<Geometry> {
+        if geometry.mobile {
+            return Err(ChaserError::msg("Mobile browsers have no window to change"));
+        }
+        if self.is_headless().await {
+            self.raw_page()
+                .execute(geometry.device_metrics(true))
+                .await?;
+        } else {
+            let window = self
+                .raw_page()
+                .execute(GetWindowForTargetParams::default())
+                .await?
+                .result;
+            let bounds = match geometry.window {
+                WindowState::Maximized => Bounds {
+                    window_state: Some(BoundsState::Maximized),
+                    ..Default::default()
+                },
+                WindowState::Windowed { .. } => {
+                    if window
+                        .bounds
+                        .window_state
+                        .is_some_and(|state| state != BoundsState::Normal)
+                    {
+                        // Chrome only moves and sizes normal windows
+                        let normal = Bounds {
+                            window_state: Some(BoundsState::Normal),
+                            ..Default::default()
+                        };
+                        self.raw_page()
+                            .execute(SetWindowBoundsParams::new(window.window_id, normal))
+                            .await?;
+                    }
+                    Bounds {
+                        left: Some(geometry.window_x as i64),
+                        top: Some(geometry.window_y as i64),
+                        width: Some(geometry.outer_width as i64),
+                        height: Some(geometry.outer_height as i64),
+                        window_state: None,
+                    }
+                }
+            };
+            self.raw_page()
+                .execute(SetWindowBoundsParams::new(window.window_id, bounds))
+                .await?;
+        }
+
+        // One synchronous script, so the page never sees a half-updated window
+        let script = geometry.window_script();
+        self.evaluate_main(&script).await?;
+        let new_script = self
+            .raw_page()
+            .execute(AddScriptToEvaluateOnNewDocumentParams {
+                source: script,
+                world_name: None,
+                include_command_line_api: None,
+                run_immediately: None,
+            })
+            .await?
+            .result
+            .identifier;
+        let old_script = {
+            let mut tracker = self.window_tracker().lock().unwrap();
+            let tracker = tracker
+                .as_mut()
+                .ok_or_else(|| ChaserError::msg("No profile applied to this page"))?;
+            tracker.geometry = geometry;
+            tracker.script.replace(new_script)
+        };
+        if let Some(old) = old_script {
+            self.raw_page()
+                .execute(RemoveScriptToEvaluateOnNewDocumentParams::new(old))
+                .await?;
+        }
+
+        self.set_viewport_size(geometry.inner_width as f64, geometry.inner_height as f64)
+            .await?;
+        Ok(geometry)
+    }
+
+    fn tracked_geometry(&self) -> Result<Geometry> {
+        self.geometry()
+            .ok_or_else(|| ChaserError::msg("No profile applied to this page"))
+    }
+
+    fn tracked_profile(&self) -> Result<ChaserProfile> {
+        self.profile()
+            .ok_or_else(|| ChaserError::msg("No profile applied to this page"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drags_ease_out_and_end_on_target() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let steps = drag_steps((100, 80, 1200, 800), (100, 80, 1500, 900), 10, &mut rng);
+        assert_eq!(steps.len(), 10);
+        assert_eq!(steps[9], (100, 80, 1500, 900));
+        // most of the distance is covered in the first few events
+        assert!(steps[2].2 > 1300);
+        assert!(steps.windows(2).all(|w| w[1].2 + 4 >= w[0].2));
+    }
+}